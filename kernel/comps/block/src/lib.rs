@@ -77,13 +77,32 @@ impl dyn BlockDevice {
     }
 }
 
+/// The type of the callbacks that are called when block devices are registered.
+pub type RegisterCallback = dyn Fn(&str, Arc<dyn BlockDevice>) + Send + Sync;
+
+static REGISTER_CALLBACKS: SpinLock<Vec<&'static RegisterCallback>> = SpinLock::new(Vec::new());
+
 pub fn register_device(name: String, device: Arc<dyn BlockDevice>) {
     COMPONENT
         .get()
         .unwrap()
         .block_device_table
         .lock()
-        .insert(name, device);
+        .insert(name.clone(), device.clone());
+
+    // The callbacks are called without holding the locks, since they may sleep.
+    let callbacks = REGISTER_CALLBACKS.lock().clone();
+    for callback in callbacks {
+        callback(&name, device.clone());
+    }
+}
+
+/// Registers a callback that is called whenever a block device is registered.
+///
+/// The callback is not called for the devices that have already been registered, which can be
+/// found with [`all_devices`].
+pub fn register_callback(callback: &'static RegisterCallback) {
+    REGISTER_CALLBACKS.lock().push(callback);
 }
 
 pub fn get_device(str: &str) -> Option<Arc<dyn BlockDevice>> {
@@ -6,7 +6,7 @@
 
 extern crate alloc;
 
-use alloc::{vec, vec::Vec};
+use alloc::{sync::Arc, vec, vec::Vec};
 use core::{
     fmt,
     ops::{Index, IndexMut},
@@ -14,12 +14,7 @@ use core::{
 
 use component::{init_component, ComponentInitError};
 use font8x8::UnicodeFonts;
-use ostd::{
-    boot::{boot_info, memory_region::MemoryRegionType},
-    io_mem::IoMem,
-    mm::{VmIo, PAGE_SIZE},
-    sync::SpinLock,
-};
+use ostd::{boot::boot_info, io_mem::IoMem, mm::VmIo, sync::SpinLock};
 use spin::Once;
 
 #[init_component]
@@ -30,45 +25,85 @@ fn framebuffer_init() -> Result<(), ComponentInitError> {
 
 pub(crate) static WRITER: Once<SpinLock<Writer>> = Once::new();
 
-// ignore the warnings since we use the `todo!` macro.
-#[allow(unused_variables)]
-#[allow(unreachable_code)]
-#[allow(clippy::diverging_sub_expression)]
-pub(crate) fn init() {
-    let mut writer = {
-        let Some(framebuffer) = boot_info().framebuffer_arg else {
-            return;
-        };
-        let mut size = 0;
-        for region in boot_info().memory_regions.iter() {
-            if region.typ() == MemoryRegionType::Framebuffer {
-                size = region.len();
-            }
-        }
-
-        let page_size = size / PAGE_SIZE;
+static FRAMEBUFFER: Once<Arc<FrameBuffer>> = Once::new();
 
-        let start_paddr = framebuffer.address;
-        let io_mem = todo!("IoMem is private for components now, should fix it.");
-
-        let mut buffer: Vec<u8> = vec![0; size];
-        log::debug!("Found framebuffer:{:?}", framebuffer);
+pub(crate) fn init() {
+    let Some(framebuffer_arg) = boot_info().framebuffer_arg else {
+        return;
+    };
+    let Some(io_mem) = IoMem::acquire_framebuffer() else {
+        return;
+    };
+    log::debug!("Found framebuffer:{:?}", framebuffer_arg);
 
-        Writer {
+    let framebuffer = FRAMEBUFFER.call_once(|| {
+        Arc::new(FrameBuffer {
             io_mem,
-            x_pos: 0,
-            y_pos: 0,
-            bytes_per_pixel: (framebuffer.bpp / 8),
-            width: framebuffer.width,
-            height: framebuffer.height,
-            buffer: buffer.leak(),
-        }
+            width: framebuffer_arg.width,
+            height: framebuffer_arg.height,
+            bpp: framebuffer_arg.bpp,
+        })
+    });
+
+    let buffer: Vec<u8> = vec![0; framebuffer.size()];
+    let mut writer = Writer {
+        io_mem: framebuffer.io_mem.clone(),
+        x_pos: 0,
+        y_pos: 0,
+        bytes_per_pixel: (framebuffer.bpp / 8),
+        width: framebuffer.width,
+        height: framebuffer.height,
+        buffer: buffer.leak(),
     };
     writer.clear();
 
     WRITER.call_once(|| SpinLock::new(writer));
 }
 
+/// Returns the framebuffer that is set up by the bootloader, if any.
+pub fn framebuffer() -> Option<Arc<FrameBuffer>> {
+    FRAMEBUFFER.get().cloned()
+}
+
+/// A linear framebuffer.
+///
+/// The pixels are stored row by row without padding, and each pixel takes
+/// `bpp / 8` bytes.
+#[derive(Debug)]
+pub struct FrameBuffer {
+    io_mem: IoMem,
+    width: usize,
+    height: usize,
+    bpp: usize,
+}
+
+impl FrameBuffer {
+    /// Returns the I/O memory of the pixels.
+    pub fn io_mem(&self) -> &IoMem {
+        &self.io_mem
+    }
+
+    /// Returns the width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of bits per pixel.
+    pub fn bpp(&self) -> usize {
+        self.bpp
+    }
+
+    /// Returns the size of the framebuffer in bytes.
+    pub fn size(&self) -> usize {
+        self.io_mem.length()
+    }
+}
+
 pub(crate) struct Writer {
     io_mem: IoMem,
     /// FIXME: remove buffer. The meaning of buffer is to facilitate the various operations of framebuffer
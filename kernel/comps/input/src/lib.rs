@@ -26,13 +26,32 @@ pub trait InputDevice: Send + Sync + Any + Debug {
     fn register_callbacks(&self, function: &'static (dyn Fn(InputEvent) + Send + Sync));
 }
 
+/// The type of the callbacks that are called when input devices are registered.
+pub type RegisterCallback = dyn Fn(&str, Arc<dyn InputDevice>) + Send + Sync;
+
+static REGISTER_CALLBACKS: SpinLock<Vec<&'static RegisterCallback>> = SpinLock::new(Vec::new());
+
 pub fn register_device(name: String, device: Arc<dyn InputDevice>) {
     COMPONENT
         .get()
         .unwrap()
         .input_device_table
         .lock()
-        .insert(name, device);
+        .insert(name.clone(), device.clone());
+
+    // The callbacks are called without holding the locks, since they may sleep.
+    let callbacks = REGISTER_CALLBACKS.lock().clone();
+    for callback in callbacks {
+        callback(&name, device.clone());
+    }
+}
+
+/// Registers a callback that is called whenever an input device is registered.
+///
+/// The callback is not called for the devices that have already been registered, which can be
+/// found with [`all_devices`].
+pub fn register_callback(callback: &'static RegisterCallback) {
+    REGISTER_CALLBACKS.lock().push(callback);
 }
 
 pub fn get_device(str: &str) -> Option<Arc<dyn InputDevice>> {
@@ -9,6 +9,17 @@ pub enum BindError {
     InUse,
 }
 
+/// An error describing the reason why the configuration of an iface cannot be changed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConfigError {
+    /// The address or the route already exists.
+    AlreadyExists,
+    /// The address or the route does not exist.
+    NotFound,
+    /// There are too many addresses or routes.
+    Exhausted,
+}
+
 pub mod tcp {
     pub use smoltcp::socket::tcp::{RecvError, SendError};
 
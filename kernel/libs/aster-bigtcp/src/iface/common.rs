@@ -9,19 +9,19 @@ use alloc::{
 
 use ostd::sync::{LocalIrqDisabled, SpinLock, SpinLockGuard};
 use smoltcp::{
-    iface::{packet::Packet, Context},
+    iface::{packet::Packet, Context, Route},
     phy::Device,
-    wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv4Packet},
+    wire::{IpAddress, IpCidr, IpEndpoint, IpListenEndpoint, Ipv4Address, Ipv6Address},
 };
 
 use super::{
     poll::{FnHelper, PollContext},
     port::BindPortConfig,
     time::get_network_timestamp,
-    Iface,
+    Iface, InterfaceType,
 };
 use crate::{
-    errors::BindError,
+    errors::{BindError, ConfigError},
     ext::Ext,
     socket::{TcpListenerBg, UdpSocketBg},
     socket_table::SocketTable,
@@ -29,6 +29,8 @@ use crate::{
 
 pub struct IfaceCommon<E: Ext> {
     name: String,
+    type_: InterfaceType,
+    mtu: usize,
     interface: SpinLock<smoltcp::iface::Interface, LocalIrqDisabled>,
     used_ports: SpinLock<BTreeMap<u16, usize>, LocalIrqDisabled>,
     sockets: SpinLock<SocketTable<E>, LocalIrqDisabled>,
@@ -38,6 +40,8 @@ pub struct IfaceCommon<E: Ext> {
 impl<E: Ext> IfaceCommon<E> {
     pub(super) fn new(
         name: String,
+        type_: InterfaceType,
+        mtu: usize,
         interface: smoltcp::iface::Interface,
         sched_poll: E::ScheduleNextPoll,
     ) -> Self {
@@ -45,6 +49,8 @@ impl<E: Ext> IfaceCommon<E> {
 
         Self {
             name,
+            type_,
+            mtu,
             interface: SpinLock::new(interface),
             used_ports: SpinLock::new(BTreeMap::new()),
             sockets: SpinLock::new(sockets),
@@ -56,15 +62,105 @@ impl<E: Ext> IfaceCommon<E> {
         &self.name
     }
 
+    pub(super) fn type_(&self) -> InterfaceType {
+        self.type_
+    }
+
+    pub(super) fn mtu(&self) -> usize {
+        self.mtu
+    }
+
     pub(super) fn ipv4_addr(&self) -> Option<Ipv4Address> {
         self.interface.lock().ipv4_addr()
     }
 
+    pub(super) fn ipv6_addr(&self) -> Option<Ipv6Address> {
+        self.interface.lock().ipv6_addr()
+    }
+
+    pub(super) fn has_ip_addr(&self, addr: IpAddress) -> bool {
+        self.interface.lock().has_ip_addr(addr)
+    }
+
     pub(super) fn sched_poll(&self) -> &E::ScheduleNextPoll {
         &self.sched_poll
     }
 }
 
+impl<E: Ext> IfaceCommon<E> {
+    pub(super) fn ip_cidrs(&self) -> Vec<IpCidr> {
+        self.interface.lock().ip_addrs().to_vec()
+    }
+
+    pub(super) fn add_ip_cidr(&self, cidr: IpCidr) -> Result<(), ConfigError> {
+        let mut interface = self.interface.lock();
+
+        if interface.has_ip_addr(cidr.address()) {
+            return Err(ConfigError::AlreadyExists);
+        }
+
+        let mut result = Ok(());
+        interface.update_ip_addrs(|ip_addrs| {
+            if ip_addrs.push(cidr).is_err() {
+                result = Err(ConfigError::Exhausted);
+            }
+        });
+        result
+    }
+
+    pub(super) fn remove_ip_cidr(&self, cidr: IpCidr) -> Result<(), ConfigError> {
+        let mut interface = self.interface.lock();
+
+        let mut result = Ok(());
+        interface.update_ip_addrs(|ip_addrs| {
+            let old_len = ip_addrs.len();
+            ip_addrs.retain(|ip_cidr| *ip_cidr != cidr);
+            if ip_addrs.len() == old_len {
+                result = Err(ConfigError::NotFound);
+            }
+        });
+        result
+    }
+
+    pub(super) fn routes(&self) -> Vec<Route> {
+        let mut interface = self.interface.lock();
+
+        let mut routes = Vec::new();
+        interface.routes_mut().update(|storage| {
+            routes.extend(storage.iter().cloned());
+        });
+        routes
+    }
+
+    pub(super) fn add_route(&self, route: Route) -> Result<(), ConfigError> {
+        let mut interface = self.interface.lock();
+
+        let mut result = Ok(());
+        interface.routes_mut().update(|storage| {
+            if storage.iter().any(|old_route| old_route.cidr == route.cidr) {
+                result = Err(ConfigError::AlreadyExists);
+            } else if storage.push(route).is_err() {
+                result = Err(ConfigError::Exhausted);
+            }
+        });
+        result
+    }
+
+    pub(super) fn remove_route(&self, cidr: IpCidr) -> Result<(), ConfigError> {
+        let mut interface = self.interface.lock();
+
+        let mut result = Ok(());
+        interface.routes_mut().update(|storage| {
+            let old_len = storage.len();
+            storage.retain(|route| route.cidr != cidr);
+            if storage.len() == old_len {
+                result = Err(ConfigError::NotFound);
+            }
+        });
+        result
+    }
+}
+
 // Lock order: interface -> sockets
 impl<E: Ext> IfaceCommon<E> {
     /// Acquires the lock to the interface.
@@ -85,10 +181,16 @@ impl<E: Ext> IfaceCommon<E> {
     pub(super) fn bind(
         &self,
         iface: Arc<dyn Iface<E>>,
+        addr: IpAddress,
         config: BindPortConfig,
     ) -> core::result::Result<BoundPort<E>, BindError> {
         let port = self.bind_port(config)?;
-        Ok(BoundPort { iface, port })
+        Ok(BoundPort {
+            iface,
+            addr,
+            port,
+            is_ipv6_only: false,
+        })
     }
 
     /// Allocates an unused ephemeral port.
@@ -107,6 +209,8 @@ impl<E: Ext> IfaceCommon<E> {
         None
     }
 
+    // FIXME: IPv4 and IPv6 sockets share the same port space here, even if an IPv6 socket is
+    // bound with `IPV6_V6ONLY`. Linux allows them to use the same port in this case.
     fn bind_port(&self, config: BindPortConfig) -> Result<u16, BindError> {
         let port = if let Some(port) = config.port() {
             port
@@ -176,7 +280,7 @@ impl<E: Ext> IfaceCommon<E> {
             &'pkt [u8],
             &'cx mut Context,
             D::TxToken<'tx>,
-            Option<(Ipv4Packet<&'pkt [u8]>, D::TxToken<'tx>)>,
+            Option<(&'pkt [u8], D::TxToken<'tx>)>,
         >,
         Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
     {
@@ -240,7 +344,9 @@ impl<E: Ext> IfaceCommon<E> {
 // FIXME: TCP and UDP ports are independent. Find a way to track the protocol here.
 pub struct BoundPort<E: Ext> {
     iface: Arc<dyn Iface<E>>,
+    addr: IpAddress,
     port: u16,
+    is_ipv6_only: bool,
 }
 
 impl<E: Ext> BoundPort<E> {
@@ -255,12 +361,49 @@ impl<E: Ext> BoundPort<E> {
     }
 
     /// Returns the bound endpoint.
-    pub fn endpoint(&self) -> Option<IpEndpoint> {
-        let ip_addr = {
-            let ipv4_addr = self.iface().ipv4_addr()?;
-            IpAddress::Ipv4(ipv4_addr)
-        };
-        Some(IpEndpoint::new(ip_addr, self.port))
+    ///
+    /// The address of the endpoint is unspecified if the port is bound to the unspecified
+    /// address.
+    pub fn endpoint(&self) -> IpEndpoint {
+        IpEndpoint::new(self.addr, self.port)
+    }
+
+    /// Returns the bound endpoint in the form that smoltcp sockets expect.
+    pub(crate) fn listen_endpoint(&self) -> IpListenEndpoint {
+        IpListenEndpoint {
+            addr: (!self.addr.is_unspecified()).then_some(self.addr),
+            port: self.port,
+        }
+    }
+
+    /// Sets whether the port bound to the unspecified IPv6 address accepts IPv6 packets only.
+    ///
+    /// If this is false (the default), the port will also accept IPv4 packets, which makes it
+    /// possible for an IPv6 socket to communicate with IPv4 peers using IPv4-mapped IPv6
+    /// addresses. This corresponds to the `IPV6_V6ONLY` socket option.
+    pub fn set_ipv6_only(&mut self, is_ipv6_only: bool) {
+        self.is_ipv6_only = is_ipv6_only;
+    }
+
+    /// Returns whether packets sent to `dst_addr` can be accepted by the bound address.
+    pub(crate) fn accepts_addr(&self, dst_addr: &IpAddress) -> bool {
+        match (self.addr, dst_addr) {
+            (IpAddress::Ipv4(addr), IpAddress::Ipv4(dst_addr)) => {
+                addr.is_unspecified() || addr == *dst_addr
+            }
+            (IpAddress::Ipv6(addr), IpAddress::Ipv6(dst_addr)) => {
+                addr.is_unspecified() || addr == *dst_addr
+            }
+            (IpAddress::Ipv6(addr), IpAddress::Ipv4(_)) => {
+                addr.is_unspecified() && !self.is_ipv6_only
+            }
+            (IpAddress::Ipv4(_), IpAddress::Ipv6(_)) => false,
+        }
+    }
+
+    /// Updates the bound address after a local address is selected for a connection.
+    pub(crate) fn set_addr(&mut self, addr: IpAddress) {
+        self.addr = addr;
     }
 }
 
@@ -1,11 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
 
-use smoltcp::wire::Ipv4Address;
+use smoltcp::{
+    iface::Route,
+    wire::{EthernetAddress, IpAddress, IpCidr, Ipv4Address, Ipv6Address},
+};
 
 use super::{port::BindPortConfig, BoundPort};
-use crate::{errors::BindError, ext::Ext};
+use crate::{
+    errors::{BindError, ConfigError},
+    ext::Ext,
+};
 
 /// A network interface.
 ///
@@ -18,12 +24,26 @@ pub trait Iface<E>: internal::IfaceInternal<E> + Send + Sync {
     fn poll(&self);
 }
 
+/// The type of an iface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterfaceType {
+    /// An Ethernet iface, which has the given hardware address.
+    Ethernet(EthernetAddress),
+    /// An IP iface, which transmits raw IP packets without any link-layer headers.
+    Ip,
+}
+
 impl<E: Ext> dyn Iface<E> {
     /// Binds a socket to the iface.
     ///
     /// After binding the socket to the iface, the iface will handle all packets to and from the
     /// socket.
     ///
+    /// The socket is bound to `addr`, which should either be an address of the iface or be the
+    /// unspecified address of the IP version. In the latter case, the socket will handle packets
+    /// sent to any address of the iface with the same IP version. See
+    /// [`BoundPort::set_ipv6_only`] for whether packets of other IP versions will be handled.
+    ///
     /// If [`BindPortConfig::Ephemeral`] is specified, the iface will pick up an ephemeral port for
     /// the socket.
     ///
@@ -32,10 +52,11 @@ impl<E: Ext> dyn Iface<E> {
     /// <https://github.com/smoltcp-rs/smoltcp/issues/779>.
     pub fn bind(
         self: &Arc<Self>,
+        addr: IpAddress,
         config: BindPortConfig,
     ) -> core::result::Result<BoundPort<E>, BindError> {
         let common = self.common();
-        common.bind(self.clone(), config)
+        common.bind(self.clone(), addr, config)
     }
 
     /// Gets the name of the iface.
@@ -45,6 +66,19 @@ impl<E: Ext> dyn Iface<E> {
         self.common().name()
     }
 
+    /// Gets the type of the iface.
+    pub fn type_(&self) -> InterfaceType {
+        self.common().type_()
+    }
+
+    /// Gets the maximum transmission unit (MTU) of the iface.
+    ///
+    /// The MTU is the maximum size of the IP packets, so the size of the link-layer headers is
+    /// _not_ included.
+    pub fn mtu(&self) -> usize {
+        self.common().mtu()
+    }
+
     /// Gets the IPv4 address of the iface, if any.
     ///
     /// FIXME: One iface may have multiple IPv4 addresses.
@@ -52,6 +86,53 @@ impl<E: Ext> dyn Iface<E> {
         self.common().ipv4_addr()
     }
 
+    /// Gets the IPv6 address of the iface, if any.
+    ///
+    /// FIXME: One iface may have multiple IPv6 addresses.
+    pub fn ipv6_addr(&self) -> Option<Ipv6Address> {
+        self.common().ipv6_addr()
+    }
+
+    /// Returns whether `addr` is an address of the iface.
+    pub fn has_ip_addr(&self, addr: IpAddress) -> bool {
+        self.common().has_ip_addr(addr)
+    }
+
+    /// Gets all the IP addresses of the iface, together with their subnet prefixes.
+    pub fn ip_cidrs(&self) -> Vec<IpCidr> {
+        self.common().ip_cidrs()
+    }
+
+    /// Adds an IP address, together with its subnet prefix, to the iface.
+    pub fn add_ip_cidr(&self, cidr: IpCidr) -> Result<(), ConfigError> {
+        self.common().add_ip_cidr(cidr)
+    }
+
+    /// Removes an IP address, together with its subnet prefix, from the iface.
+    ///
+    /// Sockets that are already bound to the address are not affected.
+    pub fn remove_ip_cidr(&self, cidr: IpCidr) -> Result<(), ConfigError> {
+        self.common().remove_ip_cidr(cidr)
+    }
+
+    /// Gets all the routes of the iface.
+    ///
+    /// Note that the routes to the subnets of the iface's IP addresses are implicit and are not
+    /// included.
+    pub fn routes(&self) -> Vec<Route> {
+        self.common().routes()
+    }
+
+    /// Adds a route to the iface.
+    pub fn add_route(&self, route: Route) -> Result<(), ConfigError> {
+        self.common().add_route(route)
+    }
+
+    /// Removes the route to `cidr` from the iface.
+    pub fn remove_route(&self, cidr: IpCidr) -> Result<(), ConfigError> {
+        self.common().remove_route(cidr)
+    }
+
     /// Returns a reference to the associated [`ScheduleNextPoll`].
     pub fn sched_poll(&self) -> &E::ScheduleNextPoll {
         self.common().sched_poll()
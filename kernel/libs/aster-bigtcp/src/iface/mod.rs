@@ -10,7 +10,8 @@ mod sched;
 mod time;
 
 pub use common::BoundPort;
-pub use iface::Iface;
+pub use iface::{Iface, InterfaceType};
 pub use phy::{EtherIface, IpIface};
 pub use port::BindPortConfig;
 pub use sched::ScheduleNextPoll;
+pub use smoltcp::iface::Route;
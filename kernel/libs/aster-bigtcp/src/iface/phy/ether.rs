@@ -4,11 +4,16 @@ use alloc::{collections::btree_map::BTreeMap, string::String, sync::Arc};
 
 use ostd::sync::{LocalIrqDisabled, SpinLock};
 use smoltcp::{
-    iface::{packet::Packet, Config, Context},
-    phy::{DeviceCapabilities, TxToken},
+    iface::{
+        packet::{IpPayload, Packet},
+        Config, Context,
+    },
+    phy::{Device, DeviceCapabilities, Medium, TxToken},
     wire::{
         self, ArpOperation, ArpPacket, ArpRepr, EthernetAddress, EthernetFrame, EthernetProtocol,
-        EthernetRepr, IpAddress, Ipv4Address, Ipv4AddressExt, Ipv4Cidr, Ipv4Packet,
+        EthernetRepr, HardwareAddress, Icmpv6Packet, Icmpv6Repr, IpAddress, IpProtocol,
+        Ipv4Address, Ipv4AddressExt, Ipv4Cidr, Ipv6Address, Ipv6Cidr, Ipv6Packet, Ipv6Repr,
+        NdiscNeighborFlags, NdiscRepr, RawHardwareAddress,
     },
 };
 
@@ -17,7 +22,7 @@ use crate::{
     ext::Ext,
     iface::{
         common::IfaceCommon, iface::internal::IfaceInternal, time::get_network_timestamp, Iface,
-        ScheduleNextPoll,
+        InterfaceType, ScheduleNextPoll,
     },
 };
 
@@ -26,40 +31,66 @@ pub struct EtherIface<D, E: Ext> {
     common: IfaceCommon<E>,
     ether_addr: EthernetAddress,
     arp_table: SpinLock<BTreeMap<Ipv4Address, EthernetAddress>, LocalIrqDisabled>,
+    ndp_table: SpinLock<BTreeMap<Ipv6Address, EthernetAddress>, LocalIrqDisabled>,
+}
+
+/// A link-layer message that is generated in response to or in place of an IP packet.
+enum LinkMessage {
+    /// An ARP packet.
+    Arp(ArpRepr),
+    /// An NDP message, which is an ICMPv6 packet sent to the given Ethernet address.
+    Ndisc(EthernetRepr, Packet<'static>),
 }
 
 impl<D: WithDevice, E: Ext> EtherIface<D, E> {
     pub fn new(
         driver: D,
         ether_addr: EthernetAddress,
-        ip_cidr: Ipv4Cidr,
-        gateway: Ipv4Address,
+        ipv4_cidr: Ipv4Cidr,
+        ipv4_gateway: Ipv4Address,
+        ipv6_cidr: Ipv6Cidr,
+        ipv6_gateway: Ipv6Address,
         name: String,
         sched_poll: E::ScheduleNextPoll,
     ) -> Arc<Self> {
-        let interface = driver.with(|device| {
+        let (interface, mtu) = driver.with(|device| {
             let config = Config::new(wire::HardwareAddress::Ethernet(ether_addr));
             let now = get_network_timestamp();
 
             let mut interface = smoltcp::iface::Interface::new(config, device, now);
             interface.update_ip_addrs(|ip_addrs| {
                 debug_assert!(ip_addrs.is_empty());
-                ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                ip_addrs.push(wire::IpCidr::Ipv4(ipv4_cidr)).unwrap();
+                ip_addrs.push(wire::IpCidr::Ipv6(ipv6_cidr)).unwrap();
             });
             interface
                 .routes_mut()
-                .add_default_ipv4_route(gateway)
+                .add_default_ipv4_route(ipv4_gateway)
                 .unwrap();
             interface
+                .routes_mut()
+                .add_default_ipv6_route(ipv6_gateway)
+                .unwrap();
+
+            let mtu = device.capabilities().max_transmission_unit - wire::ETHERNET_HEADER_LEN;
+
+            (interface, mtu)
         });
 
-        let common = IfaceCommon::new(name, interface, sched_poll);
+        let common = IfaceCommon::new(
+            name,
+            InterfaceType::Ethernet(ether_addr),
+            mtu,
+            interface,
+            sched_poll,
+        );
 
         Arc::new(Self {
             driver,
             common,
             ether_addr,
             arp_table: SpinLock::new(BTreeMap::new()),
+            ndp_table: SpinLock::new(BTreeMap::new()),
         })
     }
 }
@@ -93,40 +124,49 @@ impl<D, E: Ext> EtherIface<D, E> {
         data: &'pkt [u8],
         iface_cx: &mut Context,
         tx_token: T,
-    ) -> Option<(Ipv4Packet<&'pkt [u8]>, T)> {
-        match self.parse_ip_or_process_arp(data, iface_cx) {
+    ) -> Option<(&'pkt [u8], T)> {
+        match self.parse_ip_or_process_link(data, iface_cx) {
             Ok(pkt) => Some((pkt, tx_token)),
-            Err(Some(arp)) => {
-                Self::emit_arp(&arp, tx_token);
+            Err(Some(msg)) => {
+                Self::emit_link(&msg, &iface_cx.caps, tx_token);
                 None
             }
             Err(None) => None,
         }
     }
 
-    fn parse_ip_or_process_arp<'pkt>(
+    fn parse_ip_or_process_link<'pkt>(
         &self,
         data: &'pkt [u8],
         iface_cx: &mut Context,
-    ) -> Result<Ipv4Packet<&'pkt [u8]>, Option<ArpRepr>> {
+    ) -> Result<&'pkt [u8], Option<LinkMessage>> {
         // Parse the Ethernet header. Ignore the packet if the header is ill-formed.
         let frame = EthernetFrame::new_checked(data).map_err(|_| None)?;
         let repr = EthernetRepr::parse(&frame).map_err(|_| None)?;
 
-        // Ignore the Ethernet frame if it is not sent to us.
-        if !repr.dst_addr.is_broadcast() && repr.dst_addr != self.ether_addr {
+        // Ignore the Ethernet frame if it is not sent to us. Multicast frames are needed by IPv6
+        // (e.g., for NDP messages).
+        if !repr.dst_addr.is_broadcast()
+            && !repr.dst_addr.is_multicast()
+            && repr.dst_addr != self.ether_addr
+        {
             return Err(None);
         }
 
         // Ignore the Ethernet frame if the protocol is not supported.
         match repr.ethertype {
-            EthernetProtocol::Ipv4 => {
-                Ok(Ipv4Packet::new_checked(frame.payload()).map_err(|_| None)?)
+            EthernetProtocol::Ipv4 => Ok(frame.payload()),
+            EthernetProtocol::Ipv6 => {
+                let pkt = Ipv6Packet::new_checked(frame.payload()).map_err(|_| None)?;
+                if let Some((ipv6_repr, ndisc_repr)) = Self::parse_ndisc(&pkt, iface_cx) {
+                    return Err(self.process_ndisc(&ipv6_repr, &ndisc_repr, &repr, iface_cx));
+                }
+                Ok(frame.payload())
             }
             EthernetProtocol::Arp => {
                 let pkt = ArpPacket::new_checked(frame.payload()).map_err(|_| None)?;
                 let arp = ArpRepr::parse(&pkt).map_err(|_| None)?;
-                Err(self.process_arp(&arp, iface_cx))
+                Err(self.process_arp(&arp, iface_cx).map(LinkMessage::Arp))
             }
             _ => Err(None),
         }
@@ -169,10 +209,7 @@ impl<D, E: Ext> EtherIface<D, E> {
                 }
 
                 // Ignore the ARP packet if we do not own the target address.
-                if iface_cx
-                    .ipv4_addr()
-                    .is_none_or(|addr| addr != *target_protocol_addr)
-                {
+                if !iface_cx.has_ip_addr(*target_protocol_addr) {
                     return None;
                 }
 
@@ -188,25 +225,192 @@ impl<D, E: Ext> EtherIface<D, E> {
         }
     }
 
+    /// Parses an NDP message.
+    ///
+    /// This method returns `None` if the IPv6 packet is not a valid NDP message.
+    fn parse_ndisc<'pkt>(
+        pkt: &Ipv6Packet<&'pkt [u8]>,
+        iface_cx: &Context,
+    ) -> Option<(Ipv6Repr, NdiscRepr<'pkt>)> {
+        let ipv6_repr = Ipv6Repr::parse(pkt).ok()?;
+
+        // NDP messages must have a hop limit of 255 so that they cannot come from other links.
+        // See <https://datatracker.ietf.org/doc/html/rfc4861#section-7.1.1>.
+        if ipv6_repr.next_header != IpProtocol::Icmpv6 || ipv6_repr.hop_limit != 255 {
+            return None;
+        }
+
+        let icmp_pkt = Icmpv6Packet::new_checked(pkt.payload()).ok()?;
+        let Icmpv6Repr::Ndisc(ndisc_repr) = Icmpv6Repr::parse(
+            &ipv6_repr.src_addr,
+            &ipv6_repr.dst_addr,
+            &icmp_pkt,
+            &iface_cx.checksum_caps(),
+        )
+        .ok()?
+        else {
+            return None;
+        };
+
+        Some((ipv6_repr, ndisc_repr))
+    }
+
+    fn process_ndisc(
+        &self,
+        ipv6_repr: &Ipv6Repr,
+        ndisc_repr: &NdiscRepr,
+        ether_repr: &EthernetRepr,
+        iface_cx: &mut Context,
+    ) -> Option<LinkMessage> {
+        match ndisc_repr {
+            NdiscRepr::NeighborAdvert {
+                target_addr,
+                lladdr: Some(lladdr),
+                ..
+            } => {
+                // Ignore the NDP message if the addresses are not unicast or not local.
+                let target_hardware_addr = ether_addr_of(lladdr)?;
+                if !target_hardware_addr.is_unicast()
+                    || !iface_cx.in_same_network(&IpAddress::Ipv6(*target_addr))
+                {
+                    return None;
+                }
+
+                // Insert the mapping between the Ethernet address and the IP address.
+                //
+                // TODO: Remove the mapping if it expires.
+                self.ndp_table
+                    .lock()
+                    .insert(*target_addr, target_hardware_addr);
+
+                None
+            }
+            NdiscRepr::NeighborSolicit {
+                target_addr,
+                lladdr,
+            } => {
+                // Ignore the NDP message if we do not own the target address.
+                if !iface_cx.has_ip_addr(*target_addr) {
+                    return None;
+                }
+
+                // A solicitation from the unspecified address is sent for duplicate address
+                // detection. The advertisement should be sent to all nodes in this case. See
+                // <https://datatracker.ietf.org/doc/html/rfc4861#section-7.2.4>.
+                let is_dad = ipv6_repr.src_addr.is_unspecified();
+
+                if !is_dad {
+                    if let Some(source_hardware_addr) = lladdr.as_ref().and_then(ether_addr_of) {
+                        if source_hardware_addr.is_unicast() {
+                            self.ndp_table
+                                .lock()
+                                .insert(ipv6_repr.src_addr, source_hardware_addr);
+                        }
+                    }
+                }
+
+                let (dst_addr, flags) = if is_dad {
+                    (
+                        Ipv6Address::new(0xff02, 0, 0, 0, 0, 0, 0, 1),
+                        NdiscNeighborFlags::OVERRIDE,
+                    )
+                } else {
+                    (
+                        ipv6_repr.src_addr,
+                        NdiscNeighborFlags::SOLICITED | NdiscNeighborFlags::OVERRIDE,
+                    )
+                };
+                let dst_hardware_addr = if is_dad {
+                    multicast_ether_addr(dst_addr)
+                } else {
+                    ether_repr.src_addr
+                };
+
+                Some(self.generate_ndisc(
+                    *target_addr,
+                    dst_addr,
+                    dst_hardware_addr,
+                    NdiscRepr::NeighborAdvert {
+                        flags,
+                        target_addr: *target_addr,
+                        lladdr: Some(RawHardwareAddress::from_bytes(self.ether_addr.as_bytes())),
+                    },
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn generate_ndisc(
+        &self,
+        src_addr: Ipv6Address,
+        dst_addr: Ipv6Address,
+        dst_hardware_addr: EthernetAddress,
+        ndisc_repr: NdiscRepr<'static>,
+    ) -> LinkMessage {
+        let icmp_repr = Icmpv6Repr::Ndisc(ndisc_repr);
+        let ipv6_repr = Ipv6Repr {
+            src_addr,
+            dst_addr,
+            next_header: IpProtocol::Icmpv6,
+            payload_len: icmp_repr.buffer_len(),
+            hop_limit: 255,
+        };
+        let ether_repr = EthernetRepr {
+            src_addr: self.ether_addr,
+            dst_addr: dst_hardware_addr,
+            ethertype: EthernetProtocol::Ipv6,
+        };
+
+        LinkMessage::Ndisc(
+            ether_repr,
+            Packet::new_ipv6(ipv6_repr, IpPayload::Icmpv6(icmp_repr)),
+        )
+    }
+
     fn dispatch<T: TxToken>(&self, pkt: &Packet, iface_cx: &mut Context, tx_token: T) {
-        match self.resolve_ether_or_generate_arp(pkt, iface_cx) {
+        match self.resolve_ether_or_generate_link(pkt, iface_cx) {
             Ok(ether) => Self::emit_ip(&ether, pkt, &iface_cx.caps, tx_token),
-            Err(Some(arp)) => Self::emit_arp(&arp, tx_token),
+            Err(Some(msg)) => Self::emit_link(&msg, &iface_cx.caps, tx_token),
             Err(None) => (),
         }
     }
 
-    fn resolve_ether_or_generate_arp(
+    fn resolve_ether_or_generate_link(
         &self,
         pkt: &Packet,
         iface_cx: &mut Context,
-    ) -> Result<EthernetRepr, Option<ArpRepr>> {
+    ) -> Result<EthernetRepr, Option<LinkMessage>> {
+        let dst_addr = pkt.ip_repr().dst_addr();
+
+        // IPv6 multicast packets are sent to the corresponding Ethernet multicast addresses.
+        if let IpAddress::Ipv6(dst_addr) = dst_addr {
+            if dst_addr.is_multicast() {
+                return Ok(EthernetRepr {
+                    src_addr: self.ether_addr,
+                    dst_addr: multicast_ether_addr(dst_addr),
+                    ethertype: EthernetProtocol::Ipv6,
+                });
+            }
+        }
+
         // Resolve the next-hop IP address.
-        let next_hop_ip = match iface_cx.route(&pkt.ip_repr().dst_addr(), iface_cx.now()) {
-            Some(IpAddress::Ipv4(next_hop_ip)) => next_hop_ip,
-            None => return Err(None),
-        };
+        match iface_cx.route(&dst_addr, iface_cx.now()) {
+            Some(IpAddress::Ipv4(next_hop_ip)) => self
+                .resolve_ether_or_generate_arp(next_hop_ip, iface_cx)
+                .map_err(|arp| arp.map(LinkMessage::Arp)),
+            Some(IpAddress::Ipv6(next_hop_ip)) => {
+                self.resolve_ether_or_generate_ndisc(next_hop_ip, iface_cx)
+            }
+            None => Err(None),
+        }
+    }
 
+    fn resolve_ether_or_generate_arp(
+        &self,
+        next_hop_ip: Ipv4Address,
+        iface_cx: &mut Context,
+    ) -> Result<EthernetRepr, Option<ArpRepr>> {
         // Resolve the next-hop Ethernet address.
         let next_hop_ether = if next_hop_ip.is_broadcast() {
             EthernetAddress::BROADCAST
@@ -232,6 +436,48 @@ impl<D, E: Ext> EtherIface<D, E> {
         })
     }
 
+    fn resolve_ether_or_generate_ndisc(
+        &self,
+        next_hop_ip: Ipv6Address,
+        iface_cx: &mut Context,
+    ) -> Result<EthernetRepr, Option<LinkMessage>> {
+        // Resolve the next-hop Ethernet address.
+        let next_hop_ether = if let Some(next_hop_ether) = self.ndp_table.lock().get(&next_hop_ip) {
+            *next_hop_ether
+        } else {
+            // Similar to ARP, if the next-hop Ethernet address cannot be resolved, we drop the
+            // original packet and send a neighbor solicitation to the solicited-node multicast
+            // address instead.
+            let src_addr = iface_cx.ipv6_addr().ok_or(None)?;
+            let dst_addr = solicited_node_addr(next_hop_ip);
+            return Err(Some(self.generate_ndisc(
+                src_addr,
+                dst_addr,
+                multicast_ether_addr(dst_addr),
+                NdiscRepr::NeighborSolicit {
+                    target_addr: next_hop_ip,
+                    lladdr: Some(RawHardwareAddress::from_bytes(self.ether_addr.as_bytes())),
+                },
+            )));
+        };
+
+        Ok(EthernetRepr {
+            src_addr: self.ether_addr,
+            dst_addr: next_hop_ether,
+            ethertype: EthernetProtocol::Ipv6,
+        })
+    }
+
+    /// Consumes the token and emits a link-layer message.
+    fn emit_link<T: TxToken>(msg: &LinkMessage, caps: &DeviceCapabilities, tx_token: T) {
+        match msg {
+            LinkMessage::Arp(arp_repr) => Self::emit_arp(arp_repr, tx_token),
+            LinkMessage::Ndisc(ether_repr, ip_pkt) => {
+                Self::emit_ip(ether_repr, ip_pkt, caps, tx_token)
+            }
+        }
+    }
+
     /// Consumes the token and emits an IP packet.
     fn emit_ip<T: TxToken>(
         ether_repr: &EthernetRepr,
@@ -280,3 +526,36 @@ impl<D, E: Ext> EtherIface<D, E> {
         });
     }
 }
+
+/// Converts the link-layer address in an NDP message to an Ethernet address.
+fn ether_addr_of(lladdr: &RawHardwareAddress) -> Option<EthernetAddress> {
+    match lladdr.parse(Medium::Ethernet).ok()? {
+        HardwareAddress::Ethernet(ether_addr) => Some(ether_addr),
+        _ => None,
+    }
+}
+
+/// Maps an IPv6 multicast address to an Ethernet multicast address.
+///
+/// See <https://datatracker.ietf.org/doc/html/rfc2464#section-7>.
+fn multicast_ether_addr(addr: Ipv6Address) -> EthernetAddress {
+    let octets = addr.octets();
+    EthernetAddress([0x33, 0x33, octets[12], octets[13], octets[14], octets[15]])
+}
+
+/// Returns the solicited-node multicast address of an IPv6 address.
+///
+/// See <https://datatracker.ietf.org/doc/html/rfc4291#section-2.7.1>.
+fn solicited_node_addr(addr: Ipv6Address) -> Ipv6Address {
+    let segments = addr.segments();
+    Ipv6Address::new(
+        0xff02,
+        0,
+        0,
+        0,
+        0,
+        1,
+        0xff00 | (segments[6] & 0xff),
+        segments[7],
+    )
+}
@@ -4,8 +4,8 @@ use alloc::{string::String, sync::Arc};
 
 use smoltcp::{
     iface::Config,
-    phy::TxToken,
-    wire::{self, Ipv4Cidr, Ipv4Packet},
+    phy::{Device, TxToken},
+    wire::{self, Ipv4Cidr, Ipv6Cidr},
 };
 
 use crate::{
@@ -13,7 +13,7 @@ use crate::{
     ext::Ext,
     iface::{
         common::IfaceCommon, iface::internal::IfaceInternal, time::get_network_timestamp, Iface,
-        ScheduleNextPoll,
+        InterfaceType, ScheduleNextPoll,
     },
 };
 
@@ -25,23 +25,28 @@ pub struct IpIface<D, E: Ext> {
 impl<D: WithDevice, E: Ext> IpIface<D, E> {
     pub fn new(
         driver: D,
-        ip_cidr: Ipv4Cidr,
+        ipv4_cidr: Ipv4Cidr,
+        ipv6_cidr: Ipv6Cidr,
         name: String,
         sched_poll: E::ScheduleNextPoll,
     ) -> Arc<Self> {
-        let interface = driver.with(|device| {
+        let (interface, mtu) = driver.with(|device| {
             let config = Config::new(smoltcp::wire::HardwareAddress::Ip);
             let now = get_network_timestamp();
 
             let mut interface = smoltcp::iface::Interface::new(config, device, now);
             interface.update_ip_addrs(|ip_addrs| {
                 debug_assert!(ip_addrs.is_empty());
-                ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                ip_addrs.push(wire::IpCidr::Ipv4(ipv4_cidr)).unwrap();
+                ip_addrs.push(wire::IpCidr::Ipv6(ipv6_cidr)).unwrap();
             });
-            interface
+
+            let mtu = device.capabilities().max_transmission_unit;
+
+            (interface, mtu)
         });
 
-        let common = IfaceCommon::new(name, interface, sched_poll);
+        let common = IfaceCommon::new(name, InterfaceType::Ip, mtu, interface, sched_poll);
 
         Arc::new(Self { driver, common })
     }
@@ -58,7 +63,7 @@ impl<D: WithDevice + 'static, E: Ext> Iface<E> for IpIface<D, E> {
         self.driver.with(|device| {
             let next_poll = self.common.poll(
                 device,
-                |data, _iface_cx, tx_token| Some((Ipv4Packet::new_checked(data).ok()?, tx_token)),
+                |data, _iface_cx, tx_token| Some((data, tx_token)),
                 |pkt, iface_cx, tx_token| {
                     let ip_repr = pkt.ip_repr();
                     tx_token.consume(ip_repr.buffer_len(), |buffer| {
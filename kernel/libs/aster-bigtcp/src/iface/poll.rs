@@ -9,16 +9,17 @@ use smoltcp::{
     },
     phy::{ChecksumCapabilities, Device, RxToken, TxToken},
     wire::{
-        Icmpv4DstUnreachable, Icmpv4Repr, IpAddress, IpProtocol, IpRepr, Ipv4Address, Ipv4Packet,
-        Ipv4Repr, TcpControl, TcpPacket, TcpRepr, UdpPacket, UdpRepr, IPV4_HEADER_LEN,
-        IPV4_MIN_MTU,
+        Icmpv4DstUnreachable, Icmpv4Repr, Icmpv6DstUnreachable, Icmpv6Packet, Icmpv6Repr,
+        IpAddress, IpProtocol, IpRepr, IpVersion, Ipv4Address, Ipv4Packet, Ipv4Repr, Ipv6Address,
+        Ipv6Packet, Ipv6Repr, TcpControl, TcpPacket, TcpRepr, UdpPacket, UdpRepr, IPV4_HEADER_LEN,
+        IPV4_MIN_MTU, IPV6_HEADER_LEN, IPV6_MIN_MTU,
     },
 };
 
 use crate::{
     ext::Ext,
     socket::{TcpConnectionBg, TcpProcessResult},
-    socket_table::{ConnectionKey, ListenerKey, SocketTable},
+    socket_table::{ConnectionKey, SocketTable},
 };
 
 pub(super) struct PollContext<'a, E: Ext> {
@@ -58,7 +59,7 @@ impl<E: Ext> PollContext<'_, E> {
             &'pkt [u8],
             &'cx mut Context,
             D::TxToken<'tx>,
-            Option<(Ipv4Packet<&'pkt [u8]>, D::TxToken<'tx>)>,
+            Option<(&'pkt [u8], D::TxToken<'tx>)>,
         >,
         Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
     {
@@ -68,7 +69,7 @@ impl<E: Ext> PollContext<'_, E> {
                     return;
                 };
 
-                let Some(reply) = self.parse_and_process_ipv4(pkt) else {
+                let Some(reply) = self.parse_and_process_ip(pkt) else {
                     return;
                 };
 
@@ -77,6 +78,14 @@ impl<E: Ext> PollContext<'_, E> {
         }
     }
 
+    fn parse_and_process_ip<'pkt>(&mut self, data: &'pkt [u8]) -> Option<Packet<'pkt>> {
+        // Ignore the packet if the IP version is unknown.
+        match IpVersion::of_packet(data).ok()? {
+            IpVersion::Ipv4 => self.parse_and_process_ipv4(Ipv4Packet::new_checked(data).ok()?),
+            IpVersion::Ipv6 => self.parse_and_process_ipv6(Ipv6Packet::new_checked(data).ok()?),
+        }
+    }
+
     fn parse_and_process_ipv4<'pkt>(
         &mut self,
         pkt: Ipv4Packet<&'pkt [u8]>,
@@ -88,7 +97,7 @@ impl<E: Ext> PollContext<'_, E> {
             return self.generate_icmp_unreachable(
                 &IpRepr::Ipv4(repr),
                 pkt.payload(),
-                Icmpv4DstUnreachable::HostUnreachable,
+                UnreachableReason::Host,
             );
         }
 
@@ -107,6 +116,91 @@ impl<E: Ext> PollContext<'_, E> {
         }
     }
 
+    fn parse_and_process_ipv6<'pkt>(
+        &mut self,
+        pkt: Ipv6Packet<&'pkt [u8]>,
+    ) -> Option<Packet<'pkt>> {
+        // Parse the IP header. Ignore the packet if the header is ill-formed.
+        let repr = Ipv6Repr::parse(&pkt).ok()?;
+
+        // Multicast packets (e.g., NDP messages) are handled by the ICMPv6 logic below. Note that
+        // NDP messages have already been handled if the iface is an Ethernet iface.
+        if !repr.dst_addr.is_multicast() && !self.is_unicast_local(IpAddress::Ipv6(repr.dst_addr)) {
+            return self.generate_icmp_unreachable(
+                &IpRepr::Ipv6(repr),
+                pkt.payload(),
+                UnreachableReason::Host,
+            );
+        }
+
+        // TODO: Support IPv6 extension headers.
+        match repr.next_header {
+            IpProtocol::Tcp => self.parse_and_process_tcp(
+                &IpRepr::Ipv6(repr),
+                pkt.payload(),
+                &self.iface_cx.checksum_caps(),
+            ),
+            IpProtocol::Udp => self.parse_and_process_udp(
+                &IpRepr::Ipv6(repr),
+                pkt.payload(),
+                &self.iface_cx.checksum_caps(),
+            ),
+            IpProtocol::Icmpv6 => self.parse_and_process_icmpv6(&repr, pkt.payload()),
+            _ => None,
+        }
+    }
+
+    fn parse_and_process_icmpv6<'pkt>(
+        &mut self,
+        ipv6_repr: &Ipv6Repr,
+        ip_payload: &'pkt [u8],
+    ) -> Option<Packet<'pkt>> {
+        // Parse the ICMPv6 header. Ignore the packet if the header is ill-formed.
+        let icmp_pkt = Icmpv6Packet::new_checked(ip_payload).ok()?;
+        let icmp_repr = Icmpv6Repr::parse(
+            &ipv6_repr.src_addr,
+            &ipv6_repr.dst_addr,
+            &icmp_pkt,
+            &self.iface_cx.checksum_caps(),
+        )
+        .ok()?;
+
+        // Only echo requests are answered here. Other ICMPv6 messages, including the error
+        // messages, are ignored because we do not have the ability to handle them.
+        //
+        // TODO: Report the ICMPv6 error messages to the sockets.
+        let Icmpv6Repr::EchoRequest {
+            ident,
+            seq_no,
+            data,
+        } = icmp_repr
+        else {
+            return None;
+        };
+
+        let src_addr = if ipv6_repr.dst_addr.is_multicast() {
+            self.iface_cx.ipv6_addr()?
+        } else {
+            ipv6_repr.dst_addr
+        };
+        let reply_repr = Icmpv6Repr::EchoReply {
+            ident,
+            seq_no,
+            data,
+        };
+
+        Some(Packet::new_ipv6(
+            Ipv6Repr {
+                src_addr,
+                dst_addr: ipv6_repr.src_addr,
+                next_header: IpProtocol::Icmpv6,
+                payload_len: reply_repr.buffer_len(),
+                hop_limit: 64,
+            },
+            IpPayload::Icmpv6(reply_repr),
+        ))
+    }
+
     fn parse_and_process_tcp<'pkt>(
         &mut self,
         ip_repr: &IpRepr,
@@ -159,8 +253,10 @@ impl<E: Ext> PollContext<'_, E> {
     ) -> Option<(IpRepr, TcpRepr<'static>)> {
         // Process packets that request to create new connections first.
         if tcp_repr.control == TcpControl::Syn && tcp_repr.ack_number.is_none() {
-            let listener_key = ListenerKey::new(ip_repr.dst_addr(), tcp_repr.dst_port);
-            if let Some(listener) = self.sockets.lookup_listener(&listener_key) {
+            if let Some(listener) = self
+                .sockets
+                .lookup_listener_by_dst(ip_repr.dst_addr(), tcp_repr.dst_port)
+            {
                 let (processed, new_tcp_conn) = listener.process(self.iface_cx, ip_repr, tcp_repr);
 
                 if let Some(tcp_conn) = new_tcp_conn {
@@ -228,11 +324,7 @@ impl<E: Ext> PollContext<'_, E> {
         .ok()?;
 
         if !self.process_udp(ip_repr, &udp_repr, udp_pkt.payload()) {
-            return self.generate_icmp_unreachable(
-                ip_repr,
-                ip_payload,
-                Icmpv4DstUnreachable::PortUnreachable,
-            );
+            return self.generate_icmp_unreachable(ip_repr, ip_payload, UnreachableReason::Port);
         }
 
         None
@@ -242,7 +334,7 @@ impl<E: Ext> PollContext<'_, E> {
         let mut processed = false;
 
         for socket in self.sockets.udp_socket_iter() {
-            if !socket.can_process(udp_repr.dst_port) {
+            if !socket.can_process(&ip_repr.dst_addr(), udp_repr.dst_port) {
                 continue;
             }
 
@@ -259,7 +351,7 @@ impl<E: Ext> PollContext<'_, E> {
         &self,
         ip_repr: &IpRepr,
         ip_payload: &'pkt [u8],
-        reason: Icmpv4DstUnreachable,
+        reason: UnreachableReason,
     ) -> Option<Packet<'pkt>> {
         if !ip_repr.src_addr().is_unicast() || !ip_repr.dst_addr().is_unicast() {
             return None;
@@ -275,28 +367,60 @@ impl<E: Ext> PollContext<'_, E> {
             return None;
         }
 
-        let IpRepr::Ipv4(ipv4_repr) = ip_repr;
+        match ip_repr {
+            IpRepr::Ipv4(ipv4_repr) => {
+                let reply_len =
+                    icmp_reply_payload_len(ip_payload.len(), IPV4_MIN_MTU, IPV4_HEADER_LEN);
+                let icmp_repr = Icmpv4Repr::DstUnreachable {
+                    reason: match reason {
+                        UnreachableReason::Host => Icmpv4DstUnreachable::HostUnreachable,
+                        UnreachableReason::Port => Icmpv4DstUnreachable::PortUnreachable,
+                    },
+                    header: *ipv4_repr,
+                    data: &ip_payload[..reply_len],
+                };
 
-        let reply_len = icmp_reply_payload_len(ip_payload.len(), IPV4_MIN_MTU, IPV4_HEADER_LEN);
-        let icmp_repr = Icmpv4Repr::DstUnreachable {
-            reason,
-            header: *ipv4_repr,
-            data: &ip_payload[..reply_len],
-        };
+                Some(Packet::new_ipv4(
+                    Ipv4Repr {
+                        src_addr: self
+                            .iface_cx
+                            .ipv4_addr()
+                            .unwrap_or(Ipv4Address::UNSPECIFIED),
+                        dst_addr: ipv4_repr.src_addr,
+                        next_header: IpProtocol::Icmp,
+                        payload_len: icmp_repr.buffer_len(),
+                        hop_limit: 64,
+                    },
+                    IpPayload::Icmpv4(icmp_repr),
+                ))
+            }
+            IpRepr::Ipv6(ipv6_repr) => {
+                let reply_len =
+                    icmp_reply_payload_len(ip_payload.len(), IPV6_MIN_MTU, IPV6_HEADER_LEN);
+                let icmp_repr = Icmpv6Repr::DstUnreachable {
+                    reason: match reason {
+                        UnreachableReason::Host => Icmpv6DstUnreachable::AddrUnreachable,
+                        UnreachableReason::Port => Icmpv6DstUnreachable::PortUnreachable,
+                    },
+                    header: *ipv6_repr,
+                    data: &ip_payload[..reply_len],
+                };
 
-        Some(Packet::new_ipv4(
-            Ipv4Repr {
-                src_addr: self
-                    .iface_cx
-                    .ipv4_addr()
-                    .unwrap_or(Ipv4Address::UNSPECIFIED),
-                dst_addr: ipv4_repr.src_addr,
-                next_header: IpProtocol::Icmp,
-                payload_len: icmp_repr.buffer_len(),
-                hop_limit: 64,
-            },
-            IpPayload::Icmpv4(icmp_repr),
-        ))
+                Some(Packet::new_ipv6(
+                    Ipv6Repr {
+                        src_addr: self
+                            .iface_cx
+                            .ipv6_addr()
+                            .unwrap_or(Ipv6Address::UNSPECIFIED),
+                        dst_addr: ipv6_repr.src_addr,
+                        next_header: IpProtocol::Icmpv6,
+                        payload_len: icmp_repr.buffer_len(),
+                        hop_limit: 64,
+                    },
+                    IpPayload::Icmpv6(icmp_repr),
+                ))
+            }
+        }
     }
 
     /// Returns whether the destination address is the unicast address of a local interface.
@@ -304,15 +428,21 @@ impl<E: Ext> PollContext<'_, E> {
     /// Note: "local" means that the IP address belongs to the local interface, not to be confused
     /// with the localhost IP (127.0.0.1).
     fn is_unicast_local(&self, dst_addr: IpAddress) -> bool {
-        match dst_addr {
-            IpAddress::Ipv4(dst_addr) => self
-                .iface_cx
-                .ipv4_addr()
-                .is_some_and(|addr| addr == dst_addr),
-        }
+        self.iface_cx.has_ip_addr(dst_addr)
     }
 }
 
+/// The reason why a destination is unreachable.
+///
+/// This is mapped to the ICMPv4 or ICMPv6 code according to the IP version of the packet.
+#[derive(Debug, Clone, Copy)]
+enum UnreachableReason {
+    /// No local address matches the destination address.
+    Host,
+    /// No socket is bound to the destination port.
+    Port,
+}
+
 impl<E: Ext> PollContext<'_, E> {
     pub(super) fn poll_egress<D, Q>(&mut self, device: &mut D, dispatch_phy: &mut Q)
     where
@@ -320,13 +450,13 @@ impl<E: Ext> PollContext<'_, E> {
         Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
     {
         while let Some(tx_token) = device.transmit(self.iface_cx.now()) {
-            if !self.dispatch_ipv4(tx_token, dispatch_phy) {
+            if !self.dispatch_ip(tx_token, dispatch_phy) {
                 break;
             }
         }
     }
 
-    fn dispatch_ipv4<T, Q>(&mut self, tx_token: T, dispatch_phy: &mut Q) -> bool
+    fn dispatch_ip<T, Q>(&mut self, tx_token: T, dispatch_phy: &mut Q) -> bool
     where
         T: TxToken,
         Q: FnMut(&Packet, &mut Context, T),
@@ -376,7 +506,7 @@ impl<E: Ext> PollContext<'_, E> {
                         return None;
                     }
 
-                    if !socket.can_process(tcp_repr.dst_port) {
+                    if !socket.can_process(&ip_repr.dst_addr(), tcp_repr.dst_port) {
                         return this.process_tcp(ip_repr, tcp_repr);
                     }
 
@@ -469,7 +599,7 @@ impl<E: Ext> PollContext<'_, E> {
                     }
                 }
 
-                if !socket.can_process(udp_repr.dst_port) {
+                if !socket.can_process(&ip_repr.dst_addr(), udp_repr.dst_port) {
                     // TODO: Generate the ICMP message here once we're able to handle incoming ICMP
                     // messages.
                     let _ = this.process_udp(ip_repr, udp_repr, udp_payload);
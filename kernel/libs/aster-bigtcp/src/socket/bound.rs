@@ -11,7 +11,7 @@ use smoltcp::{
     iface::Context,
     socket::{tcp::State, udp::UdpMetadata, PollAt},
     time::{Duration, Instant},
-    wire::{IpEndpoint, IpRepr, TcpControl, TcpRepr, UdpRepr},
+    wire::{IpAddress, IpEndpoint, IpRepr, TcpControl, TcpRepr, UdpRepr},
 };
 use spin::once::Once;
 use takeable::Takeable;
@@ -303,7 +303,11 @@ impl<T: Inner<E>, E: Ext> Socket<T, E> {
         self.0.observer.call_once(|| new_observer);
     }
 
-    pub fn local_endpoint(&self) -> Option<IpEndpoint> {
+    /// Returns the local endpoint.
+    ///
+    /// The address of the endpoint is unspecified if the socket is bound to the unspecified
+    /// address and is not connected.
+    pub fn local_endpoint(&self) -> IpEndpoint {
         self.0.bound.endpoint()
     }
 
@@ -339,33 +343,29 @@ impl<E: Ext> TcpConnection<E> {
     ///
     /// Polling the iface is _always_ required after this method succeeds.
     pub fn new_connect(
-        bound: BoundPort<E>,
+        mut bound: BoundPort<E>,
         remote_endpoint: IpEndpoint,
         option: &RawTcpOption,
         observer: E::TcpEventObserver,
     ) -> Result<Self, (BoundPort<E>, ConnectError)> {
-        let Some(local_endpoint) = bound.endpoint() else {
-            return Err((bound, ConnectError::Unaddressable));
-        };
-
         let iface = bound.iface().clone();
         // We have to lock interface before locking interface
         // to avoid dead lock due to inconsistent lock orders.
         let mut interface = iface.common().interface();
         let mut sockets = iface.common().sockets();
 
-        let connection_key = ConnectionKey::from((local_endpoint, remote_endpoint));
-
-        if sockets.lookup_connection(&connection_key).is_some() {
-            return Err((bound, ConnectError::AddressInUse));
-        }
-
         let socket = {
             let mut socket = new_tcp_socket();
 
             option.apply(&mut socket);
 
-            if let Err(err) = socket.connect(interface.context(), remote_endpoint, bound.port()) {
+            // If the port is bound to the unspecified address, smoltcp will select a local address
+            // according to the remote address.
+            if let Err(err) = socket.connect(
+                interface.context(),
+                remote_endpoint,
+                bound.listen_endpoint(),
+            ) {
                 return Err((bound, err.into()));
             }
 
@@ -374,6 +374,12 @@ impl<E: Ext> TcpConnection<E> {
 
         let inner = TcpConnectionInner::new(socket, None);
 
+        if sockets.lookup_connection(&inner.connection_key).is_some() {
+            return Err((bound, ConnectError::AddressInUse));
+        }
+
+        bound.set_addr(inner.lock().local_endpoint().unwrap().addr);
+
         let connection = Self::new(bound, inner);
         connection.0.update_next_poll_at_ms(PollAt::Now);
         connection.init_observer(observer);
@@ -509,9 +515,7 @@ impl<E: Ext> TcpListener<E> {
         option: &RawTcpOption,
         observer: E::TcpEventObserver,
     ) -> Result<Self, (BoundPort<E>, ListenError)> {
-        let Some(local_endpoint) = bound.endpoint() else {
-            return Err((bound, ListenError::Unaddressable));
-        };
+        let local_endpoint = bound.endpoint();
 
         let iface = bound.iface().clone();
         let mut sockets = iface.common().sockets();
@@ -527,7 +531,7 @@ impl<E: Ext> TcpListener<E> {
 
             option.apply(&mut socket);
 
-            if let Err(err) = socket.listen(local_endpoint) {
+            if let Err(err) = socket.listen(bound.listen_endpoint()) {
                 return Err((bound, err.into()));
             }
 
@@ -604,14 +608,10 @@ impl<E: Ext> UdpSocket<E> {
         bound: BoundPort<E>,
         observer: E::UdpEventObserver,
     ) -> Result<Self, (BoundPort<E>, smoltcp::socket::udp::BindError)> {
-        let Some(local_endpoint) = bound.endpoint() else {
-            return Err((bound, smoltcp::socket::udp::BindError::Unaddressable));
-        };
-
         let socket = {
             let mut socket = new_udp_socket();
 
-            if let Err(err) = socket.bind(local_endpoint) {
+            if let Err(err) = socket.bind(bound.listen_endpoint()) {
                 return Err((bound, err));
             }
 
@@ -790,8 +790,8 @@ impl<T: Inner<E>, E: Ext> SocketBg<T, E> {
     /// Returns whether an incoming packet _may_ be processed by the socket.
     ///
     /// The check is intended to be lock-free and fast, but may have false positives.
-    pub(crate) fn can_process(&self, dst_port: u16) -> bool {
-        self.bound.port() == dst_port
+    pub(crate) fn can_process(&self, dst_addr: &IpAddress, dst_port: u16) -> bool {
+        self.bound.port() == dst_port && self.bound.accepts_addr(dst_addr)
     }
 
     /// Returns whether the socket _may_ generate an outgoing packet.
@@ -951,7 +951,10 @@ impl<E: Ext> TcpListenerBg<E> {
         let conn = TcpConnection::new(
             self.bound
                 .iface()
-                .bind(BindPortConfig::CanReuse(self.bound.port()))
+                .bind(
+                    ip_repr.dst_addr(),
+                    BindPortConfig::CanReuse(self.bound.port()),
+                )
                 .unwrap(),
             inner,
         );
@@ -4,7 +4,7 @@
 //! for efficiently inserting, looking up, and removing sockets.
 
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
-use core::net::Ipv4Addr;
+use core::net::{Ipv4Addr, Ipv6Addr};
 
 use jhash::{jhash_1vals, jhash_3vals};
 use smoltcp::wire::{IpAddress, IpEndpoint, IpListenEndpoint};
@@ -103,23 +103,30 @@ const fn hash_local_remote(
     remote_addr: IpAddress,
     remote_port: PortNum,
 ) -> SocketHash {
-    // FIXME: Deal with IPv6 addresses once IPv6 is supported.
-    let IpAddress::Ipv4(local_ipv4) = local_addr;
-    let IpAddress::Ipv4(remote_ipv4) = remote_addr;
-
     jhash_3vals(
-        local_ipv4.to_bits(),
-        remote_ipv4.to_bits(),
+        fold_addr(local_addr),
+        fold_addr(remote_addr),
         (local_port as u32).wrapping_shl(16) | remote_port as u32,
         HASH_SECRET.wrapping_add(NET_HASHMIX),
     )
 }
 
 const fn hash_addr_port(addr: IpAddress, port: PortNum) -> SocketHash {
-    // FIXME: Deal with IPv6 addresses once IPv6 is supported.
-    let IpAddress::Ipv4(ipv4_addr) = addr;
+    jhash_1vals(fold_addr(addr), NET_HASHMIX) ^ (port as u32)
+}
 
-    jhash_1vals(ipv4_addr.to_bits(), NET_HASHMIX) ^ (port as u32)
+/// Folds an IP address into a 32-bit value for hashing.
+///
+/// IPv6 addresses are folded by XORing their four 32-bit words, which is similar to what Linux
+/// does in `ipv6_addr_hash`.
+const fn fold_addr(addr: IpAddress) -> u32 {
+    match addr {
+        IpAddress::Ipv4(ipv4_addr) => ipv4_addr.to_bits(),
+        IpAddress::Ipv6(ipv6_addr) => {
+            let bits = ipv6_addr.to_bits();
+            (bits as u32) ^ ((bits >> 32) as u32) ^ ((bits >> 64) as u32) ^ ((bits >> 96) as u32)
+        }
+    }
 }
 
 /// The socket table manages TCP and UDP sockets.
@@ -134,7 +141,8 @@ pub(crate) struct SocketTable<E: Ext> {
     // the first is hashed by local address and port,
     // the second is hashed by local port only.
     // The second table is the only place where sockets listening on INADDR_ANY (0.0.0.0) can exist.
-    // Since we do not yet support INADDR_ANY, we only have the first table here.
+    // We only have the first table here, so sockets listening on unspecified addresses are
+    // looked up with separate keys (see `lookup_listener_by_dst`).
     listener_buckets: Box<[ListenerHashBucket<E>]>,
     connection_buckets: Box<[ConnectionHashBucket<E>]>,
     // Linux does not include UDP sockets in the inet hashtable.
@@ -249,6 +257,39 @@ impl<E: Ext> SocketTable<E> {
             .find(|listener| listener.listener_key() == key)
     }
 
+    /// Looks up the TCP listener that should handle a new connection to `dst_addr:dst_port`.
+    ///
+    /// A listener bound to the exact address takes precedence over a listener bound to the
+    /// unspecified address. An IPv4 connection can also be handled by a listener bound to the
+    /// unspecified IPv6 address, unless the listener accepts IPv6 connections only.
+    pub(crate) fn lookup_listener_by_dst(
+        &self,
+        dst_addr: IpAddress,
+        dst_port: PortNum,
+    ) -> Option<&Arc<TcpListenerBg<E>>> {
+        if let Some(listener) = self.lookup_listener(&ListenerKey::new(dst_addr, dst_port)) {
+            return Some(listener);
+        }
+
+        let unspecified_addr = match dst_addr {
+            IpAddress::Ipv4(_) => IpAddress::Ipv4(Ipv4Addr::UNSPECIFIED),
+            IpAddress::Ipv6(_) => IpAddress::Ipv6(Ipv6Addr::UNSPECIFIED),
+        };
+        if let Some(listener) = self.lookup_listener(&ListenerKey::new(unspecified_addr, dst_port))
+        {
+            return Some(listener);
+        }
+
+        if !matches!(dst_addr, IpAddress::Ipv4(_)) {
+            return None;
+        }
+        self.lookup_listener(&ListenerKey::new(
+            IpAddress::Ipv6(Ipv6Addr::UNSPECIFIED),
+            dst_port,
+        ))
+        .filter(|listener| listener.can_process(&dst_addr, dst_port))
+    }
+
     pub(crate) fn lookup_connection(
         &self,
         key: &ConnectionKey,
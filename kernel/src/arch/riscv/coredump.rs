@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::{cpu::UserContext, user::UserContextApi, Pod};
+
+use super::cpu::GpRegs;
+
+/// The machine type in the ELF header of core files (i.e., `EM_RISCV`).
+pub const ELF_MACHINE: u16 = 243;
+
+/// The general-purpose registers in the `NT_PRSTATUS` note.
+///
+/// This is the `elf_gregset_t` in Linux, which consists of the PC followed by
+/// the registers from `x1` to `x31`.
+pub type ElfGregs = [usize; 32];
+
+/// Returns the general-purpose registers of the user context for core dumps.
+pub fn elf_gregs(user_ctx: &UserContext) -> ElfGregs {
+    let mut gp_regs = GpRegs::default();
+    gp_regs.copy_from_raw(user_ctx.general_regs());
+
+    // The layout is the same as `GpRegs`, except that the slot of the
+    // hardwired zero register `x0` holds the PC.
+    let mut gregs = ElfGregs::new_zeroed();
+    gregs.as_bytes_mut().copy_from_slice(gp_regs.as_bytes());
+    gregs[0] = user_ctx.instruction_pointer();
+    gregs
+}
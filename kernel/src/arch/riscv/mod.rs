@@ -1,4 +1,5 @@
 // SPDX-License-Identifier: MPL-2.0
 
+pub mod coredump;
 pub mod cpu;
 pub mod signal;
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::cpu::UserContext;
+
+use super::ptrace::UserRegs;
+
+/// The machine type in the ELF header of core files (i.e., `EM_X86_64`).
+pub const ELF_MACHINE: u16 = 62;
+
+/// The general-purpose registers in the `NT_PRSTATUS` note.
+///
+/// This is the `elf_gregset_t` in Linux, which has the same layout as
+/// `user_regs_struct` on x86-64.
+pub type ElfGregs = UserRegs;
+
+/// Returns the general-purpose registers of the user context for core dumps.
+pub fn elf_gregs(user_ctx: &UserContext) -> ElfGregs {
+    UserRegs::new(user_ctx, None)
+}
@@ -1,4 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
+pub mod coredump;
 pub mod cpu;
+pub mod ptrace;
 pub mod signal;
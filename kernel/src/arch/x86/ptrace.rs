@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::{cpu::UserContext, mm::MAX_USERSPACE_VADDR, Pod};
+
+use crate::prelude::*;
+
+/// The general-purpose registers exposed to the tracer.
+///
+/// This is the `user_regs_struct` in Linux.
+#[derive(Debug, Clone, Copy, Pod, Default)]
+#[repr(C)]
+pub struct UserRegs {
+    pub r15: usize,
+    pub r14: usize,
+    pub r13: usize,
+    pub r12: usize,
+    pub rbp: usize,
+    pub rbx: usize,
+    pub r11: usize,
+    pub r10: usize,
+    pub r9: usize,
+    pub r8: usize,
+    pub rax: usize,
+    pub rcx: usize,
+    pub rdx: usize,
+    pub rsi: usize,
+    pub rdi: usize,
+    pub orig_rax: usize,
+    pub rip: usize,
+    pub cs: usize,
+    pub rflags: usize,
+    pub rsp: usize,
+    pub ss: usize,
+    pub fs_base: usize,
+    pub gs_base: usize,
+    pub ds: usize,
+    pub es: usize,
+    pub fs: usize,
+    pub gs: usize,
+}
+
+/// The offset of `u_debugreg` in the `user` struct, which is accessed by
+/// `PTRACE_PEEKUSER` and `PTRACE_POKEUSER`.
+pub const USER_DEBUGREG_OFFSET: usize = 848;
+
+// The segment selectors of the user code and data segments.
+const USER_CS: usize = 0x33;
+const USER_SS: usize = 0x2b;
+
+// The flags in RFLAGS that can be changed by the tracer, i.e.,
+// CF, PF, AF, ZF, SF, TF, DF, OF, NT, RF, and AC.
+const RFLAGS_USER_MASK: usize = 0x54dd5;
+
+impl UserRegs {
+    /// Creates the registers from the user context.
+    ///
+    /// The `orig_rax` is the number of the syscall in progress.
+    pub fn new(user_ctx: &UserContext, orig_rax: Option<usize>) -> Self {
+        let regs = user_ctx.general_regs();
+        Self {
+            r15: regs.r15,
+            r14: regs.r14,
+            r13: regs.r13,
+            r12: regs.r12,
+            rbp: regs.rbp,
+            rbx: regs.rbx,
+            r11: regs.r11,
+            r10: regs.r10,
+            r9: regs.r9,
+            r8: regs.r8,
+            rax: regs.rax,
+            rcx: regs.rcx,
+            rdx: regs.rdx,
+            rsi: regs.rsi,
+            rdi: regs.rdi,
+            orig_rax: orig_rax.unwrap_or(usize::MAX),
+            rip: regs.rip,
+            cs: USER_CS,
+            rflags: regs.rflags,
+            rsp: regs.rsp,
+            ss: USER_SS,
+            fs_base: regs.fsbase,
+            gs_base: regs.gsbase,
+            ds: 0,
+            es: 0,
+            fs: 0,
+            gs: 0,
+        }
+    }
+
+    /// Writes the registers back to the user context.
+    ///
+    /// The segment selectors cannot be changed, and only the flags in RFLAGS
+    /// that are changeable in the user mode will be updated.
+    ///
+    /// Like Linux, this method fails with `EIO` if RIP or the FS base is not
+    /// a user-space address, since returning to such an address via `sysretq`
+    /// faults in the kernel mode. The GS base cannot be changed because
+    /// `ARCH_SET_GS` is not supported.
+    pub fn write_to(&self, user_ctx: &mut UserContext, orig_rax: &mut Option<usize>) -> Result<()> {
+        if self.rip >= MAX_USERSPACE_VADDR {
+            return_errno_with_message!(Errno::EIO, "RIP is not a user-space address");
+        }
+        if self.fs_base >= MAX_USERSPACE_VADDR {
+            return_errno_with_message!(Errno::EIO, "the FS base is not a user-space address");
+        }
+        if self.gs_base != user_ctx.general_regs().gsbase {
+            return_errno_with_message!(Errno::EIO, "the GS base cannot be changed");
+        }
+
+        let regs = user_ctx.general_regs_mut();
+        regs.r15 = self.r15;
+        regs.r14 = self.r14;
+        regs.r13 = self.r13;
+        regs.r12 = self.r12;
+        regs.rbp = self.rbp;
+        regs.rbx = self.rbx;
+        regs.r11 = self.r11;
+        regs.r10 = self.r10;
+        regs.r9 = self.r9;
+        regs.r8 = self.r8;
+        regs.rax = self.rax;
+        regs.rcx = self.rcx;
+        regs.rdx = self.rdx;
+        regs.rsi = self.rsi;
+        regs.rdi = self.rdi;
+        regs.rip = self.rip;
+        regs.rflags = (regs.rflags & !RFLAGS_USER_MASK) | (self.rflags & RFLAGS_USER_MASK);
+        regs.rsp = self.rsp;
+        // The new FS base is activated when the tracee is resumed.
+        user_ctx.set_tls_pointer(self.fs_base);
+
+        if orig_rax.is_some() {
+            *orig_rax = Some(self.orig_rax);
+        }
+
+        Ok(())
+    }
+}
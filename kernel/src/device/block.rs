@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The block devices, which are shown as `/dev/<name>`.
+
+use aster_block::{BlockDevice, SECTOR_SIZE};
+use ostd::mm::VmIo;
+use spin::Once;
+
+use super::*;
+use crate::{
+    events::IoEvents,
+    fs::{
+        device::{alloc_major, register_device},
+        inode_handle::FileIo,
+        sysfs,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+};
+
+/// The number of minor numbers that are reserved for each disk and its partitions.
+pub const MINORS_PER_DISK: u32 = 16;
+
+/// The maximum number of bytes that are transferred by one read or write.
+const MAX_IO_LEN: usize = 128 * 1024;
+
+static BLOCK_MAJOR: Once<u32> = Once::new();
+
+/// The names of the disks that have been added, indexed by the positions of their minors.
+static DISKS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub(super) fn init() -> Result<()> {
+    let major = alloc_major(DeviceType::BlockDevice, "virtblk")?;
+    BLOCK_MAJOR.call_once(|| major);
+
+    // The callback is registered before enumerating the devices, so that no device is missed.
+    // A device that is found in both ways is added only once.
+    let callback = |name: &str, device: Arc<dyn BlockDevice>| {
+        if let Err(err) = add_disk(name, device) {
+            warn!("failed to add the block device {}: {:?}", name, err);
+        }
+    };
+    aster_block::register_callback(Box::leak(Box::new(callback)));
+
+    for (name, device) in aster_block::all_devices() {
+        add_disk(&name, device)?;
+    }
+
+    Ok(())
+}
+
+/// Creates the device node and the sysfs entries of a disk.
+fn add_disk(name: &str, device: Arc<dyn BlockDevice>) -> Result<()> {
+    let id = {
+        let mut disks = DISKS.lock();
+        if disks.iter().any(|registered| registered == name) {
+            return Ok(());
+        }
+        disks.push(name.to_string());
+        disk_id(disks.len() - 1)
+    };
+
+    register_device(
+        Arc::new(BlockFile {
+            device: device.clone(),
+            id,
+        }),
+        name,
+    )?;
+    sysfs::device_model::add_disk(name, &device, id)
+}
+
+/// Returns the device ID of the `index`-th disk.
+fn disk_id(index: usize) -> DeviceId {
+    DeviceId::new(*BLOCK_MAJOR.get().unwrap(), index as u32 * MINORS_PER_DISK)
+}
+
+/// A block device.
+///
+/// The device is accessed through the sectors, so the file offset and the length of each read
+/// or write must be multiples of the sector size.
+struct BlockFile {
+    device: Arc<dyn BlockDevice>,
+    id: DeviceId,
+}
+
+impl Device for BlockFile {
+    fn type_(&self) -> DeviceType {
+        DeviceType::BlockDevice
+    }
+
+    fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
+        Ok(Some(Arc::new(OpenBlockFile {
+            device: self.device.clone(),
+            offset: Mutex::new(0),
+        })))
+    }
+}
+
+impl Pollable for BlockFile {
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        let events = IoEvents::IN | IoEvents::OUT;
+        events & mask
+    }
+}
+
+impl FileIo for BlockFile {
+    fn read(&self, _writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "cannot read block device");
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "cannot write block device");
+    }
+}
+
+/// An opened block device, which has its own file offset.
+///
+/// FIXME: The file offset cannot be changed by `lseek`, since the offset of a device file is not
+/// passed to its [`FileIo`].
+struct OpenBlockFile {
+    device: Arc<dyn BlockDevice>,
+    offset: Mutex<usize>,
+}
+
+impl OpenBlockFile {
+    /// Returns the number of bytes to transfer at `offset`, which is at most `len` bytes.
+    fn io_len(&self, offset: usize, len: usize) -> Result<usize> {
+        let capacity = self.device.metadata().nr_sectors * SECTOR_SIZE;
+        let len = len.min(MAX_IO_LEN).min(capacity.saturating_sub(offset));
+        if offset % SECTOR_SIZE != 0 || len % SECTOR_SIZE != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the I/O is not aligned to sectors");
+        }
+        Ok(len)
+    }
+}
+
+impl Pollable for OpenBlockFile {
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        let events = IoEvents::IN | IoEvents::OUT;
+        events & mask
+    }
+}
+
+impl FileIo for OpenBlockFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let mut offset = self.offset.lock();
+        let len = self.io_len(*offset, writer.avail())?;
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let mut buf = vec![0u8; len];
+        self.device.read_bytes(*offset, &mut buf)?;
+        writer.write_fallible(&mut buf.as_slice().into())?;
+
+        *offset += len;
+        Ok(len)
+    }
+
+    fn write(&self, reader: &mut VmReader) -> Result<usize> {
+        let mut offset = self.offset.lock();
+        let len = self.io_len(*offset, reader.remain())?;
+        if len == 0 {
+            if reader.remain() == 0 {
+                return Ok(0);
+            }
+            return_errno_with_message!(Errno::ENOSPC, "the end of the device is reached");
+        }
+
+        let mut buf = vec![0u8; len];
+        reader.read_fallible(&mut VmWriter::from(buf.as_mut_slice()))?;
+        self.device.write_bytes(*offset, &buf)?;
+
+        *offset += len;
+        Ok(len)
+    }
+}
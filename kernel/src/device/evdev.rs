@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The event devices of the input devices, which are shown as `/dev/input/event<N>`.
+//!
+//! Each opened event device has its own queue, which receives the events in the format of
+//! `struct input_event` since the file is opened.
+
+use alloc::format;
+
+use aster_input::{key::KeyStatus, InputDevice, InputEvent};
+use ostd::sync::LocalIrqDisabled;
+
+use super::*;
+use crate::{
+    events::IoEvents,
+    fs::{
+        device::{register_device, register_major, INPUT_MAJOR},
+        inode_handle::FileIo,
+        sysfs,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    time::{clocks::RealTimeClock, timeval_t},
+};
+
+/// The first minor number of the event devices.
+const EVDEV_MINOR_BASE: u32 = 64;
+
+/// The maximum number of the events in the queue of an opened event device.
+///
+/// If the queue is full, the oldest events are dropped.
+const EVENT_QUEUE_LEN: usize = 64;
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const SYN_REPORT: u16 = 0;
+
+/// The names of the input devices that have been added, indexed by the numbers of their event
+/// devices.
+static INPUT_DEVICES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub(super) fn init() -> Result<()> {
+    register_major(DeviceType::CharDevice, INPUT_MAJOR, "input")?;
+
+    // The callback is registered before enumerating the devices, so that no device is missed.
+    // A device that is found in both ways is added only once.
+    let callback = |name: &str, input_device: Arc<dyn InputDevice>| {
+        if let Err(err) = add_input_device(name, input_device) {
+            warn!("failed to add the input device {}: {:?}", name, err);
+        }
+    };
+    aster_input::register_callback(Box::leak(Box::new(callback)));
+
+    for (name, input_device) in aster_input::all_devices() {
+        add_input_device(&name, input_device)?;
+    }
+
+    Ok(())
+}
+
+/// Creates the event device of an input device, and its device node and sysfs entries.
+fn add_input_device(name: &str, input_device: Arc<dyn InputDevice>) -> Result<()> {
+    let index = {
+        let mut input_devices = INPUT_DEVICES.lock();
+        if input_devices.iter().any(|registered| registered == name) {
+            return Ok(());
+        }
+        input_devices.push(name.to_string());
+        input_devices.len() - 1
+    };
+    let evdev = Arc::new(EventDevice::new(event_device_id(index)));
+
+    let cloned_evdev = evdev.clone();
+    let callback = move |event: InputEvent| cloned_evdev.handle_input_event(event);
+    input_device.register_callbacks(Box::leak(Box::new(callback)));
+
+    let id = evdev.id;
+    register_device(evdev, &format!("input/event{}", index))?;
+    sysfs::device_model::add_input_device(index, name, id)
+}
+
+/// Returns the device ID of the `index`-th event device.
+fn event_device_id(index: usize) -> DeviceId {
+    DeviceId::new(INPUT_MAJOR, EVDEV_MINOR_BASE + index as u32)
+}
+
+/// The event device of an input device.
+struct EventDevice {
+    id: DeviceId,
+    clients: SpinLock<Vec<Weak<EventClient>>, LocalIrqDisabled>,
+}
+
+impl EventDevice {
+    fn new(id: DeviceId) -> Self {
+        Self {
+            id,
+            clients: SpinLock::new(Vec::new()),
+        }
+    }
+
+    /// Passes an event from the input device to the opened event devices.
+    ///
+    /// This method may be called in the interrupt context.
+    fn handle_input_event(&self, event: InputEvent) {
+        let time = timeval_t::from(RealTimeClock::get().read_time());
+        let InputEvent::KeyBoard(key, status) = event;
+        let value = match status {
+            KeyStatus::Pressed => 1,
+            KeyStatus::Released => 0,
+        };
+        let events = [
+            RawInputEvent {
+                time,
+                type_: EV_KEY,
+                code: key as u16,
+                value,
+            },
+            RawInputEvent {
+                time,
+                type_: EV_SYN,
+                code: SYN_REPORT,
+                value: 0,
+            },
+        ];
+
+        let mut clients = self.clients.lock();
+        clients.retain(|client| {
+            let Some(client) = client.upgrade() else {
+                return false;
+            };
+            client.push_events(&events);
+            true
+        });
+    }
+}
+
+impl Device for EventDevice {
+    fn type_(&self) -> DeviceType {
+        DeviceType::CharDevice
+    }
+
+    fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
+        let client = Arc::new(EventClient {
+            queue: SpinLock::new(VecDeque::new()),
+            pollee: Pollee::new(),
+        });
+        self.clients.lock().push(Arc::downgrade(&client));
+        Ok(Some(client))
+    }
+}
+
+impl Pollable for EventDevice {
+    fn poll(&self, _mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        IoEvents::empty()
+    }
+}
+
+impl FileIo for EventDevice {
+    fn read(&self, _writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "cannot read event device");
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "cannot write event device");
+    }
+}
+
+/// An opened event device.
+struct EventClient {
+    queue: SpinLock<VecDeque<RawInputEvent>, LocalIrqDisabled>,
+    pollee: Pollee,
+}
+
+impl EventClient {
+    fn push_events(&self, events: &[RawInputEvent]) {
+        let mut queue = self.queue.lock();
+        for event in events {
+            if queue.len() == EVENT_QUEUE_LEN {
+                queue.pop_front();
+            }
+            queue.push_back(*event);
+        }
+        drop(queue);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let mut queue = self.queue.lock();
+        if queue.is_empty() {
+            return_errno_with_message!(Errno::EAGAIN, "the event queue is empty");
+        }
+
+        let nr_events = (writer.avail() / size_of::<RawInputEvent>()).min(queue.len());
+        let events: Vec<RawInputEvent> = queue.drain(..nr_events).collect();
+        drop(queue);
+
+        self.pollee.invalidate();
+
+        for event in events.iter() {
+            writer.write_val(event)?;
+        }
+        Ok(nr_events * size_of::<RawInputEvent>())
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.queue.lock().is_empty() {
+            IoEvents::empty()
+        } else {
+            IoEvents::IN
+        }
+    }
+}
+
+impl Pollable for EventClient {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileIo for EventClient {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        if writer.avail() < size_of::<RawInputEvent>() {
+            return_errno_with_message!(Errno::EINVAL, "the buffer is too small for an event");
+        }
+
+        // FIXME: The read should not block if the file is opened with `O_NONBLOCK`. But the file
+        // status flags are not passed to the `FileIo` of a device.
+        self.wait_events(IoEvents::IN, None, || self.try_read(writer))
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "injecting events is not supported");
+    }
+}
+
+/// The `struct input_event` in Linux.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct RawInputEvent {
+    time: timeval_t,
+    type_: u16,
+    code: u16,
+    value: i32,
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The framebuffer device, which is shown as `/dev/fb0`.
+//!
+//! FIXME: The `FBIOGET_*` ioctls and `mmap` are not supported yet, so the geometry of the
+//! framebuffer can only be found in the sysfs.
+
+use aster_framebuffer::FrameBuffer;
+use ostd::mm::VmIo;
+
+use super::*;
+use crate::{
+    events::IoEvents,
+    fs::{
+        device::{register_device, register_major, FB_MAJOR},
+        inode_handle::FileIo,
+        sysfs,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+};
+
+pub(super) fn init() -> Result<()> {
+    let Some(framebuffer) = aster_framebuffer::framebuffer() else {
+        return Ok(());
+    };
+
+    register_major(DeviceType::CharDevice, FB_MAJOR, "fb")?;
+
+    let id = DeviceId::new(FB_MAJOR, 0);
+    register_device(
+        Arc::new(FbDevice {
+            framebuffer: framebuffer.clone(),
+            id,
+        }),
+        "fb0",
+    )?;
+    sysfs::device_model::add_framebuffer("fb0", &framebuffer, id)
+}
+
+/// A framebuffer device.
+struct FbDevice {
+    framebuffer: Arc<FrameBuffer>,
+    id: DeviceId,
+}
+
+impl Device for FbDevice {
+    fn type_(&self) -> DeviceType {
+        DeviceType::CharDevice
+    }
+
+    fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
+        Ok(Some(Arc::new(OpenFbDevice {
+            framebuffer: self.framebuffer.clone(),
+            offset: Mutex::new(0),
+        })))
+    }
+}
+
+impl Pollable for FbDevice {
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        let events = IoEvents::IN | IoEvents::OUT;
+        events & mask
+    }
+}
+
+impl FileIo for FbDevice {
+    fn read(&self, _writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "cannot read framebuffer device");
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "cannot write framebuffer device");
+    }
+}
+
+/// An opened framebuffer device, which has its own file offset.
+///
+/// FIXME: The file offset cannot be changed by `lseek`, since the offset of a device file is not
+/// passed to its [`FileIo`].
+struct OpenFbDevice {
+    framebuffer: Arc<FrameBuffer>,
+    offset: Mutex<usize>,
+}
+
+impl Pollable for OpenFbDevice {
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        let events = IoEvents::IN | IoEvents::OUT;
+        events & mask
+    }
+}
+
+impl FileIo for OpenFbDevice {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let mut offset = self.offset.lock();
+        let len = writer
+            .avail()
+            .min(self.framebuffer.size().saturating_sub(*offset));
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let mut buf = vec![0u8; len];
+        self.framebuffer.io_mem().read_bytes(*offset, &mut buf)?;
+        writer.write_fallible(&mut buf.as_slice().into())?;
+
+        *offset += len;
+        Ok(len)
+    }
+
+    fn write(&self, reader: &mut VmReader) -> Result<usize> {
+        let mut offset = self.offset.lock();
+        let len = reader
+            .remain()
+            .min(self.framebuffer.size().saturating_sub(*offset));
+        if len == 0 {
+            if reader.remain() == 0 {
+                return Ok(0);
+            }
+            return_errno_with_message!(Errno::ENOSPC, "the end of the framebuffer is reached");
+        }
+
+        let mut buf = vec![0u8; len];
+        reader.read_fallible(&mut VmWriter::from(buf.as_mut_slice()))?;
+        self.framebuffer.io_mem().write_bytes(*offset, &buf)?;
+
+        *offset += len;
+        Ok(len)
+    }
+}
@@ -2,6 +2,9 @@
 
 use cfg_if::cfg_if;
 
+pub mod block;
+mod evdev;
+mod fb;
 mod null;
 mod pty;
 mod random;
@@ -26,58 +29,40 @@ pub use urandom::Urandom;
 
 use self::tty::get_n_tty;
 use crate::{
-    fs::device::{add_node, Device, DeviceId, DeviceType},
+    fs::device::{
+        register_device, register_major, Device, DeviceId, DeviceType, MEM_MAJOR, MISC_MAJOR,
+        TTYAUX_MAJOR, UNIX98_PTY_SLAVE_MAJOR,
+    },
     prelude::*,
 };
 
-/// Init the device node in fs, must be called after mounting rootfs.
+/// Registers the devices, whose device nodes are created in the devtmpfs.
+///
+/// This must be called after mounting the devtmpfs at `/dev`.
 pub fn init() -> Result<()> {
-    let null = Arc::new(null::Null);
-    add_node(null, "null")?;
-    let zero = Arc::new(zero::Zero);
-    add_node(zero, "zero")?;
+    register_major(DeviceType::CharDevice, MEM_MAJOR, "mem")?;
+    register_major(DeviceType::CharDevice, TTYAUX_MAJOR, "/dev/tty")?;
+    register_major(DeviceType::CharDevice, MISC_MAJOR, "misc")?;
+    register_major(DeviceType::CharDevice, UNIX98_PTY_SLAVE_MAJOR, "pts")?;
+
+    register_device(Arc::new(null::Null), "null")?;
+    register_device(Arc::new(zero::Zero), "zero")?;
     tty::init();
-    let console = get_n_tty().clone();
-    add_node(console, "console")?;
-    let tty = Arc::new(tty::TtyDevice);
-    add_node(tty, "tty")?;
+    register_device(get_n_tty().clone(), "console")?;
+    register_device(Arc::new(tty::TtyDevice), "tty")?;
     cfg_if! {
         if #[cfg(all(target_arch = "x86_64", feature = "cvm_guest"))] {
-            let tdx_guest = Arc::new(tdxguest::TdxGuest);
-
             if tdx_is_enabled() {
-                add_node(tdx_guest, "tdx_guest")?;
+                register_device(Arc::new(tdxguest::TdxGuest), "tdx_guest")?;
             }
         }
     }
-    let random = Arc::new(random::Random);
-    add_node(random, "random")?;
-    let urandom = Arc::new(urandom::Urandom);
-    add_node(urandom, "urandom")?;
+    register_device(Arc::new(random::Random), "random")?;
+    register_device(Arc::new(urandom::Urandom), "urandom")?;
+    block::init()?;
+    evdev::init()?;
+    fb::init()?;
     pty::init()?;
     shm::init()?;
     Ok(())
 }
-
-// TODO: Implement a more scalable solution for ID-to-device mapping.
-// Instead of hardcoding every device numbers in this function,
-// a registration mechanism should be used to allow each driver to
-// allocate device IDs either statically or dynamically.
-pub fn get_device(dev: usize) -> Result<Arc<dyn Device>> {
-    if dev == 0 {
-        return_errno_with_message!(Errno::EPERM, "whiteout device")
-    }
-
-    let devid = DeviceId::from(dev as u64);
-    let major = devid.major();
-    let minor = devid.minor();
-
-    match (major, minor) {
-        (1, 3) => Ok(Arc::new(null::Null)),
-        (1, 5) => Ok(Arc::new(zero::Zero)),
-        (5, 0) => Ok(Arc::new(tty::TtyDevice)),
-        (1, 8) => Ok(Arc::new(random::Random)),
-        (1, 9) => Ok(Arc::new(urandom::Urandom)),
-        _ => return_errno_with_message!(Errno::EINVAL, "unsupported device"),
-    }
-}
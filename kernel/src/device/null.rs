@@ -19,7 +19,7 @@ impl Device for Null {
 
     fn id(&self) -> DeviceId {
         // Same value with Linux
-        DeviceId::new(1, 3)
+        DeviceId::new(MEM_MAJOR, 3)
     }
 
     fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
@@ -7,7 +7,7 @@ use crate::{
     device::tty::{line_discipline::LineDiscipline, new_job_control_and_ldisc},
     events::IoEvents,
     fs::{
-        device::{Device, DeviceId, DeviceType},
+        device::{Device, DeviceId, DeviceType, UNIX98_PTY_SLAVE_MAJOR},
         devpts::DevPts,
         file_table::FdFlags,
         fs_resolver::FsPath,
@@ -205,7 +205,8 @@ impl FileIo for PtyMaster {
                     let fs_path = FsPath::try_from(slave_name.as_str())?;
 
                     let inode_handle = {
-                        let fs = current.fs().resolver().read();
+                        let fs_ref = current.fs();
+                        let fs = fs_ref.resolver().read();
                         let flags = AccessMode::O_RDWR as u32;
                         let mode = (InodeMode::S_IRUSR | InodeMode::S_IWUSR).bits();
                         fs.open(&fs_path, flags, mode)?
@@ -214,7 +215,7 @@ impl FileIo for PtyMaster {
                 };
 
                 let fd = {
-                    let mut file_table = current.file_table().lock();
+                    let mut file_table = current.file_table().lock_arc();
                     // TODO: deal with the O_CLOEXEC flag
                     file_table.insert(slave, FdFlags::empty())
                 };
@@ -321,7 +322,7 @@ impl Device for PtySlave {
     }
 
     fn id(&self) -> crate::fs::device::DeviceId {
-        DeviceId::new(88, self.index())
+        DeviceId::new(UNIX98_PTY_SLAVE_MAJOR, self.index())
     }
 }
 
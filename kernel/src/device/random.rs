@@ -5,7 +5,7 @@
 use crate::{
     events::IoEvents,
     fs::{
-        device::{Device, DeviceId, DeviceType},
+        device::{Device, DeviceId, DeviceType, MEM_MAJOR},
         inode_handle::FileIo,
     },
     prelude::*,
@@ -29,7 +29,7 @@ impl Device for Random {
 
     fn id(&self) -> DeviceId {
         // The same value as Linux
-        DeviceId::new(1, 8)
+        DeviceId::new(MEM_MAJOR, 8)
     }
 
     fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
@@ -5,7 +5,7 @@
 use crate::{
     events::IoEvents,
     fs::{
-        device::{Device, DeviceId, DeviceType},
+        device::{Device, DeviceId, DeviceType, TTYAUX_MAJOR},
         inode_handle::FileIo,
     },
     prelude::*,
@@ -36,7 +36,7 @@ impl Device for TtyDevice {
     }
 
     fn id(&self) -> DeviceId {
-        DeviceId::new(5, 0)
+        DeviceId::new(TTYAUX_MAJOR, 0)
     }
 }
 
@@ -10,7 +10,7 @@ use crate::{
     current_userspace,
     events::IoEvents,
     fs::{
-        device::{Device, DeviceId, DeviceType},
+        device::{Device, DeviceId, DeviceType, TTYAUX_MAJOR},
         inode_handle::FileIo,
         utils::IoctlCmd,
     },
@@ -186,7 +186,7 @@ impl Device for Tty {
 
     fn id(&self) -> DeviceId {
         // The same value as /dev/console in linux.
-        DeviceId::new(88, 0)
+        DeviceId::new(TTYAUX_MAJOR, 1)
     }
 }
 
@@ -19,7 +19,7 @@ impl Device for Zero {
 
     fn id(&self) -> DeviceId {
         // Same value with Linux
-        DeviceId::new(1, 5)
+        DeviceId::new(MEM_MAJOR, 5)
     }
 
     fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
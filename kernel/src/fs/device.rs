@@ -1,14 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::inode_handle::FileIo;
-use crate::{
-    fs::{
-        fs_resolver::{FsPath, FsResolver},
-        path::Dentry,
-        utils::{InodeMode, InodeType},
-    },
-    prelude::*,
-};
+use core::ops::RangeInclusive;
+
+use super::{devtmpfs, inode_handle::FileIo};
+use crate::prelude::*;
 
 /// The abstract of device
 pub trait Device: FileIo {
@@ -33,7 +28,7 @@ impl Debug for dyn Device {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 /// Device type
 pub enum DeviceType {
     CharDevice,
@@ -87,79 +82,154 @@ impl From<u64> for DeviceId {
     }
 }
 
-/// Add a device node to FS for the device.
+/// The major number of the memory devices (e.g., `/dev/null`).
+pub const MEM_MAJOR: u32 = 1;
+/// The major number of the TTY devices (e.g., `/dev/tty1`).
+pub const TTY_MAJOR: u32 = 4;
+/// The major number of the auxiliary TTY devices (i.e., `/dev/tty`, `/dev/console` and
+/// `/dev/ptmx`).
+pub const TTYAUX_MAJOR: u32 = 5;
+/// The major number of the miscellaneous character devices.
+pub const MISC_MAJOR: u32 = 10;
+/// The major number of the input devices (e.g., `/dev/input/event0`).
+pub const INPUT_MAJOR: u32 = 13;
+/// The major number of the framebuffer devices (e.g., `/dev/fb0`).
+pub const FB_MAJOR: u32 = 29;
+/// The major number of the pseudoterminal slaves (e.g., `/dev/pts/0`).
+pub const UNIX98_PTY_SLAVE_MAJOR: u32 = 136;
+
+/// The range of the major numbers that are allocated dynamically.
 ///
-/// If the parent path is not existing, `mkdir -p` the parent path.
-/// This function is used in registering device.
-pub fn add_node(device: Arc<dyn Device>, path: &str) -> Result<Dentry> {
-    let mut dentry = {
-        let fs_resolver = FsResolver::new();
-        fs_resolver.lookup(&FsPath::try_from("/dev").unwrap())?
-    };
-    let mut relative_path = {
-        let relative_path = path.trim_start_matches('/');
-        if relative_path.is_empty() {
-            return_errno_with_message!(Errno::EINVAL, "invalid device path");
-        }
-        relative_path
-    };
+/// The numbers are allocated from the end of the range, which is the same as Linux.
+const DYNAMIC_MAJORS: RangeInclusive<u32> = 234..=254;
+
+/// The registered majors, indexed by the device types and the major numbers.
+static MAJORS: Mutex<BTreeMap<(DeviceType, u32), String>> = Mutex::new(BTreeMap::new());
+
+/// The registered devices, indexed by the device types and the device IDs.
+static DEVICES: RwLock<BTreeMap<(DeviceType, u64), RegisteredDevice>> =
+    RwLock::new(BTreeMap::new());
+
+struct RegisteredDevice {
+    device: Arc<dyn Device>,
+    devname: String,
+}
 
-    while !relative_path.is_empty() {
-        let (next_name, path_remain) = if let Some((prefix, suffix)) = relative_path.split_once('/')
-        {
-            (prefix, suffix.trim_start_matches('/'))
-        } else {
-            (relative_path, "")
-        };
-
-        match dentry.lookup(next_name) {
-            Ok(next_dentry) => {
-                if path_remain.is_empty() {
-                    return_errno_with_message!(Errno::EEXIST, "device node is existing");
-                }
-                dentry = next_dentry;
-            }
-            Err(_) => {
-                if path_remain.is_empty() {
-                    // Create the device node
-                    dentry = dentry.mknod(
-                        next_name,
-                        InodeMode::from_bits_truncate(0o666),
-                        device.clone().into(),
-                    )?;
-                } else {
-                    // Mkdir parent path
-                    dentry = dentry.new_fs_child(
-                        next_name,
-                        InodeType::Dir,
-                        InodeMode::from_bits_truncate(0o755),
-                    )?;
-                }
-            }
+/// Returns the device type that determines the number space of the device.
+///
+/// Miscellaneous devices are character devices, so they share the number space of the
+/// character devices.
+fn number_space(type_: DeviceType) -> DeviceType {
+    match type_ {
+        DeviceType::BlockDevice => DeviceType::BlockDevice,
+        DeviceType::CharDevice | DeviceType::MiscDevice => DeviceType::CharDevice,
+    }
+}
+
+/// Registers a major number with the name of the driver.
+///
+/// Registering the same major with the same name again is allowed, so that a driver can be
+/// initialized multiple times.
+pub fn register_major(type_: DeviceType, major: u32, name: &str) -> Result<()> {
+    let mut majors = MAJORS.lock();
+    match majors.get(&(number_space(type_), major)) {
+        Some(registered_name) if registered_name == name => Ok(()),
+        Some(_) => return_errno_with_message!(Errno::EBUSY, "the major is already registered"),
+        None => {
+            majors.insert((number_space(type_), major), name.to_string());
+            Ok(())
         }
-        relative_path = path_remain;
     }
+}
 
-    Ok(dentry)
+/// Allocates a dynamic major number for the driver.
+///
+/// If the driver has already allocated a major, the same major is returned.
+pub fn alloc_major(type_: DeviceType, name: &str) -> Result<u32> {
+    let mut majors = MAJORS.lock();
+    let space = number_space(type_);
+
+    let allocated_major =
+        majors
+            .iter()
+            .find_map(|(&(registered_space, major), registered_name)| {
+                (registered_space == space
+                    && DYNAMIC_MAJORS.contains(&major)
+                    && registered_name == name)
+                    .then_some(major)
+            });
+    if let Some(major) = allocated_major {
+        return Ok(major);
+    }
+
+    let Some(major) = DYNAMIC_MAJORS
+        .rev()
+        .find(|major| !majors.contains_key(&(space, *major)))
+    else {
+        return_errno_with_message!(Errno::EBUSY, "no dynamic major is available");
+    };
+    majors.insert((space, major), name.to_string());
+    Ok(major)
+}
+
+/// Returns the registered majors of the given type and their names.
+pub fn all_majors(type_: DeviceType) -> Vec<(u32, String)> {
+    let space = number_space(type_);
+    MAJORS
+        .lock()
+        .iter()
+        .filter(|((registered_space, _), _)| *registered_space == space)
+        .map(|((_, major), name)| (*major, name.clone()))
+        .collect()
 }
 
-/// Delete the device node from FS for the device.
+/// Registers a device and creates its device node in the devtmpfs.
 ///
-/// This function is used in unregistering device.
-pub fn delete_node(path: &str) -> Result<()> {
-    let abs_path = {
-        let device_path = path.trim_start_matches('/');
-        if device_path.is_empty() {
-            return_errno_with_message!(Errno::EINVAL, "invalid device path");
+/// The major of the device must have been registered. `devname` is the path of the device node
+/// relative to the root of the devtmpfs, e.g., `input/event0`.
+pub fn register_device(device: Arc<dyn Device>, devname: &str) -> Result<()> {
+    let space = number_space(device.type_());
+    let id = device.id();
+    if !MAJORS.lock().contains_key(&(space, id.major())) {
+        return_errno_with_message!(Errno::EINVAL, "the major is not registered");
+    }
+
+    let key = (space, id.into());
+    {
+        let mut devices = DEVICES.write();
+        if devices.contains_key(&key) {
+            return_errno_with_message!(Errno::EEXIST, "the device ID is already registered");
         }
-        String::from("/dev") + "/" + device_path
-    };
+        devices.insert(
+            key,
+            RegisteredDevice {
+                device: device.clone(),
+                devname: devname.to_string(),
+            },
+        );
+    }
 
-    let (parent_dentry, name) = {
-        let fs_resolver = FsResolver::new();
-        fs_resolver.lookup_dir_and_base_name(&FsPath::try_from(abs_path.as_str()).unwrap())?
-    };
+    // The device node is created without holding the lock, since creating it may sleep.
+    if let Err(err) = devtmpfs::add_node(device, devname) {
+        DEVICES.write().remove(&key);
+        return Err(err);
+    }
 
-    parent_dentry.unlink(&name)?;
     Ok(())
 }
+
+/// Unregisters a device and removes its device node from the devtmpfs.
+pub fn unregister_device(type_: DeviceType, id: DeviceId) -> Result<()> {
+    let Some(registered) = DEVICES.write().remove(&(number_space(type_), id.into())) else {
+        return_errno_with_message!(Errno::ENOENT, "the device is not registered");
+    };
+    devtmpfs::delete_node(&registered.devname)
+}
+
+/// Looks up a registered device by its type and ID.
+pub fn get_device(type_: DeviceType, id: DeviceId) -> Option<Arc<dyn Device>> {
+    DEVICES
+        .read()
+        .get(&(number_space(type_), id.into()))
+        .map(|registered| registered.device.clone())
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The devtmpfs, which contains the device nodes of the registered devices.
+//!
+//! There is only one devtmpfs instance. It is mounted at `/dev` when the rootfs is ready, and
+//! mounting another devtmpfs shows the same device nodes. The device nodes are created when the
+//! devices are registered with [`register_device`], so they exist even before any devtmpfs is
+//! mounted. User programs can still create and remove files in the devtmpfs as in a ramfs.
+//!
+//! [`register_device`]: super::device::register_device
+
+use spin::Once;
+
+use super::{
+    device::{Device, DeviceType},
+    ramfs::RamFS,
+    utils::{FileSystem, Inode, InodeMode, InodeType},
+};
+use crate::prelude::*;
+
+static DEVTMPFS: Once<Arc<RamFS>> = Once::new();
+
+/// Returns the devtmpfs instance.
+pub fn devtmpfs() -> &'static Arc<RamFS> {
+    DEVTMPFS.call_once(RamFS::new)
+}
+
+/// Creates the device node at `path`, which is relative to the root of the devtmpfs.
+///
+/// The missing parent directories are created.
+pub(super) fn add_node(device: Arc<dyn Device>, path: &str) -> Result<()> {
+    let (parent_path, name) = split_path(path)?;
+
+    let mut dir = devtmpfs().root_inode();
+    for dir_name in parent_path.split('/').filter(|name| !name.is_empty()) {
+        dir = match dir.lookup(dir_name) {
+            Ok(child) => child,
+            Err(err) if err.error() == Errno::ENOENT => dir.create(
+                dir_name,
+                InodeType::Dir,
+                InodeMode::from_bits_truncate(0o755),
+            )?,
+            Err(err) => return Err(err),
+        };
+    }
+
+    // Block devices can be used to bypass the permission checks of the file systems on them, so
+    // only the owner (i.e., root) and the group can access them.
+    let mode = match device.type_() {
+        DeviceType::BlockDevice => InodeMode::from_bits_truncate(0o660),
+        DeviceType::CharDevice | DeviceType::MiscDevice => InodeMode::from_bits_truncate(0o666),
+    };
+    dir.mknod(name, mode, device.into())?;
+
+    Ok(())
+}
+
+/// Removes the device node at `path`, which is relative to the root of the devtmpfs.
+///
+/// FIXME: The device node is removed without going through the dentry layer, so it may remain
+/// visible through the dentries that are cached by the existing mounts.
+pub(super) fn delete_node(path: &str) -> Result<()> {
+    let (parent_path, name) = split_path(path)?;
+
+    let mut dir = devtmpfs().root_inode();
+    for dir_name in parent_path.split('/').filter(|name| !name.is_empty()) {
+        dir = dir.lookup(dir_name)?;
+    }
+    dir.unlink(name)
+}
+
+/// Splits `path` into the path of the parent directory and the name of the device node.
+fn split_path(path: &str) -> Result<(&str, &str)> {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "invalid device path");
+    }
+
+    Ok(path.rsplit_once('/').unwrap_or(("", path)))
+}
@@ -66,7 +66,7 @@ impl EpollFile {
         let file = {
             let current = current_thread!();
             let current = current.as_posix_thread().unwrap();
-            let file_table = current.file_table().lock();
+            let file_table = current.file_table().lock_arc();
             file_table.get_file(fd)?.clone()
         };
 
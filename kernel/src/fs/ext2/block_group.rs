@@ -5,9 +5,11 @@ use id_alloc::IdAlloc;
 use super::{
     block_ptr::Ext2Bid,
     fs::Ext2,
-    inode::{Inode, InodeDesc, RawInode},
+    inode::{inode_csum_seed, Inode, InodeDesc, RawInode},
+    journal::Transaction,
     prelude::*,
     super_block::SuperBlock,
+    utils::crc32c,
 };
 
 /// Blocks are clustered into block groups in order to reduce fragmentation and minimise
@@ -21,6 +23,10 @@ pub(super) struct BlockGroup {
 struct BlockGroupImpl {
     inode_table_bid: Ext2Bid,
     raw_inodes_size: usize,
+    /// The seed of the metadata checksums, if they are enabled.
+    csum_seed: Option<u32>,
+    /// The size of the extra fields of new inodes.
+    extra_isize: u16,
     inner: RwMutex<Inner>,
     fs: Weak<Ext2>,
 }
@@ -35,18 +41,36 @@ impl BlockGroup {
         fs: Weak<Ext2>,
     ) -> Result<Self> {
         let raw_inodes_size = (super_block.inodes_per_group() as usize) * super_block.inode_size();
+        let csum_seed = super_block
+            .has_metadata_csum()
+            .then(|| super_block.csum_seed());
 
         let bg_impl = {
             let metadata = {
-                let descriptor = {
+                let mut raw_descriptor = {
                     // Read the block group descriptor
                     // TODO: if the main is corrupted, should we load the backup?
-                    let offset = idx * core::mem::size_of::<RawGroupDescriptor>();
-                    let raw_descriptor = group_descriptors_segment
-                        .read_val::<RawGroupDescriptor>(offset)
+                    let desc_size = super_block.desc_size();
+                    let mut raw_descriptor = RawGroupDescriptor::new_zeroed();
+                    group_descriptors_segment
+                        .read_bytes(
+                            idx * desc_size,
+                            &mut raw_descriptor.as_bytes_mut()[..desc_size],
+                        )
                         .unwrap();
-                    GroupDescriptor::from(raw_descriptor)
+                    raw_descriptor
                 };
+                if super_block.has_group_desc_csum() {
+                    if raw_descriptor.checksum
+                        != super_block.group_descriptor_checksum(idx, raw_descriptor.as_bytes())
+                    {
+                        return_errno_with_message!(Errno::EBADMSG, "bad group descriptor checksum");
+                    }
+                } else {
+                    // The flags are meaningless without the checksum.
+                    raw_descriptor.flags = 0;
+                }
+                let descriptor = GroupDescriptor::try_from(raw_descriptor)?;
 
                 let get_bitmap = |bid: Ext2Bid, capacity: usize| -> Result<IdAlloc> {
                     if capacity > BLOCK_SIZE * 8 {
@@ -56,26 +80,66 @@ impl BlockGroup {
                     block_device.read_bytes(bid as usize * BLOCK_SIZE, &mut buf)?;
                     Ok(IdAlloc::from_bytes_with_capacity(&buf, capacity))
                 };
+                let verify_bitmap = |bitmap: &IdAlloc, csum_lo: u16, csum_hi: u16| -> Result<()> {
+                    let Some(csum_seed) = csum_seed else {
+                        return Ok(());
+                    };
+                    let csum = crc32c(csum_seed, bitmap.as_bytes());
+                    if csum as u16 != csum_lo
+                        || (super_block.desc_size() > RawGroupDescriptor::HI_OFFSET
+                            && (csum >> 16) as u16 != csum_hi)
+                    {
+                        return_errno_with_message!(Errno::EBADMSG, "bad bitmap checksum");
+                    }
+                    Ok(())
+                };
 
-                let block_bitmap = get_bitmap(
-                    descriptor.block_bitmap_bid,
-                    super_block.blocks_per_group() as usize,
-                )?;
-                let inode_bitmap = get_bitmap(
-                    descriptor.inode_bitmap_bid,
-                    super_block.inodes_per_group() as usize,
-                )?;
+                let block_bitmap = if descriptor.flags.contains(GroupFlags::BLOCK_UNINIT) {
+                    init_block_bitmap(idx, &descriptor, super_block)
+                } else {
+                    let block_bitmap = get_bitmap(
+                        descriptor.block_bitmap_bid,
+                        super_block.blocks_per_group() as usize,
+                    )?;
+                    verify_bitmap(
+                        &block_bitmap,
+                        raw_descriptor.block_bitmap_csum,
+                        raw_descriptor.block_bitmap_csum_hi,
+                    )?;
+                    block_bitmap
+                };
+                let inode_bitmap = if descriptor.flags.contains(GroupFlags::INODE_UNINIT) {
+                    IdAlloc::with_capacity(super_block.inodes_per_group() as usize)
+                } else {
+                    let inode_bitmap = get_bitmap(
+                        descriptor.inode_bitmap_bid,
+                        super_block.inodes_per_group() as usize,
+                    )?;
+                    verify_bitmap(
+                        &inode_bitmap,
+                        raw_descriptor.inode_bitmap_csum,
+                        raw_descriptor.inode_bitmap_csum_hi,
+                    )?;
+                    inode_bitmap
+                };
 
                 GroupMetadata {
                     descriptor,
                     block_bitmap,
                     inode_bitmap,
+                    inodes_per_group: super_block.inodes_per_group(),
                 }
             };
 
             Arc::new(BlockGroupImpl {
                 inode_table_bid: metadata.descriptor.inode_table_bid,
                 raw_inodes_size,
+                csum_seed,
+                extra_isize: if super_block.inode_size() > core::mem::size_of::<RawInode>() {
+                    super_block.want_extra_isize()
+                } else {
+                    0
+                },
                 inner: RwMutex::new(Inner {
                     metadata: Dirty::new(metadata),
                     inode_cache: BTreeMap::new(),
@@ -126,17 +190,31 @@ impl BlockGroup {
     /// This method may load the raw inode metadata from block device.
     fn load_inode(&self, inode_idx: u32) -> Result<Arc<Inode>> {
         let fs = self.fs();
-        let raw_inode = {
-            let offset = (inode_idx as usize) * fs.inode_size();
+        let offset = (inode_idx as usize) * fs.inode_size();
+        let raw_inode = self
+            .raw_inodes_cache
+            .pages()
+            .read_val::<RawInode>(offset)
+            .unwrap();
+        let ino = inode_idx + self.idx as u32 * fs.inodes_per_group() + 1;
+        if let Some(csum_seed) = self.bg_impl.csum_seed {
+            let mut bytes = vec![0u8; fs.inode_size()];
             self.raw_inodes_cache
                 .pages()
-                .read_val::<RawInode>(offset)
-                .unwrap()
-        };
+                .read_bytes(offset, &mut bytes)
+                .unwrap();
+            let (csum, has_hi) = calc_inode_checksum(csum_seed, ino, &bytes);
+            let csum_lo =
+                u16::from_le_bytes([bytes[INODE_CSUM_LO_OFFSET], bytes[INODE_CSUM_LO_OFFSET + 1]]);
+            let csum_hi =
+                u16::from_le_bytes([bytes[INODE_CSUM_HI_OFFSET], bytes[INODE_CSUM_HI_OFFSET + 1]]);
+            if csum as u16 != csum_lo || (has_hi && (csum >> 16) as u16 != csum_hi) {
+                return_errno_with_message!(Errno::EBADMSG, "bad inode checksum");
+            }
+        }
         let inode_desc = Dirty::new(InodeDesc::try_from(raw_inode)?);
-        let ino = inode_idx + self.idx as u32 * fs.inodes_per_group() + 1;
 
-        Ok(Inode::new(ino, self.idx, inode_desc, Arc::downgrade(&fs)))
+        Inode::new(ino, self.idx, inode_desc, Arc::downgrade(&fs))
     }
 
     /// Inserts the inode into the inode cache.
@@ -151,6 +229,8 @@ impl BlockGroup {
     }
 
     /// Allocates and returns an inode index.
+    ///
+    /// The raw inode is reset to zeros, except for the size of its extra fields.
     pub fn alloc_inode(&self, is_dir: bool) -> Option<u32> {
         // The fast path
         if self.bg_impl.inner.read().metadata.free_inodes_count() == 0 {
@@ -158,7 +238,20 @@ impl BlockGroup {
         }
 
         // The slow path
-        self.bg_impl.inner.write().metadata.alloc_inode(is_dir)
+        let inode_idx = self.bg_impl.inner.write().metadata.alloc_inode(is_dir)?;
+
+        let inode_size = self.fs().inode_size();
+        let mut bytes = vec![0u8; inode_size];
+        if inode_size > core::mem::size_of::<RawInode>() {
+            let extra_isize = self.bg_impl.extra_isize.to_le_bytes();
+            bytes[INODE_EXTRA_ISIZE_OFFSET..INODE_EXTRA_ISIZE_OFFSET + 2]
+                .copy_from_slice(&extra_isize);
+        }
+        self.raw_inodes_cache
+            .pages()
+            .write_bytes(inode_idx as usize * inode_size, &bytes)
+            .unwrap();
+        Some(inode_idx)
     }
 
     /// Frees the allocated inode idx.
@@ -209,45 +302,150 @@ impl BlockGroup {
     }
 
     /// Writes back the raw inode metadata to the raw inode metadata cache.
+    ///
+    /// The extra fields of the raw inode are kept, and the checksum is updated.
     pub fn sync_raw_inode(&self, inode_idx: u32, raw_inode: &RawInode) {
-        let offset = (inode_idx as usize) * self.fs().inode_size();
+        let fs = self.fs();
+        let offset = (inode_idx as usize) * fs.inode_size();
         self.raw_inodes_cache
             .pages()
             .write_val(offset, raw_inode)
             .unwrap();
+        self.update_inode_checksum(inode_idx);
+    }
+
+    /// Reads the area for the extended attributes after the extra fields of the raw inode.
+    ///
+    /// The area is empty if the raw inode has no room for it.
+    pub fn read_inode_xattr_area(&self, inode_idx: u32) -> Vec<u8> {
+        let Some(area) = self.inode_xattr_area(inode_idx) else {
+            return Vec::new();
+        };
+        let mut bytes = vec![0u8; area.len()];
+        self.raw_inodes_cache
+            .pages()
+            .read_bytes(area.start, &mut bytes)
+            .unwrap();
+        bytes
+    }
+
+    /// Writes the area for the extended attributes after the extra fields of the raw inode.
+    ///
+    /// The length of `bytes` must be the same as the area read by `read_inode_xattr_area`.
+    pub fn write_inode_xattr_area(&self, inode_idx: u32, bytes: &[u8]) {
+        let Some(area) = self.inode_xattr_area(inode_idx) else {
+            return;
+        };
+        debug_assert_eq!(area.len(), bytes.len());
+        self.raw_inodes_cache
+            .pages()
+            .write_bytes(area.start, bytes)
+            .unwrap();
+        self.update_inode_checksum(inode_idx);
+    }
+
+    /// Returns the range of the area for the extended attributes in the raw inode cache.
+    fn inode_xattr_area(&self, inode_idx: u32) -> Option<Range<usize>> {
+        let inode_size = self.fs().inode_size();
+        if inode_size <= core::mem::size_of::<RawInode>() {
+            return None;
+        }
+
+        let offset = (inode_idx as usize) * inode_size;
+        let extra_isize = self
+            .raw_inodes_cache
+            .pages()
+            .read_val::<u16>(offset + INODE_EXTRA_ISIZE_OFFSET)
+            .unwrap() as usize;
+        let start = core::mem::size_of::<RawInode>() + extra_isize;
+        (start < inode_size).then(|| offset + start..offset + inode_size)
     }
 
-    /// Writes back the metadata of this group.
-    pub fn sync_metadata(&self) -> Result<()> {
+    /// Updates the checksum of the raw inode in the raw inode cache.
+    fn update_inode_checksum(&self, inode_idx: u32) {
+        let fs = self.fs();
+        let offset = (inode_idx as usize) * fs.inode_size();
+        let Some(csum_seed) = self.bg_impl.csum_seed else {
+            return;
+        };
+        let mut bytes = vec![0u8; fs.inode_size()];
+        self.raw_inodes_cache
+            .pages()
+            .read_bytes(offset, &mut bytes)
+            .unwrap();
+        let ino = inode_idx + self.idx as u32 * fs.inodes_per_group() + 1;
+        let (csum, has_hi) = calc_inode_checksum(csum_seed, ino, &bytes);
+        bytes[INODE_CSUM_LO_OFFSET..INODE_CSUM_LO_OFFSET + 2]
+            .copy_from_slice(&(csum as u16).to_le_bytes());
+        if has_hi {
+            bytes[INODE_CSUM_HI_OFFSET..INODE_CSUM_HI_OFFSET + 2]
+                .copy_from_slice(&((csum >> 16) as u16).to_le_bytes());
+        }
+        self.raw_inodes_cache
+            .pages()
+            .write_bytes(offset, &bytes)
+            .unwrap();
+    }
+
+    /// Writes back the metadata of this group into the `transaction`.
+    pub fn sync_metadata(
+        &self,
+        super_block: &SuperBlock,
+        transaction: &mut Transaction,
+    ) -> Result<()> {
         if !self.bg_impl.inner.read().metadata.is_dirty() {
             return Ok(());
         }
 
         let mut inner = self.bg_impl.inner.write();
-        let fs = self.fs();
+        // The bitmaps are written in full, so they are initialized from now on.
+        inner
+            .metadata
+            .descriptor
+            .flags
+            .remove(GroupFlags::INODE_UNINIT | GroupFlags::BLOCK_UNINIT);
+
         // Writes back the descriptor.
-        let raw_descriptor = RawGroupDescriptor::from(&inner.metadata.descriptor);
-        self.fs().sync_group_descriptor(self.idx, &raw_descriptor)?;
+        let desc_size = super_block.desc_size();
+        let mut raw_descriptor = RawGroupDescriptor::from(&inner.metadata.descriptor);
+        if let Some(csum_seed) = self.bg_impl.csum_seed {
+            let block_bitmap_csum = crc32c(csum_seed, inner.metadata.block_bitmap.as_bytes());
+            let inode_bitmap_csum = crc32c(csum_seed, inner.metadata.inode_bitmap.as_bytes());
+            raw_descriptor.block_bitmap_csum = block_bitmap_csum as u16;
+            raw_descriptor.inode_bitmap_csum = inode_bitmap_csum as u16;
+            if desc_size > RawGroupDescriptor::HI_OFFSET {
+                raw_descriptor.block_bitmap_csum_hi = (block_bitmap_csum >> 16) as u16;
+                raw_descriptor.inode_bitmap_csum_hi = (inode_bitmap_csum >> 16) as u16;
+            }
+        }
+        if super_block.has_group_desc_csum() {
+            raw_descriptor.checksum =
+                super_block.group_descriptor_checksum(self.idx, raw_descriptor.as_bytes());
+        }
+        self.fs()
+            .sync_group_descriptor(self.idx, &raw_descriptor.as_bytes()[..desc_size])?;
+
+        // The unused bits at the end of a bitmap block are set to 1.
+        let to_bitmap_block = |bitmap: &IdAlloc| -> Vec<u8> {
+            let mut block = vec![0xffu8; BLOCK_SIZE];
+            let bytes = bitmap.as_bytes();
+            block[..bytes.len()].copy_from_slice(bytes);
+            block
+        };
 
-        let mut bio_waiter = BioWaiter::new();
         // Writes back the inode bitmap.
         let inode_bitmap_bid = Bid::new(inner.metadata.descriptor.inode_bitmap_bid as u64);
-        bio_waiter.concat(fs.block_device().write_bytes_async(
+        transaction.write_bytes(
             inode_bitmap_bid.to_offset(),
-            inner.metadata.inode_bitmap.as_bytes(),
-        )?);
+            &to_bitmap_block(&inner.metadata.inode_bitmap),
+        );
 
         // Writes back the block bitmap.
         let block_bitmap_bid = Bid::new(inner.metadata.descriptor.block_bitmap_bid as u64);
-        bio_waiter.concat(fs.block_device().write_bytes_async(
+        transaction.write_bytes(
             block_bitmap_bid.to_offset(),
-            inner.metadata.block_bitmap.as_bytes(),
-        )?);
-
-        // Waits for the completion of all submitted bios.
-        bio_waiter.wait().ok_or_else(|| {
-            Error::with_message(Errno::EIO, "failed to sync metadata of block group")
-        })?;
+            &to_bitmap_block(&inner.metadata.block_bitmap),
+        );
 
         inner.metadata.clear_dirty();
         Ok(())
@@ -320,26 +518,25 @@ impl Debug for BlockGroup {
 impl PageCacheBackend for BlockGroupImpl {
     fn read_page_async(&self, idx: usize, frame: &CachePage) -> Result<BioWaiter> {
         let bid = self.inode_table_bid + idx as Ext2Bid;
+        let fs = self.fs.upgrade().unwrap();
+        if let Some(buf) = fs.uncommitted_block(bid) {
+            frame.write_bytes(0, &buf)?;
+            return Ok(BioWaiter::new());
+        }
+
         let bio_segment = BioSegment::new_from_segment(
             Segment::from(frame.clone()).into(),
             BioDirection::FromDevice,
         );
-        self.fs
-            .upgrade()
-            .unwrap()
-            .read_blocks_async(bid, bio_segment)
+        fs.read_blocks_async(bid, bio_segment)
     }
 
     fn write_page_async(&self, idx: usize, frame: &CachePage) -> Result<BioWaiter> {
         let bid = self.inode_table_bid + idx as Ext2Bid;
-        let bio_segment = BioSegment::new_from_segment(
-            Segment::from(frame.clone()).into(),
-            BioDirection::ToDevice,
-        );
         self.fs
             .upgrade()
             .unwrap()
-            .write_blocks_async(bid, bio_segment)
+            .write_metadata_block_async(bid, frame)
     }
 
     fn npages(&self) -> usize {
@@ -358,6 +555,7 @@ struct GroupMetadata {
     descriptor: GroupDescriptor,
     block_bitmap: IdAlloc,
     inode_bitmap: IdAlloc,
+    inodes_per_group: u32,
 }
 
 impl GroupMetadata {
@@ -371,6 +569,9 @@ impl GroupMetadata {
         if is_dir {
             self.inc_dirs();
         }
+        // The inodes after the last used one are not initialized in the inode table.
+        let unused = self.inodes_per_group - inode_idx as u32 - 1;
+        self.descriptor.itable_unused = self.descriptor.itable_unused.min(unused);
         Some(inode_idx as u32)
     }
 
@@ -467,26 +668,135 @@ struct GroupDescriptor {
     free_inodes_count: u16,
     /// Number of directories in group
     dirs_count: u16,
+    /// Block group flags
+    flags: GroupFlags,
+    /// Number of unused inodes at the end of the inode table
+    itable_unused: u32,
+    /// Snapshot exclusion bitmap block, which is unused but kept to be written back
+    exclude_bitmap_bid: u64,
 }
 
-impl From<RawGroupDescriptor> for GroupDescriptor {
-    fn from(desc: RawGroupDescriptor) -> Self {
-        Self {
+impl TryFrom<RawGroupDescriptor> for GroupDescriptor {
+    type Error = crate::error::Error;
+
+    fn try_from(desc: RawGroupDescriptor) -> Result<Self> {
+        // Block numbers are 32-bit in memory, and the counts in a group fit in 16 bits.
+        if desc.block_bitmap_hi != 0
+            || desc.inode_bitmap_hi != 0
+            || desc.inode_table_hi != 0
+            || desc.free_blocks_count_hi != 0
+            || desc.free_inodes_count_hi != 0
+            || desc.dirs_count_hi != 0
+        {
+            return_errno_with_message!(Errno::EINVAL, "unsupported group descriptor");
+        }
+
+        Ok(Self {
             block_bitmap_bid: desc.block_bitmap,
             inode_bitmap_bid: desc.inode_bitmap,
             inode_table_bid: desc.inode_table,
             free_blocks_count: desc.free_blocks_count,
             free_inodes_count: desc.free_inodes_count,
             dirs_count: desc.dirs_count,
+            flags: GroupFlags::from_bits_truncate(desc.flags),
+            itable_unused: (desc.itable_unused_hi as u32) << 16 | desc.itable_unused as u32,
+            exclude_bitmap_bid: (desc.exclude_bitmap_hi as u64) << 32 | desc.exclude_bitmap as u64,
+        })
+    }
+}
+
+bitflags! {
+    /// Block group flags, which are valid if the group descriptors have checksums.
+    struct GroupFlags: u16 {
+        /// Inode table and bitmap are not initialized
+        const INODE_UNINIT = 1 << 0;
+        /// Block bitmap is not initialized
+        const BLOCK_UNINIT = 1 << 1;
+        /// Inode table is zeroed
+        const INODE_ZEROED = 1 << 2;
+    }
+}
+
+/// Builds the block bitmap of a group with the `GroupFlags::BLOCK_UNINIT`.
+///
+/// Such a group contains no data blocks, only its own metadata.
+fn init_block_bitmap(
+    idx: usize,
+    descriptor: &GroupDescriptor,
+    super_block: &SuperBlock,
+) -> IdAlloc {
+    let blocks_per_group = super_block.blocks_per_group();
+    let group_start = super_block.first_data_block() + idx as u32 * blocks_per_group;
+    let group_len = blocks_per_group.min(super_block.total_blocks() - group_start);
+    let mut bitmap = IdAlloc::with_capacity(blocks_per_group as usize);
+    let mut mark_used = |range: Range<Ext2Bid>| {
+        for bid in range {
+            if (group_start..group_start + group_len).contains(&bid) {
+                bitmap.alloc_specific((bid - group_start) as usize);
+            }
         }
+    };
+
+    // The superblock and the group descriptor table
+    if idx == 0 || super_block.is_backup_group(idx) {
+        let gdt_blocks = (super_block.block_groups_count() as usize * super_block.desc_size())
+            .div_ceil(super_block.block_size()) as u32;
+        let len = 1 + gdt_blocks + super_block.reserved_gdt_blocks();
+        mark_used(group_start..group_start + len);
+    }
+
+    // The bitmaps and the inode table
+    mark_used(descriptor.block_bitmap_bid..descriptor.block_bitmap_bid + 1);
+    mark_used(descriptor.inode_bitmap_bid..descriptor.inode_bitmap_bid + 1);
+    let inode_table_blocks = (super_block.inodes_per_group() as usize * super_block.inode_size())
+        .div_ceil(super_block.block_size()) as u32;
+    mark_used(descriptor.inode_table_bid..descriptor.inode_table_bid + inode_table_blocks);
+
+    // The blocks beyond the end of the filesystem
+    for block_idx in group_len..blocks_per_group {
+        bitmap.alloc_specific(block_idx as usize);
     }
+    bitmap
 }
 
-const_assert!(core::mem::size_of::<RawGroupDescriptor>() == 32);
+/// The offset of the size of the extra fields in a raw inode.
+const INODE_EXTRA_ISIZE_OFFSET: usize = 0x80;
+/// The offset of the lower 16 bits of the checksum in a raw inode.
+const INODE_CSUM_LO_OFFSET: usize = 0x7c;
+/// The offset of the upper 16 bits of the checksum in a raw inode.
+const INODE_CSUM_HI_OFFSET: usize = 0x82;
+
+/// Calculates the checksum of the raw inode in `bytes`.
+///
+/// Returns the checksum and whether the raw inode has room for its upper 16 bits.
+fn calc_inode_checksum(csum_seed: u32, ino: u32, bytes: &[u8]) -> (u32, bool) {
+    let has_hi = bytes.len() > core::mem::size_of::<RawInode>() && {
+        let extra_isize = u16::from_le_bytes([
+            bytes[INODE_EXTRA_ISIZE_OFFSET],
+            bytes[INODE_EXTRA_ISIZE_OFFSET + 1],
+        ]) as usize;
+        INODE_EXTRA_ISIZE_OFFSET + extra_isize >= INODE_CSUM_HI_OFFSET + 2
+    };
+
+    let generation = RawInode::from_bytes(&bytes[..core::mem::size_of::<RawInode>()]).generation;
+    let crc = inode_csum_seed(csum_seed, ino, generation);
+    let crc = crc32c(crc, &bytes[..INODE_CSUM_LO_OFFSET]);
+    let crc = crc32c(crc, &[0u8; 2]);
+    if has_hi {
+        let crc = crc32c(crc, &bytes[INODE_CSUM_LO_OFFSET + 2..INODE_CSUM_HI_OFFSET]);
+        let crc = crc32c(crc, &[0u8; 2]);
+        (crc32c(crc, &bytes[INODE_CSUM_HI_OFFSET + 2..]), true)
+    } else {
+        (crc32c(crc, &bytes[INODE_CSUM_LO_OFFSET + 2..]), false)
+    }
+}
+
+const_assert!(core::mem::size_of::<RawGroupDescriptor>() == 64);
 
 /// The raw block group descriptor.
 ///
-/// The table starts on the first block following the superblock.
+/// The table starts on the first block following the superblock. Only the first 32 bytes
+/// are stored on disk if the 64-bit feature is not set.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod)]
 pub(super) struct RawGroupDescriptor {
@@ -496,8 +806,31 @@ pub(super) struct RawGroupDescriptor {
     pub free_blocks_count: u16,
     pub free_inodes_count: u16,
     pub dirs_count: u16,
-    pad: u16,
-    reserved: [u32; 3],
+    pub flags: u16,
+    pub exclude_bitmap: u32,
+    pub block_bitmap_csum: u16,
+    pub inode_bitmap_csum: u16,
+    pub itable_unused: u16,
+    pub checksum: u16,
+    //
+    // The following fields are valid if the 64-bit feature is set.
+    //
+    pub block_bitmap_hi: u32,
+    pub inode_bitmap_hi: u32,
+    pub inode_table_hi: u32,
+    pub free_blocks_count_hi: u16,
+    pub free_inodes_count_hi: u16,
+    pub dirs_count_hi: u16,
+    pub itable_unused_hi: u16,
+    pub exclude_bitmap_hi: u32,
+    pub block_bitmap_csum_hi: u16,
+    pub inode_bitmap_csum_hi: u16,
+    reserved: u32,
+}
+
+impl RawGroupDescriptor {
+    /// The offset of the fields that are valid if the 64-bit feature is set.
+    const HI_OFFSET: usize = 32;
 }
 
 impl From<&GroupDescriptor> for RawGroupDescriptor {
@@ -509,8 +842,12 @@ impl From<&GroupDescriptor> for RawGroupDescriptor {
             free_blocks_count: desc.free_blocks_count,
             free_inodes_count: desc.free_inodes_count,
             dirs_count: desc.dirs_count,
-            pad: 0u16,
-            reserved: [0u32; 3],
+            flags: desc.flags.bits(),
+            exclude_bitmap: desc.exclude_bitmap_bid as u32,
+            itable_unused: desc.itable_unused as u16,
+            itable_unused_hi: (desc.itable_unused >> 16) as u16,
+            exclude_bitmap_hi: (desc.exclude_bitmap_bid >> 32) as u32,
+            ..Self::new_zeroed()
         }
     }
 }
@@ -2,7 +2,7 @@
 
 #![allow(unused_variables)]
 
-use super::{inode::MAX_FNAME_LEN, prelude::*};
+use super::{inode::MAX_FNAME_LEN, prelude::*, utils::crc32c};
 
 /// The data structure in a directory's data block. It is stored in a linked list.
 ///
@@ -100,6 +100,69 @@ pub(super) struct DirEntryHeader {
     inode_type: u8,
 }
 
+/// The fake entry at the end of each directory block, which holds the checksum
+/// of the block if the metadata checksums are enabled.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub(super) struct DirEntryTail {
+    /// Inode number, which is always zero
+    ino: u32,
+    /// Directory entry length, which is always 12
+    record_len: u16,
+    /// Name length, which is always zero
+    name_len: u8,
+    /// Type indicator, which is always 0xDE
+    inode_type: u8,
+    /// Checksum of the block
+    checksum: u32,
+}
+
+impl DirEntryTail {
+    /// The length of the tail.
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    const INODE_TYPE: u8 = 0xDE;
+
+    /// Constructs a tail without the checksum.
+    pub fn new() -> Self {
+        Self {
+            ino: 0,
+            record_len: Self::LEN as u16,
+            name_len: 0,
+            inode_type: Self::INODE_TYPE,
+            checksum: 0,
+        }
+    }
+
+    /// Returns whether the header is the header of a tail.
+    fn is_tail_header(header: &DirEntryHeader) -> bool {
+        header.ino == 0
+            && header.record_len as usize == Self::LEN
+            && header.name_len == 0
+            && header.inode_type == Self::INODE_TYPE
+    }
+
+    /// Updates the checksum in the tail of the directory block held by the `frame`.
+    ///
+    /// The block is left untouched if it has no tail.
+    pub fn update_checksum(frame: &CachePage, csum_seed: u32) -> Result<()> {
+        let mut block = vec![0u8; BLOCK_SIZE];
+        frame.read_bytes(0, &mut block)?;
+
+        let tail_offset = BLOCK_SIZE - Self::LEN;
+        let mut tail = Self::from_bytes(&block[tail_offset..]);
+        if !Self::is_tail_header(&DirEntryHeader::from_bytes(
+            &block[tail_offset..tail_offset + DirEntry::header_len()],
+        )) {
+            return Ok(());
+        }
+
+        tail.checksum = crc32c(csum_seed, &block[..tail_offset]);
+        frame.write_val(tail_offset, &tail)?;
+        Ok(())
+    }
+}
+
 /// The type indicator in the `DirEntry`.
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, TryFromInt)]
@@ -167,22 +230,30 @@ impl<'a> DirEntryReader<'a> {
         }
     }
 
-    /// Returns an iterator for iterating `DirEntryItem`s.
-    pub fn iter(&self) -> DirEntryIter<'a> {
+    /// Returns an iterator for iterating all the records, including the unused ones.
+    pub(super) fn records(&self) -> DirEntryIter<'a> {
         DirEntryIter {
             page_cache: self.page_cache,
             offset: self.from_offset,
         }
     }
 
-    /// Returns an iterator for iterating `DirEntry`s.
-    pub fn iter_entries(&'a mut self) -> impl Iterator<Item = DirEntry> + 'a {
+    /// Returns an iterator for iterating `DirEntryItem`s in use.
+    pub fn iter(&self) -> impl Iterator<Item = DirEntryItem> + 'a {
+        self.records().filter(|entry_item| entry_item.ino() != 0)
+    }
+
+    /// Returns an iterator for iterating `DirEntry`s with their offsets.
+    pub fn iter_entries(&'a mut self) -> impl Iterator<Item = (usize, DirEntry)> + 'a {
         let iter = self.iter();
         iter.filter_map(|entry_item| match self.read_name(&entry_item) {
-            Ok(name_buf) => Some(DirEntry {
-                header: entry_item.header,
-                name: CStr256::from(name_buf),
-            }),
+            Ok(name_buf) => Some((
+                entry_item.offset,
+                DirEntry {
+                    header: entry_item.header,
+                    name: CStr256::from(name_buf),
+                },
+            )),
             Err(_) => None,
         })
     }
@@ -244,6 +315,11 @@ impl DirEntryIter<'_> {
 
         let header = self.read_header()?;
         let record_len = header.record_len as usize;
+        if record_len < DirEntry::header_len()
+            || (self.offset % BLOCK_SIZE) + record_len > BLOCK_SIZE
+        {
+            return_errno_with_message!(Errno::EUCLEAN, "bad directory entry");
+        }
         let item = DirEntryItem {
             header,
             offset: self.offset,
@@ -259,9 +335,6 @@ impl DirEntryIter<'_> {
             .page_cache
             .pages()
             .read_val::<DirEntryHeader>(self.offset)?;
-        if header.ino == 0 {
-            return_errno!(Errno::ENOENT);
-        }
         Ok(header)
     }
 }
@@ -314,6 +387,11 @@ impl DirEntryItem {
         InodeType::from(DirEntryFileType::try_from(self.header.inode_type).unwrap())
     }
 
+    /// Returns whether the entry is the tail of a directory block.
+    pub fn is_tail(&self) -> bool {
+        DirEntryTail::is_tail_header(&self.header)
+    }
+
     /// Returns the distance to the next entry.
     pub fn record_len(&self) -> usize {
         self.header.record_len as _
@@ -321,7 +399,7 @@ impl DirEntryItem {
 
     /// Modifies the distance to the next entry.
     pub fn set_record_len(&mut self, record_len: usize) {
-        debug_assert!(record_len >= self.actual_len());
+        debug_assert!(self.ino() == 0 || record_len >= self.actual_len());
         self.header.record_len = record_len as _;
     }
 
@@ -348,15 +426,20 @@ impl DirEntryItem {
 pub struct DirEntryWriter<'a> {
     page_cache: &'a PageCache,
     offset: usize,
+    /// Whether each block ends with a `DirEntryTail`.
+    has_tail: bool,
     name_buf: Option<[u8; MAX_FNAME_LEN]>,
 }
 
 impl<'a> DirEntryWriter<'a> {
     /// Constructs a writer with the given page cache and offset.
-    pub(super) fn new(page_cache: &'a PageCache, from_offset: usize) -> Self {
+    ///
+    /// If `has_tail` is true, a `DirEntryTail` is reserved at the end of the new blocks.
+    pub(super) fn new(page_cache: &'a PageCache, from_offset: usize, has_tail: bool) -> Self {
         Self {
             page_cache,
             offset: from_offset,
+            has_tail,
             name_buf: None,
         }
     }
@@ -384,23 +467,46 @@ impl<'a> DirEntryWriter<'a> {
 
     /// Appends a new `DirEntry` starting from the current offset.
     ///
-    /// If there is a gap between existing entries, inserts the new entry into the gap；
+    /// If there is an unused record or a gap between existing entries, inserts the new
+    /// entry into it；
     /// If there is no available space, expands the size and appends the new entry at the end.
     pub fn append_entry(&mut self, mut new_entry: DirEntry) -> Result<()> {
         let Some(mut entry_item) = DirEntryReader::new(self.page_cache, self.offset)
-            .iter()
-            .find(|entry| entry.gap_len() >= new_entry.record_len())
+            .records()
+            .find(|entry| {
+                if entry.ino() == 0 {
+                    !entry.is_tail() && entry.record_len() >= new_entry.record_len()
+                } else {
+                    entry.gap_len() >= new_entry.record_len()
+                }
+            })
         else {
             // Resize and append it at the new block.
             let old_size = self.page_cache.pages().size();
             let new_size = old_size + BLOCK_SIZE;
             self.page_cache.resize(new_size)?;
-            new_entry.set_record_len(BLOCK_SIZE);
             self.offset = old_size;
-            self.write_entry(&new_entry)?;
+            if self.has_tail {
+                new_entry.set_record_len(BLOCK_SIZE - DirEntryTail::LEN);
+                self.write_entry(&new_entry)?;
+                self.page_cache
+                    .pages()
+                    .write_val(self.offset, &DirEntryTail::new())?;
+            } else {
+                new_entry.set_record_len(BLOCK_SIZE);
+                self.write_entry(&new_entry)?;
+            }
             return Ok(());
         };
 
+        if entry_item.ino() == 0 {
+            // Reuse the unused record.
+            new_entry.set_record_len(entry_item.record_len());
+            self.offset = entry_item.offset;
+            self.write_entry(&new_entry)?;
+            return Ok(());
+        }
+
         // Write in the gap between existing entries.
         new_entry.set_record_len(entry_item.gap_len());
         entry_item.set_record_len(entry_item.actual_len());
@@ -411,41 +517,50 @@ impl<'a> DirEntryWriter<'a> {
     }
 
     /// Removes and returns an existing `DirEntry` indicated by `name`.
+    ///
+    /// The removed entry is merged into the previous entry in the same block, or marked
+    /// as unused if it is the first one in the block. If the last block becomes empty,
+    /// the size is shrunk.
     pub fn remove_entry(&mut self, name: &str) -> Result<DirEntry> {
-        let self_entry_record_len = DirEntry::self_entry(0).record_len();
-        let reader = DirEntryReader::new(self.page_cache, 0).iter();
-        let next_reader = DirEntryReader::new(self.page_cache, self_entry_record_len).iter();
-        let Some((mut pre_entry_item, entry_item)) =
-            reader.zip(next_reader).find(|(_, entry_item)| {
-                entry_item.name_len() == name.len()
-                    && self.read_name(entry_item).unwrap() == name.as_bytes()
-            })
-        else {
-            return_errno!(Errno::ENOENT);
-        };
+        let mut entry_item = DirEntryReader::new(self.page_cache, 0)
+            .find_entry_item(name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        let removed_entry = entry_item.to_entry_with_name(name);
+
+        let block_offset = entry_item.offset.align_down(BLOCK_SIZE);
+        let pre_entry_item = DirEntryReader::new(self.page_cache, block_offset)
+            .records()
+            .take_while(|item| item.offset < entry_item.offset)
+            .last();
+        match pre_entry_item {
+            Some(mut pre_entry_item) => {
+                // Update the previous entry.
+                pre_entry_item
+                    .set_record_len(pre_entry_item.record_len() + entry_item.record_len());
+                self.offset = pre_entry_item.offset;
+                self.write_header_only(&pre_entry_item.header)?;
+            }
+            None => {
+                // Mark the first entry in the block as unused.
+                entry_item.set_ino(0);
+                self.offset = entry_item.offset;
+                self.write_header_only(&entry_item.header)?;
+            }
+        }
 
-        let pre_offset = pre_entry_item.offset;
-        let offset = entry_item.offset;
-        if Bid::from_offset(pre_offset) != Bid::from_offset(offset)
-            && DirEntryReader::new(self.page_cache, entry_item.offset)
-                .iter()
-                .next()
-                .is_none()
-        {
-            // Shrink the size.
-            let new_size = pre_offset.align_up(BLOCK_SIZE);
-            self.page_cache.resize(new_size)?;
-            pre_entry_item.set_record_len(new_size - pre_offset);
-            self.offset = pre_offset;
-            self.write_header_only(&pre_entry_item.header)?;
-        } else {
-            // Update the previous entry.
-            pre_entry_item.set_record_len(pre_entry_item.record_len() + entry_item.record_len());
-            self.offset = pre_offset;
-            self.write_header_only(&pre_entry_item.header)?;
+        // Shrink the size if the last block is empty.
+        let size = self.page_cache.pages().size();
+        let is_last_block_empty = block_offset > 0
+            && block_offset + BLOCK_SIZE == size
+            && DirEntryReader::new(self.page_cache, block_offset)
+                .records()
+                .take_while(|item| item.offset < size)
+                .all(|item| item.ino() == 0);
+        if is_last_block_empty {
+            self.page_cache.resize(block_offset)?;
         }
 
-        Ok(entry_item.to_entry_with_name(name))
+        Ok(removed_entry)
     }
 
     /// Renames the `DirEntry` from `old_name` to the `new_name` from the current offset.
@@ -0,0 +1,513 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The extent trees of Ext4.
+//!
+//! An extent maps a range of consecutive file blocks to a range of consecutive
+//! device blocks. The extents of an inode are stored in a B+tree, whose root node
+//! resides in the block pointers of the inode, and whose other nodes occupy one
+//! block each.
+//!
+//! The tree is loaded into memory entirely when the inode is loaded. Every time
+//! the mapping changes, the tree is rebuilt and written back as a whole when the
+//! inode is synced.
+
+use super::{
+    block_ptr::{BlockPtrs, Ext2Bid},
+    fs::Ext2,
+    journal::Transaction,
+    prelude::*,
+    utils::crc32c,
+};
+
+/// The magic number of the extent header.
+const EXTENT_MAGIC: u16 = 0xf30a;
+
+/// The maximum length of an initialized extent.
+const MAX_INIT_LEN: Ext2Bid = 1 << 15;
+
+/// The maximum length of an unwritten extent.
+const MAX_UNWRITTEN_LEN: Ext2Bid = (1 << 15) - 1;
+
+/// The maximum depth of the tree.
+const MAX_DEPTH: u16 = 5;
+
+const HEADER_SIZE: usize = core::mem::size_of::<RawExtentHeader>();
+const ENTRY_SIZE: usize = core::mem::size_of::<RawExtent>();
+const TAIL_SIZE: usize = core::mem::size_of::<u32>();
+
+/// The maximum number of entries in the root node.
+const ROOT_ENTRIES: usize = (core::mem::size_of::<BlockPtrs>() - HEADER_SIZE) / ENTRY_SIZE;
+
+/// The maximum number of entries in the other nodes.
+const NODE_ENTRIES: usize = (BLOCK_SIZE - HEADER_SIZE - TAIL_SIZE) / ENTRY_SIZE;
+
+/// The in-memory extent tree of an inode.
+#[derive(Debug)]
+pub(super) struct ExtentTree {
+    /// The extents indexed by their first file blocks.
+    extents: BTreeMap<Ext2Bid, Extent>,
+    /// The device blocks occupied by the nodes other than the root.
+    node_bids: Vec<Ext2Bid>,
+    /// The seed of the checksums of the nodes, if they are enabled.
+    csum_seed: Option<u32>,
+    is_dirty: bool,
+}
+
+/// A range of consecutive file blocks mapped to consecutive device blocks.
+#[derive(Clone, Copy, Debug)]
+struct Extent {
+    device_bid: Ext2Bid,
+    len: Ext2Bid,
+    /// Whether the blocks are allocated but not written, which are read as zeros.
+    is_unwritten: bool,
+}
+
+impl Extent {
+    fn max_len(&self) -> Ext2Bid {
+        if self.is_unwritten {
+            MAX_UNWRITTEN_LEN
+        } else {
+            MAX_INIT_LEN
+        }
+    }
+}
+
+/// The mapping of a range of file blocks.
+#[derive(Clone, Debug)]
+pub(super) enum BlockMapping {
+    /// The blocks are mapped to the `device_range`.
+    Mapped {
+        device_range: Range<Ext2Bid>,
+        is_unwritten: bool,
+    },
+    /// The given number of blocks are not mapped.
+    Hole(Ext2Bid),
+}
+
+impl BlockMapping {
+    /// Returns the number of blocks in the mapping.
+    pub fn len(&self) -> Ext2Bid {
+        match self {
+            Self::Mapped { device_range, .. } => device_range.len() as Ext2Bid,
+            Self::Hole(len) => *len,
+        }
+    }
+}
+
+impl ExtentTree {
+    /// Returns the block pointers that hold the root of an empty tree.
+    pub fn empty_root() -> BlockPtrs {
+        let mut root = BlockPtrs::default();
+        let header = RawExtentHeader {
+            magic: EXTENT_MAGIC,
+            entries: 0,
+            max: ROOT_ENTRIES as u16,
+            depth: 0,
+            generation: 0,
+        };
+        root.as_bytes_mut()[..HEADER_SIZE].copy_from_slice(header.as_bytes());
+        root
+    }
+
+    /// Loads the tree whose root is held in the block pointers.
+    ///
+    /// The other nodes are read with `read_block`. The checksums of the nodes
+    /// are verified if `csum_seed` is provided.
+    pub fn load(
+        root: &BlockPtrs,
+        read_block: &dyn Fn(Ext2Bid, &mut [u8]) -> Result<()>,
+        csum_seed: Option<u32>,
+    ) -> Result<Self> {
+        let mut tree = Self {
+            extents: BTreeMap::new(),
+            node_bids: Vec::new(),
+            csum_seed,
+            is_dirty: false,
+        };
+
+        let root = root.as_bytes();
+        let depth = RawExtentHeader::from_bytes(&root[..HEADER_SIZE]).depth;
+        if depth > MAX_DEPTH {
+            return_errno_with_message!(Errno::EUCLEAN, "the extent tree is too deep");
+        }
+        tree.load_node(root, depth, ROOT_ENTRIES, read_block)?;
+        Ok(tree)
+    }
+
+    fn load_node(
+        &mut self,
+        node: &[u8],
+        depth: u16,
+        max_entries: usize,
+        read_block: &dyn Fn(Ext2Bid, &mut [u8]) -> Result<()>,
+    ) -> Result<()> {
+        let header = RawExtentHeader::from_bytes(&node[..HEADER_SIZE]);
+        if header.magic != EXTENT_MAGIC
+            || header.depth != depth
+            || header.entries > header.max
+            || header.max as usize > max_entries
+        {
+            return_errno_with_message!(Errno::EUCLEAN, "bad extent header");
+        }
+
+        for idx in 0..header.entries as usize {
+            let offset = HEADER_SIZE + idx * ENTRY_SIZE;
+            let entry = &node[offset..offset + ENTRY_SIZE];
+            if depth == 0 {
+                self.load_extent(RawExtent::from_bytes(entry))?;
+                continue;
+            }
+
+            let index = RawExtentIndex::from_bytes(entry);
+            if index.leaf_hi != 0 {
+                return_errno_with_message!(Errno::EINVAL, "unsupported extent node");
+            }
+            let mut block = vec![0u8; BLOCK_SIZE];
+            read_block(index.leaf_lo, &mut block)?;
+            if let Some(csum_seed) = self.csum_seed {
+                let max = RawExtentHeader::from_bytes(&block[..HEADER_SIZE]).max as usize;
+                if max > NODE_ENTRIES {
+                    return_errno_with_message!(Errno::EUCLEAN, "bad extent header");
+                }
+                let tail_offset = HEADER_SIZE + max * ENTRY_SIZE;
+                let csum = u32::from_le_bytes(
+                    block[tail_offset..tail_offset + TAIL_SIZE]
+                        .try_into()
+                        .unwrap(),
+                );
+                if csum != crc32c(csum_seed, &block[..tail_offset]) {
+                    return_errno_with_message!(Errno::EBADMSG, "bad extent node checksum");
+                }
+            }
+            self.node_bids.push(index.leaf_lo);
+            self.load_node(&block, depth - 1, NODE_ENTRIES, read_block)?;
+        }
+        Ok(())
+    }
+
+    fn load_extent(&mut self, raw_extent: RawExtent) -> Result<()> {
+        if raw_extent.start_hi != 0 {
+            return_errno_with_message!(Errno::EINVAL, "unsupported extent");
+        }
+        let (len, is_unwritten) = if raw_extent.len as Ext2Bid > MAX_INIT_LEN {
+            (raw_extent.len as Ext2Bid - MAX_INIT_LEN, true)
+        } else {
+            (raw_extent.len as Ext2Bid, false)
+        };
+        let start = raw_extent.block;
+        let overlaps_prev = self
+            .extents
+            .range(..=start)
+            .next_back()
+            .is_some_and(|(prev_start, prev)| prev_start + prev.len > start);
+        if len == 0 || start.checked_add(len).is_none() || overlaps_prev {
+            return_errno_with_message!(Errno::EUCLEAN, "bad extent");
+        }
+
+        self.extents.insert(
+            start,
+            Extent {
+                device_bid: raw_extent.start_lo,
+                len,
+                is_unwritten,
+            },
+        );
+        Ok(())
+    }
+
+    /// Maps at most `max_len` file blocks starting from `bid`.
+    pub fn map(&self, bid: Ext2Bid, max_len: Ext2Bid) -> BlockMapping {
+        if let Some((&start, extent)) = self.extents.range(..=bid).next_back() {
+            if bid < start + extent.len {
+                let device_start = extent.device_bid + (bid - start);
+                let len = max_len.min(start + extent.len - bid);
+                return BlockMapping::Mapped {
+                    device_range: device_start..device_start + len,
+                    is_unwritten: extent.is_unwritten,
+                };
+            }
+        }
+
+        let len = match self.extents.range(bid..).next() {
+            Some((&next_start, _)) => max_len.min(next_start - bid),
+            None => max_len,
+        };
+        BlockMapping::Hole(len)
+    }
+
+    /// Returns the device block that is preferred to be mapped to `bid`,
+    /// which follows the mapping of the previous blocks.
+    pub fn goal(&self, bid: Ext2Bid) -> Option<Ext2Bid> {
+        self.extents
+            .range(..bid)
+            .next_back()
+            .map(|(&start, extent)| extent.device_bid + (bid - start))
+    }
+
+    /// Maps the file blocks starting from `bid` to the `device_range`.
+    ///
+    /// The file blocks must not be mapped before.
+    pub fn insert(&mut self, bid: Ext2Bid, device_range: Range<Ext2Bid>) {
+        debug_assert!(matches!(
+            self.map(bid, device_range.len() as Ext2Bid),
+            BlockMapping::Hole(len) if len as usize == device_range.len()
+        ));
+
+        let mut bid = bid;
+        let mut device_range = device_range;
+        while !device_range.is_empty() {
+            let len = (device_range.len() as Ext2Bid).min(MAX_INIT_LEN);
+            self.extents.insert(
+                bid,
+                Extent {
+                    device_bid: device_range.start,
+                    len,
+                    is_unwritten: false,
+                },
+            );
+            self.try_merge_with_prev(bid);
+            bid += len;
+            device_range.start += len;
+        }
+        self.is_dirty = true;
+    }
+
+    /// Marks the file blocks in `range` as written.
+    ///
+    /// The blocks must be within one unwritten extent.
+    pub fn mark_written(&mut self, range: Range<Ext2Bid>) {
+        let (&start, &extent) = self.extents.range(..=range.start).next_back().unwrap();
+        debug_assert!(extent.is_unwritten && range.end <= start + extent.len);
+
+        let end = start + extent.len;
+        if start < range.start {
+            self.extents.insert(
+                start,
+                Extent {
+                    len: range.start - start,
+                    ..extent
+                },
+            );
+        }
+        if range.end < end {
+            self.extents.insert(
+                range.end,
+                Extent {
+                    device_bid: extent.device_bid + (range.end - start),
+                    len: end - range.end,
+                    is_unwritten: true,
+                },
+            );
+        }
+        self.extents.insert(
+            range.start,
+            Extent {
+                device_bid: extent.device_bid + (range.start - start),
+                len: range.len() as Ext2Bid,
+                is_unwritten: false,
+            },
+        );
+        self.try_merge_with_prev(range.start);
+        self.is_dirty = true;
+    }
+
+    /// Merges the extent starting from `bid` into the previous extent if possible.
+    fn try_merge_with_prev(&mut self, bid: Ext2Bid) {
+        let extent = self.extents[&bid];
+        let Some((&prev_start, prev)) = self.extents.range_mut(..bid).next_back() else {
+            return;
+        };
+        if prev_start + prev.len != bid
+            || prev.device_bid + prev.len != extent.device_bid
+            || prev.is_unwritten != extent.is_unwritten
+            || prev.len + extent.len > prev.max_len()
+        {
+            return;
+        }
+        prev.len += extent.len;
+        self.extents.remove(&bid);
+    }
+
+    /// Unmaps the file blocks starting from `bid`.
+    ///
+    /// Returns the device blocks that are no longer mapped, which should be freed.
+    pub fn truncate(&mut self, bid: Ext2Bid) -> Vec<Range<Ext2Bid>> {
+        let mut freed_ranges: Vec<Range<Ext2Bid>> = self
+            .extents
+            .split_off(&bid)
+            .into_values()
+            .map(|extent| extent.device_bid..extent.device_bid + extent.len)
+            .collect();
+
+        if let Some((&start, extent)) = self.extents.iter_mut().next_back() {
+            if start + extent.len > bid {
+                let new_len = bid - start;
+                freed_ranges.push(extent.device_bid + new_len..extent.device_bid + extent.len);
+                extent.len = new_len;
+            }
+        }
+
+        if !freed_ranges.is_empty() {
+            self.is_dirty = true;
+        }
+        freed_ranges
+    }
+
+    /// Returns the number of device blocks occupied, including the nodes.
+    pub fn blocks_count(&self) -> Ext2Bid {
+        let mapped_count: Ext2Bid = self.extents.values().map(|extent| extent.len).sum();
+        mapped_count + self.node_bids.len() as Ext2Bid
+    }
+
+    /// Returns whether the tree has been modified since it was last synced.
+    pub fn is_dirty(&self) -> bool {
+        self.is_dirty
+    }
+
+    /// Writes back the tree.
+    ///
+    /// The root is written into the block pointers, and the other nodes are written
+    /// to the device, which are allocated from the `block_group_idx` group first.
+    pub fn sync(&mut self, root: &mut BlockPtrs, fs: &Ext2, block_group_idx: usize) -> Result<()> {
+        // Calculates the number of nodes other than the root.
+        let nodes_count = {
+            let mut nodes_count = 0;
+            let mut level_len = self.extents.len();
+            while level_len > ROOT_ENTRIES {
+                level_len = level_len.div_ceil(NODE_ENTRIES);
+                nodes_count += level_len;
+            }
+            nodes_count
+        };
+
+        // Reuses the current nodes, and allocates or frees the difference.
+        while self.node_bids.len() > nodes_count {
+            let bid = self.node_bids.pop().unwrap();
+            fs.free_blocks(bid..bid + 1)?;
+        }
+        while self.node_bids.len() < nodes_count {
+            let count = (nodes_count - self.node_bids.len()) as Ext2Bid;
+            let device_range = fs
+                .alloc_blocks(block_group_idx, count)
+                .ok_or_else(|| Error::new(Errno::ENOSPC))?;
+            self.node_bids.extend(device_range);
+        }
+
+        // Builds the tree from the leaves up to the root.
+        let mut level: Vec<(Ext2Bid, [u8; ENTRY_SIZE])> = self
+            .extents
+            .iter()
+            .map(|(&start, extent)| {
+                let raw_extent = RawExtent {
+                    block: start,
+                    len: if extent.is_unwritten {
+                        (extent.len + MAX_INIT_LEN) as u16
+                    } else {
+                        extent.len as u16
+                    },
+                    start_hi: 0,
+                    start_lo: extent.device_bid,
+                };
+                let mut entry = [0u8; ENTRY_SIZE];
+                entry.copy_from_slice(raw_extent.as_bytes());
+                (start, entry)
+            })
+            .collect();
+        let mut depth = 0;
+        let mut node_bids = self.node_bids.iter();
+        let mut transaction = Transaction::new();
+        while level.len() > ROOT_ENTRIES {
+            let mut upper_level = Vec::with_capacity(level.len().div_ceil(NODE_ENTRIES));
+            for entries in level.chunks(NODE_ENTRIES) {
+                let bid = *node_bids.next().unwrap();
+                let mut block = vec![0u8; BLOCK_SIZE];
+                write_node(&mut block, entries, depth, NODE_ENTRIES);
+                if let Some(csum_seed) = self.csum_seed {
+                    let tail_offset = HEADER_SIZE + NODE_ENTRIES * ENTRY_SIZE;
+                    let csum = crc32c(csum_seed, &block[..tail_offset]);
+                    block[tail_offset..tail_offset + TAIL_SIZE]
+                        .copy_from_slice(&csum.to_le_bytes());
+                }
+                transaction.write_bytes(bid as usize * BLOCK_SIZE, &block);
+
+                let first_bid = entries[0].0;
+                let raw_index = RawExtentIndex {
+                    block: first_bid,
+                    leaf_lo: bid,
+                    leaf_hi: 0,
+                    unused: 0,
+                };
+                let mut entry = [0u8; ENTRY_SIZE];
+                entry.copy_from_slice(raw_index.as_bytes());
+                upper_level.push((first_bid, entry));
+            }
+            level = upper_level;
+            depth += 1;
+        }
+        fs.write_metadata(transaction)?;
+
+        *root = BlockPtrs::default();
+        write_node(root.as_bytes_mut(), &level, depth, ROOT_ENTRIES);
+        self.is_dirty = false;
+        Ok(())
+    }
+}
+
+/// Writes a node with the `entries` into the `buf`.
+fn write_node(buf: &mut [u8], entries: &[(Ext2Bid, [u8; ENTRY_SIZE])], depth: u16, max: usize) {
+    let header = RawExtentHeader {
+        magic: EXTENT_MAGIC,
+        entries: entries.len() as u16,
+        max: max as u16,
+        depth,
+        generation: 0,
+    };
+    buf[..HEADER_SIZE].copy_from_slice(header.as_bytes());
+    for (idx, (_, entry)) in entries.iter().enumerate() {
+        let offset = HEADER_SIZE + idx * ENTRY_SIZE;
+        buf[offset..offset + ENTRY_SIZE].copy_from_slice(entry);
+    }
+}
+
+/// The header at the beginning of each node.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawExtentHeader {
+    magic: u16,
+    /// Number of valid entries.
+    entries: u16,
+    /// Maximum number of entries that the node can hold.
+    max: u16,
+    /// Depth of the node, where the leaves are of depth 0.
+    depth: u16,
+    generation: u32,
+}
+
+/// The entry of a leaf node.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawExtent {
+    /// First file block.
+    block: u32,
+    /// Number of blocks, which is larger than 32768 if the extent is unwritten.
+    len: u16,
+    /// Upper 16 bits of the first device block.
+    start_hi: u16,
+    /// Lower 32 bits of the first device block.
+    start_lo: u32,
+}
+
+/// The entry of an internal node.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawExtentIndex {
+    /// First file block that the child covers.
+    block: u32,
+    /// Lower 32 bits of the block of the child.
+    leaf_lo: u32,
+    /// Upper 16 bits of the block of the child.
+    leaf_hi: u16,
+    unused: u16,
+}
+
+const_assert!(core::mem::size_of::<RawExtentIndex>() == ENTRY_SIZE);
@@ -3,11 +3,12 @@
 #![allow(dead_code)]
 
 use super::{
-    block_group::{BlockGroup, RawGroupDescriptor},
+    block_group::BlockGroup,
     block_ptr::Ext2Bid,
     inode::{FilePerm, Inode, InodeDesc, RawInode},
+    journal::{Journal, Transaction},
     prelude::*,
-    super_block::{RawSuperBlock, SuperBlock, SUPER_BLOCK_OFFSET},
+    super_block::{FeatureInCompatSet, RawSuperBlock, SuperBlock, SUPER_BLOCK_OFFSET},
 };
 
 /// The root inode number.
@@ -23,7 +24,15 @@ pub struct Ext2 {
     blocks_per_group: Ext2Bid,
     inode_size: usize,
     block_size: usize,
+    csum_seed: Option<u32>,
     group_descriptors_segment: USegment,
+    journal: Option<Journal>,
+    /// The metadata writes since the last commit, if the filesystem has a
+    /// journal.
+    ///
+    /// They are committed together at the next sync, so that the metadata
+    /// updated by an operation reach the device atomically.
+    running_transaction: Mutex<Transaction>,
     self_ref: Weak<Self>,
 }
 
@@ -32,20 +41,39 @@ impl Ext2 {
     pub fn open(block_device: Arc<dyn BlockDevice>) -> Result<Arc<Self>> {
         // Load the superblock
         // TODO: if the main superblock is corrupted, should we load the backup?
-        let super_block = {
+        let load_super_block = || -> Result<SuperBlock> {
             let raw_super_block = block_device.read_val::<RawSuperBlock>(SUPER_BLOCK_OFFSET)?;
-            SuperBlock::try_from(raw_super_block)?
+            SuperBlock::try_from(raw_super_block)
         };
+        let mut super_block = load_super_block()?;
         assert_eq!(
             super_block.block_size(),
             BLOCK_SIZE,
             "currently only support 4096-byte block size"
         );
 
+        // Replay the journal before loading the other metadata, which may be
+        // updated by the replay.
+        let journal = if super_block.has_journal() {
+            let journal = Journal::open(block_device.clone(), &super_block)?;
+            journal.recover(super_block.needs_recovery())?;
+            if super_block.needs_recovery() {
+                write_needs_recovery(block_device.as_ref(), false)?;
+                super_block = load_super_block()?;
+            }
+
+            // The flag is set in the superblocks that are committed to the
+            // journal, so that they are replayed if the system crashes before
+            // they are checkpointed.
+            super_block.set_needs_recovery(true);
+            Some(journal)
+        } else {
+            None
+        };
+
         let group_descriptors_segment: USegment = {
-            let npages = ((super_block.block_groups_count() as usize)
-                * core::mem::size_of::<RawGroupDescriptor>())
-            .div_ceil(BLOCK_SIZE);
+            let npages = ((super_block.block_groups_count() as usize) * super_block.desc_size())
+                .div_ceil(BLOCK_SIZE);
             let segment = FrameAllocOptions::new()
                 .zeroed(false)
                 .alloc_segment(npages)?;
@@ -85,6 +113,9 @@ impl Ext2 {
             blocks_per_group: super_block.blocks_per_group(),
             inode_size: super_block.inode_size(),
             block_size: super_block.block_size(),
+            csum_seed: super_block
+                .has_metadata_csum()
+                .then(|| super_block.csum_seed()),
             block_groups: load_block_groups(
                 weak_ref.clone(),
                 block_device.as_ref(),
@@ -94,6 +125,8 @@ impl Ext2 {
             block_device,
             super_block: RwMutex::new(Dirty::new(super_block)),
             group_descriptors_segment,
+            journal,
+            running_transaction: Mutex::new(Transaction::new()),
             self_ref: weak_ref.clone(),
         });
         Ok(ext2)
@@ -124,6 +157,11 @@ impl Ext2 {
         self.blocks_per_group
     }
 
+    /// Returns the seed of the metadata checksums, if they are enabled.
+    pub fn csum_seed(&self) -> Option<u32> {
+        self.csum_seed
+    }
+
     /// Returns the super block.
     pub fn super_block(&self) -> RwMutexReadGuard<Dirty<SuperBlock>> {
         self.super_block.read()
@@ -151,8 +189,9 @@ impl Ext2 {
         let (block_group_idx, ino) =
             self.alloc_ino(dir_block_group_idx, inode_type == InodeType::Dir)?;
         let inode = {
-            let inode_desc = InodeDesc::new(inode_type, file_perm);
-            Inode::new(ino, block_group_idx, inode_desc, self.self_ref.clone())
+            let has_extents = self.super_block.read().has_extents();
+            let inode_desc = InodeDesc::new(inode_type, file_perm, has_extents);
+            Inode::new(ino, block_group_idx, inode_desc, self.self_ref.clone())?
         };
         let block_group = &self.block_groups[block_group_idx];
         block_group.insert_cache(self.inode_idx(ino), inode.clone());
@@ -204,15 +243,38 @@ impl Ext2 {
         Ok(())
     }
 
+    /// Reads the in-inode area for the extended attributes.
+    pub(super) fn read_inode_xattr_area(&self, ino: u32) -> Result<Vec<u8>> {
+        let (_, block_group) = self.block_group_of_ino(ino)?;
+        let inode_idx = self.inode_idx(ino);
+        Ok(block_group.read_inode_xattr_area(inode_idx))
+    }
+
+    /// Writes the in-inode area for the extended attributes.
+    pub(super) fn write_inode_xattr_area(&self, ino: u32, bytes: &[u8]) -> Result<()> {
+        let (_, block_group) = self.block_group_of_ino(ino)?;
+        let inode_idx = self.inode_idx(ino);
+        block_group.write_inode_xattr_area(inode_idx, bytes);
+        Ok(())
+    }
+
+    /// Marks that the inodes may have extended attributes.
+    pub(super) fn enable_xattrs(&self) {
+        if !self.super_block.read().has_xattrs() {
+            self.super_block.write().set_has_xattrs();
+        }
+    }
+
     /// Writes back the block group descriptor to the descriptors table.
     pub(super) fn sync_group_descriptor(
         &self,
         block_group_idx: usize,
-        raw_descriptor: &RawGroupDescriptor,
+        raw_descriptor: &[u8],
     ) -> Result<()> {
-        let offset = block_group_idx * core::mem::size_of::<RawGroupDescriptor>();
+        // The length of the raw descriptor is the size of group descriptors.
+        let offset = block_group_idx * raw_descriptor.len();
         self.group_descriptors_segment
-            .write_val(offset, raw_descriptor)?;
+            .write_bytes(offset, raw_descriptor)?;
         Ok(())
     }
 
@@ -295,6 +357,11 @@ impl Ext2 {
             current_range.start += range_in_group.len() as Ext2Bid
         }
 
+        // The freed blocks may be reused for data, which must not be
+        // overwritten when the running transaction is committed.
+        if self.journal.is_some() {
+            self.running_transaction.lock().discard(range);
+        }
         Ok(())
     }
 
@@ -344,45 +411,122 @@ impl Ext2 {
         Ok(waiter)
     }
 
+    /// Reads the metadata block `bid` synchronously.
+    ///
+    /// If the block is written in the running transaction, it is read from
+    /// there instead of the device.
+    pub(super) fn read_metadata_block(&self, bid: Ext2Bid, buf: &mut [u8]) -> Result<()> {
+        match self.uncommitted_block(bid) {
+            Some(block) => buf.copy_from_slice(&block),
+            None => self
+                .block_device
+                .read_bytes(bid as usize * BLOCK_SIZE, buf)?,
+        }
+        Ok(())
+    }
+
+    /// Returns the contents of the metadata block `bid` if it is written in
+    /// the running transaction.
+    ///
+    /// Such a block is not on the device yet, so it should not be read from
+    /// the device.
+    pub(super) fn uncommitted_block(&self, bid: Ext2Bid) -> Option<Vec<u8>> {
+        if self.journal.is_none() {
+            return None;
+        }
+        self.running_transaction.lock().read_block(bid)
+    }
+
+    /// Writes the metadata in the transaction to the block device.
+    ///
+    /// If the filesystem has a journal, the writes are added to the running
+    /// transaction, which is committed at the next sync. Otherwise, they are
+    /// written in place synchronously.
+    pub(super) fn write_metadata(&self, transaction: Transaction) -> Result<()> {
+        if transaction.is_empty() {
+            return Ok(());
+        }
+
+        if self.journal.is_some() {
+            self.running_transaction.lock().append(transaction);
+            return Ok(());
+        }
+
+        let bio_waiter = transaction.write_in_place(self.block_device.as_ref())?;
+        bio_waiter
+            .wait()
+            .ok_or_else(|| Error::with_message(Errno::EIO, "failed to write metadata"))?;
+        Ok(())
+    }
+
+    /// Writes a metadata block to the `bid` asynchronously.
+    ///
+    /// If the filesystem has a journal, the block is added to the running
+    /// transaction.
+    pub(super) fn write_metadata_block_async(
+        &self,
+        bid: Ext2Bid,
+        frame: &CachePage,
+    ) -> Result<BioWaiter> {
+        if self.journal.is_none() {
+            let bio_segment = BioSegment::new_from_segment(
+                Segment::from(frame.clone()).into(),
+                BioDirection::ToDevice,
+            );
+            return self.write_blocks_async(bid, bio_segment);
+        }
+
+        self.running_transaction.lock().write_block(bid, frame)?;
+        Ok(BioWaiter::new())
+    }
+
+    /// Returns whether the filesystem has a journal.
+    pub(super) fn has_journal(&self) -> bool {
+        self.journal.is_some()
+    }
+
     /// Writes back the metadata to the block device.
+    ///
+    /// If the filesystem has a journal, the running transaction is committed.
     pub fn sync_metadata(&self) -> Result<()> {
         // If the superblock is clean, the block groups must be clean.
         if !self.super_block.read().is_dirty() {
-            return Ok(());
+            return self.commit_running_transaction();
         }
 
         let mut super_block = self.super_block.write();
+        let mut transaction = Transaction::new();
         // Writes back the metadata of block groups
         for block_group in &self.block_groups {
-            block_group.sync_metadata()?;
+            block_group.sync_metadata(&super_block, &mut transaction)?;
         }
 
         // Writes back the main superblock and group descriptor table.
-        let mut bio_waiter = BioWaiter::new();
         let raw_super_block = RawSuperBlock::from((*super_block).deref());
-        bio_waiter.concat(
-            self.block_device
-                .write_bytes_async(SUPER_BLOCK_OFFSET, raw_super_block.as_bytes())?,
-        );
+        transaction.write_bytes(SUPER_BLOCK_OFFSET, raw_super_block.as_bytes());
         let group_descriptors_bio_segment = BioSegment::new_from_segment(
             self.group_descriptors_segment.clone(),
             BioDirection::ToDevice,
         );
-        bio_waiter.concat(self.block_device.write_blocks_async(
-            super_block.group_descriptors_bid(0),
-            group_descriptors_bio_segment.clone(),
-        )?);
-        bio_waiter
-            .wait()
-            .ok_or_else(|| Error::with_message(Errno::EIO, "failed to sync main metadata"))?;
-        drop(bio_waiter);
+        let mut group_descriptors = vec![0u8; self.group_descriptors_segment.size()];
+        self.group_descriptors_segment
+            .read_bytes(0, &mut group_descriptors)?;
+        transaction.write_bytes(
+            super_block.group_descriptors_bid(0).to_offset(),
+            &group_descriptors,
+        );
+        self.write_metadata(transaction)?;
+        self.commit_running_transaction()?;
 
         // Writes back the backups of superblock and group descriptor table.
+        // The backups are not journaled, and they never need recovery.
         let mut raw_super_block_backup = raw_super_block;
+        raw_super_block_backup.feature_incompat &= !FeatureInCompatSet::RECOVER.bits();
         for idx in 1..super_block.block_groups_count() {
             if super_block.is_backup_group(idx as usize) {
                 let mut bio_waiter = BioWaiter::new();
                 raw_super_block_backup.block_group_idx = idx as u16;
+                raw_super_block_backup.update_checksum();
                 bio_waiter.concat(self.block_device.write_bytes_async(
                     super_block.bid(idx as usize).to_offset(),
                     raw_super_block_backup.as_bytes(),
@@ -402,6 +546,27 @@ impl Ext2 {
         Ok(())
     }
 
+    /// Commits the running transaction to the journal, if any.
+    ///
+    /// The superblock on the device needs recovery only while the transaction
+    /// is being committed and checkpointed, so the filesystem is clean on the
+    /// device after every sync.
+    fn commit_running_transaction(&self) -> Result<()> {
+        let Some(journal) = self.journal.as_ref() else {
+            return Ok(());
+        };
+
+        let mut running_transaction = self.running_transaction.lock();
+        if running_transaction.is_empty() {
+            return Ok(());
+        }
+
+        write_needs_recovery(self.block_device.as_ref(), true)?;
+        journal.commit(&running_transaction)?;
+        *running_transaction = Transaction::new();
+        write_needs_recovery(self.block_device.as_ref(), false)
+    }
+
     /// Writes back all the cached inodes to the block device.
     pub fn sync_all_inodes(&self) -> Result<()> {
         for block_group in &self.block_groups {
@@ -438,3 +603,18 @@ impl Ext2 {
         bid % self.blocks_per_group
     }
 }
+
+/// Sets or clears the flag in the superblock on the device that the journal
+/// needs recovery.
+fn write_needs_recovery(block_device: &dyn BlockDevice, needs_recovery: bool) -> Result<()> {
+    let mut raw_super_block = block_device.read_val::<RawSuperBlock>(SUPER_BLOCK_OFFSET)?;
+    if needs_recovery {
+        raw_super_block.feature_incompat |= FeatureInCompatSet::RECOVER.bits();
+    } else {
+        raw_super_block.feature_incompat &= !FeatureInCompatSet::RECOVER.bits();
+    }
+    raw_super_block.update_checksum();
+    block_device.write_bytes(SUPER_BLOCK_OFFSET, raw_super_block.as_bytes())?;
+    block_device.sync()?;
+    Ok(())
+}
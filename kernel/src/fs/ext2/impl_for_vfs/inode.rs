@@ -8,10 +8,10 @@ use aster_rights::Full;
 
 use crate::{
     fs::{
-        ext2::{FilePerm, Inode as Ext2Inode},
+        ext2::{Ext2, FilePerm, Inode as Ext2Inode},
         utils::{
             DirentVisitor, Extension, FallocMode, FileSystem, Inode, InodeMode, InodeType,
-            IoctlCmd, Metadata, MknodType,
+            IoctlCmd, Metadata, MknodType, XattrName, XattrSetFlags,
         },
     },
     prelude::*,
@@ -175,16 +175,30 @@ impl Inode for Ext2Inode {
         Err(Error::new(Errno::EINVAL))
     }
 
+    fn set_xattr(&self, name: XattrName, value: &[u8], flags: XattrSetFlags) -> Result<()> {
+        self.set_xattr(name, value, flags)
+    }
+
+    fn get_xattr(&self, name: XattrName) -> Result<Vec<u8>> {
+        self.get_xattr(name)
+    }
+
+    fn list_xattr(&self) -> Result<Vec<String>> {
+        self.list_xattr()
+    }
+
+    fn remove_xattr(&self, name: XattrName) -> Result<()> {
+        self.remove_xattr(name)
+    }
+
     fn sync_all(&self) -> Result<()> {
         self.sync_all()?;
-        self.fs().block_device().sync()?;
-        Ok(())
+        sync_fs(&self.fs())
     }
 
     fn sync_data(&self) -> Result<()> {
         self.sync_data()?;
-        self.fs().block_device().sync()?;
-        Ok(())
+        sync_fs(&self.fs())
     }
 
     fn fs(&self) -> Arc<dyn FileSystem> {
@@ -207,3 +221,16 @@ impl From<InodeMode> for FilePerm {
         Self::from_bits_truncate(mode.bits() as _)
     }
 }
+
+/// Flushes the block device after an inode is synced.
+///
+/// The metadata of a journaled filesystem are committed as a whole, so the
+/// whole filesystem is synced.
+fn sync_fs(fs: &Ext2) -> Result<()> {
+    if fs.has_journal() {
+        return FileSystem::sync(fs);
+    }
+
+    fs.block_device().sync()?;
+    Ok(())
+}
@@ -5,6 +5,7 @@ use lru::LruCache;
 use super::{
     block_ptr::{Ext2Bid, BID_SIZE},
     fs::Ext2,
+    journal::Transaction,
     prelude::*,
 };
 
@@ -40,16 +41,7 @@ impl IndirectBlockCache {
         self.try_shrink()?;
 
         let fs = self.fs();
-        let load_block = || -> Result<IndirectBlock> {
-            let mut block = IndirectBlock::alloc_uninit()?;
-            let bio_segment = BioSegment::new_from_segment(
-                Segment::<()>::from(block.frame.clone()).into(),
-                BioDirection::FromDevice,
-            );
-            fs.read_blocks(bid, bio_segment)?;
-            block.state = State::UpToDate;
-            Ok(block)
-        };
+        let load_block = || IndirectBlock::load(&fs, bid);
 
         self.cache.try_get_or_insert(bid, load_block)
     }
@@ -61,16 +53,7 @@ impl IndirectBlockCache {
         self.try_shrink()?;
 
         let fs = self.fs();
-        let load_block = || -> Result<IndirectBlock> {
-            let mut block = IndirectBlock::alloc_uninit()?;
-            let bio_segment = BioSegment::new_from_segment(
-                Segment::<()>::from(block.frame.clone()).into(),
-                BioDirection::FromDevice,
-            );
-            fs.read_blocks(bid, bio_segment)?;
-            block.state = State::UpToDate;
-            Ok(block)
-        };
+        let load_block = || IndirectBlock::load(&fs, bid);
 
         self.cache.try_get_or_insert_mut(bid, load_block)
     }
@@ -108,23 +91,15 @@ impl IndirectBlockCache {
     fn evict(&mut self, num: usize) -> Result<()> {
         let num = num.min(self.cache.len());
 
-        let mut bio_waiter = BioWaiter::new();
+        let mut transaction = Transaction::new();
         for _ in 0..num {
             let (bid, block) = self.cache.pop_lru().unwrap();
             if block.is_dirty() {
-                let bio_segment = BioSegment::new_from_segment(
-                    Segment::<()>::from(block.frame.clone()).into(),
-                    BioDirection::ToDevice,
-                );
-                bio_waiter.concat(self.fs().write_blocks_async(bid, bio_segment)?);
+                transaction.write_block(bid, &block.frame)?;
             }
         }
 
-        bio_waiter.wait().ok_or_else(|| {
-            Error::with_message(Errno::EIO, "failed to evict the indirect blocks")
-        })?;
-
-        Ok(())
+        self.fs().write_metadata(transaction)
     }
 
     #[inline]
@@ -141,6 +116,25 @@ pub struct IndirectBlock {
 }
 
 impl IndirectBlock {
+    /// Loads the block `bid` from the disk.
+    ///
+    /// The block is read from the running transaction of the journal if it
+    /// has not been committed.
+    fn load(fs: &Ext2, bid: Ext2Bid) -> Result<Self> {
+        let mut block = Self::alloc_uninit()?;
+        if let Some(buf) = fs.uncommitted_block(bid) {
+            block.frame.write_bytes(0, &buf)?;
+        } else {
+            let bio_segment = BioSegment::new_from_segment(
+                Segment::<()>::from(block.frame.clone()).into(),
+                BioDirection::FromDevice,
+            );
+            fs.read_blocks(bid, bio_segment)?;
+        }
+        block.state = State::UpToDate;
+        Ok(block)
+    }
+
     /// Allocates an uninitialized block whose bytes are to be populated with
     /// data loaded from the disk.
     fn alloc_uninit() -> Result<Self> {
@@ -7,17 +7,20 @@ use alloc::{borrow::ToOwned, rc::Rc};
 use core::sync::atomic::{AtomicUsize, Ordering};
 
 use inherit_methods_macro::inherit_methods;
+use ostd::mm::UntypedMem;
 
 use super::{
     block_ptr::{BidPath, BlockPtrs, Ext2Bid, BID_SIZE, MAX_BLOCK_PTRS},
-    dir::{DirEntry, DirEntryItem, DirEntryReader, DirEntryWriter},
+    dir::{DirEntry, DirEntryItem, DirEntryReader, DirEntryTail, DirEntryWriter},
+    extent::{BlockMapping, ExtentTree},
     fs::Ext2,
     indirect_block_cache::{IndirectBlock, IndirectBlockCache},
     prelude::*,
-    utils::now,
+    utils::{crc32c, now},
+    xattr,
 };
 use crate::{
-    fs::utils::{Extension, FallocMode, InodeMode, Metadata},
+    fs::utils::{Extension, FallocMode, InodeMode, Metadata, XattrMap, XattrName, XattrSetFlags},
     process::{posix_thread::AsPosixThread, Gid, Uid},
 };
 
@@ -27,12 +30,20 @@ pub const MAX_FNAME_LEN: usize = 255;
 /// Max path length of the fast symlink.
 pub const MAX_FAST_SYMLINK_LEN: usize = MAX_BLOCK_PTRS * BID_SIZE;
 
+/// Max number of hard links.
+///
+/// If the `dir_nlink` feature is enabled, a directory with more subdirectories
+/// has a link count of 1.
+pub const MAX_LINKS: u16 = 65000;
+
 /// The Ext2 inode.
 pub struct Inode {
     ino: u32,
     type_: InodeType,
     block_group_idx: usize,
     inner: RwMutex<InodeInner>,
+    /// The extended attributes, which are loaded on the first access.
+    xattrs: Mutex<Option<XattrMap>>,
     fs: Weak<Ext2>,
     extension: Extension,
 }
@@ -43,15 +54,40 @@ impl Inode {
         block_group_idx: usize,
         desc: Dirty<InodeDesc>,
         fs: Weak<Ext2>,
-    ) -> Arc<Self> {
-        Arc::new_cyclic(|weak_self| Self {
+    ) -> Result<Arc<Self>> {
+        let (csum_seed, extent_tree) = {
+            let fs = fs.upgrade().unwrap();
+            let csum_seed = fs
+                .csum_seed()
+                .map(|fs_seed| inode_csum_seed(fs_seed, ino, desc.generation));
+            let extent_tree = if desc.is_extent_mapped() {
+                Some(ExtentTree::load(
+                    &desc.block_ptrs,
+                    &|bid, buf| fs.read_metadata_block(bid, buf),
+                    csum_seed,
+                )?)
+            } else {
+                None
+            };
+            (csum_seed, extent_tree)
+        };
+
+        Ok(Arc::new_cyclic(|weak_self| Self {
             ino,
             type_: desc.type_,
             block_group_idx,
-            inner: RwMutex::new(InodeInner::new(desc, weak_self.clone(), fs.clone())),
+            inner: RwMutex::new(InodeInner::new(
+                desc,
+                extent_tree,
+                csum_seed,
+                block_group_idx,
+                weak_self.clone(),
+                fs.clone(),
+            )),
+            xattrs: Mutex::new(None),
             fs,
             extension: Extension::new(),
-        })
+        }))
     }
 
     pub fn ino(&self) -> u32 {
@@ -134,6 +170,9 @@ impl Inode {
         if inner.contains_entry(name) {
             return_errno!(Errno::EEXIST);
         }
+        if inode_type == InodeType::Dir && inner.is_links_full() {
+            return_errno!(Errno::EMLINK);
+        }
 
         let inode = self
             .fs()
@@ -211,6 +250,9 @@ impl Inode {
         if inner.contains_entry(name) {
             return_errno!(Errno::EEXIST);
         }
+        if inode.hard_links() >= MAX_LINKS {
+            return_errno!(Errno::EMLINK);
+        }
 
         let new_entry = DirEntry::new(inode.ino, name, inode_type);
         let mut inner = inner.upgrade();
@@ -309,8 +351,7 @@ impl Inode {
         let now = now();
         self_inner.set_mtime(now);
         self_inner.set_ctime(now);
-        dir_inner.dec_hard_links();
-        dir_inner.dec_hard_links(); // For "."
+        dir_inner.clear_hard_links();
 
         Ok(())
     }
@@ -407,9 +448,10 @@ impl Inode {
         self_inner.set_mtime(now);
         self_inner.set_ctime(now);
 
-        dst_inner.dec_hard_links();
         if dst_inode_typ == InodeType::Dir {
-            dst_inner.dec_hard_links(); // For "."
+            dst_inner.clear_hard_links();
+        } else {
+            dst_inner.dec_hard_links();
         }
         dst_inner.set_ctime(now);
         drop(self_inner);
@@ -474,6 +516,9 @@ impl Inode {
             if self_inner.hard_links() == 0 || target_inner.hard_links() == 0 {
                 return_errno_with_message!(Errno::ENOENT, "dir removed");
             }
+            if is_dir && target_inner.is_links_full() {
+                return_errno!(Errno::EMLINK);
+            }
             let (src_offset, new_src_ino) = self_inner
                 .find_entry_item(old_name)
                 .map(|entry| (entry.offset(), entry.ino()))
@@ -577,7 +622,11 @@ impl Inode {
         target_inner.remove_entry_at(new_name, dst_offset)?;
         let new_entry = DirEntry::new(src_inode.ino, new_name, src_inode_typ);
         target_inner.append_entry(new_entry, src_inode_typ, new_name)?;
-        dst_inner.dec_hard_links();
+        if is_dir {
+            dst_inner.clear_hard_links();
+        } else {
+            dst_inner.dec_hard_links();
+        }
         let now = now();
         self_inner.set_mtime(now);
         self_inner.set_ctime(now);
@@ -586,7 +635,6 @@ impl Inode {
         dst_inner.set_ctime(now);
 
         if is_dir {
-            dst_inner.dec_hard_links(); // For "."
             let mut src_inner = write_guards.pop().unwrap();
             src_inner.set_parent_ino(target.ino)?;
             src_inner.set_ctime(now);
@@ -613,14 +661,15 @@ impl Inode {
 
             let try_readdir = |offset: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
                 let mut dir_entry_reader = DirEntryReader::new(&inner.page_cache, *offset);
-                for dir_entry in dir_entry_reader.iter_entries() {
+                for (entry_offset, dir_entry) in dir_entry_reader.iter_entries() {
+                    let next_offset = entry_offset + dir_entry.record_len();
                     visitor.visit(
                         dir_entry.name(),
                         dir_entry.ino() as u64,
                         dir_entry.type_(),
-                        dir_entry.record_len(),
+                        next_offset,
                     )?;
-                    *offset += dir_entry.record_len();
+                    *offset = next_offset;
                 }
 
                 Ok(())
@@ -776,6 +825,44 @@ impl Inode {
         &self.extension
     }
 
+    pub fn set_xattr(&self, name: XattrName, value: &[u8], flags: XattrSetFlags) -> Result<()> {
+        let mut inner = self.inner.write();
+        let mut xattrs = self.xattrs.lock();
+        let xattrs = load_xattrs(&inner, &mut xattrs)?;
+
+        let mut new_xattrs = xattrs.clone();
+        xattr::set_xattr(&mut new_xattrs, name, value, flags)?;
+        inner.write_xattrs(&new_xattrs)?;
+        *xattrs = new_xattrs;
+        inner.set_ctime(now());
+        Ok(())
+    }
+
+    pub fn get_xattr(&self, name: XattrName) -> Result<Vec<u8>> {
+        let inner = self.inner.read();
+        let mut xattrs = self.xattrs.lock();
+        load_xattrs(&inner, &mut xattrs)?.get(name)
+    }
+
+    pub fn list_xattr(&self) -> Result<Vec<String>> {
+        let inner = self.inner.read();
+        let mut xattrs = self.xattrs.lock();
+        Ok(load_xattrs(&inner, &mut xattrs)?.names())
+    }
+
+    pub fn remove_xattr(&self, name: XattrName) -> Result<()> {
+        let mut inner = self.inner.write();
+        let mut xattrs = self.xattrs.lock();
+        let xattrs = load_xattrs(&inner, &mut xattrs)?;
+
+        let mut new_xattrs = xattrs.clone();
+        new_xattrs.remove(name)?;
+        inner.write_xattrs(&new_xattrs)?;
+        *xattrs = new_xattrs;
+        inner.set_ctime(now());
+        Ok(())
+    }
+
     pub fn fallocate(&self, mode: FallocMode, offset: usize, len: usize) -> Result<()> {
         if self.type_ != InodeType::File {
             return_errno_with_message!(Errno::EISDIR, "not regular file");
@@ -849,6 +936,17 @@ impl Debug for Inode {
     }
 }
 
+/// Returns the cached extended attributes, loading them if they are not cached yet.
+fn load_xattrs<'a>(
+    inner: &InodeInner,
+    xattrs: &'a mut Option<XattrMap>,
+) -> Result<&'a mut XattrMap> {
+    if xattrs.is_none() {
+        *xattrs = Some(inner.read_xattrs()?);
+    }
+    Ok(xattrs.as_mut().unwrap())
+}
+
 fn read_lock_two_inodes<'a>(
     this: &'a Inode,
     other: &'a Inode,
@@ -908,9 +1006,17 @@ struct InodeInner {
 }
 
 impl InodeInner {
-    pub fn new(desc: Dirty<InodeDesc>, weak_self: Weak<Inode>, fs: Weak<Ext2>) -> Self {
+    pub fn new(
+        desc: Dirty<InodeDesc>,
+        extent_tree: Option<ExtentTree>,
+        csum_seed: Option<u32>,
+        block_group_idx: usize,
+        weak_self: Weak<Inode>,
+        fs: Weak<Ext2>,
+    ) -> Self {
         let num_page_bytes = desc.num_page_bytes();
-        let inode_impl = InodeImpl::new(desc, weak_self, fs);
+        let inode_impl =
+            InodeImpl::new(desc, extent_tree, csum_seed, block_group_idx, weak_self, fs);
         Self {
             page_cache: PageCache::with_capacity(
                 num_page_bytes,
@@ -1054,7 +1160,8 @@ impl InodeInner {
     ) -> Result<()> {
         debug_assert!(inode_type == entry.type_() && entry.name() == name);
 
-        DirEntryWriter::new(&self.page_cache, 0).append_entry(entry)?;
+        self.drop_dir_index()?;
+        DirEntryWriter::new(&self.page_cache, 0, self.has_dir_tail()).append_entry(entry)?;
         let file_size = self.file_size();
         let page_cache_size = self.page_cache.pages().size();
         if page_cache_size > file_size {
@@ -1062,29 +1169,34 @@ impl InodeInner {
         }
 
         let is_dir = inode_type == InodeType::Dir;
-        let is_parent = name == "..";
-        if is_dir && !is_parent {
+        let is_self_or_parent = name == "." || name == "..";
+        if is_dir && !is_self_or_parent {
             self.inc_hard_links(); // for ".."
         }
         Ok(())
     }
 
     pub fn remove_entry_at(&mut self, name: &str, offset: usize) -> Result<()> {
-        let entry = DirEntryWriter::new(&self.page_cache, offset).remove_entry(name)?;
+        self.drop_dir_index()?;
+        let entry = DirEntryWriter::new(&self.page_cache, offset, self.has_dir_tail())
+            .remove_entry(name)?;
         let is_dir = entry.type_() == InodeType::Dir;
         let file_size = self.file_size();
         let page_cache_size = self.page_cache.pages().size();
         if page_cache_size < file_size {
             self.inode_impl.resize(page_cache_size)?;
         }
-        if is_dir {
+        // A directory whose link count has overflowed to 1 keeps it.
+        if is_dir && self.hard_links() > 2 {
             self.dec_hard_links(); // for ".."
         }
         Ok(())
     }
 
     pub fn rename_entry_at(&mut self, old_name: &str, new_name: &str, offset: usize) -> Result<()> {
-        DirEntryWriter::new(&self.page_cache, offset).rename_entry(old_name, new_name)?;
+        self.drop_dir_index()?;
+        DirEntryWriter::new(&self.page_cache, offset, self.has_dir_tail())
+            .rename_entry(old_name, new_name)?;
         let file_size = self.file_size();
         let page_cache_size = self.page_cache.pages().size();
         if page_cache_size != file_size {
@@ -1096,11 +1208,71 @@ impl InodeInner {
     pub fn set_parent_ino(&mut self, parent_ino: u32) -> Result<()> {
         let mut entry_item = self.find_entry_item("..").unwrap();
         entry_item.set_ino(parent_ino);
-        DirEntryWriter::new(&self.page_cache, entry_item.offset())
+        DirEntryWriter::new(&self.page_cache, entry_item.offset(), self.has_dir_tail())
             .write_header_only(entry_item.header())?;
         Ok(())
     }
 
+    /// Returns whether the directory blocks end with a `DirEntryTail`,
+    /// which holds the checksum of the block.
+    fn has_dir_tail(&self) -> bool {
+        self.inode_impl.block_manager.csum_seed.is_some()
+    }
+
+    /// Converts the hash-indexed directory to a linear one.
+    ///
+    /// The hash index is not updated when the entries are modified, so it is dropped
+    /// before any modification. The index blocks are left in place, since they are
+    /// valid directory blocks that hold no entries.
+    fn drop_dir_index(&mut self) -> Result<()> {
+        if !self.file_flags().contains(FileFlags::INDEX_DIR) {
+            return Ok(());
+        }
+
+        if self.has_dir_tail() {
+            // Linux verifies the tails of the directory blocks that are not index
+            // blocks, so the tails are added to the index blocks.
+            let pages = self.page_cache.pages();
+            for block_offset in (0..self.file_size()).step_by(BLOCK_SIZE) {
+                let first_item = DirEntryReader::new(&self.page_cache, block_offset)
+                    .records()
+                    .next()
+                    .ok_or(Error::with_message(Errno::EUCLEAN, "bad directory block"))?;
+                // The root block holds "." and "..", whose record covers the index.
+                // The other index blocks hold an unused record covering the block.
+                let mut last_item = if block_offset == 0 {
+                    let items = DirEntryReader::new(&self.page_cache, 0).records();
+                    items.take(2).last().unwrap()
+                } else if first_item.ino() == 0 && first_item.record_len() == BLOCK_SIZE {
+                    first_item
+                } else {
+                    continue;
+                };
+                if last_item.offset() + last_item.record_len() != block_offset + BLOCK_SIZE
+                    || last_item.record_len() < last_item.actual_len() + DirEntryTail::LEN
+                {
+                    continue;
+                }
+
+                last_item.set_record_len(last_item.record_len() - DirEntryTail::LEN);
+                pages.write_val(last_item.offset(), last_item.header())?;
+                pages.write_val(
+                    block_offset + BLOCK_SIZE - DirEntryTail::LEN,
+                    &DirEntryTail::new(),
+                )?;
+            }
+        }
+
+        let flags = self.file_flags() - FileFlags::INDEX_DIR;
+        self.inode_impl.set_file_flags(flags);
+        Ok(())
+    }
+
+    /// Returns whether the directory cannot hold more subdirectories.
+    pub fn is_links_full(&self) -> bool {
+        !self.inode_impl.fs().super_block().has_dir_nlink() && self.hard_links() >= MAX_LINKS
+    }
+
     pub fn sync_data(&self) -> Result<()> {
         // Writes back the data in page cache.
         let file_size = self.file_size();
@@ -1123,6 +1295,7 @@ impl InodeInner {
     pub fn hard_links(&self) -> u16;
     pub fn inc_hard_links(&mut self);
     pub fn dec_hard_links(&mut self);
+    pub fn clear_hard_links(&mut self);
     pub fn blocks_count(&self) -> Ext2Bid;
     pub fn acl(&self) -> Option<Bid>;
     pub fn atime(&self) -> Duration;
@@ -1133,6 +1306,8 @@ impl InodeInner {
     pub fn set_ctime(&mut self, time: Duration);
     pub fn device_id(&self) -> u64;
     pub fn set_device_id(&mut self, device_id: u64);
+    pub fn read_xattrs(&self) -> Result<XattrMap>;
+    pub fn write_xattrs(&mut self, xattrs: &XattrMap) -> Result<()>;
     pub fn sync_metadata(&mut self) -> Result<()>;
 }
 
@@ -1145,11 +1320,22 @@ struct InodeImpl {
 }
 
 impl InodeImpl {
-    pub fn new(desc: Dirty<InodeDesc>, weak_self: Weak<Inode>, fs: Weak<Ext2>) -> Self {
+    pub fn new(
+        desc: Dirty<InodeDesc>,
+        extent_tree: Option<ExtentTree>,
+        csum_seed: Option<u32>,
+        block_group_idx: usize,
+        weak_self: Weak<Inode>,
+        fs: Weak<Ext2>,
+    ) -> Self {
         let block_manager = InodeBlockManager {
             nblocks: AtomicUsize::new(desc.blocks_count() as _),
             block_ptrs: RwMutex::new(desc.block_ptrs),
             indirect_blocks: RwMutex::new(IndirectBlockCache::new(fs.clone())),
+            extent_tree: extent_tree.map(RwMutex::new),
+            is_metadata: desc.type_ == InodeType::Dir,
+            csum_seed,
+            block_group_idx,
             fs,
         };
         Self {
@@ -1205,11 +1391,23 @@ impl InodeImpl {
         self.desc.flags
     }
 
+    pub fn set_file_flags(&mut self, flags: FileFlags) {
+        self.desc.flags = flags;
+    }
+
     pub fn hard_links(&self) -> u16 {
         self.desc.hard_links
     }
 
     pub fn inc_hard_links(&mut self) {
+        // A directory with too many subdirectories has a link count of 1,
+        // which means that the count is unknown.
+        if self.desc.type_ == InodeType::Dir
+            && (self.desc.hard_links == 1 || self.desc.hard_links >= MAX_LINKS)
+        {
+            self.desc.hard_links = 1;
+            return;
+        }
         self.desc.hard_links += 1;
     }
 
@@ -1218,6 +1416,11 @@ impl InodeImpl {
         self.desc.hard_links -= 1;
     }
 
+    /// Clears the link count, e.g., when a directory is removed.
+    pub fn clear_hard_links(&mut self) {
+        self.desc.hard_links = 0;
+    }
+
     pub fn blocks_count(&self) -> Ext2Bid {
         self.desc.blocks_count()
     }
@@ -1281,8 +1484,84 @@ impl InodeImpl {
         Ok(())
     }
 
+    /// Returns the attribute block, if any.
+    fn xattr_bid(&self) -> Option<Ext2Bid> {
+        self.desc
+            .acl
+            .map(|acl| acl.to_raw() as Ext2Bid)
+            .filter(|&bid| bid != 0)
+    }
+
+    /// Reads the extended attributes from the in-inode area and the attribute block.
+    pub fn read_xattrs(&self) -> Result<XattrMap> {
+        let inode = self.inode();
+        let fs = inode.fs();
+        let ibody = fs.read_inode_xattr_area(inode.ino())?;
+        let block = match self.xattr_bid() {
+            Some(bid) => Some(xattr::read_xattr_block(&fs, bid)?),
+            None => None,
+        };
+        xattr::load_xattrs(&ibody, block.as_deref())
+    }
+
+    /// Writes the extended attributes into the in-inode area and the attribute block.
+    ///
+    /// An attribute block shared with other inodes is not modified. Instead, the inode
+    /// drops its reference and gets a new block.
+    pub fn write_xattrs(&mut self, xattrs: &XattrMap) -> Result<()> {
+        let inode = self.inode();
+        let fs = inode.fs();
+        let ibody_len = fs.read_inode_xattr_area(inode.ino())?.len();
+        let (ibody, block) = xattr::layout_xattrs(xattrs, ibody_len)?;
+
+        let old_bid = self.xattr_bid();
+        let new_bid = match block {
+            Some(mut block) => {
+                let reusable_bid = match old_bid {
+                    Some(bid)
+                        if xattr::block_refcount(&xattr::read_xattr_block(&fs, bid)?) == 1 =>
+                    {
+                        Some(bid)
+                    }
+                    _ => None,
+                };
+                let bid = match reusable_bid {
+                    Some(bid) => bid,
+                    None => {
+                        fs.alloc_blocks(inode.block_group_idx, 1)
+                            .ok_or_else(|| Error::new(Errno::ENOSPC))?
+                            .start
+                    }
+                };
+                xattr::write_xattr_block(&fs, bid, &mut block)?;
+                Some(bid)
+            }
+            None => None,
+        };
+        if let Some(bid) = old_bid.filter(|&bid| Some(bid) != new_bid) {
+            xattr::release_xattr_block(&fs, bid)?;
+        }
+
+        match (old_bid, new_bid) {
+            (None, Some(_)) => self.desc.blocks_count += 1,
+            (Some(_), None) => self.desc.blocks_count = self.desc.blocks_count.saturating_sub(1),
+            _ => {}
+        }
+        self.desc.acl = Some(Bid::new(new_bid.unwrap_or(0) as u64));
+        fs.write_inode_xattr_area(inode.ino(), &ibody)?;
+        if !xattrs.is_empty() {
+            fs.enable_xattrs();
+        }
+        Ok(())
+    }
+
     pub fn sync_metadata(&mut self) -> Result<()> {
-        if !self.desc.is_dirty() {
+        let is_extent_tree_dirty = self
+            .block_manager
+            .extent_tree
+            .as_ref()
+            .is_some_and(|extent_tree| extent_tree.read().is_dirty());
+        if !self.desc.is_dirty() && !is_extent_tree_dirty {
             return Ok(());
         }
 
@@ -1291,6 +1570,11 @@ impl InodeImpl {
             self.resize(0)?;
             // Adds the check here to prevent double-free.
             if !self.is_freed {
+                if let Some(bid) = self.xattr_bid() {
+                    xattr::release_xattr_block(&inode.fs(), bid)?;
+                    self.desc.acl = Some(Bid::new(0));
+                    self.desc.blocks_count = self.desc.blocks_count.saturating_sub(1);
+                }
                 inode
                     .fs()
                     .free_inode(inode.ino(), self.desc.type_ == InodeType::Dir)?;
@@ -1298,6 +1582,20 @@ impl InodeImpl {
             }
         }
 
+        if let Some(extent_tree) = self.block_manager.extent_tree.as_ref() {
+            let mut extent_tree = extent_tree.write();
+            if extent_tree.is_dirty() {
+                let mut block_ptrs = self.desc.block_ptrs;
+                extent_tree.sync(&mut block_ptrs, &inode.fs(), inode.block_group_idx)?;
+                *self.block_manager.block_ptrs.write() = block_ptrs;
+                self.desc.block_ptrs = block_ptrs;
+                // The blocks of extended attributes are counted as well.
+                let acl_blocks_count =
+                    self.desc.acl.is_some_and(|acl| acl.to_raw() != 0) as Ext2Bid;
+                self.desc.blocks_count = extent_tree.blocks_count() + acl_blocks_count;
+            }
+        }
+
         self.block_manager.indirect_blocks.write().evict_all()?;
         inode.fs().sync_inode(inode.ino(), &self.desc)?;
         self.desc.clear_dirty();
@@ -1342,6 +1640,10 @@ impl InodeImpl {
     ///
     /// After a successful expansion, the block count will be enlarged to `range.end`.
     fn expand_blocks(&mut self, range: Range<Ext2Bid>) -> Result<()> {
+        if self.block_manager.extent_tree.is_some() {
+            return self.expand_extent_blocks(range);
+        }
+
         let mut current_range = range.clone();
         while !current_range.is_empty() {
             let Ok(expand_cnt) = self.try_expand_blocks(current_range.clone()) else {
@@ -1400,7 +1702,7 @@ impl InodeImpl {
                 self.fs().free_blocks(device_range).unwrap();
                 return Err(e);
             }
-            self.desc.blocks_count = range.start + device_range.len() as Ext2Bid;
+            self.desc.blocks_count += device_range.len() as Ext2Bid;
             self.last_alloc_device_bid = Some(device_range.end - 1);
             return Ok(device_range.len() as Ext2Bid);
         }
@@ -1435,6 +1737,9 @@ impl InodeImpl {
             (indirect_bids, device_range.unwrap())
         };
 
+        // The indirect blocks are counted here, since they are no longer counted
+        // after they are freed.
+        self.desc.blocks_count += indirect_bids.len() as Ext2Bid;
         if let Err(e) = self.set_indirect_bids(range.start, &indirect_bids) {
             self.free_indirect_blocks_required_by(range.start).unwrap();
             return Err(e);
@@ -1446,11 +1751,46 @@ impl InodeImpl {
             return Err(e);
         }
 
-        self.desc.blocks_count = range.start + device_range.len() as Ext2Bid;
+        self.desc.blocks_count += device_range.len() as Ext2Bid;
         self.last_alloc_device_bid = Some(device_range.end - 1);
         Ok(device_range.len() as Ext2Bid)
     }
 
+    /// Expands the blocks of an inode mapped by extents.
+    ///
+    /// Only the holes in the `range` are allocated, since some blocks beyond the
+    /// size may have been preallocated.
+    fn expand_extent_blocks(&mut self, range: Range<Ext2Bid>) -> Result<()> {
+        let fs = self.fs();
+        let mut extent_tree = self.block_manager.extent_tree.as_ref().unwrap().write();
+        let mut current_bid = range.start;
+        while current_bid < range.end {
+            let max_len = range.end - current_bid;
+            let len = match extent_tree.map(current_bid, max_len) {
+                BlockMapping::Mapped { device_range, .. } => device_range.len() as Ext2Bid,
+                BlockMapping::Hole(len) => {
+                    let block_group_idx = extent_tree
+                        .goal(current_bid)
+                        .map_or(self.block_manager.block_group_idx, |bid| {
+                            (bid / fs.blocks_per_group()) as usize
+                        });
+                    let Some(device_range) = fs.alloc_blocks(block_group_idx, len) else {
+                        for device_range in extent_tree.truncate(range.start) {
+                            fs.free_blocks(device_range).unwrap();
+                        }
+                        return_errno_with_message!(Errno::ENOSPC, "can not allocate blocks");
+                    };
+                    let len = device_range.len() as Ext2Bid;
+                    extent_tree.insert(current_bid, device_range);
+                    len
+                }
+            };
+            current_bid += len;
+        }
+
+        Ok(())
+    }
+
     /// Sets the device block IDs for a specified range.
     ///
     /// It updates the mapping between the file's block IDs and the device's block IDs
@@ -1582,8 +1922,9 @@ impl InodeImpl {
         let new_blocks = self.desc.size_to_blocks(new_size);
         let old_blocks = self.desc.blocks_count();
 
-        // Shrinks block count if necessary
-        if new_blocks < old_blocks {
+        // Shrinks block count if necessary. The blocks preallocated beyond the size
+        // are freed as well for the inode mapped by extents.
+        if new_blocks < old_blocks || self.block_manager.extent_tree.is_some() {
             self.shrink_blocks(new_blocks..old_blocks);
         }
 
@@ -1602,13 +1943,20 @@ impl InodeImpl {
     ///
     /// After the reduction, the block count will be decreased to `range.start`.
     fn shrink_blocks(&mut self, range: Range<Ext2Bid>) {
+        if let Some(extent_tree) = self.block_manager.extent_tree.as_ref() {
+            let fs = self.fs();
+            for device_range in extent_tree.write().truncate(range.start) {
+                fs.free_blocks(device_range).unwrap();
+            }
+            return;
+        }
+
         let mut current_range = range.clone();
         while !current_range.is_empty() {
             let free_cnt = self.try_shrink_blocks(current_range.clone());
             current_range.end -= free_cnt;
         }
 
-        self.desc.blocks_count = range.start;
         self.last_alloc_device_bid = if range.start == 0 {
             None
         } else {
@@ -1641,6 +1989,10 @@ impl InodeImpl {
         for device_range in device_range_reader {
             fs.free_blocks(device_range.clone()).unwrap();
         }
+        self.desc.blocks_count = self
+            .desc
+            .blocks_count
+            .saturating_sub(range.len() as Ext2Bid);
 
         self.free_indirect_blocks_required_by(range.start).unwrap();
         range.len() as Ext2Bid
@@ -1674,6 +2026,7 @@ impl InodeImpl {
                 self.fs()
                     .free_blocks(indirect_bid..indirect_bid + 1)
                     .unwrap();
+                self.desc.blocks_count = self.desc.blocks_count.saturating_sub(1);
             }
             BidPath::DbIndirect(lvl1_idx, _) => {
                 let db_indirect_bid = self.desc.block_ptrs.db_indirect();
@@ -1690,6 +2043,7 @@ impl InodeImpl {
                     indirect_blocks.remove(lvl1_indirect_bid);
                     fs.free_blocks(lvl1_indirect_bid..lvl1_indirect_bid + 1)
                         .unwrap();
+                    self.desc.blocks_count = self.desc.blocks_count.saturating_sub(1);
                 }
                 if lvl1_idx == 0 {
                     self.desc.block_ptrs.set_db_indirect(0);
@@ -1697,6 +2051,7 @@ impl InodeImpl {
                     indirect_blocks.remove(db_indirect_bid);
                     fs.free_blocks(db_indirect_bid..db_indirect_bid + 1)
                         .unwrap();
+                    self.desc.blocks_count = self.desc.blocks_count.saturating_sub(1);
                 }
             }
             BidPath::TbIndirect(lvl1_idx, lvl2_idx, _) => {
@@ -1719,11 +2074,13 @@ impl InodeImpl {
                         indirect_blocks.remove(lvl2_indirect_bid);
                         fs.free_blocks(lvl2_indirect_bid..lvl2_indirect_bid + 1)
                             .unwrap();
+                        self.desc.blocks_count = self.desc.blocks_count.saturating_sub(1);
                     }
                     if lvl2_idx == 0 {
                         indirect_blocks.remove(lvl1_indirect_bid);
                         fs.free_blocks(lvl1_indirect_bid..lvl1_indirect_bid + 1)
                             .unwrap();
+                        self.desc.blocks_count = self.desc.blocks_count.saturating_sub(1);
                     }
                 }
 
@@ -1733,6 +2090,7 @@ impl InodeImpl {
                     indirect_blocks.remove(tb_indirect_bid);
                     fs.free_blocks(tb_indirect_bid..tb_indirect_bid + 1)
                         .unwrap();
+                    self.desc.blocks_count = self.desc.blocks_count.saturating_sub(1);
                 }
             }
             BidPath::Direct(_) => panic!(),
@@ -1772,9 +2130,26 @@ struct InodeBlockManager {
     /// frequent reads access the `InodeDesc` copy without locking.
     block_ptrs: RwMutex<BlockPtrs>,
     indirect_blocks: RwMutex<IndirectBlockCache>,
+    /// The extent tree, if the blocks are mapped by extents instead of block pointers.
+    extent_tree: Option<RwMutex<ExtentTree>>,
+    /// Whether the blocks hold metadata, e.g., directory entries, which are
+    /// written through the journal.
+    is_metadata: bool,
+    /// The seed of the checksums of the metadata, if they are enabled.
+    csum_seed: Option<u32>,
+    /// The block group to allocate the blocks from first.
+    block_group_idx: usize,
     fs: Weak<Ext2>,
 }
 
+/// The mapping of a range of file blocks to the device.
+enum DeviceMapping {
+    /// The blocks are mapped to the device range.
+    Mapped(Range<Ext2Bid>),
+    /// The given number of blocks are not mapped or not written, which are read as zeros.
+    Zeroed(Ext2Bid),
+}
+
 impl InodeBlockManager {
     /// Reads one or multiple blocks to the segment start from `bid` asynchronously.
     pub fn read_blocks_async(
@@ -1786,7 +2161,16 @@ impl InodeBlockManager {
         debug_assert!(nblocks * BLOCK_SIZE <= writer.avail());
         let mut bio_waiter = BioWaiter::new();
 
-        for dev_range in DeviceRangeReader::new(self, bid..bid + nblocks as Ext2Bid)? {
+        for mapping in self.read_mappings(bid..bid + nblocks as Ext2Bid)? {
+            let dev_range = match mapping {
+                DeviceMapping::Mapped(dev_range) => dev_range,
+                DeviceMapping::Zeroed(nblocks) => {
+                    writer
+                        .fill_zeros(nblocks as usize * BLOCK_SIZE)
+                        .map_err(|(err, _)| Error::from(err))?;
+                    continue;
+                }
+            };
             let start_bid = dev_range.start as Ext2Bid;
             let range_nblocks = dev_range.len();
 
@@ -1810,8 +2194,22 @@ impl InodeBlockManager {
     pub fn read_block_async(&self, bid: Ext2Bid, frame: &CachePage) -> Result<BioWaiter> {
         let mut bio_waiter = BioWaiter::new();
 
-        for dev_range in DeviceRangeReader::new(self, bid..bid + 1 as Ext2Bid)? {
+        for mapping in self.read_mappings(bid..bid + 1 as Ext2Bid)? {
+            let dev_range = match mapping {
+                DeviceMapping::Mapped(dev_range) => dev_range,
+                DeviceMapping::Zeroed(_) => {
+                    frame.writer().fill(0);
+                    continue;
+                }
+            };
             let start_bid = dev_range.start as Ext2Bid;
+            if self.is_metadata {
+                if let Some(buf) = self.fs().uncommitted_block(start_bid) {
+                    frame.write_bytes(0, &buf)?;
+                    continue;
+                }
+            }
+
             let bio_segment = BioSegment::new_from_segment(
                 Segment::from(frame.clone()).into(),
                 BioDirection::FromDevice,
@@ -1833,7 +2231,7 @@ impl InodeBlockManager {
         debug_assert_eq!(nblocks * BLOCK_SIZE, reader.remain());
         let mut bio_waiter = BioWaiter::new();
 
-        for dev_range in DeviceRangeReader::new(self, bid..bid + nblocks as Ext2Bid)? {
+        for dev_range in self.write_mappings(bid..bid + nblocks as Ext2Bid)? {
             let start_bid = dev_range.start as Ext2Bid;
             let range_nblocks = dev_range.len();
 
@@ -1857,8 +2255,20 @@ impl InodeBlockManager {
     pub fn write_block_async(&self, bid: Ext2Bid, frame: &CachePage) -> Result<BioWaiter> {
         let mut bio_waiter = BioWaiter::new();
 
-        for dev_range in DeviceRangeReader::new(self, bid..bid + 1 as Ext2Bid)? {
+        if self.is_metadata {
+            if let Some(csum_seed) = self.csum_seed {
+                DirEntryTail::update_checksum(frame, csum_seed)?;
+            }
+        }
+
+        for dev_range in self.write_mappings(bid..bid + 1 as Ext2Bid)? {
             let start_bid = dev_range.start as Ext2Bid;
+            if self.is_metadata {
+                let waiter = self.fs().write_metadata_block_async(start_bid, frame)?;
+                bio_waiter.concat(waiter);
+                continue;
+            }
+
             let bio_segment = BioSegment::new_from_segment(
                 Segment::from(frame.clone()).into(),
                 BioDirection::ToDevice,
@@ -1870,6 +2280,75 @@ impl InodeBlockManager {
         Ok(bio_waiter)
     }
 
+    /// Returns the mappings of the file blocks in the `range` for reading.
+    fn read_mappings(&self, range: Range<Ext2Bid>) -> Result<Vec<DeviceMapping>> {
+        let Some(extent_tree) = self.extent_tree.as_ref() else {
+            let device_range_reader = DeviceRangeReader::new(self, range)?;
+            return Ok(device_range_reader.map(DeviceMapping::Mapped).collect());
+        };
+
+        let extent_tree = extent_tree.read();
+        let mut mappings = Vec::new();
+        let mut current_bid = range.start;
+        while current_bid < range.end {
+            let mapping = extent_tree.map(current_bid, range.end - current_bid);
+            current_bid += mapping.len();
+            mappings.push(match mapping {
+                BlockMapping::Mapped {
+                    device_range,
+                    is_unwritten: false,
+                } => DeviceMapping::Mapped(device_range),
+                _ => DeviceMapping::Zeroed(mapping.len()),
+            });
+        }
+        Ok(mappings)
+    }
+
+    /// Returns the device ranges of the file blocks in the `range` for writing.
+    ///
+    /// For the inode mapped by extents, the holes are allocated and the unwritten
+    /// blocks are marked as written.
+    fn write_mappings(&self, range: Range<Ext2Bid>) -> Result<Vec<Range<Ext2Bid>>> {
+        let Some(extent_tree) = self.extent_tree.as_ref() else {
+            let device_range_reader = DeviceRangeReader::new(self, range)?;
+            return Ok(device_range_reader.collect());
+        };
+
+        let fs = self.fs();
+        let mut extent_tree = extent_tree.write();
+        let mut device_ranges = Vec::new();
+        let mut current_bid = range.start;
+        while current_bid < range.end {
+            let device_range = match extent_tree.map(current_bid, range.end - current_bid) {
+                BlockMapping::Mapped {
+                    device_range,
+                    is_unwritten,
+                } => {
+                    if is_unwritten {
+                        let len = device_range.len() as Ext2Bid;
+                        extent_tree.mark_written(current_bid..current_bid + len);
+                    }
+                    device_range
+                }
+                BlockMapping::Hole(len) => {
+                    let block_group_idx = extent_tree
+                        .goal(current_bid)
+                        .map_or(self.block_group_idx, |bid| {
+                            (bid / fs.blocks_per_group()) as usize
+                        });
+                    let device_range = fs
+                        .alloc_blocks(block_group_idx, len)
+                        .ok_or_else(|| Error::new(Errno::ENOSPC))?;
+                    extent_tree.insert(current_bid, device_range.clone());
+                    device_range
+                }
+            };
+            current_bid += device_range.len() as Ext2Bid;
+            device_ranges.push(device_range);
+        }
+        Ok(device_ranges)
+    }
+
     pub fn nblocks(&self) -> usize {
         self.nblocks.load(Ordering::Acquire)
     }
@@ -2051,12 +2530,14 @@ pub(super) struct InodeDesc {
     dtime: Duration,
     /// Hard links count.
     hard_links: u16,
-    /// Number of blocks.
+    /// Number of blocks occupied, including the indirect blocks and the extent tree nodes.
     blocks_count: Ext2Bid,
     /// File flags.
     flags: FileFlags,
-    /// Pointers to blocks.
+    /// Pointers to blocks, or the root of the extent tree.
     block_ptrs: BlockPtrs,
+    /// File version, which is a part of the seed of the inode checksum.
+    generation: u32,
     /// File or directory acl block.
     acl: Option<Bid>,
 }
@@ -2066,12 +2547,26 @@ impl TryFrom<RawInode> for InodeDesc {
 
     fn try_from(inode: RawInode) -> Result<Self> {
         let inode_type = InodeType::from_raw_mode(inode.mode)?;
+        let flags = FileFlags::from_bits(inode.flags)
+            .ok_or(Error::with_message(Errno::EINVAL, "invalid file flags"))?;
+        let blocks_count = {
+            let raw_count =
+                (inode.os_dependent_2.blocks_high as u64) << 32 | inode.blocks_count as u64;
+            // The count is in sectors, unless the huge file flag is set.
+            let count = if flags.contains(FileFlags::HUGE_FILE) {
+                raw_count
+            } else {
+                raw_count / (BLOCK_SIZE / SECTOR_SIZE) as u64
+            };
+            Ext2Bid::try_from(count)
+                .map_err(|_| Error::with_message(Errno::EINVAL, "too many blocks"))?
+        };
         Ok(Self {
             type_: inode_type,
             perm: FilePerm::from_raw_mode(inode.mode)?,
             uid: (inode.os_dependent_2.uid_high as u32) << 16 | inode.uid as u32,
             gid: (inode.os_dependent_2.gid_high as u32) << 16 | inode.gid as u32,
-            size: if inode_type == InodeType::File {
+            size: if inode_type == InodeType::File || inode_type == InodeType::Dir {
                 (inode.size_high as usize) << 32 | inode.size_low as usize
             } else {
                 inode.size_low as usize
@@ -2081,22 +2576,24 @@ impl TryFrom<RawInode> for InodeDesc {
             mtime: Duration::from(inode.mtime),
             dtime: Duration::from(inode.dtime),
             hard_links: inode.hard_links,
-            blocks_count: inode.blocks_count,
-            flags: FileFlags::from_bits(inode.flags)
-                .ok_or(Error::with_message(Errno::EINVAL, "invalid file flags"))?,
+            blocks_count,
+            flags,
             block_ptrs: inode.block_ptrs,
-            acl: match inode_type {
-                InodeType::File => Some(Bid::new(inode.file_acl as _)),
-                InodeType::Dir => Some(Bid::new(inode.size_high as _)),
-                _ => None,
-            },
+            generation: inode.generation,
+            acl: Some(Bid::new(
+                (inode.os_dependent_2.file_acl_high as u64) << 32 | inode.file_acl as u64,
+            )),
         })
     }
 }
 
 impl InodeDesc {
-    pub fn new(type_: InodeType, perm: FilePerm) -> Dirty<Self> {
+    /// Creates a new inode descriptor.
+    ///
+    /// If `has_extents` is true, the blocks of files and directories are mapped by extents.
+    pub fn new(type_: InodeType, perm: FilePerm, has_extents: bool) -> Dirty<Self> {
         let now = now();
+        let is_extent_mapped = has_extents && (type_ == InodeType::File || type_ == InodeType::Dir);
         let credentials = current_thread!().as_posix_thread().unwrap().credentials();
         Dirty::new_dirty(Self {
             type_,
@@ -2108,14 +2605,21 @@ impl InodeDesc {
             ctime: now,
             mtime: now,
             dtime: Duration::ZERO,
-            hard_links: 1,
+            // A directory is linked by its "." entry as well.
+            hard_links: if type_ == InodeType::Dir { 2 } else { 1 },
             blocks_count: 0,
-            flags: FileFlags::empty(),
-            block_ptrs: BlockPtrs::default(),
-            acl: match type_ {
-                InodeType::File | InodeType::Dir => Some(Bid::new(0)),
-                _ => None,
+            flags: if is_extent_mapped {
+                FileFlags::EXTENTS
+            } else {
+                FileFlags::empty()
+            },
+            block_ptrs: if is_extent_mapped {
+                ExtentTree::empty_root()
+            } else {
+                BlockPtrs::default()
             },
+            generation: 0,
+            acl: Some(Bid::new(0)),
         })
     }
 
@@ -2127,9 +2631,13 @@ impl InodeDesc {
     ///
     /// Ext2 allows the `block_count` to exceed the actual number of blocks utilized.
     pub fn blocks_count(&self) -> Ext2Bid {
-        let blocks = self.size_to_blocks(self.size);
-        assert!(blocks <= self.blocks_count);
-        blocks
+        self.size_to_blocks(self.size)
+    }
+
+    /// Returns whether the blocks are mapped by an extent tree.
+    pub fn is_extent_mapped(&self) -> bool {
+        self.flags.contains(FileFlags::EXTENTS)
+            && !(self.type_ == InodeType::SymLink && self.size <= MAX_FAST_SYMLINK_LEN)
     }
 
     #[inline]
@@ -2216,6 +2724,30 @@ bitflags! {
         const DIR_SYNC = 1 << 16;
         /// Top of directory hierarchies.
         const TOP_DIR = 1 << 17;
+        /// The block count is in blocks instead of sectors.
+        const HUGE_FILE = 1 << 18;
+        /// The blocks are mapped by extents.
+        const EXTENTS = 1 << 19;
+        /// Verity protected file.
+        const VERITY = 1 << 20;
+        /// Inode used for a large extended attribute.
+        const EA_INODE = 1 << 21;
+        /// Blocks allocated beyond EOF (obsolete).
+        const EOFBLOCKS = 1 << 22;
+        /// Snapshot file.
+        const SNAPFILE = 1 << 24;
+        /// Direct access file.
+        const DAX = 1 << 25;
+        /// Snapshot is being deleted.
+        const SNAPFILE_DELETED = 1 << 26;
+        /// Snapshot shrink has completed.
+        const SNAPFILE_SHRUNK = 1 << 27;
+        /// Inode has inline data.
+        const INLINE_DATA = 1 << 28;
+        /// Children inherit the project ID.
+        const PROJ_INHERIT = 1 << 29;
+        /// Case-insensitive directory.
+        const CASEFOLD = 1 << 30;
         /// Reserved for ext2 lib.
         const RESERVED = 1 << 31;
     }
@@ -2257,8 +2789,7 @@ pub(super) struct RawInode {
     /// In revision 1, File ACL.
     pub file_acl: u32,
     /// In revision 0, this field is reserved.
-    /// In revision 1, Upper 32 bits of file size (if feature bit set)
-    /// if it's a file, Directory ACL if it's a directory.
+    /// In revision 1, Upper 32 bits of file size (if feature bit set).
     pub size_high: u32,
     /// Fragment address.
     pub frag_addr: u32,
@@ -2268,28 +2799,27 @@ pub(super) struct RawInode {
 
 impl From<&InodeDesc> for RawInode {
     fn from(inode: &InodeDesc) -> Self {
+        let sectors_count = inode.blocks_count as u64 * (BLOCK_SIZE / SECTOR_SIZE) as u64;
+        let acl = inode.acl.map_or(0, |acl| acl.to_raw());
         Self {
             mode: inode.type_ as u16 | inode.perm.bits(),
             uid: inode.uid as u16,
             size_low: inode.size as u32,
+            size_high: (inode.size as u64 >> 32) as u32,
             atime: UnixTime::from(inode.atime),
             ctime: UnixTime::from(inode.ctime),
             mtime: UnixTime::from(inode.mtime),
             dtime: UnixTime::from(inode.dtime),
             gid: inode.gid as u16,
             hard_links: inode.hard_links,
-            blocks_count: inode.blocks_count,
-            flags: inode.flags.bits(),
+            blocks_count: sectors_count as u32,
+            flags: (inode.flags - FileFlags::HUGE_FILE).bits(),
             block_ptrs: inode.block_ptrs,
-            file_acl: match inode.acl {
-                Some(acl) if inode.type_ == InodeType::File => acl.to_raw() as u32,
-                _ => Default::default(),
-            },
-            size_high: match inode.acl {
-                Some(acl) if inode.type_ == InodeType::Dir => acl.to_raw() as u32,
-                _ => Default::default(),
-            },
+            generation: inode.generation,
+            file_acl: acl as u32,
             os_dependent_2: Osd2 {
+                blocks_high: (sectors_count >> 32) as u16,
+                file_acl_high: (acl >> 32) as u16,
                 uid_high: (inode.uid >> 16) as u16,
                 gid_high: (inode.gid >> 16) as u16,
                 ..Default::default()
@@ -2303,16 +2833,23 @@ impl From<&InodeDesc> for RawInode {
 #[repr(C)]
 #[derive(Clone, Copy, Default, Debug, Pod)]
 pub(super) struct Osd2 {
-    /// Fragment number.
-    pub frag_num: u8,
-    /// Fragment size.
-    pub frag_size: u8,
-    pad1: u16,
+    /// High 16 bits of the block count.
+    pub blocks_high: u16,
+    /// High 16 bits of File ACL.
+    pub file_acl_high: u16,
     /// High 16 bits of User Id.
     pub uid_high: u16,
     /// High 16 bits of Group Id.
     pub gid_high: u16,
-    reserved2: u32,
+    /// Lower 16 bits of the inode checksum.
+    pub checksum_lo: u16,
+    reserved: u16,
+}
+
+/// Returns the seed of the checksums of the inode and its metadata blocks.
+pub(super) fn inode_csum_seed(fs_csum_seed: u32, ino: u32, generation: u32) -> u32 {
+    let crc = crc32c(fs_csum_seed, &ino.to_le_bytes());
+    crc32c(crc, &generation.to_le_bytes())
 }
 
 fn is_block_aligned(offset: usize) -> bool {
@@ -0,0 +1,874 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The JBD2 journal of Ext3 and Ext4.
+//!
+//! The journal is a circular log stored in a reserved inode. Before the metadata
+//! blocks are written in place, their new contents are written to the log and
+//! followed by a commit block. If the system crashes while the blocks are being
+//! written in place, the committed transactions are replayed at the next mount.
+//!
+//! The metadata written between two syncs are collected in a running
+//! transaction, which is committed as a whole at the next sync. So the metadata
+//! updated by a single operation, such as the bitmaps, the inode and the
+//! directory entry of a new file, reach the device atomically.
+//!
+//! Only the metadata is journaled. The data blocks are written in place by the
+//! page cache and are not ordered with the commits, like the `data=writeback`
+//! mode of Linux. Still, a sync writes back the data of the files before it
+//! commits the running transaction.
+//!
+//! The transactions are checkpointed as soon as they are committed, so the log
+//! is empty whenever no transaction is in progress. This way, no revoke records
+//! are needed for the blocks that are freed and then reused for data.
+//!
+//! The checksums in the journal are neither verified nor written. They are
+//! disabled whenever the journal is emptied, and Linux enables them again if
+//! necessary.
+//!
+//! The on-disk format is described in
+//! <https://www.kernel.org/doc/html/latest/filesystems/ext4/journal.html>.
+
+use alloc::collections::btree_map::Entry;
+
+use super::{
+    block_group::RawGroupDescriptor,
+    block_ptr::{Ext2Bid, BID_SIZE, DIRECT_RANGE},
+    extent::{BlockMapping, ExtentTree},
+    inode::RawInode,
+    prelude::*,
+    super_block::SuperBlock,
+    utils::now,
+};
+
+/// The magic number of the journal blocks.
+const JOURNAL_MAGIC: u32 = 0xc03b3998;
+
+/// The inode flag of the files that are mapped by extents.
+const EXTENTS_FL: u32 = 0x80000;
+
+/// The JBD2 journal.
+pub(super) struct Journal {
+    block_device: Arc<dyn BlockDevice>,
+    /// The device block IDs of the journal blocks.
+    bids: Vec<Ext2Bid>,
+    /// The superblock of the journal, which is locked during a transaction.
+    super_block: Mutex<RawJournalSuperBlock>,
+}
+
+impl Journal {
+    /// Loads the journal of the filesystem described by `super_block`.
+    pub fn open(block_device: Arc<dyn BlockDevice>, super_block: &SuperBlock) -> Result<Self> {
+        if super_block.journal_ino() == 0 {
+            return_errno_with_message!(Errno::EINVAL, "external journals are not supported");
+        }
+
+        let bids = journal_bids(block_device.as_ref(), super_block)?;
+        if bids.is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "the journal is empty");
+        }
+
+        let journal_super_block = {
+            let mut raw =
+                block_device.read_val::<RawJournalSuperBlock>(bids[0] as usize * BLOCK_SIZE)?;
+            raw.check(bids.len())?;
+            // The version 1 superblock does not have the feature fields.
+            if raw.header.block_type.get() == BlockType::SuperBlockV1 as u32 {
+                raw.feature_compat = Be32::new(0);
+                raw.feature_incompat = Be32::new(0);
+                raw.feature_ro_compat = Be32::new(0);
+            }
+            raw
+        };
+
+        Ok(Self {
+            block_device,
+            bids,
+            super_block: Mutex::new(journal_super_block),
+        })
+    }
+
+    /// Replays the committed transactions in the journal, then empties it.
+    ///
+    /// Like Linux, if the filesystem does not need recovery, the journal has
+    /// already been replayed by other tools, so it is emptied without replay.
+    pub fn recover(&self, needs_recovery: bool) -> Result<()> {
+        let mut super_block = self.super_block.lock();
+        let mut sequence = super_block.sequence.get();
+        if super_block.start.get() != 0 {
+            let log = self.scan(&super_block)?;
+            if needs_recovery {
+                debug!(
+                    "ext2: replaying {} transactions from the journal",
+                    log.transactions.len()
+                );
+                self.replay(&log)?;
+            } else {
+                warn!("ext2: clearing the journal that does not need recovery");
+            }
+            sequence = log.end_sequence.wrapping_add(1);
+        }
+
+        self.reset(&mut super_block, sequence)
+    }
+
+    /// Commits the transaction and writes the metadata in place.
+    ///
+    /// The method returns after the metadata has been written in place. If the
+    /// transaction is too large for the journal, it is split into several ones.
+    pub fn commit(&self, transaction: &Transaction) -> Result<()> {
+        let mut super_block = self.super_block.lock();
+
+        let blocks: Vec<(Ext2Bid, Vec<u8>)> = transaction
+            .to_blocks(self.block_device.as_ref())?
+            .into_iter()
+            .collect();
+        let max_blocks = {
+            let log_len = (super_block.max_len.get() - super_block.first.get()) as usize;
+            let tags_per_block = super_block.tags_per_block();
+            (log_len - 2) * tags_per_block / (tags_per_block + 1)
+        };
+        for blocks in blocks.chunks(max_blocks) {
+            self.commit_blocks(&mut super_block, blocks)?;
+        }
+
+        Ok(())
+    }
+
+    fn commit_blocks(
+        &self,
+        super_block: &mut RawJournalSuperBlock,
+        blocks: &[(Ext2Bid, Vec<u8>)],
+    ) -> Result<()> {
+        let sequence = super_block.sequence.get();
+        let mut log_idx = super_block.first.get();
+
+        // Points the journal superblock to the transaction, then writes the
+        // descriptor blocks and the copies of the metadata blocks.
+        super_block.start = super_block.first;
+        let mut bio_waiter = self.write_super_block(super_block)?;
+        for blocks in blocks.chunks(super_block.tags_per_block()) {
+            let descriptor_block = super_block.new_descriptor_block(sequence, blocks);
+            bio_waiter.concat(self.write_log_block(log_idx, &descriptor_block)?);
+            log_idx = super_block.next_log_idx(log_idx);
+
+            for (_, buf) in blocks {
+                let waiter = if is_escaped(buf) {
+                    let mut buf = buf.clone();
+                    buf[..4].fill(0);
+                    self.write_log_block(log_idx, &buf)?
+                } else {
+                    self.write_log_block(log_idx, buf)?
+                };
+                bio_waiter.concat(waiter);
+                log_idx = super_block.next_log_idx(log_idx);
+            }
+        }
+        wait_and_flush(self.block_device.as_ref(), bio_waiter)?;
+
+        // The transaction is committed once the commit block is on the device.
+        let commit_block = new_commit_block(sequence);
+        let bio_waiter = self.write_log_block(log_idx, &commit_block)?;
+        wait_and_flush(self.block_device.as_ref(), bio_waiter)?;
+
+        // Checkpoints the transaction.
+        let mut bio_waiter = BioWaiter::new();
+        for (bid, buf) in blocks {
+            bio_waiter.concat(
+                self.block_device
+                    .write_bytes_async(*bid as usize * BLOCK_SIZE, buf)?,
+            );
+        }
+        wait_and_flush(self.block_device.as_ref(), bio_waiter)?;
+
+        self.reset(super_block, sequence.wrapping_add(1))
+    }
+
+    /// Marks the journal as empty, with `sequence` as the sequence number of
+    /// the next transaction.
+    fn reset(&self, super_block: &mut RawJournalSuperBlock, sequence: u32) -> Result<()> {
+        super_block.start = Be32::new(0);
+        super_block.sequence = Be32::new(sequence);
+        super_block.feature_compat = Be32::new(0);
+        super_block.feature_incompat =
+            Be32::new(super_block.feature_incompat().bits() & JournalFeatures::WRITABLE.bits());
+
+        let bio_waiter = self.write_super_block(super_block)?;
+        wait_and_flush(self.block_device.as_ref(), bio_waiter)
+    }
+
+    /// Scans the log for the committed transactions and the revoke records.
+    fn scan(&self, super_block: &RawJournalSuperBlock) -> Result<Log> {
+        let features = super_block.feature_incompat();
+        let mut log = Log::default();
+        let mut sequence = super_block.sequence.get();
+        let mut log_idx = super_block.start.get();
+        let mut logged_blocks = Vec::new();
+        let mut revoked_bids = Vec::new();
+
+        let log_len = super_block.max_len.get() - super_block.first.get();
+        let mut nblocks_scanned = 0;
+        while nblocks_scanned < log_len {
+            let buf = self.read_log_block(log_idx)?;
+            let header = RawHeader::from_bytes(&buf[..core::mem::size_of::<RawHeader>()]);
+            if header.magic.get() != JOURNAL_MAGIC || header.sequence.get() != sequence {
+                break;
+            }
+
+            log_idx = super_block.next_log_idx(log_idx);
+            nblocks_scanned += 1;
+            match BlockType::try_from(header.block_type.get()) {
+                Ok(BlockType::Descriptor) => {
+                    for (bid, flags) in parse_tags(&buf, features)? {
+                        logged_blocks.push(LoggedBlock {
+                            bid,
+                            log_idx,
+                            is_escaped: flags.contains(TagFlags::ESCAPE),
+                        });
+                        log_idx = super_block.next_log_idx(log_idx);
+                        nblocks_scanned += 1;
+                    }
+                }
+                Ok(BlockType::Revoke) => {
+                    revoked_bids.extend(parse_revoke_records(&buf, features)?);
+                }
+                Ok(BlockType::Commit) => {
+                    log.transactions
+                        .push((sequence, core::mem::take(&mut logged_blocks)));
+                    for bid in revoked_bids.drain(..) {
+                        // The sequence number increases, so the last revoking
+                        // transaction is recorded.
+                        log.revoked.insert(bid, sequence);
+                    }
+                    sequence = sequence.wrapping_add(1);
+                }
+                _ => break,
+            }
+        }
+
+        log.end_sequence = sequence;
+        Ok(log)
+    }
+
+    /// Writes the blocks in the committed transactions in place.
+    fn replay(&self, log: &Log) -> Result<()> {
+        let mut bio_waiter = BioWaiter::new();
+        for (sequence, logged_blocks) in log.transactions.iter() {
+            for logged_block in logged_blocks {
+                if log.is_revoked(logged_block.bid, *sequence) {
+                    continue;
+                }
+
+                let mut buf = self.read_log_block(logged_block.log_idx)?;
+                if logged_block.is_escaped {
+                    buf[..4].copy_from_slice(&JOURNAL_MAGIC.to_be_bytes());
+                }
+                bio_waiter.concat(
+                    self.block_device
+                        .write_bytes_async(logged_block.bid as usize * BLOCK_SIZE, &buf)?,
+                );
+            }
+        }
+        wait_and_flush(self.block_device.as_ref(), bio_waiter)
+    }
+
+    fn read_log_block(&self, log_idx: u32) -> Result<Vec<u8>> {
+        let mut buf = vec![0u8; BLOCK_SIZE];
+        self.block_device
+            .read_bytes(self.bids[log_idx as usize] as usize * BLOCK_SIZE, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn write_log_block(&self, log_idx: u32, buf: &[u8]) -> Result<BioWaiter> {
+        let bio_waiter = self
+            .block_device
+            .write_bytes_async(self.bids[log_idx as usize] as usize * BLOCK_SIZE, buf)?;
+        Ok(bio_waiter)
+    }
+
+    fn write_super_block(&self, super_block: &RawJournalSuperBlock) -> Result<BioWaiter> {
+        let bio_waiter = self
+            .block_device
+            .write_bytes_async(self.bids[0] as usize * BLOCK_SIZE, super_block.as_bytes())?;
+        Ok(bio_waiter)
+    }
+}
+
+impl Debug for Journal {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let super_block = self.super_block.lock();
+        f.debug_struct("Journal")
+            .field("len", &self.bids.len())
+            .field("sequence", &super_block.sequence.get())
+            .field("start", &super_block.start.get())
+            .finish()
+    }
+}
+
+/// A set of metadata writes that reach the device atomically if the filesystem
+/// has a journal.
+#[derive(Debug, Default)]
+pub(super) struct Transaction {
+    /// The device offsets and the bytes to write.
+    writes: Vec<(usize, Vec<u8>)>,
+}
+
+impl Transaction {
+    /// Creates an empty transaction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the write of `buf` at the `offset` of the device.
+    ///
+    /// Both the `offset` and the length of `buf` must be sector-aligned.
+    pub fn write_bytes(&mut self, offset: usize, buf: &[u8]) {
+        self.push(offset, buf.to_vec());
+    }
+
+    /// Adds the write of the contents of `frame` to the block `bid`.
+    pub fn write_block<F: VmIo>(&mut self, bid: Ext2Bid, frame: &F) -> Result<()> {
+        let mut buf = vec![0u8; BLOCK_SIZE];
+        frame.read_bytes(0, &mut buf)?;
+        self.push(bid as usize * BLOCK_SIZE, buf);
+        Ok(())
+    }
+
+    /// Adds the writes of `other` after the writes of this transaction.
+    pub fn append(&mut self, other: Transaction) {
+        for (offset, buf) in other.writes {
+            self.push(offset, buf);
+        }
+    }
+
+    /// Drops the writes to the blocks in the `range`.
+    ///
+    /// The blocks are freed, so their contents no longer matter.
+    pub fn discard(&mut self, range: Range<Ext2Bid>) {
+        let range = range.start as usize * BLOCK_SIZE..range.end as usize * BLOCK_SIZE;
+        self.writes
+            .retain(|(offset, buf)| *offset < range.start || offset + buf.len() > range.end);
+    }
+
+    /// Returns the contents of the block `bid` if the whole block is written.
+    pub fn read_block(&self, bid: Ext2Bid) -> Option<Vec<u8>> {
+        let offset = bid as usize * BLOCK_SIZE;
+        self.writes
+            .iter()
+            .rev()
+            .find(|(write_offset, buf)| *write_offset == offset && buf.len() == BLOCK_SIZE)
+            .map(|(_, buf)| buf.clone())
+    }
+
+    /// Returns whether there are no writes.
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Adds a write, dropping the previous writes that it overwrites.
+    ///
+    /// A block is often written many times before the transaction is
+    /// committed, and only the last contents are needed.
+    fn push(&mut self, offset: usize, buf: Vec<u8>) {
+        let end = offset + buf.len();
+        self.writes.retain(|(write_offset, write_buf)| {
+            *write_offset < offset || write_offset + write_buf.len() > end
+        });
+        self.writes.push((offset, buf));
+    }
+
+    /// Writes the metadata in place without journaling.
+    pub fn write_in_place(&self, block_device: &dyn BlockDevice) -> Result<BioWaiter> {
+        let mut bio_waiter = BioWaiter::new();
+        for (offset, buf) in self.writes.iter() {
+            bio_waiter.concat(block_device.write_bytes_async(*offset, buf)?);
+        }
+        Ok(bio_waiter)
+    }
+
+    /// Merges the writes into the images of whole blocks.
+    ///
+    /// The parts of the blocks that are not written are read from the device.
+    fn to_blocks(&self, block_device: &dyn BlockDevice) -> Result<BTreeMap<Ext2Bid, Vec<u8>>> {
+        let mut blocks: BTreeMap<Ext2Bid, Vec<u8>> = BTreeMap::new();
+        for (offset, buf) in self.writes.iter() {
+            let mut offset = *offset;
+            let mut buf = buf.as_slice();
+            while !buf.is_empty() {
+                let bid = (offset / BLOCK_SIZE) as Ext2Bid;
+                let offset_in_block = offset % BLOCK_SIZE;
+                let len = buf.len().min(BLOCK_SIZE - offset_in_block);
+
+                let block = match blocks.entry(bid) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let mut block = vec![0u8; BLOCK_SIZE];
+                        if len < BLOCK_SIZE {
+                            block_device.read_bytes(bid as usize * BLOCK_SIZE, &mut block)?;
+                        }
+                        entry.insert(block)
+                    }
+                };
+                block[offset_in_block..offset_in_block + len].copy_from_slice(&buf[..len]);
+
+                offset += len;
+                buf = &buf[len..];
+            }
+        }
+        Ok(blocks)
+    }
+}
+
+/// The committed transactions found in the log.
+#[derive(Default)]
+struct Log {
+    /// The sequence numbers and the logged blocks of the transactions.
+    transactions: Vec<(u32, Vec<LoggedBlock>)>,
+    /// The revoked blocks and the sequence numbers of the last transactions
+    /// that revoke them.
+    revoked: BTreeMap<Ext2Bid, u32>,
+    /// The sequence number of the first uncommitted transaction.
+    end_sequence: u32,
+}
+
+impl Log {
+    /// Returns whether the block `bid` logged in the transaction `sequence` is
+    /// revoked by the same or a later transaction.
+    fn is_revoked(&self, bid: Ext2Bid, sequence: u32) -> bool {
+        self.revoked
+            .get(&bid)
+            .is_some_and(|revoke_sequence| revoke_sequence.wrapping_sub(sequence) as i32 >= 0)
+    }
+}
+
+/// A copy of a metadata block in the log.
+struct LoggedBlock {
+    /// The block on the device.
+    bid: Ext2Bid,
+    /// The index of the copy in the log.
+    log_idx: u32,
+    /// Whether the first four bytes of the copy are zeroed because the block
+    /// starts with the magic number.
+    is_escaped: bool,
+}
+
+/// Returns the device block IDs of the journal inode.
+fn journal_bids(block_device: &dyn BlockDevice, super_block: &SuperBlock) -> Result<Vec<Ext2Bid>> {
+    let raw_inode = {
+        let inode_idx = (super_block.journal_ino() - 1) % super_block.inodes_per_group();
+        let block_group_idx = (super_block.journal_ino() - 1) / super_block.inodes_per_group();
+        if block_group_idx >= super_block.block_groups_count() {
+            return_errno_with_message!(Errno::EINVAL, "invalid journal inode");
+        }
+
+        // The device is read in blocks, since the descriptors and the inodes
+        // are not aligned to the sectors.
+        let mut buf = vec![0u8; BLOCK_SIZE];
+        let descriptor_offset = super_block.group_descriptors_bid(0).to_offset()
+            + block_group_idx as usize * super_block.desc_size();
+        block_device.read_bytes(descriptor_offset.align_down(BLOCK_SIZE), &mut buf)?;
+        let descriptor = {
+            let mut descriptor = RawGroupDescriptor::new_zeroed();
+            let offset = descriptor_offset % BLOCK_SIZE;
+            let len = super_block.desc_size();
+            descriptor.as_bytes_mut()[..len].copy_from_slice(&buf[offset..offset + len]);
+            descriptor
+        };
+
+        let inode_offset = descriptor.inode_table as usize * BLOCK_SIZE
+            + inode_idx as usize * super_block.inode_size();
+        block_device.read_bytes(inode_offset.align_down(BLOCK_SIZE), &mut buf)?;
+        let offset = inode_offset % BLOCK_SIZE;
+        RawInode::from_bytes(&buf[offset..offset + core::mem::size_of::<RawInode>()])
+    };
+
+    let nblocks = raw_inode.size_low as usize / BLOCK_SIZE;
+    let mut bids = Vec::with_capacity(nblocks);
+    let block_ptrs = raw_inode.block_ptrs;
+    if raw_inode.flags & EXTENTS_FL != 0 {
+        let read_block = |bid: Ext2Bid, buf: &mut [u8]| -> Result<()> {
+            block_device.read_bytes(bid as usize * BLOCK_SIZE, buf)?;
+            Ok(())
+        };
+        let extent_tree = ExtentTree::load(&block_ptrs, &read_block, None)?;
+        while bids.len() < nblocks {
+            let bid = bids.len() as Ext2Bid;
+            match extent_tree.map(bid, (nblocks - bids.len()) as Ext2Bid) {
+                BlockMapping::Mapped { device_range, .. } => bids.extend(device_range),
+                BlockMapping::Hole(_) => {
+                    return_errno_with_message!(Errno::EINVAL, "the journal has holes")
+                }
+            }
+        }
+        return Ok(bids);
+    }
+
+    for idx in DIRECT_RANGE {
+        bids.push(block_ptrs.direct(idx));
+    }
+    for (level, bid) in [
+        (1, block_ptrs.indirect()),
+        (2, block_ptrs.db_indirect()),
+        (3, block_ptrs.tb_indirect()),
+    ] {
+        if bids.len() >= nblocks {
+            break;
+        }
+        push_indirect_bids(block_device, bid, level, nblocks, &mut bids)?;
+    }
+    bids.truncate(nblocks);
+
+    if bids.iter().any(|bid| *bid == 0) {
+        return_errno_with_message!(Errno::EINVAL, "the journal has holes");
+    }
+    Ok(bids)
+}
+
+/// Pushes the block IDs that are referred by the indirect block `bid` of
+/// `level` into `bids`, until there are `nblocks` block IDs.
+fn push_indirect_bids(
+    block_device: &dyn BlockDevice,
+    bid: Ext2Bid,
+    level: usize,
+    nblocks: usize,
+    bids: &mut Vec<Ext2Bid>,
+) -> Result<()> {
+    let mut buf = vec![0u8; BLOCK_SIZE];
+    block_device.read_bytes(bid as usize * BLOCK_SIZE, &mut buf)?;
+    for raw_bid in buf.chunks_exact(BID_SIZE) {
+        if bids.len() >= nblocks {
+            break;
+        }
+
+        let bid = Ext2Bid::from_le_bytes(raw_bid.try_into().unwrap());
+        if level == 1 {
+            bids.push(bid);
+        } else {
+            push_indirect_bids(block_device, bid, level - 1, nblocks, bids)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parses the tags in the descriptor block, and returns the block IDs and the
+/// flags of the logged blocks.
+fn parse_tags(buf: &[u8], features: JournalFeatures) -> Result<Vec<(Ext2Bid, TagFlags)>> {
+    let tag_size = features.tag_size();
+    let end = BLOCK_SIZE - features.tail_size();
+
+    let mut tags = Vec::new();
+    let mut offset = core::mem::size_of::<RawHeader>();
+    while offset + tag_size <= end {
+        let tag = &buf[offset..offset + tag_size];
+        let flags = if features.contains(JournalFeatures::CSUM_V3) {
+            read_be32(tag, 4)
+        } else {
+            u16::from_be_bytes([tag[6], tag[7]]) as u32
+        };
+        let flags = TagFlags::from_bits_truncate(flags);
+        if features.contains(JournalFeatures::BIT64) && read_be32(tag, 8) != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the logged block is out of range");
+        }
+        tags.push((read_be32(tag, 0), flags));
+
+        offset += tag_size;
+        if !flags.contains(TagFlags::SAME_UUID) {
+            offset += 16;
+        }
+        if flags.contains(TagFlags::LAST_TAG) {
+            break;
+        }
+    }
+    Ok(tags)
+}
+
+/// Parses the records in the revoke block, and returns the revoked block IDs.
+fn parse_revoke_records(buf: &[u8], features: JournalFeatures) -> Result<Vec<Ext2Bid>> {
+    let record_size = if features.contains(JournalFeatures::BIT64) {
+        8
+    } else {
+        4
+    };
+    let start = core::mem::size_of::<RawHeader>() + 4;
+    let end = (read_be32(buf, core::mem::size_of::<RawHeader>()) as usize)
+        .min(BLOCK_SIZE - features.tail_size());
+
+    let mut bids = Vec::new();
+    for record in buf[start.min(end)..end].chunks_exact(record_size) {
+        let bid = if record_size == 8 {
+            if read_be32(record, 0) != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the revoked block is out of range");
+            }
+            read_be32(record, 4)
+        } else {
+            read_be32(record, 0)
+        };
+        bids.push(bid);
+    }
+    Ok(bids)
+}
+
+fn new_commit_block(sequence: u32) -> Vec<u8> {
+    let mut buf = vec![0u8; BLOCK_SIZE];
+    let header = RawHeader::new(BlockType::Commit, sequence);
+    buf[..core::mem::size_of::<RawHeader>()].copy_from_slice(header.as_bytes());
+
+    let now = now();
+    buf[COMMIT_SEC_OFFSET..COMMIT_SEC_OFFSET + 8].copy_from_slice(&now.as_secs().to_be_bytes());
+    buf[COMMIT_NSEC_OFFSET..COMMIT_NSEC_OFFSET + 4]
+        .copy_from_slice(&now.subsec_nanos().to_be_bytes());
+    buf
+}
+
+/// The offset of the commit time in seconds in the commit block.
+const COMMIT_SEC_OFFSET: usize = 0x30;
+/// The offset of the nanoseconds of the commit time in the commit block.
+const COMMIT_NSEC_OFFSET: usize = 0x38;
+
+/// Returns whether the block has to be escaped in the log, because it starts
+/// with the magic number and could be mistaken as a journal block.
+fn is_escaped(buf: &[u8]) -> bool {
+    read_be32(buf, 0) == JOURNAL_MAGIC
+}
+
+fn read_be32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+/// Waits for the completion of the writes, then flushes the volatile write
+/// cache of the device so that the writes are durable.
+fn wait_and_flush(block_device: &dyn BlockDevice, bio_waiter: BioWaiter) -> Result<()> {
+    bio_waiter
+        .wait()
+        .ok_or_else(|| Error::with_message(Errno::EIO, "failed to write the journal"))?;
+    match block_device.sync()? {
+        BioStatus::Complete => Ok(()),
+        err_status => Err(Error::from(err_status)),
+    }
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, TryFromInt)]
+enum BlockType {
+    Descriptor = 1,
+    Commit = 2,
+    SuperBlockV1 = 3,
+    SuperBlockV2 = 4,
+    Revoke = 5,
+}
+
+bitflags! {
+    /// Incompatible features of the journal.
+    struct JournalFeatures: u32 {
+        /// The journal has revoke records.
+        const REVOKE = 1 << 0;
+        /// The logged blocks may have 64-bit block numbers.
+        const BIT64 = 1 << 1;
+        /// The commit blocks may be written without waiting for the descriptor blocks.
+        const ASYNC_COMMIT = 1 << 2;
+        /// The journal blocks have version 2 checksums.
+        const CSUM_V2 = 1 << 3;
+        /// The journal blocks have version 3 checksums.
+        const CSUM_V3 = 1 << 4;
+        /// The journal has fast commits.
+        const FAST_COMMIT = 1 << 5;
+
+        /// The features that can be replayed.
+        const SUPPORTED = Self::REVOKE.bits
+            | Self::BIT64.bits
+            | Self::ASYNC_COMMIT.bits
+            | Self::CSUM_V2.bits
+            | Self::CSUM_V3.bits;
+        /// The features that are kept when transactions are written.
+        const WRITABLE = Self::REVOKE.bits | Self::BIT64.bits;
+    }
+}
+
+impl JournalFeatures {
+    /// Returns the size of a tag in the descriptor blocks.
+    fn tag_size(&self) -> usize {
+        if self.contains(Self::CSUM_V3) {
+            return 16;
+        }
+
+        let mut size = 12;
+        if self.contains(Self::CSUM_V2) {
+            size += 2;
+        }
+        if !self.contains(Self::BIT64) {
+            size -= 4;
+        }
+        size
+    }
+
+    /// Returns the size of the checksum at the end of the descriptor and
+    /// revoke blocks.
+    fn tail_size(&self) -> usize {
+        if self.intersects(Self::CSUM_V2 | Self::CSUM_V3) {
+            4
+        } else {
+            0
+        }
+    }
+}
+
+bitflags! {
+    /// Flags of a tag in the descriptor blocks.
+    struct TagFlags: u32 {
+        /// The first four bytes of the logged block are zeroed.
+        const ESCAPE = 1 << 0;
+        /// The tag is not followed by the UUID.
+        const SAME_UUID = 1 << 1;
+        /// The block is deleted by this transaction.
+        const DELETED = 1 << 2;
+        /// The tag is the last one in the descriptor block.
+        const LAST_TAG = 1 << 3;
+    }
+}
+
+/// A big-endian `u32`, which is the byte order of the journal.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct Be32([u8; 4]);
+
+impl Be32 {
+    fn new(value: u32) -> Self {
+        Self(value.to_be_bytes())
+    }
+
+    fn get(&self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+}
+
+/// The header of the journal blocks.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct RawHeader {
+    magic: Be32,
+    block_type: Be32,
+    sequence: Be32,
+}
+
+impl RawHeader {
+    fn new(block_type: BlockType, sequence: u32) -> Self {
+        Self {
+            magic: Be32::new(JOURNAL_MAGIC),
+            block_type: Be32::new(block_type as u32),
+            sequence: Be32::new(sequence),
+        }
+    }
+}
+
+const_assert!(core::mem::size_of::<RawJournalSuperBlock>() == 1024);
+
+/// The raw journal superblock, which is located at the first block of the journal.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawJournalSuperBlock {
+    header: RawHeader,
+    block_size: Be32,
+    /// The number of blocks in the journal.
+    max_len: Be32,
+    /// The first block of the log.
+    first: Be32,
+    /// The sequence number of the first transaction in the log.
+    sequence: Be32,
+    /// The block of the first transaction in the log, or zero if the log is empty.
+    start: Be32,
+    errno: Be32,
+    feature_compat: Be32,
+    feature_incompat: Be32,
+    feature_ro_compat: Be32,
+    uuid: [u8; 16],
+    reserved: [u32; 240],
+}
+
+impl RawJournalSuperBlock {
+    /// Checks whether the journal can be used.
+    fn check(&self, nblocks: usize) -> Result<()> {
+        if self.header.magic.get() != JOURNAL_MAGIC {
+            return_errno_with_message!(Errno::EINVAL, "bad journal magic number");
+        }
+        let block_type = self.header.block_type.get();
+        if block_type != BlockType::SuperBlockV1 as u32
+            && block_type != BlockType::SuperBlockV2 as u32
+        {
+            return_errno_with_message!(Errno::EINVAL, "bad journal superblock");
+        }
+        if self.block_size.get() as usize != BLOCK_SIZE {
+            return_errno_with_message!(Errno::EINVAL, "bad journal block size");
+        }
+
+        let max_len = self.max_len.get() as usize;
+        let first = self.first.get() as usize;
+        if max_len > nblocks || first == 0 || first + 2 >= max_len {
+            return_errno_with_message!(Errno::EINVAL, "bad journal length");
+        }
+        let start = self.start.get() as usize;
+        if start != 0 && !(first..max_len).contains(&start) {
+            return_errno_with_message!(Errno::EINVAL, "bad journal start");
+        }
+
+        if block_type == BlockType::SuperBlockV2 as u32
+            && self.feature_incompat.get() & !JournalFeatures::SUPPORTED.bits() != 0
+        {
+            return_errno_with_message!(Errno::EINVAL, "unsupported journal features");
+        }
+        Ok(())
+    }
+
+    fn feature_incompat(&self) -> JournalFeatures {
+        JournalFeatures::from_bits_truncate(self.feature_incompat.get())
+    }
+
+    /// Returns the maximum number of tags in a descriptor block.
+    fn tags_per_block(&self) -> usize {
+        let features = self.feature_incompat();
+        // The UUID follows the first tag.
+        (BLOCK_SIZE - core::mem::size_of::<RawHeader>() - 16 - features.tail_size())
+            / features.tag_size()
+    }
+
+    /// Returns the index of the log block after `log_idx`.
+    fn next_log_idx(&self, log_idx: u32) -> u32 {
+        if log_idx + 1 >= self.max_len.get() {
+            self.first.get()
+        } else {
+            log_idx + 1
+        }
+    }
+
+    /// Creates a descriptor block with the tags of `blocks`.
+    fn new_descriptor_block(&self, sequence: u32, blocks: &[(Ext2Bid, Vec<u8>)]) -> Vec<u8> {
+        let features = self.feature_incompat();
+        debug_assert!(!features.intersects(JournalFeatures::CSUM_V2 | JournalFeatures::CSUM_V3));
+        let tag_size = features.tag_size();
+
+        let mut buf = vec![0u8; BLOCK_SIZE];
+        let header = RawHeader::new(BlockType::Descriptor, sequence);
+        buf[..core::mem::size_of::<RawHeader>()].copy_from_slice(header.as_bytes());
+
+        let mut offset = core::mem::size_of::<RawHeader>();
+        for (idx, (bid, block)) in blocks.iter().enumerate() {
+            let mut flags = TagFlags::empty();
+            if idx != 0 {
+                flags |= TagFlags::SAME_UUID;
+            }
+            if idx == blocks.len() - 1 {
+                flags |= TagFlags::LAST_TAG;
+            }
+            if is_escaped(block) {
+                flags |= TagFlags::ESCAPE;
+            }
+
+            buf[offset..offset + 4].copy_from_slice(&bid.to_be_bytes());
+            buf[offset + 6..offset + 8].copy_from_slice(&(flags.bits() as u16).to_be_bytes());
+            offset += tag_size;
+            if idx == 0 {
+                buf[offset..offset + 16].copy_from_slice(&self.uuid);
+                offset += 16;
+            }
+        }
+        buf
+    }
+}
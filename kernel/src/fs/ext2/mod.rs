@@ -14,6 +14,12 @@
 //!    stored in PageCache, which accelerates the performance of data access.
 //! 3. Compatible with queue-based block device. The filesystem can submits multiple
 //!    BIO requests to be block device at once, thereby enhancing I/O performance.
+//! 4. Compatible with the journal of Ext3. The journal is replayed at mount time,
+//!    and the metadata updates are journaled in the ordered mode.
+//! 5. Compatible with the images of Ext4. The extent trees, the 64-bit descriptors,
+//!    the flexible block groups and the metadata checksums are supported.
+//! 6. Supports extended attributes and POSIX ACLs, which are stored in the inodes
+//!    and in the attribute blocks.
 //!
 //! # Example
 //!
@@ -35,6 +41,10 @@
 //! Here we summarizes the features that need to be implemented in the future.
 //! 1. Supports merging small read/write operations.
 //! 2. Handles the intermediate failure status correctly.
+//! 3. Supports the filesystems with more than 2^32 blocks.
+//! 4. Maintains the hash indexes of directories, which are dropped on modification.
+//! 5. Verifies the checksums of directory blocks, which are only updated now.
+//! 6. Shares the identical attribute blocks among inodes, which are only copied on write now.
 
 pub use fs::Ext2;
 pub use inode::{FilePerm, Inode};
@@ -43,10 +53,13 @@ pub use super_block::{SuperBlock, MAGIC_NUM};
 mod block_group;
 mod block_ptr;
 mod dir;
+mod extent;
 mod fs;
 mod impl_for_vfs;
 mod indirect_block_cache;
 mod inode;
+mod journal;
 mod prelude;
 mod super_block;
 mod utils;
+mod xattr;
@@ -9,7 +9,7 @@ pub(super) use align_ext::AlignExt;
 pub(super) use aster_block::{
     bio::{BioDirection, BioSegment, BioStatus, BioWaiter},
     id::Bid,
-    BlockDevice, BLOCK_SIZE,
+    BlockDevice, BLOCK_SIZE, SECTOR_SIZE,
 };
 pub(super) use aster_rights::Full;
 pub(super) use ostd::{
@@ -1,6 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::{inode::RawInode, prelude::*};
+use super::{
+    inode::RawInode,
+    prelude::*,
+    utils::{crc16, crc32c},
+};
 
 /// The magic number of Ext2.
 pub const MAGIC_NUM: u16 = 0xef53;
@@ -10,6 +14,17 @@ pub const SUPER_BLOCK_OFFSET: usize = 1024;
 
 const SUPER_BLOCK_SIZE: usize = 1024;
 
+/// The size of group descriptors if the `FeatureInCompatSet::BIT64` is not set.
+const DESC_SIZE: usize = 32;
+
+/// The size of group descriptors if the `FeatureInCompatSet::BIT64` is set.
+///
+/// Larger descriptors are valid on disk, but no fields are defined beyond it.
+const DESC_SIZE_64BIT: usize = 64;
+
+/// The checksum type of CRC32C, which is the only one defined.
+const CHECKSUM_TYPE_CRC32C: u8 = 1;
+
 /// The in-memory rust superblock.
 ///
 /// It contains all information about the layout of the Ext2.
@@ -18,6 +33,9 @@ pub struct SuperBlock {
     /// Total number of inodes.
     inodes_count: u32,
     /// Total number of blocks.
+    ///
+    /// Block numbers are 32-bit in memory, so a filesystem with the
+    /// `FeatureInCompatSet::BIT64` can be opened if it has no more blocks than that.
     blocks_count: u32,
     /// Total number of reserved blocks.
     reserved_blocks_count: u32,
@@ -91,17 +109,78 @@ pub struct SuperBlock {
     prealloc_file_blocks: u8,
     /// Number of blocks to preallocate for directories.
     prealloc_dir_blocks: u8,
+    ///
+    /// This fields are valid if the FeatureCompatSet::HAS_JOURNAL is set.
+    ///
+    /// Uuid of journal superblock.
+    journal_uuid: [u8; 16],
+    /// Inode number of journal file.
+    journal_ino: u32,
+    /// Device number of journal file.
+    journal_dev: u32,
+    /// Start of list of inodes to delete.
+    last_orphan: u32,
+    /// Number of blocks reserved for the growth of group descriptor table.
+    reserved_gdt_blocks: u16,
+    /// Size of group descriptors.
+    desc_size: usize,
+    /// Size of the extra fields that new inodes should have.
+    want_extra_isize: u16,
+    /// Number of block groups in a flexible block group, as a power of 2.
+    log_groups_per_flex: u8,
+    /// Checksum seed, valid if the FeatureInCompatSet::CSUM_SEED is set.
+    checksum_seed: u32,
+    //
+    // The remaining fields are not used, but they are kept to be written back.
+    //
+    min_rev_level: u16,
+    algorithm_usage_bitmap: u32,
+    hash_seed: [u32; 4],
+    def_hash_version: u8,
+    jnl_backup_type: u8,
+    default_mount_opts: u32,
+    first_meta_bg: u32,
+    mkfs_time: UnixTime,
+    jnl_blocks: [u32; 17],
+    min_extra_isize: u16,
+    flags: u32,
+    raid_and_mmp: [u32; 4],
+    encryption_level: u8,
+    reserved1: [u32; 62],
+    reserved2: [u32; 98],
 }
 
 impl TryFrom<RawSuperBlock> for SuperBlock {
     type Error = crate::error::Error;
 
     fn try_from(sb: RawSuperBlock) -> Result<Self> {
+        let feature_incompat = FeatureInCompatSet::from_bits(sb.feature_incompat).ok_or(
+            Error::with_message(Errno::EINVAL, "invalid feature incompat set"),
+        )?;
+        let feature_ro_compat = FeatureRoCompatSet::from_bits(sb.feature_ro_compat).ok_or(
+            Error::with_message(Errno::EINVAL, "invalid feature ro compat set"),
+        )?;
+        let is_64bit = feature_incompat.contains(FeatureInCompatSet::BIT64);
+        let blocks = |lo: u32, hi: u32| -> Result<u32> {
+            if is_64bit && hi != 0 {
+                return_errno_with_message!(Errno::EINVAL, "too many blocks");
+            }
+            Ok(lo)
+        };
+        if feature_ro_compat.contains(FeatureRoCompatSet::METADATA_CSUM) {
+            if sb.checksum_type != CHECKSUM_TYPE_CRC32C {
+                return_errno_with_message!(Errno::EINVAL, "unknown checksum type");
+            }
+            if sb.checksum != sb.calc_checksum() {
+                return_errno_with_message!(Errno::EBADMSG, "bad superblock checksum");
+            }
+        }
+
         Ok(Self {
             inodes_count: sb.inodes_count,
-            blocks_count: sb.blocks_count,
-            reserved_blocks_count: sb.reserved_blocks_count,
-            free_blocks_count: sb.free_blocks_count,
+            blocks_count: blocks(sb.blocks_count, sb.blocks_count_hi)?,
+            reserved_blocks_count: blocks(sb.reserved_blocks_count, sb.reserved_blocks_count_hi)?,
+            free_blocks_count: blocks(sb.free_blocks_count, sb.free_blocks_count_hi)?,
             free_inodes_count: sb.free_inodes_count,
             first_data_block: Bid::new(sb.first_data_block as _),
             block_size: 1024 << sb.log_block_size,
@@ -158,20 +237,65 @@ impl TryFrom<RawSuperBlock> for SuperBlock {
                 inode_size
             },
             block_group_idx: sb.block_group_idx as _,
-            feature_compat: FeatureCompatSet::from_bits(sb.feature_compat).ok_or(
-                Error::with_message(Errno::EINVAL, "invalid feature compat set"),
-            )?,
-            feature_incompat: FeatureInCompatSet::from_bits(sb.feature_incompat).ok_or(
-                Error::with_message(Errno::EINVAL, "invalid feature incompat set"),
-            )?,
-            feature_ro_compat: FeatureRoCompatSet::from_bits(sb.feature_ro_compat).ok_or(
-                Error::with_message(Errno::EINVAL, "invalid feature ro compat set"),
-            )?,
+            feature_compat: {
+                let features = FeatureCompatSet::from_bits(sb.feature_compat).ok_or(
+                    Error::with_message(Errno::EINVAL, "invalid feature compat set"),
+                )?;
+                if !FeatureCompatSet::SUPPORTED.contains(features) {
+                    return_errno_with_message!(Errno::EINVAL, "unsupported feature compat set");
+                }
+                features
+            },
+            feature_incompat: {
+                if !FeatureInCompatSet::SUPPORTED.contains(feature_incompat) {
+                    return_errno_with_message!(Errno::EINVAL, "unsupported feature incompat set");
+                }
+                feature_incompat
+            },
+            feature_ro_compat: {
+                if !FeatureRoCompatSet::SUPPORTED.contains(feature_ro_compat) {
+                    return_errno_with_message!(Errno::EINVAL, "unsupported feature ro compat set");
+                }
+                feature_ro_compat
+            },
             uuid: sb.uuid,
             volume_name: sb.volume_name,
             last_mounted_dir: sb.last_mounted_dir,
             prealloc_file_blocks: sb.prealloc_file_blocks,
             prealloc_dir_blocks: sb.prealloc_dir_blocks,
+            journal_uuid: sb.journal_uuid,
+            journal_ino: sb.journal_ino,
+            journal_dev: sb.journal_dev,
+            last_orphan: sb.last_orphan,
+            reserved_gdt_blocks: sb.reserved_gdt_blocks,
+            desc_size: {
+                if !is_64bit {
+                    DESC_SIZE
+                } else {
+                    if sb.desc_size as usize != DESC_SIZE_64BIT {
+                        return_errno_with_message!(Errno::EINVAL, "unsupported descriptor size");
+                    }
+                    DESC_SIZE_64BIT
+                }
+            },
+            want_extra_isize: sb.want_extra_isize,
+            log_groups_per_flex: sb.log_groups_per_flex,
+            checksum_seed: sb.checksum_seed,
+            min_rev_level: sb.min_rev_level,
+            algorithm_usage_bitmap: sb.algorithm_usage_bitmap,
+            hash_seed: sb.hash_seed,
+            def_hash_version: sb.def_hash_version,
+            jnl_backup_type: sb.jnl_backup_type,
+            default_mount_opts: sb.default_mount_opts,
+            first_meta_bg: sb.first_meta_bg,
+            mkfs_time: sb.mkfs_time,
+            jnl_blocks: sb.jnl_blocks,
+            min_extra_isize: sb.min_extra_isize,
+            flags: sb.flags,
+            raid_and_mmp: sb.raid_and_mmp,
+            encryption_level: sb.encryption_level,
+            reserved1: sb.reserved1,
+            reserved2: sb.reserved2,
         })
     }
 }
@@ -202,6 +326,11 @@ impl SuperBlock {
         self.blocks_count
     }
 
+    /// Returns the first data block, which is the start of block group 0.
+    pub fn first_data_block(&self) -> u32 {
+        self.first_data_block.to_raw() as u32
+    }
+
     /// Returns the number of blocks in each block group.
     pub fn blocks_per_group(&self) -> u32 {
         self.blocks_per_group
@@ -213,8 +342,86 @@ impl SuperBlock {
     }
 
     /// Returns the number of block groups.
+    ///
+    /// The last block group may contain fewer blocks than the others.
     pub fn block_groups_count(&self) -> u32 {
-        self.blocks_count / self.blocks_per_group
+        (self.blocks_count - self.first_data_block()).div_ceil(self.blocks_per_group)
+    }
+
+    /// Returns the size of group descriptors.
+    pub fn desc_size(&self) -> usize {
+        self.desc_size
+    }
+
+    /// Returns the number of blocks reserved for the growth of group descriptor table.
+    pub fn reserved_gdt_blocks(&self) -> u32 {
+        if self.feature_compat.contains(FeatureCompatSet::RESIZE_INODE) {
+            self.reserved_gdt_blocks as u32
+        } else {
+            0
+        }
+    }
+
+    /// Returns the size of the extra fields that new inodes should have.
+    pub fn want_extra_isize(&self) -> u16 {
+        self.want_extra_isize
+    }
+
+    /// Returns whether files may be mapped by extent trees.
+    pub fn has_extents(&self) -> bool {
+        self.feature_incompat.contains(FeatureInCompatSet::EXTENTS)
+    }
+
+    /// Returns whether directories may have more than 65000 subdirectories.
+    pub fn has_dir_nlink(&self) -> bool {
+        self.feature_ro_compat
+            .contains(FeatureRoCompatSet::DIR_NLINK)
+    }
+
+    /// Returns whether the metadata is protected by CRC32C checksums.
+    pub fn has_metadata_csum(&self) -> bool {
+        self.feature_ro_compat
+            .contains(FeatureRoCompatSet::METADATA_CSUM)
+    }
+
+    /// Returns whether the group descriptors have checksums,
+    /// which makes the uninitialized block groups possible.
+    pub fn has_group_desc_csum(&self) -> bool {
+        self.feature_ro_compat
+            .intersects(FeatureRoCompatSet::GDT_CSUM | FeatureRoCompatSet::METADATA_CSUM)
+    }
+
+    /// Returns the seed of the metadata checksums.
+    pub fn csum_seed(&self) -> u32 {
+        if self
+            .feature_incompat
+            .contains(FeatureInCompatSet::CSUM_SEED)
+        {
+            self.checksum_seed
+        } else {
+            crc32c(!0, &self.uuid)
+        }
+    }
+
+    /// Calculates the checksum of the group descriptor in `bytes`.
+    ///
+    /// The checksum field itself is excluded from the calculation.
+    pub(super) fn group_descriptor_checksum(&self, block_group_idx: usize, bytes: &[u8]) -> u16 {
+        const CHECKSUM_OFFSET: usize = 0x1e;
+
+        let group = (block_group_idx as u32).to_le_bytes();
+        if self.has_metadata_csum() {
+            let crc = crc32c(self.csum_seed(), &group);
+            let crc = crc32c(crc, &bytes[..CHECKSUM_OFFSET]);
+            let crc = crc32c(crc, &[0u8; 2]);
+            let crc = crc32c(crc, &bytes[CHECKSUM_OFFSET + 2..self.desc_size]);
+            crc as u16
+        } else {
+            let crc = crc16(!0, &self.uuid);
+            let crc = crc16(crc, &group);
+            let crc = crc16(crc, &bytes[..CHECKSUM_OFFSET]);
+            crc16(crc, &bytes[CHECKSUM_OFFSET + 2..self.desc_size])
+        }
     }
 
     /// Returns the filesystem state.
@@ -242,6 +449,40 @@ impl SuperBlock {
         self.feature_ro_compat
     }
 
+    /// Returns whether the filesystem has a journal.
+    pub fn has_journal(&self) -> bool {
+        self.feature_compat.contains(FeatureCompatSet::HAS_JOURNAL)
+    }
+
+    /// Returns whether the inodes may have extended attributes.
+    pub fn has_xattrs(&self) -> bool {
+        self.feature_compat.contains(FeatureCompatSet::EXT_ATTR)
+    }
+
+    /// Marks that the inodes may have extended attributes.
+    pub(super) fn set_has_xattrs(&mut self) {
+        self.feature_compat.insert(FeatureCompatSet::EXT_ATTR);
+    }
+
+    /// Returns the inode number of the journal file.
+    pub fn journal_ino(&self) -> u32 {
+        self.journal_ino
+    }
+
+    /// Returns whether the journal needs to be replayed.
+    pub fn needs_recovery(&self) -> bool {
+        self.feature_incompat.contains(FeatureInCompatSet::RECOVER)
+    }
+
+    /// Sets whether the journal needs to be replayed.
+    ///
+    /// The flag is set in the superblocks committed to the journal, so that
+    /// the journal is replayed after a crash during the checkpoint.
+    pub(super) fn set_needs_recovery(&mut self, needs_recovery: bool) {
+        self.feature_incompat
+            .set(FeatureInCompatSet::RECOVER, needs_recovery);
+    }
+
     /// Returns the number of free blocks.
     pub fn free_blocks_count(&self) -> u32 {
         self.free_blocks_count
@@ -334,9 +575,34 @@ bitflags! {
         /// Inodes have extended attributes
         const EXT_ATTR = 1 << 3;
         /// File system can resize itself for larger partitions
-        const RESIZE_INO = 1 << 4;
+        const RESIZE_INODE = 1 << 4;
         /// Directories use hash index
         const DIR_INDEX = 1 << 5;
+        /// Lazy block group initialization (unused)
+        const LAZY_BG = 1 << 6;
+        /// Exclude bitmaps for snapshots (unused)
+        const EXCLUDE_BITMAP = 1 << 8;
+        /// Backup superblocks are only in two block groups
+        const SPARSE_SUPER2 = 1 << 9;
+        /// Fast commits are supported by the journal
+        const FAST_COMMIT = 1 << 10;
+        /// Stable inode numbers for the shrinking of the filesystem
+        const STABLE_INODES = 1 << 11;
+        /// The orphan file manages the orphan inodes
+        const ORPHAN_FILE = 1 << 12;
+
+        /// The features that can be mounted.
+        const SUPPORTED = Self::DIR_PREALLOC.bits
+            | Self::IMAGIC_INODES.bits
+            | Self::HAS_JOURNAL.bits
+            | Self::EXT_ATTR.bits
+            | Self::RESIZE_INODE.bits
+            | Self::DIR_INDEX.bits
+            | Self::LAZY_BG.bits
+            | Self::EXCLUDE_BITMAP.bits
+            | Self::FAST_COMMIT.bits
+            | Self::STABLE_INODES.bits
+            | Self::ORPHAN_FILE.bits;
     }
 }
 
@@ -353,6 +619,36 @@ bitflags! {
         const JOURNAL_DEV = 1 << 3;
         /// Metablock block group
         const META_BG = 1 << 4;
+        /// Files use extent trees
+        const EXTENTS = 1 << 6;
+        /// Block numbers are 64-bit
+        const BIT64 = 1 << 7;
+        /// Multiple mount protection
+        const MMP = 1 << 8;
+        /// Bitmaps and inode tables of block groups are packed together
+        const FLEX_BG = 1 << 9;
+        /// Inodes can store large extended attribute values
+        const EA_INODE = 1 << 10;
+        /// Data in directory entries
+        const DIRDATA = 1 << 12;
+        /// The checksum seed is stored in the superblock
+        const CSUM_SEED = 1 << 13;
+        /// Directories can be larger than 2GB or have 3-level htrees
+        const LARGEDIR = 1 << 14;
+        /// Data are stored in inodes
+        const INLINE_DATA = 1 << 15;
+        /// Files can be encrypted
+        const ENCRYPT = 1 << 16;
+        /// Directories can be case-insensitive
+        const CASEFOLD = 1 << 17;
+
+        /// The features that can be mounted.
+        const SUPPORTED = Self::FILETYPE.bits
+            | Self::RECOVER.bits
+            | Self::EXTENTS.bits
+            | Self::BIT64.bits
+            | Self::FLEX_BG.bits
+            | Self::CSUM_SEED.bits;
     }
 }
 
@@ -365,6 +661,38 @@ bitflags! {
         const LARGE_FILE = 1 << 1;
         /// Directory contents are stored in the form of a Binary Tree
         const BTREE_DIR = 1 << 2;
+        /// File sizes can be represented in units of blocks
+        const HUGE_FILE = 1 << 3;
+        /// Group descriptors have checksums
+        const GDT_CSUM = 1 << 4;
+        /// Directories can have more than 65000 subdirectories
+        const DIR_NLINK = 1 << 5;
+        /// Inodes have extra space
+        const EXTRA_ISIZE = 1 << 6;
+        /// Quotas are stored in hidden inodes
+        const QUOTA = 1 << 8;
+        /// Allocations are in units of clusters
+        const BIGALLOC = 1 << 9;
+        /// Metadata have CRC32C checksums
+        const METADATA_CSUM = 1 << 10;
+        /// Filesystem is read-only
+        const READONLY = 1 << 12;
+        /// Project quotas are supported
+        const PROJECT = 1 << 13;
+        /// Files can be verified with Merkle trees
+        const VERITY = 1 << 15;
+        /// The orphan file may contain orphan inodes
+        const ORPHAN_PRESENT = 1 << 16;
+
+        /// The features that can be mounted.
+        const SUPPORTED = Self::SPARSE_SUPER.bits
+            | Self::LARGE_FILE.bits
+            | Self::BTREE_DIR.bits
+            | Self::HUGE_FILE.bits
+            | Self::GDT_CSUM.bits
+            | Self::DIR_NLINK.bits
+            | Self::EXTRA_ISIZE.bits
+            | Self::METADATA_CSUM.bits;
     }
 }
 
@@ -419,7 +747,7 @@ const_assert!(core::mem::size_of::<RawSuperBlock>() == SUPER_BLOCK_SIZE);
 
 /// The raw superblock, it must be exactly 1024 bytes in length.
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Pod, Default)]
+#[derive(Clone, Copy, Debug, Pod)]
 pub(super) struct RawSuperBlock {
     pub inodes_count: u32,
     pub blocks_count: u32,
@@ -463,7 +791,8 @@ pub(super) struct RawSuperBlock {
     pub algorithm_usage_bitmap: u32,
     pub prealloc_file_blocks: u8,
     pub prealloc_dir_blocks: u8,
-    padding1: u16,
+    /// Number of blocks reserved for the growth of group descriptor table.
+    pub reserved_gdt_blocks: u16,
     ///
     /// This fields are for journaling support in Ext3.
     ///
@@ -479,18 +808,63 @@ pub(super) struct RawSuperBlock {
     pub hash_seed: [u32; 4],
     /// Default hash version to use
     pub def_hash_version: u8,
-    reserved_char_pad: u8,
-    reserved_word_pad: u16,
+    pub jnl_backup_type: u8,
+    /// Size of group descriptors if the 64-bit feature is set.
+    pub desc_size: u16,
     /// Default mount options.
     pub default_mount_opts: u32,
     /// First metablock block group.
     pub first_meta_bg: u32,
-    reserved: Reserved,
+    ///
+    /// The following fields are for Ext4.
+    ///
+    /// Filesystem creation time.
+    pub mkfs_time: UnixTime,
+    /// Backup of the journal inode's block pointers.
+    pub jnl_blocks: [u32; 17],
+    pub blocks_count_hi: u32,
+    pub reserved_blocks_count_hi: u32,
+    pub free_blocks_count_hi: u32,
+    /// All inodes have at least this many extra bytes.
+    pub min_extra_isize: u16,
+    /// New inodes should reserve this many extra bytes.
+    pub want_extra_isize: u16,
+    pub flags: u32,
+    /// RAID and multiple mount protection fields.
+    pub raid_and_mmp: [u32; 4],
+    /// Number of block groups in a flexible block group, as a power of 2.
+    pub log_groups_per_flex: u8,
+    pub checksum_type: u8,
+    pub encryption_level: u8,
+    reserved_pad: u8,
+    reserved1: [u32; 62],
+    /// Checksum seed used for metadata checksums.
+    pub checksum_seed: u32,
+    reserved2: [u32; 98],
+    /// Checksum of the superblock.
+    pub checksum: u32,
+}
+
+impl RawSuperBlock {
+    /// Calculates the checksum of the superblock.
+    fn calc_checksum(&self) -> u32 {
+        let bytes = self.as_bytes();
+        crc32c(!0, &bytes[..bytes.len() - core::mem::size_of::<u32>()])
+    }
+
+    /// Updates the checksum if the metadata checksums are enabled.
+    ///
+    /// The checksum must be updated whenever a field is modified.
+    pub fn update_checksum(&mut self) {
+        if self.feature_ro_compat & FeatureRoCompatSet::METADATA_CSUM.bits() != 0 {
+            self.checksum = self.calc_checksum();
+        }
+    }
 }
 
 impl From<&SuperBlock> for RawSuperBlock {
     fn from(sb: &SuperBlock) -> Self {
-        Self {
+        let mut raw_super_block = Self {
             inodes_count: sb.inodes_count,
             blocks_count: sb.blocks_count,
             reserved_blocks_count: sb.reserved_blocks_count,
@@ -526,17 +900,46 @@ impl From<&SuperBlock> for RawSuperBlock {
             last_mounted_dir: sb.last_mounted_dir,
             prealloc_file_blocks: sb.prealloc_file_blocks,
             prealloc_dir_blocks: sb.prealloc_dir_blocks,
-            ..Default::default()
-        }
-    }
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Pod)]
-struct Reserved([u32; 190]);
-
-impl Default for Reserved {
-    fn default() -> Self {
-        Self([0u32; 190])
+            journal_uuid: sb.journal_uuid,
+            journal_ino: sb.journal_ino,
+            journal_dev: sb.journal_dev,
+            last_orphan: sb.last_orphan,
+            reserved_gdt_blocks: sb.reserved_gdt_blocks,
+            min_rev_level: sb.min_rev_level,
+            algorithm_usage_bitmap: sb.algorithm_usage_bitmap,
+            hash_seed: sb.hash_seed,
+            def_hash_version: sb.def_hash_version,
+            jnl_backup_type: sb.jnl_backup_type,
+            desc_size: if sb.feature_incompat.contains(FeatureInCompatSet::BIT64) {
+                sb.desc_size as u16
+            } else {
+                0
+            },
+            default_mount_opts: sb.default_mount_opts,
+            first_meta_bg: sb.first_meta_bg,
+            mkfs_time: sb.mkfs_time,
+            jnl_blocks: sb.jnl_blocks,
+            blocks_count_hi: 0,
+            reserved_blocks_count_hi: 0,
+            free_blocks_count_hi: 0,
+            min_extra_isize: sb.min_extra_isize,
+            want_extra_isize: sb.want_extra_isize,
+            flags: sb.flags,
+            raid_and_mmp: sb.raid_and_mmp,
+            log_groups_per_flex: sb.log_groups_per_flex,
+            checksum_type: if sb.has_metadata_csum() {
+                CHECKSUM_TYPE_CRC32C
+            } else {
+                0
+            },
+            encryption_level: sb.encryption_level,
+            reserved_pad: 0,
+            reserved1: sb.reserved1,
+            checksum_seed: sb.checksum_seed,
+            reserved2: sb.reserved2,
+            checksum: 0,
+        };
+        raw_super_block.update_checksum();
+        raw_super_block
     }
 }
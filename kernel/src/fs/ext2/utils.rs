@@ -9,6 +9,58 @@ pub fn now() -> Duration {
     crate::time::clocks::RealTimeCoarseClock::get().read_time()
 }
 
+/// Calculates the CRC32C checksum of `bytes`, continuing from `crc`.
+///
+/// Like Linux, the checksum is neither inverted before nor after the calculation,
+/// so a checksum of multiple buffers can be calculated by chaining the calls.
+pub fn crc32c(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        CRC32C_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Calculates the CRC16 checksum of `bytes`, continuing from `crc`.
+///
+/// It is only used by the group descriptors of the filesystems with the
+/// `uninit_bg` feature but without the `metadata_csum` feature.
+pub fn crc16(crc: u16, bytes: &[u8]) -> u16 {
+    const POLY: u16 = 0xa001;
+
+    bytes.iter().fold(crc, |mut crc, &byte| {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+        }
+        crc
+    })
+}
+
+const CRC32C_TABLE: [u32; 256] = {
+    const POLY: u32 = 0x82f63b78;
+
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
 pub trait IsPowerOf: Copy + Sized + MulAssign + PartialOrd {
     /// Returns true if and only if `self == x^k` for some `k` where `k > 0`.
     ///
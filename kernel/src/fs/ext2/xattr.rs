@@ -0,0 +1,430 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Extended attributes of inodes.
+//!
+//! The attributes of an inode are stored in the space after the extra fields of a
+//! large raw inode, and in an attribute block referred to by the `file_acl` field.
+//! An attribute block may be shared by inodes with identical attributes, in which
+//! case it is copied on write.
+//!
+//! POSIX ACLs are stored in a compact format on the disk, which is converted from and
+//! to the format used by the VFS.
+
+use super::{block_ptr::Ext2Bid, fs::Ext2, journal::Transaction, prelude::*, utils::crc32c};
+use crate::fs::utils::{XattrMap, XattrName, XattrSetFlags};
+
+/// The magic number of the attribute block and the in-inode attribute area.
+const XATTR_MAGIC: u32 = 0xEA02_0000;
+
+const BLOCK_HEADER_SIZE: usize = core::mem::size_of::<RawXattrBlockHeader>();
+const ENTRY_HEADER_SIZE: usize = core::mem::size_of::<RawXattrEntry>();
+const IBODY_HEADER_SIZE: usize = core::mem::size_of::<u32>();
+/// The size of the zeros that terminate the entries.
+const END_MARK_SIZE: usize = core::mem::size_of::<u32>();
+
+/// The offset of the checksum in the attribute block.
+const BLOCK_CSUM_OFFSET: usize = 16;
+
+const NAME_HASH_SHIFT: u32 = 5;
+const VALUE_HASH_SHIFT: u32 = 16;
+const BLOCK_HASH_SHIFT: u32 = 16;
+
+/// The name indexes, which replace the namespace prefixes on the disk.
+///
+/// The POSIX ACLs have their own indexes and empty suffixes.
+const NAME_INDEXES: [(u8, &str); 6] = [
+    (1, "user."),
+    (2, "system.posix_acl_access"),
+    (3, "system.posix_acl_default"),
+    (4, "trusted."),
+    (6, "security."),
+    (7, "system."),
+];
+
+const ACL_INDEXES: [u8; 2] = [2, 3];
+
+/// Loads the attributes from the in-inode area and the attribute block.
+///
+/// The attributes with unknown name indexes are ignored.
+pub(super) fn load_xattrs(ibody: &[u8], block: Option<&[u8]>) -> Result<XattrMap> {
+    let mut xattrs = XattrMap::new();
+    if ibody.len() >= IBODY_HEADER_SIZE + END_MARK_SIZE
+        && u32::from_le_bytes(ibody[..IBODY_HEADER_SIZE].try_into().unwrap()) == XATTR_MAGIC
+    {
+        let area = &ibody[IBODY_HEADER_SIZE..];
+        parse_entries(area, 0, &mut xattrs)?;
+    }
+    if let Some(block) = block {
+        parse_entries(block, BLOCK_HEADER_SIZE, &mut xattrs)?;
+    }
+    Ok(xattrs)
+}
+
+/// Serializes the attributes into an in-inode area of `ibody_len` bytes and an
+/// attribute block.
+///
+/// The attributes that do not fit into the in-inode area are put into the block,
+/// which is `None` if it is not needed.
+pub(super) fn layout_xattrs(
+    xattrs: &XattrMap,
+    ibody_len: usize,
+) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+    let mut entries: Vec<(u8, &[u8], Vec<u8>)> = xattrs
+        .iter()
+        .filter_map(|(full_name, value)| {
+            let (index, suffix) = to_disk_name(full_name)?;
+            let value = if ACL_INDEXES.contains(&index) {
+                acl_to_disk(value)
+            } else {
+                value.to_vec()
+            };
+            Some((index, suffix.as_bytes(), value))
+        })
+        .collect();
+    // The entries in a block must be sorted.
+    entries.sort_by(|(lhs_index, lhs_name, _), (rhs_index, rhs_name, _)| {
+        (lhs_index, lhs_name.len(), lhs_name).cmp(&(rhs_index, rhs_name.len(), rhs_name))
+    });
+
+    let mut ibody = vec![0u8; ibody_len];
+    let mut ibody_count = 0;
+    if ibody_len >= IBODY_HEADER_SIZE + END_MARK_SIZE {
+        let area = &mut ibody[IBODY_HEADER_SIZE..];
+        while ibody_count < entries.len() && write_entries(area, 0, &entries[..=ibody_count], false)
+        {
+            ibody_count += 1;
+        }
+        write_entries(area, 0, &entries[..ibody_count], false);
+        if ibody_count > 0 {
+            ibody[..IBODY_HEADER_SIZE].copy_from_slice(&XATTR_MAGIC.to_le_bytes());
+        }
+    }
+
+    let block_entries = &entries[ibody_count..];
+    if block_entries.is_empty() {
+        return Ok((ibody, None));
+    }
+    let mut block = vec![0u8; BLOCK_SIZE];
+    if !write_entries(&mut block, BLOCK_HEADER_SIZE, block_entries, true) {
+        return_errno_with_message!(Errno::ENOSPC, "the xattrs do not fit into a block");
+    }
+    let header = RawXattrBlockHeader {
+        magic: XATTR_MAGIC,
+        refcount: 1,
+        blocks: 1,
+        hash: block_hash(&block),
+        ..Default::default()
+    };
+    block[..BLOCK_HEADER_SIZE].copy_from_slice(header.as_bytes());
+    Ok((ibody, Some(block)))
+}
+
+/// Reads and verifies the attribute block `bid`.
+pub(super) fn read_xattr_block(fs: &Ext2, bid: Ext2Bid) -> Result<Vec<u8>> {
+    let mut block = vec![0u8; BLOCK_SIZE];
+    fs.read_metadata_block(bid, &mut block)?;
+
+    let header = RawXattrBlockHeader::from_bytes(&block[..BLOCK_HEADER_SIZE]);
+    if header.magic != XATTR_MAGIC || header.blocks != 1 {
+        return_errno_with_message!(Errno::EUCLEAN, "invalid xattr block");
+    }
+    if let Some(csum_seed) = fs.csum_seed() {
+        if header.checksum != block_checksum(csum_seed, bid, &block) {
+            return_errno_with_message!(Errno::EBADMSG, "bad xattr block checksum");
+        }
+    }
+    Ok(block)
+}
+
+/// Writes the attribute block `bid` with the updated checksum.
+pub(super) fn write_xattr_block(fs: &Ext2, bid: Ext2Bid, block: &mut [u8]) -> Result<()> {
+    if let Some(csum_seed) = fs.csum_seed() {
+        let checksum = block_checksum(csum_seed, bid, block);
+        block[BLOCK_CSUM_OFFSET..BLOCK_CSUM_OFFSET + 4].copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    let mut transaction = Transaction::new();
+    transaction.write_bytes(bid as usize * BLOCK_SIZE, block);
+    fs.write_metadata(transaction)
+}
+
+/// Returns the number of inodes that share the attribute block.
+pub(super) fn block_refcount(block: &[u8]) -> u32 {
+    RawXattrBlockHeader::from_bytes(&block[..BLOCK_HEADER_SIZE]).refcount
+}
+
+/// Sets the number of inodes that share the attribute block.
+pub(super) fn set_block_refcount(block: &mut [u8], refcount: u32) {
+    let mut header = RawXattrBlockHeader::from_bytes(&block[..BLOCK_HEADER_SIZE]);
+    header.refcount = refcount;
+    block[..BLOCK_HEADER_SIZE].copy_from_slice(header.as_bytes());
+}
+
+/// Releases the reference of an inode to the attribute block `bid`.
+///
+/// The block is freed if no other inodes share it.
+pub(super) fn release_xattr_block(fs: &Ext2, bid: Ext2Bid) -> Result<()> {
+    let mut block = read_xattr_block(fs, bid)?;
+    let refcount = block_refcount(&block);
+    if refcount > 1 {
+        set_block_refcount(&mut block, refcount - 1);
+        write_xattr_block(fs, bid, &mut block)
+    } else {
+        fs.free_blocks(bid..bid + 1)
+    }
+}
+
+/// Sets an attribute in `xattrs`, checking that the value is not too large for the disk.
+pub(super) fn set_xattr(
+    xattrs: &mut XattrMap,
+    name: XattrName,
+    value: &[u8],
+    flags: XattrSetFlags,
+) -> Result<()> {
+    if to_disk_name(name.full_name()).is_none() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the xattr namespace is not supported");
+    }
+    if entry_size(name.full_name().len()) + value_size(value.len()) + BLOCK_HEADER_SIZE > BLOCK_SIZE
+    {
+        return_errno_with_message!(Errno::ENOSPC, "the xattr is too large");
+    }
+    xattrs.set(name, value, flags)
+}
+
+/// Parses the entries in the `buf`.
+///
+/// The value offsets are relative to the start of the `buf`, which is the block, or
+/// the first entry of an in-inode area.
+fn parse_entries(buf: &[u8], entries_offset: usize, xattrs: &mut XattrMap) -> Result<()> {
+    let mut offset = entries_offset;
+    while offset + END_MARK_SIZE <= buf.len()
+        && buf[offset..offset + END_MARK_SIZE] != [0u8; END_MARK_SIZE]
+    {
+        if offset + ENTRY_HEADER_SIZE > buf.len() {
+            return_errno_with_message!(Errno::EUCLEAN, "the xattr entry is out of bounds");
+        }
+        let entry = RawXattrEntry::from_bytes(&buf[offset..offset + ENTRY_HEADER_SIZE]);
+        let name_start = offset + ENTRY_HEADER_SIZE;
+        let name_end = name_start + entry.name_len as usize;
+        let value_start = entry.value_offset as usize;
+        let value_end = value_start + entry.value_size as usize;
+        if name_end > buf.len() || value_end > buf.len() {
+            return_errno_with_message!(Errno::EUCLEAN, "the xattr entry is out of bounds");
+        }
+        // The values stored in separate inodes are not supported.
+        if entry.value_inum != 0 {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the xattr value is in an inode");
+        }
+
+        let suffix = core::str::from_utf8(&buf[name_start..name_end])
+            .map_err(|_| Error::with_message(Errno::EUCLEAN, "the xattr name is invalid"))?;
+        if let Some(full_name) = from_disk_name(entry.name_index, suffix) {
+            let value = &buf[value_start..value_end];
+            let value = if ACL_INDEXES.contains(&entry.name_index) {
+                acl_from_disk(value)?
+            } else {
+                value.to_vec()
+            };
+            if let Ok(name) = XattrName::try_from_full_name(&full_name) {
+                xattrs.set(name, &value, XattrSetFlags::empty())?;
+            }
+        }
+
+        offset += entry_size(entry.name_len as usize);
+    }
+    Ok(())
+}
+
+/// Writes the `entries` into the `buf`, with the values placed at the end.
+///
+/// Returns `false` if the entries do not fit.
+fn write_entries(
+    buf: &mut [u8],
+    entries_offset: usize,
+    entries: &[(u8, &[u8], Vec<u8>)],
+    is_block: bool,
+) -> bool {
+    let entries_len: usize = entries
+        .iter()
+        .map(|(_, name, _)| entry_size(name.len()))
+        .sum();
+    let values_len: usize = entries
+        .iter()
+        .map(|(_, _, value)| value_size(value.len()))
+        .sum();
+    if entries_offset + entries_len + END_MARK_SIZE + values_len > buf.len() {
+        return false;
+    }
+
+    buf[entries_offset..].fill(0);
+    let mut offset = entries_offset;
+    let mut value_offset = buf.len();
+    for (index, name, value) in entries.iter() {
+        value_offset -= value_size(value.len());
+        buf[value_offset..value_offset + value.len()].copy_from_slice(value);
+
+        let entry = RawXattrEntry {
+            name_len: name.len() as u8,
+            name_index: *index,
+            value_offset: value_offset as u16,
+            value_inum: 0,
+            value_size: value.len() as u32,
+            hash: if is_block {
+                entry_hash(
+                    name,
+                    &buf[value_offset..value_offset + value_size(value.len())],
+                )
+            } else {
+                0
+            },
+        };
+        buf[offset..offset + ENTRY_HEADER_SIZE].copy_from_slice(entry.as_bytes());
+        buf[offset + ENTRY_HEADER_SIZE..offset + ENTRY_HEADER_SIZE + name.len()]
+            .copy_from_slice(name);
+        offset += entry_size(name.len());
+    }
+    true
+}
+
+fn to_disk_name(full_name: &str) -> Option<(u8, &str)> {
+    NAME_INDEXES.iter().find_map(|&(index, prefix)| {
+        if ACL_INDEXES.contains(&index) {
+            (full_name == prefix).then_some((index, ""))
+        } else {
+            full_name.strip_prefix(prefix).map(|suffix| (index, suffix))
+        }
+    })
+}
+
+fn from_disk_name(name_index: u8, suffix: &str) -> Option<String> {
+    let &(_, prefix) = NAME_INDEXES
+        .iter()
+        .find(|&&(index, _)| index == name_index)?;
+    let mut full_name = String::from(prefix);
+    full_name.push_str(suffix);
+    Some(full_name)
+}
+
+fn entry_size(name_len: usize) -> usize {
+    (ENTRY_HEADER_SIZE + name_len).align_up(4)
+}
+
+fn value_size(value_len: usize) -> usize {
+    value_len.align_up(4)
+}
+
+fn entry_hash(name: &[u8], padded_value: &[u8]) -> u32 {
+    let mut hash = 0u32;
+    for &byte in name {
+        hash = (hash << NAME_HASH_SHIFT) ^ (hash >> (32 - NAME_HASH_SHIFT)) ^ byte as u32;
+    }
+    for word in padded_value.chunks_exact(4) {
+        let word = u32::from_le_bytes(word.try_into().unwrap());
+        hash = (hash << VALUE_HASH_SHIFT) ^ (hash >> (32 - VALUE_HASH_SHIFT)) ^ word;
+    }
+    hash
+}
+
+/// Calculates the hash of the attribute block from the hashes of its entries.
+fn block_hash(block: &[u8]) -> u32 {
+    let mut hash = 0u32;
+    let mut offset = BLOCK_HEADER_SIZE;
+    while block[offset..offset + END_MARK_SIZE] != [0u8; END_MARK_SIZE] {
+        let entry = RawXattrEntry::from_bytes(&block[offset..offset + ENTRY_HEADER_SIZE]);
+        if entry.hash == 0 {
+            return 0;
+        }
+        hash = (hash << BLOCK_HASH_SHIFT) ^ (hash >> (32 - BLOCK_HASH_SHIFT)) ^ entry.hash;
+        offset += entry_size(entry.name_len as usize);
+    }
+    hash
+}
+
+fn block_checksum(csum_seed: u32, bid: Ext2Bid, block: &[u8]) -> u32 {
+    let crc = crc32c(csum_seed, &(bid as u64).to_le_bytes());
+    let crc = crc32c(crc, &block[..BLOCK_CSUM_OFFSET]);
+    let crc = crc32c(crc, &[0u8; 4]);
+    crc32c(crc, &block[BLOCK_CSUM_OFFSET + 4..])
+}
+
+/// The version of the POSIX ACLs on the disk.
+const DISK_ACL_VERSION: u32 = 1;
+/// The version of the POSIX ACLs in the VFS.
+const VFS_ACL_VERSION: u32 = 2;
+/// The tags of the ACL entries that have IDs.
+const ACL_TAGS_WITH_ID: [u16; 2] = [0x02, 0x08];
+const ACL_UNDEFINED_ID: u32 = u32::MAX;
+
+/// Converts an ACL from the VFS format, which has been validated, into the disk format.
+///
+/// The entries without IDs are stored in the short form.
+fn acl_to_disk(value: &[u8]) -> Vec<u8> {
+    let mut disk_value = DISK_ACL_VERSION.to_le_bytes().to_vec();
+    for entry in value[4..].chunks_exact(8) {
+        let tag = u16::from_le_bytes([entry[0], entry[1]]);
+        disk_value.extend_from_slice(&entry[..4]);
+        if ACL_TAGS_WITH_ID.contains(&tag) {
+            disk_value.extend_from_slice(&entry[4..]);
+        }
+    }
+    disk_value
+}
+
+/// Converts an ACL from the disk format into the VFS format.
+fn acl_from_disk(disk_value: &[u8]) -> Result<Vec<u8>> {
+    if disk_value.len() < 4
+        || u32::from_le_bytes(disk_value[..4].try_into().unwrap()) != DISK_ACL_VERSION
+    {
+        return_errno_with_message!(Errno::EUCLEAN, "the ACL version is invalid");
+    }
+
+    let mut value = VFS_ACL_VERSION.to_le_bytes().to_vec();
+    let mut offset = 4;
+    while offset < disk_value.len() {
+        if offset + 4 > disk_value.len() {
+            return_errno_with_message!(Errno::EUCLEAN, "the ACL entry is truncated");
+        }
+        let tag = u16::from_le_bytes([disk_value[offset], disk_value[offset + 1]]);
+        value.extend_from_slice(&disk_value[offset..offset + 4]);
+        offset += 4;
+        if ACL_TAGS_WITH_ID.contains(&tag) {
+            if offset + 4 > disk_value.len() {
+                return_errno_with_message!(Errno::EUCLEAN, "the ACL entry is truncated");
+            }
+            value.extend_from_slice(&disk_value[offset..offset + 4]);
+            offset += 4;
+        } else {
+            value.extend_from_slice(&ACL_UNDEFINED_ID.to_le_bytes());
+        }
+    }
+    Ok(value)
+}
+
+/// The header of the attribute block.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug, Pod)]
+struct RawXattrBlockHeader {
+    magic: u32,
+    /// The number of inodes that share the block.
+    refcount: u32,
+    /// The number of blocks, which is always 1.
+    blocks: u32,
+    /// The hash of all attributes.
+    hash: u32,
+    checksum: u32,
+    reserved: [u32; 3],
+}
+
+/// The header of an attribute entry, which is followed by the name suffix.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug, Pod)]
+struct RawXattrEntry {
+    name_len: u8,
+    name_index: u8,
+    value_offset: u16,
+    /// The inode that stores the value, or zero if the value is in the same block.
+    value_inum: u32,
+    value_size: u32,
+    /// The hash of the name and value.
+    hash: u32,
+}
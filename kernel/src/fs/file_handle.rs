@@ -4,6 +4,8 @@
 
 //! Opened File Handle
 
+use aster_rights::Rights;
+
 use crate::{
     fs::{
         device::Device,
@@ -12,6 +14,7 @@ use crate::{
     net::socket::Socket,
     prelude::*,
     process::{signal::Pollable, Gid, Uid},
+    vm::vmo::Vmo,
 };
 
 /// The basic operations defined on a file
@@ -107,6 +110,15 @@ pub trait FileLike: Pollable + Send + Sync + Any {
     fn as_device(&self) -> Option<Arc<dyn Device>> {
         None
     }
+
+    /// Returns the VMO that backs the file contents at `offset` for `mmap`, together with the
+    /// offset within the VMO.
+    ///
+    /// Files that are backed by inodes are mapped via their page caches, so this method is only
+    /// needed by the files that are not backed by inodes but can still be mapped.
+    fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)> {
+        return_errno_with_message!(Errno::ENODEV, "the file cannot be mapped");
+    }
 }
 
 impl dyn FileLike {
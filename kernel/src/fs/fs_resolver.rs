@@ -5,8 +5,8 @@ use alloc::str;
 use super::{
     file_table::FileDesc,
     inode_handle::InodeHandle,
-    path::Dentry,
-    rootfs::root_mount,
+    path::{Dentry, MountNamespace},
+    rootfs::init_mnt_ns,
     utils::{AccessMode, CreationFlags, InodeMode, InodeType, StatusFlags, PATH_MAX, SYMLINKS_MAX},
 };
 use crate::{prelude::*, process::posix_thread::AsPosixThread};
@@ -19,14 +19,17 @@ pub const AT_FDCWD: FileDesc = -100;
 pub struct FsResolver {
     root: Dentry,
     cwd: Dentry,
+    mnt_ns: Arc<MountNamespace>,
 }
 
 impl FsResolver {
-    /// Creates a new file system resolver.
+    /// Creates a new file system resolver in the initial mount namespace.
     pub fn new() -> Self {
+        let mnt_ns = init_mnt_ns().clone();
         Self {
-            root: Dentry::new_fs_root(root_mount().clone()),
-            cwd: Dentry::new_fs_root(root_mount().clone()),
+            root: mnt_ns.root_dentry(),
+            cwd: mnt_ns.root_dentry(),
+            mnt_ns,
         }
     }
 
@@ -50,6 +53,38 @@ impl FsResolver {
         self.root = dentry;
     }
 
+    /// Gets the mount namespace.
+    pub fn mnt_ns(&self) -> &Arc<MountNamespace> {
+        &self.mnt_ns
+    }
+
+    /// Moves to a new mount namespace copied from the current one.
+    ///
+    /// The root directory and the current working directory will be moved to
+    /// the corresponding locations in the new mount namespace.
+    pub fn unshare_mnt_ns(&mut self) {
+        let new_mnt_ns = self.mnt_ns.copy();
+
+        let translate = |dentry: &Dentry| {
+            new_mnt_ns
+                .corresponding_dentry(&self.mnt_ns, dentry)
+                .unwrap_or_else(|| new_mnt_ns.root_dentry())
+        };
+        self.root = translate(&self.root);
+        self.cwd = translate(&self.cwd);
+        self.mnt_ns = new_mnt_ns;
+    }
+
+    /// Moves to the given mount namespace.
+    ///
+    /// Both the root directory and the current working directory will be set to
+    /// the root directory of the mount namespace.
+    pub fn set_mnt_ns(&mut self, mnt_ns: Arc<MountNamespace>) {
+        self.root = mnt_ns.root_dentry();
+        self.cwd = mnt_ns.root_dentry();
+        self.mnt_ns = mnt_ns;
+    }
+
     /// Opens or creates a file inode handler.
     pub fn open(&self, path: &FsPath, flags: u32, mode: u16) -> Result<InodeHandle> {
         let open_args = OpenArgs::from_flags_and_mode(flags, mode)?;
@@ -284,7 +319,7 @@ impl FsResolver {
     pub fn lookup_from_fd(&self, fd: FileDesc) -> Result<Dentry> {
         let current = current_thread!();
         let current = current.as_posix_thread().unwrap();
-        let file_table = current.file_table().lock();
+        let file_table = current.file_table().lock_arc();
         let inode_handle = file_table
             .get_file(fd)?
             .downcast_ref::<InodeHandle>()
@@ -36,6 +36,8 @@ impl InodeHandle<Rights> {
             access_mode,
             status_flags: AtomicU32::new(status_flags.bits()),
         });
+        inner.dentry.notify_event(FsEvents::OPEN);
+
         Ok(Self(inner, Rights::from(access_mode)))
     }
 
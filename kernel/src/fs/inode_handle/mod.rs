@@ -17,6 +17,7 @@ use crate::{
     fs::{
         device::Device,
         file_handle::FileLike,
+        notify::FsEvents,
         path::Dentry,
         utils::{
             AccessMode, DirentVisitor, FallocMode, FileRange, FlockItem, FlockList, InodeMode,
@@ -89,11 +90,16 @@ impl InodeHandle_ {
             todo!("support read_at for FileIo");
         }
 
-        if self.status_flags().contains(StatusFlags::O_DIRECT) {
-            self.dentry.inode().read_direct_at(offset, writer)
+        let len = if self.status_flags().contains(StatusFlags::O_DIRECT) {
+            self.dentry.inode().read_direct_at(offset, writer)?
         } else {
-            self.dentry.inode().read_at(offset, writer)
+            self.dentry.inode().read_at(offset, writer)?
+        };
+
+        if len > 0 {
+            self.dentry.notify_event(FsEvents::ACCESS);
         }
+        Ok(len)
     }
 
     pub fn write_at(&self, mut offset: usize, reader: &mut VmReader) -> Result<usize> {
@@ -107,11 +113,16 @@ impl InodeHandle_ {
             offset = self.dentry.size();
         }
 
-        if status_flags.contains(StatusFlags::O_DIRECT) {
-            self.dentry.inode().write_direct_at(offset, reader)
+        let len = if status_flags.contains(StatusFlags::O_DIRECT) {
+            self.dentry.inode().write_direct_at(offset, reader)?
         } else {
-            self.dentry.inode().write_at(offset, reader)
+            self.dentry.inode().write_at(offset, reader)?
+        };
+
+        if len > 0 {
+            self.dentry.notify_event(FsEvents::MODIFY);
         }
+        Ok(len)
     }
 
     pub fn read_to_end(&self, buf: &mut Vec<u8>) -> Result<usize> {
@@ -218,7 +229,9 @@ impl InodeHandle_ {
             );
         }
 
-        self.dentry.inode().fallocate(mode, offset, len)
+        self.dentry.inode().fallocate(mode, offset, len)?;
+        self.dentry.notify_event(FsEvents::MODIFY);
+        Ok(())
     }
 
     fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
@@ -344,6 +357,17 @@ impl InodeHandle_ {
     pub fn set_group(&self, gid: Gid) -> Result<()>;
 }
 
+impl Drop for InodeHandle_ {
+    fn drop(&mut self) {
+        let events = if self.access_mode.is_writable() {
+            FsEvents::CLOSE_WRITE
+        } else {
+            FsEvents::CLOSE_NOWRITE
+        };
+        self.dentry.notify_event(events);
+    }
+}
+
 impl Debug for InodeHandle_ {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.debug_struct("InodeHandle_")
@@ -0,0 +1,589 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    mem::size_of,
+    sync::atomic::{fence, Ordering},
+    time::Duration,
+};
+
+use aster_rights::Rights;
+use ostd::cpu::CpuId;
+
+use super::{
+    op::{Opcode, Request, SqeFlags},
+    poll::PollTable,
+    ring::{CIoUringCqe, Rings},
+    timeout::TimeoutTable,
+    worker::{SqPoller, WorkerPool},
+    CIoUringParams, CIoUringProbe, CIoUringProbeOp, EnterFlags, Features, RegisterOp, SetupFlags,
+    SqRingFlags, IORING_MAX_CQ_ENTRIES, IORING_MAX_ENTRIES, IO_URING_OP_SUPPORTED,
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        file_table::FileDesc,
+        utils::{InodeMode, Metadata},
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+    thread::Thread,
+    time::{clocks::MonotonicClock, Clock},
+    util::IoVec,
+    vm::vmo::Vmo,
+};
+
+/// The maximum number of the registered buffers.
+const IORING_MAX_REG_BUFFERS: u32 = 1 << 14;
+/// The maximum number of the registered files.
+const IORING_MAX_FIXED_FILES: u32 = 1 << 15;
+
+/// The default idle time of the SQ polling thread.
+const DEFAULT_SQ_THREAD_IDLE: Duration = Duration::from_secs(1);
+
+/// An io_uring instance.
+pub struct IoUring {
+    ctx: Arc<RingContext>,
+}
+
+/// The states of an io_uring instance that are shared with the requests in flight.
+pub(super) struct RingContext {
+    rings: Rings,
+    flags: SetupFlags,
+    registered: Mutex<Registered>,
+    polls: PollTable,
+    timeouts: TimeoutTable,
+    workers: Arc<WorkerPool>,
+    sq_poller: Option<SqPoller>,
+}
+
+#[derive(Default)]
+struct Registered {
+    files: Option<Box<[Option<Arc<dyn FileLike>>]>>,
+    buffers: Option<Box<[IoVec]>>,
+}
+
+/// The argument of `IORING_REGISTER_FILES_UPDATE`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CIoUringFilesUpdate {
+    offset: u32,
+    resv: u32,
+    fds: u64,
+}
+
+impl IoUring {
+    /// Creates an io_uring instance with at least `entries` SQ entries.
+    ///
+    /// The actual parameters of the instance are written back to `params`.
+    pub fn new(entries: u32, params: &mut CIoUringParams, ctx: &Context) -> Result<Self> {
+        let flags = SetupFlags::from_bits(params.flags)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the setup flags are invalid"))?;
+        if !SetupFlags::SUPPORTED.contains(flags) {
+            return_errno_with_message!(Errno::EINVAL, "the setup flags are not supported");
+        }
+        if flags.contains(SetupFlags::SQ_AFF) && !flags.contains(SetupFlags::SQPOLL) {
+            return_errno_with_message!(Errno::EINVAL, "SQ_AFF requires SQPOLL");
+        }
+        if params.resv.iter().any(|resv| *resv != 0) {
+            return_errno_with_message!(Errno::EINVAL, "the reserved fields are not zero");
+        }
+
+        let sq_thread_cpu = if flags.contains(SetupFlags::SQ_AFF) {
+            let cpu_id = CpuId::try_from(params.sq_thread_cpu as usize).map_err(|_| {
+                Error::with_message(Errno::EINVAL, "the CPU of the SQ thread is invalid")
+            })?;
+            Some(cpu_id)
+        } else {
+            None
+        };
+
+        let is_clamp = flags.contains(SetupFlags::CLAMP);
+        let sq_entries = check_entries(entries, IORING_MAX_ENTRIES, is_clamp)?;
+        let cq_entries = if flags.contains(SetupFlags::CQSIZE) {
+            let cq_entries = check_entries(params.cq_entries, IORING_MAX_CQ_ENTRIES, is_clamp)?;
+            if cq_entries < sq_entries {
+                return_errno_with_message!(Errno::EINVAL, "the CQ is smaller than the SQ");
+            }
+            cq_entries
+        } else {
+            2 * sq_entries
+        };
+
+        let rings = Rings::new(sq_entries, cq_entries)?;
+
+        let sq_poller = flags.contains(SetupFlags::SQPOLL).then(|| {
+            let idle = if params.sq_thread_idle == 0 {
+                DEFAULT_SQ_THREAD_IDLE
+            } else {
+                Duration::from_millis(params.sq_thread_idle as u64)
+            };
+            SqPoller::new(idle, sq_thread_cpu)
+        });
+
+        let ring_ctx = Arc::new(RingContext {
+            rings,
+            flags,
+            registered: Mutex::new(Registered::default()),
+            polls: PollTable::new(),
+            timeouts: TimeoutTable::new(),
+            workers: WorkerPool::new(),
+            sq_poller,
+        });
+
+        if let Some(sq_poller) = ring_ctx.sq_poller.as_ref() {
+            let ring_ctx_cloned = ring_ctx.clone();
+            sq_poller.spawn(ctx, move |ctx| ring_ctx_cloned.run_sq_poller(ctx));
+        }
+
+        let (sq_off, cq_off) = ring_ctx.rings.offsets();
+        params.sq_entries = sq_entries;
+        params.cq_entries = cq_entries;
+        params.features = Features::SUPPORTED.bits();
+        params.sq_off = sq_off;
+        params.cq_off = cq_off;
+
+        Ok(Self { ctx: ring_ctx })
+    }
+
+    /// Submits the SQEs and waits for the CQEs.
+    ///
+    /// This method returns the number of the consumed SQEs.
+    pub fn enter(
+        &self,
+        to_submit: u32,
+        min_complete: u32,
+        flags: EnterFlags,
+        ctx: &Context,
+    ) -> Result<usize> {
+        if flags.intersects(EnterFlags::EXT_ARG | EnterFlags::REGISTERED_RING) {
+            return_errno_with_message!(Errno::EINVAL, "the enter flags are not supported");
+        }
+
+        let rings = &self.ctx.rings;
+
+        let num_submitted = if let Some(sq_poller) = self.ctx.sq_poller.as_ref() {
+            if flags.contains(EnterFlags::SQ_WAKEUP) {
+                sq_poller.wake_up();
+            }
+            if flags.contains(EnterFlags::SQ_WAIT) {
+                rings.sq_wait_queue().pause_until(|| {
+                    (rings.num_pending_sqes() < rings.sq_entries()).then_some(())
+                })?;
+            }
+            // The SQEs are submitted by the SQ polling thread. Like Linux, we report that all of
+            // them have been submitted.
+            to_submit as usize
+        } else if to_submit > 0 {
+            self.ctx.submit(to_submit, ctx)
+        } else {
+            0
+        };
+
+        if flags.contains(EnterFlags::GETEVENTS) {
+            let min_complete = min_complete.min(rings.cq_entries());
+
+            rings.flush_overflow();
+            let res = rings
+                .cq_wait_queue()
+                .pause_until(|| (rings.num_ready_cqes() >= min_complete).then_some(()));
+
+            // The submitted SQEs are consumed and cannot be submitted again, so the error is only
+            // reported if nothing has been submitted.
+            if let Err(err) = res {
+                if num_submitted == 0 {
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(num_submitted)
+    }
+
+    /// Registers or unregisters the resources that are used by the requests.
+    pub fn register(
+        &self,
+        op: RegisterOp,
+        arg: Vaddr,
+        nr_args: u32,
+        ctx: &Context,
+    ) -> Result<usize> {
+        let mut registered = self.ctx.registered.lock();
+
+        match op {
+            RegisterOp::RegisterBuffers => {
+                if registered.buffers.is_some() {
+                    return_errno_with_message!(Errno::EBUSY, "the buffers are already registered");
+                }
+                if nr_args == 0 || nr_args > IORING_MAX_REG_BUFFERS {
+                    return_errno_with_message!(Errno::EINVAL, "the number of buffers is invalid");
+                }
+
+                let buffers = IoVec::read_from_user(ctx, arg, nr_args as usize)?;
+                if buffers.len() != nr_args as usize {
+                    return_errno_with_message!(Errno::EFAULT, "the buffers cannot be empty");
+                }
+                registered.buffers = Some(buffers);
+            }
+            RegisterOp::UnregisterBuffers => {
+                if registered.buffers.take().is_none() {
+                    return_errno_with_message!(Errno::ENXIO, "no buffers are registered");
+                }
+            }
+            RegisterOp::RegisterFiles => {
+                if registered.files.is_some() {
+                    return_errno_with_message!(Errno::EBUSY, "the files are already registered");
+                }
+                if nr_args == 0 || nr_args > IORING_MAX_FIXED_FILES {
+                    return_errno_with_message!(Errno::EINVAL, "the number of files is invalid");
+                }
+
+                let mut files = Vec::with_capacity(nr_args as usize);
+                for i in 0..nr_args as usize {
+                    let fd = ctx
+                        .user_space()
+                        .read_val::<i32>(arg + i * size_of::<i32>())?;
+                    files.push(get_file_or_empty(fd, ctx)?);
+                }
+                registered.files = Some(files.into_boxed_slice());
+            }
+            RegisterOp::UnregisterFiles => {
+                if registered.files.take().is_none() {
+                    return_errno_with_message!(Errno::ENXIO, "no files are registered");
+                }
+            }
+            RegisterOp::RegisterFilesUpdate => {
+                let Some(files) = registered.files.as_mut() else {
+                    return_errno_with_message!(Errno::ENXIO, "no files are registered");
+                };
+
+                let update = ctx.user_space().read_val::<CIoUringFilesUpdate>(arg)?;
+                if update.resv != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the reserved field is not zero");
+                }
+                let start = update.offset as usize;
+                if start
+                    .checked_add(nr_args as usize)
+                    .is_none_or(|end| end > files.len())
+                {
+                    return_errno_with_message!(Errno::EINVAL, "the update range is out of bounds");
+                }
+
+                for i in 0..nr_args as usize {
+                    let fd = ctx
+                        .user_space()
+                        .read_val::<i32>(update.fds as Vaddr + i * size_of::<i32>())?;
+                    files[start + i] = get_file_or_empty(fd, ctx)?;
+                }
+                return Ok(nr_args as usize);
+            }
+            RegisterOp::RegisterProbe => {
+                drop(registered);
+                write_probe(arg, nr_args, ctx)?;
+            }
+            RegisterOp::RegisterEventfd
+            | RegisterOp::UnregisterEventfd
+            | RegisterOp::RegisterEventfdAsync => {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "eventfd notifications are not supported"
+                );
+            }
+        }
+
+        Ok(0)
+    }
+}
+
+impl RingContext {
+    pub(super) fn polls(&self) -> &PollTable {
+        &self.polls
+    }
+
+    pub(super) fn timeouts(&self) -> &TimeoutTable {
+        &self.timeouts
+    }
+
+    /// Returns the registered file at the index.
+    pub(super) fn registered_file(&self, index: u32) -> Result<Arc<dyn FileLike>> {
+        let registered = self.registered.lock();
+        registered
+            .files
+            .as_ref()
+            .and_then(|files| files.get(index as usize))
+            .and_then(|file| file.clone())
+            .ok_or_else(|| Error::with_message(Errno::EBADF, "the registered file does not exist"))
+    }
+
+    /// Returns the address and the length of the registered buffer at the index.
+    pub(super) fn registered_buffer(&self, index: u16) -> Result<(Vaddr, usize)> {
+        let registered = self.registered.lock();
+        registered
+            .buffers
+            .as_ref()
+            .and_then(|buffers| buffers.get(index as usize))
+            .map(|buffer| (buffer.base(), buffer.len()))
+            .ok_or_else(|| {
+                Error::with_message(Errno::EFAULT, "the registered buffer does not exist")
+            })
+    }
+
+    /// Completes a request.
+    ///
+    /// The completion is counted by the timeout requests that wait for completions.
+    pub(super) fn complete(&self, user_data: u64, flags: SqeFlags, res: i32) {
+        self.post_cqe(user_data, flags, res);
+        self.timeouts.on_completion();
+    }
+
+    /// Posts the CQE of a request without counting the completion.
+    pub(super) fn post_cqe(&self, user_data: u64, flags: SqeFlags, res: i32) {
+        if flags.contains(SqeFlags::CQE_SKIP_SUCCESS) && res >= 0 {
+            return;
+        }
+
+        self.rings.post_cqe(CIoUringCqe {
+            user_data,
+            res,
+            flags: 0,
+        });
+    }
+
+    /// Submits at most `to_submit` SQEs and returns the number of the consumed SQEs.
+    ///
+    /// The SQEs that fail to be prepared are completed with errors. The submission stops at the
+    /// first such SQE unless `IORING_SETUP_SUBMIT_ALL` is specified.
+    fn submit(self: &Arc<Self>, to_submit: u32, ctx: &Context) -> usize {
+        let mut consumer = self.rings.consume_sq();
+        let mut num_submitted = 0;
+
+        // The requests that are linked together and have not been issued.
+        let mut chain = Vec::new();
+        // Whether the rest of the current chain should be canceled.
+        let mut is_chain_broken = false;
+
+        while num_submitted < to_submit as usize {
+            let Some(sqe) = consumer.pop() else {
+                break;
+            };
+            num_submitted += 1;
+
+            let request = match Request::prepare(&sqe, self, ctx) {
+                Ok(request) => request,
+                Err(err) => {
+                    let flags = sqe.flags();
+                    self.complete(sqe.user_data(), flags, -(err.error() as i32));
+                    self.cancel_chain(core::mem::take(&mut chain));
+                    is_chain_broken = flags.intersects(SqeFlags::IO_LINK | SqeFlags::IO_HARDLINK);
+
+                    if self.flags.contains(SetupFlags::SUBMIT_ALL) {
+                        continue;
+                    } else {
+                        break;
+                    }
+                }
+            };
+
+            if is_chain_broken {
+                is_chain_broken = request.is_linked();
+                self.cancel_chain(vec![request]);
+                continue;
+            }
+
+            let is_linked = request.is_linked();
+            chain.push(request);
+            if !is_linked {
+                self.issue_chain(core::mem::take(&mut chain), ctx);
+            }
+        }
+
+        // Like Linux, a chain that is not terminated in this submission is issued as is.
+        if !chain.is_empty() {
+            self.issue_chain(chain, ctx);
+        }
+
+        drop(consumer);
+
+        num_submitted
+    }
+
+    fn issue_chain(self: &Arc<Self>, mut chain: Vec<Request>, ctx: &Context) {
+        // `IOSQE_ASYNC` is only a hint, so non-blocking requests are always issued directly
+        // unless they are linked with other requests.
+        if chain.len() == 1 && chain[0].is_nonblocking() {
+            chain.pop().unwrap().issue_nonblocking(self);
+            return;
+        }
+
+        let ring_ctx = self.clone();
+        let work = Box::new(move |ctx: &Context| ring_ctx.execute_chain(chain, ctx));
+        self.workers.queue(work, ctx);
+    }
+
+    /// Executes the linked requests one by one in a worker.
+    ///
+    /// If a request fails, the rest of the chain is canceled, unless the request is linked with
+    /// `IOSQE_IO_HARDLINK`.
+    fn execute_chain(&self, chain: Vec<Request>, ctx: &Context) {
+        let mut requests = chain.into_iter();
+
+        while let Some(request) = requests.next() {
+            let res = request.execute(self, ctx);
+            self.complete(request.user_data(), request.flags(), res);
+
+            if res < 0 && !request.flags().contains(SqeFlags::IO_HARDLINK) {
+                self.cancel_chain(requests.collect());
+                return;
+            }
+        }
+    }
+
+    fn cancel_chain(&self, chain: Vec<Request>) {
+        for request in chain {
+            self.complete(
+                request.user_data(),
+                request.flags(),
+                -(Errno::ECANCELED as i32),
+            );
+        }
+    }
+
+    /// Runs the SQ polling thread until the io_uring instance is closed.
+    fn run_sq_poller(self: &Arc<Self>, ctx: &Context) {
+        let sq_poller = self.sq_poller.as_ref().unwrap();
+        let mut last_active = MonotonicClock::get().read_time();
+
+        while !sq_poller.is_shutdown() {
+            if self.submit(self.rings.sq_entries(), ctx) > 0 {
+                last_active = MonotonicClock::get().read_time();
+                continue;
+            }
+
+            if MonotonicClock::get().read_time() - last_active < sq_poller.idle() {
+                Thread::yield_now();
+                continue;
+            }
+
+            self.rings
+                .update_sq_flags(|flags| flags.insert(SqRingFlags::NEED_WAKEUP));
+            // Make sure that the user space sees the flag if it produces SQEs after we check the
+            // SQ below. Otherwise, we will see the SQEs.
+            fence(Ordering::SeqCst);
+
+            if self.rings.num_pending_sqes() == 0 && !sq_poller.sleep() {
+                break;
+            }
+
+            self.rings
+                .update_sq_flags(|flags| flags.remove(SqRingFlags::NEED_WAKEUP));
+            last_active = MonotonicClock::get().read_time();
+        }
+    }
+}
+
+impl Drop for IoUring {
+    fn drop(&mut self) {
+        // The pending requests hold references to the ring context, so they must be canceled
+        // here to break the reference cycles.
+        self.ctx.polls.cancel_all();
+        self.ctx.timeouts.cancel_all();
+        self.ctx.workers.shutdown();
+        if let Some(sq_poller) = self.ctx.sq_poller.as_ref() {
+            sq_poller.shutdown();
+        }
+    }
+}
+
+impl Pollable for IoUring {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.ctx.rings.poll(mask, poller)
+    }
+}
+
+impl FileLike for IoUring {
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `IoUring` to it.
+        Metadata::new_file(
+            0,
+            InodeMode::from_bits_truncate(0o600),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+
+    fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)> {
+        self.ctx.rings.mmap_vmo(offset)
+    }
+}
+
+/// Checks and rounds up the number of the entries of a queue.
+fn check_entries(entries: u32, max_entries: u32, is_clamp: bool) -> Result<u32> {
+    if entries == 0 {
+        return_errno_with_message!(Errno::EINVAL, "the number of entries is zero");
+    }
+
+    let entries = if entries <= max_entries {
+        entries
+    } else if is_clamp {
+        max_entries
+    } else {
+        return_errno_with_message!(Errno::EINVAL, "the number of entries is too large");
+    };
+
+    Ok(entries.next_power_of_two())
+}
+
+/// Gets the file to be registered, where `-1` means an empty slot.
+fn get_file_or_empty(fd: i32, ctx: &Context) -> Result<Option<Arc<dyn FileLike>>> {
+    if fd == -1 {
+        return Ok(None);
+    }
+
+    let file = ctx
+        .posix_thread
+        .file_table()
+        .lock()
+        .get_file(fd as FileDesc)?
+        .clone();
+    // Registering an io_uring instance to itself would create a reference cycle.
+    if file.downcast_ref::<IoUring>().is_some() {
+        return_errno_with_message!(Errno::EBADF, "io_uring instances cannot be registered");
+    }
+
+    Ok(Some(file))
+}
+
+/// Writes the supported operations for `IORING_REGISTER_PROBE`.
+fn write_probe(addr: Vaddr, nr_ops: u32, ctx: &Context) -> Result<()> {
+    let ops_len = nr_ops.min(Opcode::LAST as u32 + 1);
+    let user_space = ctx.user_space();
+
+    let header = CIoUringProbe {
+        last_op: Opcode::LAST,
+        ops_len: ops_len as u8,
+        resv: 0,
+        resv2: [0; 3],
+    };
+    user_space.write_val(addr, &header)?;
+
+    let ops_addr = addr + size_of::<CIoUringProbe>();
+    for op in 0..ops_len {
+        let flags = if Opcode::try_from(op as u8).is_ok() {
+            IO_URING_OP_SUPPORTED
+        } else {
+            0
+        };
+        let probe_op = CIoUringProbeOp {
+            op: op as u8,
+            resv: 0,
+            flags,
+            resv2: 0,
+        };
+        user_space.write_val(
+            ops_addr + op as usize * size_of::<CIoUringProbeOp>(),
+            &probe_op,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The io_uring asynchronous I/O interface.
+//!
+//! An io_uring instance consists of a submission queue (SQ) and a completion queue (CQ), both of
+//! which are ring buffers shared between the user space and the kernel. The user space fills
+//! submission queue entries (SQEs) and notifies the kernel via `io_uring_enter`. The kernel
+//! executes the requests and posts completion queue entries (CQEs) to the CQ.
+//!
+//! Requests that may block (e.g., reading a socket) are executed by a pool of worker threads that
+//! belong to the process that submits them. Requests that only wait for events (e.g., polling a
+//! file or waiting for a timeout) are completed directly by the event callbacks. If the
+//! `IORING_SETUP_SQPOLL` flag is specified, a kernel thread polls the SQ so that the user space
+//! can submit requests without any system calls.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/io_uring.7.html>.
+
+use crate::prelude::*;
+
+mod file;
+mod op;
+mod poll;
+mod ring;
+mod timeout;
+mod worker;
+
+pub use file::IoUring;
+
+/// The maximum number of the SQ entries.
+const IORING_MAX_ENTRIES: u32 = 32768;
+/// The maximum number of the CQ entries.
+const IORING_MAX_CQ_ENTRIES: u32 = 2 * IORING_MAX_ENTRIES;
+
+bitflags! {
+    /// The flags of `io_uring_setup`.
+    pub struct SetupFlags: u32 {
+        const IOPOLL         = 1 << 0;
+        const SQPOLL         = 1 << 1;
+        const SQ_AFF         = 1 << 2;
+        const CQSIZE         = 1 << 3;
+        const CLAMP          = 1 << 4;
+        const ATTACH_WQ      = 1 << 5;
+        const R_DISABLED     = 1 << 6;
+        const SUBMIT_ALL     = 1 << 7;
+        const COOP_TASKRUN   = 1 << 8;
+        const TASKRUN_FLAG   = 1 << 9;
+        const SQE128         = 1 << 10;
+        const CQE32          = 1 << 11;
+        const SINGLE_ISSUER  = 1 << 12;
+        const DEFER_TASKRUN  = 1 << 13;
+        const NO_MMAP        = 1 << 14;
+        const REGISTERED_FD_ONLY = 1 << 15;
+        const NO_SQARRAY     = 1 << 16;
+    }
+}
+
+impl SetupFlags {
+    /// The flags that are supported.
+    ///
+    /// `COOP_TASKRUN` and `SINGLE_ISSUER` are only hints for optimization, so they can be safely
+    /// ignored.
+    const SUPPORTED: Self = Self::SQPOLL
+        .union(Self::SQ_AFF)
+        .union(Self::CQSIZE)
+        .union(Self::CLAMP)
+        .union(Self::SUBMIT_ALL)
+        .union(Self::COOP_TASKRUN)
+        .union(Self::SINGLE_ISSUER);
+}
+
+bitflags! {
+    /// The features of io_uring reported to the user space.
+    pub struct Features: u32 {
+        const SINGLE_MMAP     = 1 << 0;
+        const NODROP          = 1 << 1;
+        const SUBMIT_STABLE   = 1 << 2;
+        const RW_CUR_POS      = 1 << 3;
+        const CUR_PERSONALITY = 1 << 4;
+        const FAST_POLL       = 1 << 5;
+        const POLL_32BITS     = 1 << 6;
+        const SQPOLL_NONFIXED = 1 << 7;
+        const EXT_ARG         = 1 << 8;
+        const NATIVE_WORKERS  = 1 << 9;
+    }
+}
+
+impl Features {
+    const SUPPORTED: Self = Self::SINGLE_MMAP
+        .union(Self::NODROP)
+        .union(Self::SUBMIT_STABLE)
+        .union(Self::RW_CUR_POS)
+        .union(Self::POLL_32BITS)
+        .union(Self::SQPOLL_NONFIXED)
+        .union(Self::NATIVE_WORKERS);
+}
+
+bitflags! {
+    /// The flags of `io_uring_enter`.
+    pub struct EnterFlags: u32 {
+        const GETEVENTS       = 1 << 0;
+        const SQ_WAKEUP       = 1 << 1;
+        const SQ_WAIT         = 1 << 2;
+        const EXT_ARG         = 1 << 3;
+        const REGISTERED_RING = 1 << 4;
+    }
+}
+
+bitflags! {
+    /// The flags in the `flags` field of the SQ ring.
+    struct SqRingFlags: u32 {
+        /// The SQ polling thread is sleeping and needs to be woken up.
+        const NEED_WAKEUP = 1 << 0;
+        /// The CQ ring has overflowed.
+        const CQ_OVERFLOW = 1 << 1;
+    }
+}
+
+/// The operation codes of `io_uring_register`.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+pub enum RegisterOp {
+    RegisterBuffers = 0,
+    UnregisterBuffers = 1,
+    RegisterFiles = 2,
+    UnregisterFiles = 3,
+    RegisterEventfd = 4,
+    UnregisterEventfd = 5,
+    RegisterFilesUpdate = 6,
+    RegisterEventfdAsync = 7,
+    RegisterProbe = 8,
+}
+
+/// The parameters of `io_uring_setup`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct CIoUringParams {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: CIoSqringOffsets,
+    pub cq_off: CIoCqringOffsets,
+}
+
+/// The offsets of the fields in the SQ ring.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct CIoSqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub flags: u32,
+    pub dropped: u32,
+    pub array: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// The offsets of the fields in the CQ ring.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct CIoCqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub overflow: u32,
+    pub cqes: u32,
+    pub flags: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// The header of the result of `IORING_REGISTER_PROBE`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CIoUringProbe {
+    last_op: u8,
+    ops_len: u8,
+    resv: u16,
+    resv2: [u32; 3],
+}
+
+/// An entry in the result of `IORING_REGISTER_PROBE`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CIoUringProbeOp {
+    op: u8,
+    resv: u8,
+    flags: u16,
+    resv2: u32,
+}
+
+/// The flag in [`CIoUringProbeOp`] indicating that the operation is supported.
+const IO_URING_OP_SUPPORTED: u16 = 1 << 0;
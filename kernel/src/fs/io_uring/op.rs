@@ -0,0 +1,648 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use super::file::RingContext;
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        file_table::{FdFlags, FileDesc},
+        inode_handle::InodeHandle,
+        utils::StatusFlags,
+    },
+    net::socket::{MessageHeader, SendRecvFlags, Socket, SocketAddr},
+    prelude::*,
+    process::signal::Poller,
+    time::timespec_t,
+    util::{
+        net::{read_socket_addr_from_user, write_socket_addr_to_user, SockFlags},
+        IoVec, VmReaderArray, VmWriterArray,
+    },
+};
+
+/// A submission queue entry.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub(super) struct CIoUringSqe {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    /// The offset, or `addr2` for some operations.
+    off: u64,
+    addr: u64,
+    len: u32,
+    /// The operation-specific flags (e.g., `rw_flags`, `fsync_flags`, and `poll32_events`).
+    op_flags: u32,
+    user_data: u64,
+    buf_index: u16,
+    personality: u16,
+    file_index: u32,
+    addr3: u64,
+    __pad2: u64,
+}
+
+impl CIoUringSqe {
+    pub(super) fn user_data(&self) -> u64 {
+        self.user_data
+    }
+
+    /// Returns the flags of the SQE, ignoring the invalid bits.
+    pub(super) fn flags(&self) -> SqeFlags {
+        SqeFlags::from_bits_truncate(self.flags)
+    }
+}
+
+/// The operation codes.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+pub(super) enum Opcode {
+    Nop = 0,
+    Readv = 1,
+    Writev = 2,
+    Fsync = 3,
+    ReadFixed = 4,
+    WriteFixed = 5,
+    PollAdd = 6,
+    PollRemove = 7,
+    Timeout = 11,
+    TimeoutRemove = 12,
+    Accept = 13,
+    Connect = 16,
+    Read = 22,
+    Write = 23,
+    Send = 26,
+    Recv = 27,
+}
+
+impl Opcode {
+    /// The largest operation code that is supported.
+    pub(super) const LAST: u8 = Self::Recv as u8;
+}
+
+bitflags! {
+    /// The flags of an SQE.
+    pub(super) struct SqeFlags: u8 {
+        const FIXED_FILE       = 1 << 0;
+        const IO_DRAIN         = 1 << 1;
+        const IO_LINK          = 1 << 2;
+        const IO_HARDLINK      = 1 << 3;
+        const ASYNC            = 1 << 4;
+        const BUFFER_SELECT    = 1 << 5;
+        const CQE_SKIP_SUCCESS = 1 << 6;
+    }
+}
+
+/// The `fsync_flags` flag to sync only the file data.
+const IORING_FSYNC_DATASYNC: u32 = 1 << 0;
+/// The `timeout_flags` flag to use an absolute time.
+const IORING_TIMEOUT_ABS: u32 = 1 << 0;
+/// The `len` flag of `IORING_OP_POLL_ADD` to request multi-shot polling.
+const IORING_POLL_ADD_MULTI: u32 = 1 << 0;
+
+/// The maximum number of IO vectors in a request.
+const UIO_MAXIOV: usize = 1024;
+
+/// A request prepared from an SQE.
+///
+/// All the user memory that describes the request (e.g., the IO vectors and the socket
+/// addresses) is read when the request is prepared, so the user space can reuse it as soon as
+/// the SQE is consumed (i.e., `IORING_FEAT_SUBMIT_STABLE`).
+pub(super) struct Request {
+    user_data: u64,
+    flags: SqeFlags,
+    op: Op,
+}
+
+enum Op {
+    Nop,
+    Read {
+        file: Arc<dyn FileLike>,
+        buf: Vaddr,
+        len: usize,
+        offset: Option<usize>,
+    },
+    Write {
+        file: Arc<dyn FileLike>,
+        buf: Vaddr,
+        len: usize,
+        offset: Option<usize>,
+    },
+    Readv {
+        file: Arc<dyn FileLike>,
+        io_vecs: Box<[IoVec]>,
+        offset: Option<usize>,
+    },
+    Writev {
+        file: Arc<dyn FileLike>,
+        io_vecs: Box<[IoVec]>,
+        offset: Option<usize>,
+    },
+    Fsync {
+        file: Arc<dyn FileLike>,
+        is_datasync: bool,
+    },
+    PollAdd {
+        file: Arc<dyn FileLike>,
+        events: IoEvents,
+    },
+    PollRemove {
+        target: u64,
+    },
+    Timeout {
+        timeout: Duration,
+        is_abs: bool,
+        count: u32,
+    },
+    TimeoutRemove {
+        target: u64,
+    },
+    Accept {
+        socket: Arc<dyn Socket>,
+        addr: Vaddr,
+        addrlen: Vaddr,
+        flags: SockFlags,
+    },
+    Connect {
+        socket: Arc<dyn Socket>,
+        addr: SocketAddr,
+    },
+    Send {
+        socket: Arc<dyn Socket>,
+        buf: Vaddr,
+        len: usize,
+        flags: SendRecvFlags,
+    },
+    Recv {
+        socket: Arc<dyn Socket>,
+        buf: Vaddr,
+        len: usize,
+        flags: SendRecvFlags,
+    },
+}
+
+impl Request {
+    /// Prepares a request from an SQE.
+    ///
+    /// On errors, the error should be reported in the CQE whose user data is that of the SQE.
+    pub(super) fn prepare(sqe: &CIoUringSqe, ring: &RingContext, ctx: &Context) -> Result<Self> {
+        let flags = SqeFlags::from_bits(sqe.flags)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the SQE flags are invalid"))?;
+        if flags.intersects(SqeFlags::IO_DRAIN | SqeFlags::BUFFER_SELECT) {
+            return_errno_with_message!(Errno::EINVAL, "the SQE flags are not supported");
+        }
+
+        let opcode = Opcode::try_from(sqe.opcode)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the opcode is not supported"))?;
+
+        let get_file = || -> Result<Arc<dyn FileLike>> {
+            if flags.contains(SqeFlags::FIXED_FILE) {
+                ring.registered_file(sqe.fd as u32)
+            } else {
+                let file_table = ctx.posix_thread.file_table().lock_arc();
+                Ok(file_table.get_file(sqe.fd as FileDesc)?.clone())
+            }
+        };
+        let get_socket = || -> Result<Arc<dyn Socket>> {
+            get_file()?
+                .as_socket()
+                .ok_or_else(|| Error::with_message(Errno::ENOTSOCK, "the file is not a socket"))
+        };
+        // The offset of `-1` means the current file position (i.e., `IORING_FEAT_RW_CUR_POS`).
+        let offset = if sqe.off as i64 == -1 {
+            None
+        } else {
+            Some(sqe.off as usize)
+        };
+
+        let op = match opcode {
+            Opcode::Nop => Op::Nop,
+            Opcode::Read | Opcode::ReadFixed => {
+                let (buf, len) = check_buf(sqe, opcode == Opcode::ReadFixed, ring)?;
+                Op::Read {
+                    file: get_file()?,
+                    buf,
+                    len,
+                    offset,
+                }
+            }
+            Opcode::Write | Opcode::WriteFixed => {
+                let (buf, len) = check_buf(sqe, opcode == Opcode::WriteFixed, ring)?;
+                Op::Write {
+                    file: get_file()?,
+                    buf,
+                    len,
+                    offset,
+                }
+            }
+            Opcode::Readv => Op::Readv {
+                file: get_file()?,
+                io_vecs: read_io_vecs(sqe, ctx)?,
+                offset,
+            },
+            Opcode::Writev => Op::Writev {
+                file: get_file()?,
+                io_vecs: read_io_vecs(sqe, ctx)?,
+                offset,
+            },
+            Opcode::Fsync => Op::Fsync {
+                file: get_file()?,
+                is_datasync: sqe.op_flags & IORING_FSYNC_DATASYNC != 0,
+            },
+            Opcode::PollAdd => {
+                if sqe.len & IORING_POLL_ADD_MULTI != 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "multi-shot polling is not supported"
+                    );
+                }
+                Op::PollAdd {
+                    file: get_file()?,
+                    events: IoEvents::from_bits_truncate(sqe.op_flags)
+                        | IoEvents::ERR
+                        | IoEvents::HUP,
+                }
+            }
+            Opcode::PollRemove => Op::PollRemove { target: sqe.addr },
+            Opcode::Timeout => {
+                if sqe.len != 1 {
+                    return_errno_with_message!(Errno::EINVAL, "the timeout count is invalid");
+                }
+                if sqe.op_flags & !IORING_TIMEOUT_ABS != 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the timeout flags are not supported"
+                    );
+                }
+                let timespec = ctx.user_space().read_val::<timespec_t>(sqe.addr as Vaddr)?;
+                Op::Timeout {
+                    timeout: Duration::try_from(timespec)?,
+                    is_abs: sqe.op_flags & IORING_TIMEOUT_ABS != 0,
+                    count: sqe.off as u32,
+                }
+            }
+            Opcode::TimeoutRemove => {
+                if sqe.op_flags != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "updating timeouts is not supported");
+                }
+                Op::TimeoutRemove { target: sqe.addr }
+            }
+            Opcode::Accept => Op::Accept {
+                socket: get_socket()?,
+                addr: sqe.addr as Vaddr,
+                addrlen: sqe.off as Vaddr,
+                flags: SockFlags::from_bits(sqe.op_flags as i32).ok_or_else(|| {
+                    Error::with_message(Errno::EINVAL, "the accept flags are invalid")
+                })?,
+            },
+            Opcode::Connect => Op::Connect {
+                socket: get_socket()?,
+                addr: read_socket_addr_from_user(sqe.addr as Vaddr, sqe.off as usize)?,
+            },
+            Opcode::Send => Op::Send {
+                socket: get_socket()?,
+                buf: sqe.addr as Vaddr,
+                len: sqe.len as usize,
+                flags: SendRecvFlags::from_bits_truncate(sqe.op_flags as i32),
+            },
+            Opcode::Recv => Op::Recv {
+                socket: get_socket()?,
+                buf: sqe.addr as Vaddr,
+                len: sqe.len as usize,
+                flags: SendRecvFlags::from_bits_truncate(sqe.op_flags as i32),
+            },
+        };
+
+        Ok(Self {
+            user_data: sqe.user_data,
+            flags,
+            op,
+        })
+    }
+
+    pub(super) fn user_data(&self) -> u64 {
+        self.user_data
+    }
+
+    pub(super) fn flags(&self) -> SqeFlags {
+        self.flags
+    }
+
+    /// Returns whether the request is linked to the next one.
+    pub(super) fn is_linked(&self) -> bool {
+        self.flags
+            .intersects(SqeFlags::IO_LINK | SqeFlags::IO_HARDLINK)
+    }
+
+    /// Returns whether the request can be issued without blocking.
+    ///
+    /// Such requests either complete immediately or wait for events asynchronously, so they are
+    /// never executed by the workers unless they are linked with other requests.
+    pub(super) fn is_nonblocking(&self) -> bool {
+        matches!(
+            self.op,
+            Op::Nop
+                | Op::PollAdd { .. }
+                | Op::PollRemove { .. }
+                | Op::Timeout { .. }
+                | Op::TimeoutRemove { .. }
+        )
+    }
+
+    /// Issues a non-blocking request.
+    ///
+    /// See [`Self::is_nonblocking`] for what requests are non-blocking.
+    pub(super) fn issue_nonblocking(self, ring: &Arc<RingContext>) {
+        let Self {
+            user_data,
+            flags,
+            op,
+        } = self;
+
+        match op {
+            Op::PollAdd { file, events } => {
+                ring.polls()
+                    .arm(ring, user_data, flags, file.as_ref(), events)
+            }
+            Op::Timeout {
+                timeout,
+                is_abs,
+                count,
+            } => ring
+                .timeouts()
+                .arm(ring, user_data, flags, timeout, is_abs, count),
+            op => {
+                let res = op.execute_nonblocking(ring);
+                ring.complete(user_data, flags, res);
+            }
+        }
+    }
+
+    /// Executes the request, which may block, and returns the result.
+    ///
+    /// This method does not post the CQE. It should be called in the worker threads.
+    pub(super) fn execute(&self, ring: &RingContext, ctx: &Context) -> i32 {
+        let res = match &self.op {
+            Op::Nop | Op::PollRemove { .. } | Op::TimeoutRemove { .. } => {
+                return self.op.execute_nonblocking(ring)
+            }
+            Op::Read {
+                file,
+                buf,
+                len,
+                offset,
+            } => ctx
+                .user_space()
+                .writer(*buf, *len)
+                .and_then(|mut writer| read_file(file.as_ref(), &mut writer, *offset)),
+            Op::Write {
+                file,
+                buf,
+                len,
+                offset,
+            } => ctx
+                .user_space()
+                .reader(*buf, *len)
+                .and_then(|mut reader| write_file(file.as_ref(), &mut reader, *offset)),
+            Op::Readv {
+                file,
+                io_vecs,
+                offset,
+            } => readv(file.as_ref(), io_vecs, *offset, ctx),
+            Op::Writev {
+                file,
+                io_vecs,
+                offset,
+            } => writev(file.as_ref(), io_vecs, *offset, ctx),
+            Op::Fsync { file, is_datasync } => fsync(file.as_ref(), *is_datasync),
+            Op::PollAdd { file, events } => poll(file.as_ref(), *events),
+            Op::Timeout { .. } => Err(Error::with_message(
+                Errno::EINVAL,
+                "linked timeouts are not supported",
+            )),
+            Op::Accept {
+                socket,
+                addr,
+                addrlen,
+                flags,
+            } => accept(socket.as_ref(), *addr, *addrlen, *flags, ctx),
+            Op::Connect { socket, addr } => socket.connect(addr.clone()).map(|_| 0),
+            Op::Send {
+                socket,
+                buf,
+                len,
+                flags,
+            } => ctx.user_space().reader(*buf, *len).and_then(|mut reader| {
+                socket.sendmsg(&mut reader, MessageHeader::new(None, Vec::new()), *flags)
+            }),
+            Op::Recv {
+                socket,
+                buf,
+                len,
+                flags,
+            } => ctx.user_space().writer(*buf, *len).and_then(|mut writer| {
+                socket
+                    .recvmsg(&mut writer, *flags)
+                    .map(|(recv_len, _)| recv_len)
+            }),
+        };
+
+        to_cqe_res(res)
+    }
+}
+
+impl Op {
+    fn execute_nonblocking(&self, ring: &RingContext) -> i32 {
+        let res = match self {
+            Op::Nop => Ok(0),
+            Op::PollRemove { target } => ring.polls().remove(*target).map(|_| 0),
+            Op::TimeoutRemove { target } => ring.timeouts().remove(*target).map(|_| 0),
+            _ => unreachable!("the operation may block"),
+        };
+
+        to_cqe_res(res)
+    }
+}
+
+/// Converts the result of a request to the `res` field of the CQE.
+pub(super) fn to_cqe_res(res: Result<usize>) -> i32 {
+    match res {
+        Ok(len) => len.min(i32::MAX as usize) as i32,
+        Err(err) => -(err.error() as i32),
+    }
+}
+
+/// Checks the buffer of a read or write request.
+///
+/// If `is_fixed` is true, the buffer must be within the registered buffer specified by the
+/// `buf_index` field.
+fn check_buf(sqe: &CIoUringSqe, is_fixed: bool, ring: &RingContext) -> Result<(Vaddr, usize)> {
+    let buf = sqe.addr as Vaddr;
+    let len = sqe.len as usize;
+
+    if is_fixed {
+        let (fixed_buf, fixed_len) = ring.registered_buffer(sqe.buf_index)?;
+        if buf < fixed_buf
+            || buf
+                .checked_add(len)
+                .is_none_or(|end| end > fixed_buf + fixed_len)
+        {
+            return_errno_with_message!(
+                Errno::EFAULT,
+                "the buffer is not within the registered buffer"
+            );
+        }
+    }
+
+    Ok((buf, len))
+}
+
+fn read_io_vecs(sqe: &CIoUringSqe, ctx: &Context) -> Result<Box<[IoVec]>> {
+    if sqe.len as usize > UIO_MAXIOV {
+        return_errno_with_message!(Errno::EINVAL, "too many IO vectors");
+    }
+
+    IoVec::read_from_user(ctx, sqe.addr as Vaddr, sqe.len as usize)
+}
+
+fn read_file(file: &dyn FileLike, writer: &mut VmWriter, offset: Option<usize>) -> Result<usize> {
+    let Some(offset) = offset else {
+        return file.read(writer);
+    };
+
+    // Like Linux, the offset is ignored for the files that are not seekable.
+    match file.read_at(offset, writer) {
+        Err(err) if err.error() == Errno::ESPIPE => file.read(writer),
+        res => res,
+    }
+}
+
+fn write_file(file: &dyn FileLike, reader: &mut VmReader, offset: Option<usize>) -> Result<usize> {
+    let Some(offset) = offset else {
+        return file.write(reader);
+    };
+
+    // Like Linux, the offset is ignored for the files that are not seekable.
+    match file.write_at(offset, reader) {
+        Err(err) if err.error() == Errno::ESPIPE => file.write(reader),
+        res => res,
+    }
+}
+
+fn readv(
+    file: &dyn FileLike,
+    io_vecs: &[IoVec],
+    offset: Option<usize>,
+    ctx: &Context,
+) -> Result<usize> {
+    let mut total_len = 0;
+
+    let mut writer_array =
+        VmWriterArray::from_io_vecs(ctx.process.root_vmar().vm_space(), io_vecs)?;
+    for writer in writer_array.writers_mut() {
+        let offset = offset.map(|offset| offset + total_len);
+        let read_len = match read_file(file, writer, offset) {
+            Ok(read_len) => read_len,
+            Err(_) if total_len > 0 => break,
+            Err(err) => return Err(err),
+        };
+
+        total_len += read_len;
+        if read_len == 0 || writer.has_avail() {
+            break;
+        }
+    }
+
+    Ok(total_len)
+}
+
+fn writev(
+    file: &dyn FileLike,
+    io_vecs: &[IoVec],
+    offset: Option<usize>,
+    ctx: &Context,
+) -> Result<usize> {
+    let mut total_len = 0;
+
+    let mut reader_array =
+        VmReaderArray::from_io_vecs(ctx.process.root_vmar().vm_space(), io_vecs)?;
+    for reader in reader_array.readers_mut() {
+        let offset = offset.map(|offset| offset + total_len);
+        let write_len = match write_file(file, reader, offset) {
+            Ok(write_len) => write_len,
+            Err(_) if total_len > 0 => break,
+            Err(err) => return Err(err),
+        };
+
+        total_len += write_len;
+        if write_len == 0 || reader.has_remain() {
+            break;
+        }
+    }
+
+    Ok(total_len)
+}
+
+fn fsync(file: &dyn FileLike, is_datasync: bool) -> Result<usize> {
+    let inode_handle = file
+        .downcast_ref::<InodeHandle>()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the file cannot be synced"))?;
+
+    let dentry = inode_handle.dentry();
+    if is_datasync {
+        dentry.sync_data()?;
+    } else {
+        dentry.sync_all()?;
+    }
+
+    Ok(0)
+}
+
+/// Waits for the events of the file.
+///
+/// This is only used for linked requests. Otherwise, the events are waited asynchronously.
+fn poll(file: &dyn FileLike, events: IoEvents) -> Result<usize> {
+    let mut poller = Poller::new();
+
+    loop {
+        let revents = file.poll(events, Some(poller.as_handle_mut()));
+        if !revents.is_empty() {
+            return Ok(revents.bits() as usize);
+        }
+
+        poller.wait(None)?;
+    }
+}
+
+fn accept(
+    socket: &dyn Socket,
+    addr: Vaddr,
+    addrlen: Vaddr,
+    flags: SockFlags,
+    ctx: &Context,
+) -> Result<usize> {
+    let (connected_socket, socket_addr) = socket.accept()?;
+
+    if flags.contains(SockFlags::SOCK_NONBLOCK) {
+        connected_socket.set_status_flags(StatusFlags::O_NONBLOCK)?;
+    }
+
+    let fd_flags = if flags.contains(SockFlags::SOCK_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+
+    if addr != 0 {
+        write_socket_addr_to_user(&socket_addr, addr, addrlen)?;
+    }
+
+    let fd = ctx
+        .posix_thread
+        .file_table()
+        .lock()
+        .insert(connected_socket, fd_flags);
+
+    Ok(fd as usize)
+}
@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use ostd::sync::LocalIrqDisabled;
+
+use super::{file::RingContext, op::SqeFlags};
+use crate::{
+    events::{IoEvents, Observer},
+    fs::file_handle::FileLike,
+    prelude::*,
+    process::signal::PollHandle,
+};
+
+/// The pending `IORING_OP_POLL_ADD` requests of an io_uring instance.
+///
+/// A pending request is completed directly by the observer callback when the interesting events
+/// happen, which can be in the interrupt context. So no locks that may sleep are used here.
+pub(super) struct PollTable {
+    requests: SpinLock<Vec<Arc<PollRequest>>, LocalIrqDisabled>,
+}
+
+struct PollRequest {
+    user_data: u64,
+    flags: SqeFlags,
+    mask: IoEvents,
+    ring: Weak<RingContext>,
+    handle: SpinLock<Option<PollHandle>, LocalIrqDisabled>,
+    is_done: AtomicBool,
+}
+
+impl PollTable {
+    pub(super) fn new() -> Self {
+        Self {
+            requests: SpinLock::new(Vec::new()),
+        }
+    }
+
+    /// Arms a one-shot poll request for the file.
+    pub(super) fn arm(
+        &self,
+        ring: &Arc<RingContext>,
+        user_data: u64,
+        flags: SqeFlags,
+        file: &dyn FileLike,
+        mask: IoEvents,
+    ) {
+        let request = Arc::new(PollRequest {
+            user_data,
+            flags,
+            mask,
+            ring: Arc::downgrade(ring),
+            handle: SpinLock::new(None),
+            is_done: AtomicBool::new(false),
+        });
+
+        // Add the request to the table before polling, since the observer callback may complete
+        // the request (and remove it from the table) at any time after polling.
+        self.requests.lock().push(request.clone());
+
+        let mut handle = PollHandle::new(Arc::downgrade(&request) as _);
+        let events = file.poll(mask, Some(&mut handle));
+        *request.handle.lock() = Some(handle);
+
+        if !events.is_empty() {
+            request.complete(events.bits() as i32);
+        } else if request.is_done.load(Ordering::Acquire) {
+            // The request has been completed before the handle is stored.
+            let handle = request.handle.lock().take();
+            drop(handle);
+        }
+    }
+
+    /// Removes a pending poll request, which will complete with `ECANCELED`.
+    pub(super) fn remove(&self, user_data: u64) -> Result<()> {
+        let request = {
+            let mut requests = self.requests.lock();
+            let Some(pos) = requests
+                .iter()
+                .position(|request| request.user_data == user_data)
+            else {
+                return_errno_with_message!(Errno::ENOENT, "the poll request does not exist");
+            };
+            requests.swap_remove(pos)
+        };
+
+        request.complete(-(Errno::ECANCELED as i32));
+        Ok(())
+    }
+
+    /// Cancels all the pending poll requests without completing them.
+    pub(super) fn cancel_all(&self) {
+        let requests = core::mem::take(&mut *self.requests.lock());
+
+        for request in requests {
+            request.is_done.store(true, Ordering::Release);
+            let handle = request.handle.lock().take();
+            drop(handle);
+        }
+    }
+}
+
+impl PollRequest {
+    fn complete(&self, res: i32) {
+        if self.is_done.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let handle = self.handle.lock().take();
+        drop(handle);
+
+        let Some(ring) = self.ring.upgrade() else {
+            return;
+        };
+        ring.polls()
+            .requests
+            .lock()
+            .retain(|request| !core::ptr::eq(Arc::as_ptr(request), self));
+        ring.complete(self.user_data, self.flags, res);
+    }
+}
+
+impl Observer<IoEvents> for PollRequest {
+    fn on_events(&self, events: &IoEvents) {
+        let events = *events & self.mask;
+        if events.is_empty() {
+            return;
+        }
+
+        self.complete(events.bits() as i32);
+    }
+}
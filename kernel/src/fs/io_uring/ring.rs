@@ -0,0 +1,422 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    mem::{offset_of, size_of},
+    sync::atomic::{fence, Ordering},
+};
+
+use aster_rights::Rights;
+use ostd::{
+    mm::{UFrame, UntypedMem},
+    sync::{LocalIrqDisabled, WaitQueue},
+};
+
+use super::{op::CIoUringSqe, CIoCqringOffsets, CIoSqringOffsets, SqRingFlags};
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::signal::{PollHandle, Pollee},
+    vm::vmo::{Vmo, VmoOptions},
+};
+
+/// The `mmap` offset of the SQ ring.
+const IORING_OFF_SQ_RING: usize = 0;
+/// The `mmap` offset of the CQ ring.
+const IORING_OFF_CQ_RING: usize = 0x8000000;
+/// The `mmap` offset of the SQE array.
+const IORING_OFF_SQES: usize = 0x10000000;
+
+/// The header of the rings.
+///
+/// The SQ ring and the CQ ring share the same memory region, which starts with this header. The
+/// header is followed by the CQE array (at [`CQES_OFFSET`]) and the SQ index array.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct RingsHeader {
+    sq_head: u32,
+    sq_tail: u32,
+    cq_head: u32,
+    cq_tail: u32,
+    sq_ring_mask: u32,
+    cq_ring_mask: u32,
+    sq_ring_entries: u32,
+    cq_ring_entries: u32,
+    sq_dropped: u32,
+    sq_flags: u32,
+    cq_flags: u32,
+    cq_overflow: u32,
+}
+
+/// The offset of the CQE array in the rings.
+const CQES_OFFSET: usize = 64;
+
+const _: () = assert!(size_of::<RingsHeader>() <= CQES_OFFSET);
+
+/// A completion queue entry.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub(super) struct CIoUringCqe {
+    pub(super) user_data: u64,
+    pub(super) res: i32,
+    pub(super) flags: u32,
+}
+
+/// The shared SQ ring, CQ ring, and SQE array of an io_uring instance.
+///
+/// The memory is always committed and its frames are held, so CQEs can be posted without
+/// allocating memory or sleeping, even in the interrupt context.
+pub(super) struct Rings {
+    rings_vmo: Vmo<Rights>,
+    rings_frames: Box<[UFrame]>,
+    sqes_vmo: Vmo<Rights>,
+    sqes_frames: Box<[UFrame]>,
+    sq_entries: u32,
+    cq_entries: u32,
+    sq_array_offset: usize,
+    /// The SQ head, which is only updated by the kernel.
+    ///
+    /// The lock also serializes the consumers of the SQ.
+    sq_head: Mutex<u32>,
+    cq: SpinLock<CqInner, LocalIrqDisabled>,
+    sq_flags: SpinLock<SqRingFlags, LocalIrqDisabled>,
+    /// Threads that are waiting for new CQEs.
+    cq_wait_queue: WaitQueue,
+    /// Threads that are waiting for the SQ to have free entries.
+    sq_wait_queue: WaitQueue,
+    pollee: Pollee,
+}
+
+struct CqInner {
+    /// The CQ tail, which is only updated by the kernel.
+    tail: u32,
+    /// The CQEs that cannot be posted because the CQ ring is full.
+    overflow: VecDeque<CIoUringCqe>,
+}
+
+impl Rings {
+    pub(super) fn new(sq_entries: u32, cq_entries: u32) -> Result<Self> {
+        let sq_array_offset = CQES_OFFSET + cq_entries as usize * size_of::<CIoUringCqe>();
+        let rings_size = sq_array_offset + sq_entries as usize * size_of::<u32>();
+        let sqes_size = sq_entries as usize * size_of::<CIoUringSqe>();
+
+        let (rings_vmo, rings_frames) = alloc_committed_vmo(rings_size)?;
+        let (sqes_vmo, sqes_frames) = alloc_committed_vmo(sqes_size)?;
+
+        let rings = Self {
+            rings_vmo,
+            rings_frames,
+            sqes_vmo,
+            sqes_frames,
+            sq_entries,
+            cq_entries,
+            sq_array_offset,
+            sq_head: Mutex::new(0),
+            cq: SpinLock::new(CqInner {
+                tail: 0,
+                overflow: VecDeque::new(),
+            }),
+            sq_flags: SpinLock::new(SqRingFlags::empty()),
+            cq_wait_queue: WaitQueue::new(),
+            sq_wait_queue: WaitQueue::new(),
+            pollee: Pollee::new(),
+        };
+
+        rings.write_header(offset_of!(RingsHeader, sq_ring_mask), sq_entries - 1);
+        rings.write_header(offset_of!(RingsHeader, cq_ring_mask), cq_entries - 1);
+        rings.write_header(offset_of!(RingsHeader, sq_ring_entries), sq_entries);
+        rings.write_header(offset_of!(RingsHeader, cq_ring_entries), cq_entries);
+
+        Ok(rings)
+    }
+
+    pub(super) fn sq_entries(&self) -> u32 {
+        self.sq_entries
+    }
+
+    pub(super) fn cq_entries(&self) -> u32 {
+        self.cq_entries
+    }
+
+    /// Returns the offsets of the fields in the SQ ring and the CQ ring.
+    pub(super) fn offsets(&self) -> (CIoSqringOffsets, CIoCqringOffsets) {
+        let sq_off = CIoSqringOffsets {
+            head: offset_of!(RingsHeader, sq_head) as u32,
+            tail: offset_of!(RingsHeader, sq_tail) as u32,
+            ring_mask: offset_of!(RingsHeader, sq_ring_mask) as u32,
+            ring_entries: offset_of!(RingsHeader, sq_ring_entries) as u32,
+            flags: offset_of!(RingsHeader, sq_flags) as u32,
+            dropped: offset_of!(RingsHeader, sq_dropped) as u32,
+            array: self.sq_array_offset as u32,
+            resv1: 0,
+            user_addr: 0,
+        };
+
+        let cq_off = CIoCqringOffsets {
+            head: offset_of!(RingsHeader, cq_head) as u32,
+            tail: offset_of!(RingsHeader, cq_tail) as u32,
+            ring_mask: offset_of!(RingsHeader, cq_ring_mask) as u32,
+            ring_entries: offset_of!(RingsHeader, cq_ring_entries) as u32,
+            overflow: offset_of!(RingsHeader, cq_overflow) as u32,
+            cqes: CQES_OFFSET as u32,
+            flags: offset_of!(RingsHeader, cq_flags) as u32,
+            resv1: 0,
+            user_addr: 0,
+        };
+
+        (sq_off, cq_off)
+    }
+
+    /// Returns the VMO to be mapped at the `mmap` offset.
+    ///
+    /// Since the SQ ring and the CQ ring share the same memory region (i.e.,
+    /// `IORING_FEAT_SINGLE_MMAP`), both of their offsets are mapped to the same VMO.
+    pub(super) fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)> {
+        match offset {
+            IORING_OFF_SQ_RING | IORING_OFF_CQ_RING => Ok((self.rings_vmo.dup()?, 0)),
+            IORING_OFF_SQES => Ok((self.sqes_vmo.dup()?, 0)),
+            _ => return_errno_with_message!(Errno::EINVAL, "the mmap offset is invalid"),
+        }
+    }
+
+    /// Starts consuming the SQEs.
+    ///
+    /// The consumed SQEs are released to the user space when the returned consumer is dropped.
+    pub(super) fn consume_sq(&self) -> SqConsumer<'_> {
+        let head = self.sq_head.lock();
+        let old_head = *head;
+
+        SqConsumer {
+            rings: self,
+            head,
+            old_head,
+        }
+    }
+
+    /// Returns the number of the SQEs that have been submitted by the user space but have not
+    /// been consumed by the kernel.
+    ///
+    /// This method does not wait for the ongoing consumers, so the SQEs that are being consumed
+    /// are counted as pending.
+    pub(super) fn num_pending_sqes(&self) -> u32 {
+        let head = self.read_header(offset_of!(RingsHeader, sq_head));
+        self.read_header(offset_of!(RingsHeader, sq_tail))
+            .wrapping_sub(head)
+            .min(self.sq_entries)
+    }
+
+    /// Posts a CQE.
+    ///
+    /// If the CQ ring is full, the CQE will be kept in the kernel and posted later when the user
+    /// space makes room for it (i.e., `IORING_FEAT_NODROP`).
+    ///
+    /// This method can be called in the interrupt context.
+    pub(super) fn post_cqe(&self, cqe: CIoUringCqe) {
+        let mut cq = self.cq.lock();
+
+        if !self.flush_overflow_locked(&mut cq) || !self.try_post_locked(&mut cq, &cqe) {
+            cq.overflow.push_back(cqe);
+            self.update_sq_flags(|flags| flags.insert(SqRingFlags::CQ_OVERFLOW));
+        }
+
+        drop(cq);
+
+        self.cq_wait_queue.wake_all();
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    /// Moves the CQEs that are kept in the kernel to the CQ ring if possible.
+    pub(super) fn flush_overflow(&self) {
+        let mut cq = self.cq.lock();
+        if cq.overflow.is_empty() {
+            return;
+        }
+
+        self.flush_overflow_locked(&mut cq);
+        drop(cq);
+
+        self.cq_wait_queue.wake_all();
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    /// Moves the CQEs that are kept in the kernel to the CQ ring.
+    ///
+    /// This method returns whether all the CQEs have been moved.
+    fn flush_overflow_locked(&self, cq: &mut CqInner) -> bool {
+        if cq.overflow.is_empty() {
+            return true;
+        }
+
+        while let Some(cqe) = cq.overflow.front() {
+            let cqe = *cqe;
+            if !self.try_post_locked(cq, &cqe) {
+                return false;
+            }
+            cq.overflow.pop_front();
+        }
+
+        self.update_sq_flags(|flags| flags.remove(SqRingFlags::CQ_OVERFLOW));
+        true
+    }
+
+    fn try_post_locked(&self, cq: &mut CqInner, cqe: &CIoUringCqe) -> bool {
+        let head = self.read_header(offset_of!(RingsHeader, cq_head));
+        // Make sure that the user space has finished reading the CQE before we overwrite it.
+        fence(Ordering::Acquire);
+
+        if cq.tail.wrapping_sub(head) >= self.cq_entries {
+            return false;
+        }
+
+        let index = (cq.tail & (self.cq_entries - 1)) as usize;
+        let offset = CQES_OFFSET + index * size_of::<CIoUringCqe>();
+        self.rings_frames[offset / PAGE_SIZE]
+            .writer()
+            .skip(offset % PAGE_SIZE)
+            .write_val(cqe)
+            .unwrap();
+
+        // Make sure that the CQE is visible before the new tail.
+        fence(Ordering::Release);
+        cq.tail = cq.tail.wrapping_add(1);
+        self.write_header(offset_of!(RingsHeader, cq_tail), cq.tail);
+
+        true
+    }
+
+    /// Returns the number of the CQEs that have not been consumed by the user space.
+    pub(super) fn num_ready_cqes(&self) -> u32 {
+        let tail = self.cq.lock().tail;
+        let head = self.read_header(offset_of!(RingsHeader, cq_head));
+
+        tail.wrapping_sub(head)
+    }
+
+    pub(super) fn cq_wait_queue(&self) -> &WaitQueue {
+        &self.cq_wait_queue
+    }
+
+    pub(super) fn sq_wait_queue(&self) -> &WaitQueue {
+        &self.sq_wait_queue
+    }
+
+    pub(super) fn update_sq_flags(&self, op: impl FnOnce(&mut SqRingFlags)) {
+        let mut flags = self.sq_flags.lock();
+        op(&mut flags);
+        self.write_header(offset_of!(RingsHeader, sq_flags), flags.bits());
+    }
+
+    pub(super) fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        // The user space consumes CQEs and produces SQEs without notifying the kernel, so the
+        // cached events may be outdated.
+        self.pollee.invalidate();
+
+        self.pollee.poll_with(mask, poller, || {
+            let mut events = IoEvents::empty();
+            if self.num_ready_cqes() > 0 {
+                events |= IoEvents::IN;
+            }
+            if self.num_pending_sqes() < self.sq_entries {
+                events |= IoEvents::OUT;
+            }
+            events
+        })
+    }
+
+    fn read_header(&self, offset: usize) -> u32 {
+        self.rings_frames[0]
+            .reader()
+            .skip(offset)
+            .read_once::<u32>()
+            .unwrap()
+    }
+
+    fn write_header(&self, offset: usize, val: u32) {
+        self.rings_frames[0]
+            .writer()
+            .skip(offset)
+            .write_once(&val)
+            .unwrap();
+    }
+}
+
+/// A consumer of the SQEs.
+pub(super) struct SqConsumer<'a> {
+    rings: &'a Rings,
+    head: MutexGuard<'a, u32>,
+    old_head: u32,
+}
+
+impl SqConsumer<'_> {
+    /// Returns the number of the SQEs that are available.
+    pub(super) fn num_pending(&self) -> u32 {
+        let tail = self.rings.read_header(offset_of!(RingsHeader, sq_tail));
+        tail.wrapping_sub(*self.head).min(self.rings.sq_entries)
+    }
+
+    /// Consumes an SQE.
+    ///
+    /// The SQ index array entries that refer to invalid SQEs are skipped and counted in the
+    /// `dropped` field of the SQ ring. This method returns `None` if there are no more SQEs.
+    pub(super) fn pop(&mut self) -> Option<CIoUringSqe> {
+        let rings = self.rings;
+
+        while self.num_pending() > 0 {
+            // Make sure that we read the SQE after the user space has written it.
+            fence(Ordering::Acquire);
+
+            let array_offset = rings.sq_array_offset
+                + (*self.head & (rings.sq_entries - 1)) as usize * size_of::<u32>();
+            let index = rings.rings_frames[array_offset / PAGE_SIZE]
+                .reader()
+                .skip(array_offset % PAGE_SIZE)
+                .read_once::<u32>()
+                .unwrap();
+            *self.head = self.head.wrapping_add(1);
+
+            if index >= rings.sq_entries {
+                let dropped_offset = offset_of!(RingsHeader, sq_dropped);
+                let dropped = rings.read_header(dropped_offset);
+                rings.write_header(dropped_offset, dropped.wrapping_add(1));
+                continue;
+            }
+
+            let sqe_offset = index as usize * size_of::<CIoUringSqe>();
+            let sqe = rings.sqes_frames[sqe_offset / PAGE_SIZE]
+                .reader()
+                .skip(sqe_offset % PAGE_SIZE)
+                .read_val::<CIoUringSqe>()
+                .unwrap();
+            return Some(sqe);
+        }
+
+        None
+    }
+}
+
+impl Drop for SqConsumer<'_> {
+    fn drop(&mut self) {
+        if *self.head == self.old_head {
+            return;
+        }
+
+        // Make sure that we have finished reading the SQEs before releasing them.
+        fence(Ordering::Release);
+        self.rings
+            .write_header(offset_of!(RingsHeader, sq_head), *self.head);
+
+        self.rings.sq_wait_queue.wake_all();
+        self.rings.pollee.notify(IoEvents::OUT);
+    }
+}
+
+/// Allocates a VMO and commits all of its pages.
+fn alloc_committed_vmo(size: usize) -> Result<(Vmo<Rights>, Box<[UFrame]>)> {
+    let vmo = VmoOptions::<Rights>::new(size).alloc()?;
+
+    let frames = (0..vmo.size())
+        .step_by(PAGE_SIZE)
+        .map(|offset| vmo.commit_page(offset))
+        .collect::<Result<Box<[UFrame]>>>()?;
+
+    Ok((vmo, frames))
+}
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use ostd::sync::LocalIrqDisabled;
+
+use super::{file::RingContext, op::SqeFlags};
+use crate::{
+    prelude::*,
+    time::{clocks::MonotonicClock, timer::Timeout, Timer},
+};
+
+/// The pending `IORING_OP_TIMEOUT` requests of an io_uring instance.
+///
+/// A timeout request completes with `ETIME` when the timer expires, or with zero when the
+/// specified number of other requests have completed.
+pub(super) struct TimeoutTable {
+    requests: SpinLock<Vec<Arc<TimeoutRequest>>, LocalIrqDisabled>,
+    /// The number of completed requests, excluding the timeout requests.
+    num_completions: AtomicU64,
+}
+
+struct TimeoutRequest {
+    user_data: u64,
+    flags: SqeFlags,
+    /// The value of `num_completions` that completes the request, if any.
+    target_completions: Option<u64>,
+    timer: Arc<Timer>,
+    ring: Weak<RingContext>,
+    is_done: AtomicBool,
+}
+
+impl TimeoutTable {
+    pub(super) fn new() -> Self {
+        Self {
+            requests: SpinLock::new(Vec::new()),
+            num_completions: AtomicU64::new(0),
+        }
+    }
+
+    /// Arms a timeout request.
+    ///
+    /// If `count` is not zero, the request also completes after `count` other requests have
+    /// completed.
+    pub(super) fn arm(
+        &self,
+        ring: &Arc<RingContext>,
+        user_data: u64,
+        flags: SqeFlags,
+        timeout: Duration,
+        is_abs: bool,
+        count: u32,
+    ) {
+        let target_completions = if count == 0 {
+            None
+        } else {
+            Some(self.num_completions.load(Ordering::Relaxed) + count as u64)
+        };
+
+        let request = Arc::new_cyclic(|weak_request: &Weak<TimeoutRequest>| {
+            let weak_request = weak_request.clone();
+            let timer = MonotonicClock::timer_manager().create_timer(move || {
+                if let Some(request) = weak_request.upgrade() {
+                    request.complete(-(Errno::ETIME as i32));
+                }
+            });
+
+            TimeoutRequest {
+                user_data,
+                flags,
+                target_completions,
+                timer,
+                ring: Arc::downgrade(ring),
+                is_done: AtomicBool::new(false),
+            }
+        });
+
+        self.requests.lock().push(request.clone());
+
+        let timeout = if is_abs {
+            Timeout::When(timeout)
+        } else {
+            Timeout::After(timeout)
+        };
+        request.timer.set_timeout(timeout);
+    }
+
+    /// Removes a pending timeout request, which will complete with `ECANCELED`.
+    pub(super) fn remove(&self, user_data: u64) -> Result<()> {
+        let request = {
+            let requests = self.requests.lock();
+            let Some(request) = requests
+                .iter()
+                .find(|request| request.user_data == user_data)
+            else {
+                return_errno_with_message!(Errno::ENOENT, "the timeout request does not exist");
+            };
+            request.clone()
+        };
+
+        request.complete(-(Errno::ECANCELED as i32));
+        Ok(())
+    }
+
+    /// Counts a completed request and completes the timeout requests that are waiting for it.
+    pub(super) fn on_completion(&self) {
+        let num_completions = self.num_completions.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let completed = self
+            .requests
+            .lock()
+            .iter()
+            .filter(|request| {
+                request
+                    .target_completions
+                    .is_some_and(|target| target <= num_completions)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for request in completed {
+            request.complete(0);
+        }
+    }
+
+    /// Cancels all the pending timeout requests without completing them.
+    pub(super) fn cancel_all(&self) {
+        let requests = core::mem::take(&mut *self.requests.lock());
+
+        for request in requests {
+            request.is_done.store(true, Ordering::Release);
+            request.timer.cancel();
+        }
+    }
+}
+
+impl TimeoutRequest {
+    fn complete(&self, res: i32) {
+        if self.is_done.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        self.timer.cancel();
+
+        let Some(ring) = self.ring.upgrade() else {
+            return;
+        };
+        ring.timeouts()
+            .requests
+            .lock()
+            .retain(|request| !core::ptr::eq(Arc::as_ptr(request), self));
+        // The completions of the timeout requests are not counted.
+        ring.post_cqe(self.user_data, self.flags, res);
+    }
+}
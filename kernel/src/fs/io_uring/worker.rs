@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use ostd::{
+    cpu::{CpuId, CpuSet, UserContext},
+    sync::WaitQueue,
+    task::Task,
+    user::UserSpace,
+};
+
+use crate::{
+    prelude::*,
+    process::{
+        posix_thread::{allocate_posix_tid, AsPosixThread, AsThreadLocal, PosixThreadBuilder},
+        signal::{constants::SIGKILL, signals::kernel::KernelSignal},
+        Credentials,
+    },
+    thread::{AsThread, Thread},
+};
+
+/// The maximum number of the workers of an io_uring instance.
+const MAX_WORKERS: usize = 16;
+
+/// A piece of work that is executed by a worker.
+pub(super) type Work = Box<dyn FnOnce(&Context) + Send>;
+
+/// A pool of the workers that execute the requests that may block.
+///
+/// The workers are spawned on demand. Each worker is a POSIX thread that shares the resources
+/// (e.g., the address space and the file table) with the thread that queues the work when the
+/// worker is spawned.
+pub(super) struct WorkerPool {
+    inner: SpinLock<PoolInner>,
+    wait_queue: WaitQueue,
+}
+
+struct PoolInner {
+    works: VecDeque<Work>,
+    workers: Vec<Arc<Thread>>,
+    /// The number of the workers, including the ones that are being spawned.
+    num_workers: usize,
+    /// The number of the workers that are waiting for works.
+    num_idle: usize,
+    is_shutdown: bool,
+}
+
+impl WorkerPool {
+    pub(super) fn new() -> Arc<Self> {
+        let inner = PoolInner {
+            works: VecDeque::new(),
+            workers: Vec::new(),
+            num_workers: 0,
+            num_idle: 0,
+            is_shutdown: false,
+        };
+
+        Arc::new(Self {
+            inner: SpinLock::new(inner),
+            wait_queue: WaitQueue::new(),
+        })
+    }
+
+    /// Queues a work.
+    ///
+    /// If there are no idle workers, a new worker is spawned with the resources of the current
+    /// thread, unless the number of the workers has reached the limit.
+    pub(super) fn queue(self: &Arc<Self>, work: Work, ctx: &Context) {
+        let mut inner = self.inner.lock();
+        if inner.is_shutdown {
+            drop(inner);
+            drop(work);
+            return;
+        }
+
+        inner.works.push_back(work);
+        let should_spawn = inner.works.len() > inner.num_idle && inner.num_workers < MAX_WORKERS;
+        if should_spawn {
+            inner.num_workers += 1;
+        }
+        drop(inner);
+
+        if should_spawn {
+            let pool = self.clone();
+            let worker = spawn_kernel_worker(ctx, CpuSet::new_full(), move || pool.run_worker());
+            self.inner.lock().workers.push(worker);
+        }
+
+        self.wait_queue.wake_one();
+    }
+
+    fn run_worker(&self) {
+        with_current_context(|ctx| loop {
+            self.inner.lock().num_idle += 1;
+            let work = self.wait_queue.pause_until(|| {
+                let mut inner = self.inner.lock();
+                if inner.is_shutdown {
+                    return Some(None);
+                }
+                inner.works.pop_front().map(Some)
+            });
+            self.inner.lock().num_idle -= 1;
+
+            // The worker exits if the pool is shut down or if the worker is interrupted by
+            // signals. In the latter case, a new worker will be spawned for new works.
+            let Ok(Some(work)) = work else {
+                break;
+            };
+            work(ctx);
+        });
+
+        let mut inner = self.inner.lock();
+        inner.num_workers -= 1;
+        let current_thread = Thread::current().unwrap();
+        inner
+            .workers
+            .retain(|worker| !Arc::ptr_eq(worker, &current_thread));
+    }
+
+    /// Shuts down the pool.
+    ///
+    /// The pending works are discarded. The workers that are executing works are interrupted,
+    /// and all the workers will exit.
+    pub(super) fn shutdown(&self) {
+        let (works, workers) = {
+            let mut inner = self.inner.lock();
+            inner.is_shutdown = true;
+            (
+                core::mem::take(&mut inner.works),
+                core::mem::take(&mut inner.workers),
+            )
+        };
+        drop(works);
+
+        self.wait_queue.wake_all();
+        for worker in workers.iter() {
+            interrupt_kernel_worker(worker);
+        }
+    }
+}
+
+/// The poller of the SQ (i.e., `IORING_SETUP_SQPOLL`).
+///
+/// The poller runs in a dedicated kernel worker that submits the SQEs as soon as the user space
+/// produces them. If there are no SQEs for the idle time, the poller sleeps until the user space
+/// wakes it up via `io_uring_enter` with `IORING_ENTER_SQ_WAKEUP`.
+pub(super) struct SqPoller {
+    idle: Duration,
+    /// The CPU that the poller is bound to (i.e., `IORING_SETUP_SQ_AFF`).
+    cpu: Option<CpuId>,
+    is_woken: AtomicBool,
+    is_shutdown: AtomicBool,
+    wait_queue: WaitQueue,
+    thread: Mutex<Option<Arc<Thread>>>,
+}
+
+impl SqPoller {
+    pub(super) fn new(idle: Duration, cpu: Option<CpuId>) -> Self {
+        Self {
+            idle,
+            cpu,
+            is_woken: AtomicBool::new(false),
+            is_shutdown: AtomicBool::new(false),
+            wait_queue: WaitQueue::new(),
+            thread: Mutex::new(None),
+        }
+    }
+
+    /// Spawns the thread that runs `func` with the resources of the current thread.
+    pub(super) fn spawn<F>(&self, ctx: &Context, func: F)
+    where
+        F: FnOnce(&Context) + Send + Sync + 'static,
+    {
+        let cpu_affinity = self.cpu.map_or_else(CpuSet::new_full, CpuSet::from);
+        let thread = spawn_kernel_worker(ctx, cpu_affinity, move || with_current_context(func));
+        *self.thread.lock() = Some(thread);
+    }
+
+    /// Returns the time to keep polling before sleeping.
+    pub(super) fn idle(&self) -> Duration {
+        self.idle
+    }
+
+    /// Sleeps until the poller is woken up.
+    ///
+    /// This method returns `false` if the poller has been shut down.
+    pub(super) fn sleep(&self) -> bool {
+        let res = self.wait_queue.pause_until(|| {
+            if self.is_shutdown() {
+                return Some(false);
+            }
+            self.is_woken.swap(false, Ordering::Relaxed).then_some(true)
+        });
+
+        // If the poller is interrupted by signals, it keeps polling unless it has been shut down.
+        res.unwrap_or_else(|_| !self.is_shutdown())
+    }
+
+    pub(super) fn wake_up(&self) {
+        self.is_woken.store(true, Ordering::Relaxed);
+        self.wait_queue.wake_all();
+    }
+
+    pub(super) fn is_shutdown(&self) -> bool {
+        self.is_shutdown.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn shutdown(&self) {
+        self.is_shutdown.store(true, Ordering::Relaxed);
+        self.wait_queue.wake_all();
+
+        if let Some(thread) = self.thread.lock().take() {
+            interrupt_kernel_worker(&thread);
+        }
+    }
+}
+
+/// Spawns a kernel worker that runs `func` with the resources of the current thread.
+///
+/// The worker only runs on the CPUs in `cpu_affinity`.
+fn spawn_kernel_worker<F>(ctx: &Context, cpu_affinity: CpuSet, func: F) -> Arc<Thread>
+where
+    F: FnOnce() + Send + Sync + 'static,
+{
+    let posix_thread = ctx.posix_thread;
+
+    let user_space = {
+        let vm_space = ctx.process.root_vmar().vm_space().clone();
+        Arc::new(UserSpace::new(vm_space, UserContext::default()))
+    };
+    let credentials = Credentials::new_from(&posix_thread.credentials());
+
+    let task = PosixThreadBuilder::new(allocate_posix_tid(), user_space, credentials)
+        .process(posix_thread.weak_process())
+        .file_table(posix_thread.file_table())
+        .fs(posix_thread.fs())
+        .ns_proxy(posix_thread.ns_proxy().lock().clone())
+        .build_kernel_worker(func);
+
+    let thread = task.as_thread().unwrap().clone();
+    thread.atomic_cpu_affinity().store(&cpu_affinity);
+    thread.run();
+    thread
+}
+
+/// Interrupts a kernel worker if it is waiting for something.
+fn interrupt_kernel_worker(thread: &Thread) {
+    let posix_thread = thread.as_posix_thread().unwrap();
+
+    // The signal is never handled since the worker never returns to the user space. It only
+    // interrupts the waiting. Note that signals cannot be enqueued if the process is gone.
+    if posix_thread.weak_process().upgrade().is_some() {
+        posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+    }
+}
+
+/// Runs `func` with the context of the current kernel worker.
+fn with_current_context<R>(func: impl FnOnce(&Context) -> R) -> R {
+    let current_task = Task::current().unwrap();
+    let current_thread = current_task.as_thread().unwrap();
+    let current_posix_thread = current_thread.as_posix_thread().unwrap();
+    let current_thread_local = current_task.as_thread_local().unwrap();
+    let current_process = current_posix_thread.process();
+
+    let ctx = Context {
+        process: current_process.as_ref(),
+        thread_local: current_thread_local,
+        posix_thread: current_posix_thread,
+        thread: current_thread.as_ref(),
+        task: current_task.as_ref(),
+    };
+
+    func(&ctx)
+}
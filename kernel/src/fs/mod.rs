@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 pub mod device;
 pub mod devpts;
+pub mod devtmpfs;
 pub mod epoll;
 pub mod exfat;
 pub mod ext2;
@@ -8,12 +9,16 @@ pub mod file_handle;
 pub mod file_table;
 pub mod fs_resolver;
 pub mod inode_handle;
+pub mod io_uring;
+pub mod mqueue;
 pub mod named_pipe;
+pub mod notify;
 pub mod path;
 pub mod pipe;
 pub mod procfs;
 pub mod ramfs;
 pub mod rootfs;
+pub mod sysfs;
 pub mod thread_info;
 pub mod utils;
 
@@ -51,6 +56,14 @@ pub fn lazy_init() {
     let ext2_device_name = "vext2";
     let exfat_device_name = "vexfat";
 
+    // The devices that are not mounted here, such as the swap devices, are
+    // started as well, so that their requests are handled when they are used.
+    for (device_name, _) in aster_block::all_devices() {
+        if device_name != ext2_device_name && device_name != exfat_device_name {
+            start_block_device(&device_name).unwrap();
+        }
+    }
+
     if let Ok(block_device_ext2) = start_block_device(ext2_device_name) {
         let ext2_fs = Ext2::open(block_device_ext2).unwrap();
         let target_path = FsPath::try_from("/ext2").unwrap();
@@ -0,0 +1,477 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The mqueue file system, which holds the POSIX message queues of an IPC namespace.
+//!
+//! The queues are created by `mq_open` or by creating regular files in the file system.
+//! Reading a queue file shows the status of the queue.
+
+#![allow(unused_variables)]
+
+use core::{
+    fmt::Write,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use aster_util::slot_vec::SlotVec;
+
+use super::utils::MknodType;
+use crate::{
+    events::IoEvents,
+    fs::utils::{
+        DirentVisitor, FileSystem, FsFlags, Inode, InodeMode, InodeType, Metadata, SuperBlock,
+        NAME_MAX,
+    },
+    ipc::message_queue::posix::{MqNotifyMethod, PosixMessageQueue, PosixMessageQueueAttr},
+    prelude::*,
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{PollHandle, Pollable},
+        Gid, Uid,
+    },
+};
+
+const MQUEUE_MAGIC: u64 = 0x19800202;
+const BLOCK_SIZE: usize = PAGE_SIZE;
+
+const ROOT_INO: u64 = 1;
+const FIRST_QUEUE_INO: u64 = 2;
+
+/// The mqueue file system.
+///
+/// Each IPC namespace has its own instance, which is also used when the file system
+/// is mounted (normally at "/dev/mqueue") by processes in the namespace.
+pub struct MqueueFs {
+    sb: SuperBlock,
+    root: Arc<RootInode>,
+    next_ino: AtomicU64,
+}
+
+impl MqueueFs {
+    pub fn new() -> Arc<Self> {
+        Arc::new_cyclic(|weak_self| Self {
+            sb: SuperBlock::new(MQUEUE_MAGIC, BLOCK_SIZE, NAME_MAX),
+            root: RootInode::new(weak_self.clone()),
+            next_ino: AtomicU64::new(FIRST_QUEUE_INO),
+        })
+    }
+
+    /// Creates a queue with the attributes in the root directory.
+    pub fn create_queue(
+        &self,
+        name: &str,
+        mode: InodeMode,
+        attr: PosixMessageQueueAttr,
+        uid: Uid,
+        gid: Gid,
+    ) -> Result<Arc<MqueueInode>> {
+        self.root.create_queue(name, mode, attr, uid, gid)
+    }
+
+    fn alloc_ino(&self) -> u64 {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl FileSystem for MqueueFs {
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn flags(&self) -> FsFlags {
+        FsFlags::empty()
+    }
+}
+
+struct RootInode {
+    queues: RwLock<SlotVec<(String, Arc<MqueueInode>)>>,
+    metadata: RwLock<Metadata>,
+    fs: Weak<MqueueFs>,
+}
+
+impl RootInode {
+    fn new(fs: Weak<MqueueFs>) -> Arc<Self> {
+        Arc::new(Self {
+            queues: RwLock::new(SlotVec::new()),
+            metadata: RwLock::new(Metadata::new_dir(
+                ROOT_INO,
+                InodeMode::from_bits_truncate(0o1777),
+                BLOCK_SIZE,
+            )),
+            fs,
+        })
+    }
+
+    fn create_queue(
+        &self,
+        name: &str,
+        mode: InodeMode,
+        attr: PosixMessageQueueAttr,
+        uid: Uid,
+        gid: Gid,
+    ) -> Result<Arc<MqueueInode>> {
+        if name.len() > NAME_MAX {
+            return_errno_with_message!(Errno::ENAMETOOLONG, "the name is too long");
+        }
+
+        let mut queues = self.queues.write();
+        if queues.iter().any(|(child_name, _)| child_name == name) {
+            return_errno_with_message!(Errno::EEXIST, "the queue already exists");
+        }
+
+        let fs = self.fs.upgrade().unwrap();
+        let inode = MqueueInode::new(fs.alloc_ino(), mode, attr, uid, gid, self.fs.clone());
+        queues.put((String::from(name), inode.clone()));
+
+        Ok(inode)
+    }
+}
+
+impl Inode for RootInode {
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn resize(&self, new_size: usize) -> Result<()> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino as _
+    }
+
+    fn type_(&self) -> InodeType {
+        self.metadata.read().type_
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().atime
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().atime = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().mtime
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().mtime = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().ctime
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().ctime = time;
+    }
+
+    fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        if type_ != InodeType::File {
+            return_errno_with_message!(Errno::EPERM, "only queues can be created");
+        }
+
+        let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+        let inode = self.create_queue(
+            name,
+            mode,
+            PosixMessageQueueAttr::default(),
+            credentials.fsuid(),
+            credentials.fsgid(),
+        )?;
+        Ok(inode)
+    }
+
+    fn mknod(&self, name: &str, mode: InodeMode, type_: MknodType) -> Result<Arc<dyn Inode>> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
+        let try_readdir = |offset: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
+            // Read the 2 special entries.
+            if *offset == 0 {
+                visitor.visit(".", self.ino(), self.type_(), *offset)?;
+                *offset += 1;
+            }
+            if *offset == 1 {
+                visitor.visit("..", self.ino(), self.type_(), *offset)?;
+                *offset += 1;
+            }
+
+            // Read the queues.
+            let queues = self.queues.read();
+            let start_offset = *offset;
+            for (idx, (name, node)) in queues
+                .idxes_and_items()
+                .map(|(idx, (name, node))| (idx + 2, (name, node)))
+                .skip_while(|(idx, _)| idx < &start_offset)
+            {
+                visitor.visit(name.as_ref(), node.ino(), node.type_(), idx)?;
+                *offset = idx + 1;
+            }
+            Ok(())
+        };
+
+        let mut iterate_offset = offset;
+        match try_readdir(&mut iterate_offset, visitor) {
+            Err(e) if offset == iterate_offset => Err(e),
+            _ => Ok(iterate_offset - offset),
+        }
+    }
+
+    fn link(&self, old: &Arc<dyn Inode>, name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        let mut queues = self.queues.write();
+        let pos = queues
+            .idxes_and_items()
+            .find(|(_, (child_name, _))| child_name == name)
+            .map(|(pos, _)| pos)
+            .ok_or(Error::new(Errno::ENOENT))?;
+
+        let (_, inode) = queues.remove(pos).unwrap();
+        // The queue is destroyed after all its descriptors are closed.
+        inode.metadata.write().nlinks = 0;
+
+        Ok(())
+    }
+
+    fn rmdir(&self, name: &str) -> Result<()> {
+        Err(Error::new(Errno::ENOTDIR))
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode: Arc<dyn Inode> = match name {
+            "." | ".." => self.fs().root_inode(),
+            name => self
+                .queues
+                .read()
+                .iter()
+                .find(|(child_name, _)| child_name == name)
+                .map(|(_, node)| node.clone())
+                .ok_or(Error::new(Errno::ENOENT))?,
+        };
+        Ok(inode)
+    }
+
+    fn rename(&self, old_name: &str, target: &Arc<dyn Inode>, new_name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+}
+
+/// The inode of a POSIX message queue.
+pub struct MqueueInode {
+    queue: PosixMessageQueue,
+    metadata: RwLock<Metadata>,
+    fs: Weak<MqueueFs>,
+}
+
+impl MqueueInode {
+    fn new(
+        ino: u64,
+        mode: InodeMode,
+        attr: PosixMessageQueueAttr,
+        uid: Uid,
+        gid: Gid,
+        fs: Weak<MqueueFs>,
+    ) -> Arc<Self> {
+        let mut metadata = Metadata::new_file(ino, mode, BLOCK_SIZE);
+        metadata.uid = uid;
+        metadata.gid = gid;
+
+        Arc::new(Self {
+            queue: PosixMessageQueue::new(attr),
+            metadata: RwLock::new(metadata),
+            fs,
+        })
+    }
+
+    /// Returns the message queue.
+    pub fn queue(&self) -> &PosixMessageQueue {
+        &self.queue
+    }
+
+    /// Returns the status of the queue in the format of Linux.
+    fn status_string(&self) -> String {
+        let status = self.queue.status();
+        let (notify, signo, notify_pid) = match status.notification {
+            None => (0, 0, 0),
+            Some((pid, MqNotifyMethod::None)) => (SIGEV_NONE, 0, pid),
+            Some((pid, MqNotifyMethod::Signal(sig_num, _))) => {
+                (SIGEV_SIGNAL, sig_num.as_u8() as i32, pid)
+            }
+        };
+        let notify_pid = current!().pid_ns().local_id_or_zero(notify_pid);
+
+        let mut output = String::new();
+        writeln!(
+            output,
+            "QSIZE:{:<10} NOTIFY:{:<5} SIGNO:{:<5} NOTIFY_PID:{:<6}",
+            status.total_len, notify, signo, notify_pid
+        )
+        .unwrap();
+        output
+    }
+}
+
+// The values of `sigev_notify` shown in the status.
+const SIGEV_SIGNAL: i32 = 0;
+const SIGEV_NONE: i32 = 1;
+
+impl Inode for MqueueInode {
+    /// Do not cache dentry in DCACHE.
+    ///
+    /// The file system can be mounted multiple times, and the queue can be removed
+    /// via any of the mounts or by `mq_unlink`. So we should not cache the dentry.
+    fn is_dentry_cacheable(&self) -> bool {
+        false
+    }
+
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn resize(&self, new_size: usize) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino as _
+    }
+
+    fn type_(&self) -> InodeType {
+        self.metadata.read().type_
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().atime
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().atime = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().mtime
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().mtime = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().ctime
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().ctime = time;
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let status = self.status_string();
+        let data = status.as_bytes();
+        let start = data.len().min(offset);
+        let end = data.len().min(offset + writer.avail());
+        let len = end - start;
+        writer.write_fallible(&mut (&data[start..end]).into())?;
+        Ok(len)
+    }
+
+    fn read_direct_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        self.read_at(offset, writer)
+    }
+
+    fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "the queue cannot be written");
+    }
+
+    fn write_direct_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        self.write_at(offset, reader)
+    }
+
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.queue.poll(mask, poller)
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+}
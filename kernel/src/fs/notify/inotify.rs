@@ -0,0 +1,437 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use super::{FsEventPublisher, FsEventSubscriber, FsEvents};
+use crate::{
+    current_userspace,
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        utils::{Inode, InodeMode, InodeType, IoctlCmd, Metadata, StatusFlags},
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+};
+
+bitflags! {
+    /// The flags of `inotify_init1`.
+    pub struct InotifyFlags: u32 {
+        const IN_NONBLOCK = 1 << 11;
+        const IN_CLOEXEC  = 1 << 19;
+    }
+}
+
+bitflags! {
+    /// The flags in the mask of `inotify_add_watch`.
+    pub struct WatchFlags: u32 {
+        /// Only watch the path if it is a directory.
+        const ONLYDIR     = 1 << 24;
+        /// Do not follow the symbolic link.
+        const DONT_FOLLOW = 1 << 25;
+        /// Do not generate events for the unlinked children.
+        const EXCL_UNLINK = 1 << 26;
+        /// Only create a new watch, but do not modify the existing one.
+        const MASK_CREATE = 1 << 28;
+        /// Add the events to the existing watch instead of replacing them.
+        const MASK_ADD    = 1 << 29;
+        /// Only generate one event, then remove the watch.
+        const ONESHOT     = 1 << 31;
+    }
+}
+
+/// The maximum number of the events in the queue of an inotify instance.
+///
+/// This is the default value of `/proc/sys/fs/inotify/max_queued_events` in Linux.
+const MAX_QUEUED_EVENTS: usize = 16384;
+
+/// The maximum number of the watches of an inotify instance.
+///
+/// This is the default value of `/proc/sys/fs/inotify/max_user_watches` in Linux.
+const MAX_WATCHES: usize = 8192;
+
+/// An inotify instance.
+///
+/// An inotify instance watches inodes and queues the events of them, which can be read by the
+/// user space.
+pub struct InotifyFile {
+    watches: Mutex<Watches>,
+    queue: Mutex<VecDeque<InotifyEvent>>,
+    pollee: Pollee,
+    is_nonblocking: AtomicBool,
+    this: Weak<InotifyFile>,
+}
+
+struct Watches {
+    watches: BTreeMap<i32, Arc<Watch>>,
+    next_wd: i32,
+}
+
+/// A watch on an inode.
+struct Watch {
+    wd: i32,
+    /// The events of interest and the `ONESHOT` flag.
+    mask: AtomicU32,
+    inode: Arc<dyn Inode>,
+    publisher: Option<Arc<FsEventPublisher>>,
+    owner: Weak<InotifyFile>,
+    is_removed: AtomicBool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct InotifyEvent {
+    wd: i32,
+    mask: u32,
+    cookie: u32,
+    name: Option<String>,
+}
+
+/// The header of an event read from an inotify instance.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CInotifyEvent {
+    wd: i32,
+    mask: u32,
+    cookie: u32,
+    len: u32,
+}
+
+impl InotifyFile {
+    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+        Arc::new_cyclic(|weak_self| Self {
+            watches: Mutex::new(Watches {
+                watches: BTreeMap::new(),
+                next_wd: 1,
+            }),
+            queue: Mutex::new(VecDeque::new()),
+            pollee: Pollee::new(),
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            this: weak_self.clone(),
+        })
+    }
+
+    /// Adds a watch on the inode, or modifies the existing watch on it.
+    ///
+    /// This method returns the watch descriptor.
+    pub fn add_watch(
+        &self,
+        inode: Arc<dyn Inode>,
+        events: FsEvents,
+        flags: WatchFlags,
+    ) -> Result<i32> {
+        if (events & FsEvents::ALL_EVENTS).is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "no events are specified");
+        }
+        if flags.contains(WatchFlags::MASK_ADD | WatchFlags::MASK_CREATE) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "MASK_ADD and MASK_CREATE cannot be specified together"
+            );
+        }
+        if flags.contains(WatchFlags::ONLYDIR) && inode.type_() != InodeType::Dir {
+            return_errno_with_message!(Errno::ENOTDIR, "the inode is not a directory");
+        }
+
+        let mask = (events & FsEvents::ALL_EVENTS).bits() | (flags & WatchFlags::ONESHOT).bits();
+
+        let mut watches = self.watches.lock();
+
+        if let Some(watch) = watches
+            .watches
+            .values()
+            .find(|watch| core::ptr::addr_eq(Arc::as_ptr(&watch.inode), Arc::as_ptr(&inode)))
+        {
+            if flags.contains(WatchFlags::MASK_CREATE) {
+                return_errno_with_message!(Errno::EEXIST, "the inode is already watched");
+            }
+
+            if flags.contains(WatchFlags::MASK_ADD) {
+                watch.mask.fetch_or(mask, Ordering::Relaxed);
+            } else {
+                watch.mask.store(mask, Ordering::Relaxed);
+            }
+            return Ok(watch.wd);
+        }
+
+        if watches.watches.len() >= MAX_WATCHES {
+            return_errno_with_message!(Errno::ENOSPC, "too many watches");
+        }
+
+        let wd = watches.alloc_wd();
+        // Inodes that do not support extensions never generate events, but they can still be
+        // watched.
+        let publisher = FsEventPublisher::get_or_create(inode.as_ref());
+        let watch = Arc::new(Watch {
+            wd,
+            mask: AtomicU32::new(mask),
+            inode,
+            publisher,
+            owner: self.this.clone(),
+            is_removed: AtomicBool::new(false),
+        });
+
+        if let Some(publisher) = watch.publisher.as_ref() {
+            publisher.add_subscriber(Arc::downgrade(&watch) as _);
+        }
+        watches.watches.insert(wd, watch);
+
+        Ok(wd)
+    }
+
+    /// Removes the watch of the watch descriptor.
+    pub fn remove_watch(&self, wd: i32) -> Result<()> {
+        let Some(watch) = self.watches.lock().watches.remove(&wd) else {
+            return_errno_with_message!(Errno::EINVAL, "the watch does not exist");
+        };
+
+        watch.remove_from_publisher();
+        self.on_watch_removed(&watch);
+        Ok(())
+    }
+
+    fn on_watch_removed(&self, watch: &Watch) {
+        if watch.is_removed.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        self.push_event(InotifyEvent {
+            wd: watch.wd,
+            mask: FsEvents::IGNORED.bits(),
+            cookie: 0,
+            name: None,
+        });
+    }
+
+    fn push_event(&self, event: InotifyEvent) {
+        let mut queue = self.queue.lock();
+
+        // Like Linux, merge the event with the last one if they are identical.
+        if queue.back() == Some(&event) {
+            return;
+        }
+
+        if queue.len() >= MAX_QUEUED_EVENTS {
+            let overflow_event = InotifyEvent {
+                wd: -1,
+                mask: FsEvents::Q_OVERFLOW.bits(),
+                cookie: 0,
+                name: None,
+            };
+            if queue.back() != Some(&overflow_event) {
+                queue.push_back(overflow_event);
+            }
+            return;
+        }
+
+        queue.push_back(event);
+        drop(queue);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let mut queue = self.queue.lock();
+        if queue.is_empty() {
+            return_errno_with_message!(Errno::EAGAIN, "no events are available");
+        }
+
+        let mut read_len = 0;
+        while let Some(event) = queue.front() {
+            let event_len = event.len();
+            if event_len > writer.avail() {
+                break;
+            }
+
+            event.write_to(writer)?;
+            read_len += event_len;
+            queue.pop_front();
+        }
+
+        if read_len == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the buffer is too small");
+        }
+
+        if queue.is_empty() {
+            self.pollee.invalidate();
+        }
+
+        Ok(read_len)
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.queue.lock().is_empty() {
+            IoEvents::empty()
+        } else {
+            IoEvents::IN
+        }
+    }
+}
+
+impl Watches {
+    fn alloc_wd(&mut self) -> i32 {
+        // Like Linux, the watch descriptors are not reused until they wrap around.
+        loop {
+            let wd = self.next_wd;
+            self.next_wd = self.next_wd.checked_add(1).unwrap_or(1);
+            if !self.watches.contains_key(&wd) {
+                return wd;
+            }
+        }
+    }
+}
+
+impl Watch {
+    fn remove_from_publisher(self: &Arc<Self>) {
+        if let Some(publisher) = self.publisher.as_ref() {
+            publisher.remove_subscriber(&(Arc::downgrade(self) as _));
+        }
+    }
+}
+
+impl FsEventSubscriber for Watch {
+    fn on_events(&self, events: FsEvents, name: Option<&str>, cookie: u32) {
+        if self.is_removed.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mask = self.mask.load(Ordering::Relaxed);
+        let interesting = events & FsEvents::from_bits_truncate(mask) & FsEvents::ALL_EVENTS;
+        if interesting.is_empty() {
+            return;
+        }
+
+        let Some(owner) = self.owner.upgrade() else {
+            return;
+        };
+        owner.push_event(InotifyEvent {
+            wd: self.wd,
+            mask: (interesting | (events & FsEvents::ISDIR)).bits(),
+            cookie,
+            name: name.map(String::from),
+        });
+
+        if mask & WatchFlags::ONESHOT.bits() != 0 {
+            let Some(watch) = owner.watches.lock().watches.remove(&self.wd) else {
+                return;
+            };
+            watch.remove_from_publisher();
+            owner.on_watch_removed(&watch);
+        }
+    }
+
+    fn on_detached(&self) {
+        let Some(owner) = self.owner.upgrade() else {
+            return;
+        };
+
+        let Some(watch) = owner.watches.lock().watches.remove(&self.wd) else {
+            return;
+        };
+        owner.on_watch_removed(&watch);
+    }
+}
+
+impl InotifyEvent {
+    /// Returns the length of the event when it is read, including the padded name.
+    fn len(&self) -> usize {
+        size_of::<CInotifyEvent>() + self.name_len()
+    }
+
+    /// Returns the length of the name, which is padded with null bytes.
+    fn name_len(&self) -> usize {
+        self.name.as_ref().map_or(0, |name| {
+            (name.len() + 1).next_multiple_of(size_of::<CInotifyEvent>())
+        })
+    }
+
+    fn write_to(&self, writer: &mut VmWriter) -> Result<()> {
+        let header = CInotifyEvent {
+            wd: self.wd,
+            mask: self.mask,
+            cookie: self.cookie,
+            len: self.name_len() as u32,
+        };
+        writer.write_val(&header)?;
+
+        if let Some(name) = self.name.as_ref() {
+            let mut buf = vec![0u8; self.name_len()];
+            buf[..name.len()].copy_from_slice(name.as_bytes());
+            writer.write_fallible(&mut buf.as_slice().into())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for InotifyFile {
+    fn drop(&mut self) {
+        let watches = core::mem::take(&mut self.watches.get_mut().watches);
+
+        for watch in watches.values() {
+            watch.remove_from_publisher();
+        }
+    }
+}
+
+impl Pollable for InotifyFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for InotifyFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            self.try_read(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_read(writer))
+        }
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "inotify files do not support write");
+    }
+
+    fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
+        match cmd {
+            IoctlCmd::FIONREAD => {
+                let len = self
+                    .queue
+                    .lock()
+                    .iter()
+                    .map(InotifyEvent::len)
+                    .sum::<usize>() as i32;
+                current_userspace!().write_val(arg, &len)?;
+                Ok(0)
+            }
+            _ => return_errno_with_message!(Errno::EINVAL, "the ioctl command is not supported"),
+        }
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `InotifyFile` to it.
+        Metadata::new_file(
+            0,
+            InodeMode::from_bits_truncate(0o600),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+}
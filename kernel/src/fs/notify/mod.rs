@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! File system notifications.
+//!
+//! File system operations (e.g., creating, writing, or renaming files) publish events to the
+//! subscribers of the affected inodes. An event on a child of a directory is also published to
+//! the subscribers of the directory, along with the name of the child.
+//!
+//! The publisher of an inode is stored in the [`Extension`] of the inode, so only the inodes that
+//! support extensions (e.g., those of ramfs, ext2, and exfat) can generate events.
+//!
+//! Currently, the only subscribers are inotify watches.
+//!
+//! [`Extension`]: crate::fs::utils::Extension
+
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+use crate::{fs::utils::Inode, prelude::*};
+
+mod inotify;
+
+pub use inotify::{InotifyFile, InotifyFlags, WatchFlags};
+
+bitflags! {
+    /// The file system events.
+    ///
+    /// The values are the same as those of inotify.
+    pub struct FsEvents: u32 {
+        /// The file was accessed (e.g., read).
+        const ACCESS        = 1 << 0;
+        /// The file was modified (e.g., written or truncated).
+        const MODIFY        = 1 << 1;
+        /// The metadata of the file was changed.
+        const ATTRIB        = 1 << 2;
+        /// The file opened for writing was closed.
+        const CLOSE_WRITE   = 1 << 3;
+        /// The file not opened for writing was closed.
+        const CLOSE_NOWRITE = 1 << 4;
+        /// The file was opened.
+        const OPEN          = 1 << 5;
+        /// A child was moved out of the directory.
+        const MOVED_FROM    = 1 << 6;
+        /// A child was moved into the directory.
+        const MOVED_TO      = 1 << 7;
+        /// A child was created in the directory.
+        const CREATE        = 1 << 8;
+        /// A child was deleted from the directory.
+        const DELETE        = 1 << 9;
+        /// The file itself was deleted.
+        const DELETE_SELF   = 1 << 10;
+        /// The file itself was moved.
+        const MOVE_SELF     = 1 << 11;
+        /// The file system containing the file was unmounted.
+        const UNMOUNT       = 1 << 13;
+        /// The event queue overflowed.
+        const Q_OVERFLOW    = 1 << 14;
+        /// The subscription was removed.
+        const IGNORED       = 1 << 15;
+        /// The subject of the event is a directory.
+        const ISDIR         = 1 << 30;
+
+        const CLOSE = Self::CLOSE_WRITE.bits | Self::CLOSE_NOWRITE.bits;
+        const MOVE = Self::MOVED_FROM.bits | Self::MOVED_TO.bits;
+        const ALL_EVENTS = Self::ACCESS.bits
+            | Self::MODIFY.bits
+            | Self::ATTRIB.bits
+            | Self::CLOSE.bits
+            | Self::OPEN.bits
+            | Self::MOVE.bits
+            | Self::CREATE.bits
+            | Self::DELETE.bits
+            | Self::DELETE_SELF.bits
+            | Self::MOVE_SELF.bits;
+    }
+}
+
+/// A subscriber of the events of inodes.
+pub trait FsEventSubscriber: Send + Sync {
+    /// Handles the events.
+    ///
+    /// If the events happen on a child of the directory, `name` is the name of the child.
+    /// `cookie` is used to associate the related events (e.g., `MOVED_FROM` and `MOVED_TO`).
+    fn on_events(&self, events: FsEvents, name: Option<&str>, cookie: u32);
+
+    /// Handles the removal of the subscription because the inode has been deleted.
+    fn on_detached(&self);
+}
+
+/// The publisher of the events of an inode.
+#[derive(Default)]
+pub struct FsEventPublisher {
+    subscribers: RwLock<Vec<Weak<dyn FsEventSubscriber>>>,
+}
+
+/// The number of all the subscriptions, which is used to skip the work of publishing events if
+/// nobody is interested in them.
+static NUM_SUBSCRIPTIONS: AtomicUsize = AtomicUsize::new(0);
+
+impl FsEventPublisher {
+    /// Returns the publisher of the inode, creating one if it does not exist.
+    ///
+    /// This method returns `None` if the inode does not support extensions.
+    pub fn get_or_create(inode: &dyn Inode) -> Option<Arc<Self>> {
+        Some(inode.extension()?.get_or_put_default::<Self>())
+    }
+
+    /// Adds a subscriber.
+    pub fn add_subscriber(&self, subscriber: Weak<dyn FsEventSubscriber>) {
+        self.subscribers.write().push(subscriber);
+        NUM_SUBSCRIPTIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Removes a subscriber.
+    ///
+    /// This method returns whether the subscriber was found.
+    pub fn remove_subscriber(&self, subscriber: &Weak<dyn FsEventSubscriber>) -> bool {
+        let mut subscribers = self.subscribers.write();
+        let Some(pos) = subscribers
+            .iter()
+            .position(|this| Weak::ptr_eq(this, subscriber))
+        else {
+            return false;
+        };
+
+        subscribers.swap_remove(pos);
+        NUM_SUBSCRIPTIONS.fetch_sub(1, Ordering::Relaxed);
+        true
+    }
+
+    fn publish(&self, events: FsEvents, name: Option<&str>, cookie: u32) {
+        let subscribers = self
+            .subscribers
+            .read()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+
+        for subscriber in subscribers {
+            subscriber.on_events(events, name, cookie);
+        }
+    }
+
+    fn detach_all(&self) {
+        let subscribers = core::mem::take(&mut *self.subscribers.write());
+        NUM_SUBSCRIPTIONS.fetch_sub(subscribers.len(), Ordering::Relaxed);
+
+        for subscriber in subscribers.iter().filter_map(Weak::upgrade) {
+            subscriber.on_detached();
+        }
+    }
+}
+
+/// Returns whether there are any subscriptions in the system.
+///
+/// If this method returns `false`, callers can skip the work of preparing the events (e.g.,
+/// looking up the affected inodes).
+pub fn is_active() -> bool {
+    NUM_SUBSCRIPTIONS.load(Ordering::Relaxed) > 0
+}
+
+/// Publishes the events of the inode.
+///
+/// If the events happen on a child of the directory, `name` should be the name of the child.
+pub fn notify(inode: &dyn Inode, events: FsEvents, name: Option<&str>, cookie: u32) {
+    if !is_active() {
+        return;
+    }
+
+    let Some(publisher) = inode
+        .extension()
+        .and_then(|ext| ext.get::<FsEventPublisher>())
+    else {
+        return;
+    };
+    publisher.publish(events, name, cookie);
+}
+
+/// Publishes the deletion of the inode and removes all the subscriptions.
+pub fn notify_deleted(inode: &dyn Inode) {
+    if !is_active() {
+        return;
+    }
+
+    let Some(publisher) = inode
+        .extension()
+        .and_then(|ext| ext.del::<FsEventPublisher>())
+    else {
+        return;
+    };
+    publisher.publish(FsEvents::DELETE_SELF, None, 0);
+    publisher.detach_all();
+}
+
+/// Allocates a cookie to associate the related events.
+pub fn alloc_cookie() -> u32 {
+    static NEXT_COOKIE: AtomicU32 = AtomicU32::new(1);
+
+    NEXT_COOKIE.fetch_add(1, Ordering::Relaxed)
+}
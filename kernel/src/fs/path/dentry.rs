@@ -14,9 +14,12 @@ use ostd::sync::RwMutexWriteGuard;
 
 use crate::{
     fs::{
+        notify::{self, FsEvents},
         path::mount::MountNode,
         utils::{
-            FileSystem, Inode, InodeMode, InodeType, Metadata, MknodType, Permission, NAME_MAX,
+            check_xattr_permission, chmod_acl, inherit_acl, is_xattr_listable, set_xattr_with_acl,
+            FileSystem, Inode, InodeMode, InodeType, Metadata, MknodType, Permission, XattrName,
+            XattrSetFlags, NAME_MAX,
         },
     },
     prelude::*,
@@ -36,7 +39,10 @@ pub struct Dentry_ {
     inode: Arc<dyn Inode>,
     name_and_parent: RwLock<Option<(String, Arc<Dentry_>)>>,
     children: RwMutex<Children>,
-    flags: AtomicU32,
+    /// The number of mount nodes mounted on this dentry.
+    ///
+    /// The mount nodes may belong to different mount namespaces.
+    mount_count: AtomicU32,
     this: Weak<Dentry_>,
 }
 
@@ -52,7 +58,7 @@ impl Dentry_ {
     fn new(inode: Arc<dyn Inode>, options: DentryOptions) -> Arc<Self> {
         Arc::new_cyclic(|weak_self| Self {
             inode,
-            flags: AtomicU32::new(DentryFlags::empty().bits()),
+            mount_count: AtomicU32::new(0),
             name_and_parent: match options {
                 DentryOptions::Leaf(name_and_parent) => RwLock::new(Some(name_and_parent)),
                 _ => RwLock::new(None),
@@ -101,11 +107,6 @@ impl Dentry_ {
         &self.inode
     }
 
-    fn flags(&self) -> DentryFlags {
-        let flags = self.flags.load(Ordering::Relaxed);
-        DentryFlags::from_bits(flags).unwrap()
-    }
-
     /// Checks if this dentry is a descendant (child, grandchild, or
     /// great-grandchild, etc.) of another dentry.
     pub fn is_descendant_of(&self, ancestor: &Arc<Self>) -> bool {
@@ -119,18 +120,18 @@ impl Dentry_ {
         false
     }
 
+    /// Checks if this dentry is a mountpoint in any mount namespace.
     pub fn is_mountpoint(&self) -> bool {
-        self.flags().contains(DentryFlags::MOUNTED)
+        self.mount_count.load(Ordering::Acquire) > 0
     }
 
-    pub fn set_mountpoint_dentry(&self) {
-        self.flags
-            .fetch_or(DentryFlags::MOUNTED.bits(), Ordering::Release);
+    pub(super) fn inc_mount_count(&self) {
+        self.mount_count.fetch_add(1, Ordering::Release);
     }
 
-    pub fn clear_mountpoint(&self) {
-        self.flags
-            .fetch_and(!(DentryFlags::MOUNTED.bits()), Ordering::Release);
+    pub(super) fn dec_mount_count(&self) {
+        let old_count = self.mount_count.fetch_sub(1, Ordering::Release);
+        debug_assert!(old_count > 0);
     }
 
     /// Currently, the root `Dentry_` of a fs is the root of a mount.
@@ -150,6 +151,13 @@ impl Dentry_ {
         }
 
         let new_inode = self.inode.create(name, type_, mode)?;
+        inherit_acl(self.inode.as_ref(), new_inode.as_ref())?;
+        notify::notify(
+            self.inode.as_ref(),
+            FsEvents::CREATE | isdir_flag(type_),
+            Some(name),
+            0,
+        );
         let name = String::from(name);
         let new_child = Dentry_::new(new_inode, DentryOptions::Leaf((name.clone(), self.this())));
 
@@ -189,6 +197,8 @@ impl Dentry_ {
         }
 
         let inode = self.inode.mknod(name, mode, type_)?;
+        inherit_acl(self.inode.as_ref(), inode.as_ref())?;
+        notify::notify(self.inode.as_ref(), FsEvents::CREATE, Some(name), 0);
         let name = String::from(name);
         let new_child = Dentry_::new(inode, DentryOptions::Leaf((name.clone(), self.this())));
 
@@ -210,6 +220,8 @@ impl Dentry_ {
 
         let old_inode = old.inode();
         self.inode.link(old_inode, name)?;
+        notify::notify(old_inode.as_ref(), FsEvents::ATTRIB, None, 0);
+        notify::notify(self.inode.as_ref(), FsEvents::CREATE, Some(name), 0);
         let name = String::from(name);
         let dentry = Dentry_::new(
             old_inode.clone(),
@@ -229,11 +241,21 @@ impl Dentry_ {
 
         let children = self.children.upread();
         children.check_mountpoint(name)?;
+        let target = self.lookup_inode_for_notify(&children, name);
 
         self.inode.unlink(name)?;
 
         let mut children = children.upgrade();
         children.delete(name);
+        drop(children);
+
+        if let Some(target) = target {
+            notify::notify(target.as_ref(), FsEvents::ATTRIB, None, 0);
+            notify::notify(self.inode.as_ref(), FsEvents::DELETE, Some(name), 0);
+            if target.metadata().nlinks == 0 {
+                notify::notify_deleted(target.as_ref());
+            }
+        }
         Ok(())
     }
 
@@ -245,11 +267,23 @@ impl Dentry_ {
 
         let children = self.children.upread();
         children.check_mountpoint(name)?;
+        let target = self.lookup_inode_for_notify(&children, name);
 
         self.inode.rmdir(name)?;
 
         let mut children = children.upgrade();
         children.delete(name);
+        drop(children);
+
+        if let Some(target) = target {
+            notify::notify(
+                self.inode.as_ref(),
+                FsEvents::DELETE | FsEvents::ISDIR,
+                Some(name),
+                0,
+            );
+            notify::notify_deleted(target.as_ref());
+        }
         Ok(())
     }
 
@@ -271,8 +305,11 @@ impl Dentry_ {
             let children = self.children.upread();
             let old_dentry = children.check_mountpoint_then_find(old_name)?;
             children.check_mountpoint(new_name)?;
+            let moved = self.lookup_inode_for_notify(&children, old_name);
+            let replaced = self.lookup_inode_for_notify(&children, new_name);
 
             self.inode.rename(old_name, &self.inode, new_name)?;
+            self.notify_renamed(old_name, self, new_name, moved, replaced);
 
             let mut children = children.upgrade();
             match old_dentry.as_ref() {
@@ -291,8 +328,11 @@ impl Dentry_ {
                 write_lock_children_on_two_dentries(self, new_dir);
             let old_dentry = self_children.check_mountpoint_then_find(old_name)?;
             new_dir_children.check_mountpoint(new_name)?;
+            let moved = self.lookup_inode_for_notify(&self_children, old_name);
+            let replaced = new_dir.lookup_inode_for_notify(&new_dir_children, new_name);
 
             self.inode.rename(old_name, &new_dir.inode, new_name)?;
+            self.notify_renamed(old_name, new_dir, new_name, moved, replaced);
             match old_dentry.as_ref() {
                 Some(dentry) => {
                     self_children.delete(old_name);
@@ -306,6 +346,140 @@ impl Dentry_ {
         }
         Ok(())
     }
+
+    /// Publishes the events to the subscribers of this `Dentry_` and its parent.
+    pub fn notify_event(&self, events: FsEvents) {
+        if !notify::is_active() {
+            return;
+        }
+
+        let events = events | isdir_flag(self.type_());
+        notify::notify(self.inode.as_ref(), events, None, 0);
+
+        let name_and_parent = self.name_and_parent.read().clone();
+        if let Some((name, parent)) = name_and_parent {
+            notify::notify(parent.inode.as_ref(), events, Some(&name), 0);
+        }
+    }
+
+    /// Sets the mode of the inner inode and publishes the change.
+    pub fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.inode.set_mode(mode)?;
+        chmod_acl(self.inode.as_ref(), mode)?;
+        self.notify_event(FsEvents::ATTRIB);
+        Ok(())
+    }
+
+    /// Resizes the inner inode and publishes the change.
+    pub fn resize(&self, size: usize) -> Result<()> {
+        self.inode.resize(size)?;
+        self.notify_event(FsEvents::MODIFY);
+        Ok(())
+    }
+
+    /// Sets the owner of the inner inode and publishes the change.
+    pub fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.inode.set_owner(uid)?;
+        self.notify_event(FsEvents::ATTRIB);
+        Ok(())
+    }
+
+    /// Sets the group of the inner inode and publishes the change.
+    pub fn set_group(&self, gid: Gid) -> Result<()> {
+        self.inode.set_group(gid)?;
+        self.notify_event(FsEvents::ATTRIB);
+        Ok(())
+    }
+
+    /// Sets an extended attribute of the inner inode and publishes the change.
+    pub fn set_xattr(&self, name: XattrName, value: &[u8], flags: XattrSetFlags) -> Result<()> {
+        check_xattr_permission(self.inode.as_ref(), name, true)?;
+        set_xattr_with_acl(self.inode.as_ref(), name, value, flags)?;
+        self.notify_event(FsEvents::ATTRIB);
+        Ok(())
+    }
+
+    /// Gets an extended attribute of the inner inode.
+    pub fn get_xattr(&self, name: XattrName) -> Result<Vec<u8>> {
+        check_xattr_permission(self.inode.as_ref(), name, false)?;
+        self.inode.get_xattr(name)
+    }
+
+    /// Lists the names of the extended attributes of the inner inode that are visible to
+    /// the current thread.
+    pub fn list_xattr(&self) -> Result<Vec<String>> {
+        let mut names = self.inode.list_xattr()?;
+        names.retain(|name| {
+            XattrName::try_from_full_name(name)
+                .is_ok_and(|name| is_xattr_listable(name.namespace()))
+        });
+        Ok(names)
+    }
+
+    /// Removes an extended attribute of the inner inode and publishes the change.
+    pub fn remove_xattr(&self, name: XattrName) -> Result<()> {
+        check_xattr_permission(self.inode.as_ref(), name, true)?;
+        self.inode.remove_xattr(name)?;
+        self.notify_event(FsEvents::ATTRIB);
+        Ok(())
+    }
+
+    /// Looks up the inode of the child, which is only needed if someone is interested in the
+    /// events.
+    fn lookup_inode_for_notify(&self, children: &Children, name: &str) -> Option<Arc<dyn Inode>> {
+        if !notify::is_active() {
+            return None;
+        }
+
+        match children.find(name) {
+            Some(dentry) => Some(dentry.inode.clone()),
+            None => self.inode.lookup(name).ok(),
+        }
+    }
+
+    fn notify_renamed(
+        &self,
+        old_name: &str,
+        new_dir: &Dentry_,
+        new_name: &str,
+        moved: Option<Arc<dyn Inode>>,
+        replaced: Option<Arc<dyn Inode>>,
+    ) {
+        let Some(moved) = moved else {
+            return;
+        };
+
+        let cookie = notify::alloc_cookie();
+        let isdir = isdir_flag(moved.type_());
+        notify::notify(
+            self.inode.as_ref(),
+            FsEvents::MOVED_FROM | isdir,
+            Some(old_name),
+            cookie,
+        );
+        notify::notify(
+            new_dir.inode.as_ref(),
+            FsEvents::MOVED_TO | isdir,
+            Some(new_name),
+            cookie,
+        );
+        notify::notify(moved.as_ref(), FsEvents::MOVE_SELF, None, 0);
+
+        if let Some(replaced) = replaced
+            && replaced.metadata().nlinks == 0
+        {
+            notify::notify_deleted(replaced.as_ref());
+        }
+    }
+}
+
+/// Returns [`FsEvents::ISDIR`] if the type is a directory.
+fn isdir_flag(type_: InodeType) -> FsEvents {
+    if type_ == InodeType::Dir {
+        FsEvents::ISDIR
+    } else {
+        FsEvents::empty()
+    }
 }
 
 #[inherit_methods(from = "self.inode")]
@@ -316,13 +490,9 @@ impl Dentry_ {
     pub fn metadata(&self) -> Metadata;
     pub fn type_(&self) -> InodeType;
     pub fn mode(&self) -> Result<InodeMode>;
-    pub fn set_mode(&self, mode: InodeMode) -> Result<()>;
     pub fn size(&self) -> usize;
-    pub fn resize(&self, size: usize) -> Result<()>;
     pub fn owner(&self) -> Result<Uid>;
-    pub fn set_owner(&self, uid: Uid) -> Result<()>;
     pub fn group(&self) -> Result<Gid>;
-    pub fn set_group(&self, gid: Gid) -> Result<()>;
     pub fn atime(&self) -> Duration;
     pub fn set_atime(&self, time: Duration);
     pub fn mtime(&self) -> Duration;
@@ -364,12 +534,6 @@ impl DentryKey {
     }
 }
 
-bitflags! {
-    struct DentryFlags: u32 {
-        const MOUNTED = 1 << 0;
-    }
-}
-
 enum DentryOptions {
     Root,
     Leaf((String, Arc<Dentry_>)),
@@ -474,7 +638,19 @@ impl Dentry {
         Ok(Self::new(self.mount_node.clone(), new_child_dentry))
     }
 
-    fn new(mount_node: Arc<MountNode>, inner: Arc<Dentry_>) -> Self {
+    /// Creates a new `Dentry` to represent a child that is not linked to this directory.
+    ///
+    /// The child cannot be found by looking up this directory, so it is only accessible
+    /// via the returned `Dentry`. This is useful for anonymous files, e.g., memfd files.
+    pub fn new_unlinked_child(&self, name: &str, inode: Arc<dyn Inode>) -> Self {
+        let new_child_dentry = Dentry_::new(
+            inode,
+            DentryOptions::Leaf((String::from(name), self.inner.clone())),
+        );
+        Self::new(self.mount_node.clone(), new_child_dentry)
+    }
+
+    pub(super) fn new(mount_node: Arc<MountNode>, inner: Arc<Dentry_>) -> Self {
         Self { mount_node, inner }
     }
 
@@ -594,13 +770,6 @@ impl Dentry {
         }
     }
 
-    /// Makes current `Dentry` to be a mountpoint,
-    /// sets it as the mountpoint of the child mount.
-    pub(super) fn set_mountpoint(&self, child_mount: Arc<MountNode>) {
-        child_mount.set_mountpoint_dentry(&self.inner);
-        self.inner.set_mountpoint_dentry();
-    }
-
     /// Mounts the fs on current `Dentry` as a mountpoint.
     ///
     /// If the given mountpoint has already been mounted,
@@ -616,9 +785,7 @@ impl Dentry {
             return_errno_with_message!(Errno::EINVAL, "can not mount on root");
         }
 
-        let child_mount = self.mount_node.mount(fs, &self.this())?;
-        self.set_mountpoint(child_mount.clone());
-        Ok(child_mount)
+        self.mount_node.mount(fs, &self.this())
     }
 
     /// Unmounts and returns the mounted child mount.
@@ -636,9 +803,7 @@ impl Dentry {
         let mountpoint_mount_node = self.mount_node.parent().unwrap().upgrade().unwrap();
         let mountpoint = Self::new(mountpoint_mount_node.clone(), mountpoint_dentry.clone());
 
-        let child_mount = mountpoint_mount_node.unmount(&mountpoint)?;
-        mountpoint_dentry.clear_mountpoint();
-        Ok(child_mount)
+        mountpoint_mount_node.unmount(&mountpoint)
     }
 
     /// Creates a `Dentry` by making an inode of the `type_` with the `mode`.
@@ -694,6 +859,11 @@ impl Dentry {
     pub fn mount_node(&self) -> &Arc<MountNode> {
         &self.mount_node
     }
+
+    /// Gets the inner `Dentry_`.
+    pub(super) fn inner(&self) -> &Arc<Dentry_> {
+        &self.inner
+    }
 }
 
 #[inherit_methods(from = "self.inner")]
@@ -711,6 +881,10 @@ impl Dentry {
     pub fn set_owner(&self, uid: Uid) -> Result<()>;
     pub fn group(&self) -> Result<Gid>;
     pub fn set_group(&self, gid: Gid) -> Result<()>;
+    pub fn set_xattr(&self, name: XattrName, value: &[u8], flags: XattrSetFlags) -> Result<()>;
+    pub fn get_xattr(&self, name: XattrName) -> Result<Vec<u8>>;
+    pub fn list_xattr(&self) -> Result<Vec<String>>;
+    pub fn remove_xattr(&self, name: XattrName) -> Result<()>;
     pub fn atime(&self) -> Duration;
     pub fn set_atime(&self, time: Duration);
     pub fn mtime(&self) -> Duration;
@@ -721,4 +895,5 @@ impl Dentry {
     pub fn inode(&self) -> &Arc<dyn Inode>;
     pub fn is_root_of_mount(&self) -> bool;
     pub fn is_mountpoint(&self) -> bool;
+    pub fn notify_event(&self, events: FsEvents);
 }
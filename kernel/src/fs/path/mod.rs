@@ -4,6 +4,8 @@
 
 pub use dentry::{Dentry, DentryKey};
 pub use mount::MountNode;
+pub use mount_namespace::MountNamespace;
 
 mod dentry;
 mod mount;
+mod mount_namespace;
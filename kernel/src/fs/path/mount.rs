@@ -10,6 +10,11 @@ use crate::{
     prelude::*,
 };
 
+/// All the mount nodes that are alive, indexed by their addresses.
+///
+/// This is used to find all the mounted filesystems, e.g., when syncing them.
+static MOUNT_NODES: SpinLock<BTreeMap<usize, Weak<MountNode>>> = SpinLock::new(BTreeMap::new());
+
 /// The `MountNode` is used to form a mount tree to maintain the mount information.
 pub struct MountNode {
     /// Root dentry.
@@ -48,14 +53,19 @@ impl MountNode {
     /// exist without a mountpoint, ensuring uniformity and security, while all other
     /// mount nodes must be explicitly assigned a mountpoint to maintain structural integrity.
     fn new(fs: Arc<dyn FileSystem>, parent_mount: Option<Weak<MountNode>>) -> Arc<Self> {
-        Arc::new_cyclic(|weak_self| Self {
+        let mount_node = Arc::new_cyclic(|weak_self| Self {
             root_dentry: Dentry_::new_root(fs.root_inode()),
             mountpoint_dentry: RwLock::new(None),
             parent: RwLock::new(parent_mount),
             children: RwLock::new(HashMap::new()),
             fs,
             this: weak_self.clone(),
-        })
+        });
+        MOUNT_NODES.lock().insert(
+            Arc::as_ptr(&mount_node) as usize,
+            Arc::downgrade(&mount_node),
+        );
+        mount_node
     }
 
     /// Mounts a fs on the mountpoint, it will create a new child mount node.
@@ -77,9 +87,8 @@ impl MountNode {
             return_errno!(Errno::ENOTDIR);
         }
 
-        let key = mountpoint.key();
         let child_mount = Self::new(fs, Some(Arc::downgrade(mountpoint.mount_node())));
-        self.children.write().insert(key, child_mount.clone());
+        self.insert_child(mountpoint.inner(), child_mount.clone());
         Ok(child_mount)
     }
 
@@ -92,9 +101,7 @@ impl MountNode {
         }
 
         let child_mount = self
-            .children
-            .write()
-            .remove(&mountpoint.key())
+            .remove_child(mountpoint.inner())
             .ok_or_else(|| Error::with_message(Errno::ENOENT, "can not find child mount"))?;
         Ok(child_mount)
     }
@@ -144,14 +151,7 @@ impl MountNode {
                 }
                 let new_child_mount =
                     old_child_mount.clone_mount_node(old_child_mount.root_dentry());
-                let key = mountpoint_dentry.key();
-                new_parent_mount
-                    .children
-                    .write()
-                    .insert(key, new_child_mount.clone());
-                new_child_mount.set_parent(&new_parent_mount);
-                new_child_mount
-                    .set_mountpoint_dentry(&old_child_mount.mountpoint_dentry().unwrap());
+                new_parent_mount.insert_child(&mountpoint_dentry, new_child_mount.clone());
                 stack.push(old_child_mount.clone());
                 new_stack.push(new_child_mount);
             }
@@ -164,23 +164,70 @@ impl MountNode {
     fn detach_mount_node(&self) {
         if let Some(parent) = self.parent() {
             let parent = parent.upgrade().unwrap();
-            parent
-                .children
-                .write()
-                .remove(&self.mountpoint_dentry().unwrap().key());
+            parent.remove_child(&self.mountpoint_dentry().unwrap());
         }
     }
 
     /// Attaches the mount node to the mountpoint.
     fn attach_mount_node(&self, mountpoint: &Dentry) {
-        let key = mountpoint.key();
         mountpoint
             .mount_node()
-            .children
-            .write()
-            .insert(key, self.this());
-        self.set_parent(mountpoint.mount_node());
-        mountpoint.set_mountpoint(self.this());
+            .insert_child(mountpoint.inner(), self.this());
+    }
+
+    /// Inserts a child mount node which is mounted on the mountpoint `Dentry_`.
+    ///
+    /// If another child mount node has already been mounted on the mountpoint,
+    /// it will be replaced.
+    fn insert_child(&self, mountpoint: &Arc<Dentry_>, child_mount: Arc<Self>) {
+        child_mount.set_parent(&self.this());
+        child_mount.set_mountpoint_dentry(mountpoint);
+
+        let old_child_mount = self.children.write().insert(mountpoint.key(), child_mount);
+        if old_child_mount.is_none() {
+            mountpoint.inc_mount_count();
+        }
+    }
+
+    /// Removes the child mount node which is mounted on the mountpoint `Dentry_`.
+    fn remove_child(&self, mountpoint: &Arc<Dentry_>) -> Option<Arc<Self>> {
+        let child_mount = self.children.write().remove(&mountpoint.key())?;
+        mountpoint.dec_mount_count();
+        Some(child_mount)
+    }
+
+    /// Finds the mount node in the tree rooted at `self`, which corresponds to
+    /// `mount` in the tree rooted at `orig_root`.
+    ///
+    /// The tree rooted at `self` should be cloned from the tree rooted at
+    /// `orig_root`, so that the corresponding mount nodes are mounted on the same
+    /// mountpoints. Returns `None` if `mount` does not belong to the tree rooted
+    /// at `orig_root` or there is no such mount node.
+    pub(super) fn find_corresponding_mount(
+        &self,
+        orig_root: &Arc<Self>,
+        mount: &Arc<Self>,
+    ) -> Option<Arc<Self>> {
+        let mut mountpoints = Vec::new();
+        let mut orig_mount = mount.clone();
+        while let Some(parent) = orig_mount.parent() {
+            mountpoints.push(orig_mount.mountpoint_dentry()?);
+            orig_mount = parent.upgrade()?;
+        }
+        if !Arc::ptr_eq(&orig_mount, orig_root) {
+            return None;
+        }
+
+        let mut corresponding_mount = self.this();
+        for mountpoint in mountpoints.iter().rev() {
+            let child_mount = corresponding_mount
+                .children
+                .read()
+                .get(&mountpoint.key())
+                .cloned()?;
+            corresponding_mount = child_mount;
+        }
+        Some(corresponding_mount)
     }
 
     /// Grafts the mount node tree to the mountpoint.
@@ -234,6 +281,29 @@ impl MountNode {
         Ok(())
     }
 
+    /// Flushes all the mounted filesystems, including the ones that are only
+    /// visible in other mount namespaces or that have been lazily unmounted.
+    pub fn sync_all() -> Result<()> {
+        let mount_nodes: Vec<Arc<MountNode>> = MOUNT_NODES
+            .lock()
+            .values()
+            .filter_map(Weak::upgrade)
+            .collect();
+
+        // A filesystem may be mounted multiple times, but it only needs to be synced once.
+        let mut fses: Vec<&Arc<dyn FileSystem>> = mount_nodes
+            .iter()
+            .map(|mount_node| &mount_node.fs)
+            .collect();
+        fses.sort_by_key(|fs| Arc::as_ptr(fs) as *const () as usize);
+        fses.dedup_by_key(|fs| Arc::as_ptr(fs) as *const () as usize);
+
+        for fs in fses {
+            fs.sync()?;
+        }
+        Ok(())
+    }
+
     /// Gets the parent mount node if any.
     pub fn parent(&self) -> Option<Weak<Self>> {
         self.parent.read().as_ref().cloned()
@@ -258,6 +328,18 @@ impl MountNode {
     }
 }
 
+impl Drop for MountNode {
+    fn drop(&mut self) {
+        MOUNT_NODES.lock().remove(&(self as *const Self as usize));
+
+        for child_mount in self.children.get_mut().values() {
+            if let Some(mountpoint) = child_mount.mountpoint_dentry() {
+                mountpoint.dec_mount_count();
+            }
+        }
+    }
+}
+
 impl Debug for MountNode {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.debug_struct("MountNode")
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::path::{dentry::Dentry, mount::MountNode},
+    prelude::*,
+    process::namespace::alloc_ns_id,
+};
+
+/// A mount namespace, which isolates the mount tree seen by the processes.
+///
+/// Each mount namespace owns a separate mount tree. Mounting or unmounting file
+/// systems in one mount namespace will not affect other mount namespaces.
+pub struct MountNamespace {
+    /// The root mount node of the mount tree.
+    root: Arc<MountNode>,
+    /// The unique ID, which is shown as the inode number of `/proc/[pid]/ns/mnt`.
+    id: u64,
+}
+
+impl MountNamespace {
+    /// Creates a mount namespace with the root mount node.
+    pub fn new(root: Arc<MountNode>) -> Arc<Self> {
+        Arc::new(Self {
+            root,
+            id: alloc_ns_id(),
+        })
+    }
+
+    /// Creates a new mount namespace by copying the whole mount tree of `self`.
+    pub fn copy(&self) -> Arc<Self> {
+        let root = self
+            .root
+            .clone_mount_node_tree(self.root.root_dentry(), true);
+        Self::new(root)
+    }
+
+    /// Gets the root mount node.
+    pub fn root(&self) -> &Arc<MountNode> {
+        &self.root
+    }
+
+    /// Gets the `Dentry` of the root directory.
+    pub fn root_dentry(&self) -> Dentry {
+        Dentry::new_fs_root(self.root.clone())
+    }
+
+    /// Gets the unique ID.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Finds the `Dentry` in `self` that corresponds to `dentry` in `orig_ns`.
+    ///
+    /// The mount namespace `self` should be copied from `orig_ns`. Returns `None`
+    /// if `dentry` is not in `orig_ns` or the mount that it belongs to has not
+    /// been copied.
+    pub fn corresponding_dentry(
+        &self,
+        orig_ns: &MountNamespace,
+        dentry: &Dentry,
+    ) -> Option<Dentry> {
+        let mount_node = self
+            .root
+            .find_corresponding_mount(&orig_ns.root, dentry.mount_node())?;
+        Some(Dentry::new(mount_node, dentry.inner().clone()))
+    }
+}
+
+impl Debug for MountNamespace {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("MountNamespace")
+            .field("root", &self.root)
+            .field("id", &self.id)
+            .finish()
+    }
+}
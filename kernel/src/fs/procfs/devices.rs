@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/devices` file support, which lists the registered major numbers
+//! of the character devices and the block devices.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_devices.5.html>
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        device::{all_majors, DeviceType},
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/devices`.
+pub struct DevicesFileOps;
+
+impl DevicesFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for DevicesFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut result = String::from("Character devices:\n");
+        for (major, name) in all_majors(DeviceType::CharDevice) {
+            result.push_str(&format!("{:3} {}\n", major, name));
+        }
+
+        result.push_str("\nBlock devices:\n");
+        for (major, name) in all_majors(DeviceType::BlockDevice) {
+            result.push_str(&format!("{:3} {}\n", major, name));
+        }
+
+        Ok(result.into_bytes())
+    }
+}
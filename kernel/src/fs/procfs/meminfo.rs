@@ -16,6 +16,7 @@ use crate::{
         utils::Inode,
     },
     prelude::*,
+    vm::swap,
 };
 
 /// Represents the inode at `/proc/meminfo`.
@@ -42,7 +43,12 @@ impl FileOps for MemInfoFileOps {
     fn data(&self) -> Result<Vec<u8>> {
         let total = mem_total();
         let available = mem_available();
-        let output = format!("MemTotal:\t{}\nMemAvailable:\t{}\n", total, available);
+        let swap_total = swap::swap_total();
+        let swap_free = swap::swap_free();
+        let output = format!(
+            "MemTotal:\t{}\nMemAvailable:\t{}\nSwapTotal:\t{}\nSwapFree:\t{}\n",
+            total, available, swap_total, swap_free
+        );
         Ok(output.into_bytes())
     }
 }
@@ -4,13 +4,17 @@ use core::sync::atomic::{AtomicU64, Ordering};
 
 use filesystems::{FileSystemType, FILESYSTEM_TYPES};
 
+pub use self::pid::namespace_of_inode;
 use self::{
     cpuinfo::CpuInfoFileOps,
+    devices::DevicesFileOps,
     loadavg::LoadAvgFileOps,
     meminfo::MemInfoFileOps,
     pid::PidDirOps,
     self_::SelfSymOps,
+    swaps::SwapsFileOps,
     sys::SysDirOps,
+    sysvipc::SysVIpcDirOps,
     template::{DirOps, ProcDir, ProcDirBuilder, ProcSymBuilder, SymOps},
     thread_self::ThreadSelfSymOps,
 };
@@ -22,27 +26,34 @@ use crate::{
     },
     prelude::*,
     process::{
+        namespace::PidNamespace,
         process_table::{self, PidEvent},
         Pid,
     },
 };
 
 mod cpuinfo;
+mod devices;
 mod filesystems;
 mod loadavg;
 mod meminfo;
 mod pid;
 mod self_;
+mod swaps;
 mod sys;
-mod template;
+mod sysvipc;
+pub(super) mod template;
 mod thread_self;
 
 pub(super) fn init() {
     FILESYSTEM_TYPES.call_once(|| {
         vec![
             FileSystemType::new("proc", true),
+            FileSystemType::new("sysfs", true),
             FileSystemType::new("ramfs", true),
+            FileSystemType::new("devtmpfs", true),
             FileSystemType::new("devpts", true),
+            FileSystemType::new("mqueue", true),
             FileSystemType::new("ext2", false),
             FileSystemType::new("exfat", false),
         ]
@@ -63,10 +74,11 @@ pub struct ProcFS {
 }
 
 impl ProcFS {
-    pub fn new() -> Arc<Self> {
+    /// Creates a procfs instance that shows the processes in the PID namespace.
+    pub fn new(pid_ns: Arc<PidNamespace>) -> Arc<Self> {
         Arc::new_cyclic(|weak_fs| Self {
             sb: SuperBlock::new(PROC_MAGIC, BLOCK_SIZE, NAME_MAX),
-            root: RootDirOps::new_inode(weak_fs.clone()),
+            root: RootDirOps::new_inode(pid_ns, weak_fs.clone()),
             inode_allocator: AtomicU64::new(PROC_ROOT_INO + 1),
         })
     }
@@ -95,11 +107,13 @@ impl FileSystem for ProcFS {
 }
 
 /// Represents the inode at `/proc`.
-struct RootDirOps;
+///
+/// The process IDs in the directory are those in the PID namespace.
+struct RootDirOps(Arc<PidNamespace>);
 
 impl RootDirOps {
-    pub fn new_inode(fs: Weak<ProcFS>) -> Arc<dyn Inode> {
-        let root_inode = ProcDirBuilder::new(Self)
+    pub fn new_inode(pid_ns: Arc<PidNamespace>, fs: Weak<ProcFS>) -> Arc<dyn Inode> {
+        let root_inode = ProcDirBuilder::new(Self(pid_ns))
             .fs(fs)
             .ino(PROC_ROOT_INO)
             .build()
@@ -113,6 +127,9 @@ impl RootDirOps {
 impl Observer<PidEvent> for ProcDir<RootDirOps> {
     fn on_events(&self, events: &PidEvent) {
         let PidEvent::Exit(pid) = events;
+        let Some(pid) = self.inner().0.local_id(*pid) else {
+            return;
+        };
         let mut cached_children = self.cached_children().write();
         cached_children.remove_entry_by_name(&pid.to_string());
     }
@@ -121,11 +138,13 @@ impl Observer<PidEvent> for ProcDir<RootDirOps> {
 impl DirOps for RootDirOps {
     fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
         let child = if name == "self" {
-            SelfSymOps::new_inode(this_ptr.clone())
+            SelfSymOps::new_inode(self.0.clone(), this_ptr.clone())
         } else if name == "sys" {
             SysDirOps::new_inode(this_ptr.clone())
+        } else if name == "sysvipc" {
+            SysVIpcDirOps::new_inode(this_ptr.clone())
         } else if name == "thread-self" {
-            ThreadSelfSymOps::new_inode(this_ptr.clone())
+            ThreadSelfSymOps::new_inode(self.0.clone(), this_ptr.clone())
         } else if name == "filesystems" {
             FileSystemsFileOps::new_inode(this_ptr.clone())
         } else if name == "meminfo" {
@@ -134,10 +153,16 @@ impl DirOps for RootDirOps {
             LoadAvgFileOps::new_inode(this_ptr.clone())
         } else if name == "cpuinfo" {
             CpuInfoFileOps::new_inode(this_ptr.clone())
+        } else if name == "swaps" {
+            SwapsFileOps::new_inode(this_ptr.clone())
+        } else if name == "devices" {
+            DevicesFileOps::new_inode(this_ptr.clone())
         } else if let Ok(pid) = name.parse::<Pid>() {
-            let process_ref =
-                process_table::get_process(pid).ok_or_else(|| Error::new(Errno::ENOENT))?;
-            PidDirOps::new_inode(process_ref, this_ptr.clone())
+            let process_ref = self
+                .0
+                .get_process(pid)
+                .ok_or_else(|| Error::new(Errno::ENOENT))?;
+            PidDirOps::new_inode(process_ref, self.0.clone(), this_ptr.clone())
         } else {
             return_errno!(Errno::ENOENT);
         };
@@ -150,11 +175,15 @@ impl DirOps for RootDirOps {
             this.downcast_ref::<ProcDir<RootDirOps>>().unwrap().this()
         };
         let mut cached_children = this.cached_children().write();
-        cached_children.put_entry_if_not_found("self", || SelfSymOps::new_inode(this_ptr.clone()));
+        cached_children.put_entry_if_not_found("self", || {
+            SelfSymOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
         cached_children.put_entry_if_not_found("thread-self", || {
-            ThreadSelfSymOps::new_inode(this_ptr.clone())
+            ThreadSelfSymOps::new_inode(self.0.clone(), this_ptr.clone())
         });
         cached_children.put_entry_if_not_found("sys", || SysDirOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("sysvipc", || SysVIpcDirOps::new_inode(this_ptr.clone()));
         cached_children.put_entry_if_not_found("filesystems", || {
             FileSystemsFileOps::new_inode(this_ptr.clone())
         });
@@ -164,10 +193,15 @@ impl DirOps for RootDirOps {
             .put_entry_if_not_found("loadavg", || LoadAvgFileOps::new_inode(this_ptr.clone()));
         cached_children
             .put_entry_if_not_found("cpuinfo", || CpuInfoFileOps::new_inode(this_ptr.clone()));
-        for process in process_table::process_table_mut().iter() {
-            let pid = process.pid().to_string();
+        cached_children
+            .put_entry_if_not_found("swaps", || SwapsFileOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("devices", || DevicesFileOps::new_inode(this_ptr.clone()));
+        let process_table = process_table::process_table_mut();
+        for process in self.0.visible_processes(&process_table) {
+            let pid = self.0.local_id_or_zero(process.pid()).to_string();
             cached_children.put_entry_if_not_found(&pid, || {
-                PidDirOps::new_inode(process.clone(), this_ptr.clone())
+                PidDirOps::new_inode(process.clone(), self.0.clone(), this_ptr.clone())
             });
         }
     }
@@ -25,7 +25,11 @@ impl FdDirOps {
             .build()
             .unwrap();
         let main_thread = process_ref.main_thread();
-        let file_table = main_thread.as_posix_thread().unwrap().file_table().lock();
+        let file_table = main_thread
+            .as_posix_thread()
+            .unwrap()
+            .file_table()
+            .lock_arc();
         let weak_ptr = Arc::downgrade(&fd_inode);
         file_table.register_observer(weak_ptr);
         fd_inode
@@ -52,7 +56,11 @@ impl DirOps for FdDirOps {
                 .parse::<FileDesc>()
                 .map_err(|_| Error::new(Errno::ENOENT))?;
             let main_thread = self.0.main_thread();
-            let file_table = main_thread.as_posix_thread().unwrap().file_table().lock();
+            let file_table = main_thread
+                .as_posix_thread()
+                .unwrap()
+                .file_table()
+                .lock_arc();
             file_table
                 .get_file(fd)
                 .map_err(|_| Error::new(Errno::ENOENT))?
@@ -68,7 +76,11 @@ impl DirOps for FdDirOps {
         };
         let mut cached_children = this.cached_children().write();
         let main_thread = self.0.main_thread();
-        let file_table = main_thread.as_posix_thread().unwrap().file_table().lock();
+        let file_table = main_thread
+            .as_posix_thread()
+            .unwrap()
+            .file_table()
+            .lock_arc();
         for (fd, file) in file_table.fds_and_files() {
             cached_children.put_entry_if_not_found(&fd.to_string(), || {
                 FileSymOps::new_inode(file.clone(), this_ptr.clone())
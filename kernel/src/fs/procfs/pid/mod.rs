@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
+pub use self::ns::namespace_of_inode;
 use self::{
-    cmdline::CmdlineFileOps, comm::CommFileOps, exe::ExeSymOps, fd::FdDirOps, task::TaskDirOps,
+    cmdline::CmdlineFileOps, comm::CommFileOps, exe::ExeSymOps, fd::FdDirOps, ns::NsDirOps,
+    oom_score::OomScoreFileOps, oom_score_adj::OomScoreAdjFileOps, smaps::SmapsFileOps,
+    task::TaskDirOps,
 };
 use super::template::{DirOps, ProcDir, ProcDirBuilder};
 use crate::{
@@ -11,30 +14,45 @@ use crate::{
         utils::{DirEntryVecExt, Inode},
     },
     prelude::*,
-    process::{posix_thread::AsPosixThread, Process},
+    process::{namespace::PidNamespace, posix_thread::AsPosixThread, Process},
 };
 
 mod cmdline;
 mod comm;
 mod exe;
 mod fd;
+mod ns;
+mod oom_score;
+mod oom_score_adj;
+mod smaps;
 mod stat;
 mod status;
 mod task;
 
 /// Represents the inode at `/proc/[pid]`.
-pub struct PidDirOps(Arc<Process>);
+///
+/// The PID namespace is the one that the procfs belongs to. The IDs in the
+/// files are shown as in this namespace.
+pub struct PidDirOps(Arc<Process>, Arc<PidNamespace>);
 
 impl PidDirOps {
-    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
-        let pid_inode = ProcDirBuilder::new(Self(process_ref.clone()))
+    pub fn new_inode(
+        process_ref: Arc<Process>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        let pid_inode = ProcDirBuilder::new(Self(process_ref.clone(), pid_ns))
             .parent(parent)
             // The pid directories must be volatile, because it is just associated with one process.
             .volatile()
             .build()
             .unwrap();
         let main_thread = process_ref.main_thread();
-        let file_table = main_thread.as_posix_thread().unwrap().file_table().lock();
+        let file_table = main_thread
+            .as_posix_thread()
+            .unwrap()
+            .file_table()
+            .lock_arc();
         let weak_ptr = Arc::downgrade(&pid_inode);
         file_table.register_observer(weak_ptr);
         pid_inode
@@ -57,9 +75,17 @@ impl DirOps for PidDirOps {
             "comm" => CommFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "fd" => FdDirOps::new_inode(self.0.clone(), this_ptr.clone()),
             "cmdline" => CmdlineFileOps::new_inode(self.0.clone(), this_ptr.clone()),
-            "status" => status::StatusFileOps::new_inode(self.0.clone(), this_ptr.clone()),
-            "stat" => stat::StatFileOps::new_inode(self.0.clone(), this_ptr.clone()),
-            "task" => TaskDirOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "status" => {
+                status::StatusFileOps::new_inode(self.0.clone(), self.1.clone(), this_ptr.clone())
+            }
+            "stat" => {
+                stat::StatFileOps::new_inode(self.0.clone(), self.1.clone(), this_ptr.clone())
+            }
+            "task" => TaskDirOps::new_inode(self.0.clone(), self.1.clone(), this_ptr.clone()),
+            "ns" => NsDirOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "oom_score" => OomScoreFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "oom_score_adj" => OomScoreAdjFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "smaps" => SmapsFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             _ => return_errno!(Errno::ENOENT),
         };
         Ok(inode)
@@ -84,13 +110,25 @@ impl DirOps for PidDirOps {
             CmdlineFileOps::new_inode(self.0.clone(), this_ptr.clone())
         });
         cached_children.put_entry_if_not_found("status", || {
-            status::StatusFileOps::new_inode(self.0.clone(), this_ptr.clone())
+            status::StatusFileOps::new_inode(self.0.clone(), self.1.clone(), this_ptr.clone())
         });
         cached_children.put_entry_if_not_found("stat", || {
-            stat::StatFileOps::new_inode(self.0.clone(), this_ptr.clone())
+            stat::StatFileOps::new_inode(self.0.clone(), self.1.clone(), this_ptr.clone())
         });
         cached_children.put_entry_if_not_found("task", || {
-            TaskDirOps::new_inode(self.0.clone(), this_ptr.clone())
+            TaskDirOps::new_inode(self.0.clone(), self.1.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("ns", || {
+            NsDirOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("oom_score", || {
+            OomScoreFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("oom_score_adj", || {
+            OomScoreAdjFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("smaps", || {
+            SmapsFileOps::new_inode(self.0.clone(), this_ptr.clone())
         });
     }
 }
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        procfs::template::{DirOps, FileOps, ProcDir, ProcDirBuilder, ProcFile, ProcFileBuilder},
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+    process::{namespace::Namespace, Process},
+};
+
+/// Represents the inode at `/proc/[pid]/ns`.
+pub struct NsDirOps(Arc<Process>);
+
+impl NsDirOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+/// The files in `/proc/[pid]/ns` and the functions to get their namespaces.
+const NS_ENTRIES: [(&str, fn(&Process) -> Namespace); 4] = [
+    ("ipc", Namespace::ipc_of),
+    ("mnt", Namespace::mnt_of),
+    ("pid", Namespace::pid_of),
+    ("uts", Namespace::uts_of),
+];
+
+impl DirOps for NsDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let Some((_, ns_of)) = NS_ENTRIES.iter().find(|(entry, _)| *entry == name) else {
+            return_errno!(Errno::ENOENT);
+        };
+        Ok(NsFileOps::new_inode(ns_of(&self.0), this_ptr))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<NsDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for (name, ns_of) in NS_ENTRIES {
+            cached_children.put_entry_if_not_found(name, || {
+                NsFileOps::new_inode(ns_of(&self.0), this_ptr.clone())
+            });
+        }
+    }
+}
+
+/// Represents the inode at `/proc/[pid]/ns/[type]`.
+///
+/// The inode number is the ID of the namespace, so two processes are in the
+/// same namespace if and only if their files have the same inode number. The
+/// file can be opened and passed to `setns` to join the namespace.
+// FIXME: In Linux, these files are symbolic links whose contents are in the
+// form of `[type]:[inode number]`.
+struct NsFileOps(Namespace);
+
+impl NsFileOps {
+    fn new_inode(ns: Namespace, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(ns.clone()))
+            .parent(parent)
+            .ino(ns.id())
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for NsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        return_errno_with_message!(Errno::EINVAL, "namespace files cannot be read");
+    }
+}
+
+/// Returns the namespace that the inode at `/proc/[pid]/ns/[type]` refers to.
+///
+/// Returns `None` if the inode is not such a file.
+pub fn namespace_of_inode(inode: &Arc<dyn Inode>) -> Option<Namespace> {
+    let ns_file = inode.downcast_ref::<ProcFile<NsFileOps>>()?;
+    Some(ns_file.inner().0.clone())
+}
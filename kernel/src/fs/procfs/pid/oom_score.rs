@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    vm::oom::oom_score,
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/oom_score`.
+pub struct OomScoreFileOps(Arc<Process>);
+
+impl OomScoreFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for OomScoreFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let output = format!("{}\n", oom_score(&self.0));
+        Ok(output.into_bytes())
+    }
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::{credentials::capabilities::CapSet, posix_thread::AsPosixThread},
+    vm::oom::{OOM_SCORE_ADJ_MAX, OOM_SCORE_ADJ_MIN},
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/oom_score_adj`.
+pub struct OomScoreAdjFileOps(Arc<Process>);
+
+impl OomScoreAdjFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for OomScoreAdjFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let output = format!("{}\n", self.0.oom_score_adj());
+        Ok(output.into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let len = reader.remain();
+        let mut buf = vec![0u8; len];
+        reader.read_fallible(&mut VmWriter::from(buf.as_mut_slice()))?;
+
+        let oom_score_adj = core::str::from_utf8(&buf)
+            .ok()
+            .and_then(|value| value.trim().parse::<i16>().ok())
+            .filter(|value| (OOM_SCORE_ADJ_MIN..=OOM_SCORE_ADJ_MAX).contains(value))
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid oom_score_adj"))?;
+
+        // Only privileged processes can make a process less likely to be killed.
+        if oom_score_adj < self.0.oom_score_adj()
+            && !current_thread!()
+                .as_posix_thread()
+                .unwrap()
+                .credentials()
+                .effective_capset()
+                .contains(CapSet::SYS_RESOURCE)
+        {
+            return_errno_with_message!(
+                Errno::EACCES,
+                "`CAP_SYS_RESOURCE` is required to decrease oom_score_adj"
+            );
+        }
+
+        self.0.set_oom_score_adj(oom_score_adj);
+        Ok(len)
+    }
+}
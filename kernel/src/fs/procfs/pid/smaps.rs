@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Write;
+
+use crate::{
+    fs::{
+        device::DeviceId,
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    vm::{
+        perms::VmPerms,
+        vmar::vm_mapping::{VmLockMode, VmMappingInfo},
+    },
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/smaps`.
+/// See https://www.kernel.org/doc/html/latest/filesystems/proc.html#id14
+/// FIXME: Some fields are not implemented yet.
+///
+/// Each mapping starts with a header line, which is the same as the one in
+/// `/proc/[pid]/maps`. The fields of the mapping follow:
+/// - Size           : Size of the mapping.
+/// - KernelPageSize : Page size used by the kernel.
+/// - MMUPageSize    : Page size used by the MMU.
+/// - Rss            : Size of the mapped pages.
+/// - Locked         : Size of the mapped pages that are locked in memory.
+/// - VmFlags        : Flags of the mapping, e.g., `lo` for locked mappings.
+pub struct SmapsFileOps(Arc<Process>);
+
+impl SmapsFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for SmapsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let root_vmar = self.0.root_vmar();
+
+        let mut smaps_output = String::new();
+        for info in root_vmar.mappings_info() {
+            write_header(&mut smaps_output, &info);
+
+            let size = info.range.len();
+            let rss = root_vmar.rss_in(info.range.clone());
+            let locked = if info.lock.is_some() { rss } else { 0 };
+            write_size(&mut smaps_output, "Size:", size);
+            write_size(&mut smaps_output, "KernelPageSize:", PAGE_SIZE);
+            write_size(&mut smaps_output, "MMUPageSize:", PAGE_SIZE);
+            write_size(&mut smaps_output, "Rss:", rss);
+            write_size(&mut smaps_output, "Locked:", locked);
+
+            write!(smaps_output, "VmFlags: ").unwrap();
+            for flag in vm_flags(&info) {
+                write!(smaps_output, "{} ", flag).unwrap();
+            }
+            writeln!(smaps_output).unwrap();
+        }
+
+        Ok(smaps_output.into_bytes())
+    }
+}
+
+/// Writes the header line of the mapping, which has the address range, the
+/// permissions, the file offset, the device, the inode, and the file path.
+fn write_header(output: &mut String, info: &VmMappingInfo) {
+    let perm = |perm: VmPerms, c: char| if info.perms.contains(perm) { c } else { '-' };
+    let (offset, device, ino, path) = match &info.file {
+        Some((dentry, offset)) => {
+            let metadata = dentry.inode().metadata();
+            (
+                *offset,
+                DeviceId::from(metadata.dev),
+                metadata.ino,
+                dentry.abs_path(),
+            )
+        }
+        None => (0, DeviceId::new(0, 0), 0, String::new()),
+    };
+
+    let header = format!(
+        "{:08x}-{:08x} {}{}{}{} {:08x} {:02x}:{:02x} {} ",
+        info.range.start,
+        info.range.end,
+        perm(VmPerms::READ, 'r'),
+        perm(VmPerms::WRITE, 'w'),
+        perm(VmPerms::EXEC, 'x'),
+        if info.is_shared { 's' } else { 'p' },
+        offset,
+        device.major(),
+        device.minor(),
+        ino,
+    );
+    if path.is_empty() {
+        writeln!(output, "{}", header).unwrap();
+    } else {
+        // Like Linux, the paths are aligned in a column.
+        writeln!(output, "{:<73}{}", header, path).unwrap();
+    }
+}
+
+fn write_size(output: &mut String, name: &str, size: usize) {
+    writeln!(output, "{:<16}{:>8} kB", name, size / 1024).unwrap();
+}
+
+/// Returns the two-letter codes of the flags of the mapping.
+fn vm_flags(info: &VmMappingInfo) -> Vec<&'static str> {
+    let mut flags = Vec::new();
+    if info.perms.contains(VmPerms::READ) {
+        flags.push("rd");
+    }
+    if info.perms.contains(VmPerms::WRITE) {
+        flags.push("wr");
+    }
+    if info.perms.contains(VmPerms::EXEC) {
+        flags.push("ex");
+    }
+    if info.is_shared {
+        flags.push("sh");
+    }
+    if info.lock.is_some() {
+        flags.push("lo");
+    }
+    if info.is_dont_fork {
+        flags.push("dc");
+    }
+    if info.lock == Some(VmLockMode::OnFault) {
+        flags.push("lf");
+    }
+    if info.is_wipe_on_fork {
+        flags.push("wf");
+    }
+    if info.is_dont_dump {
+        flags.push("dd");
+    }
+    flags
+}
@@ -8,6 +8,7 @@ use crate::{
         utils::Inode,
     },
     prelude::*,
+    process::namespace::PidNamespace,
     Process,
 };
 
@@ -68,11 +69,15 @@ use crate::{
 /// - env_start        : Start address of environment variables.
 /// - env_end          : End address of environment variables.
 /// - exit_code        : Process exit code as returned by waitpid(2).
-pub struct StatFileOps(Arc<Process>);
+pub struct StatFileOps(Arc<Process>, Arc<PidNamespace>);
 
 impl StatFileOps {
-    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
-        ProcFileBuilder::new(Self(process_ref))
+    pub fn new_inode(
+        process_ref: Arc<Process>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref, pid_ns))
             .parent(parent)
             .build()
             .unwrap()
@@ -82,17 +87,18 @@ impl StatFileOps {
 impl FileOps for StatFileOps {
     fn data(&self) -> Result<Vec<u8>> {
         let process = &self.0;
+        let pid_ns = &self.1;
 
-        let pid = process.pid();
+        let pid = pid_ns.local_id_or_zero(process.pid());
         let comm = process.executable_path();
-        let ppid = process.parent().pid();
+        let ppid = pid_ns.local_id_or_zero(process.parent().pid());
         let state = if process.status().is_zombie() {
             'Z'
         } else {
             'R'
         };
         let pgrp = if let Some(pgrp) = process.process_group() {
-            pgrp.pgid()
+            pid_ns.local_id_or_zero(pgrp.pgid())
         } else {
             0
         };
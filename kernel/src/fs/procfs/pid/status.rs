@@ -8,7 +8,7 @@ use crate::{
         utils::Inode,
     },
     prelude::*,
-    process::posix_thread::AsPosixThread,
+    process::{namespace::PidNamespace, posix_thread::AsPosixThread, Pid},
     Process,
 };
 
@@ -22,6 +22,8 @@ use crate::{
 /// - Tgid:   The Thread Group ID, which is the same as the process ID for the main thread.
 /// - Pid:    The process ID.
 /// - PPid:   The parent process ID.
+/// - NSpid:  The process IDs in the PID namespaces that the process belongs to,
+///   from the outermost to the innermost.
 /// - TracerPid: The PID of the process tracing this process, or 0 if not being traced.
 /// - Uid:    Real, effective, saved set, and filesystem UIDs.
 /// - Gid:    Real, effective, saved set, and filesystem GIDs.
@@ -58,11 +60,15 @@ use crate::{
 /// - Mems_allowed_list: List of memory nodes allowed for this process.
 /// - voluntary_ctxt_switches: Number of voluntary context switches.
 /// - nonvoluntary_ctxt_switches: Number of nonvoluntary context switches.
-pub struct StatusFileOps(Arc<Process>);
+pub struct StatusFileOps(Arc<Process>, Arc<PidNamespace>);
 
 impl StatusFileOps {
-    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
-        ProcFileBuilder::new(Self(process_ref))
+    pub fn new_inode(
+        process_ref: Arc<Process>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref, pid_ns))
             .parent(parent)
             .build()
             .unwrap()
@@ -72,16 +78,28 @@ impl StatusFileOps {
 impl FileOps for StatusFileOps {
     fn data(&self) -> Result<Vec<u8>> {
         let process = &self.0;
+        let pid_ns = &self.1;
         let main_thread = process.main_thread();
         let file_table = main_thread.as_posix_thread().unwrap().file_table();
 
         let mut status_output = String::new();
         writeln!(status_output, "Name:\t{}", process.executable_path()).unwrap();
-        writeln!(status_output, "Tgid:\t{}", process.pid()).unwrap();
-        writeln!(status_output, "Pid:\t{}", process.pid()).unwrap();
-        writeln!(status_output, "PPid:\t{}", process.parent().pid()).unwrap();
-        writeln!(status_output, "TracerPid:\t{}", process.parent().pid()).unwrap(); // Assuming TracerPid is the same as PPid
+        let pid = pid_ns.local_id_or_zero(process.pid());
+        let ppid = pid_ns.local_id_or_zero(process.parent().pid());
+        writeln!(status_output, "Tgid:\t{}", pid).unwrap();
+        writeln!(status_output, "Pid:\t{}", pid).unwrap();
+        writeln!(status_output, "PPid:\t{}", ppid).unwrap();
+        writeln!(status_output, "TracerPid:\t{}", ppid).unwrap(); // Assuming TracerPid is the same as PPid
+        write!(status_output, "NSpid:").unwrap();
+        for ns_pid in ns_pids(process.pid(), process.pid_ns(), pid_ns) {
+            write!(status_output, "\t{}", ns_pid).unwrap();
+        }
+        writeln!(status_output).unwrap();
         writeln!(status_output, "FDSize:\t{}", file_table.lock().len()).unwrap();
+        let root_vmar = process.root_vmar();
+        let locked_size =
+            root_vmar.locked_size(root_vmar.base()..root_vmar.base() + root_vmar.size());
+        writeln!(status_output, "VmLck:\t{:>8} kB", locked_size / 1024).unwrap();
         writeln!(
             status_output,
             "Threads:\t{}",
@@ -91,3 +109,19 @@ impl FileOps for StatusFileOps {
         Ok(status_output.into_bytes())
     }
 }
+
+/// Returns the IDs of the process in the PID namespaces from `viewer_ns` down
+/// to `process_ns`.
+fn ns_pids(pid: Pid, process_ns: &Arc<PidNamespace>, viewer_ns: &PidNamespace) -> Vec<Pid> {
+    let mut ns_pids = Vec::new();
+    let mut ns = Some(process_ns);
+    while let Some(current_ns) = ns {
+        ns_pids.push(current_ns.local_id_or_zero(pid));
+        if core::ptr::eq(current_ns.as_ref(), viewer_ns) {
+            break;
+        }
+        ns = current_ns.parent();
+    }
+    ns_pids.reverse();
+    ns_pids
+}
@@ -8,16 +8,20 @@ use crate::{
         procfs::template::{DirOps, ProcDir, ProcDirBuilder},
         utils::{DirEntryVecExt, Inode},
     },
-    process::posix_thread::AsPosixThread,
+    process::{namespace::PidNamespace, posix_thread::AsPosixThread},
     Process,
 };
 
 /// Represents the inode at `/proc/[pid]/task`.
-pub struct TaskDirOps(Arc<Process>);
+pub struct TaskDirOps(Arc<Process>, Arc<PidNamespace>);
 
 impl TaskDirOps {
-    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
-        ProcDirBuilder::new(Self(process_ref))
+    pub fn new_inode(
+        process_ref: Arc<Process>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self(process_ref, pid_ns))
             .parent(parent)
             .build()
             .unwrap()
@@ -66,6 +70,9 @@ impl DirOps for TaskDirOps {
         let Ok(tid) = name.parse::<u32>() else {
             return_errno_with_message!(Errno::ENOENT, "Can not parse name to u32 type");
         };
+        let Some(tid) = self.1.global_id(tid) else {
+            return_errno_with_message!(Errno::ENOENT, "No such thread");
+        };
 
         for task in self.0.tasks().lock().as_slice() {
             if task.as_posix_thread().unwrap().tid() != tid {
@@ -84,7 +91,11 @@ impl DirOps for TaskDirOps {
         let mut cached_children = this.cached_children().write();
         for task in self.0.tasks().lock().as_slice() {
             cached_children.put_entry_if_not_found(
-                &format!("{}", task.as_posix_thread().unwrap().tid()),
+                &format!(
+                    "{}",
+                    self.1
+                        .local_id_or_zero(task.as_posix_thread().unwrap().tid())
+                ),
                 || ThreadDirOps::new_inode(self.0.clone(), this_ptr.clone()),
             );
         }
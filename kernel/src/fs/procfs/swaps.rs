@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/swaps` file support, which tells the user space
+//! about the swap devices in use.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_swaps.5.html>
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    vm::swap::swap_devices,
+};
+
+/// Represents the inode at `/proc/swaps`.
+pub struct SwapsFileOps;
+
+impl SwapsFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for SwapsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut output = String::from("Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n");
+        for device in swap_devices() {
+            // The sizes are in KiB.
+            output.push_str(&format!(
+                "{:<40}partition\t{}\t\t{}\t\t{}\n",
+                device.name(),
+                device.size() / 1024,
+                device.used() / 1024,
+                device.priority(),
+            ));
+        }
+        Ok(output.into_bytes())
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::{core_pattern, set_core_pattern},
+};
+
+/// Represents the inode at `/proc/sys/kernel/core_pattern`.
+pub struct CorePatternFileOps;
+
+impl CorePatternFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self)
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for CorePatternFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let output = format!("{}\n", core_pattern());
+        Ok(output.into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let len = reader.remain();
+        let mut buf = vec![0u8; len];
+        reader.read_fallible(&mut VmWriter::from(buf.as_mut_slice()))?;
+
+        let pattern = core::str::from_utf8(&buf)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the core pattern is not UTF-8"))?;
+        // Like Linux, the trailing newline is not a part of the pattern.
+        set_core_pattern(pattern.strip_suffix('\n').unwrap_or(pattern))?;
+
+        Ok(len)
+    }
+}
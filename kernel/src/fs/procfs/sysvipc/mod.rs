@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use self::{msg::MsgFileOps, shm::ShmFileOps};
+use crate::{
+    fs::{
+        procfs::template::{DirOps, ProcDir, ProcDirBuilder},
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+};
+
+mod msg;
+mod shm;
+
+/// Represents the inode at `/proc/sysvipc`.
+pub struct SysVIpcDirOps;
+
+impl SysVIpcDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for SysVIpcDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "msg" => MsgFileOps::new_inode(this_ptr.clone()),
+            "shm" => ShmFileOps::new_inode(this_ptr.clone()),
+            _ => return_errno!(Errno::ENOENT),
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<SysVIpcDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children.put_entry_if_not_found("msg", || MsgFileOps::new_inode(this_ptr.clone()));
+        cached_children.put_entry_if_not_found("shm", || ShmFileOps::new_inode(this_ptr.clone()));
+    }
+}
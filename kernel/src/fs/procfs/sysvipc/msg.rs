@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/sysvipc/msg` file support, which lists the System V
+//! message queues in the IPC namespace of the current thread.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_sysvipc.5.html>
+
+use core::fmt::Write;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+};
+
+/// Represents the inode at `/proc/sysvipc/msg`.
+pub struct MsgFileOps;
+
+impl MsgFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for MsgFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let current_thread = current_thread!();
+        let posix_thread = current_thread.as_posix_thread().unwrap();
+        let ipc_ns = posix_thread.ns_proxy().lock().ipc_ns().clone();
+        let pid_ns = current!().pid_ns().clone();
+
+        let mut output = String::from(
+            "       key      msqid perms      cbytes       qnum lspid lrpid   uid   gid  cuid  cgid      stime      rtime      ctime\n",
+        );
+        for queue in ipc_ns.msg_table().queues() {
+            let status = queue.status();
+            let permission = &status.permission;
+            writeln!(
+                output,
+                "{:>10} {:>10}  {:>4o}  {:>10} {:>10} {:>5} {:>5} {:>5} {:>5} {:>5} {:>5} {:>10} {:>10} {:>10}",
+                permission.key(),
+                queue.id(),
+                permission.mode(),
+                status.cbytes,
+                status.qnum,
+                pid_ns.local_id_or_zero(status.lspid),
+                pid_ns.local_id_or_zero(status.lrpid),
+                u32::from(permission.uid()),
+                u32::from(permission.gid()),
+                u32::from(permission.cuid()),
+                u32::from(permission.cguid()),
+                status.stime,
+                status.rtime,
+                status.ctime,
+            )
+            .unwrap();
+        }
+
+        Ok(output.into_bytes())
+    }
+}
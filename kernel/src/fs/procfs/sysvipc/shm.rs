@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/sysvipc/shm` file support, which lists the System V
+//! shared memory segments in the IPC namespace of the current thread.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_sysvipc.5.html>
+
+use core::fmt::Write;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+};
+
+/// Represents the inode at `/proc/sysvipc/shm`.
+pub struct ShmFileOps;
+
+impl ShmFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for ShmFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let current_thread = current_thread!();
+        let posix_thread = current_thread.as_posix_thread().unwrap();
+        let ipc_ns = posix_thread.ns_proxy().lock().ipc_ns().clone();
+        let pid_ns = current!().pid_ns().clone();
+
+        let mut output = String::from(
+            "       key      shmid perms                  size  cpid  lpid nattch   uid   gid  cuid  cgid      atime      dtime      ctime                   rss                  swap\n",
+        );
+        for segment in ipc_ns.shm_table().segments() {
+            let status = segment.status();
+            let permission = &status.permission;
+            // TODO: Report the resident and swapped sizes of the segment.
+            writeln!(
+                output,
+                "{:>10} {:>10}  {:>4o} {:>21} {:>5} {:>5}  {:>5} {:>5} {:>5} {:>5} {:>5} {:>10} {:>10} {:>10} {:>21} {:>21}",
+                permission.key(),
+                segment.id(),
+                permission.mode(),
+                status.size,
+                pid_ns.local_id_or_zero(status.cpid),
+                pid_ns.local_id_or_zero(status.lpid),
+                status.nattch,
+                u32::from(permission.uid()),
+                u32::from(permission.gid()),
+                u32::from(permission.cuid()),
+                u32::from(permission.cguid()),
+                status.atime,
+                status.dtime,
+                status.ctime,
+                0,
+                0,
+            )
+            .unwrap();
+        }
+
+        Ok(output.into_bytes())
+    }
+}
@@ -8,7 +8,7 @@ use super::{
     sym::{ProcSym, SymOps},
 };
 use crate::{
-    fs::utils::{FileSystem, Inode},
+    fs::utils::{FileSystem, Inode, InodeMode},
     prelude::*,
 };
 
@@ -63,6 +63,7 @@ pub struct ProcFileBuilder<O: FileOps> {
     // Mandatory field
     file: O,
     // Optional fields
+    mode: InodeMode,
     optional_builder: Option<OptionalBuilder>,
 }
 
@@ -71,10 +72,20 @@ impl<O: FileOps> ProcFileBuilder<O> {
         let optional_builder: OptionalBuilder = Default::default();
         Self {
             file,
+            mode: InodeMode::from_bits_truncate(0o444),
             optional_builder: Some(optional_builder),
         }
     }
 
+    /// Sets the mode of the file.
+    ///
+    /// The default mode is read-only, i.e., `0o444`. The file must implement
+    /// [`FileOps::write_at`] if it is writable.
+    pub fn mode(mut self, mode: InodeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     pub fn parent(self, parent: Weak<dyn Inode>) -> Self {
         self.optional_builder(|ob| ob.parent(parent))
     }
@@ -83,9 +94,13 @@ impl<O: FileOps> ProcFileBuilder<O> {
         self.optional_builder(|ob| ob.volatile())
     }
 
+    pub fn ino(self, ino: u64) -> Self {
+        self.optional_builder(|ob| ob.ino(ino))
+    }
+
     pub fn build(mut self) -> Result<Arc<ProcFile<O>>> {
-        let (fs, _, _, is_volatile) = self.optional_builder.take().unwrap().build()?;
-        Ok(ProcFile::new(self.file, fs, is_volatile))
+        let (fs, _, ino, is_volatile) = self.optional_builder.take().unwrap().build()?;
+        Ok(ProcFile::new(self.file, fs, ino, is_volatile, self.mode))
     }
 
     fn optional_builder<F>(mut self, f: F) -> Self
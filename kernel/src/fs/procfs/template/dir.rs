@@ -7,7 +7,7 @@ use core::time::Duration;
 use aster_util::slot_vec::SlotVec;
 use inherit_methods_macro::inherit_methods;
 
-use super::{Common, ProcFS};
+use super::Common;
 use crate::{
     fs::utils::{DirentVisitor, FileSystem, Inode, InodeMode, InodeType, Metadata, MknodType},
     prelude::*,
@@ -33,8 +33,7 @@ impl<D: DirOps> ProcDir<D> {
         let common = {
             let ino = ino.unwrap_or_else(|| {
                 let arc_fs = fs.upgrade().unwrap();
-                let procfs = arc_fs.downcast_ref::<ProcFS>().unwrap();
-                procfs.alloc_id()
+                super::alloc_ino(&arc_fs)
             });
 
             let metadata =
@@ -61,6 +60,10 @@ impl<D: DirOps> ProcDir<D> {
     pub fn cached_children(&self) -> &RwMutex<SlotVec<(String, Arc<dyn Inode>)>> {
         &self.cached_children
     }
+
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
 }
 
 #[inherit_methods(from = "self.common")]
@@ -157,11 +160,15 @@ impl<D: DirOps + 'static> Inode for ProcDir<D> {
             ".." => self.parent().unwrap_or(self.this()),
             name => {
                 let mut cached_children = self.cached_children.write();
-                if let Some((_, inode)) = cached_children
-                    .iter()
-                    .find(|(child_name, inode)| child_name.as_str() == name)
-                {
-                    return Ok(inode.clone());
+                let cached_child = cached_children
+                    .idxes_and_items()
+                    .find(|(_, (child_name, _))| child_name.as_str() == name)
+                    .map(|(idx, (_, inode))| (idx, inode.clone()));
+                if let Some((idx, inode)) = cached_child {
+                    if self.inner.validate_child(name, inode.as_ref()) {
+                        return Ok(inode);
+                    }
+                    cached_children.remove(idx);
                 }
                 let inode = self.inner.lookup_child(self.this.clone(), name)?;
                 cached_children.put((String::from(name), inode.clone()));
@@ -186,4 +193,11 @@ pub trait DirOps: Sync + Send {
     }
 
     fn populate_children(&self, this_ptr: Weak<dyn Inode>) {}
+
+    /// Returns whether a cached child is still valid.
+    ///
+    /// An invalid child is dropped from the cache and looked up again.
+    fn validate_child(&self, name: &str, child: &dyn Inode) -> bool {
+        true
+    }
 }
@@ -5,12 +5,15 @@ use core::time::Duration;
 pub use self::{
     builder::{ProcDirBuilder, ProcFileBuilder, ProcSymBuilder},
     dir::{DirOps, ProcDir},
-    file::FileOps,
+    file::{FileOps, ProcFile},
     sym::SymOps,
 };
 use super::{ProcFS, BLOCK_SIZE};
 use crate::{
-    fs::utils::{FileSystem, InodeMode, InodeType, Metadata},
+    fs::{
+        sysfs::SysFS,
+        utils::{FileSystem, InodeMode, InodeType, Metadata},
+    },
     prelude::*,
     process::{Gid, Uid},
 };
@@ -20,6 +23,17 @@ mod dir;
 mod file;
 mod sym;
 
+/// Allocates an inode number in `fs`.
+///
+/// The templates are shared by the procfs and the sysfs, so `fs` must be one of
+/// them.
+fn alloc_ino(fs: &Arc<dyn FileSystem>) -> u64 {
+    if let Some(procfs) = fs.downcast_ref::<ProcFS>() {
+        return procfs.alloc_id();
+    }
+    fs.downcast_ref::<SysFS>().unwrap().alloc_id()
+}
+
 struct Common {
     metadata: RwLock<Metadata>,
     fs: Weak<dyn FileSystem>,
@@ -4,7 +4,7 @@ use core::time::Duration;
 
 use inherit_methods_macro::inherit_methods;
 
-use super::{Common, ProcFS};
+use super::Common;
 use crate::{
     fs::utils::{FileSystem, Inode, InodeMode, InodeType, IoctlCmd, Metadata},
     prelude::*,
@@ -20,9 +20,8 @@ impl<S: SymOps> ProcSym<S> {
     pub fn new(sym: S, fs: Weak<dyn FileSystem>, is_volatile: bool) -> Arc<Self> {
         let common = {
             let arc_fs = fs.upgrade().unwrap();
-            let procfs = arc_fs.downcast_ref::<ProcFS>().unwrap();
             let metadata = Metadata::new_symlink(
-                procfs.alloc_id(),
+                super::alloc_ino(&arc_fs),
                 InodeMode::from_bits_truncate(0o777),
                 super::BLOCK_SIZE,
             );
@@ -30,6 +29,10 @@ impl<S: SymOps> ProcSym<S> {
         };
         Arc::new(Self { inner: sym, common })
     }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
 }
 
 #[inherit_methods(from = "self.common")]
@@ -8,22 +8,29 @@ use crate::{
         utils::Inode,
     },
     prelude::*,
-    process::posix_thread::AsPosixThread,
+    process::{namespace::PidNamespace, posix_thread::AsPosixThread},
 };
 
 /// Represents the inode at `/proc/self-thread`.
-pub struct ThreadSelfSymOps;
+pub struct ThreadSelfSymOps(Arc<PidNamespace>);
 
 impl ThreadSelfSymOps {
-    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
-        ProcSymBuilder::new(Self).parent(parent).build().unwrap()
+    pub fn new_inode(pid_ns: Arc<PidNamespace>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcSymBuilder::new(Self(pid_ns))
+            .parent(parent)
+            .build()
+            .unwrap()
     }
 }
 
 impl SymOps for ThreadSelfSymOps {
     fn read_link(&self) -> Result<String> {
+        let pid_ns = &self.0;
         let pid = current!().pid();
         let tid = current_thread!().as_posix_thread().unwrap().tid();
+        let (Some(pid), Some(tid)) = (pid_ns.local_id(pid), pid_ns.local_id(tid)) else {
+            return_errno!(Errno::ENOENT);
+        };
         Ok(format!("{}/task/{}", pid, tid))
     }
 }
@@ -23,9 +23,10 @@ use crate::{
         file_handle::FileLike,
         named_pipe::NamedPipe,
         utils::{
-            CStr256, CachePage, DirentVisitor, Extension, FallocMode, FileSystem, FsFlags, Inode,
-            InodeMode, InodeType, IoctlCmd, Metadata, MknodType, PageCache, PageCacheBackend,
-            SuperBlock,
+            CStr256, CachePage, DirentVisitor, Extension, FallocMode, FileSealState, FileSeals,
+            FileSystem, FsFlags, Inode, InodeMode, InodeType, IoctlCmd, Metadata, MknodType,
+            PageCache, PageCacheBackend, SuperBlock, WritableMappingGuard, XattrMap, XattrName,
+            XattrSetFlags,
         },
     },
     prelude::*,
@@ -60,11 +61,28 @@ impl RamFS {
                 this: weak_root.clone(),
                 fs: weak_fs.clone(),
                 extension: Extension::new(),
+                seals: FileSealState::new(FileSeals::F_SEAL_SEAL),
+                xattrs: RwLock::new(XattrMap::new()),
             }),
             inode_allocator: AtomicU64::new(ROOT_INO + 1),
         })
     }
 
+    /// Creates a regular file that is not linked to any directory.
+    ///
+    /// The file can be sealed later unless `seals` contains `F_SEAL_SEAL`.
+    pub(super) fn new_unlinked_file(
+        self: &Arc<Self>,
+        mode: InodeMode,
+        uid: Uid,
+        gid: Gid,
+        seals: FileSeals,
+    ) -> Arc<dyn Inode> {
+        let inode = RamInode::new_file(self, mode, uid, gid, seals);
+        inode.metadata.lock().dec_nlinks();
+        inode
+    }
+
     fn alloc_id(&self) -> u64 {
         self.inode_allocator.fetch_add(1, Ordering::SeqCst)
     }
@@ -105,6 +123,10 @@ struct RamInode {
     fs: Weak<RamFS>,
     /// Extensions
     extension: Extension,
+    /// File seals
+    seals: FileSealState,
+    /// Extended attributes
+    xattrs: RwLock<XattrMap>,
 }
 
 /// Inode inner specifics.
@@ -399,10 +421,18 @@ impl RamInode {
             this: weak_self.clone(),
             fs: Arc::downgrade(fs),
             extension: Extension::new(),
+            seals: FileSealState::new(FileSeals::F_SEAL_SEAL),
+            xattrs: RwLock::new(XattrMap::new()),
         })
     }
 
-    fn new_file(fs: &Arc<RamFS>, mode: InodeMode, uid: Uid, gid: Gid) -> Arc<Self> {
+    fn new_file(
+        fs: &Arc<RamFS>,
+        mode: InodeMode,
+        uid: Uid,
+        gid: Gid,
+        seals: FileSeals,
+    ) -> Arc<Self> {
         Arc::new_cyclic(|weak_self| RamInode {
             inner: Inner::new_file(weak_self.clone()),
             metadata: SpinLock::new(InodeMeta::new(mode, uid, gid)),
@@ -411,6 +441,8 @@ impl RamInode {
             this: weak_self.clone(),
             fs: Arc::downgrade(fs),
             extension: Extension::new(),
+            seals: FileSealState::new(seals),
+            xattrs: RwLock::new(XattrMap::new()),
         })
     }
 
@@ -423,6 +455,8 @@ impl RamInode {
             this: weak_self.clone(),
             fs: Arc::downgrade(fs),
             extension: Extension::new(),
+            seals: FileSealState::new(FileSeals::F_SEAL_SEAL),
+            xattrs: RwLock::new(XattrMap::new()),
         })
     }
 
@@ -441,6 +475,8 @@ impl RamInode {
             this: weak_self.clone(),
             fs: Arc::downgrade(fs),
             extension: Extension::new(),
+            seals: FileSealState::new(FileSeals::F_SEAL_SEAL),
+            xattrs: RwLock::new(XattrMap::new()),
         })
     }
 
@@ -453,6 +489,8 @@ impl RamInode {
             this: weak_self.clone(),
             fs: Arc::downgrade(fs),
             extension: Extension::new(),
+            seals: FileSealState::new(FileSeals::F_SEAL_SEAL),
+            xattrs: RwLock::new(XattrMap::new()),
         })
     }
 
@@ -465,6 +503,8 @@ impl RamInode {
             this: weak_self.clone(),
             fs: Arc::downgrade(fs),
             extension: Extension::new(),
+            seals: FileSealState::new(FileSeals::F_SEAL_SEAL),
+            xattrs: RwLock::new(XattrMap::new()),
         })
     }
 
@@ -503,6 +543,14 @@ impl PageCacheBackend for RamInode {
     fn npages(&self) -> usize {
         self.metadata.lock().blocks
     }
+
+    fn allows_huge_pages(&self) -> bool {
+        true
+    }
+
+    fn allows_eviction(&self) -> bool {
+        false
+    }
 }
 
 impl Inode for RamInode {
@@ -554,6 +602,12 @@ impl Inode for RamInode {
                 let write_len = reader.remain();
                 let new_size = offset + write_len;
                 let should_expand_size = new_size > file_size;
+
+                self.seals.check_write()?;
+                if should_expand_size {
+                    self.seals.check_resize(file_size, new_size)?;
+                }
+
                 let new_size_aligned = new_size.align_up(BLOCK_SIZE);
                 if should_expand_size {
                     page_cache.resize(new_size_aligned)?;
@@ -602,6 +656,7 @@ impl Inode for RamInode {
         if file_size == new_size {
             return Ok(());
         }
+        self.seals.check_resize(file_size, new_size)?;
 
         let page_cache = self.inner.as_file().unwrap();
         page_cache.resize(new_size)?;
@@ -740,7 +795,13 @@ impl Inode for RamInode {
 
         let fs = self.fs.upgrade().unwrap();
         let new_inode = match type_ {
-            InodeType::File => RamInode::new_file(&fs, mode, Uid::new_root(), Gid::new_root()),
+            InodeType::File => RamInode::new_file(
+                &fs,
+                mode,
+                Uid::new_root(),
+                Gid::new_root(),
+                FileSeals::F_SEAL_SEAL,
+            ),
             InodeType::SymLink => {
                 RamInode::new_symlink(&fs, mode, Uid::new_root(), Gid::new_root())
             }
@@ -1160,6 +1221,7 @@ impl Inode for RamInode {
                 if offset >= file_size {
                     return Ok(());
                 }
+                self.seals.check_write()?;
                 let range = offset..file_size.min(offset + len);
                 // TODO: Think of a more light-weight approach
                 self.inner.as_file().unwrap().fill_zeros(range)
@@ -1173,6 +1235,27 @@ impl Inode for RamInode {
         }
     }
 
+    fn seals(&self) -> Result<FileSeals> {
+        if self.typ != InodeType::File {
+            return_errno_with_message!(Errno::EINVAL, "not regular file");
+        }
+        Ok(self.seals.get())
+    }
+
+    fn add_seals(&self, seals: FileSeals) -> Result<()> {
+        if self.typ != InodeType::File {
+            return_errno_with_message!(Errno::EINVAL, "not regular file");
+        }
+        self.seals.add(seals)
+    }
+
+    fn map_shared_writable(&self) -> Result<Option<WritableMappingGuard>> {
+        if self.typ != InodeType::File {
+            return Ok(None);
+        }
+        self.seals.map_shared_writable().map(Some)
+    }
+
     fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
         if let Some(device) = self.inner.as_device() {
             return device.ioctl(cmd, arg);
@@ -1187,6 +1270,26 @@ impl Inode for RamInode {
         )
     }
 
+    fn set_xattr(&self, name: XattrName, value: &[u8], flags: XattrSetFlags) -> Result<()> {
+        self.xattrs.write().set(name, value, flags)?;
+        self.metadata.lock().set_ctime(now());
+        Ok(())
+    }
+
+    fn get_xattr(&self, name: XattrName) -> Result<Vec<u8>> {
+        self.xattrs.read().get(name)
+    }
+
+    fn list_xattr(&self) -> Result<Vec<String>> {
+        Ok(self.xattrs.read().names())
+    }
+
+    fn remove_xattr(&self, name: XattrName) -> Result<()> {
+        self.xattrs.write().remove(name)?;
+        self.metadata.lock().set_ctime(now());
+        Ok(())
+    }
+
     fn extension(&self) -> Option<&Extension> {
         Some(&self.extension)
     }
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Anonymous files created by `memfd_create`.
+
+use spin::Once;
+
+use super::RamFS;
+use crate::{
+    fs::{
+        path::{Dentry, MountNode},
+        utils::{FileSeals, InodeMode},
+    },
+    prelude::*,
+    process::{Gid, Uid},
+};
+
+/// The internal file system that the memfd files belong to, and its root.
+static MEMFD_FS: Once<(Arc<RamFS>, Dentry)> = Once::new();
+
+/// Creates an anonymous file, which lives in memory until all references to it are dropped.
+///
+/// The file can be sealed only if `allow_sealing` is true.
+pub fn new_memfd(name: &str, allow_sealing: bool, uid: Uid, gid: Gid) -> Dentry {
+    let (fs, root) = MEMFD_FS.call_once(|| {
+        let fs = RamFS::new();
+        let root = Dentry::new_fs_root(MountNode::new_root(fs.clone()));
+        (fs, root)
+    });
+
+    let seals = if allow_sealing {
+        FileSeals::empty()
+    } else {
+        FileSeals::F_SEAL_SEAL
+    };
+    let inode = fs.new_unlinked_file(InodeMode::from_bits_truncate(0o777), uid, gid, seals);
+
+    // Linux reports the path of the file as `/memfd:<name> (deleted)`.
+    root.new_unlinked_child(&format!("memfd:{} (deleted)", name), inode)
+}
@@ -6,13 +6,15 @@ use libflate::gzip::Decoder as GZipDecoder;
 use spin::Once;
 
 use super::{
+    devtmpfs::devtmpfs,
     fs_resolver::{FsPath, FsResolver},
-    path::MountNode,
+    path::{MountNamespace, MountNode},
     procfs::{self, ProcFS},
     ramfs::RamFS,
+    sysfs::SysFS,
     utils::{FileSystem, InodeMode, InodeType},
 };
-use crate::prelude::*;
+use crate::{prelude::*, process::namespace::init_pid_ns};
 
 /// Unpack and prepare the rootfs from the initramfs CPIO buffer.
 pub fn init(initramfs_buf: &[u8]) -> Result<()> {
@@ -78,10 +80,13 @@ pub fn init(initramfs_buf: &[u8]) -> Result<()> {
     }
     // Mount ProcFS
     let proc_dentry = fs.lookup(&FsPath::try_from("/proc")?)?;
-    proc_dentry.mount(ProcFS::new())?;
-    // Mount DevFS
+    proc_dentry.mount(ProcFS::new(init_pid_ns().clone()))?;
+    // Mount SysFS
+    let sys_dentry = fs.lookup(&FsPath::try_from("/sys")?)?;
+    sys_dentry.mount(SysFS::new())?;
+    // Mount DevTmpFS
     let dev_dentry = fs.lookup(&FsPath::try_from("/dev")?)?;
-    dev_dentry.mount(RamFS::new())?;
+    dev_dentry.mount(devtmpfs().clone())?;
 
     println!("[kernel] rootfs is ready");
 
@@ -94,15 +99,21 @@ pub fn mount_fs_at(fs: Arc<dyn FileSystem>, fs_path: &FsPath) -> Result<()> {
     Ok(())
 }
 
-static ROOT_MOUNT: Once<Arc<MountNode>> = Once::new();
+static INIT_MNT_NS: Once<Arc<MountNamespace>> = Once::new();
 
 pub fn init_root_mount() {
-    ROOT_MOUNT.call_once(|| -> Arc<MountNode> {
+    INIT_MNT_NS.call_once(|| -> Arc<MountNamespace> {
         let rootfs = RamFS::new();
-        MountNode::new_root(rootfs)
+        MountNamespace::new(MountNode::new_root(rootfs))
     });
 }
 
+/// Gets the root mount node of the initial mount namespace.
 pub fn root_mount() -> &'static Arc<MountNode> {
-    ROOT_MOUNT.get().unwrap()
+    init_mnt_ns().root()
+}
+
+/// Gets the initial mount namespace, which the init process belongs to.
+pub fn init_mnt_ns() -> &'static Arc<MountNamespace> {
+    INIT_MNT_NS.get().unwrap()
 }
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The block devices, which are shown at `/sys/devices/virtual/block`.
+
+use aster_block::{BlockDevice, SECTOR_SIZE};
+
+use super::{Subsystem, SysDeviceBuilder};
+use crate::{
+    device::block::MINORS_PER_DISK,
+    fs::device::{DeviceId, DeviceType},
+    prelude::*,
+};
+
+/// Adds a disk, whose device node is `/dev/<name>`, and broadcasts an `add` uevent.
+pub fn add_disk(name: &str, block_device: &Arc<dyn BlockDevice>, id: DeviceId) -> Result<()> {
+    let metadata = block_device.metadata();
+
+    let device = SysDeviceBuilder::new(name, Subsystem::Class("block"))
+        .devtype("disk")
+        .dev(DeviceType::BlockDevice, id, name)
+        .build()?;
+
+    let kobject = device.kobject();
+    kobject.add_const_attr("size", metadata.nr_sectors)?;
+    kobject.add_const_attr("ro", 0)?;
+    kobject.add_const_attr("removable", 0)?;
+    kobject.add_const_attr("range", MINORS_PER_DISK)?;
+
+    let queue = kobject.create_child("queue")?;
+    queue.add_const_attr("logical_block_size", SECTOR_SIZE)?;
+    queue.add_const_attr("physical_block_size", SECTOR_SIZE)?;
+    queue.add_const_attr("hw_sector_size", SECTOR_SIZE)?;
+    queue.add_const_attr("max_segments", metadata.max_nr_segments_per_bio)?;
+    queue.add_const_attr("rotational", 0)?;
+
+    device.register()
+}
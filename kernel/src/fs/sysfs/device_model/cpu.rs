@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The CPU devices, which are shown at `/sys/devices/system/cpu`.
+
+use alloc::format;
+
+use ostd::cpu::num_cpus;
+
+use super::{Subsystem, SysDeviceBuilder};
+use crate::{fs::sysfs::kobject::kobject_at, prelude::*};
+
+pub(super) fn init() -> Result<()> {
+    let num_cpus = num_cpus();
+    let all_cpus = cpu_list(num_cpus);
+
+    let cpu_root = kobject_at("/devices/system/cpu")?;
+    cpu_root.add_const_attr("online", &all_cpus)?;
+    cpu_root.add_const_attr("possible", &all_cpus)?;
+    cpu_root.add_const_attr("present", &all_cpus)?;
+    cpu_root.add_const_attr("offline", "")?;
+    cpu_root.add_const_attr("kernel_max", num_cpus - 1)?;
+
+    for cpu_id in 0..num_cpus {
+        let device = SysDeviceBuilder::new(&format!("cpu{}", cpu_id), Subsystem::Bus("cpu"))
+            .parent(cpu_root.clone())
+            .build()?;
+        let kobject = device.kobject();
+        kobject.add_const_attr("online", 1)?;
+
+        // FIXME: The CPU topology is not detected, so each CPU is reported as a core with a
+        // single thread, and all the cores are in the same package.
+        let topology = kobject.create_child("topology")?;
+        topology.add_const_attr("physical_package_id", 0)?;
+        topology.add_const_attr("die_id", 0)?;
+        topology.add_const_attr("core_id", cpu_id)?;
+        topology.add_const_attr("core_cpus_list", cpu_id)?;
+        topology.add_const_attr("thread_siblings_list", cpu_id)?;
+        topology.add_const_attr("core_siblings_list", &all_cpus)?;
+        topology.add_const_attr("package_cpus_list", &all_cpus)?;
+
+        device.register()?;
+    }
+
+    Ok(())
+}
+
+/// Formats the list of the CPUs from zero to `num_cpus - 1`, e.g., `0-3`.
+fn cpu_list(num_cpus: usize) -> String {
+    if num_cpus == 1 {
+        "0".to_string()
+    } else {
+        format!("0-{}", num_cpus - 1)
+    }
+}
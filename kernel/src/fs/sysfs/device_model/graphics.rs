@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The framebuffer devices, which are shown at `/sys/devices/virtual/graphics`.
+
+use alloc::format;
+
+use aster_framebuffer::FrameBuffer;
+
+use super::{Subsystem, SysDeviceBuilder};
+use crate::{
+    fs::device::{DeviceId, DeviceType},
+    prelude::*,
+};
+
+/// Adds the framebuffer, whose device node is `/dev/<name>`, and broadcasts an `add` uevent.
+pub fn add_framebuffer(name: &str, framebuffer: &FrameBuffer, id: DeviceId) -> Result<()> {
+    let device = SysDeviceBuilder::new(name, Subsystem::Class("graphics"))
+        .dev(DeviceType::CharDevice, id, name)
+        .build()?;
+
+    let kobject = device.kobject();
+    kobject.add_const_attr("name", "simple")?;
+    kobject.add_const_attr(
+        "virtual_size",
+        format!("{},{}", framebuffer.width(), framebuffer.height()),
+    )?;
+    kobject.add_const_attr("bits_per_pixel", framebuffer.bpp())?;
+    kobject.add_const_attr("stride", framebuffer.width() * framebuffer.bpp() / 8)?;
+
+    device.register()
+}
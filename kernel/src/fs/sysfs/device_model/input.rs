@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The input devices, which are shown at `/sys/devices/virtual/input`.
+
+use alloc::format;
+
+use super::{Subsystem, SysDeviceBuilder};
+use crate::{
+    fs::device::{DeviceId, DeviceType},
+    prelude::*,
+};
+
+/// Adds the `index`-th input device and its event device, whose device number is `event_id`,
+/// and broadcasts the `add` uevents.
+pub fn add_input_device(index: usize, name: &str, event_id: DeviceId) -> Result<()> {
+    let device = SysDeviceBuilder::new(&format!("input{}", index), Subsystem::Class("input"))
+        .env("NAME", format!("\"{}\"", name))
+        .build()?;
+
+    device.kobject().add_const_attr("name", name)?;
+
+    device.register()?;
+
+    let event_name = format!("event{}", index);
+    let event_device = SysDeviceBuilder::new(&event_name, Subsystem::Class("input"))
+        .parent(device.kobject().clone())
+        .dev(
+            DeviceType::CharDevice,
+            event_id,
+            &format!("input/{}", event_name),
+        )
+        .build()?;
+
+    event_device.register()
+}
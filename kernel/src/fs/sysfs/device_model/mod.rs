@@ -0,0 +1,327 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The device model.
+//!
+//! Each device is a kobject under `/sys/devices`. A device belongs to a subsystem, which is
+//! either a class (e.g., `net`) or a bus (e.g., `pci`), and is linked from the directory of the
+//! subsystem. A device that has a device number is also linked from `/sys/dev`.
+//!
+//! Registering or unregistering a device broadcasts a uevent, which user programs (e.g., udev)
+//! receive with `NETLINK_KOBJECT_UEVENT` sockets.
+
+use alloc::format;
+
+pub use self::{
+    block::add_disk, graphics::add_framebuffer, input::add_input_device, uevent::UeventAction,
+};
+use super::kobject::{kobject_at, root_kobject, Attribute, Kobject};
+use crate::{
+    fs::{
+        device::{DeviceId, DeviceType},
+        utils::InodeMode,
+    },
+    prelude::*,
+};
+
+mod block;
+mod cpu;
+mod graphics;
+mod input;
+mod net;
+mod pci;
+mod uevent;
+
+/// Registers the devices that have been found.
+///
+/// The devices that have device nodes are added by their drivers in [`crate::device`] instead,
+/// since they may be registered at any time.
+pub(super) fn init() -> Result<()> {
+    cpu::init()?;
+    pci::init()?;
+    net::init()?;
+    Ok(())
+}
+
+/// The subsystem that a device belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subsystem {
+    /// A device class, whose devices are linked from `/sys/class/<name>`.
+    Class(&'static str),
+    /// A bus, whose devices are linked from `/sys/bus/<name>/devices`.
+    Bus(&'static str),
+}
+
+impl Subsystem {
+    /// Returns the name of the subsystem.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Class(name) | Self::Bus(name) => name,
+        }
+    }
+
+    /// Returns the kobject of the subsystem.
+    fn kobject(&self) -> Result<Arc<Kobject>> {
+        match self {
+            Self::Class(name) => kobject_at(&format!("/class/{}", name)),
+            Self::Bus(name) => {
+                let bus = kobject_at(&format!("/bus/{}", name))?;
+                bus.child_or_create("drivers")?;
+                Ok(bus)
+            }
+        }
+    }
+
+    /// Returns the kobject that links to the devices in the subsystem.
+    fn devices_kobject(&self) -> Result<Arc<Kobject>> {
+        match self {
+            Self::Class(_) => self.kobject(),
+            Self::Bus(_) => self.kobject()?.child_or_create("devices"),
+        }
+    }
+}
+
+/// Returns the parent kobject of the virtual devices in the class, i.e.,
+/// `/sys/devices/virtual/<class>`.
+///
+/// Virtual devices are not attached to any bus.
+pub fn virtual_devices_kobject(class: &str) -> Result<Arc<Kobject>> {
+    kobject_at(&format!("/devices/virtual/{}", class))
+}
+
+/// A device in the device model.
+pub struct SysDevice {
+    kobject: Arc<Kobject>,
+    subsystem: Subsystem,
+    devtype: Option<&'static str>,
+    dev: Option<(DeviceType, DeviceId)>,
+    devname: Option<String>,
+    env: Vec<(&'static str, String)>,
+}
+
+/// A builder of [`SysDevice`].
+pub struct SysDeviceBuilder {
+    name: String,
+    subsystem: Subsystem,
+    parent: Option<Arc<Kobject>>,
+    devtype: Option<&'static str>,
+    dev: Option<(DeviceType, DeviceId)>,
+    devname: Option<String>,
+    env: Vec<(&'static str, String)>,
+}
+
+impl SysDeviceBuilder {
+    pub fn new(name: &str, subsystem: Subsystem) -> Self {
+        Self {
+            name: name.to_string(),
+            subsystem,
+            parent: None,
+            devtype: None,
+            dev: None,
+            devname: None,
+            env: Vec::new(),
+        }
+    }
+
+    /// Sets the parent kobject.
+    ///
+    /// The default parent is [`virtual_devices_kobject`] of the subsystem.
+    pub fn parent(mut self, parent: Arc<Kobject>) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Sets the type of the device within its subsystem, e.g., `disk` for block devices.
+    pub fn devtype(mut self, devtype: &'static str) -> Self {
+        self.devtype = Some(devtype);
+        self
+    }
+
+    /// Sets the device number and the name of the device node under `/dev`.
+    pub fn dev(mut self, type_: DeviceType, id: DeviceId, devname: &str) -> Self {
+        self.dev = Some((type_, id));
+        self.devname = Some(devname.to_string());
+        self
+    }
+
+    /// Adds an environment variable that is specific to the subsystem to the uevents.
+    pub fn env(mut self, key: &'static str, value: String) -> Self {
+        self.env.push((key, value));
+        self
+    }
+
+    /// Creates the kobject of the device with the standard attributes.
+    ///
+    /// The device is not visible to its subsystem until [`SysDevice::register`] is called, so
+    /// that the attributes specific to the device can be added in between.
+    pub fn build(self) -> Result<Arc<SysDevice>> {
+        let parent = match self.parent {
+            Some(parent) => parent,
+            None => virtual_devices_kobject(self.subsystem.name())?,
+        };
+        let kobject = parent.create_child(&self.name)?;
+
+        let device = Arc::new(SysDevice {
+            kobject,
+            subsystem: self.subsystem,
+            devtype: self.devtype,
+            dev: self.dev,
+            devname: self.devname,
+            env: self.env,
+        });
+
+        let kobject = &device.kobject;
+        kobject.add_attr(
+            "uevent",
+            Arc::new(UeventAttr {
+                device: device.clone(),
+            }),
+        )?;
+        kobject.add_link("subsystem", &device.subsystem.kobject()?)?;
+        if let Some((_, id)) = device.dev {
+            kobject.add_const_attr("dev", format!("{}:{}", id.major(), id.minor()))?;
+        }
+
+        Ok(device)
+    }
+}
+
+impl SysDevice {
+    /// Returns the kobject.
+    pub fn kobject(&self) -> &Arc<Kobject> {
+        &self.kobject
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &str {
+        self.kobject.name()
+    }
+
+    /// Makes the device visible to its subsystem and broadcasts an `add` uevent.
+    pub fn register(&self) -> Result<()> {
+        self.subsystem
+            .devices_kobject()?
+            .add_link(self.name(), &self.kobject)?;
+        if let Some(dev_kobject) = self.dev_kobject() {
+            dev_kobject.add_link(&self.dev_link_name(), &self.kobject)?;
+        }
+        if self.is_disk() {
+            root_kobject()
+                .child_or_create("block")?
+                .add_link(self.name(), &self.kobject)?;
+        }
+
+        self.send_uevent(UeventAction::Add);
+        Ok(())
+    }
+
+    /// Removes the device and broadcasts a `remove` uevent.
+    pub fn unregister(&self) -> Result<()> {
+        // The path should be recorded before the kobject is removed.
+        let devpath = self.kobject.path();
+
+        if let Ok(devices_kobject) = self.subsystem.devices_kobject() {
+            devices_kobject.remove_entry(self.name());
+        }
+        if let Some(dev_kobject) = self.dev_kobject() {
+            dev_kobject.remove_entry(&self.dev_link_name());
+        }
+        if self.is_disk() {
+            if let Ok(block_kobject) = root_kobject().child_or_create("block") {
+                block_kobject.remove_entry(self.name());
+            }
+        }
+        if let Some(parent) = self.kobject.parent() {
+            parent.remove_entry(self.name());
+        }
+
+        uevent::send_uevent(
+            UeventAction::Remove,
+            &devpath,
+            self.subsystem.name(),
+            &self.uevent_env(),
+        );
+        Ok(())
+    }
+
+    /// Broadcasts a uevent about the device.
+    pub fn send_uevent(&self, action: UeventAction) {
+        uevent::send_uevent(
+            action,
+            &self.kobject.path(),
+            self.subsystem.name(),
+            &self.uevent_env(),
+        );
+    }
+
+    /// Returns the environment variables of the uevents, except for those that are common to all
+    /// kobjects.
+    ///
+    /// These are also the content of the `uevent` attribute.
+    fn uevent_env(&self) -> Vec<(&'static str, String)> {
+        let mut env = Vec::new();
+        if let Some((_, id)) = self.dev {
+            env.push(("MAJOR", id.major().to_string()));
+            env.push(("MINOR", id.minor().to_string()));
+        }
+        if let Some(devname) = self.devname.as_ref() {
+            env.push(("DEVNAME", devname.clone()));
+        }
+        if let Some(devtype) = self.devtype {
+            env.push(("DEVTYPE", devtype.to_string()));
+        }
+        env.extend(self.env.iter().cloned());
+        env
+    }
+
+    fn dev_kobject(&self) -> Option<Arc<Kobject>> {
+        let name = match self.dev? {
+            (DeviceType::BlockDevice, _) => "block",
+            (DeviceType::CharDevice | DeviceType::MiscDevice, _) => "char",
+        };
+        kobject_at(&format!("/dev/{}", name)).ok()
+    }
+
+    fn dev_link_name(&self) -> String {
+        let (_, id) = self.dev.unwrap();
+        format!("{}:{}", id.major(), id.minor())
+    }
+
+    fn is_disk(&self) -> bool {
+        self.subsystem == Subsystem::Class("block") && self.devtype == Some("disk")
+    }
+}
+
+/// The `uevent` attribute of a device.
+///
+/// Reading the attribute shows the environment variables of the uevents. Writing an action to
+/// the attribute broadcasts a synthetic uevent, which is how udev replays the devices at boot.
+struct UeventAttr {
+    device: Arc<SysDevice>,
+}
+
+impl Attribute for UeventAttr {
+    fn show(&self) -> Result<Vec<u8>> {
+        let mut content = String::new();
+        for (key, value) in self.device.uevent_env() {
+            content.push_str(&format!("{}={}\n", key, value));
+        }
+        Ok(content.into_bytes())
+    }
+
+    fn store(&self, buf: &[u8]) -> Result<()> {
+        let buf = core::str::from_utf8(buf)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the uevent action is not UTF-8"))?;
+        // The action may be followed by arguments, which are not supported.
+        let action = buf
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .parse::<UeventAction>()?;
+        self.device.send_uevent(action);
+        Ok(())
+    }
+
+    fn mode(&self) -> InodeMode {
+        InodeMode::from_bits_truncate(0o644)
+    }
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The network interfaces, which are shown at `/sys/devices/virtual/net`.
+//!
+//! The index of an iface is its position in [`IFACES`] plus one, which is consistent with the
+//! indexes reported by `NETLINK_ROUTE` sockets.
+
+use alloc::format;
+
+use aster_bigtcp::iface::InterfaceType;
+
+use super::{Subsystem, SysDeviceBuilder};
+use crate::{net::iface::IFACES, prelude::*};
+
+/// The hardware type of Ethernet ifaces.
+const ARPHRD_ETHER: u16 = 1;
+/// The hardware type of loopback ifaces.
+const ARPHRD_LOOPBACK: u16 = 772;
+
+const IFF_UP: u32 = 0x1;
+const IFF_BROADCAST: u32 = 0x2;
+const IFF_LOOPBACK: u32 = 0x8;
+const IFF_MULTICAST: u32 = 0x1000;
+
+pub(super) fn init() -> Result<()> {
+    let Some(ifaces) = IFACES.get() else {
+        return Ok(());
+    };
+
+    for (pos, iface) in ifaces.iter().enumerate() {
+        let index = pos + 1;
+        let name = iface.name();
+
+        // FIXME: The loopback iface is the only IP iface for now.
+        let (type_, flags, operstate, hw_addr, broadcast) = match iface.type_() {
+            InterfaceType::Ethernet(ether_addr) => (
+                ARPHRD_ETHER,
+                IFF_BROADCAST | IFF_MULTICAST,
+                "up",
+                ether_addr.0,
+                [0xff; 6],
+            ),
+            InterfaceType::Ip => (ARPHRD_LOOPBACK, IFF_LOOPBACK, "unknown", [0; 6], [0; 6]),
+        };
+        // All ifaces are always up.
+        let flags = flags | IFF_UP;
+
+        let device = SysDeviceBuilder::new(name, Subsystem::Class("net"))
+            .env("INTERFACE", name.to_string())
+            .env("IFINDEX", index.to_string())
+            .build()?;
+
+        let kobject = device.kobject();
+        kobject.add_const_attr("ifindex", index)?;
+        kobject.add_const_attr("iflink", index)?;
+        kobject.add_const_attr("type", type_)?;
+        kobject.add_const_attr("flags", format!("{:#x}", flags))?;
+        kobject.add_const_attr("operstate", operstate)?;
+        kobject.add_const_attr("carrier", 1)?;
+        kobject.add_const_attr("address", format_hw_addr(&hw_addr))?;
+        kobject.add_const_attr("broadcast", format_hw_addr(&broadcast))?;
+        kobject.add_const_attr("addr_len", hw_addr.len())?;
+        kobject.add_const_attr("tx_queue_len", 1000)?;
+        let iface = iface.clone();
+        kobject.add_show_attr("mtu", move || iface.mtu())?;
+
+        device.register()?;
+    }
+
+    Ok(())
+}
+
+fn format_hw_addr(hw_addr: &[u8; 6]) -> String {
+    hw_addr
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The PCI devices, which are shown at `/sys/devices/pci0000:00`.
+
+use alloc::format;
+
+use ostd::bus::pci::{PciDeviceId, PciDeviceLocation, PCI_BUS};
+
+use super::{Subsystem, SysDeviceBuilder};
+use crate::{
+    fs::sysfs::kobject::{kobject_at, Attribute},
+    prelude::*,
+};
+
+/// The size of the configuration space that is exported by the `config` attribute.
+///
+/// This is the standard configuration space. The extended configuration space cannot be
+/// accessed with the I/O ports.
+const CONFIG_SPACE_SIZE: u16 = 256;
+
+pub(super) fn init() -> Result<()> {
+    let device_infos = PCI_BUS.lock().device_infos().to_vec();
+    if device_infos.is_empty() {
+        return Ok(());
+    }
+
+    // FIXME: The devices behind PCI bridges should be the children of the bridges. Since the
+    // bridges are not parsed, all the devices are placed under the root bus for now.
+    let root_bus = kobject_at("/devices/pci0000:00")?;
+
+    for (location, id) in device_infos {
+        let slot_name = format!(
+            "0000:{:02x}:{:02x}.{:x}",
+            location.bus, location.device, location.function
+        );
+        let class = (id.class as u32) << 16 | (id.subclass as u32) << 8 | id.prog_if as u32;
+
+        let device = SysDeviceBuilder::new(&slot_name, Subsystem::Bus("pci"))
+            .parent(root_bus.clone())
+            .env("PCI_CLASS", format!("{:X}", class))
+            .env(
+                "PCI_ID",
+                format!("{:04X}:{:04X}", id.vendor_id, id.device_id),
+            )
+            .env(
+                "PCI_SUBSYS_ID",
+                format!("{:04X}:{:04X}", id.subsystem_vendor_id, id.subsystem_id),
+            )
+            .env("PCI_SLOT_NAME", slot_name.clone())
+            .env("MODALIAS", modalias(&id))
+            .build()?;
+
+        let kobject = device.kobject();
+        kobject.add_const_attr("vendor", format!("{:#06x}", id.vendor_id))?;
+        kobject.add_const_attr("device", format!("{:#06x}", id.device_id))?;
+        kobject.add_const_attr(
+            "subsystem_vendor",
+            format!("{:#06x}", id.subsystem_vendor_id),
+        )?;
+        kobject.add_const_attr("subsystem_device", format!("{:#06x}", id.subsystem_id))?;
+        kobject.add_const_attr("class", format!("{:#08x}", class))?;
+        kobject.add_const_attr("revision", format!("{:#04x}", id.revision_id))?;
+        kobject.add_const_attr("modalias", modalias(&id))?;
+        kobject.add_attr("config", Arc::new(ConfigAttr { location }))?;
+
+        device.register()?;
+    }
+
+    Ok(())
+}
+
+/// Returns the module alias, which is used to find the driver of the device.
+fn modalias(id: &PciDeviceId) -> String {
+    format!(
+        "pci:v{:08X}d{:08X}sv{:08X}sd{:08X}bc{:02X}sc{:02X}i{:02X}",
+        id.vendor_id,
+        id.device_id,
+        id.subsystem_vendor_id,
+        id.subsystem_id,
+        id.class,
+        id.subclass,
+        id.prog_if
+    )
+}
+
+/// The `config` attribute, which contains the configuration space of the device.
+struct ConfigAttr {
+    location: PciDeviceLocation,
+}
+
+impl Attribute for ConfigAttr {
+    fn show(&self) -> Result<Vec<u8>> {
+        let mut config = Vec::with_capacity(CONFIG_SPACE_SIZE as usize);
+        for offset in (0..CONFIG_SPACE_SIZE).step_by(4) {
+            let value = self.location.read_config32(offset);
+            config.extend_from_slice(&value.to_le_bytes());
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Uevents, which notify user space of the changes of devices.
+//!
+//! A uevent is a datagram that starts with `<action>@<devpath>`, followed by the environment
+//! variables in the form of `KEY=value`. All the parts are terminated by NUL characters. See
+//! <https://www.kernel.org/doc/html/latest/core-api/kobject.html#uevents> for details.
+
+use alloc::format;
+use core::{
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{net::socket::netlink::broadcast_uevent, prelude::*};
+
+/// The sequence number of the last uevent.
+static SEQNUM: AtomicU64 = AtomicU64::new(0);
+
+/// The action of a uevent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UeventAction {
+    Add,
+    Remove,
+    Change,
+    Move,
+    Online,
+    Offline,
+    Bind,
+    Unbind,
+}
+
+impl UeventAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Remove => "remove",
+            Self::Change => "change",
+            Self::Move => "move",
+            Self::Online => "online",
+            Self::Offline => "offline",
+            Self::Bind => "bind",
+            Self::Unbind => "unbind",
+        }
+    }
+}
+
+impl FromStr for UeventAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let action = match s {
+            "add" => Self::Add,
+            "remove" => Self::Remove,
+            "change" => Self::Change,
+            "move" => Self::Move,
+            "online" => Self::Online,
+            "offline" => Self::Offline,
+            "bind" => Self::Bind,
+            "unbind" => Self::Unbind,
+            _ => return_errno_with_message!(Errno::EINVAL, "the uevent action is invalid"),
+        };
+        Ok(action)
+    }
+}
+
+/// Broadcasts a uevent about the kobject at `devpath`.
+///
+/// `env` contains the environment variables other than `ACTION`, `DEVPATH`, `SUBSYSTEM`, and
+/// `SEQNUM`, which are added here.
+pub(super) fn send_uevent(
+    action: UeventAction,
+    devpath: &str,
+    subsystem: &str,
+    env: &[(&'static str, String)],
+) {
+    let seqnum = SEQNUM.fetch_add(1, Ordering::Relaxed) + 1;
+
+    let mut message = format!("{}@{}\0", action.as_str(), devpath);
+    message.push_str(&format!("ACTION={}\0", action.as_str()));
+    message.push_str(&format!("DEVPATH={}\0", devpath));
+    message.push_str(&format!("SUBSYSTEM={}\0", subsystem));
+    for (key, value) in env {
+        message.push_str(&format!("{}={}\0", key, value));
+    }
+    message.push_str(&format!("SEQNUM={}\0", seqnum));
+
+    broadcast_uevent(message.as_bytes());
+}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        procfs::template::{DirOps, ProcDir, ProcDirBuilder},
+        sysfs::kernel::mm::transparent_hugepage::TransparentHugepageDirOps,
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+};
+
+mod transparent_hugepage;
+
+/// Represents the inode at `/sys/kernel/mm`.
+pub struct MmDirOps;
+
+impl MmDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for MmDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "transparent_hugepage" => TransparentHugepageDirOps::new_inode(this_ptr.clone()),
+            _ => return_errno!(Errno::ENOENT),
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<MmDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children.put_entry_if_not_found("transparent_hugepage", || {
+            TransparentHugepageDirOps::new_inode(this_ptr.clone())
+        });
+    }
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{DirOps, FileOps, ProcDir, ProcDirBuilder, ProcFileBuilder},
+        utils::{DirEntryVecExt, Inode, InodeMode},
+    },
+    prelude::*,
+    vm::thp::{set_thp_mode, thp_mode, ThpMode},
+};
+
+/// Represents the inode at `/sys/kernel/mm/transparent_hugepage`.
+pub struct TransparentHugepageDirOps;
+
+impl TransparentHugepageDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for TransparentHugepageDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "enabled" => EnabledFileOps::new_inode(this_ptr.clone()),
+            _ => return_errno!(Errno::ENOENT),
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<TransparentHugepageDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children
+            .put_entry_if_not_found("enabled", || EnabledFileOps::new_inode(this_ptr.clone()));
+    }
+}
+
+/// Represents the inode at `/sys/kernel/mm/transparent_hugepage/enabled`.
+struct EnabledFileOps;
+
+impl EnabledFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self)
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for EnabledFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let output = format!("{}\n", thp_mode());
+        Ok(output.into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let len = reader.remain();
+        let mut buf = vec![0u8; len];
+        reader.read_fallible(&mut VmWriter::from(buf.as_mut_slice()))?;
+
+        let mode = core::str::from_utf8(&buf)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the THP mode is not UTF-8"))?;
+        let mode = mode.strip_suffix('\n').unwrap_or(mode).parse::<ThpMode>()?;
+        set_thp_mode(mode);
+
+        Ok(len)
+    }
+}
@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Kernel objects.
+//!
+//! A kernel object (kobject) is a node in the hierarchy exported by the sysfs. Each kobject
+//! appears as a directory, which contains its attributes as files, its children as
+//! subdirectories, and links to other kobjects as symbolic links.
+//!
+//! The hierarchy is global, so all the mounted sysfs instances show the same kobjects.
+
+use alloc::format;
+use core::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use spin::Once;
+
+use crate::{fs::utils::InodeMode, prelude::*};
+
+/// A kernel object.
+pub struct Kobject {
+    name: String,
+    parent: Option<Weak<Kobject>>,
+    entries: RwLock<BTreeMap<String, KobjectEntry>>,
+    is_removed: AtomicBool,
+}
+
+/// An entry in the directory of a [`Kobject`].
+#[derive(Clone)]
+pub enum KobjectEntry {
+    /// A child kobject.
+    Object(Arc<Kobject>),
+    /// An attribute.
+    Attr(Arc<dyn Attribute>),
+    /// A symbolic link to another kobject.
+    Link(Weak<Kobject>),
+}
+
+impl KobjectEntry {
+    /// Returns whether the two entries refer to the same object.
+    pub fn ptr_eq(&self, other: &KobjectEntry) -> bool {
+        match (self, other) {
+            (Self::Object(this), Self::Object(other)) => Arc::ptr_eq(this, other),
+            (Self::Attr(this), Self::Attr(other)) => Arc::ptr_eq(this, other),
+            (Self::Link(this), Self::Link(other)) => Weak::ptr_eq(this, other),
+            _ => false,
+        }
+    }
+}
+
+impl Kobject {
+    fn new(name: String, parent: Option<Weak<Kobject>>) -> Arc<Self> {
+        Arc::new(Self {
+            name,
+            parent,
+            entries: RwLock::new(BTreeMap::new()),
+            is_removed: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the parent, or `None` if it is the root.
+    pub fn parent(&self) -> Option<Arc<Kobject>> {
+        self.parent.as_ref().and_then(|parent| parent.upgrade())
+    }
+
+    /// Returns the path relative to the root of the sysfs, e.g., `/devices/system/cpu`.
+    ///
+    /// The path of the root is empty.
+    pub fn path(&self) -> String {
+        match self.parent() {
+            Some(parent) => format!("{}/{}", parent.path(), self.name),
+            None => String::new(),
+        }
+    }
+
+    /// Returns whether the kobject has been removed from its parent.
+    pub fn is_removed(&self) -> bool {
+        self.is_removed.load(Ordering::Relaxed)
+    }
+
+    /// Creates a child kobject.
+    pub fn create_child(self: &Arc<Self>, name: &str) -> Result<Arc<Kobject>> {
+        let mut entries = self.entries.write();
+        if entries.contains_key(name) {
+            return_errno_with_message!(Errno::EEXIST, "the kobject entry already exists");
+        }
+        let child = Kobject::new(name.to_string(), Some(Arc::downgrade(self)));
+        entries.insert(name.to_string(), KobjectEntry::Object(child.clone()));
+        Ok(child)
+    }
+
+    /// Returns the child kobject with `name`, creating it if it does not exist.
+    pub fn child_or_create(self: &Arc<Self>, name: &str) -> Result<Arc<Kobject>> {
+        let mut entries = self.entries.write();
+        match entries.get(name) {
+            Some(KobjectEntry::Object(child)) => Ok(child.clone()),
+            Some(_) => {
+                return_errno_with_message!(Errno::ENOTDIR, "the kobject entry is not a kobject")
+            }
+            None => {
+                let child = Kobject::new(name.to_string(), Some(Arc::downgrade(self)));
+                entries.insert(name.to_string(), KobjectEntry::Object(child.clone()));
+                Ok(child)
+            }
+        }
+    }
+
+    /// Adds an attribute.
+    pub fn add_attr(&self, name: &str, attr: Arc<dyn Attribute>) -> Result<()> {
+        self.add_entry(name, KobjectEntry::Attr(attr))
+    }
+
+    /// Adds a read-only attribute whose value never changes.
+    pub fn add_const_attr(&self, name: &str, value: impl Display) -> Result<()> {
+        let content = format!("{}\n", value).into_bytes();
+        self.add_attr(name, Arc::new(ShowAttr::new(move || content.clone())))
+    }
+
+    /// Adds a read-only attribute whose value is formatted on each read.
+    pub fn add_show_attr<F, T>(&self, name: &str, show: F) -> Result<()>
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: Display,
+    {
+        self.add_attr(
+            name,
+            Arc::new(ShowAttr::new(move || format!("{}\n", show()).into_bytes())),
+        )
+    }
+
+    /// Adds a symbolic link to `target`.
+    pub fn add_link(&self, name: &str, target: &Arc<Kobject>) -> Result<()> {
+        self.add_entry(name, KobjectEntry::Link(Arc::downgrade(target)))
+    }
+
+    fn add_entry(&self, name: &str, entry: KobjectEntry) -> Result<()> {
+        let mut entries = self.entries.write();
+        if entries.contains_key(name) {
+            return_errno_with_message!(Errno::EEXIST, "the kobject entry already exists");
+        }
+        entries.insert(name.to_string(), entry);
+        Ok(())
+    }
+
+    /// Removes an entry.
+    ///
+    /// If the entry is a child kobject, the child and all its descendants are marked as removed.
+    pub fn remove_entry(&self, name: &str) -> Option<KobjectEntry> {
+        let entry = self.entries.write().remove(name)?;
+        if let KobjectEntry::Object(child) = &entry {
+            child.mark_removed();
+        }
+        Some(entry)
+    }
+
+    fn mark_removed(&self) {
+        self.is_removed.store(true, Ordering::Relaxed);
+        let entries = core::mem::take(&mut *self.entries.write());
+        for entry in entries.into_values() {
+            if let KobjectEntry::Object(child) = entry {
+                child.mark_removed();
+            }
+        }
+    }
+
+    /// Returns the entry with `name`.
+    pub fn entry(&self, name: &str) -> Option<KobjectEntry> {
+        self.entries.read().get(name).cloned()
+    }
+
+    /// Returns all the entries, sorted by their names.
+    pub fn entries(&self) -> Vec<(String, KobjectEntry)> {
+        self.entries
+            .read()
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.clone()))
+            .collect()
+    }
+
+    /// Returns the relative path from the directory of `self` to `target`.
+    ///
+    /// This is the content of a symbolic link in `self` that points to `target`.
+    pub fn relative_path_to(&self, target: &Kobject) -> String {
+        let from = self.path();
+        let to = target.path();
+        let from: Vec<&str> = from.split('/').filter(|name| !name.is_empty()).collect();
+        let to: Vec<&str> = to.split('/').filter(|name| !name.is_empty()).collect();
+
+        let common_len = from
+            .iter()
+            .zip(to.iter())
+            .take_while(|(from, to)| from == to)
+            .count();
+        let mut components = vec![".."; from.len() - common_len];
+        components.extend_from_slice(&to[common_len..]);
+        components.join("/")
+    }
+}
+
+/// An attribute of a kobject, which appears as a file in the sysfs.
+pub trait Attribute: Send + Sync {
+    /// Returns the content of the attribute.
+    fn show(&self) -> Result<Vec<u8>>;
+
+    /// Writes the attribute.
+    fn store(&self, _buf: &[u8]) -> Result<()> {
+        return_errno_with_message!(Errno::EACCES, "the attribute is read-only");
+    }
+
+    /// Returns the file mode of the attribute.
+    fn mode(&self) -> InodeMode {
+        InodeMode::from_bits_truncate(0o444)
+    }
+}
+
+/// A read-only attribute whose content is produced by a closure.
+struct ShowAttr<F> {
+    show: F,
+}
+
+impl<F> ShowAttr<F> {
+    fn new(show: F) -> Self {
+        Self { show }
+    }
+}
+
+impl<F: Fn() -> Vec<u8> + Send + Sync> Attribute for ShowAttr<F> {
+    fn show(&self) -> Result<Vec<u8>> {
+        Ok((self.show)())
+    }
+}
+
+/// Returns the root kobject, which is shown at `/sys`.
+pub fn root_kobject() -> &'static Arc<Kobject> {
+    static ROOT: Once<Arc<Kobject>> = Once::new();
+
+    ROOT.call_once(|| {
+        let root = Kobject::new(String::new(), None);
+        for name in ["block", "bus", "class", "dev", "devices"] {
+            root.create_child(name).unwrap();
+        }
+        let dev = root.child_or_create("dev").unwrap();
+        dev.create_child("block").unwrap();
+        dev.create_child("char").unwrap();
+        root
+    })
+}
+
+/// Returns the kobject at `path`, which is relative to the root of the sysfs.
+///
+/// The missing kobjects along the path are created.
+pub fn kobject_at(path: &str) -> Result<Arc<Kobject>> {
+    let mut kobject = root_kobject().clone();
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        kobject = kobject.child_or_create(name)?;
+    }
+    Ok(kobject)
+}
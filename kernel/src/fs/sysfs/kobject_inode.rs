@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The inodes that show kobjects, their attributes and their links.
+
+use super::kobject::{Attribute, Kobject, KobjectEntry};
+use crate::{
+    fs::{
+        procfs::template::{
+            DirOps, FileOps, ProcDir, ProcDirBuilder, ProcFile, ProcFileBuilder, ProcSym,
+            ProcSymBuilder, SymOps,
+        },
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+};
+
+/// Creates the inode of `entry`, which is an entry in the directory of `dir`.
+pub(super) fn new_entry_inode(
+    dir: &Arc<Kobject>,
+    entry: KobjectEntry,
+    parent: Weak<dyn Inode>,
+) -> Arc<dyn Inode> {
+    match entry {
+        KobjectEntry::Object(kobject) => KobjectDirOps::new_inode(kobject, parent),
+        KobjectEntry::Attr(attr) => AttrFileOps::new_inode(attr, parent),
+        KobjectEntry::Link(target) => LinkSymOps::new_inode(dir.clone(), target, parent),
+    }
+}
+
+/// Returns the kobject entry that `inode` shows.
+fn entry_of_inode(inode: &dyn Inode) -> Option<KobjectEntry> {
+    if let Some(dir) = inode.downcast_ref::<ProcDir<KobjectDirOps>>() {
+        return Some(KobjectEntry::Object(dir.inner().kobject.clone()));
+    }
+    if let Some(file) = inode.downcast_ref::<ProcFile<AttrFileOps>>() {
+        return Some(KobjectEntry::Attr(file.inner().attr.clone()));
+    }
+    if let Some(sym) = inode.downcast_ref::<ProcSym<LinkSymOps>>() {
+        return Some(KobjectEntry::Link(sym.inner().target.clone()));
+    }
+    None
+}
+
+/// Adds the entries of `kobject` to the cached children of `dir`.
+///
+/// The cached children that no longer match the entries are dropped.
+pub(super) fn populate_kobject_entries<D: DirOps + 'static>(
+    dir: &ProcDir<D>,
+    kobject: &Arc<Kobject>,
+    this_ptr: Weak<dyn Inode>,
+) {
+    let entries = kobject.entries();
+    let mut cached_children = dir.cached_children().write();
+
+    let stale_names: Vec<String> = cached_children
+        .iter()
+        .filter(|(name, inode)| {
+            entry_of_inode(inode.as_ref()).is_some()
+                && !is_entry_valid(kobject, name, inode.as_ref())
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    for name in stale_names {
+        cached_children.remove_entry_by_name(&name);
+    }
+
+    for (name, entry) in entries {
+        cached_children.put_entry_if_not_found(&name, || {
+            new_entry_inode(kobject, entry.clone(), this_ptr.clone())
+        });
+    }
+}
+
+/// Returns whether `inode` still shows the entry with `name` in `kobject`.
+pub(super) fn is_entry_valid(kobject: &Kobject, name: &str, inode: &dyn Inode) -> bool {
+    let (Some(entry), Some(shown_entry)) = (kobject.entry(name), entry_of_inode(inode)) else {
+        return false;
+    };
+    entry.ptr_eq(&shown_entry)
+}
+
+/// Represents the directory of a kobject.
+pub(super) struct KobjectDirOps {
+    kobject: Arc<Kobject>,
+}
+
+impl KobjectDirOps {
+    pub fn new_inode(kobject: Arc<Kobject>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Kobjects can be removed at any time, so their dentries should not be cached.
+        ProcDirBuilder::new(Self { kobject })
+            .parent(parent)
+            .volatile()
+            .build()
+            .unwrap()
+    }
+}
+
+impl DirOps for KobjectDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let Some(entry) = self.kobject.entry(name) else {
+            return_errno!(Errno::ENOENT);
+        };
+        Ok(new_entry_inode(&self.kobject, entry, this_ptr))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<KobjectDirOps>>()
+                .unwrap()
+                .this()
+        };
+        populate_kobject_entries(&this, &self.kobject, this_ptr);
+    }
+
+    fn validate_child(&self, name: &str, child: &dyn Inode) -> bool {
+        is_entry_valid(&self.kobject, name, child)
+    }
+}
+
+/// Represents an attribute file.
+struct AttrFileOps {
+    attr: Arc<dyn Attribute>,
+}
+
+impl AttrFileOps {
+    pub fn new_inode(attr: Arc<dyn Attribute>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let mode = attr.mode();
+        ProcFileBuilder::new(Self { attr })
+            .parent(parent)
+            .mode(mode)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for AttrFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        self.attr.show()
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let len = reader.remain();
+        let mut buf = vec![0u8; len];
+        reader.read_fallible(&mut VmWriter::from(buf.as_mut_slice()))?;
+
+        self.attr.store(&buf)?;
+        Ok(len)
+    }
+}
+
+/// Represents a symbolic link to a kobject.
+struct LinkSymOps {
+    dir: Arc<Kobject>,
+    target: Weak<Kobject>,
+}
+
+impl LinkSymOps {
+    pub fn new_inode(
+        dir: Arc<Kobject>,
+        target: Weak<Kobject>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        ProcSymBuilder::new(Self { dir, target })
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl SymOps for LinkSymOps {
+    fn read_link(&self) -> Result<String> {
+        let Some(target) = self.target.upgrade() else {
+            return_errno_with_message!(Errno::ENOENT, "the link target has been removed");
+        };
+        if target.is_removed() {
+            return_errno_with_message!(Errno::ENOENT, "the link target has been removed");
+        }
+        Ok(self.dir.relative_path_to(&target))
+    }
+}
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The sysfs, which exports the kernel objects and their attributes.
+//!
+//! The sysfs is built on the same templates as the procfs. Except for `/sys/kernel`, the
+//! directories are backed by the kobjects in [`kobject`], which are maintained by the
+//! [`device_model`].
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use self::{
+    kernel::KernelDirOps,
+    kobject::root_kobject,
+    kobject_inode::{is_entry_valid, new_entry_inode, populate_kobject_entries},
+};
+use crate::{
+    fs::{
+        procfs::template::{DirOps, ProcDir, ProcDirBuilder},
+        utils::{DirEntryVecExt, FileSystem, FsFlags, Inode, SuperBlock, NAME_MAX},
+    },
+    prelude::*,
+};
+
+pub mod device_model;
+mod kernel;
+pub mod kobject;
+mod kobject_inode;
+
+/// Magic number.
+const SYSFS_MAGIC: u64 = 0x62656572;
+/// Root Inode ID.
+const SYSFS_ROOT_INO: u64 = 1;
+/// Block size.
+const BLOCK_SIZE: usize = 1024;
+
+pub struct SysFS {
+    sb: SuperBlock,
+    root: Arc<dyn Inode>,
+    inode_allocator: AtomicU64,
+}
+
+impl SysFS {
+    pub fn new() -> Arc<Self> {
+        Arc::new_cyclic(|weak_fs| Self {
+            sb: SuperBlock::new(SYSFS_MAGIC, BLOCK_SIZE, NAME_MAX),
+            root: RootDirOps::new_inode(weak_fs.clone()),
+            inode_allocator: AtomicU64::new(SYSFS_ROOT_INO + 1),
+        })
+    }
+
+    pub(in crate::fs) fn alloc_id(&self) -> u64 {
+        self.inode_allocator.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+impl FileSystem for SysFS {
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn flags(&self) -> FsFlags {
+        FsFlags::empty()
+    }
+}
+
+/// Represents the inode at `/sys`.
+struct RootDirOps;
+
+impl RootDirOps {
+    pub fn new_inode(fs: Weak<SysFS>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self)
+            .fs(fs)
+            .ino(SYSFS_ROOT_INO)
+            .build()
+            .unwrap()
+    }
+}
+
+impl DirOps for RootDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "kernel" => KernelDirOps::new_inode(this_ptr.clone()),
+            _ => {
+                let Some(entry) = root_kobject().entry(name) else {
+                    return_errno!(Errno::ENOENT);
+                };
+                new_entry_inode(root_kobject(), entry, this_ptr)
+            }
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<RootDirOps>>().unwrap().this()
+        };
+        this.cached_children()
+            .write()
+            .put_entry_if_not_found("kernel", || KernelDirOps::new_inode(this_ptr.clone()));
+        populate_kobject_entries(&this, root_kobject(), this_ptr);
+    }
+
+    fn validate_child(&self, name: &str, child: &dyn Inode) -> bool {
+        name == "kernel" || is_entry_valid(root_kobject(), name, child)
+    }
+}
+
+/// Registers the devices in the sysfs.
+pub fn init() -> Result<()> {
+    device_model::init()
+}
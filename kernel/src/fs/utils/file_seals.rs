@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::prelude::*;
+
+bitflags! {
+    /// The seals of a file, which restrict the operations on the file.
+    ///
+    /// Reference: <https://man7.org/linux/man-pages/man2/fcntl.2.html>
+    pub struct FileSeals: u32 {
+        /// Prevents further seals from being added.
+        const F_SEAL_SEAL = 0x0001;
+        /// Prevents the file from shrinking.
+        const F_SEAL_SHRINK = 0x0002;
+        /// Prevents the file from growing.
+        const F_SEAL_GROW = 0x0004;
+        /// Prevents any modification to the contents of the file.
+        const F_SEAL_WRITE = 0x0008;
+        /// Like `F_SEAL_WRITE`, but the existing shared writable mappings can
+        /// still modify the contents.
+        const F_SEAL_FUTURE_WRITE = 0x0010;
+    }
+}
+
+/// The seals of a file, and the shared mappings that may write to the file.
+pub struct FileSealState {
+    seals: Mutex<FileSeals>,
+    writable_mappings: Arc<AtomicUsize>,
+}
+
+impl FileSealState {
+    /// Creates the state with the initial seals.
+    ///
+    /// A file that should never be sealed is created with `F_SEAL_SEAL`.
+    pub fn new(seals: FileSeals) -> Self {
+        Self {
+            seals: Mutex::new(seals),
+            writable_mappings: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns the current seals.
+    pub fn get(&self) -> FileSeals {
+        *self.seals.lock()
+    }
+
+    /// Adds the seals.
+    ///
+    /// `F_SEAL_WRITE` cannot be added if there are shared mappings that may write to the
+    /// file.
+    pub fn add(&self, new_seals: FileSeals) -> Result<()> {
+        let mut seals = self.seals.lock();
+
+        if seals.contains(FileSeals::F_SEAL_SEAL) {
+            return_errno_with_message!(Errno::EPERM, "the seals are sealed");
+        }
+        if new_seals.contains(FileSeals::F_SEAL_WRITE)
+            && !seals.contains(FileSeals::F_SEAL_WRITE)
+            && self.writable_mappings.load(Ordering::Relaxed) > 0
+        {
+            return_errno_with_message!(Errno::EBUSY, "the file has writable shared mappings");
+        }
+
+        seals.insert(new_seals);
+        Ok(())
+    }
+
+    /// Checks whether the contents of the file can be written.
+    pub fn check_write(&self) -> Result<()> {
+        if self
+            .get()
+            .intersects(FileSeals::F_SEAL_WRITE | FileSeals::F_SEAL_FUTURE_WRITE)
+        {
+            return_errno_with_message!(Errno::EPERM, "the file is sealed against writing");
+        }
+        Ok(())
+    }
+
+    /// Checks whether the file can be resized from `old_size` to `new_size`.
+    pub fn check_resize(&self, old_size: usize, new_size: usize) -> Result<()> {
+        let seals = self.get();
+        if new_size < old_size && seals.contains(FileSeals::F_SEAL_SHRINK) {
+            return_errno_with_message!(Errno::EPERM, "the file is sealed against shrinking");
+        }
+        if new_size > old_size && seals.contains(FileSeals::F_SEAL_GROW) {
+            return_errno_with_message!(Errno::EPERM, "the file is sealed against growing");
+        }
+        Ok(())
+    }
+
+    /// Registers a shared mapping that may write to the file.
+    ///
+    /// The mapping should hold the returned guard until it is removed.
+    pub fn map_shared_writable(&self) -> Result<WritableMappingGuard> {
+        // Hold the lock so that `F_SEAL_WRITE` cannot be added concurrently.
+        let seals = self.seals.lock();
+        if seals.intersects(FileSeals::F_SEAL_WRITE | FileSeals::F_SEAL_FUTURE_WRITE) {
+            return_errno_with_message!(Errno::EPERM, "the file is sealed against writing");
+        }
+
+        Ok(WritableMappingGuard::new(self.writable_mappings.clone()))
+    }
+}
+
+impl Debug for FileSealState {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("FileSealState")
+            .field("seals", &self.get())
+            .field(
+                "writable_mappings",
+                &self.writable_mappings.load(Ordering::Relaxed),
+            )
+            .finish()
+    }
+}
+
+/// A guard held by a shared mapping that may write to a file.
+///
+/// `F_SEAL_WRITE` cannot be added to the file while any guard exists.
+#[derive(Debug)]
+pub struct WritableMappingGuard(Arc<AtomicUsize>);
+
+impl WritableMappingGuard {
+    fn new(counter: Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Clone for WritableMappingGuard {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+
+impl Drop for WritableMappingGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
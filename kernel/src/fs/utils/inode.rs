@@ -8,7 +8,10 @@ use aster_rights::Full;
 use core2::io::{Error as IoError, ErrorKind as IoErrorKind, Result as IoResult, Write};
 use ostd::task::Task;
 
-use super::{AccessMode, DirentVisitor, FallocMode, FileSystem, IoctlCmd};
+use super::{
+    get_acl, AccessMode, DirentVisitor, FallocMode, FileSeals, FileSystem, IoctlCmd,
+    WritableMappingGuard, XattrName, XattrSetFlags, POSIX_ACL_ACCESS,
+};
 use crate::{
     events::IoEvents,
     fs::device::{Device, DeviceType},
@@ -460,6 +463,47 @@ pub trait Inode: Any + Sync + Send {
         return_errno!(Errno::EOPNOTSUPP);
     }
 
+    /// Returns the seals of the file.
+    fn seals(&self) -> Result<FileSeals> {
+        return_errno_with_message!(Errno::EINVAL, "the file does not support seals");
+    }
+
+    /// Adds the seals to the file.
+    fn add_seals(&self, seals: FileSeals) -> Result<()> {
+        return_errno_with_message!(Errno::EINVAL, "the file does not support seals");
+    }
+
+    /// Registers a shared mapping that may write to the file.
+    ///
+    /// If the file supports seals, the mapping should hold the returned guard until it is
+    /// removed, so that the file cannot be sealed against writing in the meantime.
+    fn map_shared_writable(&self) -> Result<Option<WritableMappingGuard>> {
+        Ok(None)
+    }
+
+    /// Sets the value of an extended attribute.
+    ///
+    /// The caller is responsible for checking the permissions and interpreting the
+    /// attributes in the `system` namespace.
+    fn set_xattr(&self, name: XattrName, value: &[u8], flags: XattrSetFlags) -> Result<()> {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "xattrs are not supported");
+    }
+
+    /// Gets the value of an extended attribute.
+    fn get_xattr(&self, name: XattrName) -> Result<Vec<u8>> {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "xattrs are not supported");
+    }
+
+    /// Lists the full names of all extended attributes.
+    fn list_xattr(&self) -> Result<Vec<String>> {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "xattrs are not supported");
+    }
+
+    /// Removes an extended attribute.
+    fn remove_xattr(&self, name: XattrName) -> Result<()> {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "xattrs are not supported");
+    }
+
     fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
         let events = IoEvents::IN | IoEvents::OUT;
         events & mask
@@ -512,6 +556,16 @@ pub trait Inode: Any + Sync + Send {
 
         perm =
             perm.intersection(Permission::MAY_READ | Permission::MAY_WRITE | Permission::MAY_EXEC);
+
+        if let Some(acl) = get_acl(self, POSIX_ACL_ACCESS).ok().flatten() {
+            let metadata = self.metadata();
+            let is_in_group = |gid| gid == creds.fsgid() || creds.groups().contains(&gid);
+            if !acl.check(creds.fsuid(), is_in_group, metadata.uid, metadata.gid, perm) {
+                return_errno_with_message!(Errno::EACCES, "ACL permission check failed");
+            }
+            return Ok(());
+        }
+
         let mode = self.mode().unwrap();
 
         if self.metadata().uid == creds.fsuid() {
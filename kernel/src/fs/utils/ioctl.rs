@@ -41,4 +41,18 @@ pub enum IoctlCmd {
     TIOCGPTPEER = 0x40045441,
     /// Get tdx report using TDCALL
     TDXGETREPORT = 0xc4405401,
+    /// Register a memory range to a userfaultfd
+    UFFDIO_REGISTER = 0xc020aa00,
+    /// Unregister a memory range from a userfaultfd
+    UFFDIO_UNREGISTER = 0x8010aa01,
+    /// Wake up the threads faulting in a memory range
+    UFFDIO_WAKE = 0x8010aa02,
+    /// Resolve missing-page faults by copying pages
+    UFFDIO_COPY = 0xc028aa03,
+    /// Resolve missing-page faults by zeroing pages
+    UFFDIO_ZEROPAGE = 0xc020aa04,
+    /// Write-protect a memory range or remove the protection
+    UFFDIO_WRITEPROTECT = 0xc018aa06,
+    /// Enable a userfaultfd and negotiate its API
+    UFFDIO_API = 0xc018aa3f,
 }
@@ -9,16 +9,23 @@ pub use dirent_visitor::DirentVisitor;
 pub use direntry_vec::DirEntryVecExt;
 pub use falloc_mode::FallocMode;
 pub use file_creation_mask::FileCreationMask;
+pub use file_seals::{FileSealState, FileSeals, WritableMappingGuard};
 pub use flock::{FlockItem, FlockList, FlockType};
 pub use fs::{FileSystem, FsFlags, SuperBlock};
 pub use inode::{Extension, Inode, InodeMode, InodeType, Metadata, MknodType, Permission};
 pub use ioctl::IoctlCmd;
 pub use page_cache::{CachePage, PageCache, PageCacheBackend};
+pub use posix_acl::{PosixAcl, POSIX_ACL_ACCESS, POSIX_ACL_DEFAULT};
 pub use random_test::{generate_random_operation, new_fs_in_memory};
 pub use range_lock::{
     FileRange, RangeLockItem, RangeLockItemBuilder, RangeLockList, RangeLockType, OFFSET_MAX,
 };
 pub use status_flags::StatusFlags;
+pub use xattr::{
+    check_xattr_permission, chmod_acl, get_acl, inherit_acl, is_xattr_listable, set_xattr_with_acl,
+    XattrMap, XattrName, XattrNamespace, XattrSetFlags, XATTR_LIST_MAX_LEN, XATTR_NAME_MAX_LEN,
+    XATTR_VALUE_MAX_LEN,
+};
 
 mod access_mode;
 mod channel;
@@ -27,14 +34,17 @@ mod dirent_visitor;
 mod direntry_vec;
 mod falloc_mode;
 mod file_creation_mask;
+mod file_seals;
 mod flock;
 mod fs;
 mod inode;
 mod ioctl;
 mod page_cache;
+mod posix_acl;
 mod random_test;
 mod range_lock;
 mod status_flags;
+mod xattr;
 
 use core::{
     borrow::Borrow,
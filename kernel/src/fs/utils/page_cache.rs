@@ -14,7 +14,7 @@ use aster_rights::Full;
 use lru::LruCache;
 use ostd::{
     impl_untyped_frame_meta_for,
-    mm::{Frame, FrameAllocOptions, UFrame, UntypedMem, VmIo},
+    mm::{Frame, FrameAllocOptions, UFrame, USegment, UntypedMem, VmIo, HUGE_PAGE_SIZE},
 };
 
 use crate::{
@@ -484,6 +484,68 @@ impl Pager for PageCacheManager {
         let page = CachePage::alloc_zero()?;
         Ok(self.pages.lock().get_or_insert(idx, || page).clone().into())
     }
+
+    fn commit_huge_page(&self, idx: usize) -> Result<Option<USegment>> {
+        let backend = self.backend();
+        if !backend.allows_huge_pages() {
+            return Ok(None);
+        }
+
+        let nr_pages = HUGE_PAGE_SIZE / PAGE_SIZE;
+        let mut pages = self.pages.lock();
+        if (idx..idx + nr_pages).any(|idx| pages.contains(&idx)) {
+            return Ok(None);
+        }
+
+        // Fall back to base pages if no aligned huge page is available.
+        let Ok(segment) = FrameAllocOptions::new()
+            .zeroed(false)
+            .alloc_segment_with(nr_pages, |_| CachePageMeta::new())
+        else {
+            return Ok(None);
+        };
+        if segment.start_paddr() % HUGE_PAGE_SIZE != 0 {
+            return Ok(None);
+        }
+
+        let backend_npages = backend.npages();
+        for (i, mut page) in segment.clone().enumerate() {
+            if idx + i < backend_npages {
+                backend.read_page(idx + i, &page)?;
+            } else {
+                page.writer().fill(0);
+            }
+            page.store_state(PageState::UpToDate);
+            pages.put(idx + i, page);
+        }
+
+        Ok(Some(segment.into()))
+    }
+
+    fn evict_page(&self, idx: usize) -> bool {
+        let Some(backend) = self.backend.upgrade() else {
+            return false;
+        };
+        if !backend.allows_eviction() {
+            return false;
+        }
+
+        // Reclaim may happen while the page cache is locked by the current
+        // thread, so never wait for the lock here.
+        let Some(mut pages) = self.pages.try_lock() else {
+            return false;
+        };
+        let Some(page) = pages.peek(&idx) else {
+            return false;
+        };
+        // Only clean pages that are referenced by nothing but the page cache
+        // and the VMO can be evicted.
+        if page.load_state() != PageState::UpToDate || page.reference_count() > 2 {
+            return false;
+        }
+        pages.pop(&idx);
+        true
+    }
 }
 
 /// A page in the page cache.
@@ -498,18 +560,23 @@ pub struct CachePageMeta {
 
 impl_untyped_frame_meta_for!(CachePageMeta);
 
+impl CachePageMeta {
+    fn new() -> Self {
+        Self {
+            state: AtomicPageState {
+                state: AtomicU8::new(PageState::Uninit as u8),
+            },
+        }
+    }
+}
+
 pub trait CachePageExt {
     fn metadata(&self) -> &CachePageMeta;
 
     fn alloc() -> Result<CachePage> {
-        let meta = CachePageMeta {
-            state: AtomicPageState {
-                state: AtomicU8::new(PageState::Uninit as u8),
-            },
-        };
         let page = FrameAllocOptions::new()
             .zeroed(false)
-            .alloc_frame_with(meta)?;
+            .alloc_frame_with(CachePageMeta::new())?;
         Ok(page)
     }
 
@@ -578,6 +645,20 @@ pub trait PageCacheBackend: Sync + Send {
     fn write_page_async(&self, idx: usize, frame: &CachePage) -> Result<BioWaiter>;
     /// Returns the number of pages in the backend.
     fn npages(&self) -> usize;
+    /// Returns whether the pages can be cached in huge pages when mapped.
+    ///
+    /// This is only worthwhile for in-memory backends, whose pages are never
+    /// evicted.
+    fn allows_huge_pages(&self) -> bool {
+        false
+    }
+
+    /// Returns whether clean pages can be evicted to reclaim memory.
+    ///
+    /// Backends that do not keep the data anywhere else must not allow this.
+    fn allows_eviction(&self) -> bool {
+        true
+    }
 }
 
 impl dyn PageCacheBackend {
@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! POSIX access control lists.
+//!
+//! An ACL is stored as the value of the `system.posix_acl_access` or
+//! `system.posix_acl_default` extended attribute, in the format used by Linux:
+//! a 4-byte version header followed by an array of 8-byte entries.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/acl.5.html>
+
+use super::{InodeMode, Permission};
+use crate::{
+    prelude::*,
+    process::{Gid, Uid},
+};
+
+/// The name of the extended attribute that stores the access ACL of an inode.
+pub const POSIX_ACL_ACCESS: &str = "system.posix_acl_access";
+
+/// The name of the extended attribute that stores the default ACL of a directory.
+pub const POSIX_ACL_DEFAULT: &str = "system.posix_acl_default";
+
+const POSIX_ACL_XATTR_VERSION: u32 = 2;
+
+const ACL_UNDEFINED_ID: u32 = u32::MAX;
+
+const ACL_PERM_MASK: u16 = 0o7;
+
+/// The tag of an ACL entry.
+///
+/// The variants are declared in the order in which valid ACLs sort their entries.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, TryFromInt)]
+enum PosixAclTag {
+    UserObj = 0x01,
+    User = 0x02,
+    GroupObj = 0x04,
+    Group = 0x08,
+    Mask = 0x10,
+    Other = 0x20,
+}
+
+/// An entry of a POSIX ACL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PosixAclEntry {
+    tag: PosixAclTag,
+    /// The read, write and execute bits, in the same layout as `Permission`.
+    perm: u16,
+    /// The user or group ID, only meaningful for `User` and `Group` entries.
+    id: u32,
+}
+
+impl PosixAclEntry {
+    fn grants(&self, perm: u16) -> bool {
+        self.perm & perm == perm
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawAclHeader {
+    version: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawAclEntry {
+    tag: u16,
+    perm: u16,
+    id: u32,
+}
+
+/// A POSIX ACL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PosixAcl {
+    entries: Vec<PosixAclEntry>,
+}
+
+impl PosixAcl {
+    /// Parses and validates an ACL from the value of an extended attribute.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let header_len = core::mem::size_of::<RawAclHeader>();
+        let entry_len = core::mem::size_of::<RawAclEntry>();
+        if bytes.len() < header_len || (bytes.len() - header_len) % entry_len != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the ACL has an invalid size");
+        }
+
+        let header = RawAclHeader::from_bytes(&bytes[..header_len]);
+        if header.version != POSIX_ACL_XATTR_VERSION {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the ACL version is not supported");
+        }
+
+        let mut entries = Vec::with_capacity((bytes.len() - header_len) / entry_len);
+        for raw_bytes in bytes[header_len..].chunks_exact(entry_len) {
+            let raw_entry = RawAclEntry::from_bytes(raw_bytes);
+            let tag = PosixAclTag::try_from(raw_entry.tag)
+                .map_err(|_| Error::with_message(Errno::EINVAL, "the ACL tag is invalid"))?;
+            if raw_entry.perm & !ACL_PERM_MASK != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the ACL permission is invalid");
+            }
+            let id = match tag {
+                PosixAclTag::User | PosixAclTag::Group => raw_entry.id,
+                _ => ACL_UNDEFINED_ID,
+            };
+            entries.push(PosixAclEntry {
+                tag,
+                perm: raw_entry.perm,
+                id,
+            });
+        }
+
+        let acl = Self { entries };
+        acl.validate()?;
+        Ok(acl)
+    }
+
+    /// Serializes the ACL into the value of an extended attribute.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = RawAclHeader {
+            version: POSIX_ACL_XATTR_VERSION,
+        };
+        let mut bytes = header.as_bytes().to_vec();
+        for entry in self.entries.iter() {
+            let raw_entry = RawAclEntry {
+                tag: entry.tag as u16,
+                perm: entry.perm,
+                id: entry.id,
+            };
+            bytes.extend_from_slice(raw_entry.as_bytes());
+        }
+        bytes
+    }
+
+    /// Checks that the entries are sorted and that the mandatory entries appear exactly once.
+    fn validate(&self) -> Result<()> {
+        let mut counts = [0usize; 6];
+        let mut prev: Option<&PosixAclEntry> = None;
+        for entry in self.entries.iter() {
+            if let Some(prev) = prev {
+                let is_ordered = match prev.tag.cmp(&entry.tag) {
+                    core::cmp::Ordering::Less => true,
+                    core::cmp::Ordering::Equal => {
+                        matches!(entry.tag, PosixAclTag::User | PosixAclTag::Group)
+                            && prev.id < entry.id
+                    }
+                    core::cmp::Ordering::Greater => false,
+                };
+                if !is_ordered {
+                    return_errno_with_message!(Errno::EINVAL, "the ACL entries are not sorted");
+                }
+            }
+            counts[(entry.tag as u16).trailing_zeros() as usize] += 1;
+            prev = Some(entry);
+        }
+
+        let [user_obj, user, group_obj, group, mask, other] = counts;
+        if user_obj != 1 || group_obj != 1 || other != 1 || mask > 1 {
+            return_errno_with_message!(Errno::EINVAL, "the ACL lacks a mandatory entry");
+        }
+        if (user > 0 || group > 0) && mask == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the ACL requires a mask entry");
+        }
+        Ok(())
+    }
+
+    fn find(&self, tag: PosixAclTag) -> Option<&PosixAclEntry> {
+        self.entries.iter().find(|entry| entry.tag == tag)
+    }
+
+    fn find_mut(&mut self, tag: PosixAclTag) -> Option<&mut PosixAclEntry> {
+        self.entries.iter_mut().find(|entry| entry.tag == tag)
+    }
+
+    /// Returns the permission bits of the file mode that reflect the ACL.
+    ///
+    /// The group class bits come from the mask entry if there is one.
+    pub fn mode_bits(&self) -> InodeMode {
+        let perm_of = |tag| self.find(tag).map_or(0, |entry: &PosixAclEntry| entry.perm);
+        let group = match self.find(PosixAclTag::Mask) {
+            Some(mask) => mask.perm,
+            None => perm_of(PosixAclTag::GroupObj),
+        };
+        InodeMode::from_bits_truncate(
+            (perm_of(PosixAclTag::UserObj) << 6) | (group << 3) | perm_of(PosixAclTag::Other),
+        )
+    }
+
+    /// Returns whether the ACL carries no information beyond the file mode.
+    pub fn is_equivalent_to_mode(&self) -> bool {
+        self.entries.len() == 3
+    }
+
+    /// Updates the entries that correspond to the permission bits of the file mode.
+    ///
+    /// This is done when the mode of a file with an access ACL is changed.
+    pub fn chmod(&mut self, mode: InodeMode) {
+        let bits = mode.bits();
+        let has_mask = self.find(PosixAclTag::Mask).is_some();
+        for entry in self.entries.iter_mut() {
+            match entry.tag {
+                PosixAclTag::UserObj => entry.perm = (bits >> 6) & ACL_PERM_MASK,
+                PosixAclTag::GroupObj if !has_mask => entry.perm = (bits >> 3) & ACL_PERM_MASK,
+                PosixAclTag::Mask => entry.perm = (bits >> 3) & ACL_PERM_MASK,
+                PosixAclTag::Other => entry.perm = bits & ACL_PERM_MASK,
+                _ => {}
+            }
+        }
+    }
+
+    /// Restricts an ACL inherited from a default ACL by the mode of a new file.
+    ///
+    /// Returns the mode that the new file should have.
+    pub fn create_masq(&mut self, mode: InodeMode) -> InodeMode {
+        let bits = mode.bits();
+        let has_mask = self.find(PosixAclTag::Mask).is_some();
+        if let Some(user_obj) = self.find_mut(PosixAclTag::UserObj) {
+            user_obj.perm &= (bits >> 6) & ACL_PERM_MASK;
+        }
+        if has_mask {
+            if let Some(mask) = self.find_mut(PosixAclTag::Mask) {
+                mask.perm &= (bits >> 3) & ACL_PERM_MASK;
+            }
+        } else if let Some(group_obj) = self.find_mut(PosixAclTag::GroupObj) {
+            group_obj.perm &= (bits >> 3) & ACL_PERM_MASK;
+        }
+        if let Some(other) = self.find_mut(PosixAclTag::Other) {
+            other.perm &= bits & ACL_PERM_MASK;
+        }
+
+        let perm_bits = InodeMode::from_bits_truncate(0o777);
+        (mode - perm_bits) | self.mode_bits()
+    }
+
+    /// Checks whether the ACL grants `perm` to a user.
+    ///
+    /// `owner` and `owning_group` are the owners of the file. `is_in_group` tells whether the
+    /// user is a member of a group.
+    pub fn check(
+        &self,
+        fsuid: Uid,
+        is_in_group: impl Fn(Gid) -> bool,
+        owner: Uid,
+        owning_group: Gid,
+        perm: Permission,
+    ) -> bool {
+        let want =
+            (perm & (Permission::MAY_READ | Permission::MAY_WRITE | Permission::MAY_EXEC)).bits();
+        let mask = self
+            .find(PosixAclTag::Mask)
+            .map_or(ACL_PERM_MASK, |mask| mask.perm);
+
+        let mut group_found = false;
+        for entry in self.entries.iter() {
+            match entry.tag {
+                PosixAclTag::UserObj if fsuid == owner => return entry.grants(want),
+                PosixAclTag::User if fsuid == Uid::new(entry.id) => {
+                    return entry.grants(want) && mask & want == want;
+                }
+                PosixAclTag::GroupObj if is_in_group(owning_group) => {
+                    group_found = true;
+                    if entry.grants(want) {
+                        return mask & want == want;
+                    }
+                }
+                PosixAclTag::Group if is_in_group(Gid::new(entry.id)) => {
+                    group_found = true;
+                    if entry.grants(want) {
+                        return mask & want == want;
+                    }
+                }
+                PosixAclTag::Other => return !group_found && entry.grants(want),
+                _ => {}
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,354 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Extended attributes.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/xattr.7.html>
+
+use aster_rights::ReadOp;
+use ostd::task::Task;
+
+use super::{
+    posix_acl::{self, PosixAcl},
+    Inode, InodeMode, InodeType, Permission,
+};
+use crate::{
+    prelude::*,
+    process::{credentials::capabilities::CapSet, posix_thread::AsPosixThread, Credentials},
+};
+
+/// The maximum length of the full name of an extended attribute.
+pub const XATTR_NAME_MAX_LEN: usize = 255;
+
+/// The maximum length of the value of an extended attribute.
+pub const XATTR_VALUE_MAX_LEN: usize = 65536;
+
+/// The maximum length of the list of the extended attribute names of an inode.
+pub const XATTR_LIST_MAX_LEN: usize = 65536;
+
+/// The namespace of an extended attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XattrNamespace {
+    /// Arbitrary attributes of regular files and directories, subject to the file permissions.
+    User,
+    /// Attributes that are only visible to and modifiable by `CAP_SYS_ADMIN`.
+    Trusted,
+    /// Attributes used by security modules.
+    Security,
+    /// Attributes interpreted by the kernel, e.g., POSIX ACLs.
+    System,
+}
+
+impl XattrNamespace {
+    const ALL: [Self; 4] = [Self::User, Self::Trusted, Self::Security, Self::System];
+
+    /// Returns the prefix of the names in the namespace, including the trailing dot.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Self::User => "user.",
+            Self::Trusted => "trusted.",
+            Self::Security => "security.",
+            Self::System => "system.",
+        }
+    }
+}
+
+/// The name of an extended attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XattrName<'a> {
+    namespace: XattrNamespace,
+    full_name: &'a str,
+}
+
+impl<'a> XattrName<'a> {
+    /// Parses a full name of the form `<namespace>.<suffix>`.
+    pub fn try_from_full_name(full_name: &'a str) -> Result<Self> {
+        if full_name.is_empty() || full_name.len() > XATTR_NAME_MAX_LEN {
+            return_errno_with_message!(Errno::ERANGE, "the xattr name length is invalid");
+        }
+
+        let Some(namespace) = XattrNamespace::ALL
+            .into_iter()
+            .find(|namespace| full_name.starts_with(namespace.prefix()))
+        else {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the xattr namespace is not supported");
+        };
+        if full_name.len() == namespace.prefix().len() {
+            return_errno_with_message!(Errno::EINVAL, "the xattr name has an empty suffix");
+        }
+
+        Ok(Self {
+            namespace,
+            full_name,
+        })
+    }
+
+    /// Returns the namespace.
+    pub fn namespace(&self) -> XattrNamespace {
+        self.namespace
+    }
+
+    /// Returns the full name, including the namespace prefix.
+    pub fn full_name(&self) -> &'a str {
+        self.full_name
+    }
+
+    /// Returns the name without the namespace prefix.
+    pub fn suffix(&self) -> &'a str {
+        &self.full_name[self.namespace.prefix().len()..]
+    }
+
+    fn is_posix_acl(&self) -> bool {
+        matches!(
+            self.full_name,
+            posix_acl::POSIX_ACL_ACCESS | posix_acl::POSIX_ACL_DEFAULT
+        )
+    }
+}
+
+bitflags! {
+    /// The flags of `setxattr`.
+    pub struct XattrSetFlags: u32 {
+        /// Fails if the attribute already exists.
+        const CREATE_ONLY = 0x1;
+        /// Fails if the attribute does not exist.
+        const REPLACE_ONLY = 0x2;
+    }
+}
+
+/// The extended attributes of an inode, kept in memory.
+///
+/// File systems use this type either as the only storage of the attributes (e.g., RamFS),
+/// or as a cache of the attributes that are stored on the disk.
+#[derive(Clone, Debug, Default)]
+pub struct XattrMap {
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl XattrMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of an attribute, respecting the `flags`.
+    pub fn set(&mut self, name: XattrName, value: &[u8], flags: XattrSetFlags) -> Result<()> {
+        let exists = self.entries.contains_key(name.full_name());
+        if exists && flags.contains(XattrSetFlags::CREATE_ONLY) {
+            return_errno_with_message!(Errno::EEXIST, "the xattr already exists");
+        }
+        if !exists && flags.contains(XattrSetFlags::REPLACE_ONLY) {
+            return_errno_with_message!(Errno::ENODATA, "the xattr does not exist");
+        }
+
+        self.entries
+            .insert(name.full_name().to_string(), value.to_vec());
+        Ok(())
+    }
+
+    /// Gets the value of an attribute.
+    pub fn get(&self, name: XattrName) -> Result<Vec<u8>> {
+        self.entries
+            .get(name.full_name())
+            .cloned()
+            .ok_or(Error::with_message(
+                Errno::ENODATA,
+                "the xattr does not exist",
+            ))
+    }
+
+    /// Removes an attribute.
+    pub fn remove(&mut self, name: XattrName) -> Result<()> {
+        self.entries
+            .remove(name.full_name())
+            .map(|_| ())
+            .ok_or(Error::with_message(
+                Errno::ENODATA,
+                "the xattr does not exist",
+            ))
+    }
+
+    /// Returns the full names of all attributes.
+    pub fn names(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    /// Iterates over the full names and values of all attributes.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_slice()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Checks whether the current thread may access the attribute `name` of `inode`.
+///
+/// Similar to Linux, the VFS imposes no restriction on reading `security.*` and `system.*`
+/// attributes, while writing `security.*` attributes requires `CAP_SYS_ADMIN` in the absence
+/// of security modules, and writing POSIX ACLs requires owning the file.
+pub fn check_xattr_permission(inode: &dyn Inode, name: XattrName, is_write: bool) -> Result<()> {
+    match name.namespace() {
+        XattrNamespace::Trusted => {
+            if !is_privileged(CapSet::SYS_ADMIN) {
+                if is_write {
+                    return_errno_with_message!(Errno::EPERM, "`CAP_SYS_ADMIN` is required");
+                }
+                return_errno_with_message!(Errno::ENODATA, "the xattr is not visible");
+            }
+            Ok(())
+        }
+        XattrNamespace::Security => {
+            if is_write && !is_privileged(CapSet::SYS_ADMIN) {
+                return_errno_with_message!(Errno::EPERM, "`CAP_SYS_ADMIN` is required");
+            }
+            Ok(())
+        }
+        XattrNamespace::System => {
+            if !name.is_posix_acl() {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "the system xattr is not supported");
+            }
+            if is_write && !is_owner(inode) && !is_privileged(CapSet::FOWNER) {
+                return_errno_with_message!(Errno::EPERM, "only the owner can change the ACL");
+            }
+            Ok(())
+        }
+        XattrNamespace::User => {
+            let type_ = inode.type_();
+            if type_ != InodeType::File && type_ != InodeType::Dir {
+                if is_write {
+                    return_errno_with_message!(
+                        Errno::EPERM,
+                        "user xattrs are only allowed on files and directories"
+                    );
+                }
+                return_errno_with_message!(Errno::ENODATA, "the xattr does not exist");
+            }
+            if is_write
+                && type_ == InodeType::Dir
+                && inode.mode()?.contains(InodeMode::S_ISVTX)
+                && !is_owner(inode)
+                && !is_privileged(CapSet::FOWNER)
+            {
+                return_errno_with_message!(Errno::EPERM, "the directory is sticky");
+            }
+            inode.check_permission(access_perm(is_write))
+        }
+    }
+}
+
+/// Returns whether the current thread may see the names in the namespace when listing.
+pub fn is_xattr_listable(namespace: XattrNamespace) -> bool {
+    namespace != XattrNamespace::Trusted || is_privileged(CapSet::SYS_ADMIN)
+}
+
+/// Sets an attribute, interpreting POSIX ACLs.
+///
+/// Setting an access ACL updates the file mode. An access ACL that carries no information
+/// beyond the file mode is not stored at all.
+pub fn set_xattr_with_acl(
+    inode: &dyn Inode,
+    name: XattrName,
+    value: &[u8],
+    flags: XattrSetFlags,
+) -> Result<()> {
+    if !name.is_posix_acl() {
+        return inode.set_xattr(name, value, flags);
+    }
+
+    let acl = PosixAcl::from_bytes(value)?;
+    if name.full_name() == posix_acl::POSIX_ACL_DEFAULT {
+        if inode.type_() != InodeType::Dir {
+            return_errno_with_message!(Errno::EACCES, "only directories have default ACLs");
+        }
+        return inode.set_xattr(name, &acl.to_bytes(), flags);
+    }
+
+    let old_mode = inode.mode()?;
+    let new_mode = (old_mode - InodeMode::from_bits_truncate(0o777)) | acl.mode_bits();
+    if acl.is_equivalent_to_mode() {
+        match inode.remove_xattr(name) {
+            Err(err) if err.error() == Errno::ENODATA => {
+                if flags.contains(XattrSetFlags::REPLACE_ONLY) {
+                    return Err(err);
+                }
+            }
+            result => result?,
+        }
+    } else {
+        inode.set_xattr(name, &acl.to_bytes(), flags)?;
+    }
+    inode.set_mode(new_mode)
+}
+
+/// Synchronizes the access ACL of `inode` with a newly set `mode`.
+pub fn chmod_acl(inode: &dyn Inode, mode: InodeMode) -> Result<()> {
+    let Some(mut acl) = get_acl(inode, posix_acl::POSIX_ACL_ACCESS)? else {
+        return Ok(());
+    };
+    acl.chmod(mode);
+
+    let name = XattrName::try_from_full_name(posix_acl::POSIX_ACL_ACCESS)?;
+    inode.set_xattr(name, &acl.to_bytes(), XattrSetFlags::REPLACE_ONLY)
+}
+
+/// Applies the default ACL of the directory `parent` to its new child `inode`.
+///
+/// The child inherits the default ACL as its access ACL, restricted by its mode. Child
+/// directories also inherit the default ACL itself.
+pub fn inherit_acl(parent: &dyn Inode, inode: &dyn Inode) -> Result<()> {
+    let Some(default_acl) = get_acl(parent, posix_acl::POSIX_ACL_DEFAULT)? else {
+        return Ok(());
+    };
+
+    if inode.type_() == InodeType::Dir {
+        let name = XattrName::try_from_full_name(posix_acl::POSIX_ACL_DEFAULT)?;
+        inode.set_xattr(name, &default_acl.to_bytes(), XattrSetFlags::empty())?;
+    }
+
+    let mut access_acl = default_acl;
+    let new_mode = access_acl.create_masq(inode.mode()?);
+    if !access_acl.is_equivalent_to_mode() {
+        let name = XattrName::try_from_full_name(posix_acl::POSIX_ACL_ACCESS)?;
+        inode.set_xattr(name, &access_acl.to_bytes(), XattrSetFlags::empty())?;
+    }
+    inode.set_mode(new_mode)
+}
+
+/// Reads the ACL stored in the attribute `full_name`, if any.
+///
+/// File systems that do not support extended attributes have no ACLs.
+pub fn get_acl<I: Inode + ?Sized>(inode: &I, full_name: &str) -> Result<Option<PosixAcl>> {
+    let name = XattrName::try_from_full_name(full_name)?;
+    match inode.get_xattr(name) {
+        Ok(value) => PosixAcl::from_bytes(&value).map(Some),
+        Err(err) if matches!(err.error(), Errno::ENODATA | Errno::EOPNOTSUPP) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn access_perm(is_write: bool) -> Permission {
+    if is_write {
+        Permission::MAY_WRITE
+    } else {
+        Permission::MAY_READ
+    }
+}
+
+/// Returns the credentials of the current thread.
+///
+/// Kernel tasks have no credentials and are not subject to permission checks.
+fn current_credentials() -> Option<Credentials<ReadOp>> {
+    let task = Task::current()?;
+    let thread = task.as_posix_thread()?;
+    Some(thread.credentials())
+}
+
+fn is_privileged(cap: CapSet) -> bool {
+    current_credentials().is_none_or(|creds| creds.effective_capset().contains(cap))
+}
+
+fn is_owner(inode: &dyn Inode) -> bool {
+    current_credentials().is_none_or(|creds| inode.metadata().uid == creds.fsuid())
+}
@@ -0,0 +1,7 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Message queue for the system, including System V message queue and
+//! POSIX message queue.
+
+pub mod posix;
+pub mod system_v;
@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! POSIX message queue.
+//!
+//! The queues live in the `mqueue` file system of an IPC namespace, so they can be
+//! opened by `mq_open` and polled like other files.
+
+use core::time::Duration;
+
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::{
+        signal::{
+            c_types::sigval_t,
+            sig_num::SigNum,
+            signals::user::{UserSignal, UserSignalKind},
+            PollHandle, Pollable, Pollee,
+        },
+        Pid, Process, Uid,
+    },
+};
+
+// The following constant values are derived from the default values in Linux.
+
+/// Default maximum number of messages in a queue.
+pub const DFLT_MSG: usize = 10;
+/// Default maximum size of a message, in bytes.
+pub const DFLT_MSGSIZE: usize = 8192;
+/// Maximum number of messages in a queue that can be set without `CAP_SYS_RESOURCE`.
+pub const MSG_MAX: usize = 10;
+/// Maximum size of a message that can be set without `CAP_SYS_RESOURCE`.
+pub const MSGSIZE_MAX: usize = 8192;
+/// Maximum number of messages in a queue.
+pub const HARD_MSGMAX: usize = 65536;
+/// Maximum size of a message, in bytes.
+pub const HARD_MSGSIZEMAX: usize = 16 * 1024 * 1024;
+/// The priorities of messages must be less than this value.
+pub const MQ_PRIO_MAX: u32 = 32768;
+
+/// The attributes of a POSIX message queue, which are fixed on creation.
+#[derive(Debug, Clone, Copy)]
+pub struct PosixMessageQueueAttr {
+    /// Maximum number of messages in the queue
+    pub max_messages: usize,
+    /// Maximum size of a message, in bytes
+    pub max_message_size: usize,
+}
+
+impl Default for PosixMessageQueueAttr {
+    fn default() -> Self {
+        Self {
+            max_messages: DFLT_MSG,
+            max_message_size: DFLT_MSGSIZE,
+        }
+    }
+}
+
+/// A POSIX message queue.
+///
+/// Messages are received in descending order of their priorities, and messages of the
+/// same priority are received in the order that they are sent.
+pub struct PosixMessageQueue {
+    attr: PosixMessageQueueAttr,
+    inner: Mutex<PosixMessageQueueInner>,
+    pollee: Pollee,
+}
+
+struct PosixMessageQueueInner {
+    /// The messages, grouped by their priorities
+    messages: BTreeMap<u32, VecDeque<Vec<u8>>>,
+    /// Number of messages in the queue
+    num_messages: usize,
+    /// Number of bytes of all the messages in the queue
+    total_len: usize,
+    /// Number of receivers that are waiting for messages
+    num_waiting_receivers: usize,
+    notification: Option<MqNotification>,
+}
+
+/// The registration of a process to be notified by `mq_notify`.
+///
+/// The process is notified when a message arrives at the empty queue while no receiver is
+/// waiting. Then the registration is removed.
+#[derive(Debug)]
+pub struct MqNotification {
+    process: Weak<Process>,
+    pid: Pid,
+    method: MqNotifyMethod,
+}
+
+/// How a registered process is notified.
+#[derive(Debug, Clone, Copy)]
+pub enum MqNotifyMethod {
+    /// Nothing is sent (i.e., `SIGEV_NONE`).
+    None,
+    /// A signal is sent with the value (i.e., `SIGEV_SIGNAL`).
+    Signal(SigNum, sigval_t),
+}
+
+impl MqNotification {
+    pub fn new(process: Weak<Process>, pid: Pid, method: MqNotifyMethod) -> Self {
+        Self {
+            process,
+            pid,
+            method,
+        }
+    }
+
+    fn notify(self, (sender_pid, sender_uid): (Pid, Uid)) {
+        let MqNotifyMethod::Signal(sig_num, value) = self.method else {
+            return;
+        };
+        let Some(process) = self.process.upgrade() else {
+            return;
+        };
+
+        let signal = UserSignal::new(
+            sig_num,
+            UserSignalKind::MessageQueue(value),
+            sender_pid,
+            sender_uid,
+        )
+        .for_receiver_in(process.pid_ns());
+        process.enqueue_signal(signal);
+    }
+}
+
+/// The status of a POSIX message queue.
+#[derive(Debug, Clone)]
+pub struct PosixMessageQueueStatus {
+    pub num_messages: usize,
+    pub total_len: usize,
+    /// The PID and the method of the registered process, if any
+    pub notification: Option<(Pid, MqNotifyMethod)>,
+}
+
+impl PosixMessageQueue {
+    pub fn new(attr: PosixMessageQueueAttr) -> Self {
+        Self {
+            attr,
+            inner: Mutex::new(PosixMessageQueueInner {
+                messages: BTreeMap::new(),
+                num_messages: 0,
+                total_len: 0,
+                num_waiting_receivers: 0,
+                notification: None,
+            }),
+            pollee: Pollee::new(),
+        }
+    }
+
+    /// Returns the attributes.
+    pub fn attr(&self) -> &PosixMessageQueueAttr {
+        &self.attr
+    }
+
+    /// Returns the current status.
+    pub fn status(&self) -> PosixMessageQueueStatus {
+        let inner = self.inner.lock();
+        PosixMessageQueueStatus {
+            num_messages: inner.num_messages,
+            total_len: inner.total_len,
+            notification: inner
+                .notification
+                .as_ref()
+                .filter(|notification| notification.process.strong_count() > 0)
+                .map(|notification| (notification.pid, notification.method)),
+        }
+    }
+
+    /// Sends a message with the priority.
+    ///
+    /// If the queue is full, this method waits until the queue has space or the timeout
+    /// expires, unless `is_nonblocking` is true.
+    ///
+    /// `sender` is the PID and the UID that are reported to the notified process.
+    pub fn send(
+        &self,
+        data: &[u8],
+        priority: u32,
+        is_nonblocking: bool,
+        timeout: Option<&Duration>,
+        sender: (Pid, Uid),
+    ) -> Result<()> {
+        if data.len() > self.attr.max_message_size {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too long");
+        }
+        if priority >= MQ_PRIO_MAX {
+            return_errno_with_message!(Errno::EINVAL, "the priority is too high");
+        }
+
+        if is_nonblocking {
+            self.try_send(data, priority, sender)
+        } else {
+            self.wait_events(IoEvents::OUT, timeout, || {
+                self.try_send(data, priority, sender)
+            })
+        }
+    }
+
+    fn try_send(&self, data: &[u8], priority: u32, sender: (Pid, Uid)) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.num_messages >= self.attr.max_messages {
+            return_errno_with_message!(Errno::EAGAIN, "the queue is full");
+        }
+
+        inner
+            .messages
+            .entry(priority)
+            .or_default()
+            .push_back(data.to_vec());
+        inner.num_messages += 1;
+        inner.total_len += data.len();
+
+        let notification = if inner.num_messages == 1 && inner.num_waiting_receivers == 0 {
+            inner.notification.take()
+        } else {
+            None
+        };
+        drop(inner);
+
+        self.pollee.notify(IoEvents::IN);
+
+        if let Some(notification) = notification {
+            notification.notify(sender);
+        }
+
+        Ok(())
+    }
+
+    /// Receives the oldest message of the highest priority.
+    ///
+    /// If the queue is empty, this method waits until a message arrives or the timeout
+    /// expires, unless `is_nonblocking` is true.
+    ///
+    /// This method returns the message and its priority.
+    pub fn receive(
+        &self,
+        is_nonblocking: bool,
+        timeout: Option<&Duration>,
+    ) -> Result<(Vec<u8>, u32)> {
+        if is_nonblocking {
+            return self.try_receive();
+        }
+
+        // The waiting receivers take precedence over the registered process.
+        self.inner.lock().num_waiting_receivers += 1;
+        let result = self.wait_events(IoEvents::IN, timeout, || self.try_receive());
+        self.inner.lock().num_waiting_receivers -= 1;
+
+        result
+    }
+
+    fn try_receive(&self) -> Result<(Vec<u8>, u32)> {
+        let mut inner = self.inner.lock();
+
+        let Some(mut entry) = inner.messages.last_entry() else {
+            return_errno_with_message!(Errno::EAGAIN, "the queue is empty");
+        };
+        let priority = *entry.key();
+        let data = entry.get_mut().pop_front().unwrap();
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+
+        inner.num_messages -= 1;
+        inner.total_len -= data.len();
+        drop(inner);
+
+        self.pollee.notify(IoEvents::OUT);
+
+        Ok((data, priority))
+    }
+
+    /// Registers the process to be notified, or unregisters the process with the PID if
+    /// `notification` is `None`.
+    pub fn set_notification(&self, notification: Option<MqNotification>, pid: Pid) -> Result<()> {
+        let mut inner = self.inner.lock();
+
+        // The registration of an exited process is no longer valid.
+        let current = inner
+            .notification
+            .as_ref()
+            .filter(|notification| notification.process.strong_count() > 0);
+
+        match notification {
+            Some(notification) => {
+                if current.is_some() {
+                    return_errno_with_message!(Errno::EBUSY, "another process has been registered");
+                }
+                inner.notification = Some(notification);
+            }
+            None => {
+                if current.is_none_or(|notification| notification.pid == pid) {
+                    inner.notification = None;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Pollable for PosixMessageQueue {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee.poll_with(mask, poller, || {
+            let inner = self.inner.lock();
+
+            let mut events = IoEvents::empty();
+            if inner.num_messages > 0 {
+                events |= IoEvents::IN;
+            }
+            if inner.num_messages < self.attr.max_messages {
+                events |= IoEvents::OUT;
+            }
+            events
+        })
+    }
+}
+
+impl Debug for PosixMessageQueue {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("PosixMessageQueue")
+            .field("attr", &self.attr)
+            .finish_non_exhaustive()
+    }
+}
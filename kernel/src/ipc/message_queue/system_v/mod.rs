@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System V message queue.
+
+mod queue;
+mod table;
+
+pub use queue::{Message, MessageQueue, MessageQueueStatus};
+pub use table::MessageQueueTable;
+
+// The following constant values are derived from the default values in Linux.
+
+/// Maximum number of message queues.
+pub const MSGMNI: usize = 32000;
+/// Maximum size of a message, in bytes.
+pub const MSGMAX: usize = 8192;
+/// Default maximum size of a message queue, in bytes.
+pub const MSGMNB: usize = 16384;
+
+bitflags! {
+    /// The flags of `msgrcv`.
+    pub struct MsgFlags: u32 {
+        /// Truncate the message if it is too long.
+        const MSG_NOERROR = 0o10000;
+        /// Receive the first message whose type is not the given type.
+        const MSG_EXCEPT = 0o20000;
+        /// Copy the message at the given index without removing it.
+        const MSG_COPY = 0o40000;
+    }
+}
+
+/// The rule to select the message to receive in `msgrcv`.
+#[derive(Debug, Clone, Copy)]
+pub enum MessageSelector {
+    /// The first message in the queue.
+    First,
+    /// The first message of the type.
+    Type(i64),
+    /// The first message whose type is not the type.
+    NotType(i64),
+    /// The first message of the lowest type that is less than or equal to the type.
+    LowestType(i64),
+    /// The message at the index, which is used by `MSG_COPY`.
+    Index(usize),
+}
+
+impl MessageSelector {
+    /// Creates the selector from the arguments of `msgrcv`.
+    pub fn new(msgtyp: i64, flags: MsgFlags) -> Self {
+        if flags.contains(MsgFlags::MSG_COPY) {
+            Self::Index(msgtyp as usize)
+        } else if msgtyp == 0 {
+            Self::First
+        } else if msgtyp < 0 {
+            Self::LowestType(msgtyp.saturating_neg())
+        } else if flags.contains(MsgFlags::MSG_EXCEPT) {
+            Self::NotType(msgtyp)
+        } else {
+            Self::Type(msgtyp)
+        }
+    }
+}
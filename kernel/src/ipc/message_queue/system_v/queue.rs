@@ -0,0 +1,340 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_rights::ReadOp;
+use ostd::sync::WaitQueue;
+
+use super::{MessageSelector, MsgFlags, MSGMNB};
+use crate::{
+    ipc::{key_t, IpcPermission},
+    prelude::*,
+    process::{credentials::capabilities::CapSet, Credentials, Gid, Pid, Uid},
+    time::clocks::RealTimeCoarseClock,
+};
+
+/// A System V message queue.
+pub struct MessageQueue {
+    /// The identifier of the queue
+    id: i32,
+    inner: Mutex<MessageQueueInner>,
+    /// Senders that are waiting for the queue to have enough space
+    send_wait_queue: WaitQueue,
+    /// Receivers that are waiting for the queue to have the wanted message
+    recv_wait_queue: WaitQueue,
+}
+
+struct MessageQueueInner {
+    permission: IpcPermission,
+    messages: VecDeque<Message>,
+    /// Number of bytes of all the messages in the queue
+    cbytes: usize,
+    /// Maximum number of bytes allowed in the queue
+    qbytes: usize,
+    /// Last send time
+    stime: u64,
+    /// Last receive time
+    rtime: u64,
+    /// Creation time or last modification via `msgctl`
+    ctime: u64,
+    /// The PID of the last process that sent a message
+    lspid: Pid,
+    /// The PID of the last process that received a message
+    lrpid: Pid,
+    /// Whether the queue has been removed by `IPC_RMID`
+    is_removed: bool,
+}
+
+/// A message in a System V message queue.
+#[derive(Debug, Clone)]
+pub struct Message {
+    mtype: i64,
+    data: Vec<u8>,
+}
+
+impl Message {
+    /// Returns the type of the message, which is always positive.
+    pub fn mtype(&self) -> i64 {
+        self.mtype
+    }
+
+    /// Returns the contents of the message.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// The status of a System V message queue, as reported by `IPC_STAT`.
+#[derive(Debug, Clone)]
+pub struct MessageQueueStatus {
+    pub permission: IpcPermission,
+    pub stime: u64,
+    pub rtime: u64,
+    pub ctime: u64,
+    pub cbytes: usize,
+    pub qnum: usize,
+    pub qbytes: usize,
+    pub lspid: Pid,
+    pub lrpid: Pid,
+}
+
+impl MessageQueue {
+    pub(super) fn new(id: i32, key: key_t, mode: u16, credentials: &Credentials<ReadOp>) -> Self {
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
+
+        Self {
+            id,
+            inner: Mutex::new(MessageQueueInner {
+                permission,
+                messages: VecDeque::new(),
+                cbytes: 0,
+                qbytes: MSGMNB,
+                stime: 0,
+                rtime: 0,
+                ctime: now_secs(),
+                lspid: 0,
+                lrpid: 0,
+                is_removed: false,
+            }),
+            send_wait_queue: WaitQueue::new(),
+            recv_wait_queue: WaitQueue::new(),
+        }
+    }
+
+    /// Returns the identifier.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Returns the current status.
+    pub fn status(&self) -> MessageQueueStatus {
+        let inner = self.inner.lock();
+        MessageQueueStatus {
+            permission: inner.permission.clone(),
+            stime: inner.stime,
+            rtime: inner.rtime,
+            ctime: inner.ctime,
+            cbytes: inner.cbytes,
+            qnum: inner.messages.len(),
+            qbytes: inner.qbytes,
+            lspid: inner.lspid,
+            lrpid: inner.lrpid,
+        }
+    }
+
+    /// Checks whether the credentials are granted the `access` to the queue.
+    pub fn check_access(&self, credentials: &Credentials<ReadOp>, access: u16) -> Result<()> {
+        self.inner
+            .lock()
+            .permission
+            .check_access(credentials, access)
+    }
+
+    /// Changes the owner, the permission bits and the maximum size, as `IPC_SET` does.
+    pub fn set_attributes(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        uid: Uid,
+        gid: Gid,
+        mode: u16,
+        qbytes: usize,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+        inner.permission.check_owner(credentials)?;
+        if qbytes > MSGMNB
+            && qbytes > inner.qbytes
+            && !credentials
+                .effective_capset()
+                .contains(CapSet::SYS_RESOURCE)
+        {
+            return_errno_with_message!(Errno::EPERM, "the queue size exceeds the limit");
+        }
+
+        inner.permission.set_owner_and_mode(uid, gid, mode);
+        inner.qbytes = qbytes;
+        inner.ctime = now_secs();
+        drop(inner);
+
+        // The queue may have more space now.
+        self.send_wait_queue.wake_all();
+
+        Ok(())
+    }
+
+    /// Sends a message of the type.
+    ///
+    /// If the queue does not have enough space, this method waits for it unless `is_nonblocking`
+    /// is true.
+    pub fn send(
+        &self,
+        mtype: i64,
+        data: &[u8],
+        is_nonblocking: bool,
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+    ) -> Result<()> {
+        if is_nonblocking {
+            return self.try_send(mtype, data, credentials, pid);
+        }
+
+        self.send_wait_queue
+            .pause_until(|| match self.try_send(mtype, data, credentials, pid) {
+                Err(err) if err.error() == Errno::EAGAIN => None,
+                result => Some(result),
+            })?
+    }
+
+    fn try_send(
+        &self,
+        mtype: i64,
+        data: &[u8],
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.is_removed {
+            return_errno_with_message!(Errno::EIDRM, "the queue has been removed");
+        }
+        inner.permission.check_access(credentials, 0o2)?;
+
+        // Like Linux, the number of messages is also limited by `qbytes`, so that the queue
+        // cannot be flooded with empty messages.
+        if inner.cbytes + data.len() > inner.qbytes || inner.messages.len() + 1 > inner.qbytes {
+            return_errno_with_message!(Errno::EAGAIN, "the queue is full");
+        }
+
+        inner.messages.push_back(Message {
+            mtype,
+            data: data.to_vec(),
+        });
+        inner.cbytes += data.len();
+        inner.stime = now_secs();
+        inner.lspid = pid;
+        drop(inner);
+
+        self.recv_wait_queue.wake_all();
+
+        Ok(())
+    }
+
+    /// Receives a message selected by `selector`.
+    ///
+    /// If there is no such message, this method waits for it unless `is_nonblocking` is true, in
+    /// which case `ENOMSG` is returned. If the message is longer than `max_len`, it is truncated
+    /// when `MSG_NOERROR` is specified, or `E2BIG` is returned otherwise.
+    pub fn receive(
+        &self,
+        max_len: usize,
+        selector: MessageSelector,
+        flags: MsgFlags,
+        is_nonblocking: bool,
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+    ) -> Result<Message> {
+        let result = if is_nonblocking {
+            self.try_receive(max_len, selector, flags, credentials, pid)
+        } else {
+            self.recv_wait_queue.pause_until(|| {
+                match self.try_receive(max_len, selector, flags, credentials, pid) {
+                    Err(err) if err.error() == Errno::EAGAIN => None,
+                    result => Some(result),
+                }
+            })?
+        };
+
+        match result {
+            Err(err) if err.error() == Errno::EAGAIN => {
+                return_errno_with_message!(Errno::ENOMSG, "no message is available")
+            }
+            result => result,
+        }
+    }
+
+    fn try_receive(
+        &self,
+        max_len: usize,
+        selector: MessageSelector,
+        flags: MsgFlags,
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+    ) -> Result<Message> {
+        let mut inner = self.inner.lock();
+        if inner.is_removed {
+            return_errno_with_message!(Errno::EIDRM, "the queue has been removed");
+        }
+        inner.permission.check_access(credentials, 0o4)?;
+
+        let Some(index) = find_message(&inner.messages, selector) else {
+            return_errno_with_message!(Errno::EAGAIN, "no message is available");
+        };
+
+        let message_len = inner.messages[index].data.len();
+        if message_len > max_len && !flags.contains(MsgFlags::MSG_NOERROR) {
+            return_errno_with_message!(Errno::E2BIG, "the message is too long");
+        }
+
+        let mut message = if flags.contains(MsgFlags::MSG_COPY) {
+            inner.messages[index].clone()
+        } else {
+            let message = inner.messages.remove(index).unwrap();
+            inner.cbytes -= message_len;
+            inner.rtime = now_secs();
+            inner.lrpid = pid;
+            drop(inner);
+
+            self.send_wait_queue.wake_all();
+
+            message
+        };
+        message.data.truncate(max_len);
+
+        Ok(message)
+    }
+
+    /// Marks the queue as removed and wakes up all the waiters.
+    pub(super) fn mark_removed(&self, credentials: &Credentials<ReadOp>) -> Result<()> {
+        let mut inner = self.inner.lock();
+        inner.permission.check_owner(credentials)?;
+        inner.is_removed = true;
+        inner.messages.clear();
+        inner.cbytes = 0;
+        drop(inner);
+
+        self.send_wait_queue.wake_all();
+        self.recv_wait_queue.wake_all();
+
+        Ok(())
+    }
+
+    pub(super) fn key(&self) -> key_t {
+        self.inner.lock().permission.key()
+    }
+}
+
+impl Debug for MessageQueue {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("MessageQueue")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+fn find_message(messages: &VecDeque<Message>, selector: MessageSelector) -> Option<usize> {
+    match selector {
+        MessageSelector::First => (!messages.is_empty()).then_some(0),
+        MessageSelector::Type(mtype) => messages.iter().position(|message| message.mtype == mtype),
+        MessageSelector::NotType(mtype) => {
+            messages.iter().position(|message| message.mtype != mtype)
+        }
+        MessageSelector::LowestType(max_mtype) => messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| message.mtype <= max_mtype)
+            // `min_by_key` returns the first one of the equal elements.
+            .min_by_key(|(_, message)| message.mtype)
+            .map(|(index, _)| index),
+        MessageSelector::Index(index) => (index < messages.len()).then_some(index),
+    }
+}
+
+fn now_secs() -> u64 {
+    RealTimeCoarseClock::get().read_time().as_secs()
+}
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::collections::btree_map::BTreeMap;
+
+use aster_rights::ReadOp;
+use id_alloc::IdAlloc;
+
+use super::{MessageQueue, MSGMNI};
+use crate::{
+    ipc::{key_t, IpcFlags, IPC_PRIVATE},
+    prelude::*,
+    process::Credentials,
+};
+
+/// The System V message queues in an IPC namespace.
+pub struct MessageQueueTable {
+    id_allocator: SpinLock<IdAlloc>,
+    queues: Mutex<BTreeMap<i32, Arc<MessageQueue>>>,
+}
+
+impl MessageQueueTable {
+    pub(in crate::ipc) fn new() -> Self {
+        let mut id_allocator = IdAlloc::with_capacity(MSGMNI + 1);
+        // Remove the first index 0
+        id_allocator.alloc();
+
+        Self {
+            id_allocator: SpinLock::new(id_allocator),
+            queues: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Gets the identifier of the queue with `key`, and creates the queue if necessary.
+    ///
+    /// This implements the semantics of `msgget`, where `mode` contains the permission bits
+    /// to create the queue with, or the permission bits to check against the existing one.
+    pub fn get_or_create(
+        &self,
+        key: key_t,
+        flags: IpcFlags,
+        mode: u16,
+        credentials: &Credentials<ReadOp>,
+    ) -> Result<i32> {
+        let mut queues = self.queues.lock();
+
+        if key != IPC_PRIVATE
+            && let Some(queue) = queues.values().find(|queue| queue.key() == key)
+        {
+            if flags.contains(IpcFlags::IPC_CREAT | IpcFlags::IPC_EXCL) {
+                return_errno_with_message!(Errno::EEXIST, "the queue already exists");
+            }
+            let access = (mode >> 6) | (mode >> 3) | mode;
+            queue.check_access(credentials, access & 0o7)?;
+
+            return Ok(queue.id());
+        }
+
+        if key != IPC_PRIVATE && !flags.contains(IpcFlags::IPC_CREAT) {
+            return_errno_with_message!(Errno::ENOENT, "the queue does not exist");
+        }
+
+        let id = self
+            .id_allocator
+            .lock()
+            .alloc()
+            .ok_or(Error::with_message(Errno::ENOSPC, "too many queues"))? as i32;
+        queues.insert(id, Arc::new(MessageQueue::new(id, key, mode, credentials)));
+
+        Ok(id)
+    }
+
+    /// Gets the queue with the identifier.
+    pub fn get(&self, id: i32) -> Result<Arc<MessageQueue>> {
+        self.queues
+            .lock()
+            .get(&id)
+            .cloned()
+            .ok_or(Error::with_message(
+                Errno::EINVAL,
+                "the queue does not exist",
+            ))
+    }
+
+    /// Removes the queue with the identifier, as `IPC_RMID` does.
+    ///
+    /// The processes waiting on the queue will be woken up with `EIDRM`.
+    pub fn remove(&self, id: i32, credentials: &Credentials<ReadOp>) -> Result<()> {
+        let mut queues = self.queues.lock();
+        let queue = queues.get(&id).ok_or(Error::with_message(
+            Errno::EINVAL,
+            "the queue does not exist",
+        ))?;
+
+        queue.mark_removed(credentials)?;
+        queues.remove(&id);
+        self.id_allocator.lock().free(id as usize);
+
+        Ok(())
+    }
+
+    /// Returns all the queues, in ascending order of their identifiers.
+    pub fn queues(&self) -> Vec<Arc<MessageQueue>> {
+        self.queues.lock().values().cloned().collect()
+    }
+}
@@ -1,15 +1,30 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_rights::ReadOp;
+
 use crate::{
     prelude::*,
-    process::{Gid, Uid},
+    process::{credentials::capabilities::CapSet, Credentials, Gid, Uid},
 };
 
+pub mod message_queue;
+mod namespace;
 pub mod semaphore;
+pub mod shm;
+
+pub use namespace::{init_ipc_ns, IpcNamespace};
 
 #[allow(non_camel_case_types)]
 pub type key_t = i32;
 
+/// The key that always creates a new IPC object.
+pub const IPC_PRIVATE: key_t = 0;
+
+/// The flag in the control commands that selects the new version of the structures.
+///
+/// All the architectures supported by us use the new version, so the flag is ignored.
+pub const IPC_64: i32 = 0x100;
+
 bitflags! {
     pub struct IpcFlags: u32{
         /// Create key if key does not exist
@@ -40,7 +55,7 @@ pub enum IpcControlCmd {
     SEM_SETALL = 17,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct IpcPermission {
     key: key_t,
@@ -56,6 +71,42 @@ pub struct IpcPermission {
     mode: u16,
 }
 
+/// The permission of an IPC object in the user space (i.e., `struct ipc64_perm`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct ipc64_perm {
+    pub key: key_t,
+    pub uid: u32,
+    pub gid: u32,
+    pub cuid: u32,
+    pub cgid: u32,
+    pub mode: u32,
+    pub seq: u16,
+    pub __pad2: u16,
+    pub __pad3: u32,
+    pub __unused1: u64,
+    pub __unused2: u64,
+}
+
+impl From<&IpcPermission> for ipc64_perm {
+    fn from(permission: &IpcPermission) -> Self {
+        Self {
+            key: permission.key,
+            uid: permission.uid.into(),
+            gid: permission.gid.into(),
+            cuid: permission.cuid.into(),
+            cgid: permission.cguid.into(),
+            mode: permission.mode as u32,
+            seq: 0,
+            __pad2: 0,
+            __pad3: 0,
+            __unused1: 0,
+            __unused2: 0,
+        }
+    }
+}
+
 impl IpcPermission {
     pub fn key(&self) -> key_t {
         self.key
@@ -86,7 +137,7 @@ impl IpcPermission {
         self.mode
     }
 
-    pub(self) fn new_sem_perm(key: key_t, uid: Uid, gid: Gid, mode: u16) -> Self {
+    pub(self) fn new(key: key_t, uid: Uid, gid: Gid, mode: u16) -> Self {
         Self {
             key,
             uid,
@@ -96,8 +147,65 @@ impl IpcPermission {
             mode,
         }
     }
-}
 
-pub(super) fn init() {
-    semaphore::init();
+    pub(self) fn set_key(&mut self, key: key_t) {
+        self.key = key;
+    }
+
+    /// Sets the owner and the permission bits, as `IPC_SET` does.
+    ///
+    /// The bits of `mode` that are not permission bits are preserved.
+    pub(self) fn set_owner_and_mode(&mut self, uid: Uid, gid: Gid, mode: u16) {
+        self.uid = uid;
+        self.gid = gid;
+        self.mode = (self.mode & !0o777) | (mode & 0o777);
+    }
+
+    pub(self) fn set_mode(&mut self, mode: u16) {
+        self.mode = mode;
+    }
+
+    /// Checks whether the credentials are granted the `access` (i.e., the combination of
+    /// `0o4` for reading and `0o2` for writing) to the IPC object.
+    pub fn check_access(&self, credentials: &Credentials<ReadOp>, access: u16) -> Result<()> {
+        let euid = credentials.euid();
+        let granted = if euid == self.uid || euid == self.cuid {
+            self.mode >> 6
+        } else if self.is_in_group(credentials) {
+            self.mode >> 3
+        } else {
+            self.mode
+        };
+
+        if access & !granted & 0o7 != 0
+            && !credentials.effective_capset().contains(CapSet::IPC_OWNER)
+        {
+            return_errno_with_message!(Errno::EACCES, "the IPC object is not accessible");
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the credentials can change or remove the IPC object.
+    pub fn check_owner(&self, credentials: &Credentials<ReadOp>) -> Result<()> {
+        let euid = credentials.euid();
+        if euid != self.uid
+            && euid != self.cuid
+            && !credentials.effective_capset().contains(CapSet::SYS_ADMIN)
+        {
+            return_errno_with_message!(Errno::EPERM, "the IPC object is not owned by the user");
+        }
+
+        Ok(())
+    }
+
+    fn is_in_group(&self, credentials: &Credentials<ReadOp>) -> bool {
+        let egid = credentials.egid();
+        if egid == self.gid || egid == self.cguid {
+            return true;
+        }
+
+        let groups = credentials.groups();
+        groups.contains(&self.gid) || groups.contains(&self.cguid)
+    }
 }
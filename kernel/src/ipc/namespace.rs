@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::{
+    message_queue::system_v::MessageQueueTable, semaphore::system_v::sem_set::SemaphoreSetTable,
+    shm::ShmSegmentTable,
+};
+use crate::{
+    fs::{mqueue::MqueueFs, path::MountNode},
+    prelude::*,
+    process::namespace::alloc_ns_id,
+};
+
+/// An IPC namespace, which isolates the System V IPC objects and the POSIX message queues.
+pub struct IpcNamespace {
+    /// The System V semaphore sets.
+    sem_table: SemaphoreSetTable,
+    /// The System V shared memory segments.
+    shm_table: Arc<ShmSegmentTable>,
+    /// The System V message queues.
+    msg_table: MessageQueueTable,
+    /// The file system of the POSIX message queues.
+    mqueue_fs: Arc<MqueueFs>,
+    /// The internal mount of `mqueue_fs`, which is used to open the queues by `mq_open`.
+    mqueue_mount: Arc<MountNode>,
+    /// The unique ID, which is shown as the inode number of `/proc/[pid]/ns/ipc`.
+    id: u64,
+}
+
+static INIT_IPC_NS: Once<Arc<IpcNamespace>> = Once::new();
+
+/// Returns the initial IPC namespace.
+pub fn init_ipc_ns() -> &'static Arc<IpcNamespace> {
+    INIT_IPC_NS.call_once(IpcNamespace::new)
+}
+
+impl IpcNamespace {
+    /// Creates an IPC namespace without any IPC objects.
+    pub fn new() -> Arc<Self> {
+        let mqueue_fs = MqueueFs::new();
+        let mqueue_mount = MountNode::new_root(mqueue_fs.clone());
+
+        Arc::new(Self {
+            sem_table: SemaphoreSetTable::new(),
+            shm_table: ShmSegmentTable::new(),
+            msg_table: MessageQueueTable::new(),
+            mqueue_fs,
+            mqueue_mount,
+            id: alloc_ns_id(),
+        })
+    }
+
+    /// Returns the System V semaphore sets.
+    pub fn sem_table(&self) -> &SemaphoreSetTable {
+        &self.sem_table
+    }
+
+    /// Returns the System V shared memory segments.
+    pub fn shm_table(&self) -> &Arc<ShmSegmentTable> {
+        &self.shm_table
+    }
+
+    /// Returns the System V message queues.
+    pub fn msg_table(&self) -> &MessageQueueTable {
+        &self.msg_table
+    }
+
+    /// Returns the file system of the POSIX message queues.
+    pub fn mqueue_fs(&self) -> &Arc<MqueueFs> {
+        &self.mqueue_fs
+    }
+
+    /// Returns the internal mount of the file system of the POSIX message queues.
+    pub fn mqueue_mount(&self) -> &Arc<MountNode> {
+        &self.mqueue_mount
+    }
+
+    /// Returns the unique ID.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Debug for IpcNamespace {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("IpcNamespace")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
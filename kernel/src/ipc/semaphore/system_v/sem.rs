@@ -11,7 +11,7 @@ use ostd::sync::{PreemptDisabled, Waiter, Waker};
 
 use super::sem_set::{SemSetInner, SEMVMX};
 use crate::{
-    ipc::{key_t, semaphore::system_v::sem_set::sem_sets, IpcFlags},
+    ipc::{key_t, IpcFlags},
     prelude::*,
     process::Pid,
     time::{clocks::JIFFIES_TIMER_MANAGER, timer::Timeout},
@@ -153,7 +153,8 @@ pub fn sem_op(
         warn!("Found duplicate sop");
     }
 
-    let local_sem_sets = sem_sets();
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+    let local_sem_sets = ipc_ns.sem_table().sem_sets();
     let sem_set = local_sem_sets
         .get(&sem_id)
         .ok_or(Error::new(Errno::EINVAL))?;
@@ -205,7 +206,7 @@ pub fn sem_op(
         Status::Removed => Err(Error::new(Errno::EIDRM)),
         Status::Pending => {
             // FIXME: Getting sem_sets maybe time-consuming.
-            let sem_sets = sem_sets();
+            let sem_sets = ipc_ns.sem_table().sem_sets();
             let sem_set = sem_sets.get(&sem_id).ok_or(Error::new(Errno::EINVAL))?;
             let mut inner = sem_set.inner();
 
@@ -8,8 +8,7 @@ use core::{
 
 use aster_rights::ReadOp;
 use id_alloc::IdAlloc;
-use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
-use spin::Once;
+use ostd::sync::{PreemptDisabled, RwLockReadGuard};
 
 use super::{
     sem::{update_pending_alter, wake_const_ops, PendingOp, Status},
@@ -181,8 +180,7 @@ impl SemaphoreSet {
             sems.push(Semaphore::new(0));
         }
 
-        let permission =
-            IpcPermission::new_sem_perm(key, credentials.euid(), credentials.egid(), mode);
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
 
         Ok(Self {
             nsems,
@@ -218,97 +216,114 @@ impl Drop for SemaphoreSet {
             }
         }
         pending_const.clear();
-
-        ID_ALLOCATOR
-            .get()
-            .unwrap()
-            .lock()
-            .free(self.permission.key() as usize);
     }
 }
 
-pub fn create_sem_set_with_id(
-    id: key_t,
-    nsems: usize,
-    mode: u16,
-    credentials: Credentials<ReadOp>,
-) -> Result<()> {
-    debug_assert!(nsems <= SEMMSL);
-    debug_assert!(id > 0);
-    if id as usize > SEMMNI {
-        return_errno_with_message!(Errno::ENOENT, "id larger than SEMMNI");
-    }
-
-    ID_ALLOCATOR
-        .get()
-        .unwrap()
-        .lock()
-        .alloc_specific(id as usize)
-        .ok_or(Error::new(Errno::EEXIST))?;
-
-    let mut sem_sets = SEMAPHORE_SETS.write();
-    sem_sets.insert(id, SemaphoreSet::new(id, nsems, mode, credentials)?);
-
-    Ok(())
+/// The semaphore sets in an IPC namespace.
+pub struct SemaphoreSetTable {
+    id_allocator: SpinLock<IdAlloc>,
+    sem_sets: RwLock<BTreeMap<key_t, SemaphoreSet>>,
 }
 
-/// Checks the semaphore. Return Ok if the semaphore exists and pass the check.
-pub fn check_sem(id: key_t, nsems: Option<usize>, required_perm: PermissionMode) -> Result<()> {
-    debug_assert!(id > 0);
+impl SemaphoreSetTable {
+    pub(in crate::ipc) fn new() -> Self {
+        let mut id_allocator = IdAlloc::with_capacity(SEMMNI + 1);
+        // Remove the first index 0
+        id_allocator.alloc();
 
-    let sem_sets = SEMAPHORE_SETS.read();
-    let sem_set = sem_sets.get(&id).ok_or(Error::new(Errno::ENOENT))?;
+        Self {
+            id_allocator: SpinLock::new(id_allocator),
+            sem_sets: RwLock::new(BTreeMap::new()),
+        }
+    }
 
-    if let Some(nsems) = nsems {
+    pub fn create_sem_set_with_id(
+        &self,
+        id: key_t,
+        nsems: usize,
+        mode: u16,
+        credentials: Credentials<ReadOp>,
+    ) -> Result<()> {
         debug_assert!(nsems <= SEMMSL);
-        if nsems > sem_set.nsems() {
-            return_errno!(Errno::EINVAL);
+        debug_assert!(id > 0);
+        if id as usize > SEMMNI {
+            return_errno_with_message!(Errno::ENOENT, "id larger than SEMMNI");
         }
-    }
 
-    if !required_perm.is_empty() {
-        // TODO: Support permission check
-        warn!("Semaphore doesn't support permission check now");
+        self.id_allocator
+            .lock()
+            .alloc_specific(id as usize)
+            .ok_or(Error::new(Errno::EEXIST))?;
+
+        let mut sem_sets = self.sem_sets.write();
+        sem_sets.insert(id, SemaphoreSet::new(id, nsems, mode, credentials)?);
+
+        Ok(())
     }
 
-    Ok(())
-}
+    /// Checks the semaphore. Return Ok if the semaphore exists and pass the check.
+    pub fn check_sem(
+        &self,
+        id: key_t,
+        nsems: Option<usize>,
+        required_perm: PermissionMode,
+    ) -> Result<()> {
+        debug_assert!(id > 0);
+
+        let sem_sets = self.sem_sets.read();
+        let sem_set = sem_sets.get(&id).ok_or(Error::new(Errno::ENOENT))?;
+
+        if let Some(nsems) = nsems {
+            debug_assert!(nsems <= SEMMSL);
+            if nsems > sem_set.nsems() {
+                return_errno!(Errno::EINVAL);
+            }
+        }
 
-pub fn create_sem_set(nsems: usize, mode: u16, credentials: Credentials<ReadOp>) -> Result<key_t> {
-    debug_assert!(nsems <= SEMMSL);
+        if !required_perm.is_empty() {
+            // TODO: Support permission check
+            warn!("Semaphore doesn't support permission check now");
+        }
 
-    let id = ID_ALLOCATOR
-        .get()
-        .unwrap()
-        .lock()
-        .alloc()
-        .ok_or(Error::new(Errno::ENOSPC))? as i32;
+        Ok(())
+    }
 
-    let mut sem_sets = SEMAPHORE_SETS.write();
-    sem_sets.insert(id, SemaphoreSet::new(id, nsems, mode, credentials)?);
+    pub fn create_sem_set(
+        &self,
+        nsems: usize,
+        mode: u16,
+        credentials: Credentials<ReadOp>,
+    ) -> Result<key_t> {
+        debug_assert!(nsems <= SEMMSL);
 
-    Ok(id)
-}
+        let id = self
+            .id_allocator
+            .lock()
+            .alloc()
+            .ok_or(Error::new(Errno::ENOSPC))? as i32;
 
-pub fn sem_sets<'a>() -> RwLockReadGuard<'a, BTreeMap<key_t, SemaphoreSet>, PreemptDisabled> {
-    SEMAPHORE_SETS.read()
-}
+        let mut sem_sets = self.sem_sets.write();
+        sem_sets.insert(id, SemaphoreSet::new(id, nsems, mode, credentials)?);
 
-pub fn sem_sets_mut<'a>() -> RwLockWriteGuard<'a, BTreeMap<key_t, SemaphoreSet>, PreemptDisabled> {
-    SEMAPHORE_SETS.write()
-}
+        Ok(id)
+    }
 
-static ID_ALLOCATOR: Once<SpinLock<IdAlloc>> = Once::new();
+    /// Removes the semaphore set if `check_removable` succeeds.
+    pub fn remove_sem_set<F>(&self, id: key_t, check_removable: F) -> Result<()>
+    where
+        F: FnOnce(&SemaphoreSet) -> Result<()>,
+    {
+        let mut sem_sets = self.sem_sets.write();
+        let sem_set = sem_sets.get(&id).ok_or(Error::new(Errno::EINVAL))?;
+        check_removable(sem_set)?;
 
-/// Semaphore sets in system
-static SEMAPHORE_SETS: RwLock<BTreeMap<key_t, SemaphoreSet>> = RwLock::new(BTreeMap::new());
+        sem_sets.remove(&id);
+        self.id_allocator.lock().free(id as usize);
 
-pub(super) fn init() {
-    ID_ALLOCATOR.call_once(|| {
-        let mut id_alloc = IdAlloc::with_capacity(SEMMNI + 1);
-        // Remove the first index 0
-        id_alloc.alloc();
+        Ok(())
+    }
 
-        SpinLock::new(id_alloc)
-    });
+    pub fn sem_sets(&self) -> RwLockReadGuard<BTreeMap<key_t, SemaphoreSet>, PreemptDisabled> {
+        self.sem_sets.read()
+    }
 }
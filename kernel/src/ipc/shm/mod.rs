@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System V shared memory.
+
+mod segment;
+mod table;
+
+pub use segment::{ShmAttachment, ShmSegment, ShmSegmentStatus};
+pub use table::ShmSegmentTable;
+
+// The following constant values are derived from the default values in Linux.
+
+/// Maximum number of shared memory segments.
+pub const SHMMNI: usize = 4096;
+/// Minimum size of a shared memory segment.
+pub const SHMMIN: usize = 1;
+/// Maximum size of a shared memory segment.
+pub const SHMMAX: usize = usize::MAX - (1 << 24);
+
+/// The segment is marked to be destroyed after the last detach.
+///
+/// This bit is reported in the mode of the segment.
+pub const SHM_DEST: u16 = 0o1000;
+
+bitflags! {
+    /// The flags of `shmat`.
+    pub struct ShmFlags: u32 {
+        /// Attach the segment for read-only access.
+        const SHM_RDONLY = 0o10000;
+        /// Round the attach address down to `SHMLBA`.
+        const SHM_RND = 0o20000;
+        /// Take over the existing mappings in the region.
+        const SHM_REMAP = 0o40000;
+        /// Allow the contents of the segment to be executed.
+        const SHM_EXEC = 0o100000;
+    }
+}
@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_rights::{ReadOp, Rights};
+
+use super::{ShmSegmentTable, SHM_DEST};
+use crate::{
+    ipc::{key_t, IpcPermission, IPC_PRIVATE},
+    prelude::*,
+    process::{Credentials, Gid, Pid, Process, Uid},
+    time::clocks::RealTimeCoarseClock,
+    vm::vmo::{Vmo, VmoOptions},
+};
+
+/// A System V shared memory segment.
+pub struct ShmSegment {
+    /// The identifier of the segment
+    id: i32,
+    /// The size requested by the creator, in bytes
+    size: usize,
+    /// The pages of the segment
+    vmo: Vmo<Rights>,
+    /// The PID of the creator
+    cpid: Pid,
+    inner: Mutex<ShmSegmentInner>,
+    /// The table that the segment belongs to
+    table: Weak<ShmSegmentTable>,
+}
+
+struct ShmSegmentInner {
+    permission: IpcPermission,
+    /// Number of current attachments
+    nattch: usize,
+    /// Last attach time
+    atime: u64,
+    /// Last detach time
+    dtime: u64,
+    /// Creation time or last modification via `shmctl`
+    ctime: u64,
+    /// The PID of the last process that attached or detached the segment
+    lpid: Pid,
+}
+
+/// The status of a shared memory segment, as reported by `IPC_STAT`.
+#[derive(Debug, Clone)]
+pub struct ShmSegmentStatus {
+    pub permission: IpcPermission,
+    pub size: usize,
+    pub atime: u64,
+    pub dtime: u64,
+    pub ctime: u64,
+    pub cpid: Pid,
+    pub lpid: Pid,
+    pub nattch: usize,
+}
+
+impl ShmSegment {
+    pub(super) fn new(
+        id: i32,
+        key: key_t,
+        size: usize,
+        mode: u16,
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+        table: Weak<ShmSegmentTable>,
+    ) -> Result<Self> {
+        let vmo = VmoOptions::<Rights>::new(size).alloc()?;
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
+
+        Ok(Self {
+            id,
+            size,
+            vmo,
+            cpid: pid,
+            inner: Mutex::new(ShmSegmentInner {
+                permission,
+                nattch: 0,
+                atime: 0,
+                dtime: 0,
+                ctime: now_secs(),
+                lpid: 0,
+            }),
+            table,
+        })
+    }
+
+    /// Returns the identifier.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Returns the size requested by the creator, in bytes.
+    ///
+    /// The segment is always mapped in whole pages.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the VMO that holds the pages of the segment.
+    pub fn vmo(&self) -> &Vmo<Rights> {
+        &self.vmo
+    }
+
+    /// Returns the current status.
+    pub fn status(&self) -> ShmSegmentStatus {
+        let inner = self.inner.lock();
+        ShmSegmentStatus {
+            permission: inner.permission.clone(),
+            size: self.size,
+            atime: inner.atime,
+            dtime: inner.dtime,
+            ctime: inner.ctime,
+            cpid: self.cpid,
+            lpid: inner.lpid,
+            nattch: inner.nattch,
+        }
+    }
+
+    /// Checks whether the credentials are granted the `access` to the segment.
+    pub fn check_access(&self, credentials: &Credentials<ReadOp>, access: u16) -> Result<()> {
+        self.inner
+            .lock()
+            .permission
+            .check_access(credentials, access)
+    }
+
+    /// Changes the owner and the permission bits, as `IPC_SET` does.
+    pub fn set_owner_and_mode(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        uid: Uid,
+        gid: Gid,
+        mode: u16,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+        inner.permission.check_owner(credentials)?;
+        inner.permission.set_owner_and_mode(uid, gid, mode);
+        inner.ctime = now_secs();
+        Ok(())
+    }
+
+    pub(super) fn key(&self) -> key_t {
+        self.inner.lock().permission.key()
+    }
+
+    /// Marks the segment to be destroyed after the last detach.
+    ///
+    /// Returns whether the segment is not attached, in which case it should be destroyed at once.
+    pub(super) fn mark_destroyed(&self, credentials: &Credentials<ReadOp>) -> Result<bool> {
+        let mut inner = self.inner.lock();
+        inner.permission.check_owner(credentials)?;
+
+        // The key is released, so that a new segment can be created with the same key.
+        inner.permission.set_key(IPC_PRIVATE);
+        let mode = inner.permission.mode();
+        inner.permission.set_mode(mode | SHM_DEST);
+        inner.ctime = now_secs();
+
+        Ok(inner.nattch == 0)
+    }
+
+    pub(super) fn is_destroyed_and_detached(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.permission.mode() & SHM_DEST != 0 && inner.nattch == 0
+    }
+}
+
+impl Debug for ShmSegment {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("ShmSegment")
+            .field("id", &self.id)
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An attachment of a shared memory segment.
+///
+/// Each memory mapping of the segment holds an attachment, so the number of
+/// attachments is reported as the `shm_nattch` of the segment. Dropping the
+/// last attachment of a segment marked with [`SHM_DEST`] destroys the segment.
+#[derive(Debug)]
+pub struct ShmAttachment(Arc<ShmSegment>);
+
+impl ShmAttachment {
+    pub(super) fn new(segment: Arc<ShmSegment>, pid: Pid) -> Self {
+        {
+            let mut inner = segment.inner.lock();
+            inner.nattch += 1;
+            inner.atime = now_secs();
+            inner.lpid = pid;
+        }
+
+        Self(segment)
+    }
+
+    /// Returns the attached segment.
+    pub fn segment(&self) -> &Arc<ShmSegment> {
+        &self.0
+    }
+}
+
+impl Clone for ShmAttachment {
+    fn clone(&self) -> Self {
+        self.0.inner.lock().nattch += 1;
+        Self(self.0.clone())
+    }
+}
+
+impl Drop for ShmAttachment {
+    fn drop(&mut self) {
+        let should_destroy = {
+            let mut inner = self.0.inner.lock();
+            inner.nattch -= 1;
+            inner.dtime = now_secs();
+            if let Some(process) = Process::current() {
+                inner.lpid = process.pid();
+            }
+            inner.permission.mode() & SHM_DEST != 0 && inner.nattch == 0
+        };
+
+        if should_destroy && let Some(table) = self.0.table.upgrade() {
+            table.remove_destroyed(&self.0);
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    RealTimeCoarseClock::get().read_time().as_secs()
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::collections::btree_map::BTreeMap;
+
+use aster_rights::ReadOp;
+use id_alloc::IdAlloc;
+
+use super::{ShmAttachment, ShmSegment, SHMMAX, SHMMIN, SHMMNI};
+use crate::{
+    ipc::{key_t, IpcFlags, IPC_PRIVATE},
+    prelude::*,
+    process::{Credentials, Pid},
+};
+
+/// The shared memory segments in an IPC namespace.
+pub struct ShmSegmentTable {
+    id_allocator: SpinLock<IdAlloc>,
+    segments: Mutex<BTreeMap<i32, Arc<ShmSegment>>>,
+    weak_self: Weak<Self>,
+}
+
+impl ShmSegmentTable {
+    pub(in crate::ipc) fn new() -> Arc<Self> {
+        let mut id_allocator = IdAlloc::with_capacity(SHMMNI + 1);
+        // Remove the first index 0
+        id_allocator.alloc();
+
+        Arc::new_cyclic(|weak_self| Self {
+            id_allocator: SpinLock::new(id_allocator),
+            segments: Mutex::new(BTreeMap::new()),
+            weak_self: weak_self.clone(),
+        })
+    }
+
+    /// Gets the identifier of the segment with `key`, and creates the segment if necessary.
+    ///
+    /// This implements the semantics of `shmget`, where `mode` contains the permission bits
+    /// to create the segment with, or the permission bits to check against the existing one.
+    pub fn get_or_create(
+        &self,
+        key: key_t,
+        size: usize,
+        flags: IpcFlags,
+        mode: u16,
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+    ) -> Result<i32> {
+        let mut segments = self.segments.lock();
+
+        if key != IPC_PRIVATE
+            && let Some(segment) = segments.values().find(|segment| segment.key() == key)
+        {
+            if flags.contains(IpcFlags::IPC_CREAT | IpcFlags::IPC_EXCL) {
+                return_errno_with_message!(Errno::EEXIST, "the segment already exists");
+            }
+            if size > segment.size() {
+                return_errno_with_message!(Errno::EINVAL, "the segment is too small");
+            }
+            let access = (mode >> 6) | (mode >> 3) | mode;
+            segment.check_access(credentials, access & 0o7)?;
+
+            return Ok(segment.id());
+        }
+
+        if key != IPC_PRIVATE && !flags.contains(IpcFlags::IPC_CREAT) {
+            return_errno_with_message!(Errno::ENOENT, "the segment does not exist");
+        }
+        if !(SHMMIN..=SHMMAX).contains(&size) {
+            return_errno_with_message!(Errno::EINVAL, "the segment size is invalid");
+        }
+
+        let id =
+            self.id_allocator
+                .lock()
+                .alloc()
+                .ok_or(Error::with_message(Errno::ENOSPC, "too many segments"))? as i32;
+        let segment = match ShmSegment::new(
+            id,
+            key,
+            size,
+            mode,
+            credentials,
+            pid,
+            self.weak_self.clone(),
+        ) {
+            Ok(segment) => segment,
+            Err(err) => {
+                self.id_allocator.lock().free(id as usize);
+                return Err(err);
+            }
+        };
+        segments.insert(id, Arc::new(segment));
+
+        Ok(id)
+    }
+
+    /// Gets the segment with the identifier.
+    pub fn get(&self, id: i32) -> Result<Arc<ShmSegment>> {
+        self.segments
+            .lock()
+            .get(&id)
+            .cloned()
+            .ok_or(Error::with_message(
+                Errno::EINVAL,
+                "the segment does not exist",
+            ))
+    }
+
+    /// Attaches the segment with the identifier if the credentials are granted the `access`.
+    pub fn attach(
+        &self,
+        id: i32,
+        credentials: &Credentials<ReadOp>,
+        access: u16,
+        pid: Pid,
+    ) -> Result<ShmAttachment> {
+        // The lock is held, so that the segment cannot be destroyed before being attached.
+        let segments = self.segments.lock();
+        let segment = segments.get(&id).ok_or(Error::with_message(
+            Errno::EINVAL,
+            "the segment does not exist",
+        ))?;
+        segment.check_access(credentials, access)?;
+
+        Ok(ShmAttachment::new(segment.clone(), pid))
+    }
+
+    /// Removes the segment with the identifier, as `IPC_RMID` does.
+    ///
+    /// If the segment is still attached, it will be destroyed after the last detach.
+    pub fn remove(&self, id: i32, credentials: &Credentials<ReadOp>) -> Result<()> {
+        let mut segments = self.segments.lock();
+        let segment = segments.get(&id).ok_or(Error::with_message(
+            Errno::EINVAL,
+            "the segment does not exist",
+        ))?;
+
+        if segment.mark_destroyed(credentials)? {
+            segments.remove(&id);
+            self.id_allocator.lock().free(id as usize);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the segment that has been marked as destroyed and has been detached.
+    pub(super) fn remove_destroyed(&self, segment: &ShmSegment) {
+        let mut segments = self.segments.lock();
+        let id = segment.id();
+
+        let Some(current) = segments.get(&id) else {
+            return;
+        };
+        if !core::ptr::eq(current.as_ref(), segment) || !segment.is_destroyed_and_detached() {
+            return;
+        }
+
+        segments.remove(&id);
+        self.id_allocator.lock().free(id as usize);
+    }
+
+    /// Returns all the segments, in ascending order of their identifiers.
+    pub fn segments(&self) -> Vec<Arc<ShmSegment>> {
+        self.segments.lock().values().cloned().collect()
+    }
+}
@@ -93,6 +93,7 @@ pub fn main() {
 
 pub fn init() {
     util::random::init();
+    vm::init();
     driver::init();
     time::init();
     #[cfg(target_arch = "x86_64")]
@@ -100,7 +101,7 @@ pub fn init() {
     sched::init();
     fs::rootfs::init(boot_info().initramfs.expect("No initramfs found!")).unwrap();
     device::init().unwrap();
-    syscall::init();
+    fs::sysfs::init().unwrap();
     vdso::init();
     process::init();
 }
@@ -133,7 +134,6 @@ fn init_thread() {
     #[cfg(target_arch = "x86_64")]
     net::lazy_init();
     fs::lazy_init();
-    ipc::init();
     // driver::pci::virtio::block::block_device_test();
     let thread = ThreadOptions::new(|| {
         println!("[kernel] Hello world from kernel!");
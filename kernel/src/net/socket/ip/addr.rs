@@ -1,37 +1,86 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use aster_bigtcp::wire::{IpAddress, IpEndpoint, Ipv4Address};
+use aster_bigtcp::wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv6Address};
 
 use crate::{net::socket::SocketAddr, prelude::*, return_errno_with_message};
 
-impl TryFrom<SocketAddr> for IpEndpoint {
-    type Error = Error;
+impl From<IpEndpoint> for SocketAddr {
+    fn from(endpoint: IpEndpoint) -> Self {
+        let port = endpoint.port;
+        match endpoint.addr {
+            IpAddress::Ipv4(addr) => SocketAddr::IPv4(addr, port),
+            IpAddress::Ipv6(addr) => SocketAddr::IPv6(addr, port),
+        }
+    }
+}
+
+/// The address family of an IP socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpFamily {
+    /// `AF_INET` sockets, which communicate via IPv4.
+    Ipv4,
+    /// `AF_INET6` sockets, which communicate via IPv6.
+    ///
+    /// Unless `IPV6_V6ONLY` is set, the sockets can also communicate via IPv4 using IPv4-mapped
+    /// IPv6 addresses (i.e., `::ffff:a.b.c.d`).
+    Ipv6,
+}
 
-    fn try_from(value: SocketAddr) -> Result<Self> {
-        match value {
-            SocketAddr::IPv4(addr, port) => Ok(IpEndpoint::new(addr.into(), port)),
+impl IpFamily {
+    /// Converts a socket address to an endpoint that is used by the network stack.
+    ///
+    /// IPv4-mapped IPv6 addresses are converted to IPv4 endpoints. If the socket is only for
+    /// IPv6 (as specified by `is_ipv6_only`), they will be rejected with `errno_if_mapped`.
+    pub(super) fn endpoint_from(
+        self,
+        socket_addr: SocketAddr,
+        is_ipv6_only: bool,
+        errno_if_mapped: Errno,
+    ) -> Result<IpEndpoint> {
+        match (self, socket_addr) {
+            (Self::Ipv4, SocketAddr::IPv4(addr, port)) => {
+                Ok(IpEndpoint::new(IpAddress::Ipv4(addr), port))
+            }
+            (Self::Ipv6, SocketAddr::IPv6(addr, port)) => {
+                let Some(ipv4_addr) = addr.to_ipv4_mapped() else {
+                    return Ok(IpEndpoint::new(IpAddress::Ipv6(addr), port));
+                };
+                if is_ipv6_only {
+                    return_errno_with_message!(
+                        errno_if_mapped,
+                        "IPv4-mapped addresses cannot be used by IPv6-only sockets"
+                    );
+                }
+                Ok(IpEndpoint::new(IpAddress::Ipv4(ipv4_addr), port))
+            }
             _ => return_errno_with_message!(
                 Errno::EAFNOSUPPORT,
                 "the address is in an unsupported address family"
             ),
         }
     }
-}
 
-impl From<IpEndpoint> for SocketAddr {
-    fn from(endpoint: IpEndpoint) -> Self {
-        let port = endpoint.port;
-        match endpoint.addr {
-            IpAddress::Ipv4(addr) => SocketAddr::IPv4(addr, port),
-            // TODO: support IPv6
+    /// Converts an endpoint that is used by the network stack to a socket address.
+    ///
+    /// IPv4 endpoints are converted to IPv4-mapped IPv6 addresses for `AF_INET6` sockets.
+    pub(super) fn socket_addr_of(self, endpoint: IpEndpoint) -> SocketAddr {
+        match (self, endpoint.addr) {
+            (Self::Ipv6, IpAddress::Ipv4(addr)) => {
+                SocketAddr::IPv6(addr.to_ipv6_mapped(), endpoint.port)
+            }
+            _ => endpoint.into(),
         }
     }
-}
 
-/// A local endpoint, which indicates that the local endpoint is unspecified.
-///
-/// According to the Linux man pages and the Linux implementation, `getsockname()` will _not_ fail
-/// even if the socket is unbound. Instead, it will return an unspecified socket address. This
-/// unspecified endpoint helps with that.
-pub(super) const UNSPECIFIED_LOCAL_ENDPOINT: IpEndpoint =
-    IpEndpoint::new(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED), 0);
+    /// Returns the unspecified local endpoint.
+    ///
+    /// According to the Linux man pages and the Linux implementation, `getsockname()` will _not_
+    /// fail even if the socket is unbound. Instead, it will return an unspecified socket address.
+    /// This unspecified endpoint helps with that.
+    pub(super) const fn unspecified_endpoint(self) -> IpEndpoint {
+        match self {
+            Self::Ipv4 => IpEndpoint::new(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED), 0),
+            Self::Ipv6 => IpEndpoint::new(IpAddress::Ipv6(Ipv6Address::UNSPECIFIED), 0),
+        }
+    }
+}
@@ -13,16 +13,16 @@ use crate::{
 
 pub(super) fn get_iface_to_bind(ip_addr: &IpAddress) -> Option<Arc<Iface>> {
     let ifaces = IFACES.get().unwrap();
-    let IpAddress::Ipv4(ipv4_addr) = ip_addr;
+
+    // FIXME: A socket bound to the unspecified address should be able to receive packets from all
+    // ifaces. Now we use the virtio-net as the default interface.
+    if ip_addr.is_unspecified() {
+        return Some(ifaces[0].clone());
+    }
+
     ifaces
         .iter()
-        .find(|iface| {
-            if let Some(iface_ipv4_addr) = iface.ipv4_addr() {
-                iface_ipv4_addr == *ipv4_addr
-            } else {
-                false
-            }
-        })
+        .find(|iface| iface.has_ip_addr(*ip_addr))
         .map(Clone::clone)
 }
 
@@ -31,21 +31,25 @@ pub(super) fn get_iface_to_bind(ip_addr: &IpAddress) -> Option<Arc<Iface>> {
 /// Otherwise, we will use a default interface.
 fn get_ephemeral_iface(remote_ip_addr: &IpAddress) -> Arc<Iface> {
     let ifaces = IFACES.get().unwrap();
-    let IpAddress::Ipv4(remote_ipv4_addr) = remote_ip_addr;
-    if let Some(iface) = ifaces.iter().find(|iface| {
-        if let Some(iface_ipv4_addr) = iface.ipv4_addr() {
-            iface_ipv4_addr == *remote_ipv4_addr
-        } else {
-            false
-        }
-    }) {
+    if let Some(iface) = ifaces
+        .iter()
+        .find(|iface| iface.has_ip_addr(*remote_ip_addr))
+    {
         return iface.clone();
     }
     // FIXME: use the virtio-net as the default interface
     ifaces[0].clone()
 }
 
-pub(super) fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundPort> {
+/// Binds a port on a suitable iface for the endpoint.
+///
+/// If the endpoint has the unspecified IPv6 address, `is_ipv6_only` determines whether the port
+/// can also accept IPv4 packets.
+pub(super) fn bind_port(
+    endpoint: &IpEndpoint,
+    can_reuse: bool,
+    is_ipv6_only: bool,
+) -> Result<BoundPort> {
     let iface = match get_iface_to_bind(&endpoint.addr) {
         Some(iface) => iface,
         None => {
@@ -58,7 +62,9 @@ pub(super) fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundP
 
     let bind_port_config = BindPortConfig::new(endpoint.port, can_reuse);
 
-    Ok(iface.bind(bind_port_config)?)
+    let mut bound_port = iface.bind(endpoint.addr, bind_port_config)?;
+    bound_port.set_ipv6_only(is_ipv6_only);
+    Ok(bound_port)
 }
 
 impl From<BindError> for Error {
@@ -74,8 +80,18 @@ impl From<BindError> for Error {
     }
 }
 
-pub(super) fn get_ephemeral_endpoint(remote_endpoint: &IpEndpoint) -> IpEndpoint {
+pub(super) fn get_ephemeral_endpoint(remote_endpoint: &IpEndpoint) -> Result<IpEndpoint> {
     let iface = get_ephemeral_iface(&remote_endpoint.addr);
-    let ip_addr = iface.ipv4_addr().unwrap();
-    IpEndpoint::new(IpAddress::Ipv4(ip_addr), 0)
+    // The iface may have no address of the IP version, since addresses can be removed at runtime.
+    let ip_addr = match remote_endpoint.addr {
+        IpAddress::Ipv4(_) => iface.ipv4_addr().map(IpAddress::Ipv4),
+        IpAddress::Ipv6(_) => iface.ipv6_addr().map(IpAddress::Ipv6),
+    }
+    .ok_or_else(|| {
+        Error::with_message(
+            Errno::ENETUNREACH,
+            "the iface has no address to reach the remote endpoint",
+        )
+    })?;
+    Ok(IpEndpoint::new(ip_addr, 0))
 }
@@ -29,7 +29,7 @@ impl BoundDatagram {
     }
 
     pub fn local_endpoint(&self) -> IpEndpoint {
-        self.bound_socket.local_endpoint().unwrap()
+        self.bound_socket.local_endpoint()
     }
 
     pub fn remote_endpoint(&self) -> Option<IpEndpoint> {
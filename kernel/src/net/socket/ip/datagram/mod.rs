@@ -2,12 +2,12 @@
 
 use core::sync::atomic::{AtomicBool, Ordering};
 
-use aster_bigtcp::wire::IpEndpoint;
+use aster_bigtcp::{socket::NeedIfacePoll, wire::IpEndpoint};
 use ostd::sync::PreemptDisabled;
 use takeable::Takeable;
 
 use self::{bound::BoundDatagram, unbound::UnboundDatagram};
-use super::{common::get_ephemeral_endpoint, UNSPECIFIED_LOCAL_ENDPOINT};
+use super::{common::get_ephemeral_endpoint, options::IpOptionSet, IpFamily};
 use crate::{
     events::IoEvents,
     fs::{
@@ -39,17 +39,20 @@ pub(in crate::net) use self::observer::DatagramObserver;
 #[derive(Debug, Clone)]
 struct OptionSet {
     socket: SocketOptionSet,
+    ip: IpOptionSet,
     // TODO: UDP option set
 }
 
 impl OptionSet {
     fn new() -> Self {
         let socket = SocketOptionSet::new_udp();
-        OptionSet { socket }
+        let ip = IpOptionSet::new();
+        OptionSet { socket, ip }
     }
 }
 
 pub struct DatagramSocket {
+    family: IpFamily,
     options: RwLock<OptionSet>,
     inner: RwLock<Takeable<Inner>, PreemptDisabled>,
     is_nonblocking: AtomicBool,
@@ -66,6 +69,7 @@ impl Inner {
         self,
         endpoint: &IpEndpoint,
         can_reuse: bool,
+        is_ipv6_only: bool,
         observer: DatagramObserver,
     ) -> core::result::Result<BoundDatagram, (Error, Self)> {
         let unbound_datagram = match self {
@@ -78,10 +82,13 @@ impl Inner {
             }
         };
 
-        let bound_datagram = match unbound_datagram.bind(endpoint, can_reuse, observer) {
-            Ok(bound_datagram) => bound_datagram,
-            Err((err, unbound_datagram)) => return Err((err, Inner::Unbound(unbound_datagram))),
-        };
+        let bound_datagram =
+            match unbound_datagram.bind(endpoint, can_reuse, is_ipv6_only, observer) {
+                Ok(bound_datagram) => bound_datagram,
+                Err((err, unbound_datagram)) => {
+                    return Err((err, Inner::Unbound(unbound_datagram)))
+                }
+            };
         Ok(bound_datagram)
     }
 
@@ -94,15 +101,19 @@ impl Inner {
             return Ok(bound_datagram);
         }
 
-        let endpoint = get_ephemeral_endpoint(remote_endpoint);
-        self.bind(&endpoint, false, observer)
+        let endpoint = match get_ephemeral_endpoint(remote_endpoint) {
+            Ok(endpoint) => endpoint,
+            Err(err) => return Err((err, self)),
+        };
+        self.bind(&endpoint, false, false, observer)
     }
 }
 
 impl DatagramSocket {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+    pub fn new(family: IpFamily, is_nonblocking: bool) -> Arc<Self> {
         let unbound_datagram = UnboundDatagram::new();
         Arc::new(Self {
+            family,
             inner: RwLock::new(Takeable::new(Inner::Unbound(unbound_datagram))),
             is_nonblocking: AtomicBool::new(is_nonblocking),
             pollee: Pollee::new(),
@@ -118,6 +129,12 @@ impl DatagramSocket {
         self.is_nonblocking.store(is_nonblocking, Ordering::Relaxed);
     }
 
+    fn endpoint_from_remote(&self, socket_addr: SocketAddr) -> Result<IpEndpoint> {
+        let is_ipv6_only = self.options.read().ip.ipv6_only();
+        self.family
+            .endpoint_from(socket_addr, is_ipv6_only, Errno::ENETUNREACH)
+    }
+
     fn remote_endpoint(&self) -> Option<IpEndpoint> {
         let inner = self.inner.read();
 
@@ -160,9 +177,12 @@ impl DatagramSocket {
             return_errno_with_message!(Errno::EAGAIN, "the socket is not bound");
         };
 
-        let recv_bytes = bound_datagram
-            .try_recv(writer, flags)
-            .map(|(recv_bytes, remote_endpoint)| (recv_bytes, remote_endpoint.into()))?;
+        let recv_bytes =
+            bound_datagram
+                .try_recv(writer, flags)
+                .map(|(recv_bytes, remote_endpoint)| {
+                    (recv_bytes, self.family.socket_addr_of(remote_endpoint))
+                })?;
         self.pollee.invalidate();
 
         Ok(recv_bytes)
@@ -277,14 +297,20 @@ impl FileLike for DatagramSocket {
 
 impl Socket for DatagramSocket {
     fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
+        let (can_reuse, is_ipv6_only) = {
+            let options = self.options.read();
+            (options.socket.reuse_addr(), options.ip.ipv6_only())
+        };
+        let endpoint = self
+            .family
+            .endpoint_from(socket_addr, is_ipv6_only, Errno::EINVAL)?;
 
-        let can_reuse = self.options.read().socket.reuse_addr();
         let mut inner = self.inner.write();
         inner.borrow_result(|owned_inner| {
             let bound_datagram = match owned_inner.bind(
                 &endpoint,
                 can_reuse,
+                is_ipv6_only,
                 DatagramObserver::new(self.pollee.clone()),
             ) {
                 Ok(bound_datagram) => bound_datagram,
@@ -297,7 +323,7 @@ impl Socket for DatagramSocket {
     }
 
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
+        let endpoint = self.endpoint_from_remote(socket_addr)?;
 
         self.try_bind_ephemeral(&endpoint)?;
 
@@ -313,14 +339,18 @@ impl Socket for DatagramSocket {
     fn addr(&self) -> Result<SocketAddr> {
         let inner = self.inner.read();
         match inner.as_ref() {
-            Inner::Unbound(_) => Ok(UNSPECIFIED_LOCAL_ENDPOINT.into()),
-            Inner::Bound(bound_datagram) => Ok(bound_datagram.local_endpoint().into()),
+            Inner::Unbound(_) => Ok(self
+                .family
+                .socket_addr_of(self.family.unspecified_endpoint())),
+            Inner::Bound(bound_datagram) => {
+                Ok(self.family.socket_addr_of(bound_datagram.local_endpoint()))
+            }
         }
     }
 
     fn peer_addr(&self) -> Result<SocketAddr> {
         self.remote_endpoint()
-            .map(|endpoint| endpoint.into())
+            .map(|endpoint| self.family.socket_addr_of(endpoint))
             .ok_or_else(|| Error::with_message(Errno::ENOTCONN, "the socket is not connected"))
     }
 
@@ -337,12 +367,12 @@ impl Socket for DatagramSocket {
 
         let MessageHeader {
             addr,
-            control_message,
+            control_messages,
         } = message_header;
 
         let remote_endpoint = match addr {
             Some(remote_addr) => {
-                let endpoint = remote_addr.try_into()?;
+                let endpoint = self.endpoint_from_remote(remote_addr)?;
                 self.try_bind_ephemeral(&endpoint)?;
                 endpoint
             }
@@ -354,7 +384,7 @@ impl Socket for DatagramSocket {
             })?,
         };
 
-        if control_message.is_some() {
+        if !control_messages.is_empty() {
             // TODO: Support sending control message
             warn!("sending control message is not supported");
         }
@@ -377,7 +407,7 @@ impl Socket for DatagramSocket {
 
         // TODO: Receive control message
 
-        let message_header = MessageHeader::new(Some(peer_addr), None);
+        let message_header = MessageHeader::new(Some(peer_addr), Vec::new());
 
         Ok((received_bytes, message_header))
     }
@@ -391,14 +421,30 @@ impl Socket for DatagramSocket {
             _ => ()
         });
 
-        self.options.read().socket.get_option(option)
+        let options = self.options.read();
+        match options.socket.get_option(option) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res,
+        }
+        options.ip.get_option(self.family, option)
     }
 
     fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
         let mut options = self.options.write();
         let mut inner = self.inner.write();
 
-        match options.socket.set_option(option, inner.as_mut()) {
+        let res = match options.socket.set_option(option, inner.as_mut()) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => {
+                let is_bound = matches!(inner.as_ref(), Inner::Bound(_));
+                options
+                    .ip
+                    .set_option(self.family, option, is_bound)
+                    .map(|_| NeedIfacePoll::FALSE)
+            }
+            res => res,
+        };
+
+        match res {
             Err(e) => Err(e),
             Ok(need_iface_poll) => {
                 let iface_to_poll = need_iface_poll
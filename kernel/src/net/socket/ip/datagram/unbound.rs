@@ -18,9 +18,10 @@ impl UnboundDatagram {
         self,
         endpoint: &IpEndpoint,
         can_reuse: bool,
+        is_ipv6_only: bool,
         observer: DatagramObserver,
     ) -> core::result::Result<BoundDatagram, (Error, Self)> {
-        let bound_port = match bind_port(endpoint, can_reuse) {
+        let bound_port = match bind_port(endpoint, can_reuse, is_ipv6_only) {
             Ok(bound_port) => bound_port,
             Err(err) => return Err((err, self)),
         };
@@ -3,6 +3,7 @@
 mod addr;
 mod common;
 pub mod datagram;
+pub mod options;
 pub mod stream;
 
-use addr::UNSPECIFIED_LOCAL_ENDPOINT;
+pub use addr::IpFamily;
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::IpFamily;
+use crate::{
+    impl_socket_options, match_sock_option_mut, match_sock_option_ref,
+    net::socket::options::SocketOption, prelude::*,
+};
+
+impl_socket_options!(
+    pub struct Ipv6Only(bool);
+);
+
+/// IP-level options shared by TCP and UDP sockets.
+#[derive(Debug, Clone, Copy, CopyGetters)]
+#[get_copy = "pub(super)"]
+pub(super) struct IpOptionSet {
+    ipv6_only: bool,
+}
+
+impl IpOptionSet {
+    pub(super) fn new() -> Self {
+        // Linux uses `/proc/sys/net/ipv6/bindv6only` as the default value, which is by default
+        // false.
+        Self { ipv6_only: false }
+    }
+
+    pub(super) fn get_option(&self, family: IpFamily, option: &mut dyn SocketOption) -> Result<()> {
+        match_sock_option_mut!(option, {
+            ipv6_only: Ipv6Only => {
+                check_ipv6_family(family)?;
+                ipv6_only.set(self.ipv6_only);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to get is unknown")
+        });
+
+        Ok(())
+    }
+
+    /// Sets the option.
+    ///
+    /// `is_bound` indicates whether the socket has been bound to a local address. Some options
+    /// cannot be changed after that.
+    pub(super) fn set_option(
+        &mut self,
+        family: IpFamily,
+        option: &dyn SocketOption,
+        is_bound: bool,
+    ) -> Result<()> {
+        match_sock_option_ref!(option, {
+            ipv6_only: Ipv6Only => {
+                check_ipv6_family(family)?;
+                if is_bound {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "`IPV6_V6ONLY` cannot be changed after the socket is bound"
+                    );
+                }
+                self.ipv6_only = *ipv6_only.get().unwrap();
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to be set is unknown")
+        });
+
+        Ok(())
+    }
+}
+
+fn check_ipv6_family(family: IpFamily) -> Result<()> {
+    if family != IpFamily::Ipv6 {
+        return_errno_with_message!(
+            Errno::ENOPROTOOPT,
+            "IPv6 options are not available for IPv4 sockets"
+        );
+    }
+
+    Ok(())
+}
@@ -143,7 +143,7 @@ impl ConnectedStream {
     }
 
     pub fn local_endpoint(&self) -> IpEndpoint {
-        self.tcp_conn.local_endpoint().unwrap()
+        self.tcp_conn.local_endpoint()
     }
 
     pub fn remote_endpoint(&self) -> IpEndpoint {
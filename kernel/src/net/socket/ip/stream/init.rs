@@ -30,6 +30,7 @@ impl InitStream {
         self,
         endpoint: &IpEndpoint,
         can_reuse: bool,
+        is_ipv6_only: bool,
     ) -> core::result::Result<BoundPort, (Error, Self)> {
         match self {
             InitStream::Unbound => (),
@@ -41,7 +42,7 @@ impl InitStream {
             }
         };
 
-        let bound_port = match bind_port(endpoint, can_reuse) {
+        let bound_port = match bind_port(endpoint, can_reuse, is_ipv6_only) {
             Ok(bound_port) => bound_port,
             Err(err) => return Err((err, Self::Unbound)),
         };
@@ -53,8 +54,11 @@ impl InitStream {
         self,
         remote_endpoint: &IpEndpoint,
     ) -> core::result::Result<BoundPort, (Error, Self)> {
-        let endpoint = get_ephemeral_endpoint(remote_endpoint);
-        self.bind(&endpoint, false)
+        let endpoint = match get_ephemeral_endpoint(remote_endpoint) {
+            Ok(endpoint) => endpoint,
+            Err(err) => return Err((err, self)),
+        };
+        self.bind(&endpoint, false, false)
     }
 
     pub fn connect(
@@ -97,7 +101,7 @@ impl InitStream {
     pub fn local_endpoint(&self) -> Option<IpEndpoint> {
         match self {
             InitStream::Unbound => None,
-            InitStream::Bound(bound_port) => Some(bound_port.endpoint().unwrap()),
+            InitStream::Bound(bound_port) => Some(bound_port.endpoint()),
         }
     }
 
@@ -15,7 +15,7 @@ use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
 use takeable::Takeable;
 use util::TcpOptionSet;
 
-use super::UNSPECIFIED_LOCAL_ENDPOINT;
+use super::{options::IpOptionSet, IpFamily};
 use crate::{
     events::IoEvents,
     fs::{
@@ -54,6 +54,7 @@ pub(in crate::net) use self::observer::StreamObserver;
 pub use self::util::CongestionControl;
 
 pub struct StreamSocket {
+    family: IpFamily,
     options: RwLock<OptionSet>,
     state: RwLock<Takeable<State>, PreemptDisabled>,
     is_nonblocking: AtomicBool,
@@ -74,14 +75,16 @@ enum State {
 #[derive(Debug, Clone)]
 struct OptionSet {
     socket: SocketOptionSet,
+    ip: IpOptionSet,
     tcp: TcpOptionSet,
 }
 
 impl OptionSet {
     fn new() -> Self {
         let socket = SocketOptionSet::new_tcp();
+        let ip = IpOptionSet::new();
         let tcp = TcpOptionSet::new();
-        OptionSet { socket, tcp }
+        OptionSet { socket, ip, tcp }
     }
 
     fn raw(&self) -> RawTcpOption {
@@ -93,9 +96,10 @@ impl OptionSet {
 }
 
 impl StreamSocket {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+    pub fn new(family: IpFamily, is_nonblocking: bool) -> Arc<Self> {
         let init_stream = InitStream::new();
         Arc::new(Self {
+            family,
             options: RwLock::new(OptionSet::new()),
             state: RwLock::new(Takeable::new(State::Init(init_stream))),
             is_nonblocking: AtomicBool::new(is_nonblocking),
@@ -103,10 +107,16 @@ impl StreamSocket {
         })
     }
 
-    fn new_accepted(connected_stream: ConnectedStream) -> Arc<Self> {
+    fn new_accepted(
+        family: IpFamily,
+        ip_options: IpOptionSet,
+        connected_stream: ConnectedStream,
+    ) -> Arc<Self> {
         let options = connected_stream.raw_with(|raw_tcp_socket| {
             let mut options = OptionSet::new();
 
+            options.ip = ip_options;
+
             if raw_tcp_socket.keep_alive().is_some() {
                 options.socket.set_keep_alive(true);
             }
@@ -124,6 +134,7 @@ impl StreamSocket {
         connected_stream.init_observer(StreamObserver::new(pollee.clone()));
 
         Arc::new(Self {
+            family,
             options: RwLock::new(options),
             state: RwLock::new(Takeable::new(State::Connected(connected_stream))),
             is_nonblocking: AtomicBool::new(false),
@@ -305,6 +316,7 @@ impl StreamSocket {
     }
 
     fn try_accept(&self) -> Result<(Arc<dyn FileLike>, SocketAddr)> {
+        let ip_options = self.options.read().ip;
         let state = self.read_updated_state();
 
         let State::Listen(listen_stream) = state.as_ref() else {
@@ -313,8 +325,11 @@ impl StreamSocket {
 
         let accepted = listen_stream.try_accept().map(|connected_stream| {
             let remote_endpoint = connected_stream.remote_endpoint();
-            let accepted_socket = Self::new_accepted(connected_stream);
-            (accepted_socket as _, remote_endpoint.into())
+            let accepted_socket = Self::new_accepted(self.family, ip_options, connected_stream);
+            (
+                accepted_socket as _,
+                self.family.socket_addr_of(remote_endpoint),
+            )
         });
         let iface_to_poll = listen_stream.iface().clone();
 
@@ -352,7 +367,7 @@ impl StreamSocket {
             iface.poll();
         }
 
-        Ok((recv_bytes, remote_endpoint.into()))
+        Ok((recv_bytes, self.family.socket_addr_of(remote_endpoint)))
     }
 
     fn recv(
@@ -470,9 +485,14 @@ impl FileLike for StreamSocket {
 
 impl Socket for StreamSocket {
     fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
+        let (can_reuse, is_ipv6_only) = {
+            let options = self.options.read();
+            (options.socket.reuse_addr(), options.ip.ipv6_only())
+        };
+        let endpoint = self
+            .family
+            .endpoint_from(socket_addr, is_ipv6_only, Errno::EINVAL)?;
 
-        let can_reuse = self.options.read().socket.reuse_addr();
         let mut state = self.write_updated_state();
 
         state.borrow_result(|owned_state| {
@@ -486,7 +506,7 @@ impl Socket for StreamSocket {
                 );
             };
 
-            let bound_port = match init_stream.bind(&endpoint, can_reuse) {
+            let bound_port = match init_stream.bind(&endpoint, can_reuse, is_ipv6_only) {
                 Ok(bound_port) => bound_port,
                 Err((err, init_stream)) => {
                     return (State::Init(init_stream), Err(err));
@@ -498,7 +518,10 @@ impl Socket for StreamSocket {
     }
 
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
-        let remote_endpoint = socket_addr.try_into()?;
+        let is_ipv6_only = self.options.read().ip.ipv6_only();
+        let remote_endpoint =
+            self.family
+                .endpoint_from(socket_addr, is_ipv6_only, Errno::ENETUNREACH)?;
 
         if let Some(result) = self.start_connect(&remote_endpoint) {
             return result;
@@ -578,12 +601,12 @@ impl Socket for StreamSocket {
         let local_endpoint = match state.as_ref() {
             State::Init(init_stream) => init_stream
                 .local_endpoint()
-                .unwrap_or(UNSPECIFIED_LOCAL_ENDPOINT),
+                .unwrap_or(self.family.unspecified_endpoint()),
             State::Connecting(connecting_stream) => connecting_stream.local_endpoint(),
             State::Listen(listen_stream) => listen_stream.local_endpoint(),
             State::Connected(connected_stream) => connected_stream.local_endpoint(),
         };
-        Ok(local_endpoint.into())
+        Ok(self.family.socket_addr_of(local_endpoint))
     }
 
     fn peer_addr(&self) -> Result<SocketAddr> {
@@ -595,7 +618,7 @@ impl Socket for StreamSocket {
             State::Connecting(connecting_stream) => connecting_stream.remote_endpoint(),
             State::Connected(connected_stream) => connected_stream.remote_endpoint(),
         };
-        Ok(remote_endpoint.into())
+        Ok(self.family.socket_addr_of(remote_endpoint))
     }
 
     fn sendmsg(
@@ -610,14 +633,14 @@ impl Socket for StreamSocket {
         }
 
         let MessageHeader {
-            control_message, ..
+            control_messages, ..
         } = message_header;
 
         // According to the Linux man pages, `EISCONN` _may_ be returned when the destination
         // address is specified for a connection-mode socket. In practice, the destination address
         // is simply ignored. We follow the same behavior as the Linux implementation to ignore it.
 
-        if control_message.is_some() {
+        if !control_messages.is_empty() {
             // TODO: Support sending control message
             warn!("sending control message is not supported");
         }
@@ -641,7 +664,7 @@ impl Socket for StreamSocket {
 
         // According to <https://elixir.bootlin.com/linux/v6.0.9/source/net/ipv4/tcp.c#L2645>,
         // peer address is ignored for connected socket.
-        let message_header = MessageHeader::new(None, None);
+        let message_header = MessageHeader::new(None, Vec::new());
 
         Ok((received_bytes, message_header))
     }
@@ -663,6 +686,11 @@ impl Socket for StreamSocket {
             res => return res,
         }
 
+        match options.ip.get_option(self.family, option) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res,
+        }
+
         // FIXME: Here we only return the previously set values, without actually
         // asking the underlying sockets for the real, effective values.
         match_sock_option_mut!(option, {
@@ -697,7 +725,13 @@ impl Socket for StreamSocket {
 
         let need_iface_poll = match options.socket.set_option(option, state.as_mut()) {
             Err(err) if err.error() == Errno::ENOPROTOOPT => {
-                do_tcp_setsockopt(option, &mut options, state.as_mut())?
+                match options.ip.set_option(self.family, option, state.is_bound()) {
+                    Err(err) if err.error() == Errno::ENOPROTOOPT => {
+                        do_tcp_setsockopt(option, &mut options, state.as_mut())?
+                    }
+                    Err(err) => return Err(err),
+                    Ok(()) => NeedIfacePoll::FALSE,
+                }
             }
             Err(err) => return Err(err),
             Ok(need_iface_poll) => need_iface_poll,
@@ -784,6 +818,10 @@ impl State {
         }
     }
 
+    fn is_bound(&self) -> bool {
+        !matches!(self, State::Init(InitStream::Unbound))
+    }
+
     fn iface(&self) -> Option<&Arc<Iface>> {
         match self {
             State::Init(_) => None,
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{net::socket::SocketAddr, prelude::*};
+
+/// A netlink socket address.
+///
+/// The port number identifies a netlink socket. The port number of the kernel is always zero. The
+/// multicast groups are represented as a bit mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetlinkSocketAddr {
+    port: u32,
+    groups: u32,
+}
+
+impl NetlinkSocketAddr {
+    /// Creates a new netlink socket address.
+    pub const fn new(port: u32, groups: u32) -> Self {
+        Self { port, groups }
+    }
+
+    /// Returns the address of the kernel.
+    pub const fn kernel() -> Self {
+        Self::new(0, 0)
+    }
+
+    /// Returns the port number.
+    pub const fn port(&self) -> u32 {
+        self.port
+    }
+
+    /// Returns the multicast groups.
+    pub const fn groups(&self) -> u32 {
+        self.groups
+    }
+}
+
+impl TryFrom<SocketAddr> for NetlinkSocketAddr {
+    type Error = Error;
+
+    fn try_from(value: SocketAddr) -> Result<Self> {
+        let SocketAddr::Netlink(netlink_addr) = value else {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the socket address is not a netlink address"
+            );
+        };
+        Ok(netlink_addr)
+    }
+}
+
+impl From<NetlinkSocketAddr> for SocketAddr {
+    fn from(value: NetlinkSocketAddr) -> Self {
+        SocketAddr::Netlink(value)
+    }
+}
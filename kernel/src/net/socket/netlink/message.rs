@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Generic netlink messages.
+//!
+//! This module handles the parts of netlink messages that are shared by all netlink protocols,
+//! i.e., the segment headers and the attributes.
+
+use crate::prelude::*;
+
+/// The header of a netlink segment (`struct nlmsghdr`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/netlink.h#L52>.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub(super) struct CSegmentHeader {
+    /// Length of the segment, including the header.
+    pub(super) len: u32,
+    /// Type of the segment content.
+    pub(super) type_: u16,
+    /// Additional flags.
+    pub(super) flags: u16,
+    /// Sequence number.
+    pub(super) seq: u32,
+    /// Sending process port ID.
+    pub(super) port: u32,
+}
+
+/// The header of a netlink attribute (`struct nlattr`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/netlink.h#L208>.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CAttrHeader {
+    /// Length of the attribute, including the header.
+    len: u16,
+    /// Type of the attribute.
+    type_: u16,
+}
+
+/// The payload of an error segment (`struct nlmsgerr`).
+///
+/// It is followed by the header of the request that causes the error.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CErrorSegment {
+    /// The negative error number, or zero for acknowledgments.
+    error: i32,
+    header: CSegmentHeader,
+}
+
+/// Segment types that are defined for all netlink protocols.
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/netlink.h#L108>.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+pub(super) enum CommonSegmentType {
+    /// Nothing
+    NOOP = 1,
+    /// Error or acknowledgment
+    ERROR = 2,
+    /// End of a dump
+    DONE = 3,
+    /// Data lost
+    OVERRUN = 4,
+}
+
+/// The minimum value of segment types that are specific to netlink protocols.
+pub(super) const MIN_PROTOCOL_SEGMENT_TYPE: u16 = 0x10;
+
+bitflags! {
+    /// Flags of netlink segments.
+    ///
+    /// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/netlink.h#L54>.
+    pub(super) struct SegmentFlags: u16 {
+        /// It is a request message.
+        const REQUEST = 0x01;
+        /// It is a multipart message, terminated by `NLMSG_DONE`.
+        const MULTI = 0x02;
+        /// Reply with an acknowledgment, with zero or an error code.
+        const ACK = 0x04;
+        /// Echo this request.
+        const ECHO = 0x08;
+        /// The dump was inconsistent due to sequence change.
+        const DUMP_INTR = 0x10;
+        /// The dump was filtered as requested.
+        const DUMP_FILTERED = 0x20;
+
+        // Modifiers to GET requests.
+
+        /// Specify the tree root.
+        const ROOT = 0x100;
+        /// Return all matching results.
+        const MATCH = 0x200;
+        /// Return an atomic snapshot.
+        const ATOMIC = 0x400;
+        /// Return all results (i.e., `ROOT | MATCH`).
+        const DUMP = 0x300;
+
+        // Modifiers to NEW requests.
+
+        /// Override existing objects.
+        const REPLACE = 0x100;
+        /// Do not touch existing objects.
+        const EXCL = 0x200;
+        /// Create objects if they do not exist.
+        const CREATE = 0x400;
+        /// Add to the end of the object list.
+        const APPEND = 0x800;
+
+        // Flags for acknowledgments.
+
+        /// The request payload is not included in the error segment.
+        const CAPPED = 0x100;
+    }
+}
+
+/// The alignment of netlink segments and attributes.
+const NETLINK_ALIGN: usize = 4;
+
+const fn align_up(len: usize) -> usize {
+    len.next_multiple_of(NETLINK_ALIGN)
+}
+
+/// A segment parsed from a netlink message.
+pub(super) struct Segment<'a> {
+    pub(super) header: CSegmentHeader,
+    pub(super) payload: &'a [u8],
+}
+
+impl Segment<'_> {
+    pub(super) fn flags(&self) -> SegmentFlags {
+        SegmentFlags::from_bits_truncate(self.header.flags)
+    }
+}
+
+/// Parses the segments in a netlink message.
+///
+/// Following Linux, the parsing stops silently at the first segment that is ill-formed.
+pub(super) fn parse_segments(mut message: &[u8]) -> impl Iterator<Item = Segment<'_>> {
+    core::iter::from_fn(move || {
+        if message.len() < size_of::<CSegmentHeader>() {
+            return None;
+        }
+
+        let header = CSegmentHeader::from_bytes(message);
+        let len = header.len as usize;
+        if len < size_of::<CSegmentHeader>() || len > message.len() {
+            return None;
+        }
+
+        let payload = &message[size_of::<CSegmentHeader>()..len];
+        message = &message[align_up(len).min(message.len())..];
+
+        Some(Segment { header, payload })
+    })
+}
+
+/// Parses the fixed-size body at the beginning of the segment payload.
+///
+/// The body is returned together with the remaining bytes, which usually contain attributes.
+pub(super) fn parse_body<T: Pod>(payload: &[u8]) -> Result<(T, &[u8])> {
+    if payload.len() < size_of::<T>() {
+        return_errno_with_message!(Errno::EINVAL, "the netlink segment is too short");
+    }
+
+    let body = T::from_bytes(payload);
+    let rest = &payload[align_up(size_of::<T>()).min(payload.len())..];
+    Ok((body, rest))
+}
+
+/// Parses the attributes.
+///
+/// Each attribute is returned as a pair of its type and its payload. The flags in the attribute
+/// type (i.e., `NLA_F_NESTED` and `NLA_F_NET_BYTEORDER`) are removed.
+pub(super) fn parse_attrs(mut attrs: &[u8]) -> impl Iterator<Item = Result<(u16, &[u8])>> {
+    const ATTR_TYPE_MASK: u16 = 0x3fff;
+
+    core::iter::from_fn(move || {
+        if attrs.is_empty() {
+            return None;
+        }
+
+        if attrs.len() < size_of::<CAttrHeader>() {
+            attrs = &[];
+            return Some(Err(Error::with_message(
+                Errno::EINVAL,
+                "the netlink attribute is too short",
+            )));
+        }
+
+        let header = CAttrHeader::from_bytes(attrs);
+        let len = header.len as usize;
+        if len < size_of::<CAttrHeader>() || len > attrs.len() {
+            attrs = &[];
+            return Some(Err(Error::with_message(
+                Errno::EINVAL,
+                "the netlink attribute has an invalid length",
+            )));
+        }
+
+        let payload = &attrs[size_of::<CAttrHeader>()..len];
+        attrs = &attrs[align_up(len).min(attrs.len())..];
+
+        Some(Ok((header.type_ & ATTR_TYPE_MASK, payload)))
+    })
+}
+
+/// Parses an attribute payload as a value of type `T`.
+pub(super) fn parse_attr_val<T: Pod>(payload: &[u8]) -> Result<T> {
+    if payload.len() != size_of::<T>() {
+        return_errno_with_message!(Errno::EINVAL, "the netlink attribute has an invalid size");
+    }
+
+    Ok(T::from_bytes(payload))
+}
+
+/// A builder that builds a netlink segment.
+pub(super) struct SegmentBuilder {
+    buf: Vec<u8>,
+}
+
+impl SegmentBuilder {
+    /// Creates a builder for a segment with the specified header fields.
+    pub(super) fn new(type_: u16, flags: SegmentFlags, seq: u32, port: u32) -> Self {
+        let header = CSegmentHeader {
+            // The length will be filled in `Self::build`.
+            len: 0,
+            type_,
+            flags: flags.bits(),
+            seq,
+            port,
+        };
+
+        Self {
+            buf: header.as_bytes().to_vec(),
+        }
+    }
+
+    /// Creates a builder for an error segment or an acknowledgment (if `error` is `None`).
+    pub(super) fn new_error(request: &CSegmentHeader, error: Option<Errno>, port: u32) -> Self {
+        let mut builder = Self::new(
+            CommonSegmentType::ERROR as u16,
+            SegmentFlags::CAPPED,
+            request.seq,
+            port,
+        );
+        builder.push_body(&CErrorSegment {
+            error: error.map_or(0, |errno| -(errno as i32)),
+            header: *request,
+        });
+        builder
+    }
+
+    /// Creates a builder for a segment that terminates a dump.
+    pub(super) fn new_done(request: &CSegmentHeader, port: u32) -> Self {
+        let mut builder = Self::new(
+            CommonSegmentType::DONE as u16,
+            SegmentFlags::MULTI,
+            request.seq,
+            port,
+        );
+        builder.push_body(&0i32);
+        builder
+    }
+
+    /// Appends the fixed-size body.
+    pub(super) fn push_body<T: Pod>(&mut self, body: &T) {
+        self.buf.extend_from_slice(body.as_bytes());
+        self.pad();
+    }
+
+    /// Appends an attribute whose payload is the given bytes.
+    pub(super) fn push_attr(&mut self, type_: u16, payload: &[u8]) {
+        let header = CAttrHeader {
+            len: (size_of::<CAttrHeader>() + payload.len()) as u16,
+            type_,
+        };
+        self.buf.extend_from_slice(header.as_bytes());
+        self.buf.extend_from_slice(payload);
+        self.pad();
+    }
+
+    /// Appends an attribute whose payload is the given value.
+    pub(super) fn push_attr_val<T: Pod>(&mut self, type_: u16, val: &T) {
+        self.push_attr(type_, val.as_bytes());
+    }
+
+    /// Appends an attribute whose payload is the given string, which will be null-terminated.
+    pub(super) fn push_attr_str(&mut self, type_: u16, val: &str) {
+        let mut payload = Vec::with_capacity(val.len() + 1);
+        payload.extend_from_slice(val.as_bytes());
+        payload.push(0);
+        self.push_attr(type_, &payload);
+    }
+
+    /// Builds the segment.
+    pub(super) fn build(mut self) -> Vec<u8> {
+        let len = self.buf.len() as u32;
+        self.buf[..size_of::<u32>()].copy_from_slice(len.as_bytes());
+        self.buf
+    }
+
+    fn pad(&mut self) {
+        self.buf.resize(align_up(self.buf.len()), 0);
+    }
+}
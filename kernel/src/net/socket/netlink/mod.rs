@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink sockets.
+//!
+//! Netlink sockets are used to transfer information between user space and the kernel. Each
+//! netlink socket belongs to a netlink protocol (e.g., `NETLINK_ROUTE`), which determines the
+//! kernel module that the socket talks to.
+//!
+//! A netlink message consists of one or more segments. Each segment starts with a header (i.e.,
+//! `struct nlmsghdr`) followed by the payload. See
+//! <https://man7.org/linux/man-pages/man7/netlink.7.html> for details.
+
+mod addr;
+mod message;
+mod route;
+mod table;
+mod uevent;
+
+pub use addr::NetlinkSocketAddr;
+pub use route::NetlinkRouteSocket;
+pub use uevent::{broadcast_uevent, NetlinkUeventSocket};
+
+/// Standard netlink protocols.
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/netlink.h#L9>.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, TryFromInt, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+pub enum StandardNetlinkProtocol {
+    /// Routing/device hook
+    ROUTE = 0,
+    /// Unused number
+    UNUSED = 1,
+    /// Reserved for user mode socket protocols
+    USERSOCK = 2,
+    /// Unused number, formerly ip_queue
+    FIREWALL = 3,
+    /// Socket monitoring
+    SOCK_DIAG = 4,
+    /// Netfilter/iptables ULOG
+    NFLOG = 5,
+    /// IPsec
+    XFRM = 6,
+    /// SELinux event notifications
+    SELINUX = 7,
+    /// Open-iSCSI
+    ISCSI = 8,
+    /// Auditing
+    AUDIT = 9,
+    FIB_LOOKUP = 10,
+    CONNECTOR = 11,
+    /// Netfilter subsystem
+    NETFILTER = 12,
+    IP6_FW = 13,
+    /// DECnet routing messages
+    DNRTMSG = 14,
+    /// Kernel messages to userspace
+    KOBJECT_UEVENT = 15,
+    GENERIC = 16,
+    /// SCSI Transports
+    SCSITRANSPORT = 18,
+    ECRYPTFS = 19,
+    RDMA = 20,
+    /// Crypto layer
+    CRYPTO = 21,
+    /// SMC monitoring
+    SMC = 22,
+}
@@ -0,0 +1,625 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The kernel side of the `NETLINK_ROUTE` protocol.
+//!
+//! Requests from user space are handled here. The interfaces (abbreviated as ifaces) exposed to
+//! user space are those in [`IFACES`]. The index of an iface is its position in [`IFACES`] plus
+//! one, since index zero means "no iface" in Linux.
+
+use aster_bigtcp::{
+    errors::ConfigError,
+    iface::{InterfaceType, Route},
+    wire::{IpAddress, IpCidr, Ipv4Address, Ipv4Cidr, Ipv6Address, Ipv6Cidr},
+};
+
+use super::message::*;
+use crate::{
+    net::{
+        iface::{Iface, IFACES},
+        socket::netlink::message::{
+            parse_attr_val, parse_attrs, parse_body, Segment, SegmentBuilder, SegmentFlags,
+            MIN_PROTOCOL_SEGMENT_TYPE,
+        },
+    },
+    prelude::*,
+    process::credentials::capabilities::CapSet,
+    util::net::CSocketAddrFamily,
+};
+
+/// Handles a request segment and appends the response segments to `responses`.
+///
+/// `port` is the port number of the socket that sends the request.
+pub(super) fn handle_request(segment: &Segment, port: u32, responses: &mut Vec<Vec<u8>>) {
+    let header = &segment.header;
+    let flags = segment.flags();
+
+    // Segments that are not requests or that have common types are not processed, but they can
+    // still be acknowledged.
+    if !flags.contains(SegmentFlags::REQUEST) || header.type_ < MIN_PROTOCOL_SEGMENT_TYPE {
+        if flags.contains(SegmentFlags::ACK) {
+            responses.push(SegmentBuilder::new_error(header, None, port).build());
+        }
+        return;
+    }
+
+    let is_dump = matches!(
+        RouteSegmentType::try_from(header.type_),
+        Ok(RouteSegmentType::RTM_GETLINK
+            | RouteSegmentType::RTM_GETADDR
+            | RouteSegmentType::RTM_GETROUTE)
+    ) && flags.intersects(SegmentFlags::DUMP);
+
+    let mut cx = RequestContext {
+        segment,
+        port,
+        is_dump,
+        responses,
+    };
+    let result = cx.handle();
+
+    match result {
+        // Dump requests are never acknowledged, since the end of the dump is marked with
+        // `NLMSG_DONE`.
+        Ok(()) if is_dump || !flags.contains(SegmentFlags::ACK) => (),
+        Ok(()) => responses.push(SegmentBuilder::new_error(header, None, port).build()),
+        Err(err) => {
+            responses.push(SegmentBuilder::new_error(header, Some(err.error()), port).build())
+        }
+    }
+}
+
+struct RequestContext<'a, 'b> {
+    segment: &'a Segment<'b>,
+    port: u32,
+    is_dump: bool,
+    responses: &'a mut Vec<Vec<u8>>,
+}
+
+impl RequestContext<'_, '_> {
+    fn handle(&mut self) -> Result<()> {
+        let Ok(type_) = RouteSegmentType::try_from(self.segment.header.type_) else {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the segment type is not supported");
+        };
+
+        match type_ {
+            RouteSegmentType::RTM_GETLINK => self.get_link(),
+            RouteSegmentType::RTM_GETADDR => self.get_addr(),
+            RouteSegmentType::RTM_NEWADDR => self.new_addr(),
+            RouteSegmentType::RTM_DELADDR => self.del_addr(),
+            RouteSegmentType::RTM_GETROUTE => self.get_route(),
+            RouteSegmentType::RTM_NEWROUTE => self.new_route(),
+            RouteSegmentType::RTM_DELROUTE => self.del_route(),
+            RouteSegmentType::RTM_NEWLINK
+            | RouteSegmentType::RTM_DELLINK
+            | RouteSegmentType::RTM_SETLINK => {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "creating, deleting, or modifying links is not supported"
+                )
+            }
+        }
+    }
+
+    fn get_link(&mut self) -> Result<()> {
+        if self.is_dump {
+            for (index, iface) in ifaces() {
+                let segment = self.link_segment(index, iface, SegmentFlags::MULTI);
+                self.responses.push(segment);
+            }
+            self.push_done();
+            return Ok(());
+        }
+
+        let (ifinfo, attrs) = parse_body::<CIfInfoMsg>(self.segment.payload)?;
+
+        let (index, iface) = if ifinfo.index > 0 {
+            (ifinfo.index as u32, iface_of_index(ifinfo.index as u32)?)
+        } else {
+            let mut name = None;
+            for attr in parse_attrs(attrs) {
+                let (type_, payload) = attr?;
+                if type_ == LinkAttr::IFLA_IFNAME as u16 {
+                    name = Some(parse_name(payload)?);
+                }
+            }
+            let Some(name) = name else {
+                return_errno_with_message!(Errno::EINVAL, "neither the index nor the name is set");
+            };
+            ifaces()
+                .find(|(_, iface)| iface.name() == name)
+                .ok_or_else(|| Error::with_message(Errno::ENODEV, "the iface does not exist"))?
+        };
+
+        let segment = self.link_segment(index, iface, SegmentFlags::empty());
+        self.responses.push(segment);
+        Ok(())
+    }
+
+    fn get_addr(&mut self) -> Result<()> {
+        if !self.is_dump {
+            // TODO: Support getting a single address.
+            return_errno_with_message!(Errno::EOPNOTSUPP, "only dumping addresses is supported");
+        }
+
+        let family = self.dump_family();
+        for (index, iface) in ifaces() {
+            for cidr in iface.ip_cidrs() {
+                if !family_matches(family, &cidr) {
+                    continue;
+                }
+                let segment = self.addr_segment(index, iface, &cidr);
+                self.responses.push(segment);
+            }
+        }
+        self.push_done();
+        Ok(())
+    }
+
+    fn new_addr(&mut self) -> Result<()> {
+        check_net_admin()?;
+
+        let (index, cidr) = self.parse_addr_request()?;
+        let iface = iface_of_index(index)?;
+
+        match iface.add_ip_cidr(cidr) {
+            Ok(()) => Ok(()),
+            Err(ConfigError::AlreadyExists)
+                if self.segment.flags().contains(SegmentFlags::REPLACE)
+                    && !self.segment.flags().contains(SegmentFlags::EXCL) =>
+            {
+                Ok(())
+            }
+            Err(ConfigError::AlreadyExists) => {
+                return_errno_with_message!(Errno::EEXIST, "the address already exists")
+            }
+            Err(ConfigError::Exhausted | ConfigError::NotFound) => {
+                return_errno_with_message!(Errno::ENOSPC, "the iface has too many addresses")
+            }
+        }
+    }
+
+    fn del_addr(&mut self) -> Result<()> {
+        check_net_admin()?;
+
+        let (index, cidr) = self.parse_addr_request()?;
+        let iface = iface_of_index(index)?;
+
+        iface
+            .remove_ip_cidr(cidr)
+            .map_err(|_| Error::with_message(Errno::EADDRNOTAVAIL, "the address does not exist"))
+    }
+
+    fn get_route(&mut self) -> Result<()> {
+        if !self.is_dump {
+            // TODO: Support looking up the route to a single destination.
+            return_errno_with_message!(Errno::EOPNOTSUPP, "only dumping routes is supported");
+        }
+
+        let family = self.dump_family();
+        for (index, iface) in ifaces() {
+            // The routes to the subnets of the iface's addresses.
+            for cidr in iface.ip_cidrs() {
+                if !family_matches(family, &cidr) {
+                    continue;
+                }
+                let segment = self.subnet_route_segment(index, &cidr);
+                self.responses.push(segment);
+            }
+
+            // The routes via gateways.
+            for route in iface.routes() {
+                if !family_matches(family, &route.cidr) {
+                    continue;
+                }
+                let segment = self.gateway_route_segment(index, &route);
+                self.responses.push(segment);
+            }
+        }
+        self.push_done();
+        Ok(())
+    }
+
+    fn new_route(&mut self) -> Result<()> {
+        check_net_admin()?;
+
+        let request = self.parse_route_request()?;
+        let Some(gateway) = request.gateway else {
+            // TODO: Support routes without gateways.
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "routes without gateways are not supported"
+            );
+        };
+
+        let iface = match request.oif {
+            Some(index) => iface_of_index(index)?,
+            None => ifaces()
+                .map(|(_, iface)| iface)
+                .find(|iface| {
+                    iface
+                        .ip_cidrs()
+                        .iter()
+                        .any(|cidr| cidr.contains_addr(&gateway))
+                })
+                .ok_or_else(|| {
+                    Error::with_message(Errno::ENETUNREACH, "the gateway is not reachable")
+                })?,
+        };
+
+        let new_route = || Route {
+            cidr: request.dst,
+            via_router: gateway,
+            preferred_until: None,
+            expires_at: None,
+        };
+        match iface.add_route(new_route()) {
+            Ok(()) => Ok(()),
+            Err(ConfigError::AlreadyExists)
+                if self.segment.flags().contains(SegmentFlags::REPLACE) =>
+            {
+                let _ = iface.remove_route(request.dst);
+                iface.add_route(new_route()).map_err(|_| {
+                    Error::with_message(Errno::ENOSPC, "the iface has too many routes")
+                })
+            }
+            Err(ConfigError::AlreadyExists) => {
+                return_errno_with_message!(Errno::EEXIST, "the route already exists")
+            }
+            Err(ConfigError::Exhausted | ConfigError::NotFound) => {
+                return_errno_with_message!(Errno::ENOSPC, "the iface has too many routes")
+            }
+        }
+    }
+
+    fn del_route(&mut self) -> Result<()> {
+        check_net_admin()?;
+
+        let request = self.parse_route_request()?;
+
+        let is_removed = ifaces()
+            .filter(|(index, _)| request.oif.is_none_or(|oif| oif == *index))
+            .any(|(_, iface)| {
+                iface.routes().iter().any(|route| {
+                    route.cidr == request.dst
+                        && request
+                            .gateway
+                            .is_none_or(|gateway| gateway == route.via_router)
+                }) && iface.remove_route(request.dst).is_ok()
+            });
+        if !is_removed {
+            return_errno_with_message!(Errno::ESRCH, "the route does not exist");
+        }
+
+        Ok(())
+    }
+}
+
+impl RequestContext<'_, '_> {
+    /// Returns the address family that is used to filter the dump results.
+    ///
+    /// Dump requests may carry only `struct rtgenmsg`, which contains nothing but the family. So
+    /// only the first byte is parsed here.
+    fn dump_family(&self) -> Option<CSocketAddrFamily> {
+        let family = *self.segment.payload.first()?;
+        match CSocketAddrFamily::try_from(family as i32) {
+            Ok(CSocketAddrFamily::AF_UNSPEC) | Err(_) => None,
+            Ok(family) => Some(family),
+        }
+    }
+
+    fn parse_addr_request(&self) -> Result<(u32, IpCidr)> {
+        let (ifaddr, attrs) = parse_body::<CIfAddrMsg>(self.segment.payload)?;
+
+        let mut local = None;
+        let mut address = None;
+        for attr in parse_attrs(attrs) {
+            let (type_, payload) = attr?;
+            if type_ == AddrAttr::IFA_LOCAL as u16 {
+                local = Some(payload);
+            } else if type_ == AddrAttr::IFA_ADDRESS as u16 {
+                address = Some(payload);
+            }
+        }
+
+        // `IFA_LOCAL` is the address of the iface. `IFA_ADDRESS` is the address of the remote
+        // peer for point-to-point ifaces, but they are the same for other ifaces.
+        let Some(addr) = local.or(address) else {
+            return_errno_with_message!(Errno::EINVAL, "the address is not specified");
+        };
+        let addr = parse_ip_addr(ifaddr.family, addr)?;
+        let cidr = new_cidr(addr, ifaddr.prefix_len)?;
+
+        Ok((ifaddr.index, cidr))
+    }
+
+    fn parse_route_request(&self) -> Result<RouteRequest> {
+        let (rtmsg, attrs) = parse_body::<CRtMsg>(self.segment.payload)?;
+
+        let mut table = rtmsg.table as u32;
+        let mut dst = None;
+        let mut gateway = None;
+        let mut oif = None;
+        for attr in parse_attrs(attrs) {
+            let (type_, payload) = attr?;
+            if type_ == RouteAttr::RTA_DST as u16 {
+                dst = Some(parse_ip_addr(rtmsg.family, payload)?);
+            } else if type_ == RouteAttr::RTA_GATEWAY as u16 {
+                gateway = Some(parse_ip_addr(rtmsg.family, payload)?);
+            } else if type_ == RouteAttr::RTA_OIF as u16 {
+                oif = Some(parse_attr_val::<u32>(payload)?);
+            } else if type_ == RouteAttr::RTA_TABLE as u16 {
+                table = parse_attr_val::<u32>(payload)?;
+            }
+        }
+
+        if table != RT_TABLE_UNSPEC as u32 && table != RT_TABLE_MAIN as u32 {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "only the main routing table is supported"
+            );
+        }
+
+        let dst = match dst {
+            Some(dst) => dst,
+            None => unspecified_addr_of(rtmsg.family)?,
+        };
+        let dst = network_of(new_cidr(dst, rtmsg.dst_len)?);
+
+        Ok(RouteRequest { dst, gateway, oif })
+    }
+}
+
+struct RouteRequest {
+    dst: IpCidr,
+    gateway: Option<IpAddress>,
+    oif: Option<u32>,
+}
+
+impl RequestContext<'_, '_> {
+    fn link_segment(&self, index: u32, iface: &Iface, flags: SegmentFlags) -> Vec<u8> {
+        let (type_, iface_flags, operstate, hw_addr, broadcast) = match iface.type_() {
+            InterfaceType::Ethernet(ether_addr) => (
+                ARPHRD_ETHER,
+                IfaceFlags::BROADCAST | IfaceFlags::MULTICAST,
+                IF_OPER_UP,
+                ether_addr.0,
+                [0xff; 6],
+            ),
+            // FIXME: The loopback iface is the only IP iface for now.
+            InterfaceType::Ip => (
+                ARPHRD_LOOPBACK,
+                IfaceFlags::LOOPBACK,
+                IF_OPER_UNKNOWN,
+                [0; 6],
+                [0; 6],
+            ),
+        };
+        // All ifaces are always up.
+        let iface_flags = iface_flags | IfaceFlags::UP | IfaceFlags::RUNNING | IfaceFlags::LOWER_UP;
+
+        let mut builder = self.new_segment(RouteSegmentType::RTM_NEWLINK, flags);
+        builder.push_body(&CIfInfoMsg {
+            family: CSocketAddrFamily::AF_UNSPEC as u8,
+            _pad: 0,
+            type_,
+            index: index as i32,
+            flags: iface_flags.bits(),
+            change: 0,
+        });
+        builder.push_attr_str(LinkAttr::IFLA_IFNAME as u16, iface.name());
+        builder.push_attr_val(LinkAttr::IFLA_MTU as u16, &(iface.mtu() as u32));
+        builder.push_attr_val(LinkAttr::IFLA_TXQLEN as u16, &1000u32);
+        builder.push_attr_val(LinkAttr::IFLA_OPERSTATE as u16, &operstate);
+        builder.push_attr(LinkAttr::IFLA_ADDRESS as u16, &hw_addr);
+        builder.push_attr(LinkAttr::IFLA_BROADCAST as u16, &broadcast);
+        builder.build()
+    }
+
+    fn addr_segment(&self, index: u32, iface: &Iface, cidr: &IpCidr) -> Vec<u8> {
+        let mut builder = self.new_segment(RouteSegmentType::RTM_NEWADDR, SegmentFlags::MULTI);
+        builder.push_body(&CIfAddrMsg {
+            family: family_of(cidr) as u8,
+            prefix_len: cidr.prefix_len(),
+            flags: IFA_F_PERMANENT,
+            scope: scope_of(&cidr.address()),
+            index,
+        });
+
+        match cidr {
+            IpCidr::Ipv4(cidr) => {
+                let addr = cidr.address().octets();
+                builder.push_attr(AddrAttr::IFA_ADDRESS as u16, &addr);
+                builder.push_attr(AddrAttr::IFA_LOCAL as u16, &addr);
+                if let Some(broadcast) = cidr.broadcast() {
+                    builder.push_attr(AddrAttr::IFA_BROADCAST as u16, &broadcast.octets());
+                }
+                builder.push_attr_str(AddrAttr::IFA_LABEL as u16, iface.name());
+            }
+            IpCidr::Ipv6(cidr) => {
+                builder.push_attr(AddrAttr::IFA_ADDRESS as u16, &cidr.address().octets());
+            }
+        }
+
+        builder.build()
+    }
+
+    fn subnet_route_segment(&self, index: u32, cidr: &IpCidr) -> Vec<u8> {
+        let mut builder = self.new_segment(RouteSegmentType::RTM_NEWROUTE, SegmentFlags::MULTI);
+        builder.push_body(&CRtMsg {
+            family: family_of(cidr) as u8,
+            dst_len: cidr.prefix_len(),
+            src_len: 0,
+            tos: 0,
+            table: RT_TABLE_MAIN,
+            protocol: RTPROT_KERNEL,
+            scope: RT_SCOPE_LINK,
+            type_: RTN_UNICAST,
+            flags: 0,
+        });
+        builder.push_attr_val(RouteAttr::RTA_TABLE as u16, &(RT_TABLE_MAIN as u32));
+        push_addr_attr(
+            &mut builder,
+            RouteAttr::RTA_DST as u16,
+            &network_of(*cidr).address(),
+        );
+        push_addr_attr(&mut builder, RouteAttr::RTA_PREFSRC as u16, &cidr.address());
+        builder.push_attr_val(RouteAttr::RTA_OIF as u16, &index);
+        builder.build()
+    }
+
+    fn gateway_route_segment(&self, index: u32, route: &Route) -> Vec<u8> {
+        let mut builder = self.new_segment(RouteSegmentType::RTM_NEWROUTE, SegmentFlags::MULTI);
+        builder.push_body(&CRtMsg {
+            family: family_of(&route.cidr) as u8,
+            dst_len: route.cidr.prefix_len(),
+            src_len: 0,
+            tos: 0,
+            table: RT_TABLE_MAIN,
+            protocol: RTPROT_BOOT,
+            scope: RT_SCOPE_UNIVERSE,
+            type_: RTN_UNICAST,
+            flags: 0,
+        });
+        builder.push_attr_val(RouteAttr::RTA_TABLE as u16, &(RT_TABLE_MAIN as u32));
+        if route.cidr.prefix_len() != 0 {
+            push_addr_attr(
+                &mut builder,
+                RouteAttr::RTA_DST as u16,
+                &route.cidr.address(),
+            );
+        }
+        push_addr_attr(
+            &mut builder,
+            RouteAttr::RTA_GATEWAY as u16,
+            &route.via_router,
+        );
+        builder.push_attr_val(RouteAttr::RTA_OIF as u16, &index);
+        builder.build()
+    }
+
+    fn new_segment(&self, type_: RouteSegmentType, flags: SegmentFlags) -> SegmentBuilder {
+        SegmentBuilder::new(type_ as u16, flags, self.segment.header.seq, self.port)
+    }
+
+    fn push_done(&mut self) {
+        let segment = SegmentBuilder::new_done(&self.segment.header, self.port).build();
+        self.responses.push(segment);
+    }
+}
+
+fn ifaces() -> impl Iterator<Item = (u32, &'static Arc<Iface>)> {
+    IFACES
+        .get()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .map(|(pos, iface)| (pos as u32 + 1, iface))
+}
+
+fn iface_of_index(index: u32) -> Result<&'static Arc<Iface>> {
+    ifaces()
+        .find(|(iface_index, _)| *iface_index == index)
+        .map(|(_, iface)| iface)
+        .ok_or_else(|| Error::with_message(Errno::ENODEV, "the iface does not exist"))
+}
+
+fn check_net_admin() -> Result<()> {
+    let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+    if !credentials.effective_capset().contains(CapSet::NET_ADMIN) {
+        return_errno_with_message!(Errno::EPERM, "`CAP_NET_ADMIN` is required");
+    }
+
+    Ok(())
+}
+
+fn parse_name(payload: &[u8]) -> Result<&str> {
+    let name = payload.split(|byte| *byte == 0).next().unwrap();
+    core::str::from_utf8(name)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the name is not valid UTF-8"))
+}
+
+fn parse_ip_addr(family: u8, payload: &[u8]) -> Result<IpAddress> {
+    match CSocketAddrFamily::try_from(family as i32) {
+        Ok(CSocketAddrFamily::AF_INET) => {
+            let octets: [u8; 4] = payload.try_into().map_err(|_| {
+                Error::with_message(Errno::EINVAL, "the IPv4 address has an invalid length")
+            })?;
+            Ok(IpAddress::Ipv4(Ipv4Address::from(octets)))
+        }
+        Ok(CSocketAddrFamily::AF_INET6) => {
+            let octets: [u8; 16] = payload.try_into().map_err(|_| {
+                Error::with_message(Errno::EINVAL, "the IPv6 address has an invalid length")
+            })?;
+            Ok(IpAddress::Ipv6(Ipv6Address::from(octets)))
+        }
+        _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "the address family is not supported"),
+    }
+}
+
+fn unspecified_addr_of(family: u8) -> Result<IpAddress> {
+    match CSocketAddrFamily::try_from(family as i32) {
+        Ok(CSocketAddrFamily::AF_INET) => Ok(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED)),
+        Ok(CSocketAddrFamily::AF_INET6) => Ok(IpAddress::Ipv6(Ipv6Address::UNSPECIFIED)),
+        _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "the address family is not supported"),
+    }
+}
+
+fn new_cidr(addr: IpAddress, prefix_len: u8) -> Result<IpCidr> {
+    match addr {
+        IpAddress::Ipv4(addr) if prefix_len <= 32 => {
+            Ok(IpCidr::Ipv4(Ipv4Cidr::new(addr, prefix_len)))
+        }
+        IpAddress::Ipv6(addr) if prefix_len <= 128 => {
+            Ok(IpCidr::Ipv6(Ipv6Cidr::new(addr, prefix_len)))
+        }
+        _ => return_errno_with_message!(Errno::EINVAL, "the prefix length is too long"),
+    }
+}
+
+/// Returns the CIDR whose address is the network address (i.e., the host bits are cleared).
+fn network_of(cidr: IpCidr) -> IpCidr {
+    match cidr {
+        IpCidr::Ipv4(cidr) => {
+            let mask = u32::MAX
+                .checked_shl(32 - cidr.prefix_len() as u32)
+                .unwrap_or(0);
+            let addr = Ipv4Address::from(u32::from(cidr.address()) & mask);
+            IpCidr::Ipv4(Ipv4Cidr::new(addr, cidr.prefix_len()))
+        }
+        IpCidr::Ipv6(cidr) => {
+            let mask = u128::MAX
+                .checked_shl(128 - cidr.prefix_len() as u32)
+                .unwrap_or(0);
+            let addr = Ipv6Address::from(u128::from(cidr.address()) & mask);
+            IpCidr::Ipv6(Ipv6Cidr::new(addr, cidr.prefix_len()))
+        }
+    }
+}
+
+fn family_of(cidr: &IpCidr) -> CSocketAddrFamily {
+    match cidr {
+        IpCidr::Ipv4(_) => CSocketAddrFamily::AF_INET,
+        IpCidr::Ipv6(_) => CSocketAddrFamily::AF_INET6,
+    }
+}
+
+fn family_matches(family: Option<CSocketAddrFamily>, cidr: &IpCidr) -> bool {
+    family.is_none_or(|family| family == family_of(cidr))
+}
+
+fn scope_of(addr: &IpAddress) -> u8 {
+    match addr {
+        IpAddress::Ipv4(addr) if addr.is_loopback() => RT_SCOPE_HOST,
+        IpAddress::Ipv6(addr) if addr.is_loopback() => RT_SCOPE_HOST,
+        // Link-local unicast addresses (i.e., `fe80::/10`).
+        IpAddress::Ipv6(addr) if addr.segments()[0] & 0xffc0 == 0xfe80 => RT_SCOPE_LINK,
+        _ => RT_SCOPE_UNIVERSE,
+    }
+}
+
+fn push_addr_attr(builder: &mut SegmentBuilder, type_: u16, addr: &IpAddress) {
+    match addr {
+        IpAddress::Ipv4(addr) => builder.push_attr(type_, &addr.octets()),
+        IpAddress::Ipv6(addr) => builder.push_attr(type_, &addr.octets()),
+    }
+}
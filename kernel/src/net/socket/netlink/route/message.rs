@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Messages of the `NETLINK_ROUTE` protocol.
+//!
+//! See <https://man7.org/linux/man-pages/man7/rtnetlink.7.html>.
+
+use crate::prelude::*;
+
+/// Segment types of the `NETLINK_ROUTE` protocol.
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/rtnetlink.h#L24>.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, TryFromInt, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub(super) enum RouteSegmentType {
+    RTM_NEWLINK = 16,
+    RTM_DELLINK = 17,
+    RTM_GETLINK = 18,
+    RTM_SETLINK = 19,
+    RTM_NEWADDR = 20,
+    RTM_DELADDR = 21,
+    RTM_GETADDR = 22,
+    RTM_NEWROUTE = 24,
+    RTM_DELROUTE = 25,
+    RTM_GETROUTE = 26,
+}
+
+/// Link information (`struct ifinfomsg`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/rtnetlink.h#L560>.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub(super) struct CIfInfoMsg {
+    pub(super) family: u8,
+    pub(super) _pad: u8,
+    /// Device type (`ARPHRD_*`).
+    pub(super) type_: u16,
+    /// Interface index.
+    pub(super) index: i32,
+    /// Device flags (`IFF_*`).
+    pub(super) flags: u32,
+    /// Change mask.
+    pub(super) change: u32,
+}
+
+/// Address information (`struct ifaddrmsg`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_addr.h#L8>.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub(super) struct CIfAddrMsg {
+    pub(super) family: u8,
+    /// The prefix length of the address.
+    pub(super) prefix_len: u8,
+    /// Address flags (`IFA_F_*`).
+    pub(super) flags: u8,
+    /// Address scope (`RT_SCOPE_*`).
+    pub(super) scope: u8,
+    /// Interface index.
+    pub(super) index: u32,
+}
+
+/// Route information (`struct rtmsg`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/rtnetlink.h#L236>.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub(super) struct CRtMsg {
+    pub(super) family: u8,
+    /// The prefix length of the destination address.
+    pub(super) dst_len: u8,
+    /// The prefix length of the source address.
+    pub(super) src_len: u8,
+    /// Type of service.
+    pub(super) tos: u8,
+    /// Routing table ID (`RT_TABLE_*`).
+    pub(super) table: u8,
+    /// Routing protocol (`RTPROT_*`).
+    pub(super) protocol: u8,
+    /// Route scope (`RT_SCOPE_*`).
+    pub(super) scope: u8,
+    /// Route type (`RTN_*`).
+    pub(super) type_: u8,
+    /// Route flags (`RTM_F_*`).
+    pub(super) flags: u32,
+}
+
+/// Link attributes (`IFLA_*`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_link.h#L205>.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+pub(super) enum LinkAttr {
+    IFLA_ADDRESS = 1,
+    IFLA_BROADCAST = 2,
+    IFLA_IFNAME = 3,
+    IFLA_MTU = 4,
+    IFLA_LINK = 5,
+    IFLA_QDISC = 6,
+    IFLA_STATS = 7,
+    IFLA_TXQLEN = 13,
+    IFLA_OPERSTATE = 16,
+    IFLA_LINKMODE = 17,
+}
+
+/// Address attributes (`IFA_*`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_addr.h#L26>.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+pub(super) enum AddrAttr {
+    IFA_ADDRESS = 1,
+    IFA_LOCAL = 2,
+    IFA_LABEL = 3,
+    IFA_BROADCAST = 4,
+    IFA_ANYCAST = 5,
+    IFA_CACHEINFO = 6,
+}
+
+/// Route attributes (`RTA_*`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/rtnetlink.h#L357>.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+pub(super) enum RouteAttr {
+    RTA_DST = 1,
+    RTA_SRC = 2,
+    RTA_IIF = 3,
+    RTA_OIF = 4,
+    RTA_GATEWAY = 5,
+    RTA_PRIORITY = 6,
+    RTA_PREFSRC = 7,
+    RTA_TABLE = 15,
+}
+
+/// Device types (`ARPHRD_*`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if_arp.h#L30>.
+pub(super) const ARPHRD_ETHER: u16 = 1;
+pub(super) const ARPHRD_LOOPBACK: u16 = 772;
+
+bitflags! {
+    /// Device flags (`IFF_*`).
+    ///
+    /// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if.h#L82>.
+    pub(super) struct IfaceFlags: u32 {
+        const UP = 1 << 0;
+        const BROADCAST = 1 << 1;
+        const DEBUG = 1 << 2;
+        const LOOPBACK = 1 << 3;
+        const POINTOPOINT = 1 << 4;
+        const NOTRAILERS = 1 << 5;
+        const RUNNING = 1 << 6;
+        const NOARP = 1 << 7;
+        const PROMISC = 1 << 8;
+        const ALLMULTI = 1 << 9;
+        const MASTER = 1 << 10;
+        const SLAVE = 1 << 11;
+        const MULTICAST = 1 << 12;
+        const PORTSEL = 1 << 13;
+        const AUTOMEDIA = 1 << 14;
+        const DYNAMIC = 1 << 15;
+        const LOWER_UP = 1 << 16;
+        const DORMANT = 1 << 17;
+        const ECHO = 1 << 18;
+    }
+}
+
+/// Operational states (`IF_OPER_*`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/if.h#L175>.
+pub(super) const IF_OPER_UNKNOWN: u8 = 0;
+pub(super) const IF_OPER_UP: u8 = 6;
+
+/// A permanent address (as opposed to addresses with lifetimes).
+pub(super) const IFA_F_PERMANENT: u8 = 0x80;
+
+/// Route scopes (`RT_SCOPE_*`).
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/rtnetlink.h#L320>.
+pub(super) const RT_SCOPE_UNIVERSE: u8 = 0;
+pub(super) const RT_SCOPE_LINK: u8 = 253;
+pub(super) const RT_SCOPE_HOST: u8 = 254;
+
+/// Routing tables (`RT_TABLE_*`).
+pub(super) const RT_TABLE_UNSPEC: u8 = 0;
+pub(super) const RT_TABLE_MAIN: u8 = 254;
+
+/// Routing protocols (`RTPROT_*`), which indicate how the routes are installed.
+pub(super) const RTPROT_KERNEL: u8 = 2;
+pub(super) const RTPROT_BOOT: u8 = 3;
+
+/// Route types (`RTN_*`).
+pub(super) const RTN_UNICAST: u8 = 1;
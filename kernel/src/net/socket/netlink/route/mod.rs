@@ -0,0 +1,359 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink sockets of the `NETLINK_ROUTE` protocol.
+//!
+//! These sockets are used to query and configure the network interfaces, addresses, and routes.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::{
+    addr::NetlinkSocketAddr,
+    message::parse_segments,
+    table::{BoundPort, PortTable},
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        utils::{InodeMode, Metadata, StatusFlags},
+    },
+    match_sock_option_mut,
+    net::socket::{
+        options::{Error as SocketError, SocketOption},
+        util::{
+            options::{SetSocketLevelOption, SocketOptionSet},
+            send_recv_flags::SendRecvFlags,
+            socket_addr::SocketAddr,
+            MessageHeader,
+        },
+        Socket,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    util::{MultiRead, MultiWrite},
+};
+
+mod kernel;
+mod message;
+
+/// The port table of the `NETLINK_ROUTE` protocol.
+static ROUTE_PORT_TABLE: PortTable = PortTable::new();
+
+/// The maximum size of a datagram that contains the response segments.
+///
+/// A dump may generate many segments, which are split into multiple datagrams so that user
+/// programs with moderately sized buffers can receive them. This is similar to Linux.
+const MAX_RESPONSE_DATAGRAM_LEN: usize = PAGE_SIZE;
+
+pub struct NetlinkRouteSocket {
+    options: RwLock<SocketOptionSet>,
+    inner: Mutex<Inner>,
+    is_nonblocking: AtomicBool,
+    pollee: Pollee,
+}
+
+struct Inner {
+    bound_port: Option<BoundPort>,
+    groups: u32,
+    /// Datagrams that contain the response segments.
+    receive_queue: VecDeque<Vec<u8>>,
+}
+
+impl NetlinkRouteSocket {
+    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+        let inner = Inner {
+            bound_port: None,
+            groups: 0,
+            receive_queue: VecDeque::new(),
+        };
+
+        Arc::new(Self {
+            options: RwLock::new(SocketOptionSet::new_udp()),
+            inner: Mutex::new(inner),
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            pollee: Pollee::new(),
+        })
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, is_nonblocking: bool) {
+        self.is_nonblocking.store(is_nonblocking, Ordering::Relaxed);
+    }
+
+    fn try_send(&self, reader: &mut dyn MultiRead) -> Result<usize> {
+        let mut request = vec![0u8; reader.sum_lens()];
+        reader.read(&mut VmWriter::from(request.as_mut_slice()))?;
+
+        let mut inner = self.inner.lock();
+        let port = inner.bind_or_autobind(0)?;
+
+        let mut responses = Vec::new();
+        for segment in parse_segments(&request) {
+            kernel::handle_request(&segment, port, &mut responses);
+        }
+
+        // Pack the response segments into datagrams.
+        let mut datagram = Vec::new();
+        for segment in responses {
+            if !datagram.is_empty() && datagram.len() + segment.len() > MAX_RESPONSE_DATAGRAM_LEN {
+                inner
+                    .receive_queue
+                    .push_back(core::mem::take(&mut datagram));
+            }
+            datagram.extend_from_slice(&segment);
+        }
+        if !datagram.is_empty() {
+            inner.receive_queue.push_back(datagram);
+        }
+
+        let has_responses = !inner.receive_queue.is_empty();
+        drop(inner);
+
+        if has_responses {
+            self.pollee.notify(IoEvents::IN);
+        }
+
+        Ok(request.len())
+    }
+
+    fn try_recv(&self, writer: &mut dyn MultiWrite, flags: SendRecvFlags) -> Result<usize> {
+        let mut inner = self.inner.lock();
+
+        let Some(datagram) = inner.receive_queue.front() else {
+            return_errno_with_message!(Errno::EAGAIN, "the receive queue is empty");
+        };
+
+        let copied_len = writer.write(&mut VmReader::from(datagram.as_slice()))?;
+        let datagram_len = datagram.len();
+
+        // The datagram is dropped even if it is truncated, unless `MSG_PEEK` is specified.
+        if !flags.contains(SendRecvFlags::MSG_PEEK) {
+            inner.receive_queue.pop_front();
+        }
+        drop(inner);
+
+        self.pollee.invalidate();
+
+        // If `MSG_TRUNC` is specified, the real length of the datagram is returned.
+        if flags.contains(SendRecvFlags::MSG_TRUNC) {
+            Ok(datagram_len)
+        } else {
+            Ok(copied_len)
+        }
+    }
+
+    fn recv(&self, writer: &mut dyn MultiWrite, flags: SendRecvFlags) -> Result<usize> {
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
+            self.try_recv(writer, flags)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_recv(writer, flags))
+        }
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let inner = self.inner.lock();
+
+        if inner.receive_queue.is_empty() {
+            IoEvents::OUT
+        } else {
+            IoEvents::IN | IoEvents::OUT
+        }
+    }
+}
+
+impl Inner {
+    /// Binds the socket to `port` if it is not bound, or checks that the bound port is `port`.
+    ///
+    /// If `port` is zero, any port is acceptable. This method returns the bound port.
+    fn bind_or_autobind(&mut self, port: u32) -> Result<u32> {
+        if let Some(bound_port) = self.bound_port.as_ref() {
+            if port != 0 && port != bound_port.port() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the socket is already bound to a different port"
+                );
+            }
+            return Ok(bound_port.port());
+        }
+
+        let bound_port = ROUTE_PORT_TABLE.bind(port)?;
+        let port = bound_port.port();
+        self.bound_port = Some(bound_port);
+        Ok(port)
+    }
+}
+
+impl Pollable for NetlinkRouteSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for NetlinkRouteSocket {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        // TODO: Set correct flags
+        let flags = SendRecvFlags::empty();
+        self.recv(writer, flags)
+    }
+
+    fn write(&self, reader: &mut VmReader) -> Result<usize> {
+        self.try_send(reader)
+    }
+
+    fn as_socket(self: Arc<Self>) -> Option<Arc<dyn Socket>> {
+        Some(self)
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        // TODO: when we fully support O_ASYNC, return the flag
+        if self.is_nonblocking() {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        if new_flags.contains(StatusFlags::O_NONBLOCK) {
+            self.set_nonblocking(true);
+        } else {
+            self.set_nonblocking(false);
+        }
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "SockFS" and link `NetlinkRouteSocket` to it.
+        Metadata::new_socket(
+            0,
+            InodeMode::from_bits_truncate(0o140777),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+}
+
+impl Socket for NetlinkRouteSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        let addr = NetlinkSocketAddr::try_from(socket_addr)?;
+
+        let mut inner = self.inner.lock();
+        inner.bind_or_autobind(addr.port())?;
+
+        if addr.groups() != 0 {
+            // TODO: Support multicast groups.
+            warn!("multicast groups of netlink route sockets are not supported");
+        }
+        inner.groups = addr.groups();
+
+        Ok(())
+    }
+
+    fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
+        let addr = NetlinkSocketAddr::try_from(socket_addr)?;
+        if addr.port() != 0 {
+            // TODO: Support communicating with other netlink sockets in user space.
+            return_errno_with_message!(Errno::ECONNREFUSED, "only the kernel can be connected to");
+        }
+
+        self.inner.lock().bind_or_autobind(0)?;
+
+        Ok(())
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let inner = self.inner.lock();
+
+        let port = inner
+            .bound_port
+            .as_ref()
+            .map_or(0, |bound_port| bound_port.port());
+        Ok(NetlinkSocketAddr::new(port, inner.groups).into())
+    }
+
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        Ok(NetlinkSocketAddr::kernel().into())
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        if let Some(addr) = addr {
+            let addr = NetlinkSocketAddr::try_from(addr)?;
+            if addr.port() != 0 {
+                // TODO: Support communicating with other netlink sockets in user space.
+                return_errno_with_message!(
+                    Errno::ECONNREFUSED,
+                    "only the kernel can receive netlink messages"
+                );
+            }
+        }
+
+        if !control_messages.is_empty() {
+            // TODO: Support sending control message
+            warn!("sending control message is not supported");
+        }
+
+        self.try_send(reader)
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let received_len = self.recv(writer, flags)?;
+
+        // TODO: Receive control message
+
+        let message_header =
+            MessageHeader::new(Some(NetlinkSocketAddr::kernel().into()), Vec::new());
+
+        Ok((received_len, message_header))
+    }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        match_sock_option_mut!(option, {
+            socket_errors: SocketError => {
+                self.options.write().get_and_clear_sock_errors(socket_errors);
+                return Ok(());
+            },
+            _ => ()
+        });
+
+        self.options.read().get_option(option)
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        let mut options = self.options.write();
+        let mut inner = self.inner.lock();
+
+        options.set_option(option, &mut *inner)?;
+
+        Ok(())
+    }
+}
+
+impl SetSocketLevelOption for Inner {}
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::collections::btree_set::BTreeSet;
+
+use crate::prelude::*;
+
+/// A table that records the port numbers of bound netlink sockets.
+///
+/// Each netlink protocol has its own table, so sockets of different protocols can use the same
+/// port number.
+pub(super) struct PortTable {
+    inner: SpinLock<PortTableInner>,
+}
+
+struct PortTableInner {
+    used_ports: BTreeSet<u32>,
+    next_ephemeral_port: u32,
+}
+
+/// The first port number to try when the process ID has been used.
+///
+/// Linux allocates ephemeral port numbers downwards from -4096 (in two's complement).
+const EPHEMERAL_PORT_START: u32 = -4096i32 as u32;
+
+impl PortTable {
+    pub(super) const fn new() -> Self {
+        Self {
+            inner: SpinLock::new(PortTableInner {
+                used_ports: BTreeSet::new(),
+                next_ephemeral_port: EPHEMERAL_PORT_START,
+            }),
+        }
+    }
+
+    /// Binds the port.
+    ///
+    /// If the port number is zero, a unique port number will be allocated. Following Linux, the
+    /// process ID is used if possible.
+    pub(super) fn bind(&'static self, port: u32) -> Result<BoundPort> {
+        let mut inner = self.inner.lock();
+
+        let port = if port != 0 {
+            if inner.used_ports.contains(&port) {
+                return_errno_with_message!(Errno::EADDRINUSE, "the netlink port is in use");
+            }
+            port
+        } else {
+            inner.alloc_ephemeral_port()
+        };
+        inner.used_ports.insert(port);
+
+        Ok(BoundPort { table: self, port })
+    }
+}
+
+impl PortTableInner {
+    fn alloc_ephemeral_port(&mut self) -> u32 {
+        let process = current!();
+        let pid = process.pid_ns().local_id_or_zero(process.pid());
+        if pid != 0 && !self.used_ports.contains(&pid) {
+            return pid;
+        }
+
+        loop {
+            let port = self.next_ephemeral_port;
+            self.next_ephemeral_port = match port.wrapping_sub(1) {
+                0 => EPHEMERAL_PORT_START,
+                next_port => next_port,
+            };
+            if !self.used_ports.contains(&port) {
+                return port;
+            }
+        }
+    }
+}
+
+/// A bound port in a [`PortTable`].
+///
+/// The port will be released when the object is dropped.
+pub(super) struct BoundPort {
+    table: &'static PortTable,
+    port: u32,
+}
+
+impl BoundPort {
+    pub(super) fn port(&self) -> u32 {
+        self.port
+    }
+}
+
+impl Drop for BoundPort {
+    fn drop(&mut self) {
+        self.table.inner.lock().used_ports.remove(&self.port);
+    }
+}
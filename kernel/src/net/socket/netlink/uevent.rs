@@ -0,0 +1,451 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink sockets of the `NETLINK_KOBJECT_UEVENT` protocol.
+//!
+//! These sockets receive the uevents that the kernel broadcasts when devices are added, removed,
+//! or changed. The kernel sends uevents to the multicast group [`UEVENT_GROUP_KERNEL`]. Unlike
+//! `NETLINK_ROUTE`, the kernel does not handle any requests. Instead, privileged user programs
+//! (e.g., udev) can multicast messages to the sockets in other groups.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::{
+    addr::NetlinkSocketAddr,
+    table::{BoundPort, PortTable},
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        utils::{InodeMode, Metadata, StatusFlags},
+    },
+    match_sock_option_mut, match_sock_option_ref,
+    net::socket::{
+        options::{Error as SocketError, PassCred, SocketOption},
+        unix::{UnixControlMessage, UnixCredentials},
+        util::{
+            options::{SetSocketLevelOption, SocketOptionSet},
+            send_recv_flags::SendRecvFlags,
+            socket_addr::SocketAddr,
+            ControlMessage, MessageHeader,
+        },
+        Socket,
+    },
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        posix_thread::AsPosixThread,
+        signal::{PollHandle, Pollable, Pollee},
+    },
+    util::{MultiRead, MultiWrite},
+};
+
+/// The port table of the `NETLINK_KOBJECT_UEVENT` protocol.
+static UEVENT_PORT_TABLE: PortTable = PortTable::new();
+
+/// The sockets that have joined multicast groups.
+static UEVENT_LISTENERS: Mutex<Vec<Weak<NetlinkUeventSocket>>> = Mutex::new(Vec::new());
+
+/// The multicast group to which the kernel sends uevents.
+pub const UEVENT_GROUP_KERNEL: u32 = 1;
+
+/// The maximum number of datagrams that can be queued in a socket.
+///
+/// New datagrams are dropped if the receive queue is full, so a slow listener cannot make the
+/// kernel run out of memory.
+const MAX_RECEIVE_QUEUE_LEN: usize = 256;
+
+pub struct NetlinkUeventSocket {
+    options: RwLock<SocketOptionSet>,
+    inner: Mutex<Inner>,
+    is_nonblocking: AtomicBool,
+    is_pass_cred: AtomicBool,
+    pollee: Pollee,
+    weak_self: Weak<Self>,
+}
+
+struct Inner {
+    bound_port: Option<BoundPort>,
+    groups: u32,
+    is_listening: bool,
+    receive_queue: VecDeque<Datagram>,
+}
+
+struct Datagram {
+    src_addr: NetlinkSocketAddr,
+    cred: UnixCredentials,
+    data: Vec<u8>,
+}
+
+/// Broadcasts a uevent to the sockets in the [`UEVENT_GROUP_KERNEL`] group.
+pub fn broadcast_uevent(message: &[u8]) {
+    multicast(
+        UEVENT_GROUP_KERNEL,
+        NetlinkSocketAddr::new(0, UEVENT_GROUP_KERNEL),
+        UnixCredentials::new_kernel(),
+        message,
+    );
+}
+
+fn multicast(groups: u32, src_addr: NetlinkSocketAddr, cred: UnixCredentials, data: &[u8]) {
+    let listeners: Vec<Arc<NetlinkUeventSocket>> = {
+        let mut listeners = UEVENT_LISTENERS.lock();
+        listeners.retain(|listener| listener.strong_count() > 0);
+        listeners.iter().filter_map(Weak::upgrade).collect()
+    };
+
+    for listener in listeners {
+        listener.deliver(groups, src_addr, cred, data);
+    }
+}
+
+impl NetlinkUeventSocket {
+    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+        let inner = Inner {
+            bound_port: None,
+            groups: 0,
+            is_listening: false,
+            receive_queue: VecDeque::new(),
+        };
+
+        Arc::new_cyclic(|weak_self| Self {
+            options: RwLock::new(SocketOptionSet::new_udp()),
+            inner: Mutex::new(inner),
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_pass_cred: AtomicBool::new(false),
+            pollee: Pollee::new(),
+            weak_self: weak_self.clone(),
+        })
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, is_nonblocking: bool) {
+        self.is_nonblocking.store(is_nonblocking, Ordering::Relaxed);
+    }
+
+    fn is_pass_cred(&self) -> bool {
+        self.is_pass_cred.load(Ordering::Relaxed)
+    }
+
+    /// Queues a datagram that is multicast to `groups`, if the socket has joined any of them.
+    fn deliver(
+        &self,
+        groups: u32,
+        src_addr: NetlinkSocketAddr,
+        cred: UnixCredentials,
+        data: &[u8],
+    ) {
+        let mut inner = self.inner.lock();
+
+        if inner.groups & groups == 0 {
+            return;
+        }
+        if inner.receive_queue.len() >= MAX_RECEIVE_QUEUE_LEN {
+            debug!("the receive queue is full, dropping the uevent");
+            return;
+        }
+
+        inner.receive_queue.push_back(Datagram {
+            src_addr,
+            cred,
+            data: data.to_vec(),
+        });
+        drop(inner);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    fn try_send(&self, reader: &mut dyn MultiRead, dst_addr: NetlinkSocketAddr) -> Result<usize> {
+        if dst_addr.port() != 0 {
+            // TODO: Support unicasting to other netlink sockets in user space.
+            return_errno_with_message!(
+                Errno::ECONNREFUSED,
+                "only the kernel can receive unicast netlink messages"
+            );
+        }
+
+        let mut data = vec![0u8; reader.sum_lens()];
+        reader.read(&mut VmWriter::from(data.as_mut_slice()))?;
+
+        let port = self.inner.lock().bind_or_autobind(0)?;
+
+        // The kernel does not handle any messages, so only multicast messages are delivered.
+        if dst_addr.groups() != 0 {
+            let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+            if !credentials.effective_capset().contains(CapSet::NET_ADMIN) {
+                return_errno_with_message!(Errno::EPERM, "`CAP_NET_ADMIN` is required");
+            }
+            multicast(
+                dst_addr.groups(),
+                NetlinkSocketAddr::new(port, dst_addr.groups()),
+                UnixCredentials::new_current(),
+                &data,
+            );
+        }
+
+        Ok(data.len())
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, NetlinkSocketAddr, UnixCredentials)> {
+        let mut inner = self.inner.lock();
+
+        let Some(datagram) = inner.receive_queue.front() else {
+            return_errno_with_message!(Errno::EAGAIN, "the receive queue is empty");
+        };
+
+        let copied_len = writer.write(&mut VmReader::from(datagram.data.as_slice()))?;
+        let datagram_len = datagram.data.len();
+        let src_addr = datagram.src_addr;
+        let cred = datagram.cred;
+
+        // The datagram is dropped even if it is truncated, unless `MSG_PEEK` is specified.
+        if !flags.contains(SendRecvFlags::MSG_PEEK) {
+            inner.receive_queue.pop_front();
+        }
+        drop(inner);
+
+        self.pollee.invalidate();
+
+        // If `MSG_TRUNC` is specified, the real length of the datagram is returned.
+        let received_len = if flags.contains(SendRecvFlags::MSG_TRUNC) {
+            datagram_len
+        } else {
+            copied_len
+        };
+        Ok((received_len, src_addr, cred))
+    }
+
+    fn recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, NetlinkSocketAddr, UnixCredentials)> {
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
+            self.try_recv(writer, flags)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_recv(writer, flags))
+        }
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let inner = self.inner.lock();
+
+        if inner.receive_queue.is_empty() {
+            IoEvents::OUT
+        } else {
+            IoEvents::IN | IoEvents::OUT
+        }
+    }
+}
+
+impl Inner {
+    /// Binds the socket to `port` if it is not bound, or checks that the bound port is `port`.
+    ///
+    /// If `port` is zero, any port is acceptable. This method returns the bound port.
+    fn bind_or_autobind(&mut self, port: u32) -> Result<u32> {
+        if let Some(bound_port) = self.bound_port.as_ref() {
+            if port != 0 && port != bound_port.port() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the socket is already bound to a different port"
+                );
+            }
+            return Ok(bound_port.port());
+        }
+
+        let bound_port = UEVENT_PORT_TABLE.bind(port)?;
+        let port = bound_port.port();
+        self.bound_port = Some(bound_port);
+        Ok(port)
+    }
+}
+
+impl Pollable for NetlinkUeventSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for NetlinkUeventSocket {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        // TODO: Set correct flags
+        let flags = SendRecvFlags::empty();
+        self.recv(writer, flags)
+            .map(|(received_len, _, _)| received_len)
+    }
+
+    fn write(&self, reader: &mut VmReader) -> Result<usize> {
+        self.try_send(reader, NetlinkSocketAddr::kernel())
+    }
+
+    fn as_socket(self: Arc<Self>) -> Option<Arc<dyn Socket>> {
+        Some(self)
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        // TODO: when we fully support O_ASYNC, return the flag
+        if self.is_nonblocking() {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        if new_flags.contains(StatusFlags::O_NONBLOCK) {
+            self.set_nonblocking(true);
+        } else {
+            self.set_nonblocking(false);
+        }
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "SockFS" and link `NetlinkUeventSocket` to it.
+        Metadata::new_socket(
+            0,
+            InodeMode::from_bits_truncate(0o140777),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+}
+
+impl Socket for NetlinkUeventSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        let addr = NetlinkSocketAddr::try_from(socket_addr)?;
+
+        let mut inner = self.inner.lock();
+        inner.bind_or_autobind(addr.port())?;
+
+        // Unprivileged sockets are allowed to receive uevents, like in Linux.
+        inner.groups = addr.groups();
+        if inner.groups != 0 && !inner.is_listening {
+            inner.is_listening = true;
+            UEVENT_LISTENERS.lock().push(self.weak_self.clone());
+        }
+
+        Ok(())
+    }
+
+    fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
+        let addr = NetlinkSocketAddr::try_from(socket_addr)?;
+        if addr.port() != 0 {
+            // TODO: Support communicating with other netlink sockets in user space.
+            return_errno_with_message!(Errno::ECONNREFUSED, "only the kernel can be connected to");
+        }
+
+        self.inner.lock().bind_or_autobind(0)?;
+
+        Ok(())
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let inner = self.inner.lock();
+
+        let port = inner
+            .bound_port
+            .as_ref()
+            .map_or(0, |bound_port| bound_port.port());
+        Ok(NetlinkSocketAddr::new(port, inner.groups).into())
+    }
+
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        Ok(NetlinkSocketAddr::kernel().into())
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        let dst_addr = match addr {
+            Some(addr) => NetlinkSocketAddr::try_from(addr)?,
+            None => NetlinkSocketAddr::kernel(),
+        };
+
+        if !control_messages.is_empty() {
+            // TODO: Support sending control message
+            warn!("sending control message is not supported");
+        }
+
+        self.try_send(reader, dst_addr)
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let (received_len, src_addr, cred) = self.recv(writer, flags)?;
+
+        let control_messages = if self.is_pass_cred() {
+            vec![ControlMessage::Unix(UnixControlMessage::Credentials(cred))]
+        } else {
+            Vec::new()
+        };
+        let message_header = MessageHeader::new(Some(src_addr.into()), control_messages);
+
+        Ok((received_len, message_header))
+    }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        match_sock_option_mut!(option, {
+            socket_errors: SocketError => {
+                self.options.write().get_and_clear_sock_errors(socket_errors);
+                return Ok(());
+            },
+            socket_pass_cred: PassCred => {
+                socket_pass_cred.set(self.is_pass_cred());
+                return Ok(());
+            },
+            _ => ()
+        });
+
+        self.options.read().get_option(option)
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        match_sock_option_ref!(option, {
+            socket_pass_cred: PassCred => {
+                let is_pass_cred = socket_pass_cred.get().unwrap();
+                self.is_pass_cred.store(*is_pass_cred, Ordering::Relaxed);
+                return Ok(());
+            },
+            _ => ()
+        });
+
+        let mut options = self.options.write();
+        let mut inner = self.inner.lock();
+
+        options.set_option(option, &mut *inner)?;
+
+        Ok(())
+    }
+}
+
+impl SetSocketLevelOption for Inner {}
@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Ancillary data of UNIX domain sockets.
+//!
+//! UNIX domain sockets can pass files (`SCM_RIGHTS`) and process credentials (`SCM_CREDENTIALS`)
+//! along with the data. See <https://man7.org/linux/man-pages/man7/unix.7.html> for details.
+
+use core::fmt;
+
+use crate::{
+    fs::file_handle::FileLike,
+    net::socket::util::ControlMessage,
+    prelude::*,
+    process::{credentials::capabilities::CapSet, posix_thread::AsPosixThread, Gid, Pid, Uid},
+};
+
+/// A control message of UNIX domain sockets.
+pub enum UnixControlMessage {
+    /// Files that are passed to the receiver (`SCM_RIGHTS`).
+    Files(Vec<Arc<dyn FileLike>>),
+    /// Credentials of the sender (`SCM_CREDENTIALS`).
+    Credentials(UnixCredentials),
+}
+
+impl fmt::Debug for UnixControlMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Files(files) => f.debug_tuple("Files").field(&files.len()).finish(),
+            Self::Credentials(cred) => f.debug_tuple("Credentials").field(cred).finish(),
+        }
+    }
+}
+
+/// The maximum number of files that can be passed in one control message.
+///
+/// This is `SCM_MAX_FD` in Linux.
+pub const MAX_FILES_PER_MESSAGE: usize = 253;
+
+/// Credentials of a process that are passed via UNIX domain sockets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnixCredentials {
+    /// The PID in the root PID namespace.
+    pid: Pid,
+    uid: Uid,
+    gid: Gid,
+}
+
+impl UnixCredentials {
+    /// Returns the credentials of the current process, which contain the real user and group IDs.
+    ///
+    /// These are the credentials attached to messages by default.
+    pub fn new_current() -> Self {
+        let current = current_thread!();
+        let posix_thread = current.as_posix_thread().unwrap();
+        let credentials = posix_thread.credentials();
+
+        Self {
+            pid: posix_thread.process().pid(),
+            uid: credentials.ruid(),
+            gid: credentials.rgid(),
+        }
+    }
+
+    /// Returns the credentials of the current process, which contain the effective user and group
+    /// IDs.
+    ///
+    /// These are the credentials recorded for `SO_PEERCRED`.
+    pub fn new_current_effective() -> Self {
+        let current = current_thread!();
+        let posix_thread = current.as_posix_thread().unwrap();
+        let credentials = posix_thread.credentials();
+
+        Self {
+            pid: posix_thread.process().pid(),
+            uid: credentials.euid(),
+            gid: credentials.egid(),
+        }
+    }
+
+    /// Returns the credentials that indicate there is no peer.
+    pub const fn new_invalid() -> Self {
+        /// The ID reported for unknown users and groups (i.e., `overflowuid` in Linux).
+        const OVERFLOW_ID: u32 = 65534;
+
+        Self {
+            pid: 0,
+            uid: Uid::new(OVERFLOW_ID),
+            gid: Gid::new(OVERFLOW_ID),
+        }
+    }
+
+    /// Returns the credentials of the kernel.
+    ///
+    /// These are the credentials attached to the messages sent by the kernel, e.g., uevents.
+    pub const fn new_kernel() -> Self {
+        Self {
+            pid: 0,
+            uid: Uid::new_root(),
+            gid: Gid::new_root(),
+        }
+    }
+
+    /// Creates the credentials specified by the user.
+    ///
+    /// `pid` is the PID in the PID namespace of the current process. Unless the current process
+    /// has the relevant capabilities, the PID must be its own PID, and the user and group IDs must
+    /// be among its real, effective, and saved IDs.
+    pub fn new_checked(pid: Pid, uid: Uid, gid: Gid) -> Result<Self> {
+        let current = current_thread!();
+        let posix_thread = current.as_posix_thread().unwrap();
+        let process = posix_thread.process();
+        let credentials = posix_thread.credentials();
+        let capset = credentials.effective_capset();
+
+        let global_pid = process.pid_ns().global_id(pid);
+        let global_pid = match global_pid {
+            Some(global_pid)
+                if global_pid == process.pid() || capset.contains(CapSet::SYS_ADMIN) =>
+            {
+                global_pid
+            }
+            _ => return_errno_with_message!(Errno::EPERM, "the PID cannot be specified"),
+        };
+
+        if ![credentials.ruid(), credentials.euid(), credentials.suid()].contains(&uid)
+            && !capset.contains(CapSet::SETUID)
+        {
+            return_errno_with_message!(Errno::EPERM, "the user ID cannot be specified");
+        }
+
+        if ![credentials.rgid(), credentials.egid(), credentials.sgid()].contains(&gid)
+            && !capset.contains(CapSet::SETGID)
+        {
+            return_errno_with_message!(Errno::EPERM, "the group ID cannot be specified");
+        }
+
+        Ok(Self {
+            pid: global_pid,
+            uid,
+            gid,
+        })
+    }
+
+    /// Returns the PID in the root PID namespace.
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    /// Returns the user ID.
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// Returns the group ID.
+    pub fn gid(&self) -> Gid {
+        self.gid
+    }
+}
+
+/// Ancillary data that is attached to a message or to a range of bytes in a stream.
+#[derive(Default, Clone)]
+pub(super) struct AuxData {
+    files: Vec<Arc<dyn FileLike>>,
+    cred: Option<UnixCredentials>,
+}
+
+impl AuxData {
+    /// Collects the ancillary data from the control messages to send.
+    pub(super) fn from_control_messages(control_messages: Vec<ControlMessage>) -> Result<Self> {
+        let mut aux_data = Self::default();
+
+        for control_message in control_messages {
+            match control_message {
+                ControlMessage::Unix(UnixControlMessage::Files(files)) => {
+                    aux_data.files.extend(files);
+                }
+                ControlMessage::Unix(UnixControlMessage::Credentials(cred)) => {
+                    aux_data.cred = Some(cred);
+                }
+            }
+        }
+
+        if aux_data.files.len() > MAX_FILES_PER_MESSAGE {
+            return_errno_with_message!(Errno::EINVAL, "too many files are passed");
+        }
+
+        Ok(aux_data)
+    }
+
+    /// Returns whether there is no ancillary data.
+    pub(super) fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.cred.is_none()
+    }
+
+    /// Sets the credentials if they are not specified explicitly.
+    pub(super) fn set_default_cred(&mut self, cred: UnixCredentials) {
+        self.cred.get_or_insert(cred);
+    }
+
+    /// Converts the ancillary data to the control messages to receive.
+    ///
+    /// Credentials are reported only if `is_pass_cred` is true (i.e., `SO_PASSCRED` is set). In
+    /// this case, `default_cred` is reported if the sender has not specified the credentials.
+    pub(super) fn into_control_messages(
+        self,
+        is_pass_cred: bool,
+        default_cred: impl FnOnce() -> UnixCredentials,
+    ) -> Vec<ControlMessage> {
+        let mut control_messages = Vec::new();
+
+        if is_pass_cred {
+            let cred = self.cred.unwrap_or_else(default_cred);
+            control_messages.push(ControlMessage::Unix(UnixControlMessage::Credentials(cred)));
+        }
+
+        if !self.files.is_empty() {
+            control_messages.push(ControlMessage::Unix(UnixControlMessage::Files(self.files)));
+        }
+
+        control_messages
+    }
+}
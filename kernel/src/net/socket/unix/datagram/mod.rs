@@ -0,0 +1,6 @@
+// SPDX-License-Identifier: MPL-2.0
+
+mod queue;
+mod socket;
+
+pub use socket::UnixDatagramSocket;
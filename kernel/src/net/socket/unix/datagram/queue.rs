@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::sync::WaitQueue;
+
+use crate::{
+    events::IoEvents,
+    net::socket::unix::{
+        addr::{UnixSocketAddr, UnixSocketAddrKey},
+        cmsg::AuxData,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollee},
+    util::MultiWrite,
+};
+
+/// The receive queue of a UNIX datagram socket.
+///
+/// Other sockets send datagrams by pushing them to the queue. The queue can be found by its bound
+/// address via [`lookup_queue`] or held directly by the connected sockets.
+pub(super) struct MessageQueue {
+    inner: Mutex<QueueInner>,
+    pollee: Pollee,
+    /// Senders that are waiting for the queue to have enough space.
+    wait_queue: WaitQueue,
+}
+
+struct QueueInner {
+    messages: VecDeque<Message>,
+    total_len: usize,
+    /// The queue of the connected peer.
+    ///
+    /// If it is set, only the connected peer can send datagrams to this queue.
+    peer: Option<Weak<MessageQueue>>,
+    is_read_shutdown: bool,
+    is_closed: bool,
+}
+
+struct Message {
+    bytes: Vec<u8>,
+    src_addr: UnixSocketAddr,
+    aux_data: AuxData,
+}
+
+impl MessageQueue {
+    pub(super) fn new() -> Self {
+        let inner = QueueInner {
+            messages: VecDeque::new(),
+            total_len: 0,
+            peer: None,
+            is_read_shutdown: false,
+            is_closed: false,
+        };
+
+        Self {
+            inner: Mutex::new(inner),
+            pollee: Pollee::new(),
+            wait_queue: WaitQueue::new(),
+        }
+    }
+
+    /// Tries to push a datagram sent by the socket that owns `sender`.
+    ///
+    /// The ancillary data is taken only if the datagram is pushed successfully.
+    fn try_push(
+        &self,
+        sender: &Arc<MessageQueue>,
+        bytes: &[u8],
+        src_addr: &UnixSocketAddr,
+        aux_data: &mut Option<AuxData>,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+
+        if inner.is_closed {
+            return_errno_with_message!(Errno::ECONNREFUSED, "the receiving socket is closed");
+        }
+        if inner.is_read_shutdown {
+            return_errno_with_message!(
+                Errno::EPIPE,
+                "the receiving socket is shut down for reading"
+            );
+        }
+        if inner
+            .peer
+            .as_ref()
+            .is_some_and(|peer| !Weak::ptr_eq(peer, &Arc::downgrade(sender)))
+        {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "the receiving socket is connected to another socket"
+            );
+        }
+
+        if !inner.messages.is_empty() && inner.total_len + bytes.len() > QUEUE_CAPACITY {
+            return_errno_with_message!(Errno::EAGAIN, "the receive queue is full");
+        }
+
+        inner.total_len += bytes.len();
+        inner.messages.push_back(Message {
+            bytes: bytes.to_vec(),
+            src_addr: src_addr.clone(),
+            aux_data: aux_data.take().unwrap_or_default(),
+        });
+        drop(inner);
+
+        self.pollee.notify(IoEvents::IN);
+
+        Ok(())
+    }
+
+    /// Pushes a datagram sent by the socket that owns `sender`.
+    ///
+    /// If the queue does not have enough space, this method waits for it unless `is_nonblocking`
+    /// is true.
+    pub(super) fn push(
+        &self,
+        sender: &Arc<MessageQueue>,
+        bytes: &[u8],
+        src_addr: &UnixSocketAddr,
+        aux_data: AuxData,
+        is_nonblocking: bool,
+    ) -> Result<()> {
+        if bytes.len() > QUEUE_CAPACITY {
+            return_errno_with_message!(Errno::EMSGSIZE, "the datagram is too large");
+        }
+
+        let mut aux_data = Some(aux_data);
+
+        if is_nonblocking {
+            return self.try_push(sender, bytes, src_addr, &mut aux_data);
+        }
+
+        self.wait_queue.pause_until(|| {
+            match self.try_push(sender, bytes, src_addr, &mut aux_data) {
+                Err(err) if err.error() == Errno::EAGAIN => None,
+                result => Some(result),
+            }
+        })?
+    }
+
+    /// Tries to pop a datagram and writes its bytes to `writer`.
+    ///
+    /// This method returns the real length of the datagram, the number of bytes that are written,
+    /// the source address, and the ancillary data. If `is_peek` is true, the datagram is kept in
+    /// the queue.
+    pub(super) fn try_pop(
+        &self,
+        writer: &mut dyn MultiWrite,
+        is_peek: bool,
+    ) -> Result<(usize, usize, UnixSocketAddr, AuxData)> {
+        let mut inner = self.inner.lock();
+
+        let Some(message) = inner.messages.front() else {
+            if inner.is_read_shutdown {
+                return Ok((0, 0, UnixSocketAddr::Unnamed, AuxData::default()));
+            }
+            return_errno_with_message!(Errno::EAGAIN, "the receive queue is empty");
+        };
+
+        let copied_len = writer.write(&mut VmReader::from(message.bytes.as_slice()))?;
+        let message_len = message.bytes.len();
+
+        let (src_addr, aux_data) = if is_peek {
+            (message.src_addr.clone(), message.aux_data.clone())
+        } else {
+            let message = inner.messages.pop_front().unwrap();
+            inner.total_len -= message_len;
+            (message.src_addr, message.aux_data)
+        };
+        drop(inner);
+
+        if !is_peek {
+            self.pollee.invalidate();
+            self.wait_queue.wake_all();
+        }
+
+        Ok((message_len, copied_len, src_addr, aux_data))
+    }
+
+    /// Sets the queue of the connected peer.
+    pub(super) fn set_peer(&self, peer: &Arc<MessageQueue>) {
+        self.inner.lock().peer = Some(Arc::downgrade(peer));
+    }
+
+    pub(super) fn shutdown_read(&self) {
+        self.inner.lock().is_read_shutdown = true;
+        self.pollee.notify(IoEvents::IN | IoEvents::RDHUP);
+        self.wait_queue.wake_all();
+    }
+
+    /// Marks the queue as closed because its socket is closed.
+    ///
+    /// The pending datagrams are discarded, and no more datagrams can be sent to the queue.
+    pub(super) fn close(&self) {
+        let mut inner = self.inner.lock();
+        inner.is_closed = true;
+        inner.messages.clear();
+        inner.total_len = 0;
+        drop(inner);
+
+        self.wait_queue.wake_all();
+    }
+
+    pub(super) fn poll(
+        &self,
+        mask: IoEvents,
+        poller: Option<&mut PollHandle>,
+        check: impl Fn() -> IoEvents,
+    ) -> IoEvents {
+        self.pollee.poll_with(mask, poller, || {
+            let inner = self.inner.lock();
+
+            let mut events = check();
+            if !inner.messages.is_empty() {
+                events |= IoEvents::IN;
+            }
+            if inner.is_read_shutdown {
+                events |= IoEvents::IN | IoEvents::RDHUP;
+            }
+
+            events
+        })
+    }
+}
+
+/// The maximum total length of the datagrams in a receive queue.
+const QUEUE_CAPACITY: usize = 65536;
+
+static QUEUE_TABLE: QueueTable = QueueTable::new();
+
+/// A table that maps bound addresses to the receive queues.
+struct QueueTable {
+    queues: RwLock<BTreeMap<UnixSocketAddrKey, Weak<MessageQueue>>>,
+}
+
+impl QueueTable {
+    const fn new() -> Self {
+        Self {
+            queues: RwLock::new(BTreeMap::new()),
+        }
+    }
+}
+
+pub(super) fn register_queue(key: UnixSocketAddrKey, queue: &Arc<MessageQueue>) {
+    QUEUE_TABLE
+        .queues
+        .write()
+        .insert(key, Arc::downgrade(queue));
+}
+
+pub(super) fn unregister_queue(key: &UnixSocketAddrKey, queue: &Arc<MessageQueue>) {
+    let mut queues = QUEUE_TABLE.queues.write();
+
+    if queues
+        .get(key)
+        .is_some_and(|registered| Weak::ptr_eq(registered, &Arc::downgrade(queue)))
+    {
+        queues.remove(key);
+    }
+}
+
+pub(super) fn lookup_queue(key: &UnixSocketAddrKey) -> Result<Arc<MessageQueue>> {
+    QUEUE_TABLE
+        .queues
+        .read()
+        .get(key)
+        .and_then(Weak::upgrade)
+        .ok_or_else(|| {
+            Error::with_message(
+                Errno::ECONNREFUSED,
+                "no datagram socket is bound to the remote address",
+            )
+        })
+}
@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::queue::{lookup_queue, register_queue, unregister_queue, MessageQueue};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        utils::{InodeMode, Metadata, StatusFlags},
+    },
+    match_sock_option_mut, match_sock_option_ref,
+    net::socket::{
+        options::{PassCred, PeerCred, SocketOption},
+        unix::{
+            addr::UnixSocketAddrBound,
+            cmsg::{AuxData, UnixCredentials},
+            UnixSocketAddr,
+        },
+        util::{send_recv_flags::SendRecvFlags, socket_addr::SocketAddr, MessageHeader},
+        SockShutdownCmd, Socket,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+    util::{MultiRead, MultiWrite},
+};
+
+pub struct UnixDatagramSocket {
+    inner: Mutex<Inner>,
+    /// The queue that receives the datagrams sent to this socket.
+    queue: Arc<MessageQueue>,
+    /// The credentials of the peer, which are only known for sockets created by `socketpair`.
+    peer_cred: Option<UnixCredentials>,
+    is_nonblocking: AtomicBool,
+    is_pass_cred: AtomicBool,
+    is_write_shutdown: AtomicBool,
+}
+
+struct Inner {
+    addr: Option<UnixSocketAddrBound>,
+    peer: Option<Peer>,
+}
+
+struct Peer {
+    addr: UnixSocketAddr,
+    queue: Arc<MessageQueue>,
+}
+
+impl UnixDatagramSocket {
+    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+        Arc::new(Self::new_with(is_nonblocking, None))
+    }
+
+    pub fn new_pair(is_nonblocking: bool) -> (Arc<Self>, Arc<Self>) {
+        let cred = UnixCredentials::new_current_effective();
+
+        let socket_a = Self::new_with(is_nonblocking, Some(cred));
+        let socket_b = Self::new_with(is_nonblocking, Some(cred));
+
+        socket_a.connect_to(UnixSocketAddr::Unnamed, socket_b.queue.clone());
+        socket_b.connect_to(UnixSocketAddr::Unnamed, socket_a.queue.clone());
+
+        (Arc::new(socket_a), Arc::new(socket_b))
+    }
+
+    fn new_with(is_nonblocking: bool, peer_cred: Option<UnixCredentials>) -> Self {
+        let inner = Inner {
+            addr: None,
+            peer: None,
+        };
+
+        Self {
+            inner: Mutex::new(inner),
+            queue: Arc::new(MessageQueue::new()),
+            peer_cred,
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_pass_cred: AtomicBool::new(false),
+            is_write_shutdown: AtomicBool::new(false),
+        }
+    }
+
+    fn connect_to(&self, addr: UnixSocketAddr, peer_queue: Arc<MessageQueue>) {
+        self.queue.set_peer(&peer_queue);
+        self.inner.lock().peer = Some(Peer {
+            addr,
+            queue: peer_queue,
+        });
+    }
+
+    fn send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote_addr: Option<UnixSocketAddr>,
+        mut aux_data: AuxData,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        if self.is_write_shutdown.load(Ordering::Relaxed) {
+            return_errno_with_message!(Errno::EPIPE, "the socket is shut down for writing");
+        }
+
+        let (src_addr, remote_queue) = {
+            let mut inner = self.inner.lock();
+
+            let remote_queue = match remote_addr {
+                Some(remote_addr) => lookup_queue(&remote_addr.connect()?)?,
+                None => match inner.peer.as_ref() {
+                    Some(peer) => peer.queue.clone(),
+                    None => {
+                        return_errno_with_message!(Errno::ENOTCONN, "the socket is not connected")
+                    }
+                },
+            };
+
+            // The receiver can only know the address of the sender if the sender is bound.
+            // Like Linux, the socket is bound automatically if the credentials are passed.
+            if inner.addr.is_none() && self.is_pass_cred() {
+                self.bind_locked(&mut inner, UnixSocketAddr::Unnamed)?;
+            }
+
+            (UnixSocketAddr::from(inner.addr.clone()), remote_queue)
+        };
+
+        let mut bytes = vec![0u8; reader.sum_lens()];
+        reader.read(&mut VmWriter::from(bytes.as_mut_slice()))?;
+
+        aux_data.set_default_cred(UnixCredentials::new_current());
+
+        let is_nonblocking = self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT);
+        remote_queue.push(&self.queue, &bytes, &src_addr, aux_data, is_nonblocking)?;
+
+        Ok(bytes.len())
+    }
+
+    fn recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, UnixSocketAddr, AuxData)> {
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
+            self.try_recv(writer, flags)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_recv(writer, flags))
+        }
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, UnixSocketAddr, AuxData)> {
+        let (message_len, copied_len, src_addr, aux_data) = self
+            .queue
+            .try_pop(writer, flags.contains(SendRecvFlags::MSG_PEEK))?;
+
+        // If `MSG_TRUNC` is specified, the real length of the datagram is returned.
+        let received_len = if flags.contains(SendRecvFlags::MSG_TRUNC) {
+            message_len
+        } else {
+            copied_len
+        };
+
+        Ok((received_len, src_addr, aux_data))
+    }
+
+    fn bind_locked(&self, inner: &mut Inner, addr: UnixSocketAddr) -> Result<()> {
+        if inner.addr.is_some() {
+            return addr.bind_unnamed();
+        }
+
+        let bound_addr = addr.bind()?;
+        register_queue(bound_addr.to_key(), &self.queue);
+        inner.addr = Some(bound_addr);
+
+        Ok(())
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) {
+        self.is_nonblocking.store(nonblocking, Ordering::Relaxed);
+    }
+
+    fn is_pass_cred(&self) -> bool {
+        self.is_pass_cred.load(Ordering::Relaxed)
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.is_write_shutdown.load(Ordering::Relaxed) {
+            IoEvents::empty()
+        } else {
+            IoEvents::OUT
+        }
+    }
+}
+
+impl Drop for UnixDatagramSocket {
+    fn drop(&mut self) {
+        self.queue.close();
+
+        if let Some(addr) = self.inner.get_mut().addr.as_ref() {
+            unregister_queue(&addr.to_key(), &self.queue);
+        }
+    }
+}
+
+impl Pollable for UnixDatagramSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.queue.poll(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for UnixDatagramSocket {
+    fn as_socket(self: Arc<Self>) -> Option<Arc<dyn Socket>> {
+        Some(self)
+    }
+
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        // TODO: Set correct flags
+        let flags = SendRecvFlags::empty();
+        let (read_len, _, _) = self.recv(writer, flags)?;
+        Ok(read_len)
+    }
+
+    fn write(&self, reader: &mut VmReader) -> Result<usize> {
+        // TODO: Set correct flags
+        let flags = SendRecvFlags::empty();
+        self.send(reader, None, AuxData::default(), flags)
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking() {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.set_nonblocking(new_flags.contains(StatusFlags::O_NONBLOCK));
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "SockFS" and link `UnixDatagramSocket` to it.
+        Metadata::new_socket(
+            0,
+            InodeMode::from_bits_truncate(0o140777),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+}
+
+impl Socket for UnixDatagramSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        let addr = UnixSocketAddr::try_from(socket_addr)?;
+
+        let mut inner = self.inner.lock();
+        self.bind_locked(&mut inner, addr)
+    }
+
+    fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
+        let remote_addr = UnixSocketAddr::try_from(socket_addr)?;
+        let remote_queue = lookup_queue(&remote_addr.connect()?)?;
+
+        self.connect_to(remote_addr, remote_queue);
+
+        Ok(())
+    }
+
+    fn shutdown(&self, cmd: SockShutdownCmd) -> Result<()> {
+        if cmd.shut_read() {
+            self.queue.shutdown_read();
+        }
+        if cmd.shut_write() {
+            self.is_write_shutdown.store(true, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let addr = self.inner.lock().addr.clone();
+
+        Ok(addr.into())
+    }
+
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        let inner = self.inner.lock();
+
+        let Some(peer) = inner.peer.as_ref() else {
+            return_errno_with_message!(Errno::ENOTCONN, "the socket is not connected");
+        };
+
+        Ok(peer.addr.clone().into())
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        let remote_addr = addr.map(UnixSocketAddr::try_from).transpose()?;
+        let aux_data = AuxData::from_control_messages(control_messages)?;
+
+        self.send(reader, remote_addr, aux_data, flags)
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let (received_len, src_addr, aux_data) = self.recv(writer, flags)?;
+
+        // The credentials of the sender are always attached to the datagrams.
+        let control_messages =
+            aux_data.into_control_messages(self.is_pass_cred(), UnixCredentials::new_invalid);
+
+        let message_header = MessageHeader::new(Some(src_addr.into()), control_messages);
+
+        Ok((received_len, message_header))
+    }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        match_sock_option_mut!(option, {
+            socket_pass_cred: PassCred => {
+                socket_pass_cred.set(self.is_pass_cred());
+            },
+            socket_peer_cred: PeerCred => {
+                let peer_cred = self.peer_cred.unwrap_or(UnixCredentials::new_invalid());
+                socket_peer_cred.set(peer_cred);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to get is unknown")
+        });
+
+        Ok(())
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        match_sock_option_ref!(option, {
+            socket_pass_cred: PassCred => {
+                let is_pass_cred = socket_pass_cred.get().unwrap();
+                self.is_pass_cred.store(*is_pass_cred, Ordering::Relaxed);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to set is unknown")
+        });
+
+        Ok(())
+    }
+}
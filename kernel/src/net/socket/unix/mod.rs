@@ -1,8 +1,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod addr;
+mod cmsg;
+mod datagram;
 mod ns;
 mod stream;
 
 pub use addr::UnixSocketAddr;
+pub use cmsg::{UnixControlMessage, UnixCredentials, MAX_FILES_PER_MESSAGE};
+pub use datagram::UnixDatagramSocket;
 pub use stream::UnixStreamSocket;
@@ -8,7 +8,11 @@ use crate::{
     events::IoEvents,
     fs::utils::{Channel, Consumer, Producer},
     net::socket::{
-        unix::{addr::UnixSocketAddrBound, UnixSocketAddr},
+        unix::{
+            addr::UnixSocketAddrBound,
+            cmsg::{AuxData, UnixCredentials},
+            UnixSocketAddr,
+        },
         SockShutdownCmd,
     },
     prelude::*,
@@ -20,31 +24,49 @@ pub(super) struct Connected {
     addr: AddrView,
     reader: Consumer<u8>,
     writer: Producer<u8>,
+    reader_aux: Arc<Mutex<AuxQueue>>,
+    writer_aux: Arc<Mutex<AuxQueue>>,
+    peer_cred: UnixCredentials,
 }
 
 impl Connected {
+    /// Creates a pair of connected sockets.
+    ///
+    /// `peer_cred` and `cred` are the credentials of the peer socket and this socket,
+    /// respectively. They are reported by `SO_PEERCRED` of the other socket.
     pub(super) fn new_pair(
         addr: Option<UnixSocketAddrBound>,
         peer_addr: Option<UnixSocketAddrBound>,
         reader_pollee: Option<Pollee>,
         writer_pollee: Option<Pollee>,
+        cred: UnixCredentials,
+        peer_cred: UnixCredentials,
     ) -> (Connected, Connected) {
         let (writer_peer, reader_this) =
             Channel::with_capacity_and_pollees(DEFAULT_BUF_SIZE, None, reader_pollee).split();
         let (writer_this, reader_peer) =
             Channel::with_capacity_and_pollees(DEFAULT_BUF_SIZE, writer_pollee, None).split();
 
+        let aux_to_this = Arc::new(Mutex::new(AuxQueue::new()));
+        let aux_to_peer = Arc::new(Mutex::new(AuxQueue::new()));
+
         let (addr_this, addr_peer) = AddrView::new_pair(addr, peer_addr);
 
         let this = Connected {
             addr: addr_this,
             reader: reader_this,
             writer: writer_this,
+            reader_aux: aux_to_this.clone(),
+            writer_aux: aux_to_peer.clone(),
+            peer_cred,
         };
         let peer = Connected {
             addr: addr_peer,
             reader: reader_peer,
             writer: writer_peer,
+            reader_aux: aux_to_peer,
+            writer_aux: aux_to_this,
+            peer_cred: cred,
         };
 
         (this, peer)
@@ -71,12 +93,61 @@ impl Connected {
         Ok(())
     }
 
-    pub(super) fn try_read(&self, writer: &mut dyn MultiWrite) -> Result<usize> {
-        self.reader.try_read(writer)
+    pub(super) fn peer_cred(&self) -> UnixCredentials {
+        self.peer_cred
     }
 
-    pub(super) fn try_write(&self, reader: &mut dyn MultiRead) -> Result<usize> {
-        self.writer.try_write(reader)
+    /// Reads bytes and the ancillary data attached to them.
+    ///
+    /// Like Linux, a read never goes past the bytes that the ancillary data is attached to, so
+    /// the ancillary data of different writes are never merged in a single read.
+    pub(super) fn try_read(&self, writer: &mut dyn MultiWrite) -> Result<(usize, Option<AuxData>)> {
+        let mut aux_queue = self.reader_aux.lock();
+
+        let Some(max_len) = aux_queue
+            .max_read_len()
+            .filter(|max_len| *max_len < writer.sum_lens())
+        else {
+            let read_len = self.reader.try_read(writer)?;
+            return Ok((read_len, aux_queue.advance_read(read_len)));
+        };
+
+        let mut buf = vec![0u8; max_len];
+        let read_len = self
+            .reader
+            .try_read(&mut VmWriter::from(buf.as_mut_slice()).to_fallible())?;
+        let aux_data = aux_queue.advance_read(read_len);
+
+        writer.write(&mut VmReader::from(&buf[..read_len]))?;
+
+        Ok((read_len, aux_data))
+    }
+
+    /// Writes bytes and attaches the ancillary data to them.
+    ///
+    /// The ancillary data is taken only if some bytes are written.
+    pub(super) fn try_write(
+        &self,
+        reader: &mut dyn MultiRead,
+        aux_data: &mut Option<AuxData>,
+    ) -> Result<usize> {
+        let mut aux_queue = self.writer_aux.lock();
+
+        let write_len = self.writer.try_write(reader)?;
+
+        let start = aux_queue.write_pos;
+        aux_queue.write_pos += write_len;
+        if write_len > 0 {
+            if let Some(aux_data) = aux_data.take() {
+                aux_queue.records.push_back(AuxRecord {
+                    start,
+                    end: start + write_len,
+                    aux_data,
+                });
+            }
+        }
+
+        Ok(write_len)
     }
 
     pub(super) fn shutdown(&self, cmd: SockShutdownCmd) {
@@ -156,4 +227,55 @@ impl AddrView {
     }
 }
 
+/// A queue of the ancillary data attached to the bytes in a stream.
+struct AuxQueue {
+    /// The total number of bytes that have been written.
+    write_pos: usize,
+    /// The total number of bytes that have been read.
+    read_pos: usize,
+    records: VecDeque<AuxRecord>,
+}
+
+/// The ancillary data attached to the bytes in `start..end`.
+struct AuxRecord {
+    start: usize,
+    end: usize,
+    aux_data: AuxData,
+}
+
+impl AuxQueue {
+    fn new() -> Self {
+        Self {
+            write_pos: 0,
+            read_pos: 0,
+            records: VecDeque::new(),
+        }
+    }
+
+    /// Returns the maximum number of bytes that can be read before passing the end of the bytes
+    /// that the next ancillary data is attached to.
+    fn max_read_len(&self) -> Option<usize> {
+        self.records
+            .front()
+            .map(|record| record.end - self.read_pos)
+    }
+
+    /// Records that `len` bytes have been read.
+    ///
+    /// This method returns the ancillary data if it is attached to any of the bytes.
+    fn advance_read(&mut self, len: usize) -> Option<AuxData> {
+        self.read_pos += len;
+
+        if self
+            .records
+            .front()
+            .is_some_and(|record| record.start < self.read_pos)
+        {
+            self.records.pop_front().map(|record| record.aux_data)
+        } else {
+            None
+        }
+    }
+}
+
 const DEFAULT_BUF_SIZE: usize = 65536;
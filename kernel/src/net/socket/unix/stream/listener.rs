@@ -13,7 +13,10 @@ use crate::{
     events::IoEvents,
     fs::file_handle::FileLike,
     net::socket::{
-        unix::addr::{UnixSocketAddrBound, UnixSocketAddrKey},
+        unix::{
+            addr::{UnixSocketAddrBound, UnixSocketAddrKey},
+            cmsg::UnixCredentials,
+        },
         SockShutdownCmd, SocketAddr,
     },
     prelude::*,
@@ -51,11 +54,14 @@ impl Listener {
         self.backlog.addr()
     }
 
-    pub(super) fn try_accept(&self) -> Result<(Arc<dyn FileLike>, SocketAddr)> {
+    /// Accepts a pending connection.
+    ///
+    /// `is_pass_cred` is inherited by the accepted socket, which is the same as Linux.
+    pub(super) fn try_accept(&self, is_pass_cred: bool) -> Result<(Arc<dyn FileLike>, SocketAddr)> {
         let connected = self.backlog.pop_incoming()?;
         let peer_addr = connected.peer_addr().into();
 
-        let socket = UnixStreamSocket::new_connected(connected, false);
+        let socket = UnixStreamSocket::new_connected(connected, false, is_pass_cred);
         Ok((socket, peer_addr))
     }
 
@@ -150,6 +156,7 @@ impl BacklogTable {
 
 pub(super) struct Backlog {
     addr: UnixSocketAddrBound,
+    cred: UnixCredentials,
     pollee: Pollee,
     backlog: AtomicUsize,
     incoming_conns: SpinLock<Option<VecDeque<Connected>>>,
@@ -158,6 +165,9 @@ pub(super) struct Backlog {
 
 impl Backlog {
     fn new(addr: UnixSocketAddrBound, pollee: Pollee, backlog: usize, is_shutdown: bool) -> Self {
+        // The credentials are recorded when `listen` is called, which is the same as Linux.
+        let cred = UnixCredentials::new_current_effective();
+
         let incoming_sockets = if is_shutdown {
             None
         } else {
@@ -166,6 +176,7 @@ impl Backlog {
 
         Self {
             addr,
+            cred,
             pollee,
             backlog: AtomicUsize::new(backlog),
             incoming_conns: SpinLock::new(incoming_sockets),
@@ -260,7 +271,7 @@ impl Backlog {
             ));
         }
 
-        let (client_conn, server_conn) = init.into_connected(self.addr.clone());
+        let (client_conn, server_conn) = init.into_connected(self.addr.clone(), self.cred);
 
         incoming_conns.push_back(server_conn);
         self.pollee.notify(IoEvents::IN);
@@ -15,8 +15,13 @@ use crate::{
         file_handle::FileLike,
         utils::{InodeMode, Metadata, StatusFlags},
     },
+    match_sock_option_mut, match_sock_option_ref,
     net::socket::{
-        unix::UnixSocketAddr,
+        options::{PassCred, PeerCred, SocketOption},
+        unix::{
+            cmsg::{AuxData, UnixCredentials},
+            UnixSocketAddr,
+        },
         util::{send_recv_flags::SendRecvFlags, socket_addr::SocketAddr, MessageHeader},
         SockShutdownCmd, Socket,
     },
@@ -28,6 +33,7 @@ use crate::{
 pub struct UnixStreamSocket {
     state: RwMutex<Takeable<State>>,
     is_nonblocking: AtomicBool,
+    is_pass_cred: AtomicBool,
 }
 
 impl UnixStreamSocket {
@@ -35,13 +41,19 @@ impl UnixStreamSocket {
         Arc::new(Self {
             state: RwMutex::new(Takeable::new(State::Init(init))),
             is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_pass_cred: AtomicBool::new(false),
         })
     }
 
-    pub(super) fn new_connected(connected: Connected, is_nonblocking: bool) -> Arc<Self> {
+    pub(super) fn new_connected(
+        connected: Connected,
+        is_nonblocking: bool,
+        is_pass_cred: bool,
+    ) -> Arc<Self> {
         Arc::new(Self {
             state: RwMutex::new(Takeable::new(State::Connected(connected))),
             is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_pass_cred: AtomicBool::new(is_pass_cred),
         })
     }
 }
@@ -58,39 +70,60 @@ impl UnixStreamSocket {
     }
 
     pub fn new_pair(is_nonblocking: bool) -> (Arc<Self>, Arc<Self>) {
-        let (conn_a, conn_b) = Connected::new_pair(None, None, None, None);
+        let cred = UnixCredentials::new_current_effective();
+        let (conn_a, conn_b) = Connected::new_pair(None, None, None, None, cred, cred);
         (
-            Self::new_connected(conn_a, is_nonblocking),
-            Self::new_connected(conn_b, is_nonblocking),
+            Self::new_connected(conn_a, is_nonblocking, false),
+            Self::new_connected(conn_b, is_nonblocking, false),
         )
     }
 
-    fn send(&self, reader: &mut dyn MultiRead, flags: SendRecvFlags) -> Result<usize> {
-        if self.is_nonblocking() {
-            self.try_send(reader, flags)
+    fn send(
+        &self,
+        reader: &mut dyn MultiRead,
+        aux_data: &mut Option<AuxData>,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
+            self.try_send(reader, aux_data, flags)
         } else {
-            self.wait_events(IoEvents::OUT, None, || self.try_send(reader, flags))
+            self.wait_events(IoEvents::OUT, None, || {
+                self.try_send(reader, aux_data, flags)
+            })
         }
     }
 
-    fn try_send(&self, buf: &mut dyn MultiRead, _flags: SendRecvFlags) -> Result<usize> {
+    fn try_send(
+        &self,
+        buf: &mut dyn MultiRead,
+        aux_data: &mut Option<AuxData>,
+        _flags: SendRecvFlags,
+    ) -> Result<usize> {
         match self.state.read().as_ref() {
-            State::Connected(connected) => connected.try_write(buf),
+            State::Connected(connected) => connected.try_write(buf, aux_data),
             State::Init(_) | State::Listen(_) => {
                 return_errno_with_message!(Errno::ENOTCONN, "the socket is not connected")
             }
         }
     }
 
-    fn recv(&self, writer: &mut dyn MultiWrite, flags: SendRecvFlags) -> Result<usize> {
-        if self.is_nonblocking() {
+    fn recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, Option<AuxData>)> {
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
             self.try_recv(writer, flags)
         } else {
             self.wait_events(IoEvents::IN, None, || self.try_recv(writer, flags))
         }
     }
 
-    fn try_recv(&self, buf: &mut dyn MultiWrite, _flags: SendRecvFlags) -> Result<usize> {
+    fn try_recv(
+        &self,
+        buf: &mut dyn MultiWrite,
+        _flags: SendRecvFlags,
+    ) -> Result<(usize, Option<AuxData>)> {
         match self.state.read().as_ref() {
             State::Connected(connected) => connected.try_read(buf),
             State::Init(_) | State::Listen(_) => {
@@ -136,7 +169,7 @@ impl UnixStreamSocket {
 
     fn try_accept(&self) -> Result<(Arc<dyn FileLike>, SocketAddr)> {
         match self.state.read().as_ref() {
-            State::Listen(listen) => listen.try_accept() as _,
+            State::Listen(listen) => listen.try_accept(self.is_pass_cred()) as _,
             State::Init(_) | State::Connected(_) => {
                 return_errno_with_message!(Errno::EINVAL, "the socket is not listening")
             }
@@ -150,6 +183,17 @@ impl UnixStreamSocket {
     fn set_nonblocking(&self, nonblocking: bool) {
         self.is_nonblocking.store(nonblocking, Ordering::Relaxed);
     }
+
+    fn is_pass_cred(&self) -> bool {
+        self.is_pass_cred.load(Ordering::Relaxed)
+    }
+
+    fn peer_cred(&self) -> UnixCredentials {
+        match self.state.read().as_ref() {
+            State::Connected(connected) => connected.peer_cred(),
+            State::Init(_) | State::Listen(_) => UnixCredentials::new_invalid(),
+        }
+    }
 }
 
 impl Pollable for UnixStreamSocket {
@@ -171,14 +215,14 @@ impl FileLike for UnixStreamSocket {
     fn read(&self, writer: &mut VmWriter) -> Result<usize> {
         // TODO: Set correct flags
         let flags = SendRecvFlags::empty();
-        let read_len = self.recv(writer, flags)?;
+        let (read_len, _) = self.recv(writer, flags)?;
         Ok(read_len)
     }
 
     fn write(&self, reader: &mut VmReader) -> Result<usize> {
         // TODO: Set correct flags
         let flags = SendRecvFlags::empty();
-        self.send(reader, flags)
+        self.send(reader, &mut None, flags)
     }
 
     fn status_flags(&self) -> StatusFlags {
@@ -320,15 +364,13 @@ impl Socket for UnixStreamSocket {
         }
 
         let MessageHeader {
-            control_message, ..
+            control_messages, ..
         } = message_header;
 
-        if control_message.is_some() {
-            // TODO: Support sending control message
-            warn!("sending control message is not supported");
-        }
+        let aux_data = AuxData::from_control_messages(control_messages)?;
+        let mut aux_data = (!aux_data.is_empty()).then_some(aux_data);
 
-        self.send(reader, flags)
+        self.send(reader, &mut aux_data, flags)
     }
 
     fn recvmsg(
@@ -341,12 +383,40 @@ impl Socket for UnixStreamSocket {
             warn!("unsupported flags: {:?}", flags);
         }
 
-        let received_bytes = self.recv(writer, flags)?;
+        let (received_bytes, aux_data) = self.recv(writer, flags)?;
 
-        // TODO: Receive control message
+        let control_messages = aux_data
+            .unwrap_or_default()
+            .into_control_messages(self.is_pass_cred(), || self.peer_cred());
 
-        let message_header = MessageHeader::new(None, None);
+        let message_header = MessageHeader::new(None, control_messages);
 
         Ok((received_bytes, message_header))
     }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        match_sock_option_mut!(option, {
+            socket_pass_cred: PassCred => {
+                socket_pass_cred.set(self.is_pass_cred());
+            },
+            socket_peer_cred: PeerCred => {
+                socket_peer_cred.set(self.peer_cred());
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to get is unknown")
+        });
+
+        Ok(())
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        match_sock_option_ref!(option, {
+            socket_pass_cred: PassCred => {
+                let is_pass_cred = socket_pass_cred.get().unwrap();
+                self.is_pass_cred.store(*is_pass_cred, Ordering::Relaxed);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to set is unknown")
+        });
+
+        Ok(())
+    }
 }
@@ -1,9 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use aster_bigtcp::wire::{Ipv4Address, PortNum};
+use aster_bigtcp::wire::{Ipv4Address, Ipv6Address, PortNum};
 
 use crate::{
-    net::socket::{unix::UnixSocketAddr, vsock::addr::VsockSocketAddr},
+    net::socket::{netlink::NetlinkSocketAddr, unix::UnixSocketAddr, vsock::addr::VsockSocketAddr},
     prelude::*,
 };
 
@@ -11,5 +11,7 @@ use crate::{
 pub enum SocketAddr {
     Unix(UnixSocketAddr),
     IPv4(Ipv4Address, PortNum),
+    IPv6(Ipv6Address, PortNum),
     Vsock(VsockSocketAddr),
+    Netlink(NetlinkSocketAddr),
 }
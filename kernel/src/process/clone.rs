@@ -9,16 +9,20 @@ use ostd::{
 };
 
 use super::{
-    posix_thread::{AsPosixThread, PosixThreadBuilder, ThreadName},
+    namespace::{check_clone_namespaces, new_ns_proxy},
+    posix_thread::{thread_table, AsPosixThread, PosixThreadBuilder, ThreadName},
     process_table,
     process_vm::ProcessVm,
     signal::{constants::SIGCHLD, sig_disposition::SigDispositions, sig_num::SigNum},
-    Credentials, Process, ProcessBuilder,
+    Credentials, PidFile, Process, ProcessBuilder,
 };
 use crate::{
     cpu::LinuxAbi,
     current_userspace,
-    fs::{file_table::FileTable, thread_info::ThreadFsInfo},
+    fs::{
+        file_table::{FdFlags, FileTable},
+        thread_info::ThreadFsInfo,
+    },
     prelude::*,
     process::posix_thread::allocate_posix_tid,
     thread::{AsThread, Tid},
@@ -81,7 +85,7 @@ bitflags! {
 #[derive(Debug, Clone, Copy, Default)]
 pub struct CloneArgs {
     pub flags: CloneFlags,
-    pub _pidfd: Option<u64>,
+    pub pidfd: Option<Vaddr>,
     pub child_tid: Vaddr,
     pub parent_tid: Option<Vaddr>,
     pub exit_signal: Option<SigNum>,
@@ -113,7 +117,7 @@ impl CloneArgs {
             flags.contains(CloneFlags::CLONE_PARENT_SETTID),
         ) {
             (false, false) => (None, None),
-            (true, false) => (Some(parent_tid as u64), None),
+            (true, false) => (Some(parent_tid), None),
             (false, true) => (None, Some(parent_tid)),
             (true, true) => {
                 return_errno_with_message!(
@@ -125,7 +129,7 @@ impl CloneArgs {
 
         Ok(Self {
             flags,
-            _pidfd: pidfd,
+            pidfd,
             child_tid,
             parent_tid,
             exit_signal: (exit_signal != 0).then(|| SigNum::from_u8(exit_signal as u8)),
@@ -157,10 +161,17 @@ impl CloneFlags {
             | CloneFlags::CLONE_FS
             | CloneFlags::CLONE_FILES
             | CloneFlags::CLONE_SIGHAND
+            | CloneFlags::CLONE_PIDFD
+            | CloneFlags::CLONE_PTRACE
             | CloneFlags::CLONE_THREAD
+            | CloneFlags::CLONE_NEWNS
+            | CloneFlags::CLONE_NEWUTS
+            | CloneFlags::CLONE_NEWIPC
+            | CloneFlags::CLONE_NEWPID
             | CloneFlags::CLONE_SYSVSEM
             | CloneFlags::CLONE_SETTLS
             | CloneFlags::CLONE_PARENT_SETTID
+            | CloneFlags::CLONE_UNTRACED
             | CloneFlags::CLONE_CHILD_SETTID
             | CloneFlags::CLONE_CHILD_CLEARTID;
         let unsupported_flags = *self - supported_flags;
@@ -181,18 +192,41 @@ pub fn clone_child(
     clone_args: CloneArgs,
 ) -> Result<Tid> {
     clone_args.flags.check_unsupported_flags()?;
+    check_clone_namespaces(clone_args.flags, ctx)?;
     if clone_args.flags.contains(CloneFlags::CLONE_THREAD) {
+        if clone_args.flags.contains(CloneFlags::CLONE_PIDFD) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "CLONE_PIDFD was specified with CLONE_THREAD"
+            );
+        }
+
         let child_task = clone_child_task(ctx, parent_context, clone_args)?;
         let child_thread = child_task.as_thread().unwrap();
+        ctx.posix_thread
+            .ptrace_report_clone(child_thread, clone_args.flags, None)?;
+
+        // The ID should be translated before the child runs, since the child may
+        // exit and release its ID at any time after that.
+        let child_tid = ctx
+            .process
+            .pid_ns()
+            .local_id_or_zero(child_thread.as_posix_thread().unwrap().tid());
         child_thread.run();
 
-        let child_tid = child_thread.as_posix_thread().unwrap().tid();
         Ok(child_tid)
     } else {
         let child_process = clone_child_process(ctx, parent_context, clone_args)?;
+        clone_pidfd(&child_process, clone_args.pidfd, clone_args.flags, ctx)?;
+        ctx.posix_thread.ptrace_report_clone(
+            &child_process.main_thread(),
+            clone_args.flags,
+            clone_args.exit_signal,
+        )?;
+
+        let child_pid = ctx.process.pid_ns().local_id_or_zero(child_process.pid());
         child_process.run();
 
-        let child_pid = child_process.pid();
         Ok(child_pid)
     }
 }
@@ -223,10 +257,10 @@ fn clone_child_task(
     clone_sysvsem(clone_flags)?;
 
     // clone file table
-    let child_file_table = clone_files(posix_thread.file_table(), clone_flags);
+    let child_file_table = clone_files(&posix_thread.file_table(), clone_flags);
 
     // clone fs
-    let child_fs = clone_fs(posix_thread.fs(), clone_flags);
+    let child_fs = clone_fs(&posix_thread.fs(), clone_flags);
 
     let child_root_vmar = process.root_vmar();
     let child_user_space = {
@@ -244,7 +278,13 @@ fn clone_child_task(
     // Inherit sigmask from current thread
     let sig_mask = posix_thread.sig_mask().load(Ordering::Relaxed).into();
 
+    // Inherit namespaces from current thread
+    let child_ns_proxy = posix_thread.ns_proxy().lock().clone();
+
     let child_tid = allocate_posix_tid();
+    let pid_ns = process.pid_ns();
+    pid_ns.alloc_pids(child_tid)?;
+
     let child_task = {
         let credentials = {
             let credentials = ctx.posix_thread.credentials();
@@ -255,21 +295,24 @@ fn clone_child_task(
             .process(posix_thread.weak_process())
             .sig_mask(sig_mask)
             .file_table(child_file_table)
-            .fs(child_fs);
+            .fs(child_fs)
+            .ns_proxy(child_ns_proxy);
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        let child_ns_tid = pid_ns.local_id_or_zero(child_tid);
+        clone_parent_settid(child_ns_tid, clone_args.parent_tid, clone_flags)
+            .inspect_err(|_| pid_ns.release_pids(child_tid))?;
         thread_builder = clone_child_cleartid(thread_builder, clone_args.child_tid, clone_flags);
         thread_builder = clone_child_settid(thread_builder, clone_args.child_tid, clone_flags);
 
         thread_builder.build()
     };
 
-    process
-        .tasks()
-        .lock()
-        .insert(child_task.clone())
-        .map_err(|_| Error::with_message(Errno::EINTR, "the process has exited"))?;
+    if process.tasks().lock().insert(child_task.clone()).is_err() {
+        thread_table::remove_thread(child_tid);
+        pid_ns.release_pids(child_tid);
+        return_errno_with_message!(Errno::EINTR, "the process has exited");
+    }
 
     Ok(child_task)
 }
@@ -287,6 +330,9 @@ fn clone_child_process(
 
     let clone_flags = clone_args.flags;
 
+    // clone namespaces
+    let child_ns_proxy = new_ns_proxy(clone_flags, ctx)?;
+
     // clone vm
     let child_process_vm = {
         let parent_process_vm = process.vm();
@@ -310,10 +356,10 @@ fn clone_child_process(
     };
 
     // clone file table
-    let child_file_table = clone_files(posix_thread.file_table(), clone_flags);
+    let child_file_table = clone_files(&posix_thread.file_table(), clone_flags);
 
     // clone fs
-    let child_fs = clone_fs(posix_thread.fs(), clone_flags);
+    let child_fs = clone_fs(&posix_thread.fs(), clone_flags);
 
     // clone sig dispositions
     let child_sig_dispositions = clone_sighand(process.sig_dispositions(), clone_flags);
@@ -328,6 +374,8 @@ fn clone_child_process(
     let child_nice = process.nice().load(Ordering::Relaxed);
 
     let child_tid = allocate_posix_tid();
+    let child_pid_ns = child_ns_proxy.pid_ns_for_children().clone();
+    child_pid_ns.alloc_pids(child_tid)?;
 
     let child = {
         let child_elf_path = process.executable_path();
@@ -344,10 +392,13 @@ fn clone_child_process(
                 .sig_mask(child_sig_mask)
                 .file_table(child_file_table)
                 .fs(child_fs)
+                .ns_proxy(child_ns_proxy)
         };
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        let child_ns_tid = process.pid_ns().local_id_or_zero(child_tid);
+        clone_parent_settid(child_ns_tid, clone_args.parent_tid, clone_flags)
+            .inspect_err(|_| child_pid_ns.release_pids(child_tid))?;
         child_thread_builder =
             clone_child_cleartid(child_thread_builder, clone_args.child_tid, clone_flags);
         child_thread_builder =
@@ -360,15 +411,21 @@ fn clone_child_process(
             .main_thread_builder(child_thread_builder)
             .process_vm(child_process_vm)
             .sig_dispositions(child_sig_dispositions)
-            .nice(child_nice);
+            .nice(child_nice)
+            .pid_ns(child_pid_ns.clone());
 
-        process_builder.build()?
+        process_builder
+            .build()
+            .inspect_err(|_| child_pid_ns.release_pids(child_tid))?
     };
 
     if let Some(sig) = clone_args.exit_signal {
         child.set_exit_signal(sig);
     };
 
+    child.set_dumpable(process.is_dumpable());
+    child.set_oom_score_adj(process.oom_score_adj());
+
     // Sets parent process and group for child process.
     set_parent_and_group(process, &child);
 
@@ -412,6 +469,27 @@ fn clone_parent_settid(
     Ok(())
 }
 
+/// Creates a pidfd that refers to the child process in the parent's file table if
+/// `CLONE_PIDFD` is specified.
+fn clone_pidfd(
+    child_process: &Arc<Process>,
+    pidfd_ptr: Option<Vaddr>,
+    clone_flags: CloneFlags,
+    ctx: &Context,
+) -> Result<()> {
+    let Some(addr) = pidfd_ptr.filter(|_| clone_flags.contains(CloneFlags::CLONE_PIDFD)) else {
+        return Ok(());
+    };
+
+    let file_table = ctx.posix_thread.file_table();
+    let pid_file = PidFile::new(child_process.clone(), false);
+    let fd = file_table.lock().insert(pid_file, FdFlags::CLOEXEC);
+    ctx.user_space().write_val(addr, &fd).inspect_err(|_| {
+        file_table.lock().close_file(fd);
+    })?;
+    Ok(())
+}
+
 /// Clone child process vm. If CLONE_VM is set, both threads share the same root vmar.
 /// Otherwise, fork a new copy-on-write vmar.
 fn clone_vm(parent_process_vm: &ProcessVm, clone_flags: CloneFlags) -> Result<ProcessVm> {
@@ -460,10 +538,15 @@ fn clone_cpu_context(
 
 fn clone_fs(parent_fs: &Arc<ThreadFsInfo>, clone_flags: CloneFlags) -> Arc<ThreadFsInfo> {
     if clone_flags.contains(CloneFlags::CLONE_FS) {
-        parent_fs.clone()
-    } else {
-        Arc::new(parent_fs.as_ref().clone())
+        return parent_fs.clone();
+    }
+
+    let child_fs = parent_fs.as_ref().clone();
+    // If CLONE_NEWNS is set, the child will be in a new mount namespace.
+    if clone_flags.contains(CloneFlags::CLONE_NEWNS) {
+        child_fs.resolver().write().unshare_mnt_ns();
     }
+    Arc::new(child_fs)
 }
 
 fn clone_files(
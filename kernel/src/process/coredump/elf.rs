@@ -0,0 +1,438 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The ELF format of core files.
+//!
+//! A core file consists of an ELF header, followed by the program headers of
+//! a `PT_NOTE` segment and one `PT_LOAD` segment for each mapping of the
+//! process. The `PT_NOTE` segment describes the process state, including the
+//! registers of each thread (`NT_PRSTATUS`), the process information
+//! (`NT_PRPSINFO`), and the mapped files (`NT_FILE`). The `PT_LOAD` segments
+//! contain the memory.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/fs/binfmt_elf.c>
+
+use core::{mem::size_of, sync::atomic::Ordering};
+
+use align_ext::AlignExt;
+use ostd::cpu::UserContext;
+
+use super::{CoreFile, StoppedThreads};
+use crate::{
+    arch::coredump::{elf_gregs, ElfGregs, ELF_MACHINE},
+    prelude::*,
+    process::{
+        posix_thread::{AsPosixThread, PosixThread},
+        signal::c_types::siginfo_t,
+    },
+    time::timeval_t,
+    vm::{perms::VmPerms, vmar::vm_mapping::VmMappingInfo},
+};
+
+/// Writes the core of the current process to `file`.
+pub(super) fn write_core(
+    file: &mut CoreFile,
+    ctx: &Context,
+    sig_info: &siginfo_t,
+    user_ctx: &UserContext,
+    stopped_threads: &StoppedThreads,
+) -> Result<()> {
+    let root_vmar = ctx.process.root_vmar();
+    let mappings = root_vmar.mappings_info();
+
+    // As in Linux, the `NT_PRSTATUS` of the current thread comes first, followed by the notes of
+    // the process and then the `NT_PRSTATUS` of the other threads. Debuggers treat the first
+    // thread as the one that triggers the core dump.
+    let notes = {
+        let mut notes = Vec::new();
+        write_note(
+            &mut notes,
+            NT_PRSTATUS,
+            new_prstatus(ctx.process, ctx.posix_thread, sig_info, user_ctx).as_bytes(),
+        );
+        write_note(&mut notes, NT_PRPSINFO, new_prpsinfo(ctx).as_bytes());
+        write_note(&mut notes, NT_FILE, &new_file_note(&mappings));
+        for (task, thread_user_ctx) in stopped_threads.threads() {
+            let posix_thread = task.as_posix_thread().unwrap();
+            write_note(
+                &mut notes,
+                NT_PRSTATUS,
+                new_prstatus(ctx.process, posix_thread, sig_info, &thread_user_ctx).as_bytes(),
+            );
+        }
+        notes
+    };
+
+    let phnum = mappings.len() + 1;
+    if phnum >= PN_XNUM as usize {
+        return_errno_with_message!(Errno::EFBIG, "there are too many mappings to dump");
+    }
+    let notes_offset = size_of::<ElfHeader>() + size_of::<ProgramHeader>() * phnum;
+    let data_offset = (notes_offset + notes.len()).align_up(PAGE_SIZE);
+
+    // Write the ELF header.
+    let header = ElfHeader::new_core(phnum as u16);
+    file.write(header.as_bytes())?;
+
+    // Write the program headers.
+    let notes_header = ProgramHeader {
+        type_: PT_NOTE,
+        flags: 0,
+        offset: notes_offset as u64,
+        vaddr: 0,
+        paddr: 0,
+        filesz: notes.len() as u64,
+        memsz: 0,
+        align: 0,
+    };
+    file.write(notes_header.as_bytes())?;
+
+    let mut offset = data_offset;
+    for mapping in mappings.iter() {
+        let size = mapping.range.len();
+        let file_size = if should_dump(mapping) { size } else { 0 };
+        let load_header = ProgramHeader {
+            type_: PT_LOAD,
+            flags: segment_flags(mapping.perms),
+            offset: offset as u64,
+            vaddr: mapping.range.start as u64,
+            paddr: 0,
+            filesz: file_size as u64,
+            memsz: size as u64,
+            align: PAGE_SIZE as u64,
+        };
+        file.write(load_header.as_bytes())?;
+        offset += file_size;
+    }
+
+    // Write the notes.
+    file.write(&notes)?;
+    file.skip(data_offset - (notes_offset + notes.len()))?;
+
+    // Write the memory.
+    let mut page = vec![0u8; PAGE_SIZE];
+    for mapping in mappings.iter().filter(|mapping| should_dump(mapping)) {
+        for page_addr in mapping.range.clone().step_by(PAGE_SIZE) {
+            // Pages that cannot be read, or have never been touched, are left
+            // as holes in the file, which will be read as zeros.
+            match root_vmar.read_page_for_dump(page_addr, &mut page) {
+                Ok(true) => file.write(&page)?,
+                Ok(false) | Err(_) => file.skip(PAGE_SIZE)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns whether the memory of the mapping should be dumped.
+///
+/// This follows the default `coredump_filter` of Linux, where anonymous
+/// mappings and private file-backed mappings that may have been modified are
+/// dumped. The content of other file-backed mappings can be found in the files
+/// listed in the `NT_FILE` note.
+fn should_dump(mapping: &VmMappingInfo) -> bool {
+    if mapping.is_dont_dump || !mapping.perms.contains(VmPerms::READ) {
+        return false;
+    }
+
+    if mapping.file.is_none() {
+        return true;
+    }
+
+    // Linux checks whether any page of a private file-backed mapping has been
+    // copied on write. We approximate it by checking whether the mapping is
+    // writable, which is the case for the data segments of ELF files.
+    !mapping.is_shared && mapping.perms.contains(VmPerms::WRITE)
+}
+
+fn segment_flags(perms: VmPerms) -> u32 {
+    let mut flags = 0;
+    if perms.contains(VmPerms::READ) {
+        flags |= PF_R;
+    }
+    if perms.contains(VmPerms::WRITE) {
+        flags |= PF_W;
+    }
+    if perms.contains(VmPerms::EXEC) {
+        flags |= PF_X;
+    }
+    flags
+}
+
+/// Appends a note, whose name is "CORE", to `notes`.
+fn write_note(notes: &mut Vec<u8>, type_: u32, desc: &[u8]) {
+    const NAME: &[u8] = b"CORE\0";
+
+    let header = NoteHeader {
+        namesz: NAME.len() as u32,
+        descsz: desc.len() as u32,
+        type_,
+    };
+    notes.extend_from_slice(header.as_bytes());
+
+    notes.extend_from_slice(NAME);
+    notes.resize(notes.len().align_up(NOTE_ALIGN), 0);
+    notes.extend_from_slice(desc);
+    notes.resize(notes.len().align_up(NOTE_ALIGN), 0);
+}
+
+fn new_prstatus(
+    process: &Process,
+    posix_thread: &PosixThread,
+    sig_info: &siginfo_t,
+    user_ctx: &UserContext,
+) -> ElfPrStatus {
+    let (user_time, kernel_time) = {
+        let prof_clock = posix_thread.prof_clock();
+        (
+            prof_clock.user_clock().read_time(),
+            prof_clock.kernel_clock().read_time(),
+        )
+    };
+
+    ElfPrStatus {
+        info: ElfSigInfo {
+            signo: sig_info.si_signo,
+            code: sig_info.si_code,
+            errno: sig_info.si_errno,
+        },
+        cursig: sig_info.si_signo as u16,
+        _pad0: 0,
+        sigpend: posix_thread.sig_pending().into(),
+        sighold: posix_thread.sig_mask().load(Ordering::Relaxed).into(),
+        pid: posix_thread.tid(),
+        ppid: process.parent().pid(),
+        pgrp: process.pgid(),
+        sid: process.session().map_or(0, |session| session.sid()),
+        utime: user_time.into(),
+        stime: kernel_time.into(),
+        cutime: timeval_t::default(),
+        cstime: timeval_t::default(),
+        reg: elf_gregs(user_ctx),
+        fpvalid: 0,
+        _pad1: 0,
+    }
+}
+
+fn new_prpsinfo(ctx: &Context) -> ElfPrPsInfo {
+    let process = ctx.process;
+    let posix_thread = ctx.posix_thread;
+
+    let mut fname = [0u8; 16];
+    if let Some(thread_name) = &*posix_thread.thread_name().lock() {
+        if let Ok(Some(name)) = thread_name.name() {
+            let name = name.to_bytes();
+            let len = name.len().min(fname.len() - 1);
+            fname[..len].copy_from_slice(&name[..len]);
+        }
+    }
+
+    // The arguments are separated by spaces, and truncated if they are too
+    // long.
+    let mut psargs = [0u8; 80];
+    if let Ok(argv) = process.init_stack_reader().argv() {
+        let args = argv
+            .iter()
+            .map(|arg| arg.to_bytes())
+            .collect::<Vec<_>>()
+            .join(&b' ');
+        let len = args.len().min(psargs.len() - 1);
+        psargs[..len].copy_from_slice(&args[..len]);
+    }
+
+    let credentials = posix_thread.credentials();
+
+    ElfPrPsInfo {
+        state: 0,
+        sname: b'R',
+        zomb: 0,
+        nice: process.nice().load(Ordering::Relaxed).range().get(),
+        _pad0: 0,
+        flag: 0,
+        uid: credentials.ruid().into(),
+        gid: credentials.rgid().into(),
+        pid: process.pid(),
+        ppid: process.parent().pid(),
+        pgrp: process.pgid(),
+        sid: process.session().map_or(0, |session| session.sid()),
+        fname,
+        psargs,
+    }
+}
+
+/// Creates the description of the `NT_FILE` note.
+///
+/// The description starts with the number of file-backed mappings and the
+/// page size. Then, the start address, the end address, and the file offset
+/// (in pages) of each mapping follow. Finally, the NUL-terminated paths of the
+/// files are listed in the same order.
+fn new_file_note(mappings: &[VmMappingInfo]) -> Vec<u8> {
+    let file_mappings = mappings
+        .iter()
+        .filter_map(|mapping| {
+            let (dentry, offset) = mapping.file.as_ref()?;
+            Some((&mapping.range, dentry, *offset))
+        })
+        .collect::<Vec<_>>();
+
+    let mut desc = Vec::new();
+    desc.extend_from_slice(&(file_mappings.len() as u64).to_ne_bytes());
+    desc.extend_from_slice(&(PAGE_SIZE as u64).to_ne_bytes());
+    for (range, _, offset) in file_mappings.iter() {
+        desc.extend_from_slice(&(range.start as u64).to_ne_bytes());
+        desc.extend_from_slice(&(range.end as u64).to_ne_bytes());
+        desc.extend_from_slice(&((offset / PAGE_SIZE) as u64).to_ne_bytes());
+    }
+    for (_, dentry, _) in file_mappings.iter() {
+        desc.extend_from_slice(dentry.abs_path().as_bytes());
+        desc.push(0);
+    }
+
+    desc
+}
+
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EV_CURRENT: u8 = 1;
+const ELFOSABI_NONE: u8 = 0;
+const ET_CORE: u16 = 4;
+
+/// The special value of `e_phnum`, which means that the number of program
+/// headers is too large to be stored in `e_phnum`.
+const PN_XNUM: u16 = 0xffff;
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_PRPSINFO: u32 = 3;
+const NT_FILE: u32 = 0x46494c45;
+
+const NOTE_ALIGN: usize = 4;
+
+/// The ELF header (i.e., `Elf64_Ehdr`).
+#[derive(Clone, Copy, Pod)]
+#[repr(C)]
+struct ElfHeader {
+    ident: [u8; 16],
+    type_: u16,
+    machine: u16,
+    version: u32,
+    entry: u64,
+    phoff: u64,
+    shoff: u64,
+    flags: u32,
+    ehsize: u16,
+    phentsize: u16,
+    phnum: u16,
+    shentsize: u16,
+    shnum: u16,
+    shstrndx: u16,
+}
+
+impl ElfHeader {
+    fn new_core(phnum: u16) -> Self {
+        let mut ident = [0u8; 16];
+        ident[..4].copy_from_slice(&ELF_MAGIC);
+        ident[4] = ELFCLASS64;
+        ident[5] = ELFDATA2LSB;
+        ident[6] = EV_CURRENT;
+        ident[7] = ELFOSABI_NONE;
+
+        Self {
+            ident,
+            type_: ET_CORE,
+            machine: ELF_MACHINE,
+            version: EV_CURRENT as u32,
+            entry: 0,
+            phoff: size_of::<ElfHeader>() as u64,
+            shoff: 0,
+            flags: 0,
+            ehsize: size_of::<ElfHeader>() as u16,
+            phentsize: size_of::<ProgramHeader>() as u16,
+            phnum,
+            shentsize: 0,
+            shnum: 0,
+            shstrndx: 0,
+        }
+    }
+}
+
+/// The program header (i.e., `Elf64_Phdr`).
+#[derive(Clone, Copy, Pod)]
+#[repr(C)]
+struct ProgramHeader {
+    type_: u32,
+    flags: u32,
+    offset: u64,
+    vaddr: u64,
+    paddr: u64,
+    filesz: u64,
+    memsz: u64,
+    align: u64,
+}
+
+/// The note header (i.e., `Elf64_Nhdr`).
+#[derive(Clone, Copy, Pod)]
+#[repr(C)]
+struct NoteHeader {
+    namesz: u32,
+    descsz: u32,
+    type_: u32,
+}
+
+/// The signal information in `NT_PRSTATUS` (i.e., `elf_siginfo`).
+#[derive(Clone, Copy, Pod)]
+#[repr(C)]
+struct ElfSigInfo {
+    signo: i32,
+    code: i32,
+    errno: i32,
+}
+
+/// The description of `NT_PRSTATUS` (i.e., `elf_prstatus`).
+#[derive(Clone, Copy, Pod)]
+#[repr(C)]
+struct ElfPrStatus {
+    info: ElfSigInfo,
+    cursig: u16,
+    _pad0: u16,
+    sigpend: u64,
+    sighold: u64,
+    pid: u32,
+    ppid: u32,
+    pgrp: u32,
+    sid: u32,
+    utime: timeval_t,
+    stime: timeval_t,
+    cutime: timeval_t,
+    cstime: timeval_t,
+    reg: ElfGregs,
+    fpvalid: i32,
+    _pad1: u32,
+}
+
+/// The description of `NT_PRPSINFO` (i.e., `elf_prpsinfo`).
+#[derive(Clone, Copy, Pod)]
+#[repr(C)]
+struct ElfPrPsInfo {
+    state: u8,
+    sname: u8,
+    zomb: u8,
+    nice: i8,
+    _pad0: u32,
+    flag: u64,
+    uid: u32,
+    gid: u32,
+    pid: u32,
+    ppid: u32,
+    pgrp: u32,
+    sid: u32,
+    fname: [u8; 16],
+    psargs: [u8; 80],
+}
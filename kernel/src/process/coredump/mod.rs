@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Core dumps.
+//!
+//! When a process is terminated by a signal whose default action is to dump
+//! core (e.g., `SIGSEGV` and `SIGABRT`), an ELF core file is written so that
+//! the crash can be debugged post-mortem.
+//!
+//! The name of the core file is determined by the core pattern, which can be
+//! changed via `/proc/sys/kernel/core_pattern`. Its size is limited by
+//! `RLIMIT_CORE`.
+
+mod elf;
+mod threads;
+
+use alloc::borrow::Cow;
+
+use ostd::cpu::UserContext;
+
+pub(super) use self::threads::{stop_other_threads, wait_for_core_dump, CoreState, StoppedThreads};
+use super::{signal::c_types::siginfo_t, ResourceType};
+use crate::{
+    fs::{
+        fs_resolver::{FsPath, AT_FDCWD},
+        inode_handle::InodeHandle,
+        utils::{AccessMode, CreationFlags, InodeMode, InodeType},
+    },
+    prelude::*,
+    time::clocks::RealTimeClock,
+};
+
+/// The maximum length of the core pattern (i.e., `CORENAME_MAX_SIZE` in Linux).
+const MAX_CORE_PATTERN_LEN: usize = 128;
+
+static CORE_PATTERN: RwLock<Cow<'static, str>> = RwLock::new(Cow::Borrowed("core"));
+
+/// Returns the core pattern.
+pub fn core_pattern() -> String {
+    CORE_PATTERN.read().to_string()
+}
+
+/// Sets the core pattern.
+///
+/// The pattern is a file path template, where the following specifiers will
+/// be expanded when a core file is created:
+///  - `%%`: a single `%`;
+///  - `%p`/`%P`: the PID of the dumped process, in its own PID namespace or
+///    in the root PID namespace;
+///  - `%i`/`%I`: the TID of the thread that triggers the core dump, in its own
+///    PID namespace or in the root PID namespace;
+///  - `%u`/`%g`: the real UID/GID of the dumped process;
+///  - `%s`: the number of the signal causing the dump;
+///  - `%t`: the time of the dump, in seconds since the Epoch;
+///  - `%e`: the thread name;
+///  - `%E`: the path of the executable, with slashes replaced by `!`.
+///
+/// Other specifiers are dropped.
+pub fn set_core_pattern(pattern: &str) -> Result<()> {
+    if pattern.len() >= MAX_CORE_PATTERN_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the core pattern is too long");
+    }
+
+    *CORE_PATTERN.write() = Cow::Owned(pattern.to_string());
+    Ok(())
+}
+
+/// Dumps the core of the current process, which is being killed by the signal
+/// described in `sig_info`.
+///
+/// The registers of the current thread are taken from `user_ctx`, and those of
+/// the other threads are recorded in `stopped_threads` when they stop.
+///
+/// This method succeeds only if the core file is completely written.
+pub(super) fn do_coredump(
+    ctx: &Context,
+    sig_info: &siginfo_t,
+    user_ctx: &UserContext,
+    stopped_threads: &StoppedThreads,
+) -> Result<()> {
+    let process = ctx.process;
+    if !process.is_dumpable() {
+        return_errno_with_message!(Errno::EPERM, "the process is not dumpable");
+    }
+
+    let limit = process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_CORE)
+        .get_cur();
+    if limit == 0 {
+        return_errno_with_message!(Errno::EFBIG, "the core file size is limited to zero");
+    }
+
+    let pattern = core_pattern();
+    if pattern.starts_with('|') {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "piping core dumps to a program is not supported"
+        );
+    }
+    let path = expand_core_pattern(&pattern, sig_info, ctx);
+    if path.is_empty() {
+        return_errno_with_message!(Errno::ENOENT, "the core file path is empty");
+    }
+
+    let inode_handle = {
+        let fs_path = FsPath::new(AT_FDCWD, &path)?;
+        let flags = AccessMode::O_WRONLY as u32
+            | (CreationFlags::O_CREAT | CreationFlags::O_NOFOLLOW).bits();
+        let mode = InodeMode::S_IRUSR | InodeMode::S_IWUSR;
+        let fs_ref = ctx.posix_thread.fs();
+        let fs = fs_ref.resolver().read();
+        fs.open(&fs_path, flags, mode.bits())?
+    };
+    // Linux refuses to dump into a file that may be observed by others via
+    // other links, since the core file may contain sensitive data.
+    let metadata = inode_handle.metadata();
+    if metadata.type_ != InodeType::File || metadata.nlinks != 1 {
+        return_errno_with_message!(Errno::EPERM, "the core file is not a regular file");
+    }
+    inode_handle.resize(0)?;
+
+    let mut file = CoreFile::new(inode_handle, limit);
+    elf::write_core(&mut file, ctx, sig_info, user_ctx, stopped_threads)?;
+    file.finish()
+}
+
+/// Expands the specifiers in the core pattern.
+fn expand_core_pattern(pattern: &str, sig_info: &siginfo_t, ctx: &Context) -> String {
+    let mut path = String::new();
+
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            path.push(c);
+            continue;
+        }
+
+        let Some(specifier) = chars.next() else {
+            break;
+        };
+        let expanded = match specifier {
+            '%' => "%".to_string(),
+            'p' => ctx.process.ns_pid().to_string(),
+            'P' => ctx.process.pid().to_string(),
+            'i' => ctx.posix_thread.ns_tid().to_string(),
+            'I' => ctx.posix_thread.tid().to_string(),
+            'u' => u32::from(ctx.posix_thread.credentials().ruid()).to_string(),
+            'g' => u32::from(ctx.posix_thread.credentials().rgid()).to_string(),
+            's' => sig_info.si_signo.to_string(),
+            't' => RealTimeClock::get().read_time().as_secs().to_string(),
+            'e' => ctx
+                .posix_thread
+                .thread_name()
+                .lock()
+                .as_ref()
+                .and_then(|thread_name| thread_name.name().ok().flatten())
+                .map(|name| name.to_string_lossy().replace('/', "!"))
+                .unwrap_or_default(),
+            'E' => ctx.process.executable_path().replace('/', "!"),
+            _ => continue,
+        };
+        path.push_str(&expanded);
+    }
+
+    path
+}
+
+/// A core file, which is written sequentially.
+struct CoreFile {
+    inode_handle: InodeHandle,
+    offset: usize,
+    /// The maximum size of the file, which is the value of `RLIMIT_CORE`.
+    limit: u64,
+}
+
+impl CoreFile {
+    fn new(inode_handle: InodeHandle, limit: u64) -> Self {
+        Self {
+            inode_handle,
+            offset: 0,
+            limit,
+        }
+    }
+
+    /// Writes `buf` at the current offset.
+    fn write(&mut self, buf: &[u8]) -> Result<()> {
+        let end = self.check_limit(buf.len())?;
+
+        let mut reader = VmReader::from(buf).to_fallible();
+        while reader.has_remain() {
+            let offset = end - reader.remain();
+            if self.inode_handle.write_at(offset, &mut reader)? == 0 {
+                return_errno_with_message!(Errno::EIO, "failed to write the core file");
+            }
+        }
+
+        self.offset = end;
+        Ok(())
+    }
+
+    /// Skips `len` bytes, which leaves a hole in the file.
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.offset = self.check_limit(len)?;
+        Ok(())
+    }
+
+    /// Finishes writing by extending the file to cover the trailing hole.
+    fn finish(self) -> Result<()> {
+        self.inode_handle.resize(self.offset)
+    }
+
+    fn check_limit(&self, len: usize) -> Result<usize> {
+        let end = self.offset + len;
+        if end as u64 > self.limit {
+            return_errno_with_message!(Errno::EFBIG, "the core file exceeds RLIMIT_CORE");
+        }
+        Ok(end)
+    }
+}
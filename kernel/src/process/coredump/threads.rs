@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Stopping the other threads of the dumped process.
+//!
+//! While a thread dumps the core, the other threads must not change the memory. So they are
+//! killed with `SIGKILL`, and when they are about to handle the signal, they record their
+//! registers and wait until the dumping thread exits the process. This is similar to
+//! `coredump_wait` in Linux.
+
+use core::ptr;
+
+use ostd::{cpu::UserContext, sync::WaitQueue, task::Task};
+
+use crate::{
+    prelude::*,
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{constants::SIGKILL, signals::kernel::KernelSignal},
+    },
+    thread::AsThread,
+};
+
+/// The state of the threads of a process whose core is being dumped.
+pub(in crate::process) struct CoreState {
+    inner: SpinLock<CoreStateInner>,
+    wait_queue: WaitQueue,
+}
+
+struct CoreStateInner {
+    /// The other threads that have not stopped yet.
+    running: Vec<Arc<Task>>,
+    /// The other threads that have stopped, and their registers.
+    stopped: Vec<(Arc<Task>, UserContext)>,
+    is_finished: bool,
+}
+
+impl CoreState {
+    /// Forgets the current thread, which exits without stopping.
+    ///
+    /// This must be called with the task set of the process locked.
+    pub(in crate::process) fn remove_exited(&self, task: &Task) {
+        let mut inner = self.inner.lock();
+        inner
+            .running
+            .retain(|running| !ptr::eq(running.as_ref(), task));
+        drop(inner);
+
+        self.wait_queue.wake_all();
+    }
+}
+
+/// The other threads of the current process, which are stopped for the core dump.
+///
+/// The threads are resumed when this is dropped, which should be after the current thread has
+/// exited the process. Then they exit silently, so the exit status is not overwritten.
+pub(in crate::process) struct StoppedThreads<'a> {
+    process: &'a Process,
+    state: Arc<CoreState>,
+}
+
+impl StoppedThreads<'_> {
+    /// Returns the stopped threads and their registers.
+    pub(super) fn threads(&self) -> Vec<(Arc<Task>, UserContext)> {
+        self.state.inner.lock().stopped.clone()
+    }
+}
+
+impl Drop for StoppedThreads<'_> {
+    fn drop(&mut self) {
+        self.process.tasks().lock().set_core_state(None);
+
+        self.state.inner.lock().is_finished = true;
+        self.state.wait_queue.wake_all();
+    }
+}
+
+/// Stops the other threads of the current process.
+///
+/// This method returns `None` if the process is exiting, or if another thread is dumping the
+/// core.
+pub(in crate::process) fn stop_other_threads<'a>(ctx: &Context<'a>) -> Option<StoppedThreads<'a>> {
+    let state = {
+        let mut tasks = ctx.process.tasks().lock();
+        if tasks.has_exited_group() || tasks.core_state().is_some() {
+            return None;
+        }
+
+        let running: Vec<_> = tasks
+            .as_slice()
+            .iter()
+            .filter(|task| {
+                !ptr::eq(task.as_ref(), ctx.task) && !task.as_thread().unwrap().is_exited()
+            })
+            .cloned()
+            .collect();
+        for task in running.iter() {
+            task.as_posix_thread()
+                .unwrap()
+                .enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+        }
+
+        let state = Arc::new(CoreState {
+            inner: SpinLock::new(CoreStateInner {
+                running,
+                stopped: Vec::new(),
+                is_finished: false,
+            }),
+            wait_queue: WaitQueue::new(),
+        });
+        tasks.set_core_state(Some(state.clone()));
+        state
+    };
+
+    state
+        .wait_queue
+        .wait_until(|| state.inner.lock().running.is_empty().then_some(()));
+
+    Some(StoppedThreads {
+        process: ctx.process,
+        state,
+    })
+}
+
+/// Stops the current thread if another thread is dumping the core.
+///
+/// The registers of the current thread, i.e., `user_ctx`, are recorded in the core. The method
+/// returns `true` after the dumping thread has exited the process, and the current thread should
+/// exit as well. It returns `false` immediately if no core is being dumped.
+pub(in crate::process) fn wait_for_core_dump(ctx: &Context, user_ctx: &UserContext) -> bool {
+    let Some(state) = ctx.process.tasks().lock().core_state().cloned() else {
+        return false;
+    };
+
+    {
+        let mut inner = state.inner.lock();
+        let Some(position) = inner
+            .running
+            .iter()
+            .position(|running| ptr::eq(running.as_ref(), ctx.task))
+        else {
+            return false;
+        };
+        let task = inner.running.swap_remove(position);
+        inner.stopped.push((task, user_ctx.clone()));
+    }
+    state.wait_queue.wake_all();
+
+    state
+        .wait_queue
+        .wait_until(|| state.inner.lock().is_finished.then_some(()));
+    true
+}
@@ -1,7 +1,14 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::{posix_thread::PosixThread, process_table, Pid, Process};
-use crate::{prelude::*, process::signal::signals::kernel::KernelSignal};
+use super::{
+    posix_thread::{ptrace_detach_all, PosixThread},
+    process_table, Process,
+};
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::signal::{constants::SIGKILL, signals::kernel::KernelSignal},
+};
 
 /// Exits the current POSIX process.
 ///
@@ -19,9 +26,15 @@ pub(super) fn exit_process(current_thread: &PosixThread, current_process: &Proce
 
     send_parent_death_signal(current_process);
 
+    ptrace_detach_all(current_process);
+
+    kill_pid_ns_if_init(current_process);
+
     move_children_to_init(current_process);
 
     send_child_death_signal(current_process);
+
+    current_process.pidfd_pollee().notify(IoEvents::IN);
 }
 
 /// Sends parent-death signals to the children.
@@ -41,13 +54,33 @@ fn send_parent_death_signal(current_process: &Process) {
     }
 }
 
-/// Moves the children to the init process.
-fn move_children_to_init(current_process: &Process) {
-    if is_init_process(current_process) {
+/// Kills all the other processes in the PID namespace if the current process
+/// is the init process of the namespace.
+fn kill_pid_ns_if_init(current_process: &Process) {
+    let pid_ns = current_process.pid_ns();
+    if pid_ns.parent().is_none() || !pid_ns.is_init_process(current_process.pid()) {
         return;
     }
 
-    let Some(init_process) = get_init_process() else {
+    pid_ns.set_dead();
+
+    let process_table = process_table::process_table_mut();
+    for process in pid_ns.visible_processes(&process_table) {
+        if core::ptr::eq(process.as_ref(), current_process) {
+            continue;
+        }
+
+        process.enqueue_signal(KernelSignal::new(SIGKILL));
+    }
+}
+
+/// Moves the children to the init process.
+///
+/// The children are moved to the init process of the nearest PID namespace
+/// whose init process is still alive, starting from the PID namespace of the
+/// current process.
+fn move_children_to_init(current_process: &Process) {
+    let Some(init_process) = find_child_reaper(current_process) else {
         return;
     };
 
@@ -59,6 +92,21 @@ fn move_children_to_init(current_process: &Process) {
     }
 }
 
+fn find_child_reaper(current_process: &Process) -> Option<Arc<Process>> {
+    let mut pid_ns = current_process.pid_ns();
+    loop {
+        if let Some(init_process) = pid_ns.init_process() {
+            if !core::ptr::eq(init_process.as_ref(), current_process)
+                && !init_process.status().is_zombie()
+            {
+                return Some(init_process);
+            }
+        }
+
+        pid_ns = pid_ns.parent()?;
+    }
+}
+
 /// Sends a child-death signal to the parent.
 fn send_child_death_signal(current_process: &Process) {
     let Some(parent) = current_process.parent().lock().process().upgrade() else {
@@ -70,14 +118,3 @@ fn send_child_death_signal(current_process: &Process) {
     };
     parent.children_wait_queue().wake_all();
 }
-
-const INIT_PROCESS_PID: Pid = 1;
-
-/// Gets the init process
-fn get_init_process() -> Option<Arc<Process>> {
-    process_table::get_process(INIT_PROCESS_PID)
-}
-
-fn is_init_process(process: &Process) -> bool {
-    process.pid() == INIT_PROCESS_PID
-}
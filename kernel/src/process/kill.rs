@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::{
-    posix_thread::{thread_table, AsPosixThread},
+    posix_thread::AsPosixThread,
     process_table,
     signal::{
         constants::SIGCONT,
@@ -14,6 +14,8 @@ use crate::{prelude::*, thread::Tid};
 
 /// Sends a signal to a process, using the current process as the sender.
 ///
+/// The `pid` is the one in the PID namespace of the current process.
+///
 /// The credentials of the current process will be checked to determine
 /// if it is authorized to send the signal to this particular target process.
 ///
@@ -21,12 +23,13 @@ use crate::{prelude::*, thread::Tid};
 /// any signal.
 pub fn kill(pid: Pid, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
     // Fast path: If the signal is sent to self, we can skip most check.
-    if pid == ctx.process.pid() {
+    if pid == ctx.process.ns_pid() {
         let Some(signal) = signal else {
             return Ok(());
         };
 
         if !ctx.posix_thread.has_signal_blocked(signal.num()) {
+            let signal = signal.for_receiver_in(ctx.process.pid_ns());
             ctx.posix_thread.enqueue_signal(Box::new(signal));
             return Ok(());
         }
@@ -36,22 +39,48 @@ pub fn kill(pid: Pid, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
 
     // Slow path
 
-    let process = process_table::get_process(pid)
-        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target process does not exist"))?;
+    let process =
+        ctx.process.pid_ns().get_process(pid).ok_or_else(|| {
+            Error::with_message(Errno::ESRCH, "the target process does not exist")
+        })?;
 
     kill_process(&process, signal, ctx)
 }
 
+/// Sends a signal to a process referred to by a pidfd, using the current process
+/// as the sender.
+///
+/// Unlike [`kill`], the target process is already known, so it cannot be replaced by
+/// a new process that reuses its PID.
+///
+/// If `signal` is `None`, this method will only check permission without sending
+/// any signal.
+pub fn kill_pidfd(process: &Process, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
+    // The PID may have been reused by another process after the target process was reaped.
+    let is_reaped = process_table::get_process(process.pid())
+        .is_none_or(|table_process| !core::ptr::eq(table_process.as_ref(), process));
+    if is_reaped {
+        return_errno_with_message!(Errno::ESRCH, "the target process has been reaped");
+    }
+
+    kill_process(process, signal, ctx)
+}
+
 /// Sends a signal to all processes in a group, using the current process
 /// as the sender.
 ///
+/// The `pgid` is the one in the PID namespace of the current process.
+///
 /// The credentials of the current process will be checked to determine
 /// if it is authorized to send the signal to the target group.
 ///
 /// If `signal` is `None`, this method will only check permission without sending
 /// any signal.
 pub fn kill_group(pgid: Pgid, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
-    let process_group = process_table::get_process_group(&pgid)
+    let process_group = ctx
+        .process
+        .pid_ns()
+        .get_process_group(pgid)
         .ok_or_else(|| Error::with_message(Errno::ESRCH, "target group does not exist"))?;
 
     let inner = process_group.inner.lock();
@@ -65,10 +94,14 @@ pub fn kill_group(pgid: Pgid, signal: Option<UserSignal>, ctx: &Context) -> Resu
 /// Sends a signal to a target thread, using the current process
 /// as the sender.
 ///
+/// The `tid` and `tgid` are the ones in the PID namespace of the current process.
+///
 /// If `signal` is `None`, this method will only check permission without sending
 /// any signal.
 pub fn tgkill(tid: Tid, tgid: Pid, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
-    let thread = thread_table::get_thread(tid)
+    let pid_ns = ctx.process.pid_ns();
+    let thread = pid_ns
+        .get_thread(tid)
         .ok_or_else(|| Error::with_message(Errno::ESRCH, "target thread does not exist"))?;
 
     if thread.is_exited() {
@@ -79,7 +112,7 @@ pub fn tgkill(tid: Tid, tgid: Pid, signal: Option<UserSignal>, ctx: &Context) ->
 
     // Check tgid
     let pid = posix_thread.process().pid();
-    if pid != tgid {
+    if pid_ns.local_id(pid) != Some(tgid) {
         return_errno_with_message!(
             Errno::EINVAL,
             "the combination of tgid and pid is not valid"
@@ -92,6 +125,7 @@ pub fn tgkill(tid: Tid, tgid: Pid, signal: Option<UserSignal>, ctx: &Context) ->
     posix_thread.check_signal_perm(signum.as_ref(), &sender)?;
 
     if let Some(signal) = signal {
+        let signal = signal.for_receiver_in(posix_thread.process().pid_ns());
         posix_thread.enqueue_signal(Box::new(signal));
     }
 
@@ -101,12 +135,16 @@ pub fn tgkill(tid: Tid, tgid: Pid, signal: Option<UserSignal>, ctx: &Context) ->
 /// Sends a signal to all processes except current process and init process, using
 /// the current process as the sender.
 ///
+/// Only the processes in the PID namespace of the current process will receive the signal.
+///
 /// The credentials of the current process will be checked to determine
 /// if it is authorized to send the signal to the target group.
 pub fn kill_all(signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
-    let current = current!();
-    for process in process_table::process_table_mut().iter() {
-        if Arc::ptr_eq(&current, process) || process.is_init_process() {
+    let current = ctx.process;
+    let pid_ns = current.pid_ns();
+    let process_table = process_table::process_table_mut();
+    for process in pid_ns.visible_processes(&process_table) {
+        if core::ptr::eq(current, process.as_ref()) || pid_ns.is_init_process(process.pid()) {
             continue;
         }
 
@@ -117,6 +155,7 @@ pub fn kill_all(signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
 }
 
 fn kill_process(process: &Process, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
+    let signal = signal.map(|signal| signal.for_receiver_in(process.pid_ns()));
     let tasks = process.tasks().lock();
 
     let signum = signal.map(|signal| signal.num());
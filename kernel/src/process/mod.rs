@@ -1,9 +1,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod clone;
+mod coredump;
 pub mod credentials;
 mod exit;
 mod kill;
+pub mod namespace;
+mod pid_file;
 pub mod posix_thread;
 #[allow(clippy::module_inception)]
 mod process;
@@ -20,8 +23,10 @@ mod term_status;
 mod wait;
 
 pub use clone::{clone_child, CloneArgs, CloneFlags};
+pub use coredump::{core_pattern, set_core_pattern};
 pub use credentials::{Credentials, Gid, Uid};
-pub use kill::{kill, kill_all, kill_group, tgkill};
+pub use kill::{kill, kill_all, kill_group, kill_pidfd, tgkill};
+pub use pid_file::PidFile;
 pub use process::{
     ExitCode, JobControl, Pgid, Pid, Process, ProcessBuilder, ProcessGroup, Session, Sid, Terminal,
 };
@@ -30,7 +35,7 @@ pub use process_vm::{MAX_ARGV_NUMBER, MAX_ARG_LEN, MAX_ENVP_NUMBER, MAX_ENV_LEN}
 pub use program_loader::{check_executable_file, load_program_to_vm};
 pub use rlimit::ResourceType;
 pub use term_status::TermStatus;
-pub use wait::{wait_child_exit, WaitOptions};
+pub use wait::{wait_child_exit, WaitOptions, WaitStatus};
 
 pub(super) fn init() {
     process::init();
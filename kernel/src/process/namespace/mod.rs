@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Namespaces.
+//!
+//! A namespace wraps a global system resource in an abstraction that makes it
+//! appear to the processes within the namespace that they have their own
+//! isolated instance of the global resource.
+//!
+//! The mount namespace of a thread is kept in its [`FsResolver`], so that the
+//! root directory and the current working directory always belong to the
+//! mount namespace. The PID namespace of a process is kept in the [`Process`].
+//! The other namespaces of a thread are kept in its [`NsProxy`].
+//!
+//! [`FsResolver`]: crate::fs::fs_resolver::FsResolver
+
+mod nsproxy;
+mod pid;
+mod uts;
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+pub use self::{
+    nsproxy::NsProxy,
+    pid::{init_pid_ns, PidNamespace, INIT_PID},
+    uts::{init_uts_ns, UtsName, UtsNamespace, UTS_FIELD_LEN},
+};
+use super::{credentials::capabilities::CapSet, posix_thread::AsPosixThread, CloneFlags, Process};
+use crate::{fs::path::MountNamespace, ipc::IpcNamespace, prelude::*};
+
+/// Allocates a unique ID for a namespace.
+///
+/// The ID is shown as the inode number of the files in `/proc/[pid]/ns`.
+pub(crate) fn alloc_ns_id() -> u64 {
+    // Linux allocates the inode numbers of namespaces starting from
+    // `PROC_DYNAMIC_FIRST`.
+    static NEXT_NS_ID: AtomicU64 = AtomicU64::new(0xF000_0000);
+
+    NEXT_NS_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A namespace that can be referred to by the files in `/proc/[pid]/ns`.
+#[derive(Debug, Clone)]
+pub enum Namespace {
+    Mnt(Arc<MountNamespace>),
+    Uts(Arc<UtsNamespace>),
+    Ipc(Arc<IpcNamespace>),
+    Pid(Arc<PidNamespace>),
+}
+
+impl Namespace {
+    /// Returns the mount namespace of the process.
+    pub fn mnt_of(process: &Process) -> Self {
+        let main_thread = process.main_thread();
+        let posix_thread = main_thread.as_posix_thread().unwrap();
+        let mnt_ns = posix_thread.fs().resolver().read().mnt_ns().clone();
+        Self::Mnt(mnt_ns)
+    }
+
+    /// Returns the UTS namespace of the process.
+    pub fn uts_of(process: &Process) -> Self {
+        Self::Uts(ns_proxy_of(process).uts_ns().clone())
+    }
+
+    /// Returns the IPC namespace of the process.
+    pub fn ipc_of(process: &Process) -> Self {
+        Self::Ipc(ns_proxy_of(process).ipc_ns().clone())
+    }
+
+    /// Returns the PID namespace of the process.
+    pub fn pid_of(process: &Process) -> Self {
+        Self::Pid(process.pid_ns().clone())
+    }
+
+    /// Returns the name of the namespace type, which is also the name of the
+    /// file in `/proc/[pid]/ns`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Mnt(_) => "mnt",
+            Self::Uts(_) => "uts",
+            Self::Ipc(_) => "ipc",
+            Self::Pid(_) => "pid",
+        }
+    }
+
+    /// Returns the clone flag that creates the namespace of the same type.
+    pub fn clone_flag(&self) -> CloneFlags {
+        match self {
+            Self::Mnt(_) => CloneFlags::CLONE_NEWNS,
+            Self::Uts(_) => CloneFlags::CLONE_NEWUTS,
+            Self::Ipc(_) => CloneFlags::CLONE_NEWIPC,
+            Self::Pid(_) => CloneFlags::CLONE_NEWPID,
+        }
+    }
+
+    /// Returns the unique ID of the namespace.
+    pub fn id(&self) -> u64 {
+        match self {
+            Self::Mnt(mnt_ns) => mnt_ns.id(),
+            Self::Uts(uts_ns) => uts_ns.id(),
+            Self::Ipc(ipc_ns) => ipc_ns.id(),
+            Self::Pid(pid_ns) => pid_ns.id(),
+        }
+    }
+}
+
+/// Moves the current thread to new namespaces as specified by `flags`.
+///
+/// This implements the namespace part of `unshare`. The caller should have
+/// checked that `flags` contains only valid flags.
+pub fn unshare_namespaces(flags: CloneFlags, ctx: &Context) -> Result<()> {
+    if flags.contains(CloneFlags::CLONE_NEWNS) {
+        check_sys_admin(ctx)?;
+    }
+    let new_ns_proxy = new_ns_proxy(flags, ctx)?;
+
+    if flags.contains(CloneFlags::CLONE_NEWNS) {
+        // Like Linux, unsharing the mount namespace implies unsharing the FS
+        // information, which keeps the mount namespace.
+        ctx.posix_thread.unshare_fs();
+        ctx.posix_thread.fs().resolver().write().unshare_mnt_ns();
+    }
+    *ctx.posix_thread.ns_proxy().lock() = new_ns_proxy;
+
+    Ok(())
+}
+
+/// Moves the current thread to the namespace.
+///
+/// This implements `setns` on the files in `/proc/[pid]/ns`.
+pub fn enter_namespace(ns: &Namespace, ctx: &Context) -> Result<()> {
+    match ns {
+        Namespace::Mnt(mnt_ns) => {
+            check_sys_admin(ctx)?;
+            ctx.posix_thread.unshare_fs();
+            ctx.posix_thread
+                .fs()
+                .resolver()
+                .write()
+                .set_mnt_ns(mnt_ns.clone());
+        }
+        Namespace::Uts(uts_ns) => {
+            check_sys_admin(ctx)?;
+            let mut ns_proxy = ctx.posix_thread.ns_proxy().lock();
+            *ns_proxy = ns_proxy.with_uts_ns(uts_ns.clone());
+        }
+        Namespace::Ipc(ipc_ns) => {
+            check_sys_admin(ctx)?;
+            let mut ns_proxy = ctx.posix_thread.ns_proxy().lock();
+            *ns_proxy = ns_proxy.with_ipc_ns(ipc_ns.clone());
+        }
+        Namespace::Pid(pid_ns) => {
+            check_sys_admin(ctx)?;
+            // The processes can only move their children to the descendant
+            // namespaces, which can see them.
+            if !ctx.process.pid_ns().is_ancestor_of(pid_ns) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the PID namespace is not a descendant of the current one"
+                );
+            }
+            let mut ns_proxy = ctx.posix_thread.ns_proxy().lock();
+            *ns_proxy = ns_proxy.with_pid_ns_for_children(pid_ns.clone());
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether the current thread can create the namespaces specified by
+/// `flags` when cloning a child.
+pub(super) fn check_clone_namespaces(flags: CloneFlags, ctx: &Context) -> Result<()> {
+    if flags.contains(CloneFlags::CLONE_NEWNS) {
+        if flags.contains(CloneFlags::CLONE_FS) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "`CLONE_NEWNS` cannot be used with `CLONE_FS`"
+            );
+        }
+        check_sys_admin(ctx)?;
+    }
+
+    if flags.contains(CloneFlags::CLONE_NEWIPC) && flags.contains(CloneFlags::CLONE_SYSVSEM) {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "`CLONE_NEWIPC` cannot be used with `CLONE_SYSVSEM`"
+        );
+    }
+
+    if flags.contains(CloneFlags::CLONE_THREAD) {
+        if flags.contains(CloneFlags::CLONE_NEWPID) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "`CLONE_NEWPID` cannot be used with `CLONE_THREAD`"
+            );
+        }
+        // All the threads in a process must be in the same PID namespace.
+        let ns_proxy = ctx.posix_thread.ns_proxy().lock();
+        if !Arc::ptr_eq(ns_proxy.pid_ns_for_children(), ctx.process.pid_ns()) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "cannot create threads after the PID namespace for children is changed"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the namespaces of a child or the current thread as specified by
+/// `flags`, except for the mount namespace.
+///
+/// The PID namespace in the returned [`NsProxy`] is the namespace for the
+/// children, which is also the namespace of the child created by `clone`.
+pub(super) fn new_ns_proxy(flags: CloneFlags, ctx: &Context) -> Result<Arc<NsProxy>> {
+    let ns_flags = CloneFlags::CLONE_NEWUTS | CloneFlags::CLONE_NEWIPC | CloneFlags::CLONE_NEWPID;
+
+    let mut ns_proxy = ctx.posix_thread.ns_proxy().lock().clone();
+    if !flags.intersects(ns_flags) {
+        return Ok(ns_proxy);
+    }
+    check_sys_admin(ctx)?;
+
+    if flags.contains(CloneFlags::CLONE_NEWUTS) {
+        ns_proxy = ns_proxy.with_uts_ns(ns_proxy.uts_ns().copy());
+    }
+    if flags.contains(CloneFlags::CLONE_NEWIPC) {
+        ns_proxy = ns_proxy.with_ipc_ns(IpcNamespace::new());
+    }
+    if flags.contains(CloneFlags::CLONE_NEWPID) {
+        let pid_ns = ctx.process.pid_ns();
+        if !Arc::ptr_eq(ns_proxy.pid_ns_for_children(), pid_ns) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the PID namespace for children has been changed"
+            );
+        }
+        ns_proxy = ns_proxy.with_pid_ns_for_children(pid_ns.new_child()?);
+    }
+
+    Ok(ns_proxy)
+}
+
+fn ns_proxy_of(process: &Process) -> Arc<NsProxy> {
+    let main_thread = process.main_thread();
+    let posix_thread = main_thread.as_posix_thread().unwrap();
+    posix_thread.ns_proxy().lock().clone()
+}
+
+fn check_sys_admin(ctx: &Context) -> Result<()> {
+    if !ctx
+        .posix_thread
+        .credentials()
+        .effective_capset()
+        .contains(CapSet::SYS_ADMIN)
+    {
+        return_errno_with_message!(Errno::EPERM, "`CAP_SYS_ADMIN` is required");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::{
+    pid::{init_pid_ns, PidNamespace},
+    uts::{init_uts_ns, UtsNamespace},
+};
+use crate::{
+    ipc::{init_ipc_ns, IpcNamespace},
+    prelude::*,
+};
+
+/// The namespaces of a thread.
+///
+/// The mount namespace is not included because it is kept in the [`FsResolver`].
+/// The PID namespace that a process belongs to is kept in the [`Process`],
+/// since it never changes.
+///
+/// The namespaces are never changed in place. Instead, a new `NsProxy` is
+/// created when the thread moves to other namespaces.
+///
+/// [`FsResolver`]: crate::fs::fs_resolver::FsResolver
+/// [`Process`]: crate::process::Process
+#[derive(Debug, Clone)]
+pub struct NsProxy {
+    uts_ns: Arc<UtsNamespace>,
+    ipc_ns: Arc<IpcNamespace>,
+    /// The PID namespace of the children that will be created by the thread.
+    pid_ns_for_children: Arc<PidNamespace>,
+}
+
+static INIT_NS_PROXY: Once<Arc<NsProxy>> = Once::new();
+
+impl NsProxy {
+    /// Returns the namespaces of the init process.
+    pub fn get_init_singleton() -> &'static Arc<NsProxy> {
+        INIT_NS_PROXY.call_once(|| {
+            Arc::new(Self {
+                uts_ns: init_uts_ns().clone(),
+                ipc_ns: init_ipc_ns().clone(),
+                pid_ns_for_children: init_pid_ns().clone(),
+            })
+        })
+    }
+
+    pub fn uts_ns(&self) -> &Arc<UtsNamespace> {
+        &self.uts_ns
+    }
+
+    pub fn ipc_ns(&self) -> &Arc<IpcNamespace> {
+        &self.ipc_ns
+    }
+
+    pub fn pid_ns_for_children(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns_for_children
+    }
+
+    pub(super) fn with_uts_ns(&self, uts_ns: Arc<UtsNamespace>) -> Arc<Self> {
+        Arc::new(Self {
+            uts_ns,
+            ..self.clone()
+        })
+    }
+
+    pub(super) fn with_ipc_ns(&self, ipc_ns: Arc<IpcNamespace>) -> Arc<Self> {
+        Arc::new(Self {
+            ipc_ns,
+            ..self.clone()
+        })
+    }
+
+    pub(super) fn with_pid_ns_for_children(&self, pid_ns: Arc<PidNamespace>) -> Arc<Self> {
+        Arc::new(Self {
+            pid_ns_for_children: pid_ns,
+            ..self.clone()
+        })
+    }
+}
@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::alloc_ns_id;
+use crate::{
+    prelude::*,
+    process::{
+        posix_thread::thread_table,
+        process_table::{self, ProcessTable},
+        Pgid, Pid, Process, ProcessGroup,
+    },
+    thread::{Thread, Tid},
+};
+
+/// The maximum nesting level of PID namespaces (i.e., `MAX_PID_NS_LEVEL` in Linux).
+const MAX_PID_NS_LEVEL: u32 = 32;
+
+/// The PID of the init process in a PID namespace.
+pub const INIT_PID: Pid = 1;
+
+/// A PID namespace, which isolates the PID number space.
+///
+/// The PID namespaces form a tree. A process is visible in the PID namespace
+/// that it belongs to and in all the ancestor namespaces, and it has a
+/// different PID in each of them. The first process created in a PID namespace
+/// gets the PID 1 and becomes the init process of the namespace.
+///
+/// The global PIDs (i.e., the TIDs allocated by [`allocate_posix_tid`]) are
+/// used as the PIDs in the root PID namespace. Other PID namespaces map the
+/// global PIDs to their own PIDs.
+///
+/// [`allocate_posix_tid`]: crate::process::posix_thread::allocate_posix_tid
+pub struct PidNamespace {
+    parent: Option<Arc<PidNamespace>>,
+    level: u32,
+    /// The unique ID, which is shown as the inode number of `/proc/[pid]/ns/pid`.
+    id: u64,
+    inner: Mutex<PidNsInner>,
+}
+
+struct PidNsInner {
+    next_pid: Pid,
+    global_to_local: BTreeMap<Pid, Pid>,
+    local_to_global: BTreeMap<Pid, Pid>,
+    /// Whether the init process has exited.
+    is_dead: bool,
+}
+
+static INIT_PID_NS: Once<Arc<PidNamespace>> = Once::new();
+
+/// Returns the root PID namespace.
+pub fn init_pid_ns() -> &'static Arc<PidNamespace> {
+    INIT_PID_NS.call_once(|| PidNamespace::new(None, 0))
+}
+
+impl PidNamespace {
+    fn new(parent: Option<Arc<PidNamespace>>, level: u32) -> Arc<Self> {
+        Arc::new(Self {
+            parent,
+            level,
+            id: alloc_ns_id(),
+            inner: Mutex::new(PidNsInner {
+                next_pid: INIT_PID,
+                global_to_local: BTreeMap::new(),
+                local_to_global: BTreeMap::new(),
+                is_dead: false,
+            }),
+        })
+    }
+
+    /// Creates a child PID namespace.
+    pub fn new_child(self: &Arc<Self>) -> Result<Arc<Self>> {
+        if self.level >= MAX_PID_NS_LEVEL {
+            return_errno_with_message!(Errno::ENOSPC, "the PID namespaces are nested too deep");
+        }
+
+        Ok(Self::new(Some(self.clone()), self.level + 1))
+    }
+
+    /// Returns the parent namespace, or `None` if `self` is the root namespace.
+    pub fn parent(&self) -> Option<&Arc<PidNamespace>> {
+        self.parent.as_ref()
+    }
+
+    /// Returns the nesting level, which is zero for the root namespace.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Returns the unique ID.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns whether `self` is `other` or one of the ancestors of `other`.
+    pub fn is_ancestor_of(&self, other: &PidNamespace) -> bool {
+        let mut ns = other;
+        loop {
+            if core::ptr::eq(ns, self) {
+                return true;
+            }
+            match ns.parent.as_ref() {
+                Some(parent) => ns = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Allocates the PIDs in `self` and all the ancestor namespaces for the
+    /// thread with `global_pid`.
+    ///
+    /// This method fails with `ENOMEM` if the init process of any namespace
+    /// has exited, like Linux.
+    pub fn alloc_pids(&self, global_pid: Pid) -> Result<()> {
+        let mut ns = self;
+        while let Some(parent) = ns.parent.as_ref() {
+            if let Err(err) = ns.inner.lock().alloc(global_pid) {
+                self.release_pids(global_pid);
+                return Err(err);
+            }
+            ns = parent;
+        }
+
+        Ok(())
+    }
+
+    /// Releases the PIDs allocated by [`Self::alloc_pids`].
+    pub fn release_pids(&self, global_pid: Pid) {
+        let mut ns = self;
+        while let Some(parent) = ns.parent.as_ref() {
+            ns.inner.lock().release(global_pid);
+            ns = parent;
+        }
+    }
+
+    /// Translates the global PID to the PID in `self`.
+    ///
+    /// Returns `None` if the process or the thread is not visible in `self`.
+    pub fn local_id(&self, global_pid: Pid) -> Option<Pid> {
+        if self.parent.is_none() {
+            return Some(global_pid);
+        }
+
+        self.inner.lock().global_to_local.get(&global_pid).copied()
+    }
+
+    /// Translates the PID in `self` to the global PID.
+    pub fn global_id(&self, local_pid: Pid) -> Option<Pid> {
+        if self.parent.is_none() {
+            return Some(local_pid);
+        }
+
+        self.inner.lock().local_to_global.get(&local_pid).copied()
+    }
+
+    /// Translates the global PID to the PID in `self`, or returns zero if the
+    /// process or the thread is not visible in `self`.
+    ///
+    /// This is how Linux reports, e.g., the parent PID of the init process of
+    /// a PID namespace.
+    pub fn local_id_or_zero(&self, global_pid: Pid) -> Pid {
+        self.local_id(global_pid).unwrap_or(0)
+    }
+
+    /// Returns whether the process or the thread with `global_pid` is visible
+    /// in `self`.
+    pub fn contains(&self, global_pid: Pid) -> bool {
+        self.local_id(global_pid).is_some()
+    }
+
+    /// Gets the process with `pid` in `self`.
+    pub fn get_process(&self, pid: Pid) -> Option<Arc<Process>> {
+        process_table::get_process(self.global_id(pid)?)
+    }
+
+    /// Gets the thread with `tid` in `self`.
+    pub fn get_thread(&self, tid: Tid) -> Option<Arc<Thread>> {
+        thread_table::get_thread(self.global_id(tid)?)
+    }
+
+    /// Gets the process group with `pgid` in `self`.
+    pub fn get_process_group(&self, pgid: Pgid) -> Option<Arc<ProcessGroup>> {
+        process_table::get_process_group(&self.global_id(pgid)?)
+    }
+
+    /// Returns whether the process group with `pgid` in `self` exists.
+    pub fn contains_process_group(&self, pgid: Pgid) -> bool {
+        self.global_id(pgid)
+            .is_some_and(|pgid| process_table::contain_process_group(&pgid))
+    }
+
+    /// Gets the init process.
+    pub fn init_process(&self) -> Option<Arc<Process>> {
+        self.get_process(INIT_PID)
+    }
+
+    /// Returns whether the process with `global_pid` is the init process.
+    pub fn is_init_process(&self, global_pid: Pid) -> bool {
+        self.local_id(global_pid) == Some(INIT_PID)
+    }
+
+    /// Returns an iterator over the processes in the process table that are
+    /// visible in `self`.
+    pub fn visible_processes<'a>(
+        &'a self,
+        process_table: &'a ProcessTable,
+    ) -> impl Iterator<Item = &'a Arc<Process>> + 'a {
+        process_table
+            .iter()
+            .filter(|process| self.contains(process.pid()))
+    }
+
+    /// Marks that the init process has exited.
+    ///
+    /// No new process can be created in the namespace thereafter.
+    pub(in crate::process) fn set_dead(&self) {
+        self.inner.lock().is_dead = true;
+    }
+}
+
+impl PidNsInner {
+    fn alloc(&mut self, global_pid: Pid) -> Result<()> {
+        if self.is_dead {
+            return_errno_with_message!(
+                Errno::ENOMEM,
+                "the init process of the PID namespace has exited"
+            );
+        }
+
+        let mut local_pid = self.next_pid;
+        while self.local_to_global.contains_key(&local_pid) {
+            local_pid += 1;
+        }
+        self.next_pid = local_pid + 1;
+
+        self.global_to_local.insert(global_pid, local_pid);
+        self.local_to_global.insert(local_pid, global_pid);
+        Ok(())
+    }
+
+    fn release(&mut self, global_pid: Pid) {
+        if let Some(local_pid) = self.global_to_local.remove(&global_pid) {
+            self.local_to_global.remove(&local_pid);
+        }
+    }
+}
+
+impl Debug for PidNamespace {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("PidNamespace")
+            .field("level", &self.level)
+            .field("id", &self.id)
+            .finish()
+    }
+}
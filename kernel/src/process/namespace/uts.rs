@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::alloc_ns_id;
+use crate::prelude::*;
+
+/// The length of each field in [`UtsName`], including the trailing null byte.
+pub const UTS_FIELD_LEN: usize = 65;
+
+/// The system identification returned by `uname`.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+pub struct UtsName {
+    sysname: [u8; UTS_FIELD_LEN],
+    nodename: [u8; UTS_FIELD_LEN],
+    release: [u8; UTS_FIELD_LEN],
+    version: [u8; UTS_FIELD_LEN],
+    machine: [u8; UTS_FIELD_LEN],
+    domainname: [u8; UTS_FIELD_LEN],
+}
+
+impl UtsName {
+    const fn new() -> Self {
+        UtsName {
+            sysname: [0; UTS_FIELD_LEN],
+            nodename: [0; UTS_FIELD_LEN],
+            release: [0; UTS_FIELD_LEN],
+            version: [0; UTS_FIELD_LEN],
+            machine: [0; UTS_FIELD_LEN],
+            domainname: [0; UTS_FIELD_LEN],
+        }
+    }
+}
+
+/// A UTS namespace, which isolates the host name and the NIS domain name.
+pub struct UtsNamespace {
+    uts_name: RwLock<UtsName>,
+    /// The unique ID, which is shown as the inode number of `/proc/[pid]/ns/uts`.
+    id: u64,
+}
+
+static INIT_UTS_NS: Once<Arc<UtsNamespace>> = Once::new();
+
+/// Returns the initial UTS namespace.
+pub fn init_uts_ns() -> &'static Arc<UtsNamespace> {
+    INIT_UTS_NS.call_once(|| {
+        // We don't use the real name and version of our os here. Instead, we pick up fake values witch is the same as the ones of linux.
+        // The values are used to fool glibc since glibc will check the version and os name.
+        let mut uts_name = UtsName::new();
+        copy_field(b"Linux", &mut uts_name.sysname);
+        copy_field(b"WHITLEY", &mut uts_name.nodename);
+        copy_field(b"5.13.0", &mut uts_name.release);
+        copy_field(b"5.13.0", &mut uts_name.version);
+        copy_field(b"x86_64", &mut uts_name.machine);
+        copy_field(b"", &mut uts_name.domainname);
+
+        UtsNamespace::new(uts_name)
+    })
+}
+
+impl UtsNamespace {
+    fn new(uts_name: UtsName) -> Arc<Self> {
+        Arc::new(Self {
+            uts_name: RwLock::new(uts_name),
+            id: alloc_ns_id(),
+        })
+    }
+
+    /// Creates a new UTS namespace with a copy of the names in `self`.
+    pub fn copy(&self) -> Arc<Self> {
+        Self::new(*self.uts_name.read())
+    }
+
+    /// Returns the system identification.
+    pub fn uts_name(&self) -> UtsName {
+        *self.uts_name.read()
+    }
+
+    /// Sets the host name.
+    pub fn set_hostname(&self, hostname: &[u8]) -> Result<()> {
+        check_field_len(hostname)?;
+        copy_field(hostname, &mut self.uts_name.write().nodename);
+        Ok(())
+    }
+
+    /// Sets the NIS domain name.
+    pub fn set_domainname(&self, domainname: &[u8]) -> Result<()> {
+        check_field_len(domainname)?;
+        copy_field(domainname, &mut self.uts_name.write().domainname);
+        Ok(())
+    }
+
+    /// Returns the unique ID.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Debug for UtsNamespace {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("UtsNamespace")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+fn check_field_len(name: &[u8]) -> Result<()> {
+    if name.len() >= UTS_FIELD_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the name is too long");
+    }
+
+    Ok(())
+}
+
+/// Copies `src` to `dst` and pads the rest of `dst` with null bytes.
+fn copy_field(src: &[u8], dst: &mut [u8; UTS_FIELD_LEN]) {
+    let len = src.len().min(UTS_FIELD_LEN - 1);
+    dst[..len].copy_from_slice(&src[..len]);
+    dst[len..].fill(0);
+}
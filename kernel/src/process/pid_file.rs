@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::Process;
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        utils::{InodeMode, Metadata, StatusFlags},
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+};
+
+/// A file that refers to a process (i.e., a pidfd).
+///
+/// The file becomes readable when the process exits.
+pub struct PidFile {
+    process: Arc<Process>,
+    is_nonblocking: AtomicBool,
+}
+
+impl PidFile {
+    pub fn new(process: Arc<Process>, is_nonblocking: bool) -> Arc<Self> {
+        Arc::new(Self {
+            process,
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+        })
+    }
+
+    /// Returns the process that the file refers to.
+    pub fn process(&self) -> &Arc<Process> {
+        &self.process
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.process.status().is_zombie() {
+            IoEvents::IN
+        } else {
+            IoEvents::empty()
+        }
+    }
+}
+
+impl Pollable for PidFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.process
+            .pidfd_pollee()
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for PidFile {
+    fn read(&self, _writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "pidfd files do not support read");
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "pidfd files do not support write");
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `PidFile` to it.
+        Metadata::new_file(
+            0,
+            InodeMode::from_bits_truncate(0o600),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+}
@@ -9,7 +9,8 @@ use crate::{
     fs::{file_table::FileTable, thread_info::ThreadFsInfo},
     prelude::*,
     process::{
-        posix_thread::name::ThreadName,
+        namespace::NsProxy,
+        posix_thread::{name::ThreadName, Tracee},
         signal::{sig_mask::AtomicSigMask, sig_queues::SigQueues},
         Credentials, Process,
     },
@@ -32,6 +33,7 @@ pub struct PosixThreadBuilder {
     clear_child_tid: Vaddr,
     file_table: Option<Arc<SpinLock<FileTable>>>,
     fs: Option<Arc<ThreadFsInfo>>,
+    ns_proxy: Option<Arc<NsProxy>>,
     sig_mask: AtomicSigMask,
     sig_queues: SigQueues,
     priority: Priority,
@@ -49,6 +51,7 @@ impl PosixThreadBuilder {
             clear_child_tid: 0,
             file_table: None,
             fs: None,
+            ns_proxy: None,
             sig_mask: AtomicSigMask::new_empty(),
             sig_queues: SigQueues::new(),
             priority: Priority::default(),
@@ -85,6 +88,11 @@ impl PosixThreadBuilder {
         self
     }
 
+    pub fn ns_proxy(mut self, ns_proxy: Arc<NsProxy>) -> Self {
+        self.ns_proxy = Some(ns_proxy);
+        self
+    }
+
     pub fn sig_mask(mut self, sig_mask: AtomicSigMask) -> Self {
         self.sig_mask = sig_mask;
         self
@@ -96,6 +104,33 @@ impl PosixThreadBuilder {
     }
 
     pub fn build(self) -> Arc<Task> {
+        let tid = self.tid;
+
+        self.build_with(|user_space, thread, thread_local| {
+            thread_table::add_thread(tid, thread.clone());
+            task::create_new_user_task(user_space, thread, thread_local)
+        })
+    }
+
+    /// Builds a POSIX thread that runs `func` in the kernel mode.
+    ///
+    /// The thread shares the resources (e.g., the address space and the file table) with the
+    /// process, but it never returns to the user mode. It is not added to the thread table, so it
+    /// is invisible to the user space. Kernel workers that perform operations on behalf of the
+    /// process (e.g., io_uring workers) are built in this way.
+    pub fn build_kernel_worker<F>(self, func: F) -> Arc<Task>
+    where
+        F: FnOnce() + Send + Sync + 'static,
+    {
+        self.build_with(|user_space, thread, thread_local| {
+            task::create_new_kernel_task_with_user_space(func, user_space, thread, thread_local)
+        })
+    }
+
+    fn build_with<F>(self, create_task: F) -> Arc<Task>
+    where
+        F: FnOnce(Arc<UserSpace>, Arc<Thread>, ThreadLocal) -> Task,
+    {
         let Self {
             tid,
             user_space,
@@ -106,6 +141,7 @@ impl PosixThreadBuilder {
             clear_child_tid,
             file_table,
             fs,
+            ns_proxy,
             sig_mask,
             sig_queues,
             priority,
@@ -116,6 +152,8 @@ impl PosixThreadBuilder {
 
         let fs = fs.unwrap_or_else(|| Arc::new(ThreadFsInfo::default()));
 
+        let ns_proxy = ns_proxy.unwrap_or_else(|| NsProxy::get_init_singleton().clone());
+
         Arc::new_cyclic(|weak_task| {
             let posix_thread = {
                 let prof_clock = ProfClock::new();
@@ -127,11 +165,13 @@ impl PosixThreadBuilder {
                     tid,
                     name: Mutex::new(thread_name),
                     credentials,
-                    file_table,
-                    fs,
+                    file_table: RwLock::new(file_table),
+                    fs: RwLock::new(fs),
+                    ns_proxy: Mutex::new(ns_proxy),
                     sig_mask,
                     sig_queues,
                     signalled_waker: SpinLock::new(None),
+                    tracee: Tracee::new(),
                     prof_clock,
                     virtual_timer_manager,
                     prof_timer_manager,
@@ -148,8 +188,7 @@ impl PosixThreadBuilder {
 
             let thread_local = ThreadLocal::new(set_child_tid, clear_child_tid);
 
-            thread_table::add_thread(tid, thread.clone());
-            task::create_new_user_task(user_space, thread, thread_local)
+            create_task(user_space, thread, thread_local)
         })
     }
 }
@@ -44,7 +44,7 @@ fn exit_internal(term_status: TermStatus, is_exiting_group: bool) {
     let thread_local = current_task.as_thread_local().unwrap();
     let posix_process = posix_thread.process();
 
-    let is_last_thread = {
+    let (is_last_thread, exit_code) = {
         let mut tasks = posix_process.tasks().lock();
         let has_exited_group = tasks.has_exited_group();
 
@@ -58,6 +58,7 @@ fn exit_internal(term_status: TermStatus, is_exiting_group: bool) {
         if !has_exited_group {
             posix_process.status().set_exit_code(term_status.as_u32());
         }
+        let exit_code = posix_process.status().exit_code();
 
         // We should only change the thread status when running as the thread, so no race
         // conditions can occur in between.
@@ -66,17 +67,25 @@ fn exit_internal(term_status: TermStatus, is_exiting_group: bool) {
         }
         current_thread.exit();
 
-        tasks.remove_exited(&current_task)
+        // The thread that is dumping the core should not wait for the current thread to stop.
+        if let Some(core_state) = tasks.core_state() {
+            core_state.remove_exited(current_task.as_ref());
+        }
+
+        (tasks.remove_exited(&current_task), exit_code)
     };
 
     wake_clear_ctid(thread_local);
 
-    wake_robust_list(thread_local, posix_thread.tid());
+    wake_robust_list(thread_local, posix_thread.ns_tid());
+
+    posix_thread.ptrace_exit(exit_code);
 
-    // According to Linux behavior, the main thread shouldn't be removed from the table until the
-    // process is reaped by its parent.
+    // According to Linux behavior, the main thread shouldn't be removed from the table (and its
+    // IDs in the PID namespaces shouldn't be released) until the process is reaped by its parent.
     if posix_thread.tid() != posix_process.pid() {
         thread_table::remove_thread(posix_thread.tid());
+        posix_process.pid_ns().release_pids(posix_thread.tid());
     }
 
     if is_last_thread {
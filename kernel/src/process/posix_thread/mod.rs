@@ -9,6 +9,7 @@ use ostd::sync::Waker;
 
 use super::{
     kill::SignalSenderIds,
+    namespace::NsProxy,
     signal::{
         sig_action::SigAction,
         sig_mask::{AtomicSigMask, SigMask, SigSet},
@@ -23,7 +24,7 @@ use crate::{
     events::Observer,
     fs::{file_table::FileTable, thread_info::ThreadFsInfo},
     prelude::*,
-    process::signal::constants::SIGCONT,
+    process::signal::constants::{SIGCONT, SIGKILL},
     thread::{Thread, Tid},
     time::{clocks::ProfClock, Timer, TimerManager},
 };
@@ -33,6 +34,7 @@ mod exit;
 pub mod futex;
 mod name;
 mod posix_thread_ext;
+mod ptrace;
 mod robust_list;
 mod thread_local;
 pub mod thread_table;
@@ -41,6 +43,8 @@ pub use builder::PosixThreadBuilder;
 pub use exit::{do_exit, do_exit_group};
 pub use name::{ThreadName, MAX_THREAD_NAME_LEN};
 pub use posix_thread_ext::{create_posix_task_from_executable, AsPosixThread};
+pub(super) use ptrace::ptrace_detach_all;
+pub use ptrace::{ptrace_attach, ptrace_detach, PtraceOptions, ResumeMode, Tracee};
 pub use robust_list::RobustListHead;
 pub use thread_local::{AsThreadLocal, ThreadLocal};
 
@@ -57,9 +61,12 @@ pub struct PosixThread {
 
     // Files
     /// File table
-    file_table: Arc<SpinLock<FileTable>>,
+    file_table: RwLock<Arc<SpinLock<FileTable>>>,
     /// File system
-    fs: Arc<ThreadFsInfo>,
+    fs: RwLock<Arc<ThreadFsInfo>>,
+
+    /// The namespaces other than the mount and the PID namespaces
+    ns_proxy: Mutex<Arc<NsProxy>>,
 
     // Signal
     /// Blocked signals
@@ -70,6 +77,9 @@ pub struct PosixThread {
     /// when enqueuing a signal.
     signalled_waker: SpinLock<Option<Arc<Waker>>>,
 
+    /// The tracing state of the thread
+    tracee: Tracee,
+
     /// A profiling clock measures the user CPU time and kernel CPU time in the thread.
     prof_clock: Arc<ProfClock>,
 
@@ -94,16 +104,51 @@ impl PosixThread {
         self.tid
     }
 
+    /// Returns the thread id in the PID namespace that the thread belongs to.
+    ///
+    /// This is the thread id seen by the thread itself, whereas [`Self::tid`]
+    /// returns the thread id in the root PID namespace.
+    pub fn ns_tid(&self) -> Tid {
+        self.process().pid_ns().local_id_or_zero(self.tid)
+    }
+
     pub fn thread_name(&self) -> &Mutex<Option<ThreadName>> {
         &self.name
     }
 
-    pub fn file_table(&self) -> &Arc<SpinLock<FileTable>> {
-        &self.file_table
+    pub fn file_table(&self) -> Arc<SpinLock<FileTable>> {
+        self.file_table.read().clone()
+    }
+
+    /// Stops sharing the file table with other threads by copying it.
+    ///
+    /// This method should only be called by the thread itself.
+    pub fn unshare_file_table(&self) {
+        let file_table = self.file_table();
+        // One of the references is held by `file_table` itself.
+        if Arc::strong_count(&file_table) > 2 {
+            let new_file_table = file_table.lock().clone();
+            *self.file_table.write() = Arc::new(SpinLock::new(new_file_table));
+        }
+    }
+
+    pub fn fs(&self) -> Arc<ThreadFsInfo> {
+        self.fs.read().clone()
     }
 
-    pub fn fs(&self) -> &Arc<ThreadFsInfo> {
-        &self.fs
+    /// Stops sharing the FS information with other threads by copying it.
+    ///
+    /// This method should only be called by the thread itself.
+    pub fn unshare_fs(&self) {
+        let fs = self.fs();
+        // One of the references is held by `fs` itself.
+        if Arc::strong_count(&fs) > 2 {
+            *self.fs.write() = Arc::new(fs.as_ref().clone());
+        }
+    }
+
+    pub fn ns_proxy(&self) -> &Mutex<Arc<NsProxy>> {
+        &self.ns_proxy
     }
 
     /// Get the reference to the signal mask of the thread.
@@ -120,8 +165,12 @@ impl PosixThread {
     }
 
     /// Returns whether the thread has some pending signals
-    /// that are not blocked.
+    /// that are not blocked, or it has been interrupted by its tracer.
     pub fn has_pending(&self) -> bool {
+        if self.tracee.is_interrupt_pending() {
+            return true;
+        }
+
         let blocked = self.sig_mask().load(Ordering::Relaxed);
         self.sig_queues.has_pending(blocked)
     }
@@ -206,6 +255,11 @@ impl PosixThread {
     pub fn enqueue_signal(&self, signal: Box<dyn Signal>) {
         let signal_number = signal.num();
         self.sig_queues.enqueue(signal);
+        if signal_number == SIGKILL || self.tracee.is_listening() {
+            // A thread in a ptrace-stop can only be woken up by `SIGKILL`,
+            // unless it is listening for signals after `PTRACE_LISTEN`.
+            self.tracee.wake_up();
+        }
         if self.process().sig_dispositions().lock().get(signal_number) != SigAction::Ign
             && let Some(waker) = &*self.signalled_waker.lock()
         {
@@ -0,0 +1,802 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Process tracing.
+//!
+//! A thread being traced (a tracee) stops at certain points and waits for its
+//! tracer to inspect it and resume it. The stops include:
+//!  - signal-delivery-stops, which happen before a signal is delivered;
+//!  - syscall-stops, which happen when a syscall is entered or exited,
+//!    if the tracee is resumed with `PTRACE_SYSCALL`;
+//!  - event-stops, which happen on events like `execve` or `fork`,
+//!    if the tracer has asked for them with `PTRACE_SETOPTIONS`;
+//!  - group-stops, which happen when a stop signal is delivered;
+//!  - interrupt-stops, which happen when a seized tracee is interrupted
+//!    by `PTRACE_INTERRUPT`.
+//!
+//! The tracer learns about the stops and the exits of the tracees via `wait4`
+//! or `waitid`, in the same way as it learns about the state changes of its
+//! children.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use ostd::{cpu::UserContext, sync::WaitQueue, task::Task};
+
+use super::{AsPosixThread, PosixThread};
+use crate::{
+    cpu::LinuxAbi,
+    prelude::*,
+    process::{
+        clone::CloneFlags,
+        signal::{
+            c_types::siginfo_t,
+            constants::{SIGCHLD, SIGKILL, SIGSTOP, SIGTRAP, SI_KERNEL},
+            sig_num::SigNum,
+            signals::{kernel::KernelSignal, raw::RawSignal, Signal},
+        },
+        ExitCode, Process,
+    },
+    thread::{Thread, Tid},
+};
+
+bitflags! {
+    /// The options set by `PTRACE_SETOPTIONS` or `PTRACE_SEIZE`.
+    pub struct PtraceOptions: u32 {
+        const PTRACE_O_TRACESYSGOOD    = 1 << 0;
+        const PTRACE_O_TRACEFORK       = 1 << 1;
+        const PTRACE_O_TRACEVFORK      = 1 << 2;
+        const PTRACE_O_TRACECLONE      = 1 << 3;
+        const PTRACE_O_TRACEEXEC       = 1 << 4;
+        const PTRACE_O_TRACEVFORKDONE  = 1 << 5;
+        const PTRACE_O_TRACEEXIT       = 1 << 6;
+        const PTRACE_O_TRACESECCOMP    = 1 << 7;
+        const PTRACE_O_EXITKILL        = 1 << 20;
+        const PTRACE_O_SUSPEND_SECCOMP = 1 << 21;
+    }
+}
+
+/// The events that can be reported with event-stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PtraceEvent {
+    Fork = 1,
+    Vfork = 2,
+    Clone = 3,
+    Exec = 4,
+    VforkDone = 5,
+    Exit = 6,
+}
+
+impl PtraceEvent {
+    /// Returns the option that enables reporting the event.
+    fn option(&self) -> PtraceOptions {
+        match self {
+            Self::Fork => PtraceOptions::PTRACE_O_TRACEFORK,
+            Self::Vfork => PtraceOptions::PTRACE_O_TRACEVFORK,
+            Self::Clone => PtraceOptions::PTRACE_O_TRACECLONE,
+            Self::Exec => PtraceOptions::PTRACE_O_TRACEEXEC,
+            Self::VforkDone => PtraceOptions::PTRACE_O_TRACEVFORKDONE,
+            Self::Exit => PtraceOptions::PTRACE_O_TRACEEXIT,
+        }
+    }
+}
+
+/// The event reported for the group-stops and the interrupt-stops of seized tracees.
+const PTRACE_EVENT_STOP: u32 = 128;
+
+/// How a stopped tracee is resumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeMode {
+    /// Resumes the tracee (`PTRACE_CONT`).
+    Cont,
+    /// Resumes the tracee and stops it at the next syscall entry or exit (`PTRACE_SYSCALL`).
+    Syscall,
+    /// Resumes the tracee and stops it after a single instruction (`PTRACE_SINGLESTEP`).
+    SingleStep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StopKind {
+    Signal(SigNum),
+    Syscall,
+    Event(PtraceEvent),
+    Group(SigNum),
+    Interrupt,
+}
+
+struct PtraceStop {
+    kind: StopKind,
+    siginfo: siginfo_t,
+    /// The user context of the tracee, which can be modified by the tracer.
+    user_ctx: UserContext,
+    /// Whether the stop has been reported to the tracer via `wait4` or `waitid`.
+    is_reported: bool,
+    /// The signal to deliver, which is set by the tracer when resuming the tracee.
+    ///
+    /// If this field is `None`, the tracee has not been resumed.
+    resumed: Option<Option<SigNum>>,
+    /// Whether the tracee waits for signals without being in the ptrace-stop (`PTRACE_LISTEN`).
+    is_listening: bool,
+}
+
+impl PtraceStop {
+    /// Returns whether the tracee is still in the ptrace-stop.
+    fn is_stopped(&self) -> bool {
+        self.resumed.is_none() && !self.is_listening
+    }
+}
+
+/// The tracing state of a POSIX thread.
+pub struct Tracee {
+    inner: SpinLock<TraceeInner>,
+    /// The wait queue on which the tracee waits to be resumed.
+    wait_queue: WaitQueue,
+    /// Whether the tracee should enter an interrupt-stop (`PTRACE_INTERRUPT`).
+    is_interrupt_pending: AtomicBool,
+}
+
+struct TraceeInner {
+    tracer: Weak<Process>,
+    options: PtraceOptions,
+    is_seized: bool,
+    resume_mode: ResumeMode,
+    stop: Option<PtraceStop>,
+    /// The event that will be reported before the current syscall returns.
+    pending_event: Option<PtraceEvent>,
+    /// The message retrieved by `PTRACE_GETEVENTMSG`.
+    event_msg: u64,
+    /// The number of the syscall in progress (i.e., `orig_rax` on x86-64).
+    orig_syscall_num: Option<usize>,
+    /// The exit status of the exited tracee, which has not been reported to the tracer.
+    exit_status: Option<ExitCode>,
+}
+
+impl TraceeInner {
+    /// Clears the tracing state and returns the tracer.
+    fn reset(&mut self) -> Option<Arc<Process>> {
+        let tracer = core::mem::take(&mut self.tracer).upgrade();
+        self.options = PtraceOptions::empty();
+        self.is_seized = false;
+        self.resume_mode = ResumeMode::Cont;
+        self.pending_event = None;
+        self.event_msg = 0;
+        tracer
+    }
+}
+
+impl Tracee {
+    pub(super) fn new() -> Self {
+        Self {
+            inner: SpinLock::new(TraceeInner {
+                tracer: Weak::new(),
+                options: PtraceOptions::empty(),
+                is_seized: false,
+                resume_mode: ResumeMode::Cont,
+                stop: None,
+                pending_event: None,
+                event_msg: 0,
+                orig_syscall_num: None,
+                exit_status: None,
+            }),
+            wait_queue: WaitQueue::new(),
+            is_interrupt_pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the tracer, or `None` if the thread is not traced.
+    pub fn tracer(&self) -> Option<Arc<Process>> {
+        self.inner.lock().tracer.upgrade()
+    }
+
+    /// Returns whether the thread is traced.
+    pub fn is_traced(&self) -> bool {
+        self.inner.lock().tracer.strong_count() > 0
+    }
+
+    /// Returns whether the thread is traced by the `process`.
+    pub fn is_traced_by(&self, process: &Process) -> bool {
+        core::ptr::eq(self.inner.lock().tracer.as_ptr(), process)
+    }
+
+    /// Returns whether the thread is in a ptrace-stop.
+    pub fn is_stopped(&self) -> bool {
+        self.inner
+            .lock()
+            .stop
+            .as_ref()
+            .is_some_and(PtraceStop::is_stopped)
+    }
+
+    /// Returns whether the tracee waits for signals after `PTRACE_LISTEN`.
+    pub(super) fn is_listening(&self) -> bool {
+        self.inner
+            .lock()
+            .stop
+            .as_ref()
+            .is_some_and(|stop| stop.resumed.is_none() && stop.is_listening)
+    }
+
+    /// Returns whether the tracee should enter an interrupt-stop.
+    pub(super) fn is_interrupt_pending(&self) -> bool {
+        self.is_interrupt_pending.load(Ordering::Relaxed)
+    }
+
+    pub fn options(&self) -> PtraceOptions {
+        self.inner.lock().options
+    }
+
+    pub fn set_options(&self, options: PtraceOptions) {
+        self.inner.lock().options = options;
+    }
+
+    pub fn event_msg(&self) -> u64 {
+        self.inner.lock().event_msg
+    }
+
+    /// Returns the signal information of the current stop.
+    pub fn siginfo(&self) -> Result<siginfo_t> {
+        self.with_stop(|stop, _| stop.siginfo)
+    }
+
+    /// Sets the signal information of the current stop.
+    pub fn set_siginfo(&self, siginfo: siginfo_t) -> Result<()> {
+        self.with_stop(|stop, _| stop.siginfo = siginfo)
+    }
+
+    /// Operates on the user context of the stopped tracee.
+    ///
+    /// The closure also receives the number of the syscall in progress, which
+    /// can be changed to skip the syscall or to invoke another syscall.
+    pub fn with_user_ctx<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut UserContext, &mut Option<usize>) -> R,
+    {
+        self.with_stop(|stop, orig_syscall_num| f(&mut stop.user_ctx, orig_syscall_num))
+    }
+
+    fn with_stop<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut PtraceStop, &mut Option<usize>) -> R,
+    {
+        let mut inner = self.inner.lock();
+        let TraceeInner {
+            stop,
+            orig_syscall_num,
+            ..
+        } = &mut *inner;
+        match stop {
+            Some(stop) if stop.is_stopped() => Ok(f(stop, orig_syscall_num)),
+            _ => return_errno_with_message!(Errno::ESRCH, "the tracee is not stopped"),
+        }
+    }
+
+    /// Resumes the stopped tracee.
+    ///
+    /// The `signal` will be delivered to the tracee if the tracee is in a
+    /// signal-delivery-stop. Otherwise, it is ignored.
+    pub fn resume(&self, mode: ResumeMode, signal: Option<SigNum>) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let Some(stop) = inner.stop.as_mut().filter(|stop| stop.is_stopped()) else {
+            return_errno_with_message!(Errno::ESRCH, "the tracee is not stopped");
+        };
+
+        let signal = match stop.kind {
+            StopKind::Signal(_) => signal,
+            StopKind::Syscall | StopKind::Event(_) | StopKind::Group(_) | StopKind::Interrupt => {
+                None
+            }
+        };
+        set_single_step(&mut stop.user_ctx, mode == ResumeMode::SingleStep);
+        stop.resumed = Some(signal);
+        inner.resume_mode = mode;
+        drop(inner);
+
+        self.wait_queue.wake_all();
+        Ok(())
+    }
+
+    /// Lets the tracee in a group-stop or an interrupt-stop wait for signals
+    /// without being resumed (`PTRACE_LISTEN`).
+    ///
+    /// The tracee leaves the ptrace-stop, so it can no longer be inspected.
+    /// It enters the stop again when it receives a signal or when it is
+    /// interrupted by `PTRACE_INTERRUPT`.
+    pub fn listen(&self) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if !inner.is_seized {
+            return_errno_with_message!(Errno::EIO, "the tracee is not seized");
+        }
+        let Some(stop) = inner.stop.as_mut().filter(|stop| stop.is_stopped()) else {
+            return_errno_with_message!(Errno::ESRCH, "the tracee is not stopped");
+        };
+        if !matches!(stop.kind, StopKind::Group(_) | StopKind::Interrupt) {
+            return_errno_with_message!(Errno::EIO, "the tracee is not in a group-stop");
+        }
+        stop.is_listening = true;
+        drop(inner);
+
+        // The tracee may have received signals during the stop.
+        self.wait_queue.wake_all();
+        Ok(())
+    }
+
+    /// Returns the wait status of the current stop if the stop has not been
+    /// reported to the tracer.
+    ///
+    /// If `consume` is true, the stop will be marked as reported.
+    pub(in crate::process) fn take_stop_status(&self, consume: bool) -> Option<u32> {
+        let mut inner = self.inner.lock();
+        let options = inner.options;
+        let is_seized = inner.is_seized;
+        let stop = inner
+            .stop
+            .as_mut()
+            .filter(|stop| stop.is_stopped() && !stop.is_reported)?;
+
+        let sig = match stop.kind {
+            StopKind::Signal(sig_num) => sig_num.as_u8() as u32,
+            StopKind::Syscall if options.contains(PtraceOptions::PTRACE_O_TRACESYSGOOD) => {
+                SIGTRAP.as_u8() as u32 | 0x80
+            }
+            StopKind::Syscall => SIGTRAP.as_u8() as u32,
+            StopKind::Event(event) => SIGTRAP.as_u8() as u32 | ((event as u32) << 8),
+            StopKind::Group(sig_num) if is_seized => {
+                sig_num.as_u8() as u32 | (PTRACE_EVENT_STOP << 8)
+            }
+            StopKind::Group(sig_num) => sig_num.as_u8() as u32,
+            StopKind::Interrupt => SIGTRAP.as_u8() as u32 | (PTRACE_EVENT_STOP << 8),
+        };
+        if consume {
+            stop.is_reported = true;
+        }
+
+        Some((sig << 8) | 0x7f)
+    }
+
+    /// Returns the exit status of the exited tracee if the exit has not been
+    /// reported to the tracer.
+    ///
+    /// If `consume` is true, the exit will be marked as reported.
+    pub(in crate::process) fn take_exit_status(&self, consume: bool) -> Option<ExitCode> {
+        let mut inner = self.inner.lock();
+        if consume {
+            inner.exit_status.take()
+        } else {
+            inner.exit_status
+        }
+    }
+
+    /// Wakes up the tracee if it is in a ptrace-stop.
+    pub(super) fn wake_up(&self) {
+        self.wait_queue.wake_all();
+    }
+}
+
+/// Makes the `tracer` process trace the `thread`.
+///
+/// If `is_seized` is false, the tracee behaves as if it is attached by
+/// `PTRACE_ATTACH` or `PTRACE_TRACEME`, and receives a `SIGTRAP` after each
+/// successful `execve` unless `PTRACE_O_TRACEEXEC` is set.
+pub fn ptrace_attach(
+    tracer: &Arc<Process>,
+    thread: &Arc<Thread>,
+    options: PtraceOptions,
+    is_seized: bool,
+) -> Result<()> {
+    let posix_thread = thread.as_posix_thread().unwrap();
+
+    {
+        let mut inner = posix_thread.tracee.inner.lock();
+        if inner.tracer.strong_count() > 0 {
+            return_errno_with_message!(Errno::EPERM, "the thread is already traced");
+        }
+        inner.tracer = Arc::downgrade(tracer);
+        inner.options = options;
+        inner.is_seized = is_seized;
+        inner.resume_mode = ResumeMode::Cont;
+    }
+
+    tracer
+        .tracees()
+        .lock()
+        .insert(posix_thread.tid(), thread.clone());
+
+    Ok(())
+}
+
+/// Detaches the `thread` from its tracer.
+///
+/// If the thread is stopped, it will be resumed with the `signal`.
+pub fn ptrace_detach(thread: &Arc<Thread>, signal: Option<SigNum>) {
+    let posix_thread = thread.as_posix_thread().unwrap();
+    let tracee = &posix_thread.tracee;
+
+    let tracer = {
+        let mut inner = tracee.inner.lock();
+        if let Some(stop) = inner.stop.as_mut().filter(|stop| stop.resumed.is_none()) {
+            let signal = match stop.kind {
+                StopKind::Signal(_) => signal,
+                StopKind::Syscall
+                | StopKind::Event(_)
+                | StopKind::Group(_)
+                | StopKind::Interrupt => None,
+            };
+            set_single_step(&mut stop.user_ctx, false);
+            stop.resumed = Some(signal);
+        }
+        inner.reset()
+    };
+    tracee.is_interrupt_pending.store(false, Ordering::Relaxed);
+    tracee.wait_queue.wake_all();
+
+    if let Some(tracer) = tracer {
+        tracer.tracees().lock().remove(&posix_thread.tid());
+    }
+}
+
+/// Detaches all the tracees of the exiting `tracer`.
+///
+/// The tracees that have set `PTRACE_O_EXITKILL` will be killed.
+pub(in crate::process) fn ptrace_detach_all(tracer: &Process) {
+    let tracees: Vec<_> = tracer
+        .tracees()
+        .lock()
+        .extract_if(|_, _| true)
+        .map(|(_, thread)| thread)
+        .collect();
+
+    for thread in tracees {
+        let posix_thread = thread.as_posix_thread().unwrap();
+        let options = posix_thread.tracee.options();
+        ptrace_detach(&thread, None);
+        if options.contains(PtraceOptions::PTRACE_O_EXITKILL) {
+            posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+        }
+    }
+}
+
+impl PosixThread {
+    /// Returns the tracing state of the thread.
+    pub fn tracee(&self) -> &Tracee {
+        &self.tracee
+    }
+
+    /// Reports a signal-delivery-stop to the tracer.
+    ///
+    /// Returns the signal that should be delivered instead, which is chosen by
+    /// the tracer. `None` means that the signal is suppressed.
+    pub(in crate::process) fn ptrace_stop_for_signal(
+        &self,
+        signal: &dyn Signal,
+        user_ctx: &mut UserContext,
+    ) -> Option<Box<dyn Signal>> {
+        let (sig_num, siginfo) =
+            self.ptrace_stop(StopKind::Signal(signal.num()), signal.to_info(), user_ctx)?;
+
+        // Like Linux, the signal information, which may be set by the tracer
+        // with `PTRACE_SETSIGINFO`, is delivered if it matches the signal.
+        if siginfo.si_signo == sig_num.as_u8() as i32 {
+            Some(Box::new(RawSignal::new(sig_num, siginfo)))
+        } else {
+            Some(Box::new(KernelSignal::new(sig_num)))
+        }
+    }
+
+    /// Reports a group-stop caused by the stop signal `sig_num` to the tracer.
+    ///
+    /// The thread stays stopped until the tracer resumes it. If the thread is
+    /// seized, the stop is reported as `PTRACE_EVENT_STOP`.
+    pub(in crate::process) fn ptrace_group_stop(
+        &self,
+        sig_num: SigNum,
+        user_ctx: &mut UserContext,
+    ) {
+        self.ptrace_stop(
+            StopKind::Group(sig_num),
+            event_stop_siginfo(sig_num),
+            user_ctx,
+        );
+    }
+
+    /// Asks the seized thread to enter an interrupt-stop (`PTRACE_INTERRUPT`).
+    ///
+    /// If the thread is blocked in a syscall, the syscall is interrupted, and
+    /// it is restarted after the stop if possible. Nothing happens if the
+    /// thread is already in a ptrace-stop.
+    pub fn ptrace_interrupt(&self) -> Result<()> {
+        {
+            let inner = self.tracee.inner.lock();
+            if !inner.is_seized {
+                return_errno_with_message!(Errno::EIO, "the tracee is not seized");
+            }
+            if inner.stop.as_ref().is_some_and(PtraceStop::is_stopped) {
+                return Ok(());
+            }
+            self.tracee
+                .is_interrupt_pending
+                .store(true, Ordering::Relaxed);
+        }
+
+        self.tracee.wake_up();
+        if let Some(waker) = &*self.signalled_waker.lock() {
+            waker.wake_up();
+        }
+        Ok(())
+    }
+
+    /// Enters an interrupt-stop if the tracer has asked for it with `PTRACE_INTERRUPT`.
+    ///
+    /// Returns whether the thread has been interrupted.
+    pub(in crate::process) fn ptrace_interrupt_stop(&self, user_ctx: &mut UserContext) -> bool {
+        if !self
+            .tracee
+            .is_interrupt_pending
+            .swap(false, Ordering::Relaxed)
+        {
+            return false;
+        }
+
+        self.ptrace_stop(StopKind::Interrupt, event_stop_siginfo(SIGTRAP), user_ctx);
+        true
+    }
+
+    /// Reports a syscall-entry-stop to the tracer if the thread is resumed by `PTRACE_SYSCALL`.
+    ///
+    /// Returns the number of the syscall to invoke, which may be changed by the tracer,
+    /// or `None` if the syscall should be skipped.
+    pub(crate) fn ptrace_syscall_entry(&self, user_ctx: &mut UserContext) -> Option<usize> {
+        let syscall_num = user_ctx.syscall_num();
+
+        {
+            let mut inner = self.tracee.inner.lock();
+            if inner.tracer.strong_count() == 0 {
+                return Some(syscall_num);
+            }
+            inner.orig_syscall_num = Some(syscall_num);
+            if inner.resume_mode != ResumeMode::Syscall {
+                return Some(syscall_num);
+            }
+        }
+
+        // On x86-64, the syscall number and the return value share the same
+        // register. Like Linux, the tracer should see `-ENOSYS` at the entry.
+        #[cfg(target_arch = "x86_64")]
+        user_ctx.set_syscall_ret(-(Errno::ENOSYS as i32) as usize);
+
+        self.ptrace_stop(
+            StopKind::Syscall,
+            siginfo_t::new(SIGTRAP, SIGTRAP.as_u8() as i32),
+            user_ctx,
+        );
+        if self.sig_pending().contains(SIGKILL) {
+            return None;
+        }
+
+        // A negative syscall number means that the tracer wants to skip the syscall.
+        self.tracee
+            .inner
+            .lock()
+            .orig_syscall_num
+            .filter(|num| (*num as isize) >= 0)
+    }
+
+    /// Reports the pending event-stop and the syscall-exit-stop to the tracer.
+    pub(crate) fn ptrace_syscall_exit(&self, user_ctx: &mut UserContext) {
+        let (pending_event, resume_mode) = {
+            let mut inner = self.tracee.inner.lock();
+            if inner.tracer.strong_count() == 0 {
+                inner.orig_syscall_num = None;
+                return;
+            }
+            (inner.pending_event.take(), inner.resume_mode)
+        };
+
+        if let Some(event) = pending_event {
+            self.ptrace_stop(
+                StopKind::Event(event),
+                siginfo_t::new(SIGTRAP, SI_KERNEL),
+                user_ctx,
+            );
+        }
+
+        if resume_mode == ResumeMode::Syscall && !self.sig_pending().contains(SIGKILL) {
+            self.ptrace_stop(
+                StopKind::Syscall,
+                siginfo_t::new(SIGTRAP, SIGTRAP.as_u8() as i32),
+                user_ctx,
+            );
+        }
+
+        self.tracee.inner.lock().orig_syscall_num = None;
+    }
+
+    /// Notifies the tracer of a successful `execve`.
+    pub(crate) fn ptrace_report_exec(&self, old_tid: Tid) {
+        let mut inner = self.tracee.inner.lock();
+        if inner.tracer.strong_count() == 0 {
+            return;
+        }
+
+        if inner.options.contains(PtraceOptions::PTRACE_O_TRACEEXEC) {
+            inner.pending_event = Some(PtraceEvent::Exec);
+            inner.event_msg = old_tid as u64;
+        } else if !inner.is_seized {
+            drop(inner);
+            self.enqueue_signal(Box::new(KernelSignal::new(SIGTRAP)));
+        }
+    }
+
+    /// Makes the newly-created `child` traced if the current thread is traced and
+    /// the tracer asks for it.
+    ///
+    /// This method should be called before the child starts to run.
+    pub(in crate::process) fn ptrace_report_clone(
+        &self,
+        child: &Arc<Thread>,
+        clone_flags: CloneFlags,
+        exit_signal: Option<SigNum>,
+    ) -> Result<()> {
+        let event = if clone_flags.contains(CloneFlags::CLONE_VFORK) {
+            PtraceEvent::Vfork
+        } else if !clone_flags.contains(CloneFlags::CLONE_THREAD) && exit_signal == Some(SIGCHLD) {
+            PtraceEvent::Fork
+        } else {
+            PtraceEvent::Clone
+        };
+
+        let (tracer, options, is_seized) = {
+            let inner = self.tracee.inner.lock();
+            let Some(tracer) = inner.tracer.upgrade() else {
+                return Ok(());
+            };
+            (tracer, inner.options, inner.is_seized)
+        };
+
+        let is_event_traced = options.contains(event.option());
+        if clone_flags.contains(CloneFlags::CLONE_UNTRACED)
+            || !(is_event_traced || clone_flags.contains(CloneFlags::CLONE_PTRACE))
+        {
+            return Ok(());
+        }
+
+        ptrace_attach(&tracer, child, options, is_seized)?;
+        // The new tracee starts with a `SIGSTOP`, so that the tracer can take control of it.
+        let child_posix_thread = child.as_posix_thread().unwrap();
+        child_posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGSTOP)));
+
+        if is_event_traced {
+            let mut inner = self.tracee.inner.lock();
+            inner.pending_event = Some(event);
+            inner.event_msg = child_posix_thread.tid() as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Detaches the exiting thread from its tracer, and reports the exit to the tracer.
+    ///
+    /// If the tracer is the parent and the thread is the main thread, the exit is
+    /// reported when the process becomes a zombie, so it is not reported here.
+    pub(super) fn ptrace_exit(&self, exit_code: ExitCode) {
+        let Some(tracer) = self.tracee.inner.lock().reset() else {
+            return;
+        };
+
+        let process = self.process();
+        let is_reported_as_zombie = self.tid() == process.pid()
+            && process
+                .parent()
+                .lock()
+                .process()
+                .upgrade()
+                .is_some_and(|parent| Arc::ptr_eq(&parent, &tracer));
+
+        if is_reported_as_zombie {
+            tracer.tracees().lock().remove(&self.tid());
+        } else {
+            // The tracee is kept in `tracees` until the tracer waits for it.
+            self.tracee.inner.lock().exit_status = Some(exit_code);
+            tracer.enqueue_signal(KernelSignal::new(SIGCHLD));
+        }
+        tracer.children_wait_queue().wake_all();
+    }
+
+    /// Enters a ptrace-stop and waits until the tracer resumes the thread.
+    ///
+    /// Returns the signal set by the tracer when resuming the thread and the
+    /// signal information of the stop, or `None` if no signal is set, the
+    /// thread is not traced, or it is killed when stopped.
+    fn ptrace_stop(
+        &self,
+        kind: StopKind,
+        siginfo: siginfo_t,
+        user_ctx: &mut UserContext,
+    ) -> Option<(SigNum, siginfo_t)> {
+        let tracer = {
+            let mut inner = self.tracee.inner.lock();
+            let tracer = inner.tracer.upgrade()?;
+            inner.stop = Some(PtraceStop {
+                kind,
+                siginfo,
+                user_ctx: user_ctx.clone(),
+                is_reported: false,
+                resumed: None,
+                is_listening: false,
+            });
+            tracer
+        };
+
+        tracer.enqueue_signal(KernelSignal::new(SIGCHLD));
+        tracer.children_wait_queue().wake_all();
+
+        self.tracee.wait_queue.wait_until(|| {
+            if self.sig_pending().contains(SIGKILL) {
+                return Some(());
+            }
+            let inner = self.tracee.inner.lock();
+            match &inner.stop {
+                // A listening tracee is woken up by signals and `PTRACE_INTERRUPT`.
+                Some(stop) if stop.resumed.is_none() => {
+                    (stop.is_listening && self.has_pending()).then_some(())
+                }
+                _ => Some(()),
+            }
+        });
+
+        let stop = self.tracee.inner.lock().stop.take()?;
+        if let Some(signal) = stop.resumed {
+            restore_user_ctx(user_ctx, &stop.user_ctx);
+            return signal.map(|sig_num| (sig_num, stop.siginfo));
+        }
+        if !stop.is_listening || self.sig_pending().contains(SIGKILL) {
+            return None;
+        }
+
+        // Like Linux, the listening tracee enters the same stop again to notify
+        // the tracer of the signal or the interrupt.
+        restore_user_ctx(user_ctx, &stop.user_ctx);
+        self.tracee
+            .is_interrupt_pending
+            .store(false, Ordering::Relaxed);
+        self.ptrace_stop(stop.kind, stop.siginfo, user_ctx)
+    }
+}
+
+/// Restores the user context that may have been modified by the tracer.
+///
+/// If the TLS pointer is changed, it is applied to the current task in the
+/// same way as `arch_prctl(ARCH_SET_FS)`.
+fn restore_user_ctx(user_ctx: &mut UserContext, new_user_ctx: &UserContext) {
+    let old_tls_pointer = user_ctx.tls_pointer();
+    *user_ctx = new_user_ctx.clone();
+
+    let tls_pointer = user_ctx.tls_pointer();
+    if tls_pointer != old_tls_pointer {
+        Task::current().unwrap().set_tls_pointer(tls_pointer);
+        user_ctx.activate_tls_pointer();
+    }
+}
+
+/// Returns the signal information of a `PTRACE_EVENT_STOP` caused by `sig_num`.
+fn event_stop_siginfo(sig_num: SigNum) -> siginfo_t {
+    siginfo_t::new(
+        SIGTRAP,
+        (sig_num.as_u8() as u32 | (PTRACE_EVENT_STOP << 8)) as i32,
+    )
+}
+
+/// Sets or clears the single-step flag of the user context.
+fn set_single_step(user_ctx: &mut UserContext, enabled: bool) {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "x86_64")] {
+            // Bit 8 is the TF (trap) flag.
+            const X86_RFLAGS_TF: usize = 1 << 8;
+            if enabled {
+                user_ctx.general_regs_mut().rflags |= X86_RFLAGS_TF;
+            } else {
+                user_ctx.general_regs_mut().rflags &= !X86_RFLAGS_TF;
+            }
+        } else {
+            let _ = (user_ctx, enabled);
+        }
+    }
+}
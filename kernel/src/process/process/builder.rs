@@ -6,6 +6,7 @@ use super::{Pid, Process};
 use crate::{
     prelude::*,
     process::{
+        namespace::{init_pid_ns, PidNamespace},
         posix_thread::{create_posix_task_from_executable, PosixThreadBuilder},
         process_vm::ProcessVm,
         rlimit::ResourceLimits,
@@ -30,6 +31,7 @@ pub struct ProcessBuilder<'a> {
     sig_dispositions: Option<Arc<Mutex<SigDispositions>>>,
     credentials: Option<Credentials>,
     nice: Option<Nice>,
+    pid_ns: Option<Arc<PidNamespace>>,
 }
 
 impl<'a> ProcessBuilder<'a> {
@@ -46,6 +48,7 @@ impl<'a> ProcessBuilder<'a> {
             sig_dispositions: None,
             credentials: None,
             nice: None,
+            pid_ns: None,
         }
     }
 
@@ -89,6 +92,11 @@ impl<'a> ProcessBuilder<'a> {
         self
     }
 
+    pub fn pid_ns(&mut self, pid_ns: Arc<PidNamespace>) -> &mut Self {
+        self.pid_ns = Some(pid_ns);
+        self
+    }
+
     fn check_build(&self) -> Result<()> {
         if self.main_thread_builder.is_some() {
             debug_assert!(self.parent.upgrade().is_some());
@@ -121,6 +129,7 @@ impl<'a> ProcessBuilder<'a> {
             sig_dispositions,
             credentials,
             nice,
+            pid_ns,
         } = self;
 
         let process_vm = process_vm.or_else(|| Some(ProcessVm::alloc())).unwrap();
@@ -135,8 +144,11 @@ impl<'a> ProcessBuilder<'a> {
 
         let nice = nice.or_else(|| Some(Nice::default())).unwrap();
 
+        let pid_ns = pid_ns.unwrap_or_else(|| init_pid_ns().clone());
+
         let process = Process::new(
             pid,
+            pid_ns,
             parent,
             executable_path.to_string(),
             process_vm,
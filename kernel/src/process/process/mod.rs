@@ -1,9 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU32, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicI16, AtomicU32, Ordering};
 
 use self::timer_manager::PosixTimerManager;
 use super::{
+    namespace::PidNamespace,
     posix_thread::{allocate_posix_tid, AsPosixThread},
     process_table,
     process_vm::{Heap, InitStackReader, ProcessVm},
@@ -12,6 +13,7 @@ use super::{
         sig_disposition::SigDispositions,
         sig_num::{AtomicSigNum, SigNum},
         signals::Signal,
+        Pollee,
     },
     status::ProcessStatus,
     task_set::TaskSet,
@@ -21,7 +23,7 @@ use crate::{
     device::tty::open_ntty_as_controlling_terminal,
     prelude::*,
     sched::priority::{AtomicNice, Nice},
-    thread::{AsThread, Thread},
+    thread::{AsThread, Thread, Tid},
     time::clocks::ProfClock,
     vm::vmar::Vmar,
 };
@@ -63,6 +65,8 @@ pub(super) fn init() {
 pub struct Process {
     // Immutable Part
     pid: Pid,
+    /// The PID namespace that the process belongs to
+    pid_ns: Arc<PidNamespace>,
 
     process_vm: ProcessVm,
     /// Wait for child status changed
@@ -75,10 +79,14 @@ pub struct Process {
     tasks: Mutex<TaskSet>,
     /// Process status
     status: ProcessStatus,
+    /// The pollee of the pidfd files, which will be notified when the process exits
+    pidfd_pollee: Pollee,
     /// Parent process
     pub(super) parent: ParentProcess,
     /// Children processes
     children: Mutex<BTreeMap<Pid, Arc<Process>>>,
+    /// Threads traced by the process
+    tracees: Mutex<BTreeMap<Tid, Arc<Thread>>>,
     /// Process group
     pub(super) process_group: Mutex<Weak<ProcessGroup>>,
     /// resource limits
@@ -87,6 +95,11 @@ pub struct Process {
     /// According to POSIX.1, the nice value is a per-process attribute,
     /// the threads in a process should share a nice value.
     nice: AtomicNice,
+    /// Whether the process can produce core dumps and be attached by `ptrace`.
+    is_dumpable: AtomicBool,
+    /// The adjustment to the badness of the process when the OOM killer
+    /// chooses a victim.
+    oom_score_adj: AtomicI16,
 
     // Signal
     /// Sig dispositions
@@ -174,6 +187,7 @@ impl Process {
     #[allow(clippy::too_many_arguments)]
     fn new(
         pid: Pid,
+        pid_ns: Arc<PidNamespace>,
         parent: Weak<Process>,
         executable_path: String,
         process_vm: ProcessVm,
@@ -190,19 +204,24 @@ impl Process {
 
         Arc::new_cyclic(|process_ref: &Weak<Process>| Self {
             pid,
+            pid_ns,
             tasks: Mutex::new(TaskSet::new()),
             executable_path: RwLock::new(executable_path),
             process_vm,
             children_wait_queue,
             status: ProcessStatus::default(),
+            pidfd_pollee: Pollee::new(),
             parent: ParentProcess::new(parent),
             children: Mutex::new(BTreeMap::new()),
+            tracees: Mutex::new(BTreeMap::new()),
             process_group: Mutex::new(Weak::new()),
             sig_dispositions,
             parent_death_signal: AtomicSigNum::new_empty(),
             exit_signal: AtomicSigNum::new_empty(),
             resource_limits: Mutex::new(resource_limits),
             nice: AtomicNice::new(nice),
+            is_dumpable: AtomicBool::new(true),
+            oom_score_adj: AtomicI16::new(0),
             timer_manager: PosixTimerManager::new(&prof_clock, process_ref),
             prof_clock,
         })
@@ -282,6 +301,19 @@ impl Process {
         self.pid
     }
 
+    /// Returns the PID namespace that the process belongs to.
+    pub fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
+    /// Returns the PID in the PID namespace that the process belongs to.
+    ///
+    /// This is the PID seen by the process itself, whereas [`Self::pid`]
+    /// returns the PID in the root PID namespace.
+    pub fn ns_pid(&self) -> Pid {
+        self.pid_ns.local_id_or_zero(self.pid)
+    }
+
     /// Gets the profiling clock of the process.
     pub fn prof_clock(&self) -> &Arc<ProfClock> {
         &self.prof_clock
@@ -312,6 +344,33 @@ impl Process {
         &self.nice
     }
 
+    /// Returns whether the process is dumpable.
+    ///
+    /// A process that is not dumpable will not produce core dumps.
+    pub fn is_dumpable(&self) -> bool {
+        self.is_dumpable.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether the process is dumpable.
+    pub fn set_dumpable(&self, is_dumpable: bool) {
+        self.is_dumpable.store(is_dumpable, Ordering::Relaxed);
+    }
+
+    /// Returns the adjustment to the OOM score of the process.
+    ///
+    /// The value ranges from [`OOM_SCORE_ADJ_MIN`] to [`OOM_SCORE_ADJ_MAX`].
+    ///
+    /// [`OOM_SCORE_ADJ_MIN`]: crate::vm::oom::OOM_SCORE_ADJ_MIN
+    /// [`OOM_SCORE_ADJ_MAX`]: crate::vm::oom::OOM_SCORE_ADJ_MAX
+    pub fn oom_score_adj(&self) -> i16 {
+        self.oom_score_adj.load(Ordering::Relaxed)
+    }
+
+    /// Sets the adjustment to the OOM score of the process.
+    pub fn set_oom_score_adj(&self, oom_score_adj: i16) {
+        self.oom_score_adj.store(oom_score_adj, Ordering::Relaxed);
+    }
+
     pub fn main_thread(&self) -> Arc<Thread> {
         self.tasks.lock().main().as_thread().unwrap().clone()
     }
@@ -337,6 +396,10 @@ impl Process {
         &self.children_wait_queue
     }
 
+    pub(super) fn tracees(&self) -> &Mutex<BTreeMap<Tid, Arc<Thread>>> {
+        &self.tracees
+    }
+
     // *********** Process group & Session***********
 
     /// Returns the process group ID of the process.
@@ -668,6 +731,11 @@ impl Process {
     pub fn status(&self) -> &ProcessStatus {
         &self.status
     }
+
+    /// Returns the pollee of the pidfd files that refer to the process.
+    pub fn pidfd_pollee(&self) -> &Pollee {
+        &self.pidfd_pollee
+    }
 }
 
 #[cfg(ktest)]
@@ -688,6 +756,7 @@ mod test {
         };
         Process::new(
             pid,
+            crate::process::namespace::init_pid_ns().clone(),
             parent,
             String::new(),
             ProcessVm::alloc(),
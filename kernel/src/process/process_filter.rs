@@ -27,6 +27,8 @@ impl ProcessFilter {
     }
 
     // used for wait4 and kill
+    //
+    // The IDs are the ones in the PID namespace of the current process.
     pub fn from_id(wait_pid: i32) -> Self {
         // https://man7.org/linux/man-pages/man2/waitpid.2.html
         // https://man7.org/linux/man-pages/man2/kill.2.html
@@ -38,7 +40,8 @@ impl ProcessFilter {
             ProcessFilter::Any
         } else if wait_pid == 0 {
             // wait for any child process with same process group ID
-            let pgid = current!().pgid();
+            let current = current!();
+            let pgid = current.pid_ns().local_id_or_zero(current.pgid());
             ProcessFilter::WithPgid(pgid)
         } else {
             // pid > 0. wait for the child whose process ID is equal to the value of pid.
@@ -28,6 +28,11 @@ pub fn process_table_mut() -> MutexGuard<'static, ProcessTable> {
     PROCESS_TABLE.lock()
 }
 
+/// Gets the process table without waiting for the lock.
+pub fn try_process_table_mut() -> Option<MutexGuard<'static, ProcessTable>> {
+    PROCESS_TABLE.try_lock()
+}
+
 /// Process Table.
 pub struct ProcessTable {
     inner: BTreeMap<Pid, Arc<Process>>,
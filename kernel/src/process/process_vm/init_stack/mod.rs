@@ -20,7 +20,7 @@ use core::{
 
 use align_ext::AlignExt;
 use aster_rights::Full;
-use ostd::mm::{vm_space::VmItem, UntypedMem, VmIo, VmSpace, MAX_USERSPACE_VADDR};
+use ostd::mm::{UntypedMem, VmIo, VmSpace, MAX_USERSPACE_VADDR};
 
 use self::aux_vec::{AuxKey, AuxVec};
 use crate::{
@@ -28,6 +28,7 @@ use crate::{
     util::random::getrandom,
     vm::{
         perms::VmPerms,
+        util::mapped_frame_at,
         vmar::Vmar,
         vmo::{Vmo, VmoOptions, VmoRightsOp},
     },
@@ -387,7 +388,7 @@ impl InitStackReader<'_> {
         let mut cursor = self
             .vm_space
             .cursor(&(page_base_addr..page_base_addr + PAGE_SIZE))?;
-        let VmItem::Mapped { frame, .. } = cursor.query()? else {
+        let Some((frame, _)) = mapped_frame_at(cursor.query()?, page_base_addr) else {
             return_errno_with_message!(Errno::EACCES, "Page not accessible");
         };
 
@@ -411,7 +412,7 @@ impl InitStackReader<'_> {
         let mut cursor = self
             .vm_space
             .cursor(&(page_base_addr..page_base_addr + PAGE_SIZE))?;
-        let VmItem::Mapped { frame, .. } = cursor.query()? else {
+        let Some((frame, _)) = mapped_frame_at(cursor.query()?, page_base_addr) else {
             return_errno_with_message!(Errno::EACCES, "Page not accessible");
         };
 
@@ -453,7 +454,7 @@ impl InitStackReader<'_> {
         let mut cursor = self
             .vm_space
             .cursor(&(page_base_addr..page_base_addr + PAGE_SIZE))?;
-        let VmItem::Mapped { frame, .. } = cursor.query()? else {
+        let Some((frame, _)) = mapped_frame_at(cursor.query()?, page_base_addr) else {
             return_errno_with_message!(Errno::EACCES, "Page not accessible");
         };
 
@@ -108,6 +108,12 @@ impl ProcessVm {
         &self.root_vmar
     }
 
+    /// Returns the resident set size (RSS), i.e., the size of the memory
+    /// mapped in the process, in bytes.
+    pub fn rss(&self) -> usize {
+        self.root_vmar.rss()
+    }
+
     /// Returns a reader for reading contents from
     /// the `InitStack`.
     pub fn init_stack_reader(&self) -> InitStackReader {
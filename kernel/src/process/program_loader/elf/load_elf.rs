@@ -333,6 +333,7 @@ fn map_segment_vmo(
         let mut vm_map_options = root_vmar
             .new_map(segment_size, perms)?
             .vmo(segment_vmo)
+            .dentry(elf_file.clone())
             .vmo_offset(segment_offset)
             .vmo_limit(segment_offset + segment_size)
             .can_overwrite(true);
@@ -58,6 +58,22 @@ impl siginfo_t {
         // let siginfo = *self;
         read_union_fields!(self.siginfo_fields.sigfault.addr)
     }
+
+    pub fn set_si_pid_uid(&mut self, pid: Pid, uid: Uid) {
+        self.siginfo_fields.common.first.piduid = siginfo_piduid_t { pid, uid };
+    }
+
+    pub fn si_pid(&self) -> Pid {
+        read_union_fields!(self.siginfo_fields.common.first.piduid.pid)
+    }
+
+    pub fn si_uid(&self) -> Uid {
+        read_union_fields!(self.siginfo_fields.common.first.piduid.uid)
+    }
+
+    pub fn set_si_value(&mut self, value: sigval_t) {
+        self.siginfo_fields.common.second.value = value;
+    }
 }
 
 #[derive(Clone, Copy, Pod)]
@@ -78,7 +94,7 @@ impl siginfo_fields_t {
 
 #[derive(Clone, Copy, Pod)]
 #[repr(C)]
-union siginfo_common_t {
+struct siginfo_common_t {
     first: siginfo_common_first_t,
     second: siginfo_common_second_t,
 }
@@ -118,6 +134,14 @@ pub union sigval_t {
     sigval_ptr: Vaddr, //*mut c_void
 }
 
+impl Debug for sigval_t {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("sigval_t")
+            .field("sigval_ptr", &self.read_ptr())
+            .finish()
+    }
+}
+
 impl sigval_t {
     pub fn read_int(&self) -> i32 {
         read_union_fields!(self.sigval_int)
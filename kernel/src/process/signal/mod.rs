@@ -32,7 +32,11 @@ use crate::{
     cpu::LinuxAbi,
     current_userspace,
     prelude::*,
-    process::{posix_thread::do_exit_group, TermStatus},
+    process::{
+        coredump::{do_coredump, stop_other_threads, wait_for_core_dump},
+        posix_thread::do_exit_group,
+        TermStatus,
+    },
 };
 
 pub trait SignalContext {
@@ -48,17 +52,46 @@ pub fn handle_pending_signal(
     ctx: &Context,
     syscall_number: Option<usize>,
 ) {
-    // We first deal with signal in current thread, then signal in current process.
     let posix_thread = ctx.posix_thread;
-    let signal = {
+    // A seized thread stops before handling signals if the tracer has interrupted it.
+    let is_interrupted = posix_thread.ptrace_interrupt_stop(user_ctx);
+
+    // We first deal with signal in current thread, then signal in current process.
+    let mut signal = {
         let sig_mask = posix_thread.sig_mask().load(Ordering::Relaxed);
         if let Some(signal) = posix_thread.dequeue_signal(&sig_mask) {
             signal
         } else {
+            // The syscall is interrupted by the tracer rather than a signal.
+            if is_interrupted {
+                restart_syscall(user_ctx, syscall_number);
+            }
             return;
         }
     };
 
+    // If another thread is dumping the core, the current thread has been killed. It stops until
+    // the core is dumped, and then exits.
+    if wait_for_core_dump(ctx, user_ctx) {
+        do_exit_group(TermStatus::Killed(SIGKILL));
+        return;
+    }
+
+    // A traced thread enters a signal-delivery-stop, where the tracer can
+    // suppress the signal or replace it with another one.
+    if signal.num() != SIGKILL && posix_thread.tracee().is_traced() {
+        match posix_thread.ptrace_stop_for_signal(signal.as_ref(), user_ctx) {
+            Some(new_signal) => signal = new_signal,
+            None => {
+                if posix_thread.sig_pending().contains(SIGKILL) {
+                    return handle_pending_signal(user_ctx, ctx, syscall_number);
+                }
+                restart_syscall(user_ctx, syscall_number);
+                return;
+            }
+        }
+    }
+
     let sig_num = signal.num();
     trace!("sig_num = {:?}, sig_name = {}", sig_num, sig_num.sig_name());
     let current = posix_thread.process();
@@ -114,7 +147,40 @@ pub fn handle_pending_signal(
             let sig_default_action = SigDefaultAction::from_signum(sig_num);
             trace!("sig_default_action: {:?}", sig_default_action);
             match sig_default_action {
-                SigDefaultAction::Core | SigDefaultAction::Term => {
+                SigDefaultAction::Core => {
+                    // The other threads stay stopped until the current thread exits the process,
+                    // so they cannot change the memory being dumped or the exit status.
+                    let Some(stopped_threads) = stop_other_threads(ctx) else {
+                        // Another thread is dumping the core, or the process is exiting.
+                        wait_for_core_dump(ctx, user_ctx);
+                        do_exit_group(TermStatus::Killed(sig_num));
+                        return;
+                    };
+
+                    let term_status =
+                        match do_coredump(ctx, &signal.to_info(), user_ctx, &stopped_threads) {
+                            Ok(()) => {
+                                warn!(
+                                    "{:?}: terminating on signal {} (core dumped)",
+                                    current.executable_path(),
+                                    sig_num.sig_name()
+                                );
+                                TermStatus::Dumped(sig_num)
+                            }
+                            Err(err) => {
+                                warn!(
+                                    "{:?}: terminating on signal {} (core not dumped: {:?})",
+                                    current.executable_path(),
+                                    sig_num.sig_name(),
+                                    err
+                                );
+                                TermStatus::Killed(sig_num)
+                            }
+                        };
+                    do_exit_group(term_status);
+                    drop(stopped_threads);
+                }
+                SigDefaultAction::Term => {
                     warn!(
                         "{:?}: terminating on signal {}",
                         current.executable_path(),
@@ -125,7 +191,12 @@ pub fn handle_pending_signal(
                 }
                 SigDefaultAction::Ign => {}
                 SigDefaultAction::Stop => {
-                    let _ = ctx.thread.stop();
+                    // A traced thread stays in a group-stop until the tracer resumes it.
+                    if posix_thread.tracee().is_traced() {
+                        posix_thread.ptrace_group_stop(sig_num, user_ctx);
+                    } else {
+                        let _ = ctx.thread.stop();
+                    }
                 }
                 SigDefaultAction::Cont => {
                     let _ = ctx.thread.resume();
@@ -135,6 +206,16 @@ pub fn handle_pending_signal(
     }
 }
 
+/// Restarts the syscall if it has returned `ERESTARTSYS` but no signal is delivered.
+fn restart_syscall(user_ctx: &mut UserContext, syscall_number: Option<usize>) {
+    if let Some(syscall_number) = syscall_number
+        && user_ctx.syscall_ret() == -(Errno::ERESTARTSYS as i32) as usize
+    {
+        user_ctx.set_syscall_num(syscall_number);
+        user_ctx.set_instruction_pointer(user_ctx.instruction_pointer() - 2);
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn handle_user_signal(
     ctx: &Context,
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Debug;
+
+use super::Signal;
+use crate::process::signal::{c_types::siginfo_t, sig_num::SigNum};
+
+/// A signal whose `siginfo_t` is given as is, e.g., by the tracer with `PTRACE_SETSIGINFO`.
+#[derive(Clone, Copy)]
+pub struct RawSignal {
+    num: SigNum,
+    info: siginfo_t,
+}
+
+impl RawSignal {
+    /// Creates a signal from `info`.
+    ///
+    /// The signal number in `info` must be `num`.
+    pub fn new(num: SigNum, info: siginfo_t) -> Self {
+        debug_assert_eq!(info.si_signo, num.as_u8() as i32);
+        Self { num, info }
+    }
+}
+
+impl Debug for RawSignal {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RawSignal")
+            .field("num", &self.num)
+            .field("code", &self.info.si_code)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Signal for RawSignal {
+    fn num(&self) -> SigNum {
+        self.num
+    }
+
+    fn to_info(&self) -> siginfo_t {
+        self.info
+    }
+}
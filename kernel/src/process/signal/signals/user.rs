@@ -4,9 +4,10 @@
 
 use super::Signal;
 use crate::process::{
+    namespace::PidNamespace,
     signal::{
-        c_types::siginfo_t,
-        constants::{SI_QUEUE, SI_TKILL, SI_USER},
+        c_types::{siginfo_t, sigval_t},
+        constants::{SI_MESGQ, SI_QUEUE, SI_TKILL, SI_USER},
         sig_num::SigNum,
     },
     Pid, Uid,
@@ -25,9 +26,15 @@ pub enum UserSignalKind {
     Kill,
     Tkill,
     Sigqueue,
+    /// A message arrives at an empty POSIX message queue.
+    MessageQueue(sigval_t),
 }
 
 impl UserSignal {
+    /// Creates a signal sent by the process with the global PID `pid`.
+    ///
+    /// The PID should be translated with [`Self::for_receiver_in`] before the
+    /// signal is delivered.
     pub fn new(num: SigNum, kind: UserSignalKind, pid: Pid, uid: Uid) -> Self {
         Self {
             num,
@@ -37,6 +44,17 @@ impl UserSignal {
         }
     }
 
+    /// Translates the PID of the sender to the one in the PID namespace of
+    /// the receiver, i.e., `pid_ns`.
+    ///
+    /// The PID is zero if the sender is not visible in `pid_ns`.
+    pub fn for_receiver_in(self, pid_ns: &PidNamespace) -> Self {
+        Self {
+            pid: pid_ns.local_id_or_zero(self.pid),
+            ..self
+        }
+    }
+
     pub fn pid(&self) -> Pid {
         self.pid
     }
@@ -56,13 +74,17 @@ impl Signal for UserSignal {
             UserSignalKind::Kill => SI_USER,
             UserSignalKind::Tkill => SI_TKILL,
             UserSignalKind::Sigqueue => SI_QUEUE,
+            UserSignalKind::MessageQueue(_) => SI_MESGQ,
         };
 
-        siginfo_t::new(self.num, code)
-        // info.set_si_pid(self.pid);
-        // info.set_si_uid(self.uid);
+        let mut info = siginfo_t::new(self.num, code);
+        info.set_si_pid_uid(self.pid, self.uid);
+        if let UserSignalKind::MessageQueue(value) = self.kind {
+            info.set_si_value(value);
+        }
         // if let UserSignalKind::Sigqueue(val) = self.kind {
         //     info.set_si_value(val);
         // }
+        info
     }
 }
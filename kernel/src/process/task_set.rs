@@ -4,6 +4,7 @@
 
 use ostd::task::{CurrentTask, Task};
 
+use super::coredump::CoreState;
 use crate::prelude::*;
 
 /// A task set that maintains all tasks in a POSIX process.
@@ -11,6 +12,8 @@ pub struct TaskSet {
     tasks: Vec<Arc<Task>>,
     has_exited_main: bool,
     has_exited_group: bool,
+    /// The state of the core dump in progress.
+    core_state: Option<Arc<CoreState>>,
 }
 
 impl TaskSet {
@@ -20,14 +23,16 @@ impl TaskSet {
             tasks: Vec::new(),
             has_exited_main: false,
             has_exited_group: false,
+            core_state: None,
         }
     }
 
     /// Inserts a new task to the task set.
     ///
-    /// This method will fail if [`Self::set_exited_group`] has been called before.
+    /// This method will fail if [`Self::set_exited_group`] has been called before, or if the core
+    /// is being dumped.
     pub(super) fn insert(&mut self, task: Arc<Task>) -> core::result::Result<(), Arc<Task>> {
-        if self.has_exited_group {
+        if self.has_exited_group || self.core_state.is_some() {
             return Err(task);
         }
 
@@ -71,6 +76,16 @@ impl TaskSet {
     pub(super) fn has_exited_group(&self) -> bool {
         self.has_exited_group
     }
+
+    /// Sets the state of the core dump in progress.
+    pub(super) fn set_core_state(&mut self, core_state: Option<Arc<CoreState>>) {
+        self.core_state = core_state;
+    }
+
+    /// Returns the state of the core dump in progress.
+    pub(super) fn core_state(&self) -> Option<&Arc<CoreState>> {
+        self.core_state.as_ref()
+    }
 }
 
 impl TaskSet {
@@ -6,10 +6,12 @@ use super::{process_filter::ProcessFilter, signal::constants::SIGCHLD, ExitCode,
 use crate::{
     prelude::*,
     process::{
+        namespace::PidNamespace,
         posix_thread::{thread_table, AsPosixThread},
         process_table,
         signal::with_signal_blocked,
     },
+    thread::{Thread, Tid},
 };
 
 // The definition of WaitOptions is from Occlum
@@ -34,27 +36,87 @@ impl WaitOptions {
     }
 }
 
+/// A state change of a child or a tracee reported by `wait4` or `waitid`.
+pub enum WaitStatus {
+    /// The child process has exited and become a zombie.
+    Zombie(Arc<Process>),
+    /// The tracee has entered a ptrace-stop.
+    PtraceStopped { tid: Tid, status: u32 },
+    /// The tracee, which is not a child reported as a zombie, has exited.
+    TraceeExited { tid: Tid, status: u32 },
+}
+
+impl WaitStatus {
+    /// Returns the PID of the child or the TID of the tracee.
+    pub fn pid(&self) -> Pid {
+        match self {
+            WaitStatus::Zombie(process) => process.pid(),
+            WaitStatus::PtraceStopped { tid, .. } | WaitStatus::TraceeExited { tid, .. } => *tid,
+        }
+    }
+
+    /// Returns the status encoded as specified in the wait(2) man page.
+    pub fn status(&self) -> u32 {
+        match self {
+            WaitStatus::Zombie(process) => process.status().exit_code(),
+            WaitStatus::PtraceStopped { status, .. } | WaitStatus::TraceeExited { status, .. } => {
+                *status
+            }
+        }
+    }
+}
+
 pub fn wait_child_exit(
     child_filter: ProcessFilter,
     wait_options: WaitOptions,
     ctx: &Context,
-) -> Result<Option<Arc<Process>>> {
+) -> Result<Option<WaitStatus>> {
     let current = ctx.process;
-    let zombie_child = with_signal_blocked(ctx, SIGCHLD.into(), || {
+    // The IDs in `child_filter` are the ones in the PID namespace of the current process.
+    let pid_ns = current.pid_ns();
+    let consume = !wait_options.contains(WaitOptions::WNOWAIT);
+    let wait_status = with_signal_blocked(ctx, SIGCHLD.into(), || {
         current.children_wait_queue().pause_until(|| {
+            // Ptrace-stops are reported regardless of `WSTOPPED`.
+            let (has_tracees, tracee_status) = {
+                let tracees = current.tracees().lock();
+                let mut matched_tracees = tracees
+                    .iter()
+                    .filter(|(tid, thread)| tracee_matches(child_filter, pid_ns, **tid, thread))
+                    .peekable();
+                let has_tracees = matched_tracees.peek().is_some();
+                let tracee_status = matched_tracees.find_map(|(tid, thread)| {
+                    let tracee = thread.as_posix_thread().unwrap().tracee();
+                    if let Some(status) = tracee.take_exit_status(consume) {
+                        return Some(WaitStatus::TraceeExited { tid: *tid, status });
+                    }
+                    let status = tracee.take_stop_status(consume)?;
+                    Some(WaitStatus::PtraceStopped { tid: *tid, status })
+                });
+                (has_tracees, tracee_status)
+            };
+
+            if let Some(tracee_status) = tracee_status {
+                // The exited tracee is forgotten once its exit has been reported.
+                if consume && let WaitStatus::TraceeExited { tid, .. } = &tracee_status {
+                    current.tracees().lock().remove(tid);
+                }
+                return Some(Ok(Some(tracee_status)));
+            }
+
             let unwaited_children = current
                 .children()
                 .lock()
                 .values()
                 .filter(|child| match child_filter {
                     ProcessFilter::Any => true,
-                    ProcessFilter::WithPid(pid) => child.pid() == pid,
-                    ProcessFilter::WithPgid(pgid) => child.pgid() == pgid,
+                    ProcessFilter::WithPid(pid) => pid_ns.local_id(child.pid()) == Some(pid),
+                    ProcessFilter::WithPgid(pgid) => pid_ns.local_id(child.pgid()) == Some(pgid),
                 })
                 .cloned()
                 .collect::<Vec<_>>();
 
-            if unwaited_children.is_empty() {
+            if unwaited_children.is_empty() && !has_tracees {
                 return Some(Err(Error::with_message(
                     Errno::ECHILD,
                     "the process has no child to wait",
@@ -70,10 +132,10 @@ pub fn wait_child_exit(
                 let zombie_pid = zombie_child.pid();
                 if wait_options.contains(WaitOptions::WNOWAIT) {
                     // does not reap child, directly return
-                    return Some(Ok(Some(zombie_child.clone())));
+                    return Some(Ok(Some(WaitStatus::Zombie(zombie_child.clone()))));
                 } else {
                     reap_zombie_child(current, zombie_pid);
-                    return Some(Ok(Some(zombie_child.clone())));
+                    return Some(Ok(Some(WaitStatus::Zombie(zombie_child.clone()))));
                 }
             }
 
@@ -86,7 +148,25 @@ pub fn wait_child_exit(
         })
     })??;
 
-    Ok(zombie_child)
+    Ok(wait_status)
+}
+
+fn tracee_matches(
+    child_filter: ProcessFilter,
+    pid_ns: &PidNamespace,
+    tid: Tid,
+    thread: &Thread,
+) -> bool {
+    match child_filter {
+        ProcessFilter::Any => true,
+        ProcessFilter::WithPid(pid) => pid_ns.local_id(tid) == Some(pid),
+        ProcessFilter::WithPgid(pgid) => thread
+            .as_posix_thread()
+            .unwrap()
+            .weak_process()
+            .upgrade()
+            .is_some_and(|process| pid_ns.local_id(process.pgid()) == Some(pgid)),
+    }
 }
 
 /// Free zombie child with pid, returns the exit code of child process.
@@ -124,5 +204,6 @@ fn reap_zombie_child(process: &Process, pid: Pid) -> ExitCode {
     }
 
     process_table_mut.remove(child_process.pid());
+    child_process.pid_ns().release_pids(child_process.pid());
     child_process.status().exit_code()
 }
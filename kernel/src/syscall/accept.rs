@@ -71,7 +71,7 @@ fn do_accept(
     }
 
     let fd = {
-        let mut file_table = ctx.posix_thread.file_table().lock();
+        let mut file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.insert(connected_socket, fd_flags)
     };
 
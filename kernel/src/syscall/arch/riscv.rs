@@ -47,21 +47,35 @@ use crate::syscall::{
     gettimeofday::sys_gettimeofday,
     getuid::sys_getuid,
     impl_syscall_nums_and_dispatch_fn,
+    inotify::{sys_inotify_add_watch, sys_inotify_init1, sys_inotify_rm_watch},
+    io_uring::{sys_io_uring_enter, sys_io_uring_register, sys_io_uring_setup},
     ioctl::sys_ioctl,
     kill::sys_kill,
     link::sys_linkat,
     listen::sys_listen,
     lseek::sys_lseek,
     madvise::sys_madvise,
+    memfd_create::sys_memfd_create,
     mkdir::sys_mkdirat,
     mknod::sys_mknodat,
+    mlock::{sys_mlock, sys_mlock2, sys_mlockall, sys_munlock, sys_munlockall},
     mmap::sys_mmap,
     mount::sys_mount,
     mprotect::sys_mprotect,
+    mqueue::{
+        sys_mq_getsetattr, sys_mq_notify, sys_mq_open, sys_mq_timedreceive, sys_mq_timedsend,
+        sys_mq_unlink,
+    },
+    mremap::sys_mremap,
+    msgctl::sys_msgctl,
+    msgget::sys_msgget,
+    msgrcv::sys_msgrcv,
+    msgsnd::sys_msgsnd,
     msync::sys_msync,
     munmap::sys_munmap,
     nanosleep::{sys_clock_nanosleep, sys_nanosleep},
     open::sys_openat,
+    pidfd::{sys_pidfd_open, sys_pidfd_send_signal},
     pipe::sys_pipe2,
     prctl::sys_prctl,
     pread64::sys_pread64,
@@ -94,7 +108,9 @@ use crate::syscall::{
     setfsuid::sys_setfsuid,
     setgid::sys_setgid,
     setgroups::sys_setgroups,
+    sethostname::{sys_setdomainname, sys_sethostname},
     setitimer::{sys_getitimer, sys_setitimer},
+    setns::sys_setns,
     setpgid::sys_setpgid,
     setregid::sys_setregid,
     setresgid::sys_setresgid,
@@ -103,29 +119,55 @@ use crate::syscall::{
     setsid::sys_setsid,
     setsockopt::sys_setsockopt,
     setuid::sys_setuid,
+    shmat::sys_shmat,
+    shmctl::sys_shmctl,
+    shmdt::sys_shmdt,
+    shmget::sys_shmget,
     shutdown::sys_shutdown,
     sigaltstack::sys_sigaltstack,
+    signalfd::sys_signalfd4,
     socket::sys_socket,
     socketpair::sys_socketpair,
     stat::{sys_fstat, sys_fstatat},
     statfs::{sys_fstatfs, sys_statfs},
+    swapon::{sys_swapoff, sys_swapon},
     symlink::sys_symlinkat,
     sync::sys_sync,
     tgkill::sys_tgkill,
     timer_create::{sys_timer_create, sys_timer_delete},
     timer_settime::{sys_timer_gettime, sys_timer_settime},
+    timerfd::{sys_timerfd_create, sys_timerfd_gettime, sys_timerfd_settime},
     truncate::{sys_ftruncate, sys_truncate},
     umask::sys_umask,
     umount::sys_umount,
     uname::sys_uname,
     unlink::sys_unlinkat,
+    unshare::sys_unshare,
+    userfaultfd::sys_userfaultfd,
     utimens::sys_utimensat,
     wait4::sys_wait4,
     waitid::sys_waitid,
     write::sys_write,
+    xattr::{
+        sys_fgetxattr, sys_flistxattr, sys_fremovexattr, sys_fsetxattr, sys_getxattr,
+        sys_lgetxattr, sys_listxattr, sys_llistxattr, sys_lremovexattr, sys_lsetxattr,
+        sys_removexattr, sys_setxattr,
+    },
 };
 
 impl_syscall_nums_and_dispatch_fn! {
+    SYS_SETXATTR = 5             => sys_setxattr(args[..5]);
+    SYS_LSETXATTR = 6            => sys_lsetxattr(args[..5]);
+    SYS_FSETXATTR = 7            => sys_fsetxattr(args[..5]);
+    SYS_GETXATTR = 8             => sys_getxattr(args[..4]);
+    SYS_LGETXATTR = 9            => sys_lgetxattr(args[..4]);
+    SYS_FGETXATTR = 10           => sys_fgetxattr(args[..4]);
+    SYS_LISTXATTR = 11           => sys_listxattr(args[..3]);
+    SYS_LLISTXATTR = 12          => sys_llistxattr(args[..3]);
+    SYS_FLISTXATTR = 13          => sys_flistxattr(args[..3]);
+    SYS_REMOVEXATTR = 14         => sys_removexattr(args[..2]);
+    SYS_LREMOVEXATTR = 15        => sys_lremovexattr(args[..2]);
+    SYS_FREMOVEXATTR = 16        => sys_fremovexattr(args[..2]);
     SYS_GETCWD = 17              => sys_getcwd(args[..2]);
     SYS_EVENTFD2 = 19            => sys_eventfd2(args[..2]);
     SYS_EPOLL_CREATE1 = 20       => sys_epoll_create1(args[..1]);
@@ -134,6 +176,9 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_DUP = 23                 => sys_dup(args[..1]);
     SYS_DUP3 = 24                => sys_dup3(args[..3]);
     SYS_FCNTL = 25               => sys_fcntl(args[..3]);
+    SYS_INOTIFY_INIT1 = 26       => sys_inotify_init1(args[..1]);
+    SYS_INOTIFY_ADD_WATCH = 27   => sys_inotify_add_watch(args[..3]);
+    SYS_INOTIFY_RM_WATCH = 28    => sys_inotify_rm_watch(args[..2]);
     SYS_IOCTL = 29               => sys_ioctl(args[..3]);
     SYS_FLOCK = 32               => sys_flock(args[..2]);
     SYS_MKNODAT = 33             => sys_mknodat(args[..4]);
@@ -172,18 +217,23 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_PWRITEV = 70             => sys_pwritev(args[..4]);
     SYS_SENDFILE64 = 71          => sys_sendfile(args[..4]);
     SYS_PSELECT6 = 72            => sys_pselect6(args[..6]);
+    SYS_SIGNALFD4 = 74           => sys_signalfd4(args[..4]);
     SYS_READLINKAT = 78          => sys_readlinkat(args[..4]);
     SYS_NEWFSTATAT = 79          => sys_fstatat(args[..4]);
     SYS_NEWFSTAT = 80            => sys_fstat(args[..2]);
     SYS_SYNC = 81                => sys_sync(args[..0]);
     SYS_FSYNC = 82               => sys_fsync(args[..1]);
     SYS_FDATASYNC = 83           => sys_fdatasync(args[..1]);
+    SYS_TIMERFD_CREATE = 85      => sys_timerfd_create(args[..2]);
+    SYS_TIMERFD_SETTIME = 86     => sys_timerfd_settime(args[..4]);
+    SYS_TIMERFD_GETTIME = 87     => sys_timerfd_gettime(args[..2]);
     SYS_CAPGET = 90              => sys_capget(args[..2]);
     SYS_CAPSET = 91              => sys_capset(args[..2]);
     SYS_EXIT = 93                => sys_exit(args[..1]);
     SYS_EXIT_GROUP = 94          => sys_exit_group(args[..1]);
     SYS_WAITID = 95              => sys_waitid(args[..5]);
     SYS_SET_TID_ADDRESS = 96     => sys_set_tid_address(args[..1]);
+    SYS_UNSHARE = 97             => sys_unshare(args[..1]);
     SYS_FUTEX = 98               => sys_futex(args[..6]);
     SYS_SET_ROBUST_LIST = 99     => sys_set_robust_list(args[..2]);
     SYS_NANOSLEEP = 101          => sys_nanosleep(args[..2]);
@@ -220,6 +270,8 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_GETGROUPS = 158          => sys_getgroups(args[..2]);
     SYS_SETGROUPS = 159          => sys_setgroups(args[..2]);
     SYS_NEWUNAME = 160           => sys_uname(args[..1]);
+    SYS_SETHOSTNAME = 161        => sys_sethostname(args[..2]);
+    SYS_SETDOMAINNAME = 162      => sys_setdomainname(args[..2]);
     SYS_GETRLIMIT = 163          => sys_getrlimit(args[..2]);
     SYS_SETRLIMIT = 164          => sys_setrlimit(args[..2]);
     SYS_GETRUSAGE = 165          => sys_getrusage(args[..2]);
@@ -233,9 +285,23 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_GETGID = 176             => sys_getgid(args[..0]);
     SYS_GETEGID = 177            => sys_getegid(args[..0]);
     SYS_GETTID = 178             => sys_gettid(args[..0]);
+    SYS_MQ_OPEN = 180            => sys_mq_open(args[..4]);
+    SYS_MQ_UNLINK = 181          => sys_mq_unlink(args[..1]);
+    SYS_MQ_TIMEDSEND = 182       => sys_mq_timedsend(args[..5]);
+    SYS_MQ_TIMEDRECEIVE = 183    => sys_mq_timedreceive(args[..5]);
+    SYS_MQ_NOTIFY = 184          => sys_mq_notify(args[..2]);
+    SYS_MQ_GETSETATTR = 185      => sys_mq_getsetattr(args[..3]);
+    SYS_MSGGET = 186             => sys_msgget(args[..2]);
+    SYS_MSGCTL = 187             => sys_msgctl(args[..3]);
+    SYS_MSGRCV = 188             => sys_msgrcv(args[..5]);
+    SYS_MSGSND = 189             => sys_msgsnd(args[..4]);
     SYS_SEMGET = 190             => sys_semget(args[..3]);
     SYS_SEMCTL = 191             => sys_semctl(args[..4]);
     SYS_SEMOP = 193              => sys_semop(args[..3]);
+    SYS_SHMGET = 194             => sys_shmget(args[..3]);
+    SYS_SHMCTL = 195             => sys_shmctl(args[..3]);
+    SYS_SHMAT = 196              => sys_shmat(args[..3]);
+    SYS_SHMDT = 197              => sys_shmdt(args[..1]);
     SYS_SOCKET = 198             => sys_socket(args[..3]);
     SYS_SOCKETPAIR = 199         => sys_socketpair(args[..4]);
     SYS_BIND = 200               => sys_bind(args[..3]);
@@ -253,17 +319,28 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_RECVMSG = 212            => sys_recvmsg(args[..3]);
     SYS_BRK = 214                => sys_brk(args[..1]);
     SYS_MUNMAP = 215             => sys_munmap(args[..2]);
+    SYS_MREMAP = 216             => sys_mremap(args[..5]);
     SYS_CLONE = 220              => sys_clone(args[..5], &user_ctx);
     SYS_EXECVE = 221             => sys_execve(args[..3], &mut user_ctx);
     SYS_MMAP = 222               => sys_mmap(args[..6]);
+    SYS_SWAPON = 224             => sys_swapon(args[..2]);
+    SYS_SWAPOFF = 225            => sys_swapoff(args[..1]);
     SYS_MPROTECT = 226           => sys_mprotect(args[..3]);
     SYS_MSYNC = 227              => sys_msync(args[..3]);
+    SYS_MLOCK = 228              => sys_mlock(args[..2]);
+    SYS_MUNLOCK = 229            => sys_munlock(args[..2]);
+    SYS_MLOCKALL = 230           => sys_mlockall(args[..1]);
+    SYS_MUNLOCKALL = 231         => sys_munlockall(args[..0]);
     SYS_MADVISE = 233            => sys_madvise(args[..3]);
     SYS_ACCEPT4 = 242            => sys_accept4(args[..4]);
     SYS_WAIT4 = 260              => sys_wait4(args[..4]);
     SYS_PRLIMIT64 = 261          => sys_prlimit64(args[..4]);
+    SYS_SETNS = 268              => sys_setns(args[..2]);
     SYS_GETRANDOM = 278          => sys_getrandom(args[..3]);
+    SYS_MEMFD_CREATE = 279       => sys_memfd_create(args[..2]);
     SYS_EXECVEAT = 281           => sys_execveat(args[..5], &mut user_ctx);
+    SYS_USERFAULTFD = 282        => sys_userfaultfd(args[..1]);
+    SYS_MLOCK2 = 284             => sys_mlock2(args[..3]);
     SYS_PREADV2 = 286            => sys_preadv2(args[..5]);
     SYS_PWRITEV2 = 287           => sys_pwritev2(args[..5]);
     SYS_PRLIMIT64 = 302          => sys_prlimit64(args[..4]);
@@ -273,5 +350,10 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_TIMER_SETTIME = 409      => sys_timer_settime(args[..4]);
     SYS_UTIMENSAT = 412          => sys_utimensat(args[..4]);
     SYS_SEMTIMEDOP = 420         => sys_semtimedop(args[..4]);
+    SYS_PIDFD_SEND_SIGNAL = 424  => sys_pidfd_send_signal(args[..4]);
+    SYS_IO_URING_SETUP = 425     => sys_io_uring_setup(args[..2]);
+    SYS_IO_URING_ENTER = 426     => sys_io_uring_enter(args[..6]);
+    SYS_IO_URING_REGISTER = 427  => sys_io_uring_register(args[..4]);
+    SYS_PIDFD_OPEN = 434         => sys_pidfd_open(args[..2]);
     SYS_CLONE3 = 435             => sys_clone3(args[..2], &user_ctx);
 }
@@ -11,7 +11,8 @@ pub fn sys_chdir(path_ptr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
     let path = ctx.user_space().read_cstring(path_ptr, MAX_FILENAME_LEN)?;
     debug!("path = {:?}", path);
 
-    let mut fs = ctx.posix_thread.fs().resolver().write();
+    let fs_ref = ctx.posix_thread.fs();
+    let mut fs = fs_ref.resolver().write();
     let dentry = {
         let path = path.to_string_lossy();
         if path.is_empty() {
@@ -31,7 +32,7 @@ pub fn sys_fchdir(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
     debug!("fd = {}", fd);
 
     let dentry = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         let file = file_table.get_file(fd)?;
         let inode_handle = file
             .downcast_ref::<InodeHandle>()
@@ -14,7 +14,7 @@ pub fn sys_fchmod(fd: FileDesc, mode: u16, ctx: &Context) -> Result<SyscallRetur
     debug!("fd = {}, mode = 0o{:o}", fd, mode);
 
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     file.set_mode(InodeMode::from_bits_truncate(mode))?;
@@ -21,7 +21,7 @@ pub fn sys_fchown(fd: FileDesc, uid: i32, gid: i32, ctx: &Context) -> Result<Sys
     }
 
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     if let Some(uid) = uid {
@@ -80,7 +80,8 @@ pub fn sys_fchownat(
     let dentry = {
         let path = path.to_string_lossy();
         let fs_path = FsPath::new(dirfd, path.as_ref())?;
-        let fs = ctx.posix_thread.fs().resolver().read();
+        let fs_ref = ctx.posix_thread.fs();
+        let fs = fs_ref.resolver().read();
         if flags.contains(ChownFlags::AT_SYMLINK_NOFOLLOW) {
             fs.lookup_no_follow(&fs_path)?
         } else {
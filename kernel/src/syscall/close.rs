@@ -7,7 +7,7 @@ pub fn sys_close(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
     debug!("fd = {}", fd);
 
     let file = {
-        let mut file_table = ctx.posix_thread.file_table().lock();
+        let mut file_table = ctx.posix_thread.file_table().lock_arc();
         let _ = file_table.get_file(fd)?;
         file_table.close_file(fd).unwrap()
     };
@@ -41,7 +41,7 @@ pub fn sys_epoll_create1(flags: u32, ctx: &Context) -> Result<SyscallReturn> {
     };
 
     let epoll_file: Arc<EpollFile> = EpollFile::new();
-    let mut file_table = ctx.posix_thread.file_table().lock();
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
     let fd = file_table.insert(epoll_file, fd_flags);
     Ok(SyscallReturn::Return(fd as _))
 }
@@ -80,7 +80,7 @@ pub fn sys_epoll_ctl(
     };
 
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(epfd)?.clone()
     };
     let epoll_file = file
@@ -110,7 +110,7 @@ fn do_epoll_wait(
     };
 
     let epoll_file_arc = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(epfd)?.clone()
     };
     let epoll_file = epoll_file_arc
@@ -157,7 +157,7 @@ pub fn sys_epoll_wait(
     Ok(SyscallReturn::Return(epoll_events.len() as _))
 }
 
-fn set_signal_mask(set_ptr: Vaddr, ctx: &Context) -> Result<SigMask> {
+pub(super) fn set_signal_mask(set_ptr: Vaddr, ctx: &Context) -> Result<SigMask> {
     let new_mask: Option<SigMask> = if set_ptr != 0 {
         Some(ctx.user_space().read_val::<u64>(set_ptr)?.into())
     } else {
@@ -175,7 +175,7 @@ fn set_signal_mask(set_ptr: Vaddr, ctx: &Context) -> Result<SigMask> {
     Ok(old_sig_mask_value)
 }
 
-fn restore_signal_mask(sig_mask_val: SigMask, ctx: &Context) {
+pub(super) fn restore_signal_mask(sig_mask_val: SigMask, ctx: &Context) {
     ctx.posix_thread
         .sig_mask()
         .store(sig_mask_val, Ordering::Relaxed);
@@ -62,7 +62,8 @@ fn lookup_executable_file(
     flags: OpenFlags,
     ctx: &Context,
 ) -> Result<Dentry> {
-    let fs_resolver = ctx.posix_thread.fs().resolver().read();
+    let fs_ref = ctx.posix_thread.fs();
+    let fs_resolver = fs_ref.resolver().read();
     let dentry = if flags.contains(OpenFlags::AT_EMPTY_PATH) && filename.is_empty() {
         fs_resolver.lookup_from_fd(dfd)
     } else {
@@ -116,7 +117,8 @@ fn do_execve(
 
     debug!("load program to root vmar");
     let (new_executable_path, elf_load_info) = {
-        let fs_resolver = &*posix_thread.fs().resolver().read();
+        let fs_ref = posix_thread.fs();
+        let fs_resolver = &*fs_ref.resolver().read();
         let process_vm = process.vm();
         load_program_to_vm(process_vm, elf_file.clone(), argv, envp, fs_resolver, 1)?
     };
@@ -126,6 +128,9 @@ fn do_execve(
     *thread_local.robust_list().borrow_mut() = None;
     debug!("load elf in execve succeeds");
 
+    // The process becomes dumpable again, unless the credentials are changed below.
+    process.set_dumpable(true);
+
     let credentials = posix_thread.credentials_mut();
     set_uid_from_elf(process, &credentials, &elf_file)?;
     set_gid_from_elf(process, &credentials, &elf_file)?;
@@ -150,6 +155,8 @@ fn do_execve(
     // set new user stack top
     user_context.set_stack_pointer(elf_load_info.user_stack_top() as _);
     debug!("user stack top: 0x{:x}", elf_load_info.user_stack_top());
+    // notify the tracer, if any
+    posix_thread.ptrace_report_exec(posix_thread.tid());
     Ok(())
 }
 
@@ -209,6 +216,7 @@ fn set_uid_from_elf(
         credentials.set_euid(uid);
 
         current.clear_parent_death_signal();
+        current.set_dumpable(false);
     }
 
     // No matter whether the elf_file has `set_uid` bit, suid should be reset.
@@ -227,6 +235,7 @@ fn set_gid_from_elf(
         credentials.set_egid(gid);
 
         current.clear_parent_death_signal();
+        current.set_dumpable(false);
     }
 
     // No matter whether the the elf file has `set_gid` bit, sgid should be reset.
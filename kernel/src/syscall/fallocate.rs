@@ -22,7 +22,7 @@ pub fn sys_fallocate(
     check_offset_and_len(offset, len, ctx)?;
 
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
 
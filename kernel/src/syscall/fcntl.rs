@@ -7,11 +7,12 @@ use crate::{
         file_table::{FdFlags, FileDesc},
         inode_handle::InodeHandle,
         utils::{
-            FileRange, RangeLockItem, RangeLockItemBuilder, RangeLockType, StatusFlags, OFFSET_MAX,
+            FileRange, FileSeals, RangeLockItem, RangeLockItemBuilder, RangeLockType, StatusFlags,
+            OFFSET_MAX,
         },
     },
     prelude::*,
-    process::{process_table, Pid},
+    process::Pid,
 };
 
 pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64, ctx: &Context) -> Result<SyscallReturn> {
@@ -32,17 +33,19 @@ pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64, ctx: &Context) -> Result<Sysc
         }),
         FcntlCmd::F_GETOWN => handle_getown(fd, ctx),
         FcntlCmd::F_SETOWN => handle_setown(fd, arg, ctx),
+        FcntlCmd::F_ADD_SEALS => handle_addseals(fd, arg, ctx),
+        FcntlCmd::F_GET_SEALS => handle_getseals(fd, ctx),
     }
 }
 
 fn handle_dupfd(fd: FileDesc, arg: u64, flags: FdFlags, ctx: &Context) -> Result<SyscallReturn> {
-    let mut file_table = ctx.posix_thread.file_table().lock();
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
     let new_fd = file_table.dup(fd, arg as FileDesc, flags)?;
     Ok(SyscallReturn::Return(new_fd as _))
 }
 
 fn handle_getfd(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
-    let file_table = ctx.posix_thread.file_table().lock();
+    let file_table = ctx.posix_thread.file_table().lock_arc();
     let entry = file_table.get_entry(fd)?;
     let fd_flags = entry.flags();
     Ok(SyscallReturn::Return(fd_flags.bits() as _))
@@ -54,7 +57,7 @@ fn handle_setfd(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
     } else {
         FdFlags::from_bits(arg as u8).ok_or(Error::with_message(Errno::EINVAL, "invalid flags"))?
     };
-    let file_table = ctx.posix_thread.file_table().lock();
+    let file_table = ctx.posix_thread.file_table().lock_arc();
     let entry = file_table.get_entry(fd)?;
     entry.set_flags(flags);
     Ok(SyscallReturn::Return(0))
@@ -62,7 +65,7 @@ fn handle_setfd(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
 
 fn handle_getfl(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     let status_flags = file.status_flags();
@@ -74,7 +77,7 @@ fn handle_getfl(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
 
 fn handle_setfl(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn> {
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     let valid_flags_mask = StatusFlags::O_APPEND
@@ -91,7 +94,7 @@ fn handle_setfl(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
 
 fn handle_getlk(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn> {
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     let lock_mut_ptr = arg as Vaddr;
@@ -120,7 +123,7 @@ fn handle_setlk(
     ctx: &Context,
 ) -> Result<SyscallReturn> {
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     let lock_mut_ptr = arg as Vaddr;
@@ -138,7 +141,7 @@ fn handle_setlk(
 }
 
 fn handle_getown(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
-    let file_table = ctx.posix_thread.file_table().lock();
+    let file_table = ctx.posix_thread.file_table().lock_arc();
     let file_entry = file_table.get_entry(fd)?;
     let pid = file_entry.owner().unwrap_or(0);
     Ok(SyscallReturn::Return(pid as _))
@@ -156,18 +159,55 @@ fn handle_setown(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
     let owner_process = if pid == 0 {
         None
     } else {
-        Some(process_table::get_process(pid).ok_or(Error::with_message(
-            Errno::ESRCH,
-            "cannot set_owner with an invalid pid",
-        ))?)
+        Some(
+            ctx.process
+                .pid_ns()
+                .get_process(pid)
+                .ok_or(Error::with_message(
+                    Errno::ESRCH,
+                    "cannot set_owner with an invalid pid",
+                ))?,
+        )
     };
 
-    let mut file_table = ctx.posix_thread.file_table().lock();
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
     let file_entry = file_table.get_entry_mut(fd)?;
     file_entry.set_owner(owner_process.as_ref())?;
     Ok(SyscallReturn::Return(0))
 }
 
+fn handle_addseals(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn> {
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(fd)?.clone()
+    };
+    let seals = u32::try_from(arg)
+        .ok()
+        .and_then(FileSeals::from_bits)
+        .ok_or(Error::with_message(Errno::EINVAL, "invalid seals"))?;
+
+    let inode_file = file
+        .downcast_ref::<InodeHandle>()
+        .ok_or(Error::with_message(Errno::EINVAL, "not inode"))?;
+    if !inode_file.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EPERM, "the file is not opened for writing");
+    }
+    inode_file.dentry().inode().add_seals(seals)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+fn handle_getseals(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(fd)?.clone()
+    };
+    let inode_file = file
+        .downcast_ref::<InodeHandle>()
+        .ok_or(Error::with_message(Errno::EINVAL, "not inode"))?;
+    let seals = inode_file.dentry().inode().seals()?;
+    Ok(SyscallReturn::Return(seals.bits() as _))
+}
+
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, TryFromInt)]
 #[allow(non_camel_case_types)]
@@ -183,6 +223,8 @@ enum FcntlCmd {
     F_SETOWN = 8,
     F_GETOWN = 9,
     F_DUPFD_CLOEXEC = 1030,
+    F_ADD_SEALS = 1033,
+    F_GET_SEALS = 1034,
 }
 
 #[allow(non_camel_case_types)]
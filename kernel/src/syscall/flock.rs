@@ -15,7 +15,7 @@ pub fn sys_flock(fd: FileDesc, ops: i32, ctx: &Context) -> Result<SyscallReturn>
 
     let file = {
         let current = ctx.posix_thread;
-        let file_table = current.file_table().lock();
+        let file_table = current.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     let inode_file = file
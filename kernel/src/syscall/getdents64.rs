@@ -24,7 +24,7 @@ pub fn sys_getdents(
     );
 
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     let inode_handle = file
@@ -54,7 +54,7 @@ pub fn sys_getdents64(
     );
 
     let file = {
-        let file_table = ctx.posix_thread.file_table().lock();
+        let file_table = ctx.posix_thread.file_table().lock_arc();
         file_table.get_file(fd)?.clone()
     };
     let inode_handle = file
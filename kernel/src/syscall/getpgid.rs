@@ -1,10 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
-use crate::{
-    prelude::*,
-    process::{process_table, Pid},
-};
+use crate::{prelude::*, process::Pid};
 
 pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     debug!("pid = {}", pid);
@@ -14,11 +11,14 @@ pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // }
 
     // if pid is 0, should return the pgid of current process
+    let pid_ns = ctx.process.pid_ns();
     if pid == 0 {
-        return Ok(SyscallReturn::Return(ctx.process.pgid() as _));
+        let pgid = pid_ns.local_id_or_zero(ctx.process.pgid());
+        return Ok(SyscallReturn::Return(pgid as _));
     }
 
-    let process = process_table::get_process(pid)
+    let process = pid_ns
+        .get_process(pid)
         .ok_or(Error::with_message(Errno::ESRCH, "process does not exist"))?;
 
     if !Arc::ptr_eq(&ctx.process.session().unwrap(), &process.session().unwrap()) {
@@ -28,5 +28,6 @@ pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
         );
     }
 
-    Ok(SyscallReturn::Return(process.pgid() as _))
+    let pgid = pid_ns.local_id_or_zero(process.pgid());
+    Ok(SyscallReturn::Return(pgid as _))
 }
@@ -4,5 +4,11 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_getppid(ctx: &Context) -> Result<SyscallReturn> {
-    Ok(SyscallReturn::Return(ctx.process.parent().pid() as _))
+    // The parent of the init process of a PID namespace is not visible in the
+    // namespace, so zero is returned.
+    let ppid = ctx
+        .process
+        .pid_ns()
+        .local_id_or_zero(ctx.process.parent().pid());
+    Ok(SyscallReturn::Return(ppid as _))
 }
@@ -1,22 +1,19 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
-use crate::{
-    prelude::*,
-    process::{process_table, Pid},
-};
+use crate::{prelude::*, process::Pid};
 
 pub fn sys_getsid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     debug!("pid = {}", pid);
 
     let session = ctx.process.session().unwrap();
-    let sid = session.sid();
+    let sid = ctx.process.pid_ns().local_id_or_zero(session.sid());
 
     if pid == 0 {
         return Ok(SyscallReturn::Return(sid as _));
     }
 
-    let Some(process) = process_table::get_process(pid) else {
+    let Some(process) = ctx.process.pid_ns().get_process(pid) else {
         return_errno_with_message!(Errno::ESRCH, "the process does not exist")
     };
 
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::{FdFlags, FileDesc},
+        fs_resolver::{FsPath, AT_FDCWD},
+        notify::{FsEvents, InotifyFile, InotifyFlags, WatchFlags},
+        utils::{Permission, PATH_MAX},
+    },
+    prelude::*,
+};
+
+pub fn sys_inotify_init(ctx: &Context) -> Result<SyscallReturn> {
+    sys_inotify_init1(0, ctx)
+}
+
+pub fn sys_inotify_init1(flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("flags = 0x{:x}", flags);
+
+    let flags = InotifyFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+
+    let fd_flags = if flags.contains(InotifyFlags::IN_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+
+    let inotify_file = InotifyFile::new(flags.contains(InotifyFlags::IN_NONBLOCK));
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
+    let fd = file_table.insert(inotify_file, fd_flags);
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+pub fn sys_inotify_add_watch(
+    fd: FileDesc,
+    path_addr: Vaddr,
+    mask: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let path = ctx.user_space().read_cstring(path_addr, PATH_MAX)?;
+    debug!("fd = {}, path = {:?}, mask = 0x{:x}", fd, path, mask);
+
+    let events = FsEvents::from_bits_truncate(mask);
+    let flags = WatchFlags::from_bits_truncate(mask);
+
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(fd)?.clone()
+    };
+    let inotify_file = file
+        .downcast_ref::<InotifyFile>()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "not inotify file"))?;
+
+    let dentry = {
+        let path = path.to_string_lossy();
+        let fs_path = FsPath::new(AT_FDCWD, path.as_ref())?;
+        let fs_ref = ctx.posix_thread.fs();
+        let fs = fs_ref.resolver().read();
+        if flags.contains(WatchFlags::DONT_FOLLOW) {
+            fs.lookup_no_follow(&fs_path)?
+        } else {
+            fs.lookup(&fs_path)?
+        }
+    };
+    dentry.inode().check_permission(Permission::MAY_READ)?;
+
+    let wd = inotify_file.add_watch(dentry.inode().clone(), events, flags)?;
+    Ok(SyscallReturn::Return(wd as _))
+}
+
+pub fn sys_inotify_rm_watch(fd: FileDesc, wd: i32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("fd = {}, wd = {}", fd, wd);
+
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(fd)?.clone()
+    };
+    let inotify_file = file
+        .downcast_ref::<InotifyFile>()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "not inotify file"))?;
+
+    inotify_file.remove_watch(wd)?;
+    Ok(SyscallReturn::Return(0))
+}
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{
+    epoll::{restore_signal_mask, set_signal_mask},
+    SyscallReturn,
+};
+use crate::{
+    fs::{
+        file_table::{FdFlags, FileDesc},
+        io_uring::{CIoUringParams, EnterFlags, IoUring, RegisterOp},
+    },
+    prelude::*,
+};
+
+pub fn sys_io_uring_setup(
+    entries: u32,
+    params_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let user_space = ctx.user_space();
+    let mut params = user_space.read_val::<CIoUringParams>(params_addr)?;
+    debug!("entries = {}, params = {:?}", entries, params);
+
+    let io_uring = IoUring::new(entries, &mut params, ctx)?;
+    user_space.write_val(params_addr, &params)?;
+
+    let fd = {
+        let mut file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.insert(Arc::new(io_uring), FdFlags::CLOEXEC)
+    };
+
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+pub fn sys_io_uring_enter(
+    fd: FileDesc,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    sigmask: Vaddr,
+    sigset_size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = EnterFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
+    debug!(
+        "fd = {}, to_submit = {}, min_complete = {}, flags = {:?}, sigmask = 0x{:x}, sigset_size = {}",
+        fd, to_submit, min_complete, flags, sigmask, sigset_size
+    );
+
+    if sigmask != 0 && sigset_size != 8 {
+        return_errno_with_message!(Errno::EINVAL, "sigset size is not equal to 8");
+    }
+
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(fd)?.clone()
+    };
+    let io_uring = file
+        .downcast_ref::<IoUring>()
+        .ok_or(Error::with_message(Errno::EOPNOTSUPP, "not io_uring file"))?;
+
+    // The signal mask is only used when waiting for the completions.
+    let old_sig_mask_value = if flags.contains(EnterFlags::GETEVENTS) {
+        Some(set_signal_mask(sigmask, ctx)?)
+    } else {
+        None
+    };
+
+    let res = io_uring.enter(to_submit, min_complete, flags, ctx);
+
+    if let Some(old_sig_mask_value) = old_sig_mask_value {
+        restore_signal_mask(old_sig_mask_value, ctx);
+    }
+
+    Ok(SyscallReturn::Return(res? as _))
+}
+
+pub fn sys_io_uring_register(
+    fd: FileDesc,
+    opcode: u32,
+    arg: Vaddr,
+    nr_args: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let op = RegisterOp::try_from(opcode)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "unknown opcode"))?;
+    debug!(
+        "fd = {}, op = {:?}, arg = 0x{:x}, nr_args = {}",
+        fd, op, arg, nr_args
+    );
+
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(fd)?.clone()
+    };
+    let io_uring = file
+        .downcast_ref::<IoUring>()
+        .ok_or(Error::with_message(Errno::EOPNOTSUPP, "not io_uring file"))?;
+
+    let res = io_uring.register(op, arg, nr_args, ctx)?;
+
+    Ok(SyscallReturn::Return(res as _))
+}
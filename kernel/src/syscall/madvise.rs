@@ -3,7 +3,7 @@
 use align_ext::AlignExt;
 
 use super::SyscallReturn;
-use crate::prelude::*;
+use crate::{prelude::*, vm::thp::ThpAdvice};
 
 pub fn sys_madvise(
     start: Vaddr,
@@ -32,32 +32,45 @@ pub fn sys_madvise(
         Errno::EINVAL,
         "integer overflow when (start + len)",
     ))?;
+    let range = start..end;
+    let root_vmar = ctx.process.root_vmar();
     match behavior {
         MadviseBehavior::MADV_NORMAL
-        | MadviseBehavior::MADV_SEQUENTIAL
-        | MadviseBehavior::MADV_WILLNEED => {
-            // perform a read at first
-            let mut buffer = vec![0u8; len];
-            ctx.user_space()
-                .read_bytes(start, &mut VmWriter::from(buffer.as_mut_slice()))?;
+        | MadviseBehavior::MADV_RANDOM
+        | MadviseBehavior::MADV_SEQUENTIAL => {
+            // The hints on the access patterns are ignored.
+            root_vmar.check_mapped(range)?;
+        }
+        MadviseBehavior::MADV_WILLNEED => root_vmar.prefetch(range)?,
+        MadviseBehavior::MADV_DONTNEED => root_vmar.discard_pages(range, false)?,
+        MadviseBehavior::MADV_DONTNEED_LOCKED => root_vmar.discard_pages(range, true)?,
+        MadviseBehavior::MADV_FREE => root_vmar.free_pages(range)?,
+        MadviseBehavior::MADV_REMOVE => root_vmar.punch_hole(range)?,
+        MadviseBehavior::MADV_DONTFORK => root_vmar.set_dont_fork(range, true)?,
+        MadviseBehavior::MADV_DOFORK => root_vmar.set_dont_fork(range, false)?,
+        MadviseBehavior::MADV_WIPEONFORK => root_vmar.set_wipe_on_fork(range, true)?,
+        MadviseBehavior::MADV_KEEPONFORK => root_vmar.set_wipe_on_fork(range, false)?,
+        MadviseBehavior::MADV_DONTDUMP => root_vmar.set_dont_dump(range, true)?,
+        MadviseBehavior::MADV_DODUMP => root_vmar.set_dont_dump(range, false)?,
+        MadviseBehavior::MADV_HUGEPAGE => root_vmar.set_thp_advice(range, ThpAdvice::HugePage)?,
+        MadviseBehavior::MADV_NOHUGEPAGE => {
+            root_vmar.set_thp_advice(range, ThpAdvice::NoHugePage)?
         }
-        MadviseBehavior::MADV_DONTNEED => {
-            warn!("MADV_DONTNEED isn't implemented, do nothing for now.");
+        MadviseBehavior::MADV_COLD => root_vmar.deactivate_pages(range)?,
+        MadviseBehavior::MADV_PAGEOUT => root_vmar.page_out(range)?,
+        MadviseBehavior::MADV_POPULATE_READ => root_vmar.prefault(range, false)?,
+        MadviseBehavior::MADV_POPULATE_WRITE => root_vmar.prefault(range, true)?,
+        MadviseBehavior::MADV_MERGEABLE
+        | MadviseBehavior::MADV_UNMERGEABLE
+        | MadviseBehavior::MADV_HWPOISON
+        | MadviseBehavior::MADV_SOFT_OFFLINE => {
+            // Like Linux built without KSM and memory failure support.
+            return_errno_with_message!(Errno::EINVAL, "the advice is not supported");
         }
-        MadviseBehavior::MADV_FREE => madv_free(start, end, ctx)?,
-        _ => todo!(),
     }
     Ok(SyscallReturn::Return(0))
 }
 
-fn madv_free(start: Vaddr, end: Vaddr, ctx: &Context) -> Result<()> {
-    let root_vmar = ctx.process.root_vmar();
-    let advised_range = start..end;
-    let _ = root_vmar.remove_mapping(advised_range);
-
-    Ok(())
-}
-
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, TryFromInt)]
 #[allow(non_camel_case_types)]
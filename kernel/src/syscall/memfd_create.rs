@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::FdFlags,
+        inode_handle::InodeHandle,
+        ramfs::new_memfd,
+        utils::{AccessMode, StatusFlags},
+    },
+    prelude::*,
+    syscall::constants::MAX_FILENAME_LEN,
+};
+
+/// The maximum length of the name, excluding the "memfd:" prefix.
+const MFD_NAME_MAX_LEN: usize = 249;
+
+pub fn sys_memfd_create(name_addr: Vaddr, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = MemfdFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    debug!("name = {:?}, flags = {:?}", name, flags);
+
+    if flags.contains(MemfdFlags::MFD_HUGETLB) {
+        // TODO: Support memfd files backed by huge pages.
+        return_errno_with_message!(Errno::EINVAL, "MFD_HUGETLB is not supported");
+    }
+
+    let name = name.to_string_lossy();
+    if name.len() > MFD_NAME_MAX_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the name is too long");
+    }
+
+    let credentials = ctx.posix_thread.credentials();
+    let dentry = new_memfd(
+        &name,
+        flags.contains(MemfdFlags::MFD_ALLOW_SEALING),
+        credentials.fsuid(),
+        credentials.fsgid(),
+    );
+    let inode_handle =
+        InodeHandle::new_unchecked_access(dentry, AccessMode::O_RDWR, StatusFlags::empty())?;
+
+    let fd_flags = if flags.contains(MemfdFlags::MFD_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
+    let fd = file_table.insert(Arc::new(inode_handle), fd_flags);
+
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+bitflags! {
+    struct MemfdFlags: u32 {
+        const MFD_CLOEXEC = 0x0001;
+        const MFD_ALLOW_SEALING = 0x0002;
+        const MFD_HUGETLB = 0x0004;
+    }
+}
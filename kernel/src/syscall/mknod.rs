@@ -2,8 +2,8 @@
 
 use super::SyscallReturn;
 use crate::{
-    device::get_device,
     fs::{
+        device::{get_device, DeviceId, DeviceType},
         file_table::FileDesc,
         fs_resolver::{FsPath, AT_FDCWD},
         utils::{InodeMode, InodeType, MknodType},
@@ -49,8 +49,18 @@ pub fn sys_mknodat(
             let _ = dir_dentry.new_fs_child(&name, InodeType::File, inode_mode)?;
         }
         InodeType::CharDevice | InodeType::BlockDevice => {
-            let device_inode = get_device(dev)?;
-            let _ = dir_dentry.mknod(&name, inode_mode, device_inode.into())?;
+            if dev == 0 {
+                return_errno_with_message!(Errno::EPERM, "whiteout device");
+            }
+            let device_type = if inode_type == InodeType::BlockDevice {
+                DeviceType::BlockDevice
+            } else {
+                DeviceType::CharDevice
+            };
+            let Some(device) = get_device(device_type, DeviceId::from(dev as u64)) else {
+                return_errno_with_message!(Errno::EINVAL, "unsupported device");
+            };
+            let _ = dir_dentry.mknod(&name, inode_mode, device.into())?;
         }
         InodeType::NamedPipe => {
             let _ = dir_dentry.mknod(&name, inode_mode, MknodType::NamedPipeNode)?;
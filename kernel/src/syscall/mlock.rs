@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ops::Range;
+
+use align_ext::AlignExt;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{credentials::capabilities::CapSet, ResourceType},
+    vm::vmar::vm_mapping::VmLockMode,
+};
+
+pub fn sys_mlock(start: Vaddr, len: usize, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("start = 0x{:x}, len = 0x{:x}", start, len);
+
+    do_mlock(start, len, VmLockMode::Populate, ctx)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mlock2(start: Vaddr, len: usize, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = Mlock2Flags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown mlock2 flags"))?;
+    debug!(
+        "start = 0x{:x}, len = 0x{:x}, flags = {:?}",
+        start, len, flags
+    );
+
+    let mode = if flags.contains(Mlock2Flags::MLOCK_ONFAULT) {
+        VmLockMode::OnFault
+    } else {
+        VmLockMode::Populate
+    };
+    do_mlock(start, len, mode, ctx)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_munlock(start: Vaddr, len: usize, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("start = 0x{:x}, len = 0x{:x}", start, len);
+
+    let range = lock_range(start, len)?;
+    if range.is_empty() {
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    ctx.process.root_vmar().set_lock(range, None)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mlockall(flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = MlockallFlags::from_bits(flags)
+        .filter(|flags| flags.intersects(MlockallFlags::MCL_CURRENT | MlockallFlags::MCL_FUTURE))
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid mlockall flags"))?;
+    debug!("flags = {:?}", flags);
+
+    check_can_lock(ctx)?;
+
+    let mode = if flags.contains(MlockallFlags::MCL_ONFAULT) {
+        VmLockMode::OnFault
+    } else {
+        VmLockMode::Populate
+    };
+    let root_vmar = ctx.process.root_vmar();
+
+    if flags.contains(MlockallFlags::MCL_CURRENT) {
+        let mapped_size = root_vmar
+            .mappings_info()
+            .iter()
+            .map(|info| info.range.len())
+            .sum();
+        check_lock_limit(mapped_size, Errno::ENOMEM, ctx)?;
+
+        root_vmar.set_lock_all(Some(mode))?;
+        if mode == VmLockMode::Populate {
+            // Like Linux, the errors of populating the pages are ignored.
+            let _ = root_vmar.populate(root_vmar.base()..root_vmar.base() + root_vmar.size());
+        }
+    }
+
+    // Like Linux, new mappings are no longer locked if `MCL_FUTURE` is not
+    // specified.
+    root_vmar.set_future_lock(flags.contains(MlockallFlags::MCL_FUTURE).then_some(mode));
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_munlockall(ctx: &Context) -> Result<SyscallReturn> {
+    let root_vmar = ctx.process.root_vmar();
+    root_vmar.set_lock_all(None)?;
+    root_vmar.set_future_lock(None);
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn do_mlock(start: Vaddr, len: usize, mode: VmLockMode, ctx: &Context) -> Result<()> {
+    check_can_lock(ctx)?;
+
+    let range = lock_range(start, len)?;
+    if range.is_empty() {
+        return Ok(());
+    }
+
+    // The pages that are already locked in the range are not counted twice.
+    let root_vmar = ctx.process.root_vmar();
+    let locked_size = root_vmar.locked_size(root_vmar.base()..root_vmar.base() + root_vmar.size())
+        - root_vmar.locked_size(range.clone())
+        + range.len();
+    check_lock_limit(locked_size, Errno::ENOMEM, ctx)?;
+
+    root_vmar.set_lock(range.clone(), Some(mode))?;
+    if mode == VmLockMode::Populate {
+        root_vmar.populate(range).map_err(|err| match err.error() {
+            Errno::ENOMEM => Error::with_message(Errno::EAGAIN, "cannot populate the pages"),
+            _ => Error::with_message(Errno::ENOMEM, "cannot populate the pages"),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Returns the page-aligned range that covers `start..start + len`.
+fn lock_range(start: Vaddr, len: usize) -> Result<Range<Vaddr>> {
+    let end = start
+        .checked_add(len)
+        .filter(|end| *end <= usize::MAX - PAGE_SIZE + 1)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "integer overflow when (start + len)"))?;
+
+    Ok(start.align_down(PAGE_SIZE)..end.align_up(PAGE_SIZE))
+}
+
+/// Checks whether the process is allowed to lock memory at all.
+pub(super) fn check_can_lock(ctx: &Context) -> Result<()> {
+    let limit = ctx
+        .process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_MEMLOCK)
+        .get_cur();
+    if limit == 0 && !has_ipc_lock(ctx) {
+        return_errno_with_message!(Errno::EPERM, "locking memory is not allowed");
+    }
+
+    Ok(())
+}
+
+/// Checks whether `locked_size` bytes of memory can be locked by the
+/// process according to `RLIMIT_MEMLOCK`, and fails with `errno` if not.
+pub(super) fn check_lock_limit(locked_size: usize, errno: Errno, ctx: &Context) -> Result<()> {
+    let limit = ctx
+        .process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_MEMLOCK)
+        .get_cur();
+    if locked_size as u64 > limit && !has_ipc_lock(ctx) {
+        return_errno_with_message!(errno, "the size of locked memory exceeds RLIMIT_MEMLOCK");
+    }
+
+    Ok(())
+}
+
+fn has_ipc_lock(ctx: &Context) -> bool {
+    ctx.posix_thread
+        .credentials()
+        .effective_capset()
+        .contains(CapSet::IPC_LOCK)
+}
+
+bitflags! {
+    struct Mlock2Flags: u32 {
+        const MLOCK_ONFAULT = 1 << 0;
+    }
+}
+
+bitflags! {
+    struct MlockallFlags: u32 {
+        const MCL_CURRENT = 1 << 0;
+        const MCL_FUTURE  = 1 << 1;
+        const MCL_ONFAULT = 1 << 2;
+    }
+}
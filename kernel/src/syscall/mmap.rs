@@ -5,13 +5,16 @@
 use align_ext::AlignExt;
 use aster_rights::Rights;
 
-use super::SyscallReturn;
+use super::{
+    mlock::{check_can_lock, check_lock_limit},
+    SyscallReturn,
+};
 use crate::{
     fs::{file_handle::FileLike, file_table::FileDesc, inode_handle::InodeHandle},
     prelude::*,
     vm::{
         perms::VmPerms,
-        vmar::is_userspace_vaddr,
+        vmar::{is_userspace_vaddr, vm_mapping::VmLockMode},
         vmo::{VmoOptions, VmoRightsOp},
     },
 };
@@ -89,6 +92,20 @@ fn do_sys_mmap(
     };
 
     let root_vmar = ctx.process.root_vmar();
+
+    // The mapping is also locked if `mlockall(MCL_FUTURE)` has been called.
+    let lock = if option.flags.contains(MMapFlags::MAP_LOCKED) {
+        check_can_lock(ctx)?;
+        Some(VmLockMode::Populate)
+    } else {
+        root_vmar.future_lock()
+    };
+    if lock.is_some() {
+        let locked_size =
+            root_vmar.locked_size(root_vmar.base()..root_vmar.base() + root_vmar.size()) + len;
+        check_lock_limit(locked_size, Errno::EAGAIN, ctx)?;
+    }
+
     let vm_map_options = {
         let mut options = root_vmar.new_map(len, vm_perms)?;
         let flags = option.flags;
@@ -120,13 +137,9 @@ fn do_sys_mmap(
                 options = options.vmo(shared_vmo);
             }
         } else {
-            let vmo = {
-                let file_table = ctx.posix_thread.file_table().lock();
-                let file = file_table.get_file(fd)?;
-                let inode_handle = file
-                    .downcast_ref::<InodeHandle>()
-                    .ok_or(Error::with_message(Errno::EINVAL, "no inode"))?;
+            let file = ctx.posix_thread.file_table().lock().get_file(fd)?.clone();
 
+            if let Some(inode_handle) = file.downcast_ref::<InodeHandle>() {
                 let access_mode = inode_handle.access_mode();
                 if vm_perms.contains(VmPerms::READ) && !access_mode.is_readable() {
                     return_errno!(Errno::EACCES);
@@ -138,26 +151,59 @@ fn do_sys_mmap(
                     return_errno!(Errno::EACCES);
                 }
 
-                let inode = inode_handle.dentry().inode();
-                inode
+                let dentry = inode_handle.dentry();
+
+                // A shared mapping of a writable file may become writable later via
+                // `mprotect`, so it prevents the file from being sealed against writing.
+                let writable_mapping =
+                    if option.typ() == MMapType::Shared && access_mode.is_writable() {
+                        match dentry.inode().map_shared_writable() {
+                            Ok(guard) => guard,
+                            Err(_) if !vm_perms.contains(VmPerms::WRITE) => None,
+                            Err(err) => return Err(err),
+                        }
+                    } else {
+                        None
+                    };
+                if let Some(guard) = writable_mapping {
+                    options = options.writable_mapping(guard);
+                }
+
+                let vmo = dentry
+                    .inode()
                     .page_cache()
                     .ok_or(Error::with_message(
                         Errno::EBADF,
                         "File does not have page cache",
                     ))?
-                    .to_dyn()
-            };
+                    .to_dyn();
+
+                options = options
+                    .vmo(vmo)
+                    .dentry(dentry.clone())
+                    .vmo_offset(offset)
+                    .handle_page_faults_around();
+            } else {
+                let (vmo, vmo_offset) = file.mmap_vmo(offset)?;
+                options = options.vmo(vmo).vmo_offset(vmo_offset);
+            }
+        }
 
-            options = options
-                .vmo(vmo)
-                .vmo_offset(offset)
-                .handle_page_faults_around();
+        if let Some(lock) = lock {
+            options = options.lock(lock);
         }
 
         options
     };
 
     let map_addr = vm_map_options.build()?;
+
+    if lock == Some(VmLockMode::Populate) {
+        // Like Linux, the errors of populating the pages are ignored. They
+        // will be reported when the pages are accessed.
+        let _ = root_vmar.populate(map_addr..map_addr + len);
+    }
+
     Ok(map_addr)
 }
 
@@ -58,22 +58,33 @@ mod getsockopt;
 mod gettid;
 mod gettimeofday;
 mod getuid;
+mod inotify;
+mod io_uring;
 mod ioctl;
 mod kill;
 mod link;
 mod listen;
 mod lseek;
 mod madvise;
+mod memfd_create;
 mod mkdir;
 mod mknod;
+mod mlock;
 mod mmap;
 mod mount;
 mod mprotect;
+mod mqueue;
+mod mremap;
+mod msgctl;
+mod msgget;
+mod msgrcv;
+mod msgsnd;
 mod msync;
 mod munmap;
 mod nanosleep;
 mod open;
 mod pause;
+mod pidfd;
 mod pipe;
 mod poll;
 mod prctl;
@@ -81,6 +92,8 @@ mod pread64;
 mod preadv;
 mod prlimit64;
 mod pselect6;
+#[cfg(target_arch = "x86_64")]
+mod ptrace;
 mod pwrite64;
 mod pwritev;
 mod read;
@@ -110,7 +123,9 @@ mod setfsgid;
 mod setfsuid;
 mod setgid;
 mod setgroups;
+mod sethostname;
 mod setitimer;
+mod setns;
 mod setpgid;
 mod setregid;
 mod setresgid;
@@ -119,12 +134,18 @@ mod setreuid;
 mod setsid;
 mod setsockopt;
 mod setuid;
+mod shmat;
+mod shmctl;
+mod shmdt;
+mod shmget;
 mod shutdown;
 mod sigaltstack;
+mod signalfd;
 mod socket;
 mod socketpair;
 mod stat;
 mod statfs;
+mod swapon;
 mod symlink;
 mod sync;
 mod sysinfo;
@@ -132,15 +153,19 @@ mod tgkill;
 mod time;
 mod timer_create;
 mod timer_settime;
+mod timerfd;
 mod truncate;
 mod umask;
 mod umount;
 mod uname;
 mod unlink;
+mod unshare;
+mod userfaultfd;
 mod utimens;
 mod wait4;
 mod waitid;
 mod write;
+mod xattr;
 
 /// This macro is used to define syscall handler.
 /// The first param is the number of parameters,
@@ -319,6 +344,20 @@ impl SyscallArgument {
 }
 
 pub fn handle_syscall(ctx: &Context, user_ctx: &mut UserContext) {
+    let posix_thread = ctx.posix_thread;
+
+    // The tracer may change the syscall number or skip the syscall.
+    if let Some(syscall_number) = posix_thread.ptrace_syscall_entry(user_ctx) {
+        user_ctx.set_syscall_num(syscall_number);
+        dispatch_syscall(ctx, user_ctx);
+    }
+
+    if !ctx.thread.is_exited() {
+        posix_thread.ptrace_syscall_exit(user_ctx);
+    }
+}
+
+fn dispatch_syscall(ctx: &Context, user_ctx: &mut UserContext) {
     let syscall_frame = SyscallArgument::new_from_context(user_ctx);
     let syscall_return = arch::syscall_dispatch(
         syscall_frame.syscall_number,
@@ -361,7 +400,3 @@ macro_rules! log_syscall_entry {
         }
     };
 }
-
-pub(super) fn init() {
-    uname::init();
-}
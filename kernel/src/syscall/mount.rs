@@ -3,10 +3,13 @@
 use super::SyscallReturn;
 use crate::{
     fs::{
+        devtmpfs::devtmpfs,
         exfat::{ExfatFS, ExfatMountOptions},
         ext2::Ext2,
         fs_resolver::{FsPath, AT_FDCWD},
         path::Dentry,
+        procfs::ProcFS,
+        sysfs::SysFS,
         utils::{FileSystem, InodeType},
     },
     prelude::*,
@@ -146,13 +149,33 @@ fn do_new_mount(
     if fs_type.is_empty() {
         return_errno_with_message!(Errno::EINVAL, "fs_type is empty");
     }
-    let fs = get_fs(fs_type, devname)?;
+    let fs = get_fs(fs_type, devname, ctx)?;
     target_dentry.mount(fs)?;
     Ok(())
 }
 
 /// Get the filesystem by fs_type and devname.
-fn get_fs(fs_type: CString, devname: CString) -> Result<Arc<dyn FileSystem>> {
+fn get_fs(fs_type: CString, devname: CString, ctx: &Context) -> Result<Arc<dyn FileSystem>> {
+    // The procfs is not backed by a device. It shows the processes in the PID
+    // namespace of the mounting process.
+    if fs_type.as_bytes() == b"proc" {
+        return Ok(ProcFS::new(ctx.process.pid_ns().clone()));
+    }
+    if fs_type.as_bytes() == b"sysfs" {
+        return Ok(SysFS::new());
+    }
+    // All the devtmpfs mounts share the same instance, which contains the device nodes of the
+    // registered devices.
+    if fs_type.as_bytes() == b"devtmpfs" {
+        return Ok(devtmpfs().clone());
+    }
+    // The mqueue file system is not backed by a device. It shows the POSIX message
+    // queues in the IPC namespace of the mounting thread.
+    if fs_type.as_bytes() == b"mqueue" {
+        let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+        return Ok(ipc_ns.mqueue_fs().clone());
+    }
+
     let devname = devname.to_str().unwrap();
     let device = match aster_block::get_device(devname) {
         Some(device) => device,
@@ -160,7 +183,7 @@ fn get_fs(fs_type: CString, devname: CString) -> Result<Arc<dyn FileSystem>> {
     };
     let fs_type = fs_type.to_str().unwrap();
     match fs_type {
-        "ext2" => {
+        "ext2" | "ext3" | "ext4" => {
             let ext2_fs = Ext2::open(device)?;
             Ok(ext2_fs)
         }
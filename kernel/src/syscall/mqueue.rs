@@ -0,0 +1,388 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_handle::FileLike,
+        file_table::{FdFlags, FileDesc},
+        inode_handle::InodeHandle,
+        mqueue::MqueueInode,
+        path::Dentry,
+        utils::{AccessMode, CreationFlags, InodeMode, Permission, StatusFlags},
+    },
+    ipc::message_queue::posix::{
+        MqNotification, MqNotifyMethod, PosixMessageQueueAttr, HARD_MSGMAX, HARD_MSGSIZEMAX,
+        MQ_PRIO_MAX, MSGSIZE_MAX, MSG_MAX,
+    },
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        signal::{
+            c_types::{sigevent_t, SigNotify},
+            sig_num::SigNum,
+        },
+    },
+    syscall::constants::MAX_FILENAME_LEN,
+    time::{clocks::RealTimeClock, timespec_t},
+};
+
+pub fn sys_mq_open(
+    name_addr: Vaddr,
+    oflag: u32,
+    mode: u16,
+    attr_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    debug!(
+        "name = {:?}, oflag = 0x{:x}, mode = 0o{:o}, attr_addr = 0x{:x}",
+        name, oflag, mode, attr_addr
+    );
+
+    let name = name.to_string_lossy();
+    check_queue_name(&name)?;
+
+    let creation_flags = CreationFlags::from_bits_truncate(oflag);
+    let status_flags = StatusFlags::from_bits_truncate(oflag) & StatusFlags::O_NONBLOCK;
+    let access_mode = AccessMode::from_u32(oflag)?;
+
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+    let root = Dentry::new_fs_root(ipc_ns.mqueue_mount().clone());
+
+    let inode_handle = match root.lookup(&name) {
+        Ok(dentry) => {
+            if creation_flags.contains(CreationFlags::O_CREAT | CreationFlags::O_EXCL) {
+                return_errno_with_message!(Errno::EEXIST, "the queue already exists");
+            }
+            InodeHandle::new(dentry, access_mode, status_flags)?
+        }
+        Err(err)
+            if err.error() == Errno::ENOENT && creation_flags.contains(CreationFlags::O_CREAT) =>
+        {
+            if root
+                .inode()
+                .check_permission(Permission::MAY_WRITE)
+                .is_err()
+            {
+                return_errno_with_message!(Errno::EACCES, "the queue cannot be created");
+            }
+
+            let credentials = ctx.posix_thread.credentials();
+            let attr = if attr_addr == 0 {
+                PosixMessageQueueAttr::default()
+            } else {
+                let mq_attr = ctx.user_space().read_val::<mq_attr>(attr_addr)?;
+                mq_attr.to_queue_attr(
+                    credentials
+                        .effective_capset()
+                        .contains(CapSet::SYS_RESOURCE),
+                )?
+            };
+            let mode = mode & !ctx.posix_thread.fs().umask().read().get();
+
+            ipc_ns.mqueue_fs().create_queue(
+                &name,
+                InodeMode::from_bits_truncate(mode),
+                attr,
+                credentials.fsuid(),
+                credentials.fsgid(),
+            )?;
+
+            // The creator can access the queue regardless of its permission bits.
+            let dentry = root.lookup(&name)?;
+            InodeHandle::new_unchecked_access(dentry, access_mode, status_flags)?
+        }
+        Err(err) => return Err(err),
+    };
+
+    // A message queue descriptor is always close-on-exec.
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
+    let fd = file_table.insert(Arc::new(inode_handle), FdFlags::CLOEXEC);
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+pub fn sys_mq_unlink(name_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    debug!("name = {:?}", name);
+
+    let name = name.to_string_lossy();
+    check_queue_name(&name)?;
+
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+    let root = Dentry::new_fs_root(ipc_ns.mqueue_mount().clone());
+    let dentry = root.lookup(&name)?;
+
+    if root
+        .inode()
+        .check_permission(Permission::MAY_WRITE | Permission::MAY_EXEC)
+        .is_err()
+    {
+        return_errno_with_message!(Errno::EACCES, "the queue cannot be removed");
+    }
+
+    // The root directory has the sticky bit, so only the owner can remove the queue.
+    let credentials = ctx.posix_thread.credentials();
+    let fsuid = credentials.fsuid();
+    if root.mode()?.has_sticky_bit()
+        && fsuid != dentry.owner()?
+        && fsuid != root.owner()?
+        && !credentials.effective_capset().contains(CapSet::FOWNER)
+    {
+        return_errno_with_message!(Errno::EPERM, "the queue is not owned by the user");
+    }
+
+    root.unlink(&name)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_timedsend(
+    mqdes: FileDesc,
+    msg_ptr: Vaddr,
+    msg_len: usize,
+    msg_prio: u32,
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, msg_ptr = 0x{:x}, msg_len = {}, msg_prio = {}, abs_timeout_addr = 0x{:x}",
+        mqdes, msg_ptr, msg_len, msg_prio, abs_timeout_addr
+    );
+
+    if msg_prio >= MQ_PRIO_MAX {
+        return_errno_with_message!(Errno::EINVAL, "the priority is too high");
+    }
+    let timeout = read_timeout(abs_timeout_addr, ctx)?;
+
+    let file = get_file(mqdes, ctx)?;
+    if !file.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "the queue is not opened for writing");
+    }
+    let queue = queue_inode(&file)?.queue();
+
+    if msg_len > queue.attr().max_message_size {
+        return_errno_with_message!(Errno::EMSGSIZE, "the message is too long");
+    }
+    let mut data = vec![0u8; msg_len];
+    ctx.user_space()
+        .read_bytes(msg_ptr, &mut VmWriter::from(data.as_mut_slice()))?;
+
+    let is_nonblocking = file.status_flags().contains(StatusFlags::O_NONBLOCK);
+    let sender = (ctx.process.pid(), ctx.posix_thread.credentials().ruid());
+    queue
+        .send(&data, msg_prio, is_nonblocking, timeout.as_ref(), sender)
+        .map_err(map_timeout_error)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_timedreceive(
+    mqdes: FileDesc,
+    msg_ptr: Vaddr,
+    msg_len: usize,
+    msg_prio_addr: Vaddr,
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, msg_ptr = 0x{:x}, msg_len = {}, msg_prio_addr = 0x{:x}, abs_timeout_addr = 0x{:x}",
+        mqdes, msg_ptr, msg_len, msg_prio_addr, abs_timeout_addr
+    );
+
+    let timeout = read_timeout(abs_timeout_addr, ctx)?;
+
+    let file = get_file(mqdes, ctx)?;
+    if !file.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "the queue is not opened for reading");
+    }
+    let queue = queue_inode(&file)?.queue();
+
+    if msg_len < queue.attr().max_message_size {
+        return_errno_with_message!(Errno::EMSGSIZE, "the buffer is too small");
+    }
+
+    let is_nonblocking = file.status_flags().contains(StatusFlags::O_NONBLOCK);
+    let (data, priority) = queue
+        .receive(is_nonblocking, timeout.as_ref())
+        .map_err(map_timeout_error)?;
+
+    let user_space = ctx.user_space();
+    user_space.write_bytes(msg_ptr, &mut VmReader::from(data.as_slice()))?;
+    if msg_prio_addr != 0 {
+        user_space.write_val(msg_prio_addr, &priority)?;
+    }
+
+    Ok(SyscallReturn::Return(data.len() as _))
+}
+
+pub fn sys_mq_notify(
+    mqdes: FileDesc,
+    sigevent_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("mqdes = {}, sigevent_addr = 0x{:x}", mqdes, sigevent_addr);
+
+    let notification = if sigevent_addr == 0 {
+        None
+    } else {
+        let sig_event = ctx.user_space().read_val::<sigevent_t>(sigevent_addr)?;
+        let method = match SigNotify::try_from(sig_event.sigev_notify)? {
+            SigNotify::SIGEV_NONE => MqNotifyMethod::None,
+            SigNotify::SIGEV_SIGNAL => {
+                let sig_num = u8::try_from(sig_event.sigev_signo)
+                    .map_err(|_| Error::with_message(Errno::EINVAL, "invalid signal number"))
+                    .and_then(SigNum::try_from)?;
+                MqNotifyMethod::Signal(sig_num, sig_event.sigev_value)
+            }
+            // TODO: Support `SIGEV_THREAD`, which is implemented by the C libraries with a
+            // netlink socket to receive the notification.
+            SigNotify::SIGEV_THREAD | SigNotify::SIGEV_THREAD_ID => {
+                return_errno_with_message!(Errno::EINVAL, "the notification is not supported");
+            }
+        };
+        Some(MqNotification::new(
+            ctx.posix_thread.weak_process(),
+            ctx.process.pid(),
+            method,
+        ))
+    };
+
+    let file = get_file(mqdes, ctx)?;
+    queue_inode(&file)?
+        .queue()
+        .set_notification(notification, ctx.process.pid())?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_getsetattr(
+    mqdes: FileDesc,
+    new_attr_addr: Vaddr,
+    old_attr_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, new_attr_addr = 0x{:x}, old_attr_addr = 0x{:x}",
+        mqdes, new_attr_addr, old_attr_addr
+    );
+
+    let new_attr = if new_attr_addr == 0 {
+        None
+    } else {
+        let new_attr = ctx.user_space().read_val::<mq_attr>(new_attr_addr)?;
+        if new_attr.mq_flags & !(StatusFlags::O_NONBLOCK.bits() as i64) != 0 {
+            return_errno_with_message!(Errno::EINVAL, "invalid flags");
+        }
+        Some(new_attr)
+    };
+
+    let file = get_file(mqdes, ctx)?;
+    let queue = queue_inode(&file)?.queue();
+
+    let status_flags = file.status_flags();
+    let attr = queue.attr();
+    let old_attr = mq_attr {
+        mq_flags: (status_flags & StatusFlags::O_NONBLOCK).bits() as i64,
+        mq_maxmsg: attr.max_messages as i64,
+        mq_msgsize: attr.max_message_size as i64,
+        mq_curmsgs: queue.status().num_messages as i64,
+        __reserved: [0; 4],
+    };
+
+    if let Some(new_attr) = new_attr {
+        let mut status_flags = status_flags;
+        status_flags.set(
+            StatusFlags::O_NONBLOCK,
+            new_attr.mq_flags & StatusFlags::O_NONBLOCK.bits() as i64 != 0,
+        );
+        file.set_status_flags(status_flags)?;
+    }
+
+    if old_attr_addr != 0 {
+        ctx.user_space().write_val(old_attr_addr, &old_attr)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Checks the name of a queue, whose leading slash has been removed by the C libraries.
+fn check_queue_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return_errno_with_message!(Errno::ENOENT, "the name is empty");
+    }
+    if name.contains('/') || name == "." || name == ".." {
+        return_errno_with_message!(Errno::EACCES, "the name is invalid");
+    }
+
+    Ok(())
+}
+
+fn get_file(mqdes: FileDesc, ctx: &Context) -> Result<Arc<dyn FileLike>> {
+    let file_table = ctx.posix_thread.file_table().lock_arc();
+    Ok(file_table.get_file(mqdes)?.clone())
+}
+
+fn queue_inode(file: &Arc<dyn FileLike>) -> Result<&MqueueInode> {
+    file.downcast_ref::<InodeHandle>()
+        .and_then(|inode_handle| inode_handle.dentry().inode().downcast_ref::<MqueueInode>())
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "not a message queue descriptor"))
+}
+
+/// Reads the absolute timeout, and converts it to the timeout relative to now.
+fn read_timeout(abs_timeout_addr: Vaddr, ctx: &Context) -> Result<Option<Duration>> {
+    if abs_timeout_addr == 0 {
+        return Ok(None);
+    }
+
+    let abs_timeout = ctx.user_space().read_val::<timespec_t>(abs_timeout_addr)?;
+    let abs_timeout = Duration::try_from(abs_timeout)?;
+    let now = RealTimeClock::get().read_time();
+    Ok(Some(abs_timeout.saturating_sub(now)))
+}
+
+fn map_timeout_error(err: Error) -> Error {
+    if err.error() == Errno::ETIME {
+        Error::with_message(Errno::ETIMEDOUT, "the timeout expired")
+    } else {
+        err
+    }
+}
+
+/// The attributes of a message queue in the user space (i.e., `struct mq_attr`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct mq_attr {
+    mq_flags: i64,
+    mq_maxmsg: i64,
+    mq_msgsize: i64,
+    mq_curmsgs: i64,
+    __reserved: [i64; 4],
+}
+
+impl mq_attr {
+    /// Checks the attributes to create a queue with.
+    ///
+    /// The hard limits apply if `has_cap_sys_resource` is true, or the default limits apply.
+    fn to_queue_attr(&self, has_cap_sys_resource: bool) -> Result<PosixMessageQueueAttr> {
+        if self.mq_maxmsg <= 0 || self.mq_msgsize <= 0 {
+            return_errno_with_message!(Errno::EINVAL, "the attributes must be positive");
+        }
+
+        let (max_messages, max_message_size) = if has_cap_sys_resource {
+            (HARD_MSGMAX, HARD_MSGSIZEMAX)
+        } else {
+            (MSG_MAX, MSGSIZE_MAX)
+        };
+        if self.mq_maxmsg as usize > max_messages || self.mq_msgsize as usize > max_message_size {
+            return_errno_with_message!(Errno::EINVAL, "the attributes exceed the limits");
+        }
+
+        Ok(PosixMessageQueueAttr {
+            max_messages: self.mq_maxmsg as usize,
+            max_message_size: self.mq_msgsize as usize,
+        })
+    }
+}
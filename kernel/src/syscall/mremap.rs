@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use align_ext::AlignExt;
+
+use super::{mlock::check_lock_limit, SyscallReturn};
+use crate::{
+    prelude::*,
+    vm::vmar::{vm_mapping::VmLockMode, RemapTarget},
+};
+
+pub fn sys_mremap(
+    old_addr: Vaddr,
+    old_size: usize,
+    new_size: usize,
+    flags: u32,
+    new_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = MremapFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown mremap flags"))?;
+    debug!(
+        "old_addr = 0x{:x}, old_size = 0x{:x}, new_size = 0x{:x}, flags = {:?}, new_addr = 0x{:x}",
+        old_addr, old_size, new_size, flags, new_addr
+    );
+
+    let may_move = flags.contains(MremapFlags::MREMAP_MAYMOVE);
+    let dont_unmap = flags.contains(MremapFlags::MREMAP_DONTUNMAP);
+    if flags.intersects(MremapFlags::MREMAP_FIXED | MremapFlags::MREMAP_DONTUNMAP) && !may_move {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "MREMAP_FIXED and MREMAP_DONTUNMAP require MREMAP_MAYMOVE"
+        );
+    }
+    if dont_unmap && old_size != new_size {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "MREMAP_DONTUNMAP cannot change the size of the mapping"
+        );
+    }
+
+    if old_addr % PAGE_SIZE != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the old address should be page aligned");
+    }
+    if old_size > isize::MAX as usize || new_size > isize::MAX as usize {
+        return_errno_with_message!(Errno::EINVAL, "the size is too large");
+    }
+    let old_size = old_size.align_up(PAGE_SIZE);
+    let new_size = new_size.align_up(PAGE_SIZE);
+    if new_size == 0 {
+        return_errno_with_message!(Errno::EINVAL, "the new size cannot be zero");
+    }
+    if old_size == 0 {
+        // TODO: Support duplicating shared mappings, which is done by Linux if the old
+        // size is zero.
+        return_errno_with_message!(Errno::EINVAL, "the old size cannot be zero");
+    }
+
+    // With `MREMAP_DONTUNMAP` but without `MREMAP_FIXED`, the new address is a hint,
+    // which is ignored for now. But it is still checked like Linux.
+    if flags.intersects(MremapFlags::MREMAP_FIXED | MremapFlags::MREMAP_DONTUNMAP)
+        && new_addr % PAGE_SIZE != 0
+    {
+        return_errno_with_message!(Errno::EINVAL, "the new address should be page aligned");
+    }
+
+    let target = if flags.contains(MremapFlags::MREMAP_FIXED) {
+        RemapTarget::Fixed(new_addr)
+    } else if may_move {
+        RemapTarget::MayMove
+    } else {
+        RemapTarget::InPlace
+    };
+
+    let root_vmar = ctx.process.root_vmar();
+
+    // The pages added to a locked mapping are locked as well.
+    let lock = root_vmar
+        .mappings_info()
+        .into_iter()
+        .find(|info| info.range.contains(&old_addr))
+        .and_then(|info| info.lock);
+    let grown_size = new_size.saturating_sub(old_size);
+    if lock.is_some() && grown_size > 0 {
+        let locked_size = root_vmar
+            .locked_size(root_vmar.base()..root_vmar.base() + root_vmar.size())
+            + grown_size;
+        check_lock_limit(locked_size, Errno::EAGAIN, ctx)?;
+    }
+
+    let new_addr = root_vmar.remap(old_addr, old_size, new_size, target, dont_unmap)?;
+
+    if lock == Some(VmLockMode::Populate) && grown_size > 0 {
+        let _ = root_vmar.populate(new_addr + old_size..new_addr + new_size);
+    }
+
+    Ok(SyscallReturn::Return(new_addr as _))
+}
+
+bitflags! {
+    struct MremapFlags: u32 {
+        const MREMAP_MAYMOVE = 1 << 0;
+        const MREMAP_FIXED = 1 << 1;
+        const MREMAP_DONTUNMAP = 1 << 2;
+    }
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{ipc64_perm, IpcControlCmd, IPC_64},
+    prelude::*,
+    process::{Gid, Uid},
+};
+
+pub fn sys_msgctl(msqid: i32, cmd: i32, buf: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let cmd = IpcControlCmd::try_from(cmd & !IPC_64)?;
+    debug!(
+        "[sys_msgctl] msqid = {}, cmd = {:?}, buf = 0x{:x}",
+        msqid, cmd, buf
+    );
+
+    let credentials = ctx.posix_thread.credentials();
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+    let msg_table = ipc_ns.msg_table();
+
+    match cmd {
+        IpcControlCmd::IPC_STAT => {
+            let queue = msg_table.get(msqid)?;
+            queue.check_access(&credentials, 0o4)?;
+
+            let status = queue.status();
+            let pid_ns = ctx.process.pid_ns();
+            let msqid_ds = msqid64_ds {
+                msg_perm: ipc64_perm::from(&status.permission),
+                msg_stime: status.stime as i64,
+                msg_rtime: status.rtime as i64,
+                msg_ctime: status.ctime as i64,
+                msg_cbytes: status.cbytes as u64,
+                msg_qnum: status.qnum as u64,
+                msg_qbytes: status.qbytes as u64,
+                msg_lspid: pid_ns.local_id_or_zero(status.lspid) as i32,
+                msg_lrpid: pid_ns.local_id_or_zero(status.lrpid) as i32,
+                __unused4: 0,
+                __unused5: 0,
+            };
+            ctx.user_space().write_val(buf, &msqid_ds)?;
+        }
+        IpcControlCmd::IPC_SET => {
+            let msqid_ds = ctx.user_space().read_val::<msqid64_ds>(buf)?;
+            let perm = &msqid_ds.msg_perm;
+
+            let queue = msg_table.get(msqid)?;
+            queue.set_attributes(
+                &credentials,
+                Uid::new(perm.uid),
+                Gid::new(perm.gid),
+                perm.mode as u16,
+                msqid_ds.msg_qbytes as usize,
+            )?;
+        }
+        IpcControlCmd::IPC_RMID => {
+            msg_table.remove(msqid, &credentials)?;
+        }
+        _ => {
+            return_errno_with_message!(Errno::EINVAL, "the command is not supported");
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// The status of a message queue in the user space (i.e., `struct msqid64_ds`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct msqid64_ds {
+    msg_perm: ipc64_perm,
+    msg_stime: i64,
+    msg_rtime: i64,
+    msg_ctime: i64,
+    msg_cbytes: u64,
+    msg_qnum: u64,
+    msg_qbytes: u64,
+    msg_lspid: i32,
+    msg_lrpid: i32,
+    __unused4: u64,
+    __unused5: u64,
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{ipc::IpcFlags, prelude::*};
+
+pub fn sys_msgget(key: i32, msgflg: i32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("[sys_msgget] key = {}, msgflg = 0x{:x}", key, msgflg);
+
+    let flags = IpcFlags::from_bits_truncate(msgflg as u32);
+    let mode = (msgflg as u32 & 0o777) as u16;
+    let credentials = ctx.posix_thread.credentials();
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+
+    let msqid = ipc_ns
+        .msg_table()
+        .get_or_create(key, flags, mode, &credentials)?;
+
+    Ok(SyscallReturn::Return(msqid as _))
+}
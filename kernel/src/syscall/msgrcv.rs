@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        message_queue::system_v::{MessageSelector, MsgFlags},
+        IpcFlags,
+    },
+    prelude::*,
+};
+
+pub fn sys_msgrcv(
+    msqid: i32,
+    msgp: Vaddr,
+    msgsz: isize,
+    msgtyp: i64,
+    msgflg: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "[sys_msgrcv] msqid = {}, msgp = 0x{:x}, msgsz = {}, msgtyp = {}, msgflg = 0x{:x}",
+        msqid, msgp, msgsz, msgtyp, msgflg
+    );
+
+    if msgsz < 0 {
+        return_errno_with_message!(Errno::EINVAL, "the buffer size is negative");
+    }
+
+    let ipc_flags = IpcFlags::from_bits_truncate(msgflg as u32);
+    let msg_flags = MsgFlags::from_bits_truncate(msgflg as u32);
+    if msg_flags.contains(MsgFlags::MSG_COPY)
+        && (msg_flags.contains(MsgFlags::MSG_EXCEPT) || !ipc_flags.contains(IpcFlags::IPC_NOWAIT))
+    {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "MSG_COPY requires IPC_NOWAIT and conflicts with MSG_EXCEPT"
+        );
+    }
+
+    let credentials = ctx.posix_thread.credentials();
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+
+    let queue = ipc_ns.msg_table().get(msqid)?;
+    let message = queue.receive(
+        msgsz as usize,
+        MessageSelector::new(msgtyp, msg_flags),
+        msg_flags,
+        ipc_flags.contains(IpcFlags::IPC_NOWAIT),
+        &credentials,
+        ctx.process.pid(),
+    )?;
+
+    // The message buffer is `struct msgbuf { long mtype; char mtext[]; }`.
+    let user_space = ctx.user_space();
+    user_space.write_val(msgp, &message.mtype())?;
+    user_space.write_bytes(
+        msgp + core::mem::size_of::<i64>(),
+        &mut VmReader::from(message.data()),
+    )?;
+
+    Ok(SyscallReturn::Return(message.data().len() as _))
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{message_queue::system_v::MSGMAX, IpcFlags},
+    prelude::*,
+};
+
+pub fn sys_msgsnd(
+    msqid: i32,
+    msgp: Vaddr,
+    msgsz: usize,
+    msgflg: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "[sys_msgsnd] msqid = {}, msgp = 0x{:x}, msgsz = {}, msgflg = 0x{:x}",
+        msqid, msgp, msgsz, msgflg
+    );
+
+    if msgsz > MSGMAX {
+        return_errno_with_message!(Errno::EINVAL, "the message is too long");
+    }
+
+    // The message buffer is `struct msgbuf { long mtype; char mtext[]; }`.
+    let user_space = ctx.user_space();
+    let mtype = user_space.read_val::<i64>(msgp)?;
+    if mtype < 1 {
+        return_errno_with_message!(Errno::EINVAL, "the message type must be positive");
+    }
+    let mut data = vec![0u8; msgsz];
+    user_space.read_bytes(
+        msgp + core::mem::size_of::<i64>(),
+        &mut VmWriter::from(data.as_mut_slice()),
+    )?;
+
+    let flags = IpcFlags::from_bits_truncate(msgflg as u32);
+    let credentials = ctx.posix_thread.credentials();
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+
+    let queue = ipc_ns.msg_table().get(msqid)?;
+    queue.send(
+        mtype,
+        &data,
+        flags.contains(IpcFlags::IPC_NOWAIT),
+        &credentials,
+        ctx.process.pid(),
+    )?;
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -40,7 +40,7 @@ pub fn sys_openat(
             })?;
         Arc::new(inode_handle)
     };
-    let mut file_table = current.file_table().lock();
+    let mut file_table = current.file_table().lock_arc();
     let fd = {
         let fd_flags =
             if CreationFlags::from_bits_truncate(flags).contains(CreationFlags::O_CLOEXEC) {
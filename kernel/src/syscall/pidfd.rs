@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::{FdFlags, FileDesc},
+        utils::StatusFlags,
+    },
+    prelude::*,
+    process::{
+        kill_pidfd,
+        signal::{
+            c_types::siginfo_t,
+            sig_num::SigNum,
+            signals::user::{UserSignal, UserSignalKind},
+        },
+        PidFile,
+    },
+};
+
+pub fn sys_pidfd_open(pid: i32, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("pid = {}, flags = 0x{:x}", pid, flags);
+
+    if pid <= 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid pid");
+    }
+    let flags = PidfdFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+
+    let process =
+        ctx.process.pid_ns().get_process(pid as _).ok_or_else(|| {
+            Error::with_message(Errno::ESRCH, "the target process does not exist")
+        })?;
+
+    let pid_file = PidFile::new(process, flags.contains(PidfdFlags::PIDFD_NONBLOCK));
+    // A pidfd is always close-on-exec.
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
+    let fd = file_table.insert(pid_file, FdFlags::CLOEXEC);
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+pub fn sys_pidfd_send_signal(
+    pidfd: FileDesc,
+    sig_num: u64,
+    siginfo_addr: Vaddr,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "pidfd = {}, sig_num = {}, siginfo_addr = 0x{:x}, flags = 0x{:x}",
+        pidfd, sig_num, siginfo_addr, flags
+    );
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+    let sig_num = if sig_num == 0 {
+        None
+    } else {
+        Some(SigNum::try_from(sig_num as u8)?)
+    };
+
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(pidfd)?.clone()
+    };
+    let pid_file = file
+        .downcast_ref::<PidFile>()
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "not pidfd file"))?;
+    let process = pid_file.process();
+
+    let kind = if siginfo_addr == 0 {
+        UserSignalKind::Kill
+    } else {
+        let siginfo = ctx.user_space().read_val::<siginfo_t>(siginfo_addr)?;
+        if sig_num.is_none_or(|sig_num| siginfo.si_signo != sig_num.as_u8() as i32) {
+            return_errno_with_message!(Errno::EINVAL, "the signal numbers do not match");
+        }
+        // Only the kernel can send signals with non-negative codes to other processes.
+        if siginfo.si_code >= 0 && !core::ptr::eq(process.as_ref(), ctx.process) {
+            return_errno_with_message!(Errno::EPERM, "the signal code is not permitted");
+        }
+        // TODO: Deliver the other fields of the `siginfo_t` provided by the user.
+        UserSignalKind::Sigqueue
+    };
+
+    let signal = sig_num.map(|sig_num| {
+        let pid = ctx.process.pid();
+        let uid = ctx.posix_thread.credentials().ruid();
+        UserSignal::new(sig_num, kind, pid, uid)
+    });
+    kill_pidfd(process, signal, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+bitflags! {
+    struct PidfdFlags: u32 {
+        const PIDFD_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+    }
+}
@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::mem::size_of;
+
+use super::SyscallReturn;
+use crate::{
+    arch::ptrace::{UserRegs, USER_DEBUGREG_OFFSET},
+    prelude::*,
+    process::{
+        posix_thread::{
+            ptrace_attach, ptrace_detach, AsPosixThread, PtraceOptions, ResumeMode, Tracee,
+        },
+        signal::{
+            c_types::siginfo_t,
+            constants::{SIGKILL, SIGSTOP},
+            sig_num::SigNum,
+            signals::kernel::KernelSignal,
+        },
+    },
+    thread::{AsThread, Thread, Tid},
+};
+
+pub fn sys_ptrace(
+    request: u64,
+    pid: Tid,
+    addr: Vaddr,
+    data: u64,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let request = PtraceRequest::try_from(request as u32)
+        .map_err(|_| Error::with_message(Errno::EIO, "unsupported ptrace request"))?;
+    debug!(
+        "request = {:?}, pid = {}, addr = 0x{:x}, data = 0x{:x}",
+        request, pid, addr, data
+    );
+
+    match request {
+        PtraceRequest::PTRACE_TRACEME => {
+            let Some(parent) = ctx.process.parent().lock().process().upgrade() else {
+                return_errno_with_message!(Errno::EPERM, "the process has no parent");
+            };
+            let current_thread = ctx.task.as_thread().unwrap();
+            ptrace_attach(&parent, current_thread, PtraceOptions::empty(), false)?;
+            return Ok(SyscallReturn::Return(0));
+        }
+        PtraceRequest::PTRACE_ATTACH | PtraceRequest::PTRACE_SEIZE => {
+            let thread = get_thread_to_attach(pid, ctx)?;
+            if request == PtraceRequest::PTRACE_ATTACH {
+                ptrace_attach(
+                    &ctx.posix_thread.process(),
+                    &thread,
+                    PtraceOptions::empty(),
+                    false,
+                )?;
+                thread
+                    .as_posix_thread()
+                    .unwrap()
+                    .enqueue_signal(Box::new(KernelSignal::new(SIGSTOP)));
+            } else {
+                if addr != 0 {
+                    return_errno_with_message!(Errno::EIO, "addr must be zero for PTRACE_SEIZE");
+                }
+                let options = PtraceOptions::from_bits(data as u32)
+                    .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid ptrace options"))?;
+                ptrace_attach(&ctx.posix_thread.process(), &thread, options, true)?;
+            }
+            return Ok(SyscallReturn::Return(0));
+        }
+        _ => (),
+    }
+
+    let thread = get_tracee(pid, ctx)?;
+    let tracee_posix_thread = thread.as_posix_thread().unwrap();
+    let tracee = tracee_posix_thread.tracee();
+
+    match request {
+        PtraceRequest::PTRACE_PEEKTEXT | PtraceRequest::PTRACE_PEEKDATA => {
+            let mut word = [0u8; size_of::<u64>()];
+            tracee_posix_thread
+                .process()
+                .root_vmar()
+                .read_remote(addr, &mut word)?;
+            ctx.user_space()
+                .write_val(data as Vaddr, &u64::from_ne_bytes(word))?;
+        }
+        PtraceRequest::PTRACE_POKETEXT | PtraceRequest::PTRACE_POKEDATA => {
+            tracee_posix_thread
+                .process()
+                .root_vmar()
+                .write_remote(addr, &data.to_ne_bytes())?;
+        }
+        PtraceRequest::PTRACE_PEEKUSER => {
+            let word = read_user_area(tracee, addr)?;
+            ctx.user_space().write_val(data as Vaddr, &word)?;
+        }
+        PtraceRequest::PTRACE_POKEUSER => {
+            write_user_area(tracee, addr, data)?;
+        }
+        PtraceRequest::PTRACE_GETREGS => {
+            let regs = tracee.with_user_ctx(|user_ctx, orig_syscall_num| {
+                UserRegs::new(user_ctx, *orig_syscall_num)
+            })?;
+            ctx.user_space().write_val(data as Vaddr, &regs)?;
+        }
+        PtraceRequest::PTRACE_SETREGS => {
+            let regs = ctx.user_space().read_val::<UserRegs>(data as Vaddr)?;
+            tracee.with_user_ctx(|user_ctx, orig_syscall_num| {
+                regs.write_to(user_ctx, orig_syscall_num)
+            })??;
+        }
+        PtraceRequest::PTRACE_GETREGSET | PtraceRequest::PTRACE_SETREGSET => {
+            const NT_PRSTATUS: usize = 1;
+            if addr != NT_PRSTATUS {
+                return_errno_with_message!(Errno::EINVAL, "unsupported register set");
+            }
+
+            let user_space = ctx.user_space();
+            let mut iov = user_space.read_val::<iovec_t>(data as Vaddr)?;
+            let len = iov.len.min(size_of::<UserRegs>());
+            let mut regs = tracee.with_user_ctx(|user_ctx, orig_syscall_num| {
+                UserRegs::new(user_ctx, *orig_syscall_num)
+            })?;
+            if request == PtraceRequest::PTRACE_GETREGSET {
+                user_space.write_bytes(iov.base, &mut VmReader::from(&regs.as_bytes()[..len]))?;
+            } else {
+                user_space.read_bytes(
+                    iov.base,
+                    &mut VmWriter::from(&mut regs.as_bytes_mut()[..len]),
+                )?;
+                tracee.with_user_ctx(|user_ctx, orig_syscall_num| {
+                    regs.write_to(user_ctx, orig_syscall_num)
+                })?;
+            }
+            iov.len = len;
+            user_space.write_val(data as Vaddr, &iov)?;
+        }
+        PtraceRequest::PTRACE_GETSIGINFO => {
+            let siginfo = tracee.siginfo()?;
+            ctx.user_space().write_val(data as Vaddr, &siginfo)?;
+        }
+        PtraceRequest::PTRACE_SETSIGINFO => {
+            let siginfo = ctx.user_space().read_val::<siginfo_t>(data as Vaddr)?;
+            tracee.set_siginfo(siginfo)?;
+        }
+        PtraceRequest::PTRACE_SETOPTIONS => {
+            let options = PtraceOptions::from_bits(data as u32)
+                .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid ptrace options"))?;
+            tracee.set_options(options);
+        }
+        PtraceRequest::PTRACE_GETEVENTMSG => {
+            ctx.user_space()
+                .write_val(data as Vaddr, &tracee.event_msg())?;
+        }
+        PtraceRequest::PTRACE_CONT => {
+            tracee.resume(ResumeMode::Cont, parse_signal(data)?)?;
+        }
+        PtraceRequest::PTRACE_SYSCALL => {
+            tracee.resume(ResumeMode::Syscall, parse_signal(data)?)?;
+        }
+        PtraceRequest::PTRACE_SINGLESTEP => {
+            tracee.resume(ResumeMode::SingleStep, parse_signal(data)?)?;
+        }
+        PtraceRequest::PTRACE_DETACH => {
+            ptrace_detach(&thread, parse_signal(data)?);
+        }
+        PtraceRequest::PTRACE_KILL => {
+            tracee_posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+        }
+        PtraceRequest::PTRACE_INTERRUPT => {
+            tracee_posix_thread.ptrace_interrupt()?;
+        }
+        PtraceRequest::PTRACE_LISTEN => {
+            tracee.listen()?;
+        }
+        PtraceRequest::PTRACE_TRACEME
+        | PtraceRequest::PTRACE_ATTACH
+        | PtraceRequest::PTRACE_SEIZE => unreachable!(),
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+#[allow(non_camel_case_types)]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+enum PtraceRequest {
+    PTRACE_TRACEME = 0,
+    PTRACE_PEEKTEXT = 1,
+    PTRACE_PEEKDATA = 2,
+    PTRACE_PEEKUSER = 3,
+    PTRACE_POKETEXT = 4,
+    PTRACE_POKEDATA = 5,
+    PTRACE_POKEUSER = 6,
+    PTRACE_CONT = 7,
+    PTRACE_KILL = 8,
+    PTRACE_SINGLESTEP = 9,
+    PTRACE_GETREGS = 12,
+    PTRACE_SETREGS = 13,
+    PTRACE_ATTACH = 16,
+    PTRACE_DETACH = 17,
+    PTRACE_SYSCALL = 24,
+    PTRACE_SETOPTIONS = 0x4200,
+    PTRACE_GETEVENTMSG = 0x4201,
+    PTRACE_GETSIGINFO = 0x4202,
+    PTRACE_SETSIGINFO = 0x4203,
+    PTRACE_GETREGSET = 0x4204,
+    PTRACE_SETREGSET = 0x4205,
+    PTRACE_SEIZE = 0x4206,
+    PTRACE_INTERRUPT = 0x4207,
+    PTRACE_LISTEN = 0x4208,
+}
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct iovec_t {
+    base: Vaddr,
+    len: usize,
+}
+
+/// Gets the thread that the current process is going to attach to.
+fn get_thread_to_attach(tid: Tid, ctx: &Context) -> Result<Arc<Thread>> {
+    let thread = ctx
+        .process
+        .pid_ns()
+        .get_thread(tid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the thread does not exist"))?;
+    let posix_thread = thread.as_posix_thread().unwrap();
+
+    if Arc::ptr_eq(&posix_thread.process(), &ctx.posix_thread.process()) {
+        return_errno_with_message!(Errno::EPERM, "a process cannot trace itself");
+    }
+
+    // The tracer must be privileged, or have the same user and group IDs as the tracee.
+    let tracer_credentials = ctx.posix_thread.credentials();
+    if tracer_credentials.euid().is_root() {
+        return Ok(thread);
+    }
+
+    let tracee_credentials = posix_thread.credentials();
+    let uid = tracer_credentials.ruid();
+    let gid = tracer_credentials.rgid();
+    if [
+        tracee_credentials.ruid(),
+        tracee_credentials.euid(),
+        tracee_credentials.suid(),
+    ]
+    .iter()
+    .any(|tracee_uid| *tracee_uid != uid)
+        || [
+            tracee_credentials.rgid(),
+            tracee_credentials.egid(),
+            tracee_credentials.sgid(),
+        ]
+        .iter()
+        .any(|tracee_gid| *tracee_gid != gid)
+    {
+        return_errno_with_message!(Errno::EPERM, "tracing the thread is not allowed");
+    }
+
+    if !posix_thread.process().is_dumpable() {
+        return_errno_with_message!(Errno::EPERM, "the process is not dumpable");
+    }
+
+    Ok(thread)
+}
+
+/// Gets the thread traced by the current process.
+fn get_tracee(tid: Tid, ctx: &Context) -> Result<Arc<Thread>> {
+    ctx.process
+        .pid_ns()
+        .get_thread(tid)
+        .filter(|thread| {
+            thread
+                .as_posix_thread()
+                .unwrap()
+                .tracee()
+                .is_traced_by(ctx.process)
+        })
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the thread is not traced by the caller"))
+}
+
+fn parse_signal(data: u64) -> Result<Option<SigNum>> {
+    if data == 0 {
+        return Ok(None);
+    }
+
+    u8::try_from(data)
+        .ok()
+        .and_then(|sig_num| SigNum::try_from(sig_num).ok())
+        .map(Some)
+        .ok_or_else(|| Error::with_message(Errno::EIO, "invalid signal number"))
+}
+
+/// Reads a word from the `user` struct of the tracee.
+fn read_user_area(tracee: &Tracee, offset: usize) -> Result<u64> {
+    if offset % size_of::<u64>() != 0 {
+        return_errno_with_message!(Errno::EIO, "the offset is not aligned");
+    }
+
+    if offset < size_of::<UserRegs>() {
+        let regs = tracee.with_user_ctx(|user_ctx, orig_syscall_num| {
+            UserRegs::new(user_ctx, *orig_syscall_num)
+        })?;
+        let bytes = &regs.as_bytes()[offset..offset + size_of::<u64>()];
+        return Ok(u64::from_ne_bytes(bytes.try_into().unwrap()));
+    }
+
+    // Debug registers are not supported. Report them as zeros.
+    if (USER_DEBUGREG_OFFSET..USER_DEBUGREG_OFFSET + 8 * size_of::<u64>()).contains(&offset) {
+        return Ok(0);
+    }
+
+    return_errno_with_message!(Errno::EIO, "the offset is not supported");
+}
+
+/// Writes a word to the `user` struct of the tracee.
+fn write_user_area(tracee: &Tracee, offset: usize, word: u64) -> Result<()> {
+    if offset % size_of::<u64>() != 0 || offset >= size_of::<UserRegs>() {
+        return_errno_with_message!(Errno::EIO, "the offset is not supported");
+    }
+
+    tracee.with_user_ctx(|user_ctx, orig_syscall_num| {
+        let mut regs = UserRegs::new(user_ctx, *orig_syscall_num);
+        regs.as_bytes_mut()[offset..offset + size_of::<u64>()].copy_from_slice(&word.to_ne_bytes());
+        regs.write_to(user_ctx, orig_syscall_num)
+    })?
+}
@@ -14,7 +14,7 @@ pub fn sys_recvmsg(
     flags: i32,
     ctx: &Context,
 ) -> Result<SyscallReturn> {
-    let c_user_msghdr: CUserMsgHdr = ctx.user_space().read_val(user_msghdr_ptr)?;
+    let mut c_user_msghdr: CUserMsgHdr = ctx.user_space().read_val(user_msghdr_ptr)?;
     let flags = SendRecvFlags::from_bits_truncate(flags);
 
     debug!(
@@ -22,7 +22,7 @@ pub fn sys_recvmsg(
         sockfd, c_user_msghdr, flags
     );
 
-    let (total_bytes, message_header) = {
+    let (total_bytes, mut message_header) = {
         let socket = get_socket_from_fd(sockfd)?;
         let mut io_vec_writer = c_user_msghdr.copy_writer_array_from_user(ctx)?;
         socket
@@ -34,13 +34,14 @@ pub fn sys_recvmsg(
             })?
     };
 
-    if let Some(addr) = message_header.addr() {
-        c_user_msghdr.write_socket_addr_to_user(addr)?;
-    }
-
-    if c_user_msghdr.msg_control != 0 {
-        warn!("receiving control message is not supported");
-    }
+    c_user_msghdr.write_socket_addr_to_user(message_header.addr())?;
+    c_user_msghdr.write_control_messages_to_user(
+        message_header.take_control_messages(),
+        flags,
+        ctx,
+    )?;
+    ctx.user_space()
+        .write_val(user_msghdr_ptr, &c_user_msghdr)?;
 
     Ok(SyscallReturn::Return(total_bytes as _))
 }
@@ -5,7 +5,7 @@ use core::{cmp, mem};
 use ostd::cpu::{num_cpus, CpuId, CpuSet};
 
 use super::SyscallReturn;
-use crate::{prelude::*, process::posix_thread::thread_table, thread::Tid};
+use crate::{prelude::*, thread::Tid};
 
 pub fn sys_sched_getaffinity(
     tid: Tid,
@@ -15,7 +15,7 @@ pub fn sys_sched_getaffinity(
 ) -> Result<SyscallReturn> {
     let cpu_set = match tid {
         0 => ctx.thread.atomic_cpu_affinity().load(),
-        _ => match thread_table::get_thread(tid) {
+        _ => match ctx.process.pid_ns().get_thread(tid) {
             Some(thread) => thread.atomic_cpu_affinity().load(),
             None => return Err(Error::with_message(Errno::ESRCH, "thread does not exist")),
         },
@@ -40,7 +40,7 @@ pub fn sys_sched_setaffinity(
 
     match tid {
         0 => ctx.thread.atomic_cpu_affinity().store(&user_cpu_set),
-        _ => match thread_table::get_thread(tid) {
+        _ => match ctx.process.pid_ns().get_thread(tid) {
             Some(thread) => {
                 thread.atomic_cpu_affinity().store(&user_cpu_set);
             }
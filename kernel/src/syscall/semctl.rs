@@ -3,12 +3,8 @@
 use super::SyscallReturn;
 use crate::{
     ipc::{
-        semaphore::system_v::{
-            sem::Semaphore,
-            sem_set::{check_sem, sem_sets, sem_sets_mut, SemaphoreSet},
-            PermissionMode,
-        },
-        IpcControlCmd,
+        semaphore::system_v::{sem::Semaphore, sem_set::SemaphoreSet, PermissionMode},
+        IpcControlCmd, IpcNamespace,
     },
     prelude::*,
     process::Pid,
@@ -31,21 +27,19 @@ pub fn sys_semctl(
         semid, semnum, cmd, arg
     );
 
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+
     match cmd {
         IpcControlCmd::IPC_RMID => {
-            let mut sem_sets_mut = sem_sets_mut();
-            let sem_set = sem_sets_mut.get(&semid).ok_or(Error::new(Errno::EINVAL))?;
-
-            let euid = ctx.posix_thread.credentials().euid();
-            let permission = sem_set.permission();
-            let can_removed = (euid == permission.uid()) || (euid == permission.cuid());
-            if !can_removed {
-                return_errno!(Errno::EPERM);
-            }
-
-            sem_sets_mut
-                .remove(&semid)
-                .ok_or(Error::new(Errno::EINVAL))?;
+            ipc_ns.sem_table().remove_sem_set(semid, |sem_set| {
+                let euid = ctx.posix_thread.credentials().euid();
+                let permission = sem_set.permission();
+                let can_removed = (euid == permission.uid()) || (euid == permission.cuid());
+                if !can_removed {
+                    return_errno!(Errno::EPERM);
+                }
+                Ok(())
+            })?;
         }
         IpcControlCmd::SEM_SETVAL => {
             // In setval, arg is parse as i32
@@ -54,7 +48,7 @@ pub fn sys_semctl(
                 return_errno!(Errno::ERANGE);
             }
 
-            check_and_ctl(semid, PermissionMode::ALTER, |sem_set| {
+            check_and_ctl(&ipc_ns, semid, PermissionMode::ALTER, |sem_set| {
                 sem_set.setval(semnum as usize, val, ctx.process.pid())
             })?;
         }
@@ -62,7 +56,7 @@ pub fn sys_semctl(
             fn sem_val(sem: &Semaphore) -> i32 {
                 sem.val()
             }
-            let val: i32 = check_and_ctl(semid, PermissionMode::READ, |sem_set| {
+            let val: i32 = check_and_ctl(&ipc_ns, semid, PermissionMode::READ, |sem_set| {
                 sem_set.get(semnum as usize, &sem_val)
             })?;
 
@@ -72,21 +66,22 @@ pub fn sys_semctl(
             fn sem_pid(sem: &Semaphore) -> Pid {
                 sem.latest_modified_pid()
             }
-            let pid: Pid = check_and_ctl(semid, PermissionMode::READ, |sem_set| {
+            let pid: Pid = check_and_ctl(&ipc_ns, semid, PermissionMode::READ, |sem_set| {
                 sem_set.get(semnum as usize, &sem_pid)
             })?;
 
+            let pid = ctx.process.pid_ns().local_id_or_zero(pid);
             return Ok(SyscallReturn::Return(pid as isize));
         }
         IpcControlCmd::SEM_GETZCNT => {
-            let cnt: usize = check_and_ctl(semid, PermissionMode::READ, |sem_set| {
+            let cnt: usize = check_and_ctl(&ipc_ns, semid, PermissionMode::READ, |sem_set| {
                 Ok(sem_set.pending_const_count(semnum as u16))
             })?;
 
             return Ok(SyscallReturn::Return(cnt as isize));
         }
         IpcControlCmd::SEM_GETNCNT => {
-            let cnt: usize = check_and_ctl(semid, PermissionMode::READ, |sem_set| {
+            let cnt: usize = check_and_ctl(&ipc_ns, semid, PermissionMode::READ, |sem_set| {
                 Ok(sem_set.pending_alter_count(semnum as u16))
             })?;
 
@@ -98,12 +93,18 @@ pub fn sys_semctl(
     Ok(SyscallReturn::Return(0))
 }
 
-fn check_and_ctl<T, F>(semid: i32, permission: PermissionMode, ctl_func: F) -> Result<T>
+fn check_and_ctl<T, F>(
+    ipc_ns: &IpcNamespace,
+    semid: i32,
+    permission: PermissionMode,
+    ctl_func: F,
+) -> Result<T>
 where
     F: FnOnce(&SemaphoreSet) -> Result<T>,
 {
-    check_sem(semid, None, permission)?;
-    let sem_sets = sem_sets();
+    let sem_table = ipc_ns.sem_table();
+    sem_table.check_sem(semid, None, permission)?;
+    let sem_sets = sem_table.sem_sets();
     let sem_set = sem_sets.get(&semid).ok_or(Error::new(Errno::EINVAL))?;
     ctl_func.call_once((sem_set,))
 }
@@ -28,7 +28,7 @@ pub fn sys_sendto(
 
     let socket = get_socket_from_fd(sockfd)?;
 
-    let message_header = MessageHeader::new(socket_addr, None);
+    let message_header = MessageHeader::new(socket_addr, Vec::new());
 
     let mut reader = {
         let vm_space = ctx.process.root_vmar().vm_space();
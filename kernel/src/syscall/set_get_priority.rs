@@ -86,6 +86,7 @@ fn get_processes(prio_target: PriorityTarget) -> Result<Vec<Arc<Process>>> {
     })
 }
 
+/// The target of the priority operations, where the IDs are the global ones.
 #[derive(Debug)]
 enum PriorityTarget {
     Process(Pid),
@@ -102,7 +103,7 @@ impl PriorityTarget {
                 let pid = if who == 0 {
                     ctx.process.pid()
                 } else {
-                    who as Pid
+                    global_id(who, ctx)?
                 };
                 Self::Process(pid)
             }
@@ -110,7 +111,7 @@ impl PriorityTarget {
                 let pgid = if who == 0 {
                     ctx.process.pgid()
                 } else {
-                    who as Pgid
+                    global_id(who, ctx)?
                 };
                 Self::ProcessGroup(pgid)
             }
@@ -126,6 +127,14 @@ impl PriorityTarget {
     }
 }
 
+/// Translates the ID in the PID namespace of the current process to the global ID.
+fn global_id(id: u32, ctx: &Context) -> Result<Pid> {
+    ctx.process
+        .pid_ns()
+        .global_id(id)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process (group) does not exist"))
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Clone, Debug, TryFromInt)]
 #[repr(i32)]
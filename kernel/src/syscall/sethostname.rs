@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{credentials::capabilities::CapSet, namespace::UTS_FIELD_LEN},
+};
+
+pub fn sys_sethostname(addr: Vaddr, len: usize, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("addr = 0x{:x}, len = {}", addr, len);
+
+    let hostname = read_uts_field(addr, len, ctx)?;
+    ctx.posix_thread
+        .ns_proxy()
+        .lock()
+        .uts_ns()
+        .set_hostname(&hostname)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_setdomainname(addr: Vaddr, len: usize, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("addr = 0x{:x}, len = {}", addr, len);
+
+    let domainname = read_uts_field(addr, len, ctx)?;
+    ctx.posix_thread
+        .ns_proxy()
+        .lock()
+        .uts_ns()
+        .set_domainname(&domainname)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Reads a new value of a UTS field from the user space.
+///
+/// The value is not required to be NUL-terminated.
+fn read_uts_field(addr: Vaddr, len: usize, ctx: &Context) -> Result<Vec<u8>> {
+    // A negative `len` is also rejected here since it becomes a huge `usize`.
+    if len >= UTS_FIELD_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the name is too long");
+    }
+
+    if !ctx
+        .posix_thread
+        .credentials()
+        .effective_capset()
+        .contains(CapSet::SYS_ADMIN)
+    {
+        return_errno_with_message!(Errno::EPERM, "`CAP_SYS_ADMIN` is required");
+    }
+
+    let mut buffer = vec![0u8; len];
+    ctx.user_space()
+        .read_bytes(addr, &mut VmWriter::from(buffer.as_mut_slice()))?;
+    Ok(buffer)
+}
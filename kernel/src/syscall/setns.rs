@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{file_table::FileDesc, inode_handle::InodeHandle, procfs::namespace_of_inode},
+    prelude::*,
+    process::{namespace::enter_namespace, CloneFlags},
+};
+
+pub fn sys_setns(fd: FileDesc, nstype: i32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("fd = {}, nstype = {:#x}", fd, nstype);
+
+    let ns = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        let file = file_table.get_file(fd)?;
+        file.downcast_ref::<InodeHandle>()
+            .and_then(|inode_handle| namespace_of_inode(inode_handle.dentry().inode()))
+            .ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "the file does not refer to a namespace")
+            })?
+    };
+
+    // If `nstype` is zero, any type of namespace can be joined. Otherwise,
+    // the namespace must be of the specified type.
+    if nstype != 0 && CloneFlags::from_bits(nstype as u32) != Some(ns.clone_flag()) {
+        return_errno_with_message!(Errno::EINVAL, "the namespace type does not match");
+    }
+
+    enter_namespace(&ns, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -8,10 +8,23 @@ use crate::{
 
 pub fn sys_setpgid(pid: Pid, pgid: Pgid, ctx: &Context) -> Result<SyscallReturn> {
     let current = ctx.process;
+    let pid_ns = current.pid_ns();
     // if pid is 0, pid should be the pid of current process
-    let pid = if pid == 0 { current.pid() } else { pid };
+    let pid = if pid == 0 {
+        current.pid()
+    } else {
+        pid_ns
+            .global_id(pid)
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "process does not exist"))?
+    };
     // if pgid is 0, pgid should be pid
-    let pgid = if pgid == 0 { pid } else { pgid };
+    let pgid = if pgid == 0 {
+        pid
+    } else {
+        pid_ns
+            .global_id(pgid)
+            .ok_or_else(|| Error::with_message(Errno::EPERM, "process group must exist"))?
+    };
     debug!("pid = {}, pgid = {}", pid, pgid);
 
     if pid != current.pid() && !current.has_child(&pid) {
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use align_ext::AlignExt;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::shm::ShmFlags,
+    prelude::*,
+    vm::{perms::VmPerms, vmar::is_userspace_vaddr},
+};
+
+/// The alignment of the attach addresses.
+const SHMLBA: usize = PAGE_SIZE;
+
+pub fn sys_shmat(shmid: i32, shmaddr: Vaddr, shmflg: i32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!(
+        "[sys_shmat] shmid = {}, shmaddr = 0x{:x}, shmflg = 0x{:x}",
+        shmid, shmaddr, shmflg
+    );
+
+    let flags = ShmFlags::from_bits_truncate(shmflg as u32);
+
+    let addr = if shmaddr % SHMLBA == 0 {
+        shmaddr
+    } else if flags.contains(ShmFlags::SHM_RND) && shmaddr >= SHMLBA {
+        shmaddr.align_down(SHMLBA)
+    } else {
+        return_errno_with_message!(Errno::EINVAL, "the address is not aligned");
+    };
+    if addr == 0 && flags.contains(ShmFlags::SHM_REMAP) {
+        return_errno_with_message!(Errno::EINVAL, "SHM_REMAP requires an address");
+    }
+
+    let (perms, access) = {
+        let (mut perms, mut access) = (VmPerms::READ, 0o4);
+        if !flags.contains(ShmFlags::SHM_RDONLY) {
+            perms |= VmPerms::WRITE;
+            access |= 0o2;
+        }
+        if flags.contains(ShmFlags::SHM_EXEC) {
+            perms |= VmPerms::EXEC;
+            access |= 0o1;
+        }
+        (perms, access)
+    };
+
+    let credentials = ctx.posix_thread.credentials();
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+    let attachment = ipc_ns
+        .shm_table()
+        .attach(shmid, &credentials, access, ctx.process.pid())?;
+
+    let segment = attachment.segment().clone();
+    let size = segment.size().align_up(PAGE_SIZE);
+    if addr != 0
+        && !(is_userspace_vaddr(addr)
+            && addr
+                .checked_add(size)
+                .is_some_and(|end| is_userspace_vaddr(end - 1)))
+    {
+        return_errno_with_message!(Errno::EINVAL, "the address range is invalid");
+    }
+
+    let root_vmar = ctx.process.root_vmar();
+    let mut options = root_vmar
+        .new_map(size, perms)?
+        .vmo(segment.vmo().dup()?)
+        .is_shared(true)
+        .shm(attachment);
+    if addr != 0 {
+        options = options
+            .offset(addr)
+            .can_overwrite(flags.contains(ShmFlags::SHM_REMAP));
+    }
+
+    let map_addr = options.build().map_err(|err| {
+        // Unlike `mmap`, `shmat` reports `EINVAL` if the region is occupied.
+        if err.error() == Errno::EACCES {
+            Error::with_message(Errno::EINVAL, "the region is already occupied")
+        } else {
+            err
+        }
+    })?;
+
+    Ok(SyscallReturn::Return(map_addr as _))
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{ipc64_perm, IpcControlCmd, IPC_64},
+    prelude::*,
+    process::{Gid, Uid},
+};
+
+pub fn sys_shmctl(shmid: i32, cmd: i32, buf: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let cmd = IpcControlCmd::try_from(cmd & !IPC_64)?;
+    debug!(
+        "[sys_shmctl] shmid = {}, cmd = {:?}, buf = 0x{:x}",
+        shmid, cmd, buf
+    );
+
+    let credentials = ctx.posix_thread.credentials();
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+    let shm_table = ipc_ns.shm_table();
+
+    match cmd {
+        IpcControlCmd::IPC_STAT => {
+            let segment = shm_table.get(shmid)?;
+            segment.check_access(&credentials, 0o4)?;
+
+            let status = segment.status();
+            let pid_ns = ctx.process.pid_ns();
+            let shmid_ds = shmid64_ds {
+                shm_perm: ipc64_perm::from(&status.permission),
+                shm_segsz: status.size as u64,
+                shm_atime: status.atime as i64,
+                shm_dtime: status.dtime as i64,
+                shm_ctime: status.ctime as i64,
+                shm_cpid: pid_ns.local_id_or_zero(status.cpid) as i32,
+                shm_lpid: pid_ns.local_id_or_zero(status.lpid) as i32,
+                shm_nattch: status.nattch as u64,
+                __unused4: 0,
+                __unused5: 0,
+            };
+            ctx.user_space().write_val(buf, &shmid_ds)?;
+        }
+        IpcControlCmd::IPC_SET => {
+            let shmid_ds = ctx.user_space().read_val::<shmid64_ds>(buf)?;
+            let perm = &shmid_ds.shm_perm;
+
+            let segment = shm_table.get(shmid)?;
+            segment.set_owner_and_mode(
+                &credentials,
+                Uid::new(perm.uid),
+                Gid::new(perm.gid),
+                perm.mode as u16,
+            )?;
+        }
+        IpcControlCmd::IPC_RMID => {
+            shm_table.remove(shmid, &credentials)?;
+        }
+        _ => {
+            return_errno_with_message!(Errno::EINVAL, "the command is not supported");
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// The status of a shared memory segment in the user space (i.e., `struct shmid64_ds`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct shmid64_ds {
+    shm_perm: ipc64_perm,
+    shm_segsz: u64,
+    shm_atime: i64,
+    shm_dtime: i64,
+    shm_ctime: i64,
+    shm_cpid: i32,
+    shm_lpid: i32,
+    shm_nattch: u64,
+    __unused4: u64,
+    __unused5: u64,
+}
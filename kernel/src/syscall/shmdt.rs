@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::prelude::*;
+
+pub fn sys_shmdt(shmaddr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("[sys_shmdt] shmaddr = 0x{:x}", shmaddr);
+
+    if shmaddr % PAGE_SIZE != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the address is not aligned");
+    }
+
+    ctx.process.root_vmar().remove_shm_mapping(shmaddr)?;
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{ipc::IpcFlags, prelude::*};
+
+pub fn sys_shmget(key: i32, size: usize, shmflg: i32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!(
+        "[sys_shmget] key = {}, size = {}, shmflg = 0x{:x}",
+        key, size, shmflg
+    );
+
+    let flags = IpcFlags::from_bits_truncate(shmflg as u32);
+    let mode = (shmflg as u32 & 0o777) as u16;
+    let credentials = ctx.posix_thread.credentials();
+    let ipc_ns = ctx.posix_thread.ns_proxy().lock().ipc_ns().clone();
+
+    let shmid = ipc_ns.shm_table().get_or_create(
+        key,
+        size,
+        flags,
+        mode,
+        &credentials,
+        ctx.process.pid(),
+    )?;
+
+    Ok(SyscallReturn::Return(shmid as _))
+}
@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! `signalfd()` creates a file descriptor (we name it as `SignalFile`)
+//! that can be used to accept the signals targeted at the caller.
+//!
+//! Reading from `SignalFile` dequeues the pending signals in the mask of the file,
+//! so the signals should be blocked to prevent them from being handled
+//! according to the default dispositions.
+//!
+//! For more detailed information about this syscall,
+//! refer to the man 2 signalfd documentation.
+//!
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::SyscallReturn;
+use crate::{
+    events::{IoEvents, Observer},
+    fs::{
+        file_handle::FileLike,
+        file_table::{FdFlags, FileDesc},
+        utils::{CreationFlags, InodeMode, Metadata, StatusFlags},
+    },
+    prelude::*,
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{
+            c_types::siginfo_t,
+            constants::{SIGKILL, SIGSTOP},
+            sig_mask::SigMask,
+            PollHandle, Pollable, Pollee, SigEvents, SigEventsFilter,
+        },
+    },
+    thread::Thread,
+};
+
+pub fn sys_signalfd(
+    fd: FileDesc,
+    mask_ptr: Vaddr,
+    sizemask: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    sys_signalfd4(fd, mask_ptr, sizemask, 0, ctx)
+}
+
+pub fn sys_signalfd4(
+    fd: FileDesc,
+    mask_ptr: Vaddr,
+    sizemask: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd = {}, mask_ptr = 0x{:x}, sizemask = {}, flags = 0x{:x}",
+        fd, mask_ptr, sizemask, flags
+    );
+
+    if sizemask != size_of::<SigMask>() {
+        return_errno_with_message!(Errno::EINVAL, "sigset size is not equal to 8");
+    }
+    let flags = Flags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+
+    let mut mask = ctx.user_space().read_val::<SigMask>(mask_ptr)?;
+    // According to man pages, "it is not possible to receive SIGKILL or SIGSTOP signals via a
+    // signalfd file descriptor; these signals are silently ignored if specified in mask."
+    mask -= SIGKILL;
+    mask -= SIGSTOP;
+
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
+
+    if fd >= 0 {
+        let file = file_table.get_file(fd)?;
+        let signal_file = file
+            .downcast_ref::<SignalFile>()
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "not signalfd file"))?;
+        signal_file.set_mask(mask);
+        return Ok(SyscallReturn::Return(fd as _));
+    }
+    if fd != -1 {
+        return_errno_with_message!(Errno::EBADF, "invalid file descriptor");
+    }
+
+    let signal_file = SignalFile::new(
+        mask,
+        flags.contains(Flags::SFD_NONBLOCK),
+        &current_thread!(),
+    );
+    let fd_flags = if flags.contains(Flags::SFD_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+    let fd = file_table.insert(signal_file, fd_flags);
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+bitflags! {
+    struct Flags: u32 {
+        const SFD_CLOEXEC = CreationFlags::O_CLOEXEC.bits();
+        const SFD_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+    }
+}
+
+struct SignalFile {
+    mask: Mutex<SigMask>,
+    pollee: Pollee,
+    is_nonblocking: AtomicBool,
+    /// The thread that creates the file.
+    ///
+    /// The file observes the signal queue of the thread, so that the pollers can be notified
+    /// when signals arrive.
+    thread: Weak<Thread>,
+    this: Weak<SignalFile>,
+}
+
+impl SignalFile {
+    fn new(mask: SigMask, is_nonblocking: bool, thread: &Arc<Thread>) -> Arc<Self> {
+        let signal_file = Arc::new_cyclic(|weak_self| Self {
+            mask: Mutex::new(mask),
+            pollee: Pollee::new(),
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            thread: Arc::downgrade(thread),
+            this: weak_self.clone(),
+        });
+        signal_file.register_observer(mask);
+        signal_file
+    }
+
+    fn observer(&self) -> Weak<dyn Observer<SigEvents>> {
+        self.this.clone() as _
+    }
+
+    /// Registers the file as an observer of the signals in the mask.
+    ///
+    /// If the file has been registered, the filter will be updated.
+    fn register_observer(&self, mask: SigMask) {
+        let Some(thread) = self.thread.upgrade() else {
+            return;
+        };
+
+        // Signals that are _not_ in the filter will be reported.
+        let filter = SigEventsFilter::new(SigMask::new_full() - mask);
+        thread
+            .as_posix_thread()
+            .unwrap()
+            .register_sigqueue_observer(self.observer(), filter);
+    }
+
+    fn set_mask(&self, new_mask: SigMask) {
+        *self.mask.lock() = new_mask;
+        self.register_observer(new_mask);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let mask = *self.mask.lock();
+        let current = current_thread!();
+        let pending = current.as_posix_thread().unwrap().sig_pending();
+
+        if (pending & mask).is_empty() {
+            IoEvents::empty()
+        } else {
+            IoEvents::IN
+        }
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        // The signals that are not in the mask must be left in the queue.
+        let blocked = SigMask::new_full() - *self.mask.lock();
+        let current = current_thread!();
+        let posix_thread = current.as_posix_thread().unwrap();
+
+        let mut read_len = 0;
+        while writer.avail() >= size_of::<c_signalfd_siginfo>() {
+            let Some(signal) = posix_thread.dequeue_signal(&blocked) else {
+                break;
+            };
+
+            writer.write_val(&c_signalfd_siginfo::from(signal.to_info()))?;
+            read_len += size_of::<c_signalfd_siginfo>();
+        }
+
+        if read_len == 0 {
+            return_errno_with_message!(Errno::EAGAIN, "no signals are pending");
+        }
+
+        self.pollee.invalidate();
+
+        Ok(read_len)
+    }
+}
+
+impl Observer<SigEvents> for SignalFile {
+    fn on_events(&self, _events: &SigEvents) {
+        self.pollee.notify(IoEvents::IN);
+    }
+}
+
+impl Drop for SignalFile {
+    fn drop(&mut self) {
+        let Some(thread) = self.thread.upgrade() else {
+            return;
+        };
+
+        thread
+            .as_posix_thread()
+            .unwrap()
+            .unregister_sigqueue_observer(&self.observer());
+    }
+}
+
+impl Pollable for SignalFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for SignalFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        if writer.avail() < size_of::<c_signalfd_siginfo>() {
+            return_errno_with_message!(Errno::EINVAL, "the buffer is too small");
+        }
+
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            self.try_read(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_read(writer))
+        }
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "signalfd files do not support write");
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `SignalFile` to it.
+        Metadata::new_file(
+            0,
+            InodeMode::from_bits_truncate(0o600),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+}
+
+/// The signal information read from a signalfd file.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct c_signalfd_siginfo {
+    ssi_signo: u32,
+    ssi_errno: i32,
+    ssi_code: i32,
+    ssi_pid: u32,
+    ssi_uid: u32,
+    ssi_fd: i32,
+    ssi_tid: u32,
+    ssi_band: u32,
+    ssi_overrun: u32,
+    ssi_trapno: u32,
+    ssi_status: i32,
+    ssi_int: i32,
+    ssi_ptr: u64,
+    ssi_utime: u64,
+    ssi_stime: u64,
+    ssi_addr: u64,
+    ssi_addr_lsb: u16,
+    __pad2: u16,
+    ssi_syscall: i32,
+    ssi_call_addr: u64,
+    ssi_arch: u32,
+    __pad: [u8; 28],
+}
+
+impl From<siginfo_t> for c_signalfd_siginfo {
+    fn from(info: siginfo_t) -> Self {
+        let mut ssi = Self::new_zeroed();
+        ssi.ssi_signo = info.si_signo as u32;
+        ssi.ssi_errno = info.si_errno;
+        ssi.ssi_code = info.si_code;
+        ssi.ssi_pid = info.si_pid();
+        ssi.ssi_uid = info.si_uid().into();
+        ssi.ssi_addr = info.si_addr() as u64;
+        ssi
+    }
+}
@@ -4,8 +4,9 @@ use super::SyscallReturn;
 use crate::{
     fs::{file_handle::FileLike, file_table::FdFlags},
     net::socket::{
-        ip::{datagram::DatagramSocket, stream::StreamSocket},
-        unix::UnixStreamSocket,
+        ip::{datagram::DatagramSocket, stream::StreamSocket, IpFamily},
+        netlink::{NetlinkRouteSocket, NetlinkUeventSocket, StandardNetlinkProtocol},
+        unix::{UnixDatagramSocket, UnixStreamSocket},
         vsock::VsockStreamSocket,
     },
     prelude::*,
@@ -16,34 +17,59 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32, ctx: &Context) -> Resu
     let domain = CSocketAddrFamily::try_from(domain)?;
     let sock_type = SockType::try_from(type_ & SOCK_TYPE_MASK)?;
     let sock_flags = SockFlags::from_bits_truncate(type_ & !SOCK_TYPE_MASK);
-    let protocol = Protocol::try_from(protocol)?;
     debug!(
         "domain = {:?}, sock_type = {:?}, sock_flags = {:?}, protocol = {:?}",
         domain, sock_type, sock_flags, protocol
     );
     let nonblocking = sock_flags.contains(SockFlags::SOCK_NONBLOCK);
-    let file_like = match (domain, sock_type, protocol) {
+    let file_like = match (domain, sock_type) {
         // FIXME: SOCK_SEQPACKET is added to run fcntl_test, not supported yet.
-        (CSocketAddrFamily::AF_UNIX, SockType::SOCK_STREAM | SockType::SOCK_SEQPACKET, _) => {
+        (CSocketAddrFamily::AF_UNIX, SockType::SOCK_STREAM | SockType::SOCK_SEQPACKET) => {
             UnixStreamSocket::new(nonblocking) as Arc<dyn FileLike>
         }
-        (
-            CSocketAddrFamily::AF_INET,
-            SockType::SOCK_STREAM,
-            Protocol::IPPROTO_IP | Protocol::IPPROTO_TCP,
-        ) => StreamSocket::new(nonblocking) as Arc<dyn FileLike>,
-        (
-            CSocketAddrFamily::AF_INET,
-            SockType::SOCK_DGRAM,
-            Protocol::IPPROTO_IP | Protocol::IPPROTO_UDP,
-        ) => DatagramSocket::new(nonblocking) as Arc<dyn FileLike>,
-        (CSocketAddrFamily::AF_VSOCK, SockType::SOCK_STREAM, _) => {
+        (CSocketAddrFamily::AF_UNIX, SockType::SOCK_DGRAM) => {
+            UnixDatagramSocket::new(nonblocking) as Arc<dyn FileLike>
+        }
+        (CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6, _) => {
+            let family = if domain == CSocketAddrFamily::AF_INET {
+                IpFamily::Ipv4
+            } else {
+                IpFamily::Ipv6
+            };
+            match (sock_type, Protocol::try_from(protocol)?) {
+                (SockType::SOCK_STREAM, Protocol::IPPROTO_IP | Protocol::IPPROTO_TCP) => {
+                    StreamSocket::new(family, nonblocking) as Arc<dyn FileLike>
+                }
+                (SockType::SOCK_DGRAM, Protocol::IPPROTO_IP | Protocol::IPPROTO_UDP) => {
+                    DatagramSocket::new(family, nonblocking) as Arc<dyn FileLike>
+                }
+                _ => return_errno_with_message!(
+                    Errno::EPROTONOSUPPORT,
+                    "unsupported socket type or protocol"
+                ),
+            }
+        }
+        (CSocketAddrFamily::AF_NETLINK, SockType::SOCK_RAW | SockType::SOCK_DGRAM) => {
+            match StandardNetlinkProtocol::try_from(protocol) {
+                Ok(StandardNetlinkProtocol::ROUTE) => {
+                    NetlinkRouteSocket::new(nonblocking) as Arc<dyn FileLike>
+                }
+                Ok(StandardNetlinkProtocol::KOBJECT_UEVENT) => {
+                    NetlinkUeventSocket::new(nonblocking) as Arc<dyn FileLike>
+                }
+                _ => return_errno_with_message!(
+                    Errno::EPROTONOSUPPORT,
+                    "unsupported netlink protocol"
+                ),
+            }
+        }
+        (CSocketAddrFamily::AF_VSOCK, SockType::SOCK_STREAM) => {
             Arc::new(VsockStreamSocket::new(nonblocking)) as Arc<dyn FileLike>
         }
         _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "unsupported domain"),
     };
     let fd = {
-        let mut file_table = ctx.posix_thread.file_table().lock();
+        let mut file_table = ctx.posix_thread.file_table().lock_arc();
         let fd_flags = if sock_flags.contains(SockFlags::SOCK_CLOEXEC) {
             FdFlags::CLOEXEC
         } else {
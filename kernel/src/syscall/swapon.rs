@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::credentials::capabilities::CapSet,
+    syscall::constants::MAX_FILENAME_LEN,
+    vm::swap::{swap_off, swap_on},
+};
+
+pub fn sys_swapon(path_addr: Vaddr, flags: i32, ctx: &Context) -> Result<SyscallReturn> {
+    let path = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+    let swap_flags = SwapFlags::from_bits(flags as u32)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!("path = {:?}, flags = {:?}", path, swap_flags);
+
+    check_sys_admin(ctx)?;
+
+    // Discarding freed swap slots is only an optimization for SSDs, so the
+    // discard flags are ignored.
+    let priority = if swap_flags.contains(SwapFlags::SWAP_FLAG_PREFER) {
+        Some((flags as u32 & SWAP_FLAG_PRIO_MASK) as i16)
+    } else {
+        None
+    };
+
+    swap_on(&device_name(&path)?, priority)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_swapoff(path_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let path = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+    debug!("path = {:?}", path);
+
+    check_sys_admin(ctx)?;
+
+    let name = device_name(&path)?;
+    if aster_block::get_device(&name).is_none() {
+        return_errno_with_message!(Errno::ENOENT, "the block device does not exist");
+    }
+
+    swap_off(&name)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+fn check_sys_admin(ctx: &Context) -> Result<()> {
+    if !ctx
+        .posix_thread
+        .credentials()
+        .effective_capset()
+        .contains(CapSet::SYS_ADMIN)
+    {
+        return_errno_with_message!(Errno::EPERM, "`CAP_SYS_ADMIN` is required");
+    }
+    Ok(())
+}
+
+/// Returns the name of the block device.
+///
+/// Like `mount(2)`, the path is used as the name of the block device directly.
+fn device_name(path: &CStr) -> Result<String> {
+    let path = path.to_string_lossy();
+    if path.is_empty() {
+        return_errno_with_message!(Errno::ENOENT, "path is empty");
+    }
+    Ok(path.into_owned())
+}
+
+const SWAP_FLAG_PRIO_MASK: u32 = 0x7fff;
+
+bitflags! {
+    struct SwapFlags: u32 {
+        const SWAP_FLAG_PRIO_MASK     = SWAP_FLAG_PRIO_MASK;
+        const SWAP_FLAG_PREFER        = 0x8000;   // Set if swap priority is specified.
+        const SWAP_FLAG_DISCARD       = 0x10000;  // Enable discard for swap.
+        const SWAP_FLAG_DISCARD_ONCE  = 0x20000;  // Discard swap area at swapon-time.
+        const SWAP_FLAG_DISCARD_PAGES = 0x40000;  // Discard page-clusters after use.
+    }
+}
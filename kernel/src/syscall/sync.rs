@@ -1,9 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::SyscallReturn;
-use crate::prelude::*;
+use crate::{fs::path::MountNode, prelude::*};
 
 pub fn sys_sync(_ctx: &Context) -> Result<SyscallReturn> {
-    crate::fs::rootfs::root_mount().sync()?;
+    // Like Linux, sync all the mounted filesystems, not only the ones that are visible to the
+    // caller.
+    MountNode::sync_all()?;
     Ok(SyscallReturn::Return(0))
 }
@@ -4,7 +4,10 @@ use aster_time::read_monotonic_time;
 use ostd::mm::stat::{mem_available, mem_total};
 
 use super::SyscallReturn;
-use crate::prelude::*;
+use crate::{
+    prelude::*,
+    vm::swap::{swap_free, swap_total},
+};
 
 #[derive(Debug, Default, Clone, Copy, Pod)]
 #[repr(C)]
@@ -28,6 +31,8 @@ pub fn sys_sysinfo(sysinfo_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn>
         uptime: read_monotonic_time().as_secs() as i64,
         totalram: mem_total() as u64,
         freeram: mem_available() as u64,
+        totalswap: swap_total() as u64,
+        freeswap: swap_free() as u64,
         ..Default::default() // TODO: add other system information
     };
     ctx.user_space().write_val(sysinfo_addr, &info)?;
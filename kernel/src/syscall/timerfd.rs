@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! `timerfd_create()` creates a timer (we name it as `TimerfdFile`)
+//! that delivers timer expiration notifications via a file descriptor.
+//!
+//! `TimerfdFile` holds a u64 counter of the expirations since the last read.
+//! Reading from `TimerfdFile` returns the counter value and resets it.
+//! The read operation may be blocked if the timer has not expired yet.
+//!
+//! For more detailed information about this syscall,
+//! refer to the man 2 timerfd_create documentation.
+//!
+
+use core::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use super::SyscallReturn;
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        file_table::{FdFlags, FileDesc},
+        utils::{CreationFlags, InodeMode, Metadata, StatusFlags},
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    syscall::ClockId,
+    time::{
+        clockid_t,
+        clocks::{BootTimeClock, MonotonicClock, RealTimeClock},
+        itimerspec_t,
+        timer::{Timeout, Timer, TimerManager},
+        timespec_t,
+    },
+};
+
+pub fn sys_timerfd_create(clockid: clockid_t, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("clockid = {}, flags = 0x{:x}", clockid, flags);
+
+    let flags = Flags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+
+    let timer_manager = match ClockId::try_from(clockid)? {
+        ClockId::CLOCK_REALTIME => RealTimeClock::timer_manager(),
+        ClockId::CLOCK_MONOTONIC => MonotonicClock::timer_manager(),
+        ClockId::CLOCK_BOOTTIME => BootTimeClock::timer_manager(),
+        _ => return_errno_with_message!(Errno::EINVAL, "the clock is not supported by timerfd"),
+    };
+
+    let timerfd_file = TimerfdFile::new(timer_manager, flags.contains(Flags::TFD_NONBLOCK));
+    let fd_flags = if flags.contains(Flags::TFD_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+    let mut file_table = ctx.posix_thread.file_table().lock_arc();
+    let fd = file_table.insert(timerfd_file, fd_flags);
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+pub fn sys_timerfd_settime(
+    fd: FileDesc,
+    flags: u32,
+    new_itimerspec_addr: Vaddr,
+    old_itimerspec_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd = {}, flags = 0x{:x}, new_itimerspec_addr = 0x{:x}, old_itimerspec_addr = 0x{:x}",
+        fd, flags, new_itimerspec_addr, old_itimerspec_addr
+    );
+
+    let flags = SetTimeFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+
+    let user_space = ctx.user_space();
+    let new_itimerspec = user_space.read_val::<itimerspec_t>(new_itimerspec_addr)?;
+    let interval = Duration::try_from(new_itimerspec.it_interval)?;
+    let expire_time = Duration::try_from(new_itimerspec.it_value)?;
+
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(fd)?.clone()
+    };
+    let timerfd_file = file
+        .downcast_ref::<TimerfdFile>()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "not timerfd file"))?;
+
+    if old_itimerspec_addr != 0 {
+        user_space.write_val(old_itimerspec_addr, &timerfd_file.itimerspec())?;
+    }
+
+    // TODO: Support `TFD_TIMER_CANCEL_ON_SET`, which requires the notifications of the
+    // discontinuous changes of the real-time clock.
+    let timeout = if expire_time == Duration::ZERO {
+        None
+    } else if flags.contains(SetTimeFlags::TFD_TIMER_ABSTIME) {
+        Some(Timeout::When(expire_time))
+    } else {
+        Some(Timeout::After(expire_time))
+    };
+    timerfd_file.set_time(interval, timeout);
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_timerfd_gettime(
+    fd: FileDesc,
+    itimerspec_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("fd = {}, itimerspec_addr = 0x{:x}", fd, itimerspec_addr);
+
+    let file = {
+        let file_table = ctx.posix_thread.file_table().lock_arc();
+        file_table.get_file(fd)?.clone()
+    };
+    let timerfd_file = file
+        .downcast_ref::<TimerfdFile>()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "not timerfd file"))?;
+
+    ctx.user_space()
+        .write_val(itimerspec_addr, &timerfd_file.itimerspec())?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+bitflags! {
+    struct Flags: u32 {
+        const TFD_CLOEXEC = CreationFlags::O_CLOEXEC.bits();
+        const TFD_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+    }
+}
+
+bitflags! {
+    struct SetTimeFlags: u32 {
+        const TFD_TIMER_ABSTIME = 1 << 0;
+        const TFD_TIMER_CANCEL_ON_SET = 1 << 1;
+    }
+}
+
+struct TimerfdFile {
+    timer: Arc<Timer>,
+    /// The number of expirations since the last read.
+    ticks: AtomicU64,
+    pollee: Pollee,
+    is_nonblocking: AtomicBool,
+}
+
+impl TimerfdFile {
+    fn new(timer_manager: &Arc<TimerManager>, is_nonblocking: bool) -> Arc<Self> {
+        Arc::new_cyclic(|weak_self: &Weak<Self>| {
+            let weak_self = weak_self.clone();
+            // The callback runs in the interrupt context, so it must not sleep.
+            let timer = timer_manager.create_timer(move || {
+                if let Some(timerfd_file) = weak_self.upgrade() {
+                    timerfd_file.on_expired();
+                }
+            });
+
+            Self {
+                timer,
+                ticks: AtomicU64::new(0),
+                pollee: Pollee::new(),
+                is_nonblocking: AtomicBool::new(is_nonblocking),
+            }
+        })
+    }
+
+    fn on_expired(&self) {
+        self.ticks.fetch_add(1, Ordering::Relaxed);
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    /// Arms the timer with the timeout, or disarms it if `timeout` is `None`.
+    ///
+    /// The expirations that have not been read are discarded.
+    fn set_time(&self, interval: Duration, timeout: Option<Timeout>) {
+        self.timer.cancel();
+        self.ticks.store(0, Ordering::Relaxed);
+        self.pollee.invalidate();
+
+        self.timer.set_interval(interval);
+        if let Some(timeout) = timeout {
+            self.timer.set_timeout(timeout);
+        }
+    }
+
+    fn itimerspec(&self) -> itimerspec_t {
+        itimerspec_t {
+            it_interval: timespec_t::from(self.timer.interval()),
+            it_value: timespec_t::from(self.timer.remain()),
+        }
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.ticks.load(Ordering::Relaxed) > 0 {
+            IoEvents::IN
+        } else {
+            IoEvents::empty()
+        }
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let ticks = self.ticks.swap(0, Ordering::Relaxed);
+        if ticks == 0 {
+            return_errno_with_message!(Errno::EAGAIN, "the timer has not expired");
+        }
+
+        self.pollee.invalidate();
+
+        if let Err(err) = writer.write_val(&ticks) {
+            // Do not lose the expirations if the user buffer is invalid.
+            self.ticks.fetch_add(ticks, Ordering::Relaxed);
+            return Err(err.into());
+        }
+
+        Ok(size_of::<u64>())
+    }
+}
+
+impl Drop for TimerfdFile {
+    fn drop(&mut self) {
+        self.timer.cancel();
+    }
+}
+
+impl Pollable for TimerfdFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for TimerfdFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        if writer.avail() < size_of::<u64>() {
+            return_errno_with_message!(Errno::EINVAL, "the buffer is too small");
+        }
+
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            self.try_read(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_read(writer))
+        }
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "timerfd files do not support write");
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `TimerfdFile` to it.
+        Metadata::new_file(
+            0,
+            InodeMode::from_bits_truncate(0o600),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+}
@@ -30,7 +30,9 @@ pub fn sys_umount(path_addr: Vaddr, flags: u64, ctx: &Context) -> Result<Syscall
         ctx.posix_thread.fs().resolver().read().lookup(&fs_path)?
     };
 
-    target_dentry.unmount()?;
+    let mount_node = target_dentry.unmount()?;
+    // Like Linux, the filesystem is synced so that it is clean on the device.
+    mount_node.sync()?;
 
     Ok(SyscallReturn::Return(0))
 }
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{namespace::unshare_namespaces, CloneFlags},
+};
+
+pub fn sys_unshare(flags: u64, ctx: &Context) -> Result<SyscallReturn> {
+    let valid_flags = CloneFlags::CLONE_THREAD
+        | CloneFlags::CLONE_FS
+        | CloneFlags::CLONE_NEWNS
+        | CloneFlags::CLONE_SIGHAND
+        | CloneFlags::CLONE_VM
+        | CloneFlags::CLONE_FILES
+        | CloneFlags::CLONE_SYSVSEM
+        | CloneFlags::CLONE_NEWCGROUP
+        | CloneFlags::CLONE_NEWUTS
+        | CloneFlags::CLONE_NEWIPC
+        | CloneFlags::CLONE_NEWUSER
+        | CloneFlags::CLONE_NEWPID
+        | CloneFlags::CLONE_NEWNET;
+    let flags = u32::try_from(flags)
+        .ok()
+        .and_then(CloneFlags::from_bits)
+        .filter(|flags| valid_flags.contains(*flags))
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid unshare flags"))?;
+    debug!("flags = {:?}", flags);
+
+    let unsupported_ns_flags = flags
+        & (CloneFlags::CLONE_NEWCGROUP | CloneFlags::CLONE_NEWUSER | CloneFlags::CLONE_NEWNET);
+    if !unsupported_ns_flags.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "the namespaces are not supported");
+    }
+
+    // Unsharing the thread group, the signal handlers, or the address space
+    // is only possible if the process is single-threaded, in which case they
+    // are not shared at all.
+    if flags.intersects(CloneFlags::CLONE_THREAD | CloneFlags::CLONE_SIGHAND | CloneFlags::CLONE_VM)
+        && ctx.process.tasks().lock().as_slice().len() > 1
+    {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "cannot unshare resources of a multi-threaded process"
+        );
+    }
+
+    unshare_namespaces(flags, ctx)?;
+    if flags.contains(CloneFlags::CLONE_FS) {
+        ctx.posix_thread.unshare_fs();
+    }
+    if flags.contains(CloneFlags::CLONE_FILES) {
+        ctx.posix_thread.unshare_file_table();
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -0,0 +1,615 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! `userfaultfd()` creates a file descriptor (we name it as `UserfaultFile`)
+//! for handling page faults in the user space.
+//!
+//! After the API is negotiated by `ioctl(UFFDIO_API)`, memory ranges can be
+//! registered to the file by `ioctl(UFFDIO_REGISTER)`. The page faults in the
+//! ranges are then read from the file, and resolved by `ioctl(UFFDIO_COPY)`,
+//! `ioctl(UFFDIO_ZEROPAGE)`, or `ioctl(UFFDIO_WRITEPROTECT)`.
+//!
+//! For more detailed information about this syscall,
+//! refer to the man 2 userfaultfd documentation.
+//!
+
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use align_ext::AlignExt;
+use ostd::mm::{FrameAllocOptions, UFrame, VmIo, MAX_USERSPACE_VADDR};
+
+use super::SyscallReturn;
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        file_table::FdFlags,
+        utils::{CreationFlags, InodeMode, IoctlCmd, Metadata, StatusFlags},
+    },
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        signal::{PollHandle, Pollable},
+    },
+    vm::userfault::{Userfault, UserfaultCtx, UserfaultMode, UserfaultRegistration},
+};
+
+pub fn sys_userfaultfd(flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = Flags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!("flags = {:?}", flags);
+
+    // Like Linux with `vm.unprivileged_userfaultfd` set to 0, handling the
+    // page faults in the kernel mode requires `CAP_SYS_PTRACE`.
+    //
+    // TODO: The page faults in the kernel mode should not be handled by the
+    // user space if `UFFD_USER_MODE_ONLY` is specified.
+    if !flags.contains(Flags::UFFD_USER_MODE_ONLY)
+        && !ctx
+            .posix_thread
+            .credentials()
+            .effective_capset()
+            .contains(CapSet::SYS_PTRACE)
+    {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "`CAP_SYS_PTRACE` is required to handle kernel-mode page faults"
+        );
+    }
+
+    let userfault_file = UserfaultFile::new(
+        UserfaultCtx::new(&current!()),
+        flags.contains(Flags::O_NONBLOCK),
+    );
+    let fd_flags = if flags.contains(Flags::O_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+    let fd = ctx
+        .posix_thread
+        .file_table()
+        .lock()
+        .insert(Arc::new(userfault_file), fd_flags);
+
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+bitflags! {
+    struct Flags: u32 {
+        const UFFD_USER_MODE_ONLY = 1;
+        const O_CLOEXEC = CreationFlags::O_CLOEXEC.bits();
+        const O_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+    }
+}
+
+struct UserfaultFile {
+    ctx: Arc<UserfaultCtx>,
+    /// The features enabled by `ioctl(UFFDIO_API)`, or `None` if the API has
+    /// not been negotiated.
+    features: Mutex<Option<Features>>,
+    is_nonblocking: AtomicBool,
+}
+
+impl UserfaultFile {
+    fn new(ctx: Arc<UserfaultCtx>, is_nonblocking: bool) -> Self {
+        Self {
+            ctx,
+            features: Mutex::new(None),
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+        }
+    }
+
+    fn features(&self) -> Result<Features> {
+        let features = *self.features.lock();
+        features.ok_or_else(|| Error::with_message(Errno::EINVAL, "the API is not negotiated"))
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        // Like Linux, polling a blocking userfaultfd fails because a fault
+        // may be resolved before it is read, so the read may still block.
+        if self.features.lock().is_none() || !self.is_nonblocking.load(Ordering::Relaxed) {
+            return IoEvents::ERR;
+        }
+
+        if self.ctx.has_unread_faults() {
+            IoEvents::IN
+        } else {
+            IoEvents::empty()
+        }
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let features = self.features()?;
+
+        let mut read_len = 0;
+        while writer.avail() >= size_of::<uffd_msg>() {
+            let Some(fault) = self.ctx.read_fault() else {
+                break;
+            };
+
+            writer.write_val(&uffd_msg::new_pagefault(&fault, features))?;
+            read_len += size_of::<uffd_msg>();
+        }
+
+        if read_len == 0 {
+            return_errno_with_message!(Errno::EAGAIN, "no page faults are pending");
+        }
+
+        self.ctx.pollee().invalidate();
+
+        Ok(read_len)
+    }
+
+    fn api(&self, arg: Vaddr) -> Result<i32> {
+        let user_space = current_userspace!();
+        let mut api: uffdio_api = user_space.read_val(arg)?;
+
+        let mut features = self.features.lock();
+        if features.is_some() {
+            return_errno_with_message!(Errno::EINVAL, "the API has been negotiated");
+        }
+
+        let requested_features = Features::from_bits(api.features);
+        let Some(requested_features) = requested_features.filter(|_| api.api == UFFD_API) else {
+            user_space.write_val(arg, &uffdio_api::new_zeroed())?;
+            return_errno_with_message!(Errno::EINVAL, "the API or the features are invalid");
+        };
+
+        api.features = Features::all().bits();
+        api.ioctls = UFFD_API_IOCTLS;
+        user_space.write_val(arg, &api)?;
+
+        *features = Some(requested_features);
+        self.ctx.pollee().invalidate();
+        Ok(0)
+    }
+
+    fn register(&self, arg: Vaddr) -> Result<i32> {
+        let user_space = current_userspace!();
+        let mut register: uffdio_register = user_space.read_val(arg)?;
+
+        let range = register.range.to_range()?;
+        let mode = UserfaultMode::from_bits(register.mode)
+            .filter(|mode| !mode.is_empty())
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid register mode"))?;
+
+        self.ctx.process()?.root_vmar().register_userfault(
+            range,
+            UserfaultRegistration {
+                ctx: self.ctx.clone(),
+                mode,
+            },
+        )?;
+
+        register.ioctls = if mode.contains(UserfaultMode::WP) {
+            UFFD_API_RANGE_IOCTLS
+        } else {
+            UFFD_API_RANGE_IOCTLS & !(1 << UFFDIO_WRITEPROTECT_NR)
+        };
+        user_space.write_val(arg, &register)?;
+
+        Ok(0)
+    }
+
+    fn unregister(&self, arg: Vaddr) -> Result<i32> {
+        let range = current_userspace!()
+            .read_val::<uffdio_range>(arg)?
+            .to_range()?;
+
+        self.ctx
+            .process()?
+            .root_vmar()
+            .unregister_userfault(range, &self.ctx)?;
+
+        Ok(0)
+    }
+
+    fn wake(&self, arg: Vaddr) -> Result<i32> {
+        let range = current_userspace!()
+            .read_val::<uffdio_range>(arg)?
+            .to_range()?;
+
+        self.ctx.wake(&range);
+
+        Ok(0)
+    }
+
+    fn copy(&self, arg: Vaddr) -> Result<i32> {
+        let user_space = current_userspace!();
+        let mut copy: uffdio_copy = user_space.read_val(arg)?;
+
+        let range = uffdio_range {
+            start: copy.dst,
+            len: copy.len,
+        }
+        .to_range()?;
+        let src = copy.src as Vaddr;
+        if src.checked_add(range.len()).is_none() {
+            return_errno_with_message!(Errno::EINVAL, "the source range overflows");
+        }
+        let mode = CopyMode::from_bits(copy.mode)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid copy mode"))?;
+
+        let mut buf = vec![0u8; PAGE_SIZE];
+        let (copied, result) = self.fill_pages(&range, mode.contains(CopyMode::WP), |offset| {
+            user_space.read_bytes(src + offset, &mut VmWriter::from(buf.as_mut_slice()))?;
+            let frame: UFrame = FrameAllocOptions::new().zeroed(false).alloc_frame()?.into();
+            frame.write_bytes(0, &buf)?;
+            Ok(frame)
+        });
+
+        copy.copy = filled_or_errno(copied, &result);
+        user_space.write_val(arg, &copy)?;
+
+        self.finish_fill(&range, copied, result, mode.contains(CopyMode::DONTWAKE))
+    }
+
+    fn zeropage(&self, arg: Vaddr) -> Result<i32> {
+        let user_space = current_userspace!();
+        let mut zeropage: uffdio_zeropage = user_space.read_val(arg)?;
+
+        let range = zeropage.range.to_range()?;
+        let mode = ZeropageMode::from_bits(zeropage.mode)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid zeropage mode"))?;
+
+        let (zeroed, result) = self.fill_pages(&range, false, |_| {
+            Ok(FrameAllocOptions::new().alloc_frame()?.into())
+        });
+
+        zeropage.zeropage = filled_or_errno(zeroed, &result);
+        user_space.write_val(arg, &zeropage)?;
+
+        self.finish_fill(
+            &range,
+            zeroed,
+            result,
+            mode.contains(ZeropageMode::DONTWAKE),
+        )
+    }
+
+    fn write_protect(&self, arg: Vaddr) -> Result<i32> {
+        let write_protect: uffdio_writeprotect = current_userspace!().read_val(arg)?;
+
+        let range = write_protect.range.to_range()?;
+        let mode = WriteProtectMode::from_bits(write_protect.mode)
+            .filter(|mode| !mode.contains(WriteProtectMode::WP | WriteProtectMode::DONTWAKE))
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid write-protect mode"))?;
+        let is_wp = mode.contains(WriteProtectMode::WP);
+
+        self.ctx
+            .process()?
+            .root_vmar()
+            .set_userfault_wp(&self.ctx, range.clone(), is_wp)?;
+
+        if !is_wp && !mode.contains(WriteProtectMode::DONTWAKE) {
+            self.ctx.wake(&range);
+        }
+
+        Ok(0)
+    }
+
+    /// Fills the missing pages in `range` with the frames returned by
+    /// `new_frame`, which is called with the offset of each page.
+    ///
+    /// Returns the number of filled bytes and the error that stops filling,
+    /// if any.
+    fn fill_pages<F>(
+        &self,
+        range: &Range<Vaddr>,
+        is_wp: bool,
+        mut new_frame: F,
+    ) -> (usize, Result<()>)
+    where
+        F: FnMut(usize) -> Result<UFrame>,
+    {
+        let process = match self.ctx.process() {
+            Ok(process) => process,
+            Err(err) => return (0, Err(err)),
+        };
+        let root_vmar = process.root_vmar();
+
+        let mut filled = 0;
+        while filled < range.len() {
+            let result = new_frame(filled).and_then(|frame| {
+                root_vmar.fill_userfault_page(&self.ctx, range.start + filled, frame, is_wp)
+            });
+            if let Err(err) = result {
+                return (filled, Err(err));
+            }
+            filled += PAGE_SIZE;
+        }
+
+        (filled, Ok(()))
+    }
+
+    /// Wakes up the threads faulting on the filled pages, and returns the
+    /// result of the ioctl.
+    ///
+    /// Like Linux, if some pages are filled before an error occurs, the
+    /// error is replaced by [`EAGAIN`] so that the user space can retry the
+    /// rest of the range.
+    ///
+    /// [`EAGAIN`]: crate::error::Errno::EAGAIN
+    fn finish_fill(
+        &self,
+        range: &Range<Vaddr>,
+        filled: usize,
+        result: Result<()>,
+        is_dont_wake: bool,
+    ) -> Result<i32> {
+        if filled == 0 {
+            result?;
+        }
+
+        if !is_dont_wake {
+            self.ctx.wake(&(range.start..range.start + filled));
+        }
+
+        if filled != range.len() {
+            return_errno_with_message!(Errno::EAGAIN, "the range is partially filled");
+        }
+        Ok(0)
+    }
+}
+
+/// Returns the number of filled bytes, or the negated error number if no
+/// bytes are filled.
+fn filled_or_errno(filled: usize, result: &Result<()>) -> i64 {
+    match result {
+        Err(err) if filled == 0 => -(err.error() as i64),
+        _ => filled as i64,
+    }
+}
+
+impl Drop for UserfaultFile {
+    fn drop(&mut self) {
+        self.ctx.release();
+    }
+}
+
+impl Pollable for UserfaultFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.ctx
+            .pollee()
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for UserfaultFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        if writer.avail() < size_of::<uffd_msg>() {
+            return_errno_with_message!(Errno::EINVAL, "the buffer is too small");
+        }
+        self.features()?;
+
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            self.try_read(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_read(writer))
+        }
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "userfaultfd files do not support write");
+    }
+
+    fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
+        if !matches!(cmd, IoctlCmd::UFFDIO_API) {
+            self.features()?;
+        }
+
+        match cmd {
+            IoctlCmd::UFFDIO_API => self.api(arg),
+            IoctlCmd::UFFDIO_REGISTER => self.register(arg),
+            IoctlCmd::UFFDIO_UNREGISTER => self.unregister(arg),
+            IoctlCmd::UFFDIO_WAKE => self.wake(arg),
+            IoctlCmd::UFFDIO_COPY => self.copy(arg),
+            IoctlCmd::UFFDIO_ZEROPAGE => self.zeropage(arg),
+            IoctlCmd::UFFDIO_WRITEPROTECT => self.write_protect(arg),
+            _ => return_errno_with_message!(Errno::EINVAL, "the ioctl command is not supported"),
+        }
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        self.ctx.pollee().invalidate();
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `UserfaultFile` to it.
+        Metadata::new_file(
+            0,
+            InodeMode::from_bits_truncate(0o600),
+            aster_block::BLOCK_SIZE,
+        )
+    }
+}
+
+const UFFD_API: u64 = 0xaa;
+
+const UFFDIO_REGISTER_NR: u32 = 0x00;
+const UFFDIO_UNREGISTER_NR: u32 = 0x01;
+const UFFDIO_WAKE_NR: u32 = 0x02;
+const UFFDIO_COPY_NR: u32 = 0x03;
+const UFFDIO_ZEROPAGE_NR: u32 = 0x04;
+const UFFDIO_WRITEPROTECT_NR: u32 = 0x06;
+const UFFDIO_API_NR: u32 = 0x3f;
+
+/// The ioctls supported on a userfaultfd.
+const UFFD_API_IOCTLS: u64 =
+    1 << UFFDIO_REGISTER_NR | 1 << UFFDIO_UNREGISTER_NR | 1 << UFFDIO_API_NR;
+/// The ioctls supported on a registered range.
+const UFFD_API_RANGE_IOCTLS: u64 = 1 << UFFDIO_WAKE_NR
+    | 1 << UFFDIO_COPY_NR
+    | 1 << UFFDIO_ZEROPAGE_NR
+    | 1 << UFFDIO_WRITEPROTECT_NR;
+
+bitflags! {
+    /// The features of the userfaultfd API.
+    struct Features: u64 {
+        /// Report whether a fault is caused by writing a write-protected page.
+        const UFFD_FEATURE_PAGEFAULT_FLAG_WP = 1 << 0;
+        /// Report the ID of the faulting thread.
+        const UFFD_FEATURE_THREAD_ID         = 1 << 8;
+        /// Report the exact faulting address instead of the page address.
+        const UFFD_FEATURE_EXACT_ADDRESS     = 1 << 11;
+    }
+}
+
+bitflags! {
+    struct CopyMode: u64 {
+        const DONTWAKE = 1 << 0;
+        const WP       = 1 << 1;
+    }
+}
+
+bitflags! {
+    struct ZeropageMode: u64 {
+        const DONTWAKE = 1 << 0;
+    }
+}
+
+bitflags! {
+    struct WriteProtectMode: u64 {
+        const WP       = 1 << 0;
+        const DONTWAKE = 1 << 1;
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct uffdio_api {
+    api: u64,
+    features: u64,
+    ioctls: u64,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct uffdio_range {
+    start: u64,
+    len: u64,
+}
+
+impl uffdio_range {
+    /// Converts to a non-empty page-aligned range in the user space.
+    fn to_range(self) -> Result<Range<Vaddr>> {
+        let start = self.start as Vaddr;
+        let len = self.len as usize;
+        if start % PAGE_SIZE != 0 || len % PAGE_SIZE != 0 || len == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the range is empty or not page-aligned");
+        }
+
+        let Some(end) = start
+            .checked_add(len)
+            .filter(|end| *end <= MAX_USERSPACE_VADDR)
+        else {
+            return_errno_with_message!(Errno::EINVAL, "the range is out of the user space");
+        };
+
+        Ok(start..end)
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct uffdio_register {
+    range: uffdio_range,
+    mode: u64,
+    ioctls: u64,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct uffdio_copy {
+    dst: u64,
+    src: u64,
+    len: u64,
+    mode: u64,
+    copy: i64,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct uffdio_zeropage {
+    range: uffdio_range,
+    mode: u64,
+    zeropage: i64,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct uffdio_writeprotect {
+    range: uffdio_range,
+    mode: u64,
+}
+
+/// The message read from a userfaultfd.
+///
+/// Only page fault events are supported, so the union in the C definition is
+/// flattened into the fields of the page fault event.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct uffd_msg {
+    event: u8,
+    reserved1: u8,
+    reserved2: u16,
+    reserved3: u32,
+    flags: u64,
+    address: u64,
+    ptid: u32,
+    __pad: u32,
+}
+
+const UFFD_EVENT_PAGEFAULT: u8 = 0x12;
+
+const UFFD_PAGEFAULT_FLAG_WRITE: u64 = 1 << 0;
+const UFFD_PAGEFAULT_FLAG_WP: u64 = 1 << 1;
+
+impl uffd_msg {
+    fn new_pagefault(fault: &Userfault, features: Features) -> Self {
+        let mut msg = Self::new_zeroed();
+        msg.event = UFFD_EVENT_PAGEFAULT;
+
+        if fault.is_write {
+            msg.flags |= UFFD_PAGEFAULT_FLAG_WRITE;
+        }
+        if fault.is_wp {
+            msg.flags |= UFFD_PAGEFAULT_FLAG_WP;
+        }
+
+        msg.address = if features.contains(Features::UFFD_FEATURE_EXACT_ADDRESS) {
+            fault.address as u64
+        } else {
+            fault.address.align_down(PAGE_SIZE) as u64
+        };
+        if features.contains(Features::UFFD_FEATURE_THREAD_ID) {
+            msg.ptid = fault.tid;
+        }
+
+        msg
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
     fs::{
         file_table::FileDesc,
         fs_resolver::{FsPath, AT_FDCWD},
+        notify::FsEvents,
         path::Dentry,
     },
     prelude::*,
@@ -142,6 +143,7 @@ fn vfs_utimes(dentry: &Dentry, times: Option<TimeSpecPair>) -> Result<SyscallRet
     dentry.set_atime(atime);
     dentry.set_mtime(mtime);
     dentry.set_ctime(ctime);
+    dentry.notify_event(FsEvents::ATTRIB);
 
     Ok(SyscallReturn::Return(0))
 }
@@ -168,7 +170,8 @@ fn do_utimes(
     let dentry = {
         // Determine the file system path and the corresponding entry
         let fs_path = FsPath::new(dirfd, pathname.as_ref())?;
-        let fs = ctx.posix_thread.fs().resolver().read();
+        let fs_ref = ctx.posix_thread.fs();
+        let fs = fs_ref.resolver().read();
         if flags.contains(UtimensFlags::AT_SYMLINK_NOFOLLOW) {
             fs.lookup_no_follow(&fs_path)?
         } else {
@@ -3,7 +3,7 @@
 use super::{getrusage::rusage_t, SyscallReturn};
 use crate::{
     prelude::*,
-    process::{wait_child_exit, ProcessFilter, WaitOptions},
+    process::{wait_child_exit, ProcessFilter, WaitOptions, WaitStatus},
 };
 
 pub fn sys_wait4(
@@ -22,26 +22,32 @@ pub fn sys_wait4(
     debug!("wait4 current pid = {}", ctx.process.pid());
     let process_filter = ProcessFilter::from_id(wait_pid as _);
 
-    let waited_process =
+    let wait_status =
         wait_child_exit(process_filter, wait_options, ctx).map_err(|err| match err.error() {
             Errno::EINTR => Error::new(Errno::ERESTARTSYS),
             _ => err,
         })?;
-    let Some(process) = waited_process else {
+    let Some(wait_status) = wait_status else {
         return Ok(SyscallReturn::Return(0 as _));
     };
 
-    let (return_pid, exit_code) = (process.pid(), process.status().exit_code());
+    let return_pid = ctx.process.pid_ns().local_id_or_zero(wait_status.pid());
+    let exit_code = wait_status.status();
     if exit_status_ptr != 0 {
         ctx.user_space()
             .write_val(exit_status_ptr as _, &exit_code)?;
     }
 
     if rusage_addr != 0 {
-        let rusage = rusage_t {
-            ru_utime: process.prof_clock().user_clock().read_time().into(),
-            ru_stime: process.prof_clock().kernel_clock().read_time().into(),
-            ..Default::default()
+        let rusage = match &wait_status {
+            WaitStatus::Zombie(process) => rusage_t {
+                ru_utime: process.prof_clock().user_clock().read_time().into(),
+                ru_stime: process.prof_clock().kernel_clock().read_time().into(),
+                ..Default::default()
+            },
+            WaitStatus::PtraceStopped { .. } | WaitStatus::TraceeExited { .. } => {
+                rusage_t::default()
+            }
         };
 
         ctx.user_space().write_val(rusage_addr, &rusage)?;
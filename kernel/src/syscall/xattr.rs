@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::FileDesc,
+        fs_resolver::{FsPath, AT_FDCWD},
+        inode_handle::InodeHandle,
+        path::Dentry,
+        utils::{
+            XattrName, XattrSetFlags, PATH_MAX, XATTR_LIST_MAX_LEN, XATTR_NAME_MAX_LEN,
+            XATTR_VALUE_MAX_LEN,
+        },
+    },
+    prelude::*,
+};
+
+pub fn sys_setxattr(
+    path_ptr: Vaddr,
+    name_ptr: Vaddr,
+    value_ptr: Vaddr,
+    size: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = lookup_dentry_for_xattr(path_ptr, true, ctx)?;
+    set_xattr(&dentry, name_ptr, value_ptr, size, flags, ctx)
+}
+
+pub fn sys_lsetxattr(
+    path_ptr: Vaddr,
+    name_ptr: Vaddr,
+    value_ptr: Vaddr,
+    size: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = lookup_dentry_for_xattr(path_ptr, false, ctx)?;
+    set_xattr(&dentry, name_ptr, value_ptr, size, flags, ctx)
+}
+
+pub fn sys_fsetxattr(
+    fd: FileDesc,
+    name_ptr: Vaddr,
+    value_ptr: Vaddr,
+    size: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = get_dentry_from_fd(fd, ctx)?;
+    set_xattr(&dentry, name_ptr, value_ptr, size, flags, ctx)
+}
+
+pub fn sys_getxattr(
+    path_ptr: Vaddr,
+    name_ptr: Vaddr,
+    value_ptr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = lookup_dentry_for_xattr(path_ptr, true, ctx)?;
+    get_xattr(&dentry, name_ptr, value_ptr, size, ctx)
+}
+
+pub fn sys_lgetxattr(
+    path_ptr: Vaddr,
+    name_ptr: Vaddr,
+    value_ptr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = lookup_dentry_for_xattr(path_ptr, false, ctx)?;
+    get_xattr(&dentry, name_ptr, value_ptr, size, ctx)
+}
+
+pub fn sys_fgetxattr(
+    fd: FileDesc,
+    name_ptr: Vaddr,
+    value_ptr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = get_dentry_from_fd(fd, ctx)?;
+    get_xattr(&dentry, name_ptr, value_ptr, size, ctx)
+}
+
+pub fn sys_listxattr(
+    path_ptr: Vaddr,
+    list_ptr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = lookup_dentry_for_xattr(path_ptr, true, ctx)?;
+    list_xattr(&dentry, list_ptr, size, ctx)
+}
+
+pub fn sys_llistxattr(
+    path_ptr: Vaddr,
+    list_ptr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = lookup_dentry_for_xattr(path_ptr, false, ctx)?;
+    list_xattr(&dentry, list_ptr, size, ctx)
+}
+
+pub fn sys_flistxattr(
+    fd: FileDesc,
+    list_ptr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let dentry = get_dentry_from_fd(fd, ctx)?;
+    list_xattr(&dentry, list_ptr, size, ctx)
+}
+
+pub fn sys_removexattr(path_ptr: Vaddr, name_ptr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let dentry = lookup_dentry_for_xattr(path_ptr, true, ctx)?;
+    remove_xattr(&dentry, name_ptr, ctx)
+}
+
+pub fn sys_lremovexattr(path_ptr: Vaddr, name_ptr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let dentry = lookup_dentry_for_xattr(path_ptr, false, ctx)?;
+    remove_xattr(&dentry, name_ptr, ctx)
+}
+
+pub fn sys_fremovexattr(fd: FileDesc, name_ptr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let dentry = get_dentry_from_fd(fd, ctx)?;
+    remove_xattr(&dentry, name_ptr, ctx)
+}
+
+fn set_xattr(
+    dentry: &Dentry,
+    name_ptr: Vaddr,
+    value_ptr: Vaddr,
+    size: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = XattrSetFlags::from_bits(flags)
+        .ok_or(Error::with_message(Errno::EINVAL, "invalid xattr flags"))?;
+    if flags.contains(XattrSetFlags::CREATE_ONLY | XattrSetFlags::REPLACE_ONLY) {
+        return_errno_with_message!(Errno::EINVAL, "the xattr flags are exclusive");
+    }
+    if size > XATTR_VALUE_MAX_LEN {
+        return_errno_with_message!(Errno::E2BIG, "the xattr value is too long");
+    }
+
+    let name = read_xattr_name(name_ptr, ctx)?;
+    let mut value = vec![0u8; size];
+    if size > 0 {
+        ctx.user_space()
+            .read_bytes(value_ptr, &mut VmWriter::from(value.as_mut_slice()))?;
+    }
+    debug!("name = {:?}, size = {}, flags = {:?}", name, size, flags);
+
+    dentry.set_xattr(XattrName::try_from_full_name(&name)?, &value, flags)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+fn get_xattr(
+    dentry: &Dentry,
+    name_ptr: Vaddr,
+    value_ptr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let name = read_xattr_name(name_ptr, ctx)?;
+    debug!("name = {:?}, size = {}", name, size);
+
+    let value = dentry.get_xattr(XattrName::try_from_full_name(&name)?)?;
+    // A zero `size` queries the length of the value.
+    if size == 0 {
+        return Ok(SyscallReturn::Return(value.len() as _));
+    }
+    if value.len() > size {
+        return_errno_with_message!(Errno::ERANGE, "the buffer is too small for the xattr value");
+    }
+
+    ctx.user_space()
+        .write_bytes(value_ptr, &mut VmReader::from(value.as_slice()))?;
+    Ok(SyscallReturn::Return(value.len() as _))
+}
+
+fn list_xattr(
+    dentry: &Dentry,
+    list_ptr: Vaddr,
+    size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("size = {}", size);
+
+    let mut list = Vec::new();
+    for name in dentry.list_xattr()? {
+        list.extend_from_slice(name.as_bytes());
+        list.push(0);
+    }
+    if list.len() > XATTR_LIST_MAX_LEN {
+        return_errno_with_message!(Errno::E2BIG, "the xattr list is too long");
+    }
+    // A zero `size` queries the length of the list.
+    if size == 0 {
+        return Ok(SyscallReturn::Return(list.len() as _));
+    }
+    if list.len() > size {
+        return_errno_with_message!(Errno::ERANGE, "the buffer is too small for the xattr list");
+    }
+
+    ctx.user_space()
+        .write_bytes(list_ptr, &mut VmReader::from(list.as_slice()))?;
+    Ok(SyscallReturn::Return(list.len() as _))
+}
+
+fn remove_xattr(dentry: &Dentry, name_ptr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let name = read_xattr_name(name_ptr, ctx)?;
+    debug!("name = {:?}", name);
+
+    dentry.remove_xattr(XattrName::try_from_full_name(&name)?)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+fn read_xattr_name(name_ptr: Vaddr, ctx: &Context) -> Result<String> {
+    let name = ctx
+        .user_space()
+        .read_cstring(name_ptr, XATTR_NAME_MAX_LEN + 1)
+        .map_err(|err| match err.error() {
+            Errno::E2BIG => Error::with_message(Errno::ERANGE, "the xattr name is too long"),
+            _ => err,
+        })?;
+    Ok(name.to_string_lossy().into_owned())
+}
+
+fn lookup_dentry_for_xattr(path_ptr: Vaddr, follow_symlink: bool, ctx: &Context) -> Result<Dentry> {
+    let path = ctx.user_space().read_cstring(path_ptr, PATH_MAX)?;
+    debug!("path = {:?}, follow_symlink = {}", path, follow_symlink);
+
+    let path = path.to_string_lossy();
+    if path.is_empty() {
+        return_errno_with_message!(Errno::ENOENT, "path is empty");
+    }
+    let fs_path = FsPath::new(AT_FDCWD, path.as_ref())?;
+    let fs_ref = ctx.posix_thread.fs();
+    let fs = fs_ref.resolver().read();
+    if follow_symlink {
+        fs.lookup(&fs_path)
+    } else {
+        fs.lookup_no_follow(&fs_path)
+    }
+}
+
+fn get_dentry_from_fd(fd: FileDesc, ctx: &Context) -> Result<Dentry> {
+    debug!("fd = {}", fd);
+
+    let file_table = ctx.posix_thread.file_table().lock_arc();
+    let file = file_table.get_file(fd)?;
+    let inode_handle = file
+        .downcast_ref::<InodeHandle>()
+        .ok_or(Error::with_message(Errno::EOPNOTSUPP, "not inode"))?;
+    Ok(inode_handle.dentry().clone())
+}
@@ -8,7 +8,7 @@ use ostd::{cpu::*, mm::VmSpace};
 use crate::{
     prelude::*,
     process::signal::signals::fault::FaultSignal,
-    vm::{page_fault_handler::PageFaultHandler, perms::VmPerms, vmar::Vmar},
+    vm::{oom::out_of_memory, page_fault_handler::PageFaultHandler, perms::VmPerms, vmar::Vmar},
 };
 
 /// Page fault information converted from [`CpuExceptionInfo`].
@@ -31,8 +31,13 @@ pub fn handle_exception(ctx: &Context, context: &UserContext) {
     log_trap_info(trap_info);
 
     if let Ok(page_fault_info) = PageFaultInfo::try_from(trap_info) {
-        if handle_page_fault_from_vmar(ctx.process.root_vmar(), &page_fault_info).is_ok() {
-            return;
+        match handle_page_fault_from_vmar(ctx.process.root_vmar(), &page_fault_info) {
+            Ok(()) => return,
+            // The thread is interrupted by a signal while waiting for the
+            // fault to be handled by the user space. The access will be
+            // retried after the signal is handled.
+            Err(err) if err.error() == Errno::EINTR => return,
+            Err(_) => {}
         }
     }
 
@@ -53,20 +58,26 @@ pub(crate) fn handle_page_fault_from_vm_space(
         vm_space as *const VmSpace
     );
 
-    handle_page_fault_from_vmar(root_vmar, page_fault_info)
+    handle_page_fault_from_vmar(root_vmar, page_fault_info).map_err(|_| ())
 }
 
 /// Handles the page fault occurs in the input `Vmar`.
 pub(crate) fn handle_page_fault_from_vmar(
     root_vmar: &Vmar<Full>,
     page_fault_info: &PageFaultInfo,
-) -> core::result::Result<(), ()> {
+) -> Result<()> {
     if let Err(e) = root_vmar.handle_page_fault(page_fault_info) {
-        warn!(
-            "page fault handler failed: addr: 0x{:x}, err: {:?}",
-            page_fault_info.address, e
-        );
-        return Err(());
+        // The faulting access is retried after some memory is freed.
+        if e.error() == Errno::ENOMEM && out_of_memory() {
+            return Ok(());
+        }
+        if e.error() != Errno::EINTR {
+            warn!(
+                "page fault handler failed: addr: 0x{:x}, err: {:?}",
+                page_fault_info.address, e
+            );
+        }
+        return Err(e);
     }
     Ok(())
 }
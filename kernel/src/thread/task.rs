@@ -113,3 +113,30 @@ pub fn create_new_user_task(
     .build()
     .expect("spawn task failed")
 }
+
+/// Creates a new task that runs `func` in the kernel mode with the user space of a process.
+///
+/// The task never returns to the user mode, but it can access the user space (e.g., via
+/// [`Context::user_space`]) to perform operations on behalf of the process.
+pub fn create_new_kernel_task_with_user_space<F>(
+    func: F,
+    user_space: Arc<UserSpace>,
+    thread_ref: Arc<Thread>,
+    thread_local: ThreadLocal,
+) -> Task
+where
+    F: FnOnce() + Send + Sync + 'static,
+{
+    let task_fn = move || {
+        let _ = oops::catch_panics_as_oops(func);
+        // Ensure that the thread exits.
+        current_thread!().exit();
+    };
+
+    TaskOptions::new(task_fn)
+        .data(thread_ref)
+        .local_data(thread_local)
+        .user_space(Some(user_space))
+        .build()
+        .expect("spawn task failed")
+}
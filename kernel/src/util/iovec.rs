@@ -6,7 +6,7 @@ use crate::prelude::*;
 
 /// A kernel space IO vector.
 #[derive(Debug, Clone, Copy)]
-struct IoVec {
+pub struct IoVec {
     base: Vaddr,
     len: usize,
 }
@@ -39,8 +39,18 @@ impl TryFrom<UserIoVec> for IoVec {
 }
 
 impl IoVec {
+    /// Returns the base address of the user buffer.
+    pub const fn base(&self) -> Vaddr {
+        self.base
+    }
+
+    /// Returns the length of the user buffer.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
     /// Returns whether the `IoVec` points to an empty user buffer.
-    const fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.len == 0 || self.base == 0
     }
 
@@ -51,36 +61,44 @@ impl IoVec {
     fn writer<'a>(&self, vm_space: &'a VmSpace) -> Result<VmWriter<'a>> {
         Ok(vm_space.writer(self.base, self.len)?)
     }
-}
 
-/// The util function for create [`VmReader`]/[`VmWriter`]s.
-fn copy_iovs_and_convert<'a, T: 'a>(
-    ctx: &'a Context,
-    start_addr: Vaddr,
-    count: usize,
-    convert_iovec: impl Fn(&IoVec, &'a VmSpace) -> Result<T>,
-) -> Result<Box<[T]>> {
-    let vm_space = ctx.process.root_vmar().vm_space();
-
-    let mut v = Vec::with_capacity(count);
-    for idx in 0..count {
-        let iov = {
-            let addr = start_addr + idx * core::mem::size_of::<UserIoVec>();
-            let uiov: UserIoVec = vm_space
-                .reader(addr, core::mem::size_of::<UserIoVec>())?
-                .read_val()?;
-            IoVec::try_from(uiov)?
-        };
-
-        if iov.is_empty() {
-            continue;
+    /// Reads `count` IO vectors from the user space.
+    ///
+    /// The IO vectors that point to empty user buffers are skipped.
+    pub fn read_from_user(ctx: &Context, start_addr: Vaddr, count: usize) -> Result<Box<[IoVec]>> {
+        let vm_space = ctx.process.root_vmar().vm_space();
+
+        let mut v = Vec::with_capacity(count);
+        for idx in 0..count {
+            let iov = {
+                let addr = start_addr + idx * core::mem::size_of::<UserIoVec>();
+                let uiov: UserIoVec = vm_space
+                    .reader(addr, core::mem::size_of::<UserIoVec>())?
+                    .read_val()?;
+                IoVec::try_from(uiov)?
+            };
+
+            if iov.is_empty() {
+                continue;
+            }
+
+            v.push(iov)
         }
 
-        let converted = convert_iovec(&iov, vm_space)?;
-        v.push(converted)
+        Ok(v.into_boxed_slice())
     }
+}
 
-    Ok(v.into_boxed_slice())
+/// The util function for create [`VmReader`]/[`VmWriter`]s.
+fn convert_iovs<'a, T: 'a>(
+    vm_space: &'a VmSpace,
+    io_vecs: &[IoVec],
+    convert_iovec: impl Fn(&IoVec, &'a VmSpace) -> Result<T>,
+) -> Result<Box<[T]>> {
+    io_vecs
+        .iter()
+        .map(|iov| convert_iovec(iov, vm_space))
+        .collect()
 }
 
 /// A collection of [`VmReader`]s.
@@ -100,7 +118,13 @@ impl<'a> VmReaderArray<'a> {
         start_addr: Vaddr,
         count: usize,
     ) -> Result<Self> {
-        let readers = copy_iovs_and_convert(ctx, start_addr, count, IoVec::reader)?;
+        let io_vecs = IoVec::read_from_user(ctx, start_addr, count)?;
+        Self::from_io_vecs(ctx.process.root_vmar().vm_space(), &io_vecs)
+    }
+
+    /// Creates a new `IoVecReader` from IO vectors that have been read from the user space.
+    pub fn from_io_vecs(vm_space: &'a VmSpace, io_vecs: &[IoVec]) -> Result<Self> {
+        let readers = convert_iovs(vm_space, io_vecs, IoVec::reader)?;
         Ok(Self(readers))
     }
 
@@ -117,7 +141,13 @@ impl<'a> VmWriterArray<'a> {
         start_addr: Vaddr,
         count: usize,
     ) -> Result<Self> {
-        let writers = copy_iovs_and_convert(ctx, start_addr, count, IoVec::writer)?;
+        let io_vecs = IoVec::read_from_user(ctx, start_addr, count)?;
+        Self::from_io_vecs(ctx.process.root_vmar().vm_space(), &io_vecs)
+    }
+
+    /// Creates a new `IoVecWriter` from IO vectors that have been read from the user space.
+    pub fn from_io_vecs(vm_space: &'a VmSpace, io_vecs: &[IoVec]) -> Result<Self> {
+        let writers = convert_iovs(vm_space, io_vecs, IoVec::writer)?;
         Ok(Self(writers))
     }
 
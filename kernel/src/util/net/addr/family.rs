@@ -4,7 +4,12 @@ use core::cmp::min;
 
 use ostd::task::Task;
 
-use super::{ip::CSocketAddrInet, unix, vsock::CSocketAddrVm};
+use super::{
+    ip::{CSocketAddrInet, CSocketAddrInet6},
+    netlink::CSocketAddrNetlink,
+    unix,
+    vsock::CSocketAddrVm,
+};
 use crate::{current_userspace, net::socket::SocketAddr, prelude::*};
 
 /// Address family.
@@ -159,10 +164,24 @@ pub fn read_socket_addr_from_user(addr: Vaddr, addr_len: usize) -> Result<Socket
             let (addr, port) = CSocketAddrInet::from_bytes(storage.as_bytes()).into();
             SocketAddr::IPv4(addr, port)
         }
+        Ok(CSocketAddrFamily::AF_INET6) => {
+            if addr_len < CSocketAddrInet6::MIN_LEN {
+                return_errno_with_message!(Errno::EINVAL, "the socket address length is too small");
+            }
+            let (addr, port) = CSocketAddrInet6::from_bytes(storage.as_bytes()).into();
+            SocketAddr::IPv6(addr, port)
+        }
         Ok(CSocketAddrFamily::AF_UNIX) => {
             let addr = unix::from_c_bytes(&storage.as_bytes()[..addr_len])?;
             SocketAddr::Unix(addr)
         }
+        Ok(CSocketAddrFamily::AF_NETLINK) => {
+            if addr_len < size_of::<CSocketAddrNetlink>() {
+                return_errno_with_message!(Errno::EINVAL, "the socket address length is too small");
+            }
+            let addr = CSocketAddrNetlink::from_bytes(storage.as_bytes());
+            SocketAddr::Netlink(addr.into())
+        }
         Ok(CSocketAddrFamily::AF_VSOCK) => {
             if addr_len < size_of::<CSocketAddrVm>() {
                 return_errno_with_message!(Errno::EINVAL, "the socket address length is too small");
@@ -253,6 +272,16 @@ pub fn write_socket_addr_with_max_len(
             )?;
             actual_len
         }
+        SocketAddr::IPv6(addr, port) => {
+            let socket_addr = CSocketAddrInet6::from((*addr, *port));
+            let actual_len = size_of::<CSocketAddrInet6>();
+            let written_len = min(actual_len, max_len as _);
+            user_space.write_bytes(
+                dest,
+                &mut VmReader::from(&socket_addr.as_bytes()[..written_len]),
+            )?;
+            actual_len
+        }
         SocketAddr::Unix(addr) => unix::into_c_bytes_and(addr, |bytes| {
             let written_len = min(bytes.len(), max_len as _);
             user_space.write_bytes(dest, &mut VmReader::from(&bytes[..written_len]))?;
@@ -268,6 +297,16 @@ pub fn write_socket_addr_with_max_len(
             )?;
             actual_len
         }
+        SocketAddr::Netlink(addr) => {
+            let socket_addr = CSocketAddrNetlink::from(*addr);
+            let actual_len = size_of::<CSocketAddrNetlink>();
+            let written_len = min(actual_len, max_len as _);
+            user_space.write_bytes(
+                dest,
+                &mut VmReader::from(&socket_addr.as_bytes()[..written_len]),
+            )?;
+            actual_len
+        }
     };
 
     Ok(actual_len as i32)
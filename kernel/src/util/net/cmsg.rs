@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Control messages (i.e., ancillary data) in the user space.
+//!
+//! See <https://man7.org/linux/man-pages/man3/cmsg.3.html> for the layout of control messages.
+
+use super::CSocketOptionLevel;
+use crate::{
+    fs::file_table::{FdFlags, FileDesc},
+    net::socket::{
+        unix::{UnixControlMessage, UnixCredentials, MAX_FILES_PER_MESSAGE},
+        ControlMessage, SendRecvFlags,
+    },
+    prelude::*,
+    process::{Gid, Pid, Uid},
+};
+
+/// The header of a control message (i.e., `struct cmsghdr`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CControlHeader {
+    /// The length of the control message, including the header.
+    len: usize,
+    level: i32,
+    type_: i32,
+}
+
+/// The alignment of control messages and their data.
+const CMSG_ALIGN: usize = size_of::<usize>();
+
+const fn cmsg_align(len: usize) -> usize {
+    len.next_multiple_of(CMSG_ALIGN)
+}
+
+/// The length of the header, including the padding before the data.
+const HEADER_LEN: usize = cmsg_align(size_of::<CControlHeader>());
+
+/// Types of the control messages at the `SOL_SOCKET` level.
+///
+/// See <https://elixir.bootlin.com/linux/v6.0.9/source/include/linux/socket.h#L163>.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, TryFromInt, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[allow(clippy::upper_case_acronyms)]
+#[allow(dead_code)]
+enum CSocketControlType {
+    /// File descriptors
+    SCM_RIGHTS = 1,
+    /// Process credentials (i.e., `struct ucred`)
+    SCM_CREDENTIALS = 2,
+    /// Security label
+    SCM_SECURITY = 3,
+    /// PID file descriptor
+    SCM_PIDFD = 4,
+}
+
+/// Process credentials (i.e., `struct ucred`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub(super) struct CUserCred {
+    pid: i32,
+    uid: u32,
+    gid: u32,
+}
+
+impl From<UnixCredentials> for CUserCred {
+    fn from(value: UnixCredentials) -> Self {
+        // The PID is reported as seen in the PID namespace of the current process.
+        let pid = current!().pid_ns().local_id_or_zero(value.pid());
+
+        Self {
+            pid: pid as i32,
+            uid: value.uid().into(),
+            gid: value.gid().into(),
+        }
+    }
+}
+
+/// Reads the control messages from the user space.
+///
+/// The files referred to by `SCM_RIGHTS` messages are looked up in the file table of the current
+/// thread.
+pub(super) fn read_control_messages_from_user(
+    addr: Vaddr,
+    len: usize,
+    ctx: &Context,
+) -> Result<Vec<ControlMessage>> {
+    let user_space = ctx.user_space();
+
+    let mut control_messages = Vec::new();
+
+    let mut offset = 0;
+    while offset + size_of::<CControlHeader>() <= len {
+        let header = user_space.read_val::<CControlHeader>(addr + offset)?;
+        if header.len < size_of::<CControlHeader>() || header.len > len - offset {
+            return_errno_with_message!(Errno::EINVAL, "the control message length is invalid");
+        }
+
+        let data_addr = addr + offset + HEADER_LEN;
+        let data_len = header.len.saturating_sub(HEADER_LEN);
+
+        match CSocketOptionLevel::try_from(header.level) {
+            Ok(CSocketOptionLevel::SOL_SOCKET) => {
+                let control_message =
+                    read_socket_control_message(header.type_, data_addr, data_len, ctx)?;
+                control_messages.push(control_message);
+            }
+            _ => {
+                // TODO: Support control messages at other levels.
+                warn!(
+                    "control messages at level {} are not supported",
+                    header.level
+                );
+            }
+        }
+
+        offset += cmsg_align(header.len);
+    }
+
+    Ok(control_messages)
+}
+
+fn read_socket_control_message(
+    type_: i32,
+    addr: Vaddr,
+    len: usize,
+    ctx: &Context,
+) -> Result<ControlMessage> {
+    let user_space = ctx.user_space();
+
+    let control_message = match CSocketControlType::try_from(type_) {
+        Ok(CSocketControlType::SCM_RIGHTS) => {
+            let num_fds = len / size_of::<FileDesc>();
+            if num_fds > MAX_FILES_PER_MESSAGE {
+                return_errno_with_message!(Errno::EINVAL, "too many file descriptors are passed");
+            }
+
+            let fds = (0..num_fds)
+                .map(|i| user_space.read_val::<FileDesc>(addr + i * size_of::<FileDesc>()))
+                .collect::<Result<Vec<_>>>()?;
+
+            let file_table = ctx.posix_thread.file_table().lock_arc();
+            let files = fds
+                .into_iter()
+                .map(|fd| file_table.get_file(fd).cloned())
+                .collect::<Result<Vec<_>>>()?;
+
+            UnixControlMessage::Files(files)
+        }
+        Ok(CSocketControlType::SCM_CREDENTIALS) => {
+            if len != size_of::<CUserCred>() {
+                return_errno_with_message!(Errno::EINVAL, "the credentials length is invalid");
+            }
+
+            let c_cred = user_space.read_val::<CUserCred>(addr)?;
+            let cred = UnixCredentials::new_checked(
+                c_cred.pid as Pid,
+                Uid::new(c_cred.uid),
+                Gid::new(c_cred.gid),
+            )?;
+
+            UnixControlMessage::Credentials(cred)
+        }
+        _ => {
+            return_errno_with_message!(Errno::EINVAL, "the control message type is not supported")
+        }
+    };
+
+    Ok(ControlMessage::Unix(control_message))
+}
+
+/// Writes the control messages to the user space.
+///
+/// The files in `SCM_RIGHTS` messages are installed in the file table of the current thread.
+/// This method returns the number of bytes written and whether the control messages are
+/// truncated because the buffer is too small.
+pub(super) fn write_control_messages_to_user(
+    control_messages: Vec<ControlMessage>,
+    addr: Vaddr,
+    max_len: usize,
+    flags: SendRecvFlags,
+    ctx: &Context,
+) -> Result<(usize, bool)> {
+    let mut offset = 0;
+    let mut is_truncated = false;
+
+    for control_message in control_messages {
+        let remain_len = max_len.saturating_sub(offset);
+
+        let written_len = match control_message {
+            ControlMessage::Unix(UnixControlMessage::Credentials(cred)) => {
+                let data = CUserCred::from(cred);
+                let msg_len = HEADER_LEN + size_of::<CUserCred>();
+                if remain_len < msg_len {
+                    is_truncated = true;
+                    continue;
+                }
+
+                write_header(
+                    addr + offset,
+                    msg_len,
+                    CSocketControlType::SCM_CREDENTIALS,
+                    ctx,
+                )?;
+                ctx.user_space()
+                    .write_val(addr + offset + HEADER_LEN, &data)?;
+                msg_len
+            }
+            ControlMessage::Unix(UnixControlMessage::Files(files)) => {
+                // Files that cannot fit in the buffer are discarded (and closed if they are not
+                // referred to elsewhere).
+                let max_fds = remain_len.saturating_sub(HEADER_LEN) / size_of::<FileDesc>();
+                if max_fds < files.len() {
+                    is_truncated = true;
+                }
+                if max_fds == 0 {
+                    continue;
+                }
+
+                let fd_flags = if flags.contains(SendRecvFlags::MSG_CMSG_CLOEXEC) {
+                    FdFlags::CLOEXEC
+                } else {
+                    FdFlags::empty()
+                };
+
+                // Write the header first, so no files will be installed if it fails.
+                let num_fds = files.len().min(max_fds);
+                let msg_len = HEADER_LEN + num_fds * size_of::<FileDesc>();
+                write_header(addr + offset, msg_len, CSocketControlType::SCM_RIGHTS, ctx)?;
+
+                let fds = {
+                    let mut file_table = ctx.posix_thread.file_table().lock_arc();
+                    files
+                        .into_iter()
+                        .take(max_fds)
+                        .map(|file| file_table.insert(file, fd_flags))
+                        .collect::<Vec<_>>()
+                };
+
+                let fds_addr = addr + offset + HEADER_LEN;
+                let write_res = fds.iter().enumerate().try_for_each(|(i, fd)| {
+                    ctx.user_space()
+                        .write_val(fds_addr + i * size_of::<FileDesc>(), fd)
+                });
+                if let Err(err) = write_res {
+                    let mut file_table = ctx.posix_thread.file_table().lock_arc();
+                    for fd in fds {
+                        file_table.close_file(fd);
+                    }
+                    return Err(err);
+                }
+
+                msg_len
+            }
+        };
+
+        offset += cmsg_align(written_len).min(remain_len);
+    }
+
+    Ok((offset, is_truncated))
+}
+
+fn write_header(addr: Vaddr, len: usize, type_: CSocketControlType, ctx: &Context) -> Result<()> {
+    let header = CControlHeader {
+        len,
+        level: CSocketOptionLevel::SOL_SOCKET as i32,
+        type_: type_ as i32,
+    };
+    ctx.user_space().write_val(addr, &header)
+}
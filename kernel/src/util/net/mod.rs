@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod addr;
+mod cmsg;
 mod options;
 mod socket;
 
@@ -18,6 +19,6 @@ use crate::{
 pub fn get_socket_from_fd(sockfd: FileDesc) -> Result<Arc<dyn Socket>> {
     let current = current_thread!();
     let current = current.as_posix_thread().unwrap();
-    let file_table = current.file_table().lock();
+    let file_table = current.file_table().lock_arc();
     file_table.get_socket(sockfd)
 }
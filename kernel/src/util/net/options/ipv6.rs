@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::RawSocketOption;
+use crate::{
+    impl_raw_socket_option, net::socket::ip::options::Ipv6Only, prelude::*,
+    util::net::options::SocketOption,
+};
+
+/// Sock options for IPv6 sockets.
+///
+/// The raw definition is from https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/in6.h#L178
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, TryFromInt)]
+#[allow(non_camel_case_types)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum CIpv6OptionName {
+    V6ONLY = 26, /* Restrict the socket to IPv6 communication only */
+}
+
+pub fn new_ipv6_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
+    let name = CIpv6OptionName::try_from(name).map_err(|_| Errno::ENOPROTOOPT)?;
+    match name {
+        CIpv6OptionName::V6ONLY => Ok(Box::new(Ipv6Only::new())),
+    }
+}
+
+impl_raw_socket_option!(Ipv6Only);
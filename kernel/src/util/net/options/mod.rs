@@ -53,11 +53,12 @@
 
 use crate::{net::socket::options::SocketOption, prelude::*};
 
+mod ipv6;
 mod socket;
 mod tcp;
 mod utils;
 
-use self::{socket::new_socket_option, tcp::new_tcp_option};
+use self::{ipv6::new_ipv6_option, socket::new_socket_option, tcp::new_tcp_option};
 
 pub trait RawSocketOption: SocketOption {
     fn read_from_user(&mut self, addr: Vaddr, max_len: u32) -> Result<()>;
@@ -134,6 +135,7 @@ pub fn new_raw_socket_option(
     match level {
         CSocketOptionLevel::SOL_SOCKET => new_socket_option(name),
         CSocketOptionLevel::SOL_TCP => new_tcp_option(name),
+        CSocketOptionLevel::SOL_IPV6 => new_ipv6_option(name),
         _ => return_errno_with_message!(Errno::EOPNOTSUPP, "unsupported option level"),
     }
 }
@@ -4,8 +4,9 @@ use core::time::Duration;
 
 use crate::{
     current_userspace,
-    net::socket::{ip::stream::CongestionControl, LingerOption},
+    net::socket::{ip::stream::CongestionControl, unix::UnixCredentials, LingerOption},
     prelude::*,
+    util::net::cmsg::CUserCred,
 };
 
 /// Create an object by reading its C counterpart from the user space.
@@ -162,6 +163,20 @@ impl WriteToUser for CongestionControl {
     }
 }
 
+impl WriteToUser for UnixCredentials {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        let write_len = core::mem::size_of::<CUserCred>();
+
+        if (max_len as usize) < write_len {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        let cred = CUserCred::from(*self);
+        current_userspace!().write_val(addr, &cred)?;
+        Ok(write_len)
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod)]
 struct CLinger {
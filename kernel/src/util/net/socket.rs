@@ -1,8 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::read_socket_addr_from_user;
+use super::{
+    cmsg::{read_control_messages_from_user, write_control_messages_to_user},
+    read_socket_addr_from_user,
+};
 use crate::{
-    net::socket::SocketAddr,
+    net::socket::{ControlMessage, SendRecvFlags, SocketAddr},
     prelude::*,
     util::{net::write_socket_addr_with_max_len, VmReaderArray, VmWriterArray},
 };
@@ -84,13 +87,13 @@ pub struct CUserMsgHdr {
     /// Scatter/Gather iov array
     pub msg_iov: Vaddr,
     /// The # of elements in msg_iov
-    pub msg_iovlen: u32,
+    pub msg_iovlen: usize,
     /// Ancillary data
     pub msg_control: Vaddr,
     /// Ancillary data buffer length
-    pub msg_controllen: u32,
+    pub msg_controllen: usize,
     /// Flags on received message
-    pub msg_flags: u32,
+    pub msg_flags: i32,
 }
 
 impl CUserMsgHdr {
@@ -103,20 +106,64 @@ impl CUserMsgHdr {
         Ok(Some(socket_addr))
     }
 
-    pub fn write_socket_addr_to_user(&self, addr: &SocketAddr) -> Result<()> {
+    /// Writes the socket address to the user space and updates `msg_namelen`.
+    ///
+    /// If `addr` is `None`, `msg_namelen` is set to zero.
+    pub fn write_socket_addr_to_user(&mut self, addr: Option<&SocketAddr>) -> Result<()> {
         if self.msg_name == 0 {
             return Ok(());
         }
 
-        write_socket_addr_with_max_len(addr, self.msg_name, self.msg_namelen)?;
+        self.msg_namelen = match addr {
+            Some(addr) => write_socket_addr_with_max_len(addr, self.msg_name, self.msg_namelen)?,
+            None => 0,
+        };
+        Ok(())
+    }
+
+    pub fn read_control_messages_from_user(&self, ctx: &Context) -> Result<Vec<ControlMessage>> {
+        if self.msg_control == 0 {
+            return Ok(Vec::new());
+        }
+
+        read_control_messages_from_user(self.msg_control, self.msg_controllen, ctx)
+    }
+
+    /// Writes the control messages to the user space and updates `msg_controllen` and
+    /// `msg_flags`.
+    ///
+    /// `flags` are the flags passed to `recvmsg`.
+    pub fn write_control_messages_to_user(
+        &mut self,
+        control_messages: Vec<ControlMessage>,
+        flags: SendRecvFlags,
+        ctx: &Context,
+    ) -> Result<()> {
+        let (written_len, is_truncated) = if self.msg_control == 0 {
+            (0, !control_messages.is_empty())
+        } else {
+            write_control_messages_to_user(
+                control_messages,
+                self.msg_control,
+                self.msg_controllen,
+                flags,
+                ctx,
+            )?
+        };
+
+        self.msg_controllen = written_len;
+        if is_truncated {
+            self.msg_flags |= SendRecvFlags::MSG_CTRUNC.bits();
+        }
+
         Ok(())
     }
 
     pub fn copy_reader_array_from_user<'a>(&self, ctx: &'a Context) -> Result<VmReaderArray<'a>> {
-        VmReaderArray::from_user_io_vecs(ctx, self.msg_iov, self.msg_iovlen as usize)
+        VmReaderArray::from_user_io_vecs(ctx, self.msg_iov, self.msg_iovlen)
     }
 
     pub fn copy_writer_array_from_user<'a>(&self, ctx: &'a Context) -> Result<VmWriterArray<'a>> {
-        VmWriterArray::from_user_io_vecs(ctx, self.msg_iov, self.msg_iovlen as usize)
+        VmWriterArray::from_user_io_vecs(ctx, self.msg_iov, self.msg_iovlen)
     }
 }
@@ -16,8 +16,17 @@
 //! In Asterinas, VMARs and VMOs, as well as other capabilities, are implemented
 //! as zero-cost capabilities.
 
+pub mod oom;
 pub mod page_fault_handler;
 pub mod perms;
+pub mod reclaim;
+pub mod swap;
+pub mod thp;
+pub mod userfault;
 pub mod util;
 pub mod vmar;
 pub mod vmo;
+
+pub(super) fn init() {
+    reclaim::init();
+}
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The out-of-memory (OOM) killer.
+//!
+//! When memory is exhausted and no more pages can be reclaimed, the OOM killer
+//! chooses a victim process and kills its process group to free the memory.
+//!
+//! The victim is the process with the highest badness, which is the number of
+//! its resident pages adjusted by its `oom_score_adj`. The adjustment is in
+//! units of 1/1000 of the total pages of memory and swap space, so that an
+//! adjustment of 1000 always makes the process the preferred victim, while an
+//! adjustment of -1000 ([`OOM_SCORE_ADJ_MIN`]) prevents the process from being
+//! killed at all.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.0/source/mm/oom_kill.c>
+
+use ostd::mm::stat::{mem_available, mem_total};
+
+use super::swap::{swap_free, swap_total};
+use crate::{
+    prelude::*,
+    process::{
+        process_table,
+        signal::{constants::SIGKILL, signals::kernel::KernelSignal},
+        Process,
+    },
+    thread::Thread,
+};
+
+/// The minimum value of `oom_score_adj`, which disables OOM killing.
+pub const OOM_SCORE_ADJ_MIN: i16 = -1000;
+/// The maximum value of `oom_score_adj`.
+pub const OOM_SCORE_ADJ_MAX: i16 = 1000;
+
+/// The last victim of the OOM killer.
+static OOM_VICTIM: Mutex<Weak<Process>> = Mutex::new(Weak::new());
+
+/// Handles the exhaustion of memory by killing a process.
+///
+/// If the last victim is still exiting, no more process is killed. Instead,
+/// the current thread yields to let the victim exit.
+///
+/// Returns whether the memory allocation of the current process should be
+/// retried. It is not the case if no process can be killed, or if the current
+/// process is killed.
+pub fn out_of_memory() -> bool {
+    let current = current!();
+
+    let mut last_victim = OOM_VICTIM.lock();
+    if let Some(victim) = last_victim.upgrade()
+        && !victim.status().is_zombie()
+    {
+        drop(last_victim);
+        if is_killed_with(&current, &victim) {
+            return false;
+        }
+        Thread::yield_now();
+        return true;
+    }
+
+    let total_pages = total_pages();
+    let processes: Vec<_> = process_table::process_table_mut().iter().cloned().collect();
+    let candidates: Vec<_> = processes
+        .iter()
+        .filter_map(|process| {
+            let rss = process.vm().rss();
+            Some((process, rss, badness(process, rss, total_pages)?))
+        })
+        .collect();
+    let Some(&(victim, victim_rss, _)) = candidates.iter().max_by_key(|(_, _, badness)| *badness)
+    else {
+        error!("Out of memory: no killable process");
+        return false;
+    };
+
+    report(&candidates);
+    error!(
+        "Out of memory: Killed process {} ({}) rss:{}kB oom_score_adj:{}",
+        victim.pid(),
+        victim.executable_path(),
+        victim_rss / 1024,
+        victim.oom_score_adj()
+    );
+
+    // The whole process group of the victim is killed, since the other
+    // processes in the group (e.g., the stages of a pipeline) are usually
+    // useless without the victim. Other processes sharing the memory with the
+    // victim, e.g., those created by `vfork`, are killed as well. Otherwise,
+    // the memory is not freed.
+    let mut is_current_killed = false;
+    for process in processes.iter() {
+        if is_killed_with(process, victim) {
+            process.enqueue_signal(KernelSignal::new(SIGKILL));
+            is_current_killed |= Arc::ptr_eq(process, &current);
+        }
+    }
+
+    *last_victim = Arc::downgrade(victim);
+    !is_current_killed && !shares_memory(&current, victim)
+}
+
+/// Returns whether the process is killed along with the victim.
+fn is_killed_with(process: &Arc<Process>, victim: &Arc<Process>) -> bool {
+    if Arc::ptr_eq(process, victim) {
+        return true;
+    }
+
+    let is_related = process.pgid() == victim.pgid() || shares_memory(process, victim);
+    is_related && is_killable(process)
+}
+
+/// Returns the OOM score of the process, which is shown in
+/// `/proc/[pid]/oom_score`.
+///
+/// The score ranges from 0 to 2000. A process with a higher score is more
+/// likely to be killed, and a process that cannot be killed has a score of 0.
+pub fn oom_score(process: &Process) -> usize {
+    let total_pages = total_pages();
+    let Some(badness) = badness(process, process.vm().rss(), total_pages) else {
+        return 0;
+    };
+
+    (1000 + badness * 1000 / total_pages as isize).clamp(0, 2000) as usize
+}
+
+/// Returns the badness of the process with `rss` bytes of resident memory,
+/// or `None` if the process cannot be killed.
+fn badness(process: &Process, rss: usize, total_pages: usize) -> Option<isize> {
+    if !is_killable(process) {
+        return None;
+    }
+
+    let adj = process.oom_score_adj() as isize * total_pages as isize / 1000;
+    Some((rss / PAGE_SIZE) as isize + adj)
+}
+
+fn is_killable(process: &Process) -> bool {
+    !process.is_init_process()
+        && !process.status().is_zombie()
+        && process.oom_score_adj() != OOM_SCORE_ADJ_MIN
+}
+
+fn shares_memory(process: &Process, victim: &Process) -> bool {
+    Arc::ptr_eq(
+        process.root_vmar().vm_space(),
+        victim.root_vmar().vm_space(),
+    )
+}
+
+/// Returns the total number of pages of memory and swap space.
+fn total_pages() -> usize {
+    ((mem_total() + swap_total()) / PAGE_SIZE).max(1)
+}
+
+/// Logs the memory statistics and the candidates of the victim.
+fn report(candidates: &[(&Arc<Process>, usize, isize)]) {
+    error!(
+        "Mem-Info: total:{}kB available:{}kB swap-total:{}kB swap-free:{}kB",
+        mem_total() / 1024,
+        mem_available() / 1024,
+        swap_total() / 1024,
+        swap_free() / 1024
+    );
+    error!("Tasks state (memory values in pages):");
+    error!("[  pid  ]      rss oom_score_adj name");
+    for (process, rss, _) in candidates {
+        error!(
+            "[{:>7}] {:>8} {:>13} {}",
+            process.pid(),
+            rss / PAGE_SIZE,
+            process.oom_score_adj(),
+            process.executable_path()
+        );
+    }
+}
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Page reclaim.
+//!
+//! When memory runs out, pages of VMOs that are used by nobody else are
+//! reclaimed: clean pages of the page cache are evicted, and pages of
+//! anonymous VMOs are swapped out to the swap devices.
+//!
+//! Pages mapped to user space cannot be reclaimed until they are unmapped.
+//! Like the clock algorithm, the accessed bits of the mapped pages are cleared
+//! on each pass, and the pages still not accessed on the next pass are
+//! unmapped. So the pages that have been accessed recently stay in memory.
+//!
+//! Reclaim is triggered when the frame allocator runs out of memory. Since the
+//! allocating thread may hold any lock, reclaim never waits for the locks of
+//! the VMARs, the VMOs and the page caches. It is skipped for allocations in
+//! atomic mode, e.g., when a page table is locked while handling a page
+//! fault, so failed page faults are retried after reclaiming memory.
+
+use ostd::mm::frame::allocator::register_reclaim_handler;
+
+use super::vmo;
+use crate::{prelude::*, process::process_table};
+
+/// The number of pages to reclaim when memory runs out.
+pub const RECLAIM_BATCH_PAGES: usize = 32;
+
+/// The maximum number of passes to unmap idle pages in one reclaim.
+///
+/// Two passes are enough for an idle page to be unmapped since its accessed
+/// bit is cleared in the first pass.
+const MAX_UNMAP_PASSES: usize = 2;
+
+/// The lock that serializes reclaim and `swapoff`.
+static RECLAIM_LOCK: Mutex<()> = Mutex::new(());
+
+/// Reclaims at most `nr_pages` pages, and returns the number of reclaimed
+/// pages.
+pub fn reclaim_pages(nr_pages: usize) -> usize {
+    let _guard = RECLAIM_LOCK.lock();
+    reclaim_pages_locked(nr_pages)
+}
+
+/// Reclaims memory for the frame allocator that runs out of memory.
+///
+/// Returns whether some pages have been reclaimed.
+fn reclaim_for_allocation(nr_frames: usize) -> bool {
+    // Reclaim may allocate memory itself, and `swapoff` allocates memory
+    // while holding the lock. So never wait for the lock here.
+    let Some(_guard) = RECLAIM_LOCK.try_lock() else {
+        return false;
+    };
+    reclaim_pages_locked(nr_frames.max(RECLAIM_BATCH_PAGES)) > 0
+}
+
+fn reclaim_pages_locked(nr_pages: usize) -> usize {
+    let mut nr_reclaimed = vmo::reclaim_pages(nr_pages);
+    for _ in 0..MAX_UNMAP_PASSES {
+        if nr_reclaimed >= nr_pages {
+            break;
+        }
+        if unmap_idle_pages() == 0 {
+            continue;
+        }
+        nr_reclaimed += vmo::reclaim_pages(nr_pages - nr_reclaimed);
+    }
+
+    nr_reclaimed
+}
+
+/// Unmaps the idle pages of all processes, and returns the number of
+/// unmapped pages.
+fn unmap_idle_pages() -> usize {
+    // The lock may be held by the current thread that triggers reclaim,
+    // so never wait for it.
+    let Some(process_table) = process_table::try_process_table_mut() else {
+        return 0;
+    };
+    let processes: Vec<_> = process_table.iter().cloned().collect();
+    drop(process_table);
+
+    processes
+        .iter()
+        .map(|process| process.root_vmar().unmap_idle_pages())
+        .sum()
+}
+
+/// Acquires the lock to prevent reclaim from running.
+pub(super) fn lock_reclaim() -> MutexGuard<'static, ()> {
+    RECLAIM_LOCK.lock()
+}
+
+pub(super) fn init() {
+    register_reclaim_handler(reclaim_for_allocation);
+}
@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Swap devices.
+//!
+//! Under memory pressure, the pages of anonymous VMOs are written to swap
+//! devices, which are block devices enabled by `swapon(2)`. Each page is
+//! stored in a page-sized slot of a swap device, which is represented by a
+//! [`SwapSlot`] owned by the VMO until the page is read back.
+//!
+//! A swap device must be formatted by `mkswap(8)`. The first page of the
+//! device is the header, which ends with the `SWAPSPACE2` signature.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.0/source/include/linux/swap.h>
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use aster_block::{
+    bio::{BioDirection, BioSegment, BioStatus},
+    id::Bid,
+    BlockDevice, SECTOR_SIZE,
+};
+use id_alloc::IdAlloc;
+use ostd::mm::{FrameAllocOptions, Segment, UFrame, VmIo};
+
+use crate::prelude::*;
+
+/// The signature at the end of the header page of a swap device.
+const SWAP_SIGNATURE: &[u8; 10] = b"SWAPSPACE2";
+/// The offset of the version in the header page.
+const SWAP_VERSION_OFFSET: usize = 1024;
+/// The offset of the index of the last page in the header page.
+const SWAP_LAST_PAGE_OFFSET: usize = 1028;
+/// The offset of the number of bad pages in the header page.
+const SWAP_NR_BAD_PAGES_OFFSET: usize = 1032;
+/// The offset of the indices of the bad pages in the header page.
+const SWAP_BAD_PAGES_OFFSET: usize = 1536;
+/// The maximum number of bad pages that the header page can record.
+const SWAP_MAX_BAD_PAGES: usize = (PAGE_SIZE - SWAP_SIGNATURE.len() - SWAP_BAD_PAGES_OFFSET) / 4;
+
+/// A swap device.
+pub struct SwapDevice {
+    /// The name of the block device.
+    name: String,
+    device: Arc<dyn BlockDevice>,
+    priority: i16,
+    /// The allocated slots, including the header and the bad pages.
+    slots: Mutex<IdAlloc>,
+    /// The number of slots that can hold pages.
+    nr_slots: usize,
+    /// The number of slots that are holding pages.
+    nr_used: AtomicUsize,
+}
+
+impl SwapDevice {
+    /// Opens the block device as a swap device by checking its header.
+    ///
+    /// The priority of the swap device is zero until it is set.
+    fn open(name: String, device: Arc<dyn BlockDevice>) -> Result<Self> {
+        let nr_pages = device.metadata().nr_sectors * SECTOR_SIZE / PAGE_SIZE;
+
+        let header: UFrame = FrameAllocOptions::new().alloc_frame()?.into();
+        read_page(device.as_ref(), 0, &header)?;
+
+        let mut signature = [0u8; SWAP_SIGNATURE.len()];
+        header.read_bytes(PAGE_SIZE - SWAP_SIGNATURE.len(), &mut signature)?;
+        if &signature != SWAP_SIGNATURE {
+            return_errno_with_message!(Errno::EINVAL, "the device is not a swap device");
+        }
+        if header.read_val::<u32>(SWAP_VERSION_OFFSET)? != 1 {
+            return_errno_with_message!(Errno::EINVAL, "the swap version is not supported");
+        }
+
+        let last_page = header.read_val::<u32>(SWAP_LAST_PAGE_OFFSET)? as usize;
+        let capacity = nr_pages.min(last_page + 1);
+        if capacity <= 1 {
+            return_errno_with_message!(Errno::EINVAL, "the swap device is too small");
+        }
+
+        let mut slots = IdAlloc::with_capacity(capacity);
+        slots.alloc_specific(0).unwrap();
+        let mut nr_slots = capacity - 1;
+
+        let nr_bad_pages = header.read_val::<u32>(SWAP_NR_BAD_PAGES_OFFSET)? as usize;
+        if nr_bad_pages > SWAP_MAX_BAD_PAGES {
+            return_errno_with_message!(Errno::EINVAL, "there are too many bad pages");
+        }
+        for i in 0..nr_bad_pages {
+            let bad_page = header.read_val::<u32>(SWAP_BAD_PAGES_OFFSET + i * 4)? as usize;
+            if bad_page < capacity && slots.alloc_specific(bad_page).is_some() {
+                nr_slots -= 1;
+            }
+        }
+
+        Ok(Self {
+            name,
+            device,
+            priority: 0,
+            slots: Mutex::new(slots),
+            nr_slots,
+            nr_used: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the name of the block device.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the priority of the swap device.
+    ///
+    /// Devices with higher priorities are used first.
+    pub fn priority(&self) -> i16 {
+        self.priority
+    }
+
+    /// Returns the size of the swap space, in bytes.
+    pub fn size(&self) -> usize {
+        self.nr_slots * PAGE_SIZE
+    }
+
+    /// Returns the size of the used swap space, in bytes.
+    pub fn used(&self) -> usize {
+        self.nr_used.load(Ordering::Relaxed) * PAGE_SIZE
+    }
+
+    /// Writes the frame to a free slot of the swap device.
+    fn write(self: &Arc<Self>, frame: &UFrame) -> Result<SwapSlot> {
+        let Some(index) = self.slots.lock().alloc() else {
+            return_errno_with_message!(Errno::ENOSPC, "the swap device is full");
+        };
+        self.nr_used.fetch_add(1, Ordering::Relaxed);
+        let slot = SwapSlot {
+            device: self.clone(),
+            index,
+        };
+
+        write_page(self.device.as_ref(), index, frame)?;
+        Ok(slot)
+    }
+}
+
+impl Debug for SwapDevice {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SwapDevice")
+            .field("name", &self.name)
+            .field("priority", &self.priority)
+            .field("nr_slots", &self.nr_slots)
+            .field("nr_used", &self.nr_used)
+            .finish()
+    }
+}
+
+/// A slot of a swap device that holds the content of a swapped-out page.
+///
+/// The slot is freed when it is dropped.
+#[derive(Debug)]
+pub struct SwapSlot {
+    device: Arc<SwapDevice>,
+    index: usize,
+}
+
+impl SwapSlot {
+    /// Returns the swap device of the slot.
+    pub fn device(&self) -> &Arc<SwapDevice> {
+        &self.device
+    }
+
+    /// Reads the page in the slot into a new frame.
+    pub fn read(&self) -> Result<UFrame> {
+        let frame: UFrame = FrameAllocOptions::new().zeroed(false).alloc_frame()?.into();
+        read_page(self.device.device.as_ref(), self.index, &frame)?;
+        Ok(frame)
+    }
+}
+
+impl Drop for SwapSlot {
+    fn drop(&mut self) {
+        self.device.slots.lock().free(self.index);
+        self.device.nr_used.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn read_page(device: &dyn BlockDevice, index: usize, frame: &UFrame) -> Result<()> {
+    let bio_segment =
+        BioSegment::new_from_segment(Segment::from(frame.clone()), BioDirection::FromDevice);
+    match device.read_blocks(Bid::new(index as u64), bio_segment)? {
+        BioStatus::Complete => Ok(()),
+        _ => return_errno_with_message!(Errno::EIO, "failed to read the swap device"),
+    }
+}
+
+fn write_page(device: &dyn BlockDevice, index: usize, frame: &UFrame) -> Result<()> {
+    let bio_segment =
+        BioSegment::new_from_segment(Segment::from(frame.clone()), BioDirection::ToDevice);
+    match device.write_blocks(Bid::new(index as u64), bio_segment)? {
+        BioStatus::Complete => Ok(()),
+        _ => return_errno_with_message!(Errno::EIO, "failed to write the swap device"),
+    }
+}
+
+/// The enabled swap devices, sorted by their priorities in descending order.
+static SWAP_DEVICES: Mutex<Vec<Arc<SwapDevice>>> = Mutex::new(Vec::new());
+
+/// Enables the block device as a swap device.
+///
+/// If `priority` is `None`, the device is given a priority lower than all the
+/// enabled devices, like Linux does.
+pub fn swap_on(name: &str, priority: Option<i16>) -> Result<()> {
+    let Some(device) = aster_block::get_device(name) else {
+        return_errno_with_message!(Errno::ENOENT, "the block device does not exist");
+    };
+
+    // Reading the header may trigger reclaim, which swaps pages out to the
+    // enabled swap devices. So the lock cannot be held here.
+    let mut swap_device = SwapDevice::open(name.to_string(), device)?;
+
+    let mut devices = SWAP_DEVICES.lock();
+    if devices.iter().any(|swap_device| swap_device.name == name) {
+        return_errno_with_message!(Errno::EBUSY, "the device is already a swap device");
+    }
+
+    let priority = priority.unwrap_or_else(|| {
+        devices
+            .iter()
+            .map(|swap_device| swap_device.priority)
+            .filter(|priority| *priority < 0)
+            .min()
+            .unwrap_or(0)
+            - 1
+    });
+    swap_device.priority = priority;
+
+    let pos = devices.partition_point(|device| device.priority >= priority);
+    devices.insert(pos, Arc::new(swap_device));
+    Ok(())
+}
+
+/// Disables the swap device.
+///
+/// All the pages in the swap device are read back into memory first. If it
+/// fails, e.g., due to the lack of memory, the swap device is kept enabled.
+pub fn swap_off(name: &str) -> Result<()> {
+    // No page should be swapped out to the device during swapping in.
+    let _guard = super::reclaim::lock_reclaim();
+
+    let swap_device = {
+        let mut devices = SWAP_DEVICES.lock();
+        let Some(pos) = devices.iter().position(|device| device.name == name) else {
+            return_errno_with_message!(Errno::EINVAL, "the device is not a swap device");
+        };
+        devices.remove(pos)
+    };
+
+    if let Err(err) = super::vmo::swap_in_pages(&swap_device) {
+        let mut devices = SWAP_DEVICES.lock();
+        let pos = devices.partition_point(|device| device.priority >= swap_device.priority);
+        devices.insert(pos, swap_device);
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Returns the enabled swap devices.
+pub fn swap_devices() -> Vec<Arc<SwapDevice>> {
+    SWAP_DEVICES.lock().clone()
+}
+
+/// Returns the total size of the swap space, in bytes.
+pub fn swap_total() -> usize {
+    SWAP_DEVICES.lock().iter().map(|device| device.size()).sum()
+}
+
+/// Returns the size of the free swap space, in bytes.
+pub fn swap_free() -> usize {
+    SWAP_DEVICES
+        .lock()
+        .iter()
+        .map(|device| device.size() - device.used())
+        .sum()
+}
+
+/// Writes the frame to a swap device.
+///
+/// The swap devices with higher priorities are tried first.
+pub(super) fn swap_out(frame: &UFrame) -> Result<SwapSlot> {
+    for device in swap_devices() {
+        match device.write(frame) {
+            Err(err) if err.error() == Errno::ENOSPC => continue,
+            result => return result,
+        }
+    }
+
+    return_errno_with_message!(Errno::ENOSPC, "no swap space is available");
+}
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Transparent huge pages (THP).
+//!
+//! When a page fault happens in an anonymous or tmpfs-backed mapping, the
+//! kernel may populate the whole huge-page-aligned region around the faulting
+//! address with a single huge page, instead of a base page. This reduces the
+//! page faults and the TLB misses for programs with large working sets.
+//!
+//! Whether a mapping is populated with huge pages is decided by the system-wide
+//! [`ThpMode`], which can be changed via
+//! `/sys/kernel/mm/transparent_hugepage/enabled`, and the per-mapping
+//! [`ThpAdvice`], which can be changed via `madvise(MADV_HUGEPAGE)` and
+//! `madvise(MADV_NOHUGEPAGE)`.
+
+use core::{
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use crate::prelude::*;
+
+/// The system-wide mode of transparent huge pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+#[repr(u8)]
+pub enum ThpMode {
+    /// Huge pages are used for all mappings, unless `MADV_NOHUGEPAGE` is given.
+    Always = 0,
+    /// Huge pages are only used for mappings given `MADV_HUGEPAGE`.
+    Madvise = 1,
+    /// Huge pages are never used.
+    Never = 2,
+}
+
+static THP_MODE: AtomicU8 = AtomicU8::new(ThpMode::Madvise as u8);
+
+impl ThpMode {
+    const ALL: [Self; 3] = [Self::Always, Self::Madvise, Self::Never];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::Madvise => "madvise",
+            Self::Never => "never",
+        }
+    }
+
+    /// Returns whether a mapping with the advice should use huge pages.
+    fn allows(&self, advice: ThpAdvice) -> bool {
+        match self {
+            Self::Always => advice != ThpAdvice::NoHugePage,
+            Self::Madvise => advice == ThpAdvice::HugePage,
+            Self::Never => false,
+        }
+    }
+}
+
+impl FromStr for ThpMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name() == s)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid THP mode"))
+    }
+}
+
+impl core::fmt::Display for ThpMode {
+    /// Formats all the modes with the current one in brackets, which is the
+    /// format of `/sys/kernel/mm/transparent_hugepage/enabled`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, mode) in Self::ALL.iter().enumerate() {
+            if i != 0 {
+                f.write_str(" ")?;
+            }
+            if mode == self {
+                write!(f, "[{}]", mode.name())?;
+            } else {
+                f.write_str(mode.name())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the system-wide mode of transparent huge pages.
+pub fn thp_mode() -> ThpMode {
+    ThpMode::try_from(THP_MODE.load(Ordering::Relaxed)).unwrap()
+}
+
+/// Sets the system-wide mode of transparent huge pages.
+///
+/// The mode only affects the following page faults. Existing huge pages are
+/// kept.
+pub fn set_thp_mode(mode: ThpMode) {
+    THP_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// The advice on whether a mapping should use transparent huge pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThpAdvice {
+    /// No advice is given. The system-wide mode decides.
+    #[default]
+    Default,
+    /// The mapping is worth backing with huge pages (`MADV_HUGEPAGE`).
+    HugePage,
+    /// The mapping is not worth backing with huge pages (`MADV_NOHUGEPAGE`).
+    NoHugePage,
+}
+
+impl ThpAdvice {
+    /// Returns whether a mapping with the advice should use huge pages under
+    /// the current system-wide mode.
+    pub fn allows_huge_page(&self) -> bool {
+        thp_mode().allows(*self)
+    }
+}
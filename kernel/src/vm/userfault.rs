@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Page faults handled by the user space.
+//!
+//! A range of the memory can be registered to a userfaultfd, whose state is
+//! kept in a [`UserfaultCtx`]. When a page fault in the range should be
+//! handled by the user space, e.g., because the page is missing, the faulting
+//! thread queues the fault to the context and sleeps. A handler thread reads
+//! the fault from the userfaultfd and resolves it, e.g., by copying the
+//! content to the page. Then the faulting thread is woken up to retry the
+//! memory access.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/mm/userfaultfd.html>
+
+use core::{
+    fmt,
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use align_ext::AlignExt;
+use ostd::{mm::PageFlags, sync::WaitQueue};
+
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::{signal::Pollee, Process},
+    thread::Tid,
+};
+
+bitflags! {
+    /// The kinds of page faults in a registered range that are handled by the
+    /// user space.
+    pub struct UserfaultMode: u64 {
+        /// Faults on missing pages.
+        const MISSING = 1 << 0;
+        /// Write faults on write-protected pages.
+        const WP      = 1 << 1;
+    }
+}
+
+/// The page flag that marks a page as write-protected by a userfaultfd.
+///
+/// Such pages are mapped as read-only. The flag tells a write fault on them
+/// from a copy-on-write fault.
+pub(super) const PAGE_FLAG_UFFD_WP: PageFlags = PageFlags::AVAIL1;
+
+/// The registration of a mapping to a userfaultfd.
+#[derive(Debug, Clone)]
+pub struct UserfaultRegistration {
+    pub ctx: Arc<UserfaultCtx>,
+    pub mode: UserfaultMode,
+}
+
+/// A page fault to be handled by the user space.
+#[derive(Debug, Clone, Copy)]
+pub struct Userfault {
+    /// The faulting address.
+    pub address: Vaddr,
+    /// Whether the fault is caused by a write access.
+    pub is_write: bool,
+    /// Whether the fault is caused by writing a write-protected page.
+    pub is_wp: bool,
+    /// The ID of the faulting thread.
+    pub tid: Tid,
+}
+
+/// The state of a userfaultfd.
+pub struct UserfaultCtx {
+    /// The process whose memory is handled by the userfaultfd.
+    process: Weak<Process>,
+    faults: SpinLock<PendingFaults>,
+    /// Whether the userfaultfd has been closed.
+    ///
+    /// The page faults in the registered ranges of a released context are
+    /// handled by the kernel as usual.
+    is_released: AtomicBool,
+    pollee: Pollee,
+    /// The wait queue of the faulting threads.
+    wait_queue: WaitQueue,
+}
+
+struct PendingFaults {
+    faults: VecDeque<PendingFault>,
+    next_id: u64,
+}
+
+struct PendingFault {
+    id: u64,
+    fault: Userfault,
+    /// Whether the fault has been read from the userfaultfd.
+    is_read: bool,
+}
+
+impl UserfaultCtx {
+    /// Creates a context that handles the page faults of the process.
+    pub fn new(process: &Arc<Process>) -> Arc<Self> {
+        Arc::new(Self {
+            process: Arc::downgrade(process),
+            faults: SpinLock::new(PendingFaults {
+                faults: VecDeque::new(),
+                next_id: 0,
+            }),
+            is_released: AtomicBool::new(false),
+            pollee: Pollee::new(),
+            wait_queue: WaitQueue::new(),
+        })
+    }
+
+    /// Returns the process whose memory is handled by the userfaultfd.
+    pub fn process(&self) -> Result<Arc<Process>> {
+        self.process
+            .upgrade()
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process has exited"))
+    }
+
+    /// Returns the pollee that is notified when faults are queued.
+    pub fn pollee(&self) -> &Pollee {
+        &self.pollee
+    }
+
+    /// Returns whether there are faults that have not been read.
+    pub fn has_unread_faults(&self) -> bool {
+        self.faults
+            .lock()
+            .faults
+            .iter()
+            .any(|pending| !pending.is_read)
+    }
+
+    /// Reads the earliest fault that has not been read.
+    ///
+    /// The faulting thread keeps sleeping until it is woken up by
+    /// [`Self::wake`].
+    pub fn read_fault(&self) -> Option<Userfault> {
+        let mut faults = self.faults.lock();
+        let pending = faults.faults.iter_mut().find(|pending| !pending.is_read)?;
+        pending.is_read = true;
+        Some(pending.fault)
+    }
+
+    /// Wakes up the threads faulting on the pages in `range`.
+    pub fn wake(&self, range: &Range<Vaddr>) {
+        self.faults
+            .lock()
+            .faults
+            .retain(|pending| !range.contains(&pending.fault.address.align_down(PAGE_SIZE)));
+        self.pollee.invalidate();
+        self.wait_queue.wake_all();
+    }
+
+    /// Releases the context when the userfaultfd is closed.
+    ///
+    /// All faulting threads are woken up, and the page faults are handled by
+    /// the kernel from now on.
+    pub fn release(&self) {
+        self.is_released.store(true, Ordering::Relaxed);
+        self.faults.lock().faults.clear();
+        self.wait_queue.wake_all();
+    }
+
+    /// Returns whether the userfaultfd has been closed.
+    pub fn is_released(&self) -> bool {
+        self.is_released.load(Ordering::Relaxed)
+    }
+
+    /// Queues the fault to be read from the userfaultfd.
+    ///
+    /// This method does not sleep, so it can be called when the page table
+    /// is locked. The caller should then wait for the fault to be resolved by
+    /// [`QueuedUserfault::wait`].
+    pub(super) fn queue_fault(self: &Arc<Self>, fault: Userfault) -> Option<QueuedUserfault> {
+        if self.is_released() {
+            return None;
+        }
+
+        let mut faults = self.faults.lock();
+        let id = faults.next_id;
+        faults.next_id += 1;
+        faults.faults.push_back(PendingFault {
+            id,
+            fault,
+            is_read: false,
+        });
+
+        Some(QueuedUserfault {
+            ctx: self.clone(),
+            id,
+        })
+    }
+
+    fn is_pending(&self, id: u64) -> bool {
+        !self.is_released()
+            && self
+                .faults
+                .lock()
+                .faults
+                .iter()
+                .any(|pending| pending.id == id)
+    }
+}
+
+impl Debug for UserfaultCtx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserfaultCtx")
+            .field("is_released", &self.is_released)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A fault queued to a userfaultfd.
+pub(super) struct QueuedUserfault {
+    ctx: Arc<UserfaultCtx>,
+    id: u64,
+}
+
+impl QueuedUserfault {
+    /// Waits until the fault is resolved by the user space.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EINTR`] if the wait is interrupted by a signal. The fault is
+    /// then removed from the userfaultfd.
+    ///
+    /// [`EINTR`]: crate::error::Errno::EINTR
+    pub(super) fn wait(self) -> Result<()> {
+        self.ctx.pollee.notify(IoEvents::IN);
+
+        let result = self
+            .ctx
+            .wait_queue
+            .pause_until(|| (!self.ctx.is_pending(self.id)).then_some(()));
+        if result.is_err() {
+            self.ctx
+                .faults
+                .lock()
+                .faults
+                .retain(|pending| pending.id != self.id);
+        }
+        result
+    }
+}
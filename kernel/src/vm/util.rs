@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use ostd::mm::{Frame, FrameAllocOptions, UFrame, UntypedMem};
+use align_ext::AlignExt;
+use ostd::mm::{vm_space::VmItem, Frame, FrameAllocOptions, PageProperty, UFrame, UntypedMem};
 
 use crate::prelude::*;
 
@@ -12,3 +13,20 @@ pub fn duplicate_frame(src: &UFrame) -> Result<Frame<()>> {
     new_frame.writer().write(&mut src.reader());
     Ok(new_frame)
 }
+
+/// Returns the frame that maps the base page at `page_addr` and the property
+/// of the mapping, given the item queried at the address.
+///
+/// If the page is mapped as a part of a huge page, the frame is the base frame
+/// of the huge page that covers the base page.
+pub fn mapped_frame_at(item: VmItem, page_addr: Vaddr) -> Option<(UFrame, PageProperty)> {
+    match item {
+        VmItem::NotMapped { .. } => None,
+        VmItem::Mapped { frame, prop, .. } => Some((frame, prop)),
+        VmItem::MappedHuge { va, frames, prop } => {
+            let offset = page_addr.align_down(PAGE_SIZE) - va;
+            let frame = frames.slice(&(offset..offset + PAGE_SIZE)).next().unwrap();
+            Some((frame, prop))
+        }
+    }
+}
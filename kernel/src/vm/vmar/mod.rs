@@ -13,19 +13,24 @@ use align_ext::AlignExt;
 use aster_rights::Rights;
 use ostd::{
     cpu::CpuExceptionInfo,
-    mm::{tlb::TlbFlushOp, PageFlags, PageProperty, VmSpace, MAX_USERSPACE_VADDR},
+    mm::{tlb::TlbFlushOp, PageFlags, PageProperty, UFrame, VmIo, VmSpace, MAX_USERSPACE_VADDR},
 };
 
 use self::{
     interval_set::{Interval, IntervalSet},
-    vm_mapping::{MappedVmo, VmMapping},
+    vm_mapping::{MappedVmo, VmLockMode, VmMapping, VmMappingInfo},
 };
 use super::page_fault_handler::PageFaultHandler;
 use crate::{
+    fs::{path::Dentry, utils::WritableMappingGuard},
+    ipc::shm::ShmAttachment,
     prelude::*,
     thread::exception::{handle_page_fault_from_vm_space, PageFaultInfo},
     vm::{
         perms::VmPerms,
+        reclaim::{reclaim_pages, RECLAIM_BATCH_PAGES},
+        thp::ThpAdvice,
+        userfault::{UserfaultCtx, UserfaultMode, UserfaultRegistration},
         vmo::{Vmo, VmoRightsOp},
     },
 };
@@ -88,6 +93,24 @@ impl<R> Vmar<R> {
         self.0.vm_space()
     }
 
+    /// Unmaps the pages that have not been accessed recently, so that they
+    /// can be reclaimed.
+    ///
+    /// Returns the number of unmapped pages.
+    pub(in crate::vm) fn unmap_idle_pages(&self) -> usize {
+        self.0.unmap_idle_pages()
+    }
+
+    /// Returns the size of the memory mapped in the VMAR, in bytes.
+    pub fn rss(&self) -> usize {
+        self.0.rss(&(self.0.base..self.0.base + self.0.size))
+    }
+
+    /// Returns the size of the memory mapped in `range`, in bytes.
+    pub fn rss_in(&self, range: Range<Vaddr>) -> usize {
+        self.0.rss(&range)
+    }
+
     /// Resizes the original mapping.
     ///
     /// The range of the mapping goes from `map_addr..map_addr + old_size` to
@@ -104,6 +127,366 @@ impl<R> Vmar<R> {
     pub fn resize_mapping(&self, map_addr: Vaddr, old_size: usize, new_size: usize) -> Result<()> {
         self.0.resize_mapping(map_addr, old_size, new_size)
     }
+
+    /// Remaps the pages in `old_addr..old_addr + old_size` to a range of
+    /// `new_size` bytes, and returns the start address of the new range.
+    ///
+    /// The old range must be within a single [`VmMapping`]. `target` decides
+    /// where the new range is. If the pages are moved, their page table
+    /// entries are moved to the new range without copying the contents, and
+    /// the new mapping keeps the attributes of the old one.
+    ///
+    /// If `dont_unmap` is true, the pages are always moved, and the old range
+    /// remains mapped without any pages. In this case, `old_size` must be
+    /// equal to `new_size`.
+    pub fn remap(
+        &self,
+        old_addr: Vaddr,
+        old_size: usize,
+        new_size: usize,
+        target: RemapTarget,
+        dont_unmap: bool,
+    ) -> Result<Vaddr> {
+        self.0
+            .remap(old_addr, old_size, new_size, target, dont_unmap)
+    }
+
+    /// Reads the memory at `vaddr` into `buf`.
+    ///
+    /// Unlike accessing the memory via [`VmSpace`], the VMAR does not need to
+    /// be activated on the current CPU. So this method can be used to access
+    /// the memory of other processes, e.g., by `ptrace`.
+    ///
+    /// The page permissions are bypassed.
+    ///
+    /// FIXME: This function should require access control
+    pub fn read_remote(&self, vaddr: Vaddr, buf: &mut [u8]) -> Result<()> {
+        let range = vaddr..vaddr.checked_add(buf.len()).ok_or(Errno::EFAULT)?;
+        self.0
+            .access_remote(range, false, |frame, offset, buf_range| {
+                frame.read_bytes(offset, &mut buf[buf_range])
+            })
+    }
+
+    /// Writes `buf` into the memory at `vaddr`.
+    ///
+    /// Like [`Self::read_remote`], this method can be used to access the memory
+    /// of other processes. The page permissions are bypassed, so that, e.g.,
+    /// debuggers can insert breakpoints into read-only code. Writing to a
+    /// private mapping only modifies the private copy of the pages.
+    ///
+    /// FIXME: This function should require access control
+    pub fn write_remote(&self, vaddr: Vaddr, buf: &[u8]) -> Result<()> {
+        let range = vaddr..vaddr.checked_add(buf.len()).ok_or(Errno::EFAULT)?;
+        self.0
+            .access_remote(range, true, |frame, offset, buf_range| {
+                frame.write_bytes(offset, &buf[buf_range])
+            })
+    }
+
+    /// Returns the information of all mappings, in ascending order of their
+    /// addresses.
+    pub fn mappings_info(&self) -> Vec<VmMappingInfo> {
+        let inner = self.0.inner.read();
+        inner.vm_mappings.iter().map(VmMapping::info).collect()
+    }
+
+    /// Reads the page at `page_addr` into `buf` for a core dump.
+    ///
+    /// Returns `Ok(false)` if the page is an untouched anonymous page, whose
+    /// content should be treated as zeros.
+    ///
+    /// FIXME: This function should require access control
+    pub fn read_page_for_dump(&self, page_addr: Vaddr, buf: &mut [u8]) -> Result<bool> {
+        debug_assert!(page_addr % PAGE_SIZE == 0);
+
+        let inner = self.0.inner.read();
+        let Some(vm_mapping) = inner.vm_mappings.find_one(&page_addr) else {
+            return_errno_with_message!(Errno::EIO, "the address is not mapped");
+        };
+        vm_mapping.read_page_for_dump(&self.0.vm_space, page_addr, buf)
+    }
+
+    /// Sets whether the mappings in `range` are excluded from core dumps.
+    ///
+    /// The mappings will be split if they are partially covered by `range`.
+    /// If some pages in `range` are not mapped, this method will still update
+    /// the mapped pages, but return [`Errno::ENOMEM`].
+    pub fn set_dont_dump(&self, range: Range<Vaddr>, is_dont_dump: bool) -> Result<()> {
+        self.0.update_mappings(
+            range,
+            |vm_mapping| vm_mapping.is_dont_dump() != is_dont_dump,
+            |vm_mapping| vm_mapping.set_dont_dump(is_dont_dump),
+        )
+    }
+
+    /// Sets the advice on whether the mappings in `range` should use
+    /// transparent huge pages.
+    ///
+    /// The mappings will be split if they are partially covered by `range`.
+    /// If some pages in `range` are not mapped, this method will still update
+    /// the mapped pages, but return [`Errno::ENOMEM`].
+    ///
+    /// Huge pages that are already mapped are kept.
+    pub fn set_thp_advice(&self, range: Range<Vaddr>, advice: ThpAdvice) -> Result<()> {
+        self.0.update_mappings(
+            range,
+            |vm_mapping| vm_mapping.thp_advice() != advice,
+            |vm_mapping| vm_mapping.set_thp_advice(advice),
+        )
+    }
+
+    /// Sets whether the mappings in `range` are not inherited by the child on
+    /// fork.
+    ///
+    /// The mappings will be split if they are partially covered by `range`.
+    /// If some pages in `range` are not mapped, this method will still update
+    /// the mapped pages, but return [`Errno::ENOMEM`].
+    pub fn set_dont_fork(&self, range: Range<Vaddr>, is_dont_fork: bool) -> Result<()> {
+        self.0.update_mappings(
+            range,
+            |vm_mapping| vm_mapping.is_dont_fork() != is_dont_fork,
+            |vm_mapping| vm_mapping.set_dont_fork(is_dont_fork),
+        )
+    }
+
+    /// Sets whether the child gets zero-filled pages instead of a copy of the
+    /// pages in `range` on fork.
+    ///
+    /// Only private anonymous mappings can be wiped on fork. Otherwise, this
+    /// method fails with [`Errno::EINVAL`] without updating any mapping.
+    pub fn set_wipe_on_fork(&self, range: Range<Vaddr>, is_wipe_on_fork: bool) -> Result<()> {
+        self.0.set_wipe_on_fork(range, is_wipe_on_fork)
+    }
+
+    /// Checks whether all pages in `range` are mapped.
+    ///
+    /// Returns [`Errno::ENOMEM`] if not.
+    pub fn check_mapped(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.for_each_mapping(&range, |_, _| Ok(()))
+    }
+
+    /// Unmaps the pages in `range` from the page table, while keeping the
+    /// mappings.
+    ///
+    /// The pages of private anonymous mappings are discarded, so zero-filled
+    /// pages will be faulted in when they are accessed again. The pages of
+    /// other mappings will be faulted in from the files or the shared memory,
+    /// which loses the private copies made by copy-on-write.
+    ///
+    /// Locked pages cannot be discarded unless `can_discard_locked` is true.
+    pub fn discard_pages(&self, range: Range<Vaddr>, can_discard_locked: bool) -> Result<()> {
+        self.0.for_each_mapping(&range, |vm_mapping, range| {
+            if vm_mapping.lock().is_some() && !can_discard_locked {
+                return_errno_with_message!(Errno::EINVAL, "the mapping is locked");
+            }
+            vm_mapping.discard_pages(&self.0.vm_space, range)
+        })
+    }
+
+    /// Frees the pages in `range`, which must be in private anonymous
+    /// mappings.
+    ///
+    /// Zero-filled pages will be faulted in when the pages are accessed
+    /// again.
+    pub fn free_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.for_each_mapping(&range, |vm_mapping, range| {
+            if vm_mapping.lock().is_some() || !vm_mapping.is_private_anonymous() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "only the pages of unlocked private anonymous mappings can be freed"
+                );
+            }
+            vm_mapping.discard_pages(&self.0.vm_space, range)
+        })
+    }
+
+    /// Punches a hole in the files or the shared memory mapped in `range`,
+    /// so that the pages read as zeros afterwards.
+    ///
+    /// The mappings in `range` must be shared mappings that may write to the
+    /// files or the shared memory.
+    pub fn punch_hole(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0
+            .for_each_mapping(&range, |vm_mapping, range| vm_mapping.punch_hole(range))
+    }
+
+    /// Marks the pages in `range` as not accessed recently, so that they are
+    /// the first to be reclaimed when memory runs out.
+    pub fn deactivate_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.for_each_mapping(&range, |vm_mapping, range| {
+            if vm_mapping.lock().is_some() {
+                return_errno_with_message!(Errno::EINVAL, "the mapping is locked");
+            }
+            vm_mapping.deactivate_pages(&self.0.vm_space, range)
+        })
+    }
+
+    /// Reclaims the pages in `range` right away.
+    ///
+    /// Only the pages of files and shared memory that are used by nobody else
+    /// can be reclaimed. The other pages are kept.
+    pub fn page_out(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.for_each_mapping(&range, |vm_mapping, range| {
+            if vm_mapping.lock().is_some() {
+                return_errno_with_message!(Errno::EINVAL, "the mapping is locked");
+            }
+            vm_mapping.page_out(&self.0.vm_space, range)
+        })
+    }
+
+    /// Reads the pages of the files mapped in `range` ahead, so that the
+    /// page faults on them will not wait for the I/O.
+    pub fn prefetch(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.for_each_mapping(&range, |vm_mapping, range| {
+            vm_mapping.prefetch_pages(range);
+            Ok(())
+        })
+    }
+
+    /// Sets how the pages of the mappings in `range` are locked in memory, or
+    /// unlocks them if `lock` is `None`.
+    ///
+    /// The mappings will be split if they are partially covered by `range`.
+    /// If some pages in `range` are not mapped, this method will still update
+    /// the mapped pages, but return [`Errno::ENOMEM`].
+    ///
+    /// The pages are not populated by this method. Use [`Self::populate`] to
+    /// populate them.
+    pub fn set_lock(&self, range: Range<Vaddr>, lock: Option<VmLockMode>) -> Result<()> {
+        self.0.update_mappings(
+            range,
+            |vm_mapping| vm_mapping.lock() != lock,
+            |vm_mapping| vm_mapping.set_lock(lock),
+        )
+    }
+
+    /// Sets how the pages of all mappings are locked in memory, or unlocks
+    /// them if `lock` is `None`.
+    pub fn set_lock_all(&self, lock: Option<VmLockMode>) -> Result<()> {
+        let result = self.set_lock(self.0.base..self.0.base + self.0.size, lock);
+        // The holes between the mappings are ignored.
+        if let Err(err) = result
+            && err.error() != Errno::ENOMEM
+        {
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Returns how the pages of new mappings are locked in memory, if they
+    /// are.
+    pub fn future_lock(&self) -> Option<VmLockMode> {
+        self.0.inner.read().future_lock
+    }
+
+    /// Sets how the pages of new mappings are locked in memory, or stops
+    /// locking them if `lock` is `None`.
+    pub fn set_future_lock(&self, lock: Option<VmLockMode>) {
+        self.0.inner.write().future_lock = lock;
+    }
+
+    /// Returns the size of the locked pages in `range`, in bytes.
+    pub fn locked_size(&self, range: Range<Vaddr>) -> usize {
+        let inner = self.0.inner.read();
+        inner
+            .vm_mappings
+            .find(&range)
+            .filter(|vm_mapping| vm_mapping.lock().is_some())
+            .map(|vm_mapping| get_intersected_range(&range, &vm_mapping.range()).len())
+            .sum()
+    }
+
+    /// Populates the pages in `range` by faulting them in.
+    ///
+    /// Like Linux, the pages of writable private mappings are populated for
+    /// writing, so that they are not shared with others by copy-on-write.
+    /// The holes and the mappings that cannot be accessed are skipped.
+    pub fn populate(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.populate(range)
+    }
+
+    /// Faults in the pages in `range` for reading or writing.
+    ///
+    /// Unlike [`Self::populate`], this method fails with [`Errno::EINVAL`] if
+    /// some mappings in `range` cannot be accessed so, with [`Errno::ENOMEM`]
+    /// if some pages in `range` are not mapped, and with [`Errno::EFAULT`] if
+    /// accessing some pages would cause a `SIGBUS`.
+    pub fn prefault(&self, range: Range<Vaddr>, is_write: bool) -> Result<()> {
+        self.0.prefault(range, is_write)
+    }
+
+    /// Registers the mappings in `range` to the userfaultfd.
+    ///
+    /// The range must contain at least one mapping, and the mappings must
+    /// support userfaultfd. The mappings will be split if they are partially covered
+    /// by `range`.
+    pub fn register_userfault(
+        &self,
+        range: Range<Vaddr>,
+        registration: UserfaultRegistration,
+    ) -> Result<()> {
+        self.0.register_userfault(range, registration)
+    }
+
+    /// Unregisters the mappings in `range` from the userfaultfd.
+    ///
+    /// The write protection of the pages is removed, and the threads faulting
+    /// in `range` are woken up.
+    pub fn unregister_userfault(&self, range: Range<Vaddr>, ctx: &Arc<UserfaultCtx>) -> Result<()> {
+        self.0.unregister_userfault(range, ctx)
+    }
+
+    /// Maps the frame to the missing page at `page_addr` in a mapping that is
+    /// registered to the userfaultfd.
+    ///
+    /// If `is_wp` is true, the page is write-protected for the userfaultfd.
+    pub fn fill_userfault_page(
+        &self,
+        ctx: &Arc<UserfaultCtx>,
+        page_addr: Vaddr,
+        frame: UFrame,
+        is_wp: bool,
+    ) -> Result<()> {
+        debug_assert!(page_addr % PAGE_SIZE == 0);
+        self.0.fill_userfault_page(ctx, page_addr, frame, is_wp)
+    }
+
+    /// Write-protects the pages in `range` for the userfaultfd, or removes
+    /// the protection.
+    ///
+    /// The mappings in `range` must be registered to the userfaultfd in the
+    /// write-protect mode.
+    pub fn set_userfault_wp(
+        &self,
+        ctx: &Arc<UserfaultCtx>,
+        range: Range<Vaddr>,
+        is_wp: bool,
+    ) -> Result<()> {
+        self.0.set_userfault_wp(ctx, range, is_wp)
+    }
+
+    /// Detaches the System V shared memory segment attached at `addr`.
+    ///
+    /// All the mappings of the segment that are derived from the attachment,
+    /// i.e., the mappings within the size of the segment from `addr`, are
+    /// removed.
+    pub fn remove_shm_mapping(&self, addr: Vaddr) -> Result<()> {
+        self.0.remove_shm_mapping(addr)
+    }
+}
+
+/// Where [`Vmar::remap`] places the remapped pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemapTarget {
+    /// The pages stay at the original address.
+    InPlace,
+    /// The pages stay at the original address if the mapping can be resized
+    /// there. Otherwise, they are moved to a free region.
+    MayMove,
+    /// The pages are moved to the specified address. The existing mappings
+    /// there are removed.
+    Fixed(Vaddr),
 }
 
 pub(super) struct Vmar_ {
@@ -120,15 +503,64 @@ pub(super) struct Vmar_ {
 struct VmarInner {
     /// The mapped pages and associated metadata.
     vm_mappings: IntervalSet<Vaddr, VmMapping>,
+    /// How the pages of new mappings are locked in memory, if they are.
+    ///
+    /// This is set by `mlockall(MCL_FUTURE)`.
+    future_lock: Option<VmLockMode>,
 }
 
 impl VmarInner {
     const fn new() -> Self {
         Self {
             vm_mappings: IntervalSet::new(),
+            future_lock: None,
         }
     }
 
+    /// Updates the attributes of the mappings in `range` with `update`.
+    ///
+    /// Only the mappings for which `needs_update` returns `true` are updated.
+    /// Such mappings are split if they are partially covered by `range`.
+    fn update_mappings(
+        &mut self,
+        range: Range<Vaddr>,
+        needs_update: impl Fn(&VmMapping) -> bool,
+        update: impl Fn(VmMapping) -> VmMapping,
+    ) -> Result<()> {
+        assert!(range.start % PAGE_SIZE == 0);
+        assert!(range.end % PAGE_SIZE == 0);
+
+        let mut mapped_size = 0;
+        let mut update_mappings = Vec::new();
+        for vm_mapping in self.vm_mappings.find(&range) {
+            mapped_size += get_intersected_range(&range, &vm_mapping.range()).len();
+            if needs_update(vm_mapping) {
+                update_mappings.push(vm_mapping.map_to_addr());
+            }
+        }
+
+        for vm_mapping_addr in update_mappings {
+            let vm_mapping = self.vm_mappings.remove(&vm_mapping_addr).unwrap();
+            let vm_mapping_range = vm_mapping.range();
+            let intersected_range = get_intersected_range(&range, &vm_mapping_range);
+
+            let (left, taken, right) = vm_mapping.split_range(&intersected_range)?;
+            self.vm_mappings.insert(update(taken));
+            if let Some(left) = left {
+                self.vm_mappings.insert(left);
+            }
+            if let Some(right) = right {
+                self.vm_mappings.insert(right);
+            }
+        }
+
+        if mapped_size != range.len() {
+            return_errno_with_message!(Errno::ENOMEM, "the range is not fully mapped");
+        }
+
+        Ok(())
+    }
+
     /// Allocates a free region for mapping with a specific offset and size.
     ///
     /// If the provided range is already occupied, return an error.
@@ -249,9 +681,7 @@ impl Vmar_ {
     }
 
     fn new_root() -> Arc<Self> {
-        let vmar_inner = VmarInner {
-            vm_mappings: IntervalSet::new(),
-        };
+        let vmar_inner = VmarInner::new();
         let mut vm_space = VmSpace::new();
         vm_space.register_page_fault_handler(handle_page_fault_wrapper);
         Vmar_::new(vmar_inner, Arc::new(vm_space), 0, ROOT_VMAR_CAP_ADDR)
@@ -272,6 +702,7 @@ impl Vmar_ {
         let mut protect_mappings = Vec::new();
 
         for vm_mapping in inner.vm_mappings.find(&range) {
+            vm_mapping.check_protect(perms)?;
             protect_mappings.push((vm_mapping.map_to_addr(), vm_mapping.perms()));
         }
 
@@ -301,6 +732,68 @@ impl Vmar_ {
         Ok(())
     }
 
+    /// Updates the attributes of the mappings in `range` with `update`.
+    ///
+    /// See [`VmarInner::update_mappings`].
+    fn update_mappings(
+        &self,
+        range: Range<Vaddr>,
+        needs_update: impl Fn(&VmMapping) -> bool,
+        update: impl Fn(VmMapping) -> VmMapping,
+    ) -> Result<()> {
+        self.inner
+            .write()
+            .update_mappings(range, needs_update, update)
+    }
+
+    /// Calls `op` with each mapping in `range` and the part of the mapping
+    /// that is in `range`.
+    ///
+    /// Like Linux, the mapped parts are operated on even if some pages in
+    /// `range` are not mapped, but [`Errno::ENOMEM`] is returned then.
+    fn for_each_mapping(
+        &self,
+        range: &Range<Vaddr>,
+        mut op: impl FnMut(&VmMapping, &Range<Vaddr>) -> Result<()>,
+    ) -> Result<()> {
+        let inner = self.inner.read();
+
+        let mut mapped_size = 0;
+        for vm_mapping in inner.vm_mappings.find(range) {
+            let intersected_range = get_intersected_range(range, &vm_mapping.range());
+            op(vm_mapping, &intersected_range)?;
+            mapped_size += intersected_range.len();
+        }
+
+        if mapped_size != range.len() {
+            return_errno_with_message!(Errno::ENOMEM, "the range is not fully mapped");
+        }
+
+        Ok(())
+    }
+
+    fn set_wipe_on_fork(&self, range: Range<Vaddr>, is_wipe_on_fork: bool) -> Result<()> {
+        let mut inner = self.inner.write();
+
+        if is_wipe_on_fork
+            && inner
+                .vm_mappings
+                .find(&range)
+                .any(|vm_mapping| !vm_mapping.is_private_anonymous())
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "only private anonymous mappings can be wiped on fork"
+            );
+        }
+
+        inner.update_mappings(
+            range,
+            |vm_mapping| vm_mapping.is_wipe_on_fork() != is_wipe_on_fork,
+            |vm_mapping| vm_mapping.set_wipe_on_fork(is_wipe_on_fork),
+        )
+    }
+
     /// Handles user space page fault, if the page fault is successfully handled, return Ok(()).
     pub fn handle_page_fault(&self, page_fault_info: &PageFaultInfo) -> Result<()> {
         let address = page_fault_info.address;
@@ -308,16 +801,308 @@ impl Vmar_ {
             return_errno_with_message!(Errno::EACCES, "page fault addr is not in current vmar");
         }
 
+        let result = self.do_handle_page_fault(page_fault_info);
+        if result
+            .as_ref()
+            .is_err_and(|err| err.error() == Errno::ENOMEM)
+            && reclaim_pages(RECLAIM_BATCH_PAGES) > 0
+        {
+            // Retry once after reclaiming some memory.
+            return self.do_handle_page_fault(page_fault_info);
+        }
+        result
+    }
+
+    fn do_handle_page_fault(&self, page_fault_info: &PageFaultInfo) -> Result<()> {
+        let address = page_fault_info.address;
         let inner = self.inner.read();
 
         if let Some(vm_mapping) = inner.vm_mappings.find_one(&address) {
             debug_assert!(vm_mapping.range().contains(&address));
+            if let Some(userfault) = vm_mapping.queue_userfault(&self.vm_space, page_fault_info)? {
+                // The user-space handler needs to lock the VMAR to resolve the
+                // fault, so the lock must be released before waiting.
+                drop(inner);
+                return userfault.wait();
+            }
             return vm_mapping.handle_page_fault(&self.vm_space, page_fault_info);
         }
 
         return_errno_with_message!(Errno::EACCES, "page fault addr is not in current vmar");
     }
 
+    fn rss(&self, range: &Range<Vaddr>) -> usize {
+        let inner = self.inner.read();
+        inner
+            .vm_mappings
+            .find(range)
+            .map(|vm_mapping| vm_mapping.rss(&self.vm_space, range).unwrap_or(0))
+            .sum()
+    }
+
+    /// Unmaps the pages that have not been accessed recently, so that they
+    /// can be reclaimed.
+    ///
+    /// Returns the number of unmapped pages.
+    fn unmap_idle_pages(&self) -> usize {
+        // The lock may be held by the current thread that triggers reclaim,
+        // so never wait for it.
+        let Some(inner) = self.inner.try_read() else {
+            return 0;
+        };
+
+        inner
+            .vm_mappings
+            .iter()
+            .map(|vm_mapping| vm_mapping.unmap_idle_pages(&self.vm_space).unwrap_or(0))
+            .sum()
+    }
+
+    fn populate(&self, range: Range<Vaddr>) -> Result<()> {
+        let ranges_to_fault: Vec<_> = {
+            let inner = self.inner.read();
+            inner
+                .vm_mappings
+                .find(&range)
+                .filter(|vm_mapping| !vm_mapping.perms().is_empty())
+                .map(|vm_mapping| {
+                    let required_perms =
+                        if vm_mapping.perms().contains(VmPerms::WRITE) && !vm_mapping.is_shared() {
+                            VmPerms::WRITE
+                        } else {
+                            VmPerms::empty()
+                        };
+                    (
+                        get_intersected_range(&range, &vm_mapping.range()),
+                        required_perms,
+                    )
+                })
+                .collect()
+        };
+
+        self.fault_in(ranges_to_fault)
+    }
+
+    fn prefault(&self, range: Range<Vaddr>, is_write: bool) -> Result<()> {
+        let required_perms = if is_write {
+            VmPerms::WRITE
+        } else {
+            VmPerms::READ
+        };
+
+        let mut ranges_to_fault = Vec::new();
+        let mut mapped_size = 0;
+        {
+            let inner = self.inner.read();
+            for vm_mapping in inner.vm_mappings.find(&range) {
+                if !vm_mapping.perms().contains(required_perms) {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the mapping does not allow the access"
+                    );
+                }
+                let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+                mapped_size += intersected_range.len();
+                ranges_to_fault.push((intersected_range, required_perms));
+            }
+        }
+
+        self.fault_in(ranges_to_fault)
+            .map_err(|err| match err.error() {
+                Errno::ENOMEM => err,
+                _ => Error::with_message(Errno::EFAULT, "cannot fault in the pages"),
+            })?;
+
+        if mapped_size != range.len() {
+            return_errno_with_message!(Errno::ENOMEM, "the range is not fully mapped");
+        }
+
+        Ok(())
+    }
+
+    /// Handles the page faults on each page in the ranges with the required
+    /// permissions.
+    fn fault_in(&self, ranges: Vec<(Range<Vaddr>, VmPerms)>) -> Result<()> {
+        for (range, required_perms) in ranges {
+            for address in range.step_by(PAGE_SIZE) {
+                self.handle_page_fault(&PageFaultInfo {
+                    address,
+                    required_perms,
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn register_userfault(
+        &self,
+        range: Range<Vaddr>,
+        registration: UserfaultRegistration,
+    ) -> Result<()> {
+        let mut inner = self.inner.write();
+
+        let mut is_mapped = false;
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            is_mapped = true;
+            if !vm_mapping.can_userfault() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the mapping cannot be registered to a userfaultfd"
+                );
+            }
+            if vm_mapping.userfault().is_some() && !vm_mapping.is_registered_to(&registration.ctx) {
+                return_errno_with_message!(
+                    Errno::EBUSY,
+                    "the mapping is registered to another userfaultfd"
+                );
+            }
+        }
+        if !is_mapped {
+            return_errno_with_message!(Errno::EINVAL, "the range is not mapped");
+        }
+
+        let result = inner.update_mappings(
+            range,
+            |_| true,
+            |vm_mapping| vm_mapping.set_userfault(Some(registration.clone())),
+        );
+        // Like Linux, registering a range with holes is allowed.
+        if let Err(err) = result
+            && err.error() != Errno::ENOMEM
+        {
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn unregister_userfault(&self, range: Range<Vaddr>, ctx: &Arc<UserfaultCtx>) -> Result<()> {
+        let mut inner = self.inner.write();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            if vm_mapping.is_registered_to(ctx) {
+                let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+                vm_mapping.set_userfault_wp(&self.vm_space, &intersected_range, false)?;
+            }
+        }
+
+        let result = inner.update_mappings(
+            range.clone(),
+            |vm_mapping| vm_mapping.is_registered_to(ctx),
+            |vm_mapping| vm_mapping.set_userfault(None),
+        );
+        // Unregistering a range with holes is allowed.
+        if let Err(err) = result
+            && err.error() != Errno::ENOMEM
+        {
+            return Err(err);
+        }
+        drop(inner);
+
+        ctx.wake(&range);
+        Ok(())
+    }
+
+    fn fill_userfault_page(
+        &self,
+        ctx: &Arc<UserfaultCtx>,
+        page_addr: Vaddr,
+        frame: UFrame,
+        is_wp: bool,
+    ) -> Result<()> {
+        let inner = self.inner.read();
+
+        let Some(vm_mapping) = inner
+            .vm_mappings
+            .find_one(&page_addr)
+            .filter(|vm_mapping| vm_mapping.is_registered_to(ctx))
+        else {
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "the page is not registered to the userfaultfd"
+            );
+        };
+        if is_wp
+            && !vm_mapping
+                .userfault()
+                .unwrap()
+                .mode
+                .contains(UserfaultMode::WP)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the mapping is not registered in the write-protect mode"
+            );
+        }
+
+        vm_mapping.fill_userfault_page(&self.vm_space, page_addr, frame, is_wp)
+    }
+
+    fn set_userfault_wp(
+        &self,
+        ctx: &Arc<UserfaultCtx>,
+        range: Range<Vaddr>,
+        is_wp: bool,
+    ) -> Result<()> {
+        let inner = self.inner.read();
+
+        let mut mapped_size = 0;
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            mapped_size += get_intersected_range(&range, &vm_mapping.range()).len();
+            if !vm_mapping.is_registered_to(ctx)
+                || !vm_mapping
+                    .userfault()
+                    .unwrap()
+                    .mode
+                    .contains(UserfaultMode::WP)
+            {
+                return_errno_with_message!(
+                    Errno::ENOENT,
+                    "the mapping is not registered in the write-protect mode"
+                );
+            }
+        }
+        if mapped_size != range.len() {
+            return_errno_with_message!(Errno::ENOENT, "the range is not fully mapped");
+        }
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.set_userfault_wp(&self.vm_space, &intersected_range, is_wp)?;
+        }
+
+        Ok(())
+    }
+
+    /// Accesses the memory in `range` page by page.
+    ///
+    /// For each page, `access` is called with the frame mapped to the page,
+    /// the offset in the frame, and the corresponding range in the buffer.
+    fn access_remote<F>(&self, range: Range<Vaddr>, is_write: bool, mut access: F) -> Result<()>
+    where
+        F: FnMut(&UFrame, usize, Range<usize>) -> core::result::Result<(), ostd::Error>,
+    {
+        let inner = self.inner.read();
+
+        let mut addr = range.start;
+        while addr < range.end {
+            let page_addr = addr.align_down(PAGE_SIZE);
+            let end = (page_addr + PAGE_SIZE).min(range.end);
+
+            let Some(vm_mapping) = inner.vm_mappings.find_one(&addr) else {
+                return_errno_with_message!(Errno::EIO, "the address is not mapped");
+            };
+            let frame = vm_mapping.commit_page_for_remote(&self.vm_space, page_addr, is_write)?;
+
+            let buf_range = (addr - range.start)..(end - range.start);
+            access(&frame, addr - page_addr, buf_range)?;
+
+            addr = end;
+        }
+
+        Ok(())
+    }
+
     /// Clears all content of the root VMAR.
     fn clear_root_vmar(&self) -> Result<()> {
         self.vm_space.clear().unwrap();
@@ -332,6 +1117,39 @@ impl Vmar_ {
         Ok(())
     }
 
+    fn remove_shm_mapping(&self, addr: Vaddr) -> Result<()> {
+        let mut inner = self.inner.write();
+
+        let segment = match inner.vm_mappings.find_one(&addr) {
+            Some(vm_mapping) if vm_mapping.map_to_addr() == addr => match vm_mapping.shm() {
+                Some((attachment, 0)) => attachment.segment().clone(),
+                _ => return_errno_with_message!(Errno::EINVAL, "no segment is attached at addr"),
+            },
+            _ => return_errno_with_message!(Errno::EINVAL, "no segment is attached at addr"),
+        };
+
+        let size = segment.size().align_up(PAGE_SIZE);
+        let range = addr..addr.saturating_add(size);
+        let mut mappings_to_remove = Vec::new();
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let Some((attachment, offset)) = vm_mapping.shm() else {
+                continue;
+            };
+            if Arc::ptr_eq(attachment.segment(), &segment)
+                && vm_mapping.map_to_addr() - addr == offset
+            {
+                mappings_to_remove.push(vm_mapping.map_to_addr());
+            }
+        }
+
+        for vm_mapping_addr in mappings_to_remove {
+            let vm_mapping = inner.vm_mappings.remove(&vm_mapping_addr).unwrap();
+            vm_mapping.unmap(&self.vm_space)?;
+        }
+
+        Ok(())
+    }
+
     // Split and unmap the found mapping if resize smaller.
     // Enlarge the last mapping if resize larger.
     fn resize_mapping(&self, map_addr: Vaddr, old_size: usize, new_size: usize) -> Result<()> {
@@ -367,6 +1185,118 @@ impl Vmar_ {
         Ok(())
     }
 
+    fn remap(
+        &self,
+        old_addr: Vaddr,
+        old_size: usize,
+        new_size: usize,
+        target: RemapTarget,
+        dont_unmap: bool,
+    ) -> Result<Vaddr> {
+        debug_assert!(old_addr % PAGE_SIZE == 0);
+        debug_assert!(old_size % PAGE_SIZE == 0 && old_size != 0);
+        debug_assert!(new_size % PAGE_SIZE == 0 && new_size != 0);
+        debug_assert!(!dont_unmap || (old_size == new_size && target != RemapTarget::InPlace));
+
+        let mut inner = self.inner.write();
+
+        let old_end = old_addr.checked_add(old_size).ok_or(Error::with_message(
+            Errno::EFAULT,
+            "the old range overflows",
+        ))?;
+        let Some(vm_mapping) = inner.vm_mappings.find_one(&old_addr) else {
+            return_errno_with_message!(Errno::EFAULT, "the old range is not mapped");
+        };
+        if vm_mapping.map_end() < old_end {
+            return_errno_with_message!(Errno::EFAULT, "the old range spans multiple mappings");
+        }
+        let (mapping_addr, mapping_end) = (vm_mapping.map_to_addr(), vm_mapping.map_end());
+
+        let new_addr = match target {
+            RemapTarget::Fixed(new_addr) => {
+                let Some(new_end) = new_addr.checked_add(new_size).filter(|new_end| {
+                    new_addr >= ROOT_VMAR_LOWEST_ADDR && *new_end <= ROOT_VMAR_CAP_ADDR
+                }) else {
+                    return_errno_with_message!(Errno::EINVAL, "the new range is not in user space");
+                };
+                if new_addr < old_end && old_addr < new_end {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the new range overlaps the old range"
+                    );
+                }
+                inner.alloc_free_region_exact_truncate(&self.vm_space, new_addr, new_size)?;
+                new_addr
+            }
+            RemapTarget::MayMove if dont_unmap => {
+                inner.alloc_free_region(new_size, PAGE_SIZE)?.start
+            }
+            RemapTarget::InPlace | RemapTarget::MayMove => {
+                if new_size <= old_size {
+                    if new_size < old_size {
+                        inner.alloc_free_region_exact_truncate(
+                            &self.vm_space,
+                            old_addr + new_size,
+                            old_size - new_size,
+                        )?;
+                    }
+                    return Ok(old_addr);
+                }
+
+                // Try to enlarge the mapping in place.
+                let extra_size = new_size - old_size;
+                if old_end == mapping_end
+                    && old_end
+                        .checked_add(extra_size)
+                        .is_some_and(|new_end| new_end <= ROOT_VMAR_CAP_ADDR)
+                    && inner.alloc_free_region_exact(old_end, extra_size).is_ok()
+                {
+                    let vm_mapping = inner.vm_mappings.remove(&mapping_addr).unwrap();
+                    inner.vm_mappings.insert(vm_mapping.enlarge(extra_size));
+                    return Ok(old_addr);
+                }
+
+                if target == RemapTarget::InPlace {
+                    return_errno_with_message!(
+                        Errno::ENOMEM,
+                        "the mapping cannot be enlarged in place"
+                    );
+                }
+                inner.alloc_free_region(new_size, PAGE_SIZE)?.start
+            }
+        };
+
+        // Only the pages that fit in the new range are moved.
+        let moved_size = old_size.min(new_size);
+        if moved_size < old_size {
+            inner.alloc_free_region_exact_truncate(
+                &self.vm_space,
+                old_addr + moved_size,
+                old_size - moved_size,
+            )?;
+        }
+
+        // The mapping may have been split when removing the mappings in the
+        // new range, so look it up again.
+        let mapping_addr = inner.vm_mappings.find_one(&old_addr).unwrap().map_to_addr();
+        let vm_mapping = inner.vm_mappings.remove(&mapping_addr).unwrap();
+        let (left, taken, right) = vm_mapping.split_range(&(old_addr..old_addr + moved_size))?;
+        if let Some(left) = left {
+            inner.vm_mappings.insert(left);
+        }
+        if let Some(right) = right {
+            inner.vm_mappings.insert(right);
+        }
+        if dont_unmap {
+            inner.vm_mappings.insert(taken.new_fork()?);
+        }
+
+        let moved = taken.relocate(&self.vm_space, new_addr, new_size)?;
+        inner.vm_mappings.insert(moved);
+
+        Ok(new_addr)
+    }
+
     /// Returns the attached `VmSpace`.
     fn vm_space(&self) -> &Arc<VmSpace> {
         &self.vm_space
@@ -391,12 +1321,21 @@ impl Vmar_ {
             let cur_vmspace = self.vm_space();
             let mut cur_cursor = cur_vmspace.cursor_mut(&range).unwrap();
             for vm_mapping in inner.vm_mappings.iter() {
+                if vm_mapping.is_dont_fork() {
+                    continue;
+                }
                 let base = vm_mapping.map_to_addr();
 
                 // Clone the `VmMapping` to the new VMAR.
                 let new_mapping = vm_mapping.new_fork()?;
                 new_inner.vm_mappings.insert(new_mapping);
 
+                // The pages of a private anonymous mapping are not copied, so
+                // zero-filled pages will be faulted in by the child.
+                if vm_mapping.is_wipe_on_fork() {
+                    continue;
+                }
+
                 // Protect the mapping and copy to the new page table for COW.
                 cur_cursor.jump(base).unwrap();
                 new_cursor.jump(base).unwrap();
@@ -441,6 +1380,9 @@ impl<R> Vmar<R> {
 pub struct VmarMapOptions<R1, R2> {
     parent: Vmar<R1>,
     vmo: Option<Vmo<R2>>,
+    dentry: Option<Dentry>,
+    writable_mapping: Option<WritableMappingGuard>,
+    shm: Option<ShmAttachment>,
     perms: VmPerms,
     vmo_offset: usize,
     vmo_limit: usize,
@@ -452,6 +1394,7 @@ pub struct VmarMapOptions<R1, R2> {
     is_shared: bool,
     // Whether the mapping needs to handle surrounding pages when handling page fault.
     handle_page_faults_around: bool,
+    lock: Option<VmLockMode>,
 }
 
 impl<R1, R2> VmarMapOptions<R1, R2> {
@@ -465,6 +1408,9 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
         Self {
             parent,
             vmo: None,
+            dentry: None,
+            writable_mapping: None,
+            shm: None,
             perms,
             vmo_offset: 0,
             vmo_limit: usize::MAX,
@@ -474,6 +1420,7 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
             can_overwrite: false,
             is_shared: false,
             handle_page_faults_around: false,
+            lock: None,
         }
     }
 
@@ -498,6 +1445,34 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
         self
     }
 
+    /// Sets the file that the bound VMO belongs to.
+    ///
+    /// This makes the mapping a file-backed mapping, and the VMO offset is
+    /// the offset in the file. The file is only used to report the mapping
+    /// information, e.g., in core dumps.
+    pub fn dentry(mut self, dentry: Dentry) -> Self {
+        self.dentry = Some(dentry);
+        self
+    }
+
+    /// Sets the guard that marks the mapping as a shared mapping that may write
+    /// to the file.
+    ///
+    /// The guard is released when the mapping is removed.
+    pub fn writable_mapping(mut self, guard: WritableMappingGuard) -> Self {
+        self.writable_mapping = Some(guard);
+        self
+    }
+
+    /// Sets the System V shared memory segment that the mapping attaches.
+    ///
+    /// The bound VMO must be the one of the segment. The attachment is
+    /// released when the mapping is removed.
+    pub fn shm(mut self, attachment: ShmAttachment) -> Self {
+        self.shm = Some(attachment);
+        self
+    }
+
     /// Sets the offset of the first memory page in the VMO that is to be
     /// mapped into the VMAR.
     ///
@@ -566,6 +1541,15 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
         self
     }
 
+    /// Sets how the pages of the mapping are locked in memory.
+    ///
+    /// If not set, the mapping is locked as specified by
+    /// [`Vmar::set_future_lock`]. The pages are not populated by this method.
+    pub fn lock(mut self, lock: VmLockMode) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
     /// Creates the mapping and adds it to the parent VMAR.
     ///
     /// All options will be checked at this point.
@@ -576,6 +1560,9 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
         let Self {
             parent,
             vmo,
+            dentry,
+            writable_mapping,
+            shm,
             perms,
             vmo_offset,
             vmo_limit,
@@ -585,6 +1572,7 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
             can_overwrite,
             is_shared,
             handle_page_faults_around,
+            lock,
         } = self;
 
         // Allocates a free region.
@@ -612,10 +1600,14 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
             NonZeroUsize::new(map_size).unwrap(),
             map_to_addr,
             vmo,
+            dentry,
+            writable_mapping,
+            shm,
             is_shared,
             handle_page_faults_around,
             perms,
-        );
+        )
+        .set_lock(lock.or(inner.future_lock));
 
         // Add the mapping to the VMAR.
         inner.vm_mappings.insert(vm_mapping);
@@ -9,14 +9,30 @@ use core::{
 use align_ext::AlignExt;
 use ostd::mm::{
     tlb::TlbFlushOp, vm_space::VmItem, CachePolicy, FrameAllocOptions, PageFlags, PageProperty,
-    UFrame, VmSpace,
+    UFrame, VmIo, VmSpace, HUGE_PAGE_SIZE,
 };
 
-use super::interval_set::Interval;
+use super::{get_intersected_range, interval_set::Interval};
 use crate::{
+    fs::{
+        path::Dentry,
+        utils::{FallocMode, FileSeals, WritableMappingGuard},
+    },
+    ipc::shm::ShmAttachment,
     prelude::*,
+    process::posix_thread::AsPosixThread,
     thread::exception::PageFaultInfo,
-    vm::{perms::VmPerms, util::duplicate_frame, vmo::Vmo},
+    vm::{
+        perms::VmPerms,
+        reclaim::lock_reclaim,
+        thp::ThpAdvice,
+        userfault::{
+            QueuedUserfault, Userfault, UserfaultCtx, UserfaultMode, UserfaultRegistration,
+            PAGE_FLAG_UFFD_WP,
+        },
+        util::{duplicate_frame, mapped_frame_at},
+        vmo::Vmo,
+    },
 };
 
 /// Mapping a range of physical pages into a `Vmar`.
@@ -50,6 +66,19 @@ pub(super) struct VmMapping {
     /// The start of the virtual address maps to the start of the range
     /// specified in [`MappedVmo`].
     vmo: Option<MappedVmo>,
+    /// The file that the mapping maps, if the mapping is file-backed.
+    ///
+    /// The start of the virtual address maps to the start of the VMO range,
+    /// which is also the offset in the file.
+    dentry: Option<Dentry>,
+    /// The guard of a shared mapping that may write to the file, if any.
+    ///
+    /// It prevents the file from being sealed against writing.
+    writable_mapping: Option<WritableMappingGuard>,
+    /// The System V shared memory segment that the mapping attaches, if any.
+    ///
+    /// The start of the VMO range is the offset in the segment.
+    shm: Option<ShmAttachment>,
     /// Whether the mapping is shared.
     ///
     /// The updates to a shared mapping are visible among processes, or carried
@@ -62,6 +91,33 @@ pub(super) struct VmMapping {
     ///
     /// All pages within the same `VmMapping` have the same permissions.
     perms: VmPerms,
+    /// Whether the mapping is excluded from core dumps.
+    ///
+    /// This is set by `madvise(MADV_DONTDUMP)`.
+    is_dont_dump: bool,
+    /// Whether the mapping is not inherited by the child on fork.
+    ///
+    /// This is set by `madvise(MADV_DONTFORK)`.
+    is_dont_fork: bool,
+    /// Whether the child gets zero-filled pages instead of a copy of the
+    /// pages on fork.
+    ///
+    /// This is set by `madvise(MADV_WIPEONFORK)`. Only private anonymous
+    /// mappings can be wiped on fork.
+    is_wipe_on_fork: bool,
+    /// The advice on whether the mapping should use transparent huge pages.
+    ///
+    /// This is set by `madvise(MADV_HUGEPAGE)` and `madvise(MADV_NOHUGEPAGE)`.
+    thp_advice: ThpAdvice,
+    /// The userfaultfd that the mapping is registered to, if any.
+    ///
+    /// This is set by `ioctl(UFFDIO_REGISTER)`.
+    userfault: Option<UserfaultRegistration>,
+    /// How the pages of the mapping are locked in memory, if they are.
+    ///
+    /// The locked pages are never unmapped for reclaim. This is set by
+    /// `mlock`, `mlockall`, or `mmap` with `MAP_LOCKED`.
+    lock: Option<VmLockMode>,
 }
 
 impl Interval<Vaddr> for VmMapping {
@@ -73,10 +129,14 @@ impl Interval<Vaddr> for VmMapping {
 /***************************** Basic methods *********************************/
 
 impl VmMapping {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         map_size: NonZeroUsize,
         map_to_addr: Vaddr,
         vmo: Option<MappedVmo>,
+        dentry: Option<Dentry>,
+        writable_mapping: Option<WritableMappingGuard>,
+        shm: Option<ShmAttachment>,
         is_shared: bool,
         handle_page_faults_around: bool,
         perms: VmPerms,
@@ -85,15 +145,32 @@ impl VmMapping {
             map_size,
             map_to_addr,
             vmo,
+            dentry,
+            writable_mapping,
+            shm,
             is_shared,
             handle_page_faults_around,
             perms,
+            is_dont_dump: false,
+            is_dont_fork: false,
+            is_wipe_on_fork: false,
+            thp_advice: ThpAdvice::Default,
+            userfault: None,
+            lock: None,
         }
     }
 
     pub(super) fn new_fork(&self) -> Result<VmMapping> {
         Ok(VmMapping {
             vmo: self.vmo.as_ref().map(|vmo| vmo.dup()).transpose()?,
+            dentry: self.dentry.clone(),
+            writable_mapping: self.writable_mapping.clone(),
+            shm: self.shm.clone(),
+            // Like Linux without `UFFD_FEATURE_EVENT_FORK`, the child is not
+            // registered to the userfaultfd.
+            userfault: None,
+            // Like Linux, memory locks are not inherited by the child.
+            lock: None,
             ..*self
         })
     }
@@ -117,6 +194,118 @@ impl VmMapping {
     pub fn perms(&self) -> VmPerms {
         self.perms
     }
+
+    /// Returns whether the mapping is shared.
+    pub fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+
+    /// Returns whether the mapping is excluded from core dumps.
+    pub fn is_dont_dump(&self) -> bool {
+        self.is_dont_dump
+    }
+
+    /// Returns whether the mapping is not inherited by the child on fork.
+    pub fn is_dont_fork(&self) -> bool {
+        self.is_dont_fork
+    }
+
+    /// Returns whether the child gets zero-filled pages instead of a copy of
+    /// the pages on fork.
+    pub fn is_wipe_on_fork(&self) -> bool {
+        self.is_wipe_on_fork
+    }
+
+    /// Returns whether the mapping is a private anonymous mapping.
+    pub fn is_private_anonymous(&self) -> bool {
+        self.vmo.is_none() && !self.is_shared
+    }
+
+    /// Returns the advice on whether the mapping should use transparent huge
+    /// pages.
+    pub fn thp_advice(&self) -> ThpAdvice {
+        self.thp_advice
+    }
+
+    /// Returns how the pages of the mapping are locked in memory, if they are.
+    pub fn lock(&self) -> Option<VmLockMode> {
+        self.lock
+    }
+
+    /// Returns the userfaultfd that the mapping is registered to, if the
+    /// userfaultfd has not been closed.
+    pub(super) fn userfault(&self) -> Option<&UserfaultRegistration> {
+        self.userfault
+            .as_ref()
+            .filter(|registration| !registration.ctx.is_released())
+    }
+
+    /// Returns whether the mapping is registered to the userfaultfd.
+    pub(super) fn is_registered_to(&self, ctx: &Arc<UserfaultCtx>) -> bool {
+        self.userfault()
+            .is_some_and(|registration| Arc::ptr_eq(&registration.ctx, ctx))
+    }
+
+    /// Returns the attached System V shared memory segment and the offset in
+    /// the segment, if the mapping attaches a segment.
+    pub(super) fn shm(&self) -> Option<(&ShmAttachment, usize)> {
+        self.shm
+            .as_ref()
+            .zip(self.vmo.as_ref())
+            .map(|(shm, vmo)| (shm, vmo.range.start))
+    }
+
+    /// Returns the information of the mapping.
+    pub(super) fn info(&self) -> VmMappingInfo {
+        VmMappingInfo {
+            range: self.range(),
+            perms: self.perms,
+            is_shared: self.is_shared,
+            is_dont_dump: self.is_dont_dump,
+            is_dont_fork: self.is_dont_fork,
+            is_wipe_on_fork: self.is_wipe_on_fork,
+            lock: self.lock,
+            file: self
+                .dentry
+                .as_ref()
+                .zip(self.vmo.as_ref())
+                .map(|(dentry, vmo)| (dentry.clone(), vmo.range.start)),
+        }
+    }
+}
+
+/// The information of a mapping, which can be reported to the user
+/// space, e.g., in core dumps.
+#[derive(Debug, Clone)]
+pub struct VmMappingInfo {
+    /// The range of the mapping.
+    pub range: Range<Vaddr>,
+    /// The permissions of pages in the mapping.
+    pub perms: VmPerms,
+    /// Whether the mapping is shared.
+    pub is_shared: bool,
+    /// Whether the mapping is excluded from core dumps.
+    pub is_dont_dump: bool,
+    /// Whether the mapping is not inherited by the child on fork.
+    pub is_dont_fork: bool,
+    /// Whether the child gets zero-filled pages on fork.
+    pub is_wipe_on_fork: bool,
+    /// How the pages of the mapping are locked in memory, if they are.
+    pub lock: Option<VmLockMode>,
+    /// The mapped file and the offset in the file, if the mapping is
+    /// file-backed.
+    pub file: Option<(Dentry, usize)>,
+}
+
+/// The modes of locking the pages of a mapping in memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmLockMode {
+    /// All pages are populated when the mapping is locked.
+    Populate,
+    /// Pages are locked when they are faulted in.
+    ///
+    /// This is set by `MLOCK_ONFAULT` or `MCL_ONFAULT`.
+    OnFault,
 }
 
 /****************************** Page faults **********************************/
@@ -142,6 +331,20 @@ impl VmMapping {
         let page_aligned_addr = address.align_down(PAGE_SIZE);
         let is_write = page_fault_info.required_perms.contains(VmPerms::WRITE);
 
+        if self.handle_huge_page_fault(vm_space, address, is_write)? {
+            return Ok(());
+        }
+
+        // Commit the page of the VMO before locking the page table, since it
+        // may sleep to read the page from the disk or the swap device. Errors
+        // are reported later when the page is mapped.
+        if let Some(vmo) = &self.vmo {
+            let page_offset = page_aligned_addr - self.map_to_addr;
+            if page_offset < vmo.size() {
+                let _ = vmo.get_committed_frame(page_offset);
+            }
+        }
+
         if !is_write && self.vmo.is_some() && self.handle_page_faults_around {
             self.handle_page_faults_around(vm_space, address)?;
             return Ok(());
@@ -150,6 +353,18 @@ impl VmMapping {
         let mut cursor =
             vm_space.cursor_mut(&(page_aligned_addr..page_aligned_addr + PAGE_SIZE))?;
 
+        if let VmItem::MappedHuge { va, prop, .. } = cursor.query().unwrap() {
+            if VmPerms::from(prop.flags).contains(page_fault_info.required_perms) {
+                // The page fault is already handled maybe by other threads.
+                // Just flush the TLB and return.
+                TlbFlushOp::Range(va..va + HUGE_PAGE_SIZE).perform_on_current();
+                return Ok(());
+            }
+            // Copy-on-write is done in base pages. The base pages keep
+            // sharing the frames of the huge page until they are written.
+            cursor.split_huge();
+        }
+
         match cursor.query().unwrap() {
             VmItem::Mapped {
                 va,
@@ -212,10 +427,156 @@ impl VmMapping {
 
                 cursor.map(frame, map_prop);
             }
+            VmItem::MappedHuge { .. } => unreachable!("the huge page has been split"),
         }
         Ok(())
     }
 
+    /// Handles a page fault by mapping a huge page that covers the faulting
+    /// address, if possible.
+    ///
+    /// Returns `Ok(false)` if the page fault should be handled with a base
+    /// page instead, e.g., if the huge page does not fit in the mapping, or if
+    /// some base pages within the huge page have already been mapped.
+    fn handle_huge_page_fault(
+        &self,
+        vm_space: &VmSpace,
+        address: Vaddr,
+        is_write: bool,
+    ) -> Result<bool> {
+        if !self.thp_advice.allows_huge_page() || self.userfault().is_some() {
+            return Ok(false);
+        }
+        // Private VMO-backed mappings need copy-on-write in base pages.
+        if self.vmo.is_some() && !self.is_shared {
+            return Ok(false);
+        }
+
+        let huge_addr = address.align_down(HUGE_PAGE_SIZE);
+        let huge_range = huge_addr..huge_addr + HUGE_PAGE_SIZE;
+        if huge_range.start < self.map_to_addr || huge_range.end > self.map_end() {
+            return Ok(false);
+        }
+
+        let vmo_offset = if let Some(vmo) = &self.vmo {
+            let offset = vmo.range.start + (huge_addr - self.map_to_addr);
+            if offset % HUGE_PAGE_SIZE != 0 || offset + HUGE_PAGE_SIZE > vmo.range.end {
+                return Ok(false);
+            }
+            Some(offset)
+        } else {
+            None
+        };
+
+        let mut cursor = vm_space.cursor_mut(&huge_range)?;
+        match cursor.query().unwrap() {
+            VmItem::NotMapped { len, .. } if len >= HUGE_PAGE_SIZE => {}
+            _ => return Ok(false),
+        }
+
+        let frames = match (&self.vmo, vmo_offset) {
+            (Some(vmo), Some(offset)) => {
+                let Some(frames) = vmo.vmo.commit_huge_page(offset)? else {
+                    return Ok(false);
+                };
+                frames
+            }
+            _ => {
+                let Ok(segment) =
+                    FrameAllocOptions::new().alloc_segment(HUGE_PAGE_SIZE / PAGE_SIZE)
+                else {
+                    return Ok(false);
+                };
+                if segment.start_paddr() % HUGE_PAGE_SIZE != 0 {
+                    return Ok(false);
+                }
+                segment.into()
+            }
+        };
+
+        let mut page_flags = PageFlags::from(self.perms) | PageFlags::ACCESSED;
+        if is_write {
+            page_flags |= PageFlags::DIRTY;
+        }
+        cursor.map_huge(
+            frames,
+            PageProperty::new(page_flags, CachePolicy::Writeback),
+        );
+
+        Ok(true)
+    }
+
+    /// Commits the page at `page_addr` for an access from outside of the
+    /// address space, and returns the frame mapped to the page.
+    ///
+    /// Writing to a read-only private mapping is allowed. It breaks the
+    /// sharing of the page by mapping a copy of the page, which keeps the page
+    /// read-only to the user.
+    pub(super) fn commit_page_for_remote(
+        &self,
+        vm_space: &VmSpace,
+        page_addr: Vaddr,
+        is_write: bool,
+    ) -> Result<UFrame> {
+        let is_writable = self.perms.contains(VmPerms::WRITE);
+        if is_write && self.is_shared && !is_writable {
+            return_errno_with_message!(Errno::EIO, "the shared mapping is not writable");
+        }
+
+        let required_perms = if is_write && is_writable {
+            VmPerms::WRITE
+        } else {
+            VmPerms::empty()
+        };
+        self.handle_page_fault(
+            vm_space,
+            &PageFaultInfo {
+                address: page_addr,
+                required_perms,
+            },
+        )?;
+
+        let mut cursor = vm_space.cursor_mut(&(page_addr..page_addr + PAGE_SIZE))?;
+        let Some((frame, prop)) = mapped_frame_at(cursor.query()?, page_addr) else {
+            return_errno_with_message!(Errno::EIO, "the page is not mapped");
+        };
+        if !is_write || prop.flags.contains(PageFlags::W) {
+            return Ok(frame);
+        }
+
+        cursor.split_huge();
+        let new_frame: UFrame = duplicate_frame(&frame)?.into();
+        cursor.map(new_frame.clone(), prop);
+        Ok(new_frame)
+    }
+
+    /// Reads the page at `page_addr` into `buf` for a core dump.
+    ///
+    /// Returns `Ok(false)` without reading anything if the page is in an
+    /// anonymous mapping and has never been touched. Such a page should be
+    /// treated as zeros, and will not be committed just for the dump.
+    pub(super) fn read_page_for_dump(
+        &self,
+        vm_space: &VmSpace,
+        page_addr: Vaddr,
+        buf: &mut [u8],
+    ) -> Result<bool> {
+        debug_assert_eq!(buf.len(), PAGE_SIZE);
+
+        let frame = if self.vmo.is_none() {
+            let mut cursor = vm_space.cursor(&(page_addr..page_addr + PAGE_SIZE))?;
+            let Some((frame, _)) = mapped_frame_at(cursor.query()?, page_addr) else {
+                return Ok(false);
+            };
+            frame
+        } else {
+            self.commit_page_for_remote(vm_space, page_addr, false)?
+        };
+
+        frame.read_bytes(0, buf)?;
+        Ok(true)
+    }
+
     fn prepare_page(&self, page_fault_addr: Vaddr, write: bool) -> Result<(UFrame, bool)> {
         let mut is_readonly = false;
         let Some(vmo) = &self.vmo else {
@@ -290,6 +651,142 @@ impl VmMapping {
     }
 }
 
+/****************************** Userfaults **********************************/
+
+impl VmMapping {
+    /// Returns whether the mapping can be registered to a userfaultfd.
+    ///
+    /// Only private anonymous mappings are supported for now.
+    pub(super) fn can_userfault(&self) -> bool {
+        self.vmo.is_none()
+    }
+
+    /// Queues the page fault to the userfaultfd if it should be handled by
+    /// the user space.
+    ///
+    /// The page table is locked when the fault is queued. So a page mapped to
+    /// resolve the fault later is always mapped after the fault is queued,
+    /// and the faulting thread will not miss the wakeup.
+    pub(super) fn queue_userfault(
+        &self,
+        vm_space: &VmSpace,
+        page_fault_info: &PageFaultInfo,
+    ) -> Result<Option<QueuedUserfault>> {
+        let Some(registration) = self.userfault() else {
+            return Ok(None);
+        };
+        if !self.perms.contains(page_fault_info.required_perms) {
+            return Ok(None);
+        }
+
+        let address = page_fault_info.address;
+        let page_addr = address.align_down(PAGE_SIZE);
+        let is_write = page_fault_info.required_perms.contains(VmPerms::WRITE);
+
+        let mut cursor = vm_space.cursor(&(page_addr..page_addr + PAGE_SIZE))?;
+        let is_wp = match cursor.query()? {
+            VmItem::NotMapped { .. } if registration.mode.contains(UserfaultMode::MISSING) => false,
+            VmItem::Mapped { prop, .. }
+                if is_write
+                    && registration.mode.contains(UserfaultMode::WP)
+                    && prop.flags.contains(PAGE_FLAG_UFFD_WP) =>
+            {
+                true
+            }
+            _ => return Ok(None),
+        };
+
+        let tid = current_thread!().as_posix_thread().unwrap().tid();
+        Ok(registration.ctx.queue_fault(Userfault {
+            address,
+            is_write,
+            is_wp,
+            tid,
+        }))
+    }
+
+    /// Maps the frame to the missing page at `page_addr` to resolve a fault
+    /// handled by the user space.
+    ///
+    /// If `is_wp` is true, the page is write-protected for the userfaultfd.
+    pub(super) fn fill_userfault_page(
+        &self,
+        vm_space: &VmSpace,
+        page_addr: Vaddr,
+        frame: UFrame,
+        is_wp: bool,
+    ) -> Result<()> {
+        debug_assert!(self.range().contains(&page_addr));
+
+        let mut cursor = vm_space.cursor_mut(&(page_addr..page_addr + PAGE_SIZE))?;
+        if !matches!(cursor.query()?, VmItem::NotMapped { .. }) {
+            return_errno_with_message!(Errno::EEXIST, "the page is already mapped");
+        }
+
+        let mut page_flags = PageFlags::from(self.perms) | PageFlags::ACCESSED;
+        if is_wp {
+            page_flags -= PageFlags::W;
+            page_flags |= PAGE_FLAG_UFFD_WP;
+        }
+        cursor.map(frame, PageProperty::new(page_flags, CachePolicy::Writeback));
+
+        Ok(())
+    }
+
+    /// Write-protects the mapped pages in `range` for the userfaultfd, or
+    /// removes the protection.
+    ///
+    /// Removing the protection does not make the pages writable directly.
+    /// The next write is handled as a normal page fault, which may need
+    /// copy-on-write.
+    pub(super) fn set_userfault_wp(
+        &self,
+        vm_space: &VmSpace,
+        range: &Range<Vaddr>,
+        is_wp: bool,
+    ) -> Result<()> {
+        debug_assert!(self.range().start <= range.start && range.end <= self.range().end);
+
+        let mut cursor = vm_space.cursor_mut(range)?;
+
+        // The write protection is tracked in base pages.
+        while cursor.virt_addr() < range.end {
+            let next_addr = match cursor.query()? {
+                VmItem::MappedHuge { va, .. } => {
+                    cursor.split_huge();
+                    va + HUGE_PAGE_SIZE
+                }
+                VmItem::Mapped { va, .. } => va + PAGE_SIZE,
+                VmItem::NotMapped { va, len } => va + len,
+            };
+            if next_addr >= range.end {
+                break;
+            }
+            cursor.jump(next_addr)?;
+        }
+        cursor.jump(range.start)?;
+
+        let op = |p: &mut PageProperty| {
+            if is_wp {
+                p.flags -= PageFlags::W;
+                p.flags |= PAGE_FLAG_UFFD_WP;
+            } else {
+                p.flags -= PAGE_FLAG_UFFD_WP;
+            }
+        };
+        while cursor.virt_addr() < range.end {
+            if let Some(va) = cursor.protect_next(range.end - cursor.virt_addr(), op) {
+                cursor.flusher().issue_tlb_flush(TlbFlushOp::Range(va));
+            } else {
+                break;
+            }
+        }
+        cursor.flusher().dispatch_tlb_flush();
+
+        Ok(())
+    }
+}
+
 /**************************** Transformations ********************************/
 
 impl VmMapping {
@@ -327,6 +824,10 @@ impl VmMapping {
             map_to_addr: self.map_to_addr,
             map_size: NonZeroUsize::new(left_size).unwrap(),
             vmo: l_vmo,
+            dentry: self.dentry.clone(),
+            writable_mapping: self.writable_mapping.clone(),
+            shm: self.shm.clone(),
+            userfault: self.userfault.clone(),
             ..self
         };
         let right = Self {
@@ -389,13 +890,105 @@ impl VmMapping {
         Ok(())
     }
 
+    /// Moves the mapping to `new_addr` and resizes it to `new_size` bytes.
+    ///
+    /// The mapped pages are moved to the new range in the VM space without
+    /// copying their contents. The new size must not be smaller than the
+    /// current size, and the new range must not be mapped.
+    pub(super) fn relocate(
+        self,
+        vm_space: &VmSpace,
+        new_addr: Vaddr,
+        new_size: usize,
+    ) -> Result<Self> {
+        debug_assert!(new_addr % PAGE_SIZE == 0);
+        debug_assert!(new_size >= self.map_size.get());
+
+        let old_range = self.range();
+
+        // Huge pages crossing the boundaries of the mapping are only partially
+        // moved, so they must be split first.
+        let mut cursor = vm_space.cursor_mut(&old_range)?;
+        cursor.split_huge();
+        cursor.jump(old_range.end - PAGE_SIZE)?;
+        cursor.split_huge();
+        drop(cursor);
+
+        let items: Vec<_> = vm_space
+            .cursor(&old_range)?
+            .filter(|item| !matches!(item, VmItem::NotMapped { .. }))
+            .collect();
+        vm_space.cursor_mut(&old_range)?.unmap(old_range.len());
+
+        let mut cursor = vm_space.cursor_mut(&(new_addr..new_addr + new_size))?;
+        for item in items {
+            match item {
+                VmItem::Mapped { va, frame, prop } => {
+                    cursor.jump(new_addr + (va - old_range.start))?;
+                    cursor.map(frame, prop);
+                }
+                VmItem::MappedHuge { va, frames, prop } => {
+                    let new_va = new_addr + (va - old_range.start);
+                    cursor.jump(new_va)?;
+                    if new_va % HUGE_PAGE_SIZE == 0
+                        && matches!(
+                            cursor.query()?,
+                            VmItem::NotMapped { len, .. } if len >= HUGE_PAGE_SIZE
+                        )
+                    {
+                        cursor.map_huge(frames, prop);
+                        continue;
+                    }
+                    // The huge page cannot be kept at the new address, so its
+                    // frames are mapped as base pages.
+                    for (i, frame) in frames.enumerate() {
+                        cursor.jump(new_va + i * PAGE_SIZE)?;
+                        cursor.map(frame, prop);
+                    }
+                }
+                VmItem::NotMapped { .. } => unreachable!(),
+            }
+        }
+
+        Ok(Self {
+            map_to_addr: new_addr,
+            map_size: NonZeroUsize::new(new_size).unwrap(),
+            ..self
+        })
+    }
+
+    /// Checks whether the permissions can be applied to the mapping.
+    pub(super) fn check_protect(&self, perms: VmPerms) -> Result<()> {
+        // A shared mapping that cannot write to the file when created (e.g., because the
+        // file has been sealed against writing) cannot become writable.
+        if self.is_shared
+            && perms.contains(VmPerms::WRITE)
+            && self.writable_mapping.is_none()
+            && let Some(dentry) = &self.dentry
+            && dentry.inode().seals().is_ok_and(|seals| {
+                seals.intersects(FileSeals::F_SEAL_WRITE | FileSeals::F_SEAL_FUTURE_WRITE)
+            })
+        {
+            return_errno_with_message!(Errno::EACCES, "the file is sealed against writing");
+        }
+
+        Ok(())
+    }
+
     /// Change the perms of the mapping.
     pub(super) fn protect(self, vm_space: &VmSpace, perms: VmPerms) -> Self {
         let range = self.range();
 
         let mut cursor = vm_space.cursor_mut(&range).unwrap();
 
-        let op = |p: &mut PageProperty| p.flags = perms.into();
+        let op = |p: &mut PageProperty| {
+            let is_uffd_wp = p.flags.contains(PAGE_FLAG_UFFD_WP);
+            p.flags = perms.into();
+            if is_uffd_wp {
+                p.flags -= PageFlags::W;
+                p.flags |= PAGE_FLAG_UFFD_WP;
+            }
+        };
         while cursor.virt_addr() < range.end {
             if let Some(va) = cursor.protect_next(range.end - cursor.virt_addr(), op) {
                 cursor.flusher().issue_tlb_flush(TlbFlushOp::Range(va));
@@ -407,6 +1000,325 @@ impl VmMapping {
 
         Self { perms, ..self }
     }
+
+    /// Returns the size of the pages mapped in the part of the mapping that
+    /// intersects with `range`, in bytes.
+    pub(super) fn rss(&self, vm_space: &VmSpace, range: &Range<Vaddr>) -> Result<usize> {
+        let range = get_intersected_range(range, &self.range());
+        let mut cursor = vm_space.cursor(&range)?;
+
+        let mut rss = 0;
+        while cursor.virt_addr() < range.end {
+            let next_addr = match cursor.query()? {
+                VmItem::Mapped { va, .. } => {
+                    rss += PAGE_SIZE;
+                    va + PAGE_SIZE
+                }
+                VmItem::MappedHuge { va, .. } => {
+                    rss += HUGE_PAGE_SIZE;
+                    va + HUGE_PAGE_SIZE
+                }
+                VmItem::NotMapped { va, len } => va + len,
+            };
+            if next_addr >= range.end {
+                break;
+            }
+            cursor.jump(next_addr)?;
+        }
+
+        Ok(rss)
+    }
+
+    /// Unmaps the pages of the VMO that have not been accessed recently, so
+    /// that they can be reclaimed.
+    ///
+    /// The accessed bits of the mapped pages are cleared, so the pages that
+    /// are still not accessed on the next call will be unmapped. Only base
+    /// pages committed in the VMO are unmapped. Other pages, e.g., the private
+    /// copies made by copy-on-write, are kept since they exist nowhere else.
+    ///
+    /// Returns the number of unmapped pages.
+    pub(super) fn unmap_idle_pages(&self, vm_space: &VmSpace) -> Result<usize> {
+        if self.vmo.is_none() || self.lock.is_some() {
+            return Ok(0);
+        }
+        let range = self.range();
+
+        // Collect the idle pages, and age the others.
+        let mut idle_pages = Vec::new();
+        let mut cursor = vm_space.cursor_mut(&range)?;
+        while cursor.virt_addr() < range.end {
+            let next_addr = match cursor.query()? {
+                VmItem::Mapped { va, frame, prop } => {
+                    if prop.flags.contains(PageFlags::ACCESSED) {
+                        cursor.protect_next(PAGE_SIZE, |p| p.flags -= PageFlags::ACCESSED);
+                        cursor.flusher().issue_tlb_flush(TlbFlushOp::Address(va));
+                        continue;
+                    }
+                    idle_pages.push((va, frame));
+                    va + PAGE_SIZE
+                }
+                VmItem::MappedHuge { va, .. } => va + HUGE_PAGE_SIZE,
+                VmItem::NotMapped { va, len } => va + len,
+            };
+            if next_addr >= range.end {
+                break;
+            }
+            cursor.jump(next_addr)?;
+        }
+        cursor.flusher().dispatch_tlb_flush();
+        drop(cursor);
+
+        let unmapped_pages = self.unmap_vmo_pages(vm_space, idle_pages, false)?;
+        Ok(unmapped_pages.len())
+    }
+
+    /// Clears the accessed bits of the pages in `range`, so that they are the
+    /// first to be unmapped for reclaim.
+    pub(super) fn deactivate_pages(&self, vm_space: &VmSpace, range: &Range<Vaddr>) -> Result<()> {
+        let mut cursor = vm_space.cursor_mut(range)?;
+        while cursor.virt_addr() < range.end {
+            let next_addr = match cursor.query()? {
+                VmItem::Mapped { va, prop, .. } => {
+                    if prop.flags.contains(PageFlags::ACCESSED) {
+                        cursor.protect_next(PAGE_SIZE, |p| p.flags -= PageFlags::ACCESSED);
+                        cursor.flusher().issue_tlb_flush(TlbFlushOp::Address(va));
+                        continue;
+                    }
+                    va + PAGE_SIZE
+                }
+                // Huge pages are never unmapped for reclaim.
+                VmItem::MappedHuge { va, .. } => va + HUGE_PAGE_SIZE,
+                VmItem::NotMapped { va, len } => va + len,
+            };
+            if next_addr >= range.end {
+                break;
+            }
+            cursor.jump(next_addr)?;
+        }
+        cursor.flusher().dispatch_tlb_flush();
+
+        Ok(())
+    }
+
+    /// Unmaps the pages of the VMO in `range` and reclaims them right away.
+    ///
+    /// Like [`Self::unmap_idle_pages`], only base pages committed in the VMO
+    /// are unmapped. The pages that are still used by others are not
+    /// reclaimed, but will be faulted in again from the VMO when they are
+    /// accessed.
+    pub(super) fn page_out(&self, vm_space: &VmSpace, range: &Range<Vaddr>) -> Result<()> {
+        let Some(vmo) = &self.vmo else {
+            return Ok(());
+        };
+
+        let mut pages = Vec::new();
+        let mut cursor = vm_space.cursor(range)?;
+        while cursor.virt_addr() < range.end {
+            let next_addr = match cursor.query()? {
+                VmItem::Mapped { va, frame, .. } => {
+                    pages.push((va, frame));
+                    va + PAGE_SIZE
+                }
+                VmItem::MappedHuge { va, .. } => va + HUGE_PAGE_SIZE,
+                VmItem::NotMapped { va, len } => va + len,
+            };
+            if next_addr >= range.end {
+                break;
+            }
+            cursor.jump(next_addr)?;
+        }
+        drop(cursor);
+
+        let unmapped_pages = self.unmap_vmo_pages(vm_space, pages, true)?;
+        let (Some(first), Some(last)) = (unmapped_pages.first(), unmapped_pages.last()) else {
+            return Ok(());
+        };
+
+        let _guard = lock_reclaim();
+        vmo.vmo.reclaim_pages(*first..*last + 1);
+
+        Ok(())
+    }
+
+    /// Unmaps the pages of the VMO, which are mapped at the given addresses.
+    ///
+    /// A page is skipped if it is not the one committed in the VMO, e.g., if
+    /// it is a private copy made by copy-on-write. Unless `is_forced` is
+    /// true, a page is also skipped if it has been accessed in the meantime.
+    ///
+    /// Returns the indices of the unmapped pages in the VMO, in ascending
+    /// order.
+    fn unmap_vmo_pages(
+        &self,
+        vm_space: &VmSpace,
+        pages: Vec<(Vaddr, UFrame)>,
+        is_forced: bool,
+    ) -> Result<Vec<usize>> {
+        let Some(vmo) = &self.vmo else {
+            return Ok(Vec::new());
+        };
+        let range = self.range();
+
+        // The VMO cannot be locked with the cursor held, since committing a
+        // page of the VMO may sleep.
+        let vmo_pages: Vec<_> = pages
+            .into_iter()
+            .filter_map(|(va, frame)| {
+                let vmo_offset = vmo.range.start + (va - self.map_to_addr);
+                if vmo_offset >= vmo.range.end {
+                    return None;
+                }
+                let page_idx = vmo_offset / PAGE_SIZE;
+                let committed = vmo.vmo.committed_page(page_idx)?;
+                let paddr = frame.start_paddr();
+                (committed.start_paddr() == paddr).then_some((va, page_idx, paddr))
+            })
+            .collect();
+
+        let mut unmapped_pages = Vec::new();
+        let mut dirty_pages = Vec::new();
+        let mut cursor = vm_space.cursor_mut(&range)?;
+        for (va, page_idx, paddr) in vmo_pages {
+            cursor.jump(va)?;
+            // The page may have been accessed or remapped in the meantime.
+            let VmItem::Mapped { frame, prop, .. } = cursor.query()? else {
+                continue;
+            };
+            if frame.start_paddr() != paddr
+                || (!is_forced && prop.flags.contains(PageFlags::ACCESSED))
+            {
+                continue;
+            }
+            if prop.flags.contains(PageFlags::DIRTY) {
+                dirty_pages.push(page_idx);
+            }
+            cursor.unmap(PAGE_SIZE);
+            unmapped_pages.push(page_idx);
+        }
+        drop(cursor);
+
+        for page_idx in dirty_pages {
+            vmo.vmo.mark_page_dirty(page_idx)?;
+        }
+
+        Ok(unmapped_pages)
+    }
+
+    /// Unmaps the pages in `range`, so that they are faulted in again when
+    /// they are accessed.
+    ///
+    /// The pages of a private anonymous mapping are discarded, and zero-filled
+    /// pages will be faulted in. Otherwise, the pages will be faulted in from
+    /// the VMO, which loses the private copies made by copy-on-write.
+    pub(super) fn discard_pages(&self, vm_space: &VmSpace, range: &Range<Vaddr>) -> Result<()> {
+        // Huge pages crossing the boundaries of the range are only partially
+        // discarded, so they must be split first.
+        let mut cursor = vm_space.cursor_mut(range)?;
+        cursor.split_huge();
+        cursor.jump(range.end - PAGE_SIZE)?;
+        cursor.split_huge();
+        cursor.jump(range.start)?;
+        cursor.unmap(range.len());
+
+        Ok(())
+    }
+
+    /// Punches a hole in the file or the shared memory in `range`, so that
+    /// the pages read as zeros afterwards.
+    ///
+    /// Only shared mappings that may write to the file or the shared memory
+    /// can punch holes.
+    pub(super) fn punch_hole(&self, range: &Range<Vaddr>) -> Result<()> {
+        if self.lock.is_some() {
+            return_errno_with_message!(Errno::EINVAL, "the mapping is locked");
+        }
+        let Some(vmo) = &self.vmo else {
+            return_errno_with_message!(Errno::EINVAL, "the mapping is anonymous");
+        };
+        if !self.is_shared
+            || !(self.perms.contains(VmPerms::WRITE) || self.writable_mapping.is_some())
+        {
+            return_errno_with_message!(Errno::EACCES, "the mapping is not shared and writable");
+        }
+
+        let start = vmo.range.start + (range.start - self.map_to_addr);
+        let end = min(
+            vmo.range.start + (range.end - self.map_to_addr),
+            vmo.range.end,
+        );
+        if start >= end {
+            return Ok(());
+        }
+        match &self.dentry {
+            Some(dentry) => {
+                dentry
+                    .inode()
+                    .fallocate(FallocMode::PunchHoleKeepSize, start, end - start)
+            }
+            None => vmo.vmo.clear(start..end),
+        }
+    }
+
+    /// Reads the pages of the file in `range` ahead, so that the page faults
+    /// on them will not wait for the I/O.
+    ///
+    /// The pages are not mapped. Errors of reading the pages are ignored.
+    pub(super) fn prefetch_pages(&self, range: &Range<Vaddr>) {
+        let (Some(vmo), Some(_)) = (&self.vmo, &self.dentry) else {
+            return;
+        };
+
+        let start_offset = range.start - self.map_to_addr;
+        let end_offset = (range.end - self.map_to_addr).min(vmo.size());
+        if start_offset >= end_offset {
+            return;
+        }
+        let _ = vmo.operate_on_range(&(start_offset..end_offset), |commit_fn| {
+            commit_fn().map(|_| ())
+        });
+    }
+
+    /// Sets whether the mapping is excluded from core dumps.
+    pub(super) fn set_dont_dump(self, is_dont_dump: bool) -> Self {
+        Self {
+            is_dont_dump,
+            ..self
+        }
+    }
+
+    /// Sets whether the mapping is not inherited by the child on fork.
+    pub(super) fn set_dont_fork(self, is_dont_fork: bool) -> Self {
+        Self {
+            is_dont_fork,
+            ..self
+        }
+    }
+
+    /// Sets whether the child gets zero-filled pages instead of a copy of the
+    /// pages on fork.
+    pub(super) fn set_wipe_on_fork(self, is_wipe_on_fork: bool) -> Self {
+        Self {
+            is_wipe_on_fork,
+            ..self
+        }
+    }
+
+    /// Sets the advice on whether the mapping should use transparent huge
+    /// pages.
+    pub(super) fn set_thp_advice(self, thp_advice: ThpAdvice) -> Self {
+        Self { thp_advice, ..self }
+    }
+
+    /// Sets the userfaultfd that the mapping is registered to.
+    pub(super) fn set_userfault(self, userfault: Option<UserfaultRegistration>) -> Self {
+        Self { userfault, ..self }
+    }
+
+    /// Sets how the pages of the mapping are locked in memory.
+    pub(super) fn set_lock(self, lock: Option<VmLockMode>) -> Self {
+        Self { lock, ..self }
+    }
 }
 
 /// A wrapper that represents a mapped [`Vmo`] and provide required functionalities
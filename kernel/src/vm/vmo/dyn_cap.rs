@@ -3,7 +3,7 @@
 use core::ops::Range;
 
 use aster_rights::{Rights, TRights};
-use ostd::mm::{UFrame, VmIo};
+use ostd::mm::{UFrame, USegment, VmIo};
 
 use super::{CommitFlags, Vmo, VmoRightsOp};
 use crate::prelude::*;
@@ -15,6 +15,16 @@ impl Vmo<Rights> {
         self.0.commit_page(offset)
     }
 
+    /// Commits a huge page at specific offset, which must be aligned to the
+    /// size of huge pages.
+    ///
+    /// Returns `None` if the huge page cannot be committed, then base pages
+    /// should be committed with [`Self::commit_page`] instead.
+    pub fn commit_huge_page(&self, offset: usize) -> Result<Option<USegment>> {
+        self.check_rights(Rights::WRITE)?;
+        self.0.commit_huge_page(offset)
+    }
+
     /// Commits the pages specified in the range (in bytes).
     ///
     /// The range must be within the size of the VMO.
@@ -104,7 +114,7 @@ impl Vmo<Rights> {
     /// The method requires the Dup right.
     pub fn dup_independent(&self) -> Result<Self> {
         self.check_rights(Rights::DUP | Rights::WRITE)?;
-        Ok(Vmo(Arc::new(super::Vmo_::clone(&self.0)), self.1))
+        Ok(Vmo(super::Vmo_::clone(&self.0).into_arc(), self.1))
     }
 
     /// Replaces the page at the `page_idx` in the VMO with the input `page`.
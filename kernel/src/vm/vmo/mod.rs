@@ -11,9 +11,13 @@ use align_ext::AlignExt;
 use aster_rights::Rights;
 use ostd::{
     collections::xarray::{CursorMut, XArray},
-    mm::{FrameAllocOptions, UFrame, UntypedMem, VmReader, VmWriter},
+    mm::{
+        FrameAllocOptions, Segment, UFrame, USegment, UntypedMem, VmReader, VmWriter,
+        HUGE_PAGE_SIZE,
+    },
 };
 
+use super::swap::{swap_out, SwapDevice, SwapSlot};
 use crate::prelude::*;
 
 mod dyn_cap;
@@ -133,19 +137,6 @@ pub(super) enum Pages {
     Resizable(Mutex<(XArray<UFrame>, usize)>),
 }
 
-impl Clone for Pages {
-    fn clone(&self) -> Self {
-        match self {
-            Self::Nonresizable(_, _) => {
-                self.with(|pages, size| Self::Nonresizable(Mutex::new(pages.clone()), size))
-            }
-            Self::Resizable(_) => {
-                self.with(|pages, size| Self::Resizable(Mutex::new((pages.clone(), size))))
-            }
-        }
-    }
-}
-
 impl Pages {
     fn with<R, F>(&self, func: F) -> R
     where
@@ -160,6 +151,22 @@ impl Pages {
             }
         }
     }
+
+    /// Does the same as [`Self::with`], but returns `None` without calling
+    /// `func` if the pages are locked by others.
+    fn try_with<R, F>(&self, func: F) -> Option<R>
+    where
+        F: FnOnce(&mut XArray<UFrame>, usize) -> R,
+    {
+        match self {
+            Self::Nonresizable(pages, size) => Some(func(&mut pages.try_lock()?, *size)),
+            Self::Resizable(pages) => {
+                let mut lock = pages.try_lock()?;
+                let size = lock.1;
+                Some(func(&mut lock.0, size))
+            }
+        }
+    }
 }
 
 /// `Vmo_` is the structure that actually manages the content of VMO.
@@ -167,13 +174,43 @@ impl Pages {
 /// 1. File-backed VMO: the VMO backed by a file and resides in the `PageCache`,
 ///    which includes a pager to provide it with actual pages.
 /// 2. Anonymous VMO: the VMO without a file backup, which does not have a pager.
-#[derive(Clone)]
 pub(super) struct Vmo_ {
     pager: Option<Arc<dyn Pager>>,
     /// Flags
     flags: VmoFlags,
     /// The virtual pages where the VMO resides.
     pages: Pages,
+    /// The swap slots of the pages that have been swapped out.
+    ///
+    /// Only anonymous VMOs swap out pages. A page is either committed in
+    /// `pages` or swapped out here, but not both. The lock is always acquired
+    /// with the lock of `pages` held.
+    swapped: Mutex<BTreeMap<usize, Arc<SwapSlot>>>,
+}
+
+impl Clone for Vmo_ {
+    fn clone(&self) -> Self {
+        // The swap slots are cloned with the lock of `pages` held, so that no
+        // page is swapped in or out in between.
+        let mut swapped = BTreeMap::new();
+        let pages = match &self.pages {
+            Pages::Nonresizable(_, _) => self.pages.with(|pages, size| {
+                swapped = self.swapped.lock().clone();
+                Pages::Nonresizable(Mutex::new(pages.clone()), size)
+            }),
+            Pages::Resizable(_) => self.pages.with(|pages, size| {
+                swapped = self.swapped.lock().clone();
+                Pages::Resizable(Mutex::new((pages.clone(), size)))
+            }),
+        };
+
+        Self {
+            pager: self.pager.clone(),
+            flags: self.flags,
+            pages,
+            swapped: Mutex::new(swapped),
+        }
+    }
 }
 
 impl Debug for Vmo_ {
@@ -201,10 +238,37 @@ impl CommitFlags {
 }
 
 impl Vmo_ {
+    /// Wraps the VMO in an `Arc`.
+    ///
+    /// The VMO is registered for page reclaim, unless its pages must stay in
+    /// place, e.g., for DMA.
+    fn into_arc(self) -> Arc<Self> {
+        let is_reclaimable = !self.flags.intersects(VmoFlags::CONTIGUOUS | VmoFlags::DMA);
+        let vmo = Arc::new(self);
+        if is_reclaimable {
+            let mut vmos = RECLAIMABLE_VMOS.lock();
+            if vmos.len() == vmos.capacity() {
+                vmos.retain(|vmo| vmo.strong_count() > 0);
+            }
+            vmos.push(Arc::downgrade(&vmo));
+        }
+        vmo
+    }
+
     /// Prepares a new `UFrame` for the target index in pages, returns this new frame.
+    ///
+    /// If the page has been swapped out, it is read back from the swap device.
     fn prepare_page(&self, page_idx: usize) -> Result<UFrame> {
         match &self.pager {
-            None => Ok(FrameAllocOptions::new().alloc_frame()?.into()),
+            None => {
+                let mut swapped = self.swapped.lock();
+                let Some(slot) = swapped.get(&page_idx) else {
+                    return Ok(FrameAllocOptions::new().alloc_frame()?.into());
+                };
+                let frame = slot.read()?;
+                swapped.remove(&page_idx);
+                Ok(frame)
+            }
             Some(pager) => pager.commit_page(page_idx),
         }
     }
@@ -214,6 +278,8 @@ impl Vmo_ {
         if let Some(pager) = &self.pager {
             pager.commit_overwrite(page_idx)
         } else {
+            // The swapped-out content is no longer needed.
+            self.swapped.lock().remove(&page_idx);
             Ok(FrameAllocOptions::new().alloc_frame()?.into())
         }
     }
@@ -252,6 +318,66 @@ impl Vmo_ {
         })
     }
 
+    /// Commits the huge page starting at the target offset in the VMO and
+    /// returns the frames of the huge page.
+    ///
+    /// The frames of the huge page are stored as base pages in the VMO, so
+    /// they can be accessed individually later.
+    ///
+    /// Returns `None` if the huge page is out of the VMO, if the pages in the
+    /// range are only partially committed or are not physically contiguous,
+    /// or if no aligned huge page can be allocated.
+    pub fn commit_huge_page(&self, offset: usize) -> Result<Option<USegment>> {
+        debug_assert!(offset % HUGE_PAGE_SIZE == 0);
+
+        let page_idx = offset / PAGE_SIZE;
+        let nr_pages = HUGE_PAGE_SIZE / PAGE_SIZE;
+        self.pages.with(|pages, size| {
+            if offset + HUGE_PAGE_SIZE > size {
+                return Ok(None);
+            }
+            // Swapped-out pages are swapped in one by one as base pages.
+            let page_idx_range = page_idx..page_idx + nr_pages;
+            if self.swapped.lock().range(page_idx_range).next().is_some() {
+                return Ok(None);
+            }
+
+            let committed: Vec<UFrame> = (page_idx..page_idx + nr_pages)
+                .filter_map(|idx| pages.load(idx as u64).cloned())
+                .collect();
+            if committed.len() == nr_pages {
+                return Ok(Segment::from_frames(committed)
+                    .ok()
+                    .filter(|frames| frames.start_paddr() % HUGE_PAGE_SIZE == 0));
+            } else if !committed.is_empty() {
+                return Ok(None);
+            }
+
+            let Some(frames) = self.prepare_huge_page(page_idx)? else {
+                return Ok(None);
+            };
+            for (i, frame) in frames.clone().enumerate() {
+                pages.store((page_idx + i) as u64, frame);
+            }
+            Ok(Some(frames))
+        })
+    }
+
+    /// Prepares the frames of a new huge page starting from the target index.
+    fn prepare_huge_page(&self, page_idx: usize) -> Result<Option<USegment>> {
+        if let Some(pager) = &self.pager {
+            return pager.commit_huge_page(page_idx);
+        }
+
+        let Ok(frames) = FrameAllocOptions::new().alloc_segment(HUGE_PAGE_SIZE / PAGE_SIZE) else {
+            return Ok(None);
+        };
+        if frames.start_paddr() % HUGE_PAGE_SIZE != 0 {
+            return Ok(None);
+        }
+        Ok(Some(frames.into()))
+    }
+
     /// Decommits the page corresponding to the target offset in the VMO.
     fn decommit_page(&mut self, offset: usize) -> Result<()> {
         let page_idx = offset / PAGE_SIZE;
@@ -265,6 +391,7 @@ impl Vmo_ {
             {
                 pager.decommit_page(page_idx)?;
             }
+            self.swapped.lock().remove(&page_idx);
             Ok(())
         })
     }
@@ -403,6 +530,10 @@ impl Vmo_ {
 
     fn decommit_pages(&self, pages: &mut XArray<UFrame>, range: Range<usize>) -> Result<()> {
         let page_idx_range = get_page_idx_range(&range);
+        self.swapped
+            .lock()
+            .retain(|page_idx, _| !page_idx_range.contains(page_idx));
+
         let mut cursor = pages.cursor_mut(page_idx_range.start as u64);
         for page_idx in page_idx_range {
             if cursor.remove().is_some()
@@ -421,6 +552,24 @@ impl Vmo_ {
             .with(|pages, _| pages.load(page_idx as u64).is_some())
     }
 
+    /// Returns the committed page at the target index, if any.
+    ///
+    /// Unlike [`Self::commit_page`], this neither commits the page nor swaps
+    /// the page in.
+    pub fn committed_page(&self, page_idx: usize) -> Option<UFrame> {
+        self.pages
+            .with(|pages, _| pages.load(page_idx as u64).cloned())
+    }
+
+    /// Notifies the pager that the page at the target index has been updated
+    /// without going through the VMO, e.g., by writing to a mapping.
+    pub fn mark_page_dirty(&self, page_idx: usize) -> Result<()> {
+        if let Some(pager) = &self.pager {
+            pager.update_page(page_idx)?;
+        }
+        Ok(())
+    }
+
     /// Returns the flags of current VMO.
     pub fn flags(&self) -> VmoFlags {
         self.flags
@@ -432,9 +581,111 @@ impl Vmo_ {
                 return_errno_with_message!(Errno::EINVAL, "the page index is outside of the vmo");
             }
             pages.store(page_idx as u64, page);
+            self.swapped.lock().remove(&page_idx);
             Ok(())
         })
     }
+
+    /// Reclaims at most `max_pages` pages whose indices are in `page_idxs`,
+    /// and returns the number of reclaimed pages.
+    ///
+    /// Only the pages that are used by nobody but the VMO can be reclaimed.
+    /// Pages of anonymous VMOs are swapped out, while pages of VMOs with
+    /// pagers are evicted if the pager agrees.
+    ///
+    /// Reclaim may happen while the current thread is holding the lock of the
+    /// VMO, e.g., when a page fault occurs in [`Self::read`]. So the VMO is
+    /// skipped if it is locked.
+    fn reclaim_pages(&self, page_idxs: Range<usize>, max_pages: usize) -> usize {
+        self.pages
+            .try_with(|pages, size| {
+                let mut swapped = self.swapped.lock();
+                let mut nr_reclaimed = 0;
+
+                let page_idxs = page_idxs.start..page_idxs.end.min(size / PAGE_SIZE);
+                let mut cursor = pages.cursor_mut(page_idxs.start as u64);
+                for page_idx in page_idxs {
+                    if nr_reclaimed == max_pages {
+                        break;
+                    }
+
+                    let Some(page) = cursor.load() else {
+                        cursor.next();
+                        continue;
+                    };
+                    let is_reclaimed = match &self.pager {
+                        None if page.reference_count() == 1 => {
+                            let Ok(slot) = swap_out(page) else {
+                                // No swap space is available.
+                                break;
+                            };
+                            swapped.insert(page_idx, Arc::new(slot));
+                            true
+                        }
+                        None => false,
+                        Some(pager) => pager.evict_page(page_idx),
+                    };
+                    if is_reclaimed {
+                        cursor.remove();
+                        nr_reclaimed += 1;
+                    }
+                    cursor.next();
+                }
+
+                nr_reclaimed
+            })
+            .unwrap_or(0)
+    }
+
+    /// Reads all the pages swapped out to the swap device back.
+    fn swap_in_pages(&self, device: &Arc<SwapDevice>) -> Result<()> {
+        self.pages.with(|pages, _| {
+            let mut swapped = self.swapped.lock();
+            let page_idxs: Vec<usize> = swapped
+                .iter()
+                .filter(|(_, slot)| Arc::ptr_eq(slot.device(), device))
+                .map(|(page_idx, _)| *page_idx)
+                .collect();
+
+            for page_idx in page_idxs {
+                let page = swapped[&page_idx].read()?;
+                pages.store(page_idx as u64, page);
+                swapped.remove(&page_idx);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// The VMOs whose pages can be reclaimed.
+static RECLAIMABLE_VMOS: Mutex<Vec<Weak<Vmo_>>> = Mutex::new(Vec::new());
+
+/// Returns the living VMOs whose pages can be reclaimed.
+fn reclaimable_vmos() -> Vec<Arc<Vmo_>> {
+    let mut vmos = RECLAIMABLE_VMOS.lock();
+    vmos.retain(|vmo| vmo.strong_count() > 0);
+    vmos.iter().filter_map(Weak::upgrade).collect()
+}
+
+/// Reclaims at most `max_pages` pages from all the VMOs, and returns the
+/// number of reclaimed pages.
+pub(super) fn reclaim_pages(max_pages: usize) -> usize {
+    let mut nr_reclaimed = 0;
+    for vmo in reclaimable_vmos() {
+        if nr_reclaimed == max_pages {
+            break;
+        }
+        nr_reclaimed += vmo.reclaim_pages(0..usize::MAX, max_pages - nr_reclaimed);
+    }
+    nr_reclaimed
+}
+
+/// Reads all the pages swapped out to the swap device back into the VMOs.
+pub(super) fn swap_in_pages(device: &Arc<SwapDevice>) -> Result<()> {
+    for vmo in reclaimable_vmos() {
+        vmo.swap_in_pages(device)?;
+    }
+    Ok(())
 }
 
 impl<R> Vmo<R> {
@@ -447,6 +698,34 @@ impl<R> Vmo<R> {
     pub fn flags(&self) -> VmoFlags {
         self.0.flags()
     }
+
+    /// Returns the committed page at the target index, if any.
+    pub(in crate::vm) fn committed_page(&self, page_idx: usize) -> Option<UFrame> {
+        self.0.committed_page(page_idx)
+    }
+
+    /// Marks the page at the target index as dirty.
+    pub(in crate::vm) fn mark_page_dirty(&self, page_idx: usize) -> Result<()> {
+        self.0.mark_page_dirty(page_idx)
+    }
+
+    /// Reclaims the pages whose indices are in `page_idxs`, and returns the
+    /// number of reclaimed pages.
+    ///
+    /// The pages that are still used by others, e.g., mapped to the user
+    /// space, are not reclaimed. The caller should hold the lock returned by
+    /// [`lock_reclaim`].
+    ///
+    /// [`lock_reclaim`]: crate::vm::reclaim::lock_reclaim
+    pub(in crate::vm) fn reclaim_pages(&self, page_idxs: Range<usize>) -> usize {
+        if self
+            .flags()
+            .intersects(VmoFlags::CONTIGUOUS | VmoFlags::DMA)
+        {
+            return 0;
+        }
+        self.0.reclaim_pages(page_idxs, usize::MAX)
+    }
 }
 
 /// Gets the page index range that contains the offset range of VMO.
@@ -97,7 +97,7 @@ impl VmoOptions<Rights> {
             size, flags, pager, ..
         } = self;
         let vmo_ = alloc_vmo_(size, flags, pager)?;
-        Ok(Vmo(Arc::new(vmo_), Rights::all()))
+        Ok(Vmo(vmo_.into_arc(), Rights::all()))
     }
 }
 
@@ -116,7 +116,7 @@ impl<R: TRights> VmoOptions<TRightSet<R>> {
             pager,
         } = self;
         let vmo_ = alloc_vmo_(size, flags, pager)?;
-        Ok(Vmo(Arc::new(vmo_), TRightSet(R::new())))
+        Ok(Vmo(vmo_.into_arc(), TRightSet(R::new())))
     }
 }
 
@@ -134,6 +134,7 @@ fn alloc_vmo_(size: usize, flags: VmoFlags, pager: Option<Arc<dyn Pager>>) -> Re
         pager,
         flags,
         pages,
+        swapped: Mutex::new(BTreeMap::new()),
     })
 }
 
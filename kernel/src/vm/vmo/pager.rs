@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use ostd::mm::UFrame;
+use ostd::mm::{UFrame, USegment};
 
 use crate::prelude::*;
 
@@ -55,4 +55,29 @@ pub trait Pager: Send + Sync {
     /// Notify the pager that the frame will be fully overwritten soon, so pager can
     /// choose not to initialize it.
     fn commit_overwrite(&self, idx: usize) -> Result<UFrame>;
+
+    /// Ask the pager to provide the frames of a huge page starting at a
+    /// specified index.
+    ///
+    /// The frames must be physically contiguous, and the physical address must
+    /// be aligned to the size of the huge page. The pager may return `None` if
+    /// it cannot provide such frames, then the VMO will commit base pages
+    /// instead. By default, huge pages are not provided.
+    fn commit_huge_page(&self, idx: usize) -> Result<Option<USegment>> {
+        Ok(None)
+    }
+
+    /// Ask the pager to evict the frame at a specified index to reclaim memory.
+    ///
+    /// The pager should only evict a frame that is used by nobody but the
+    /// pager and the VMO, e.g., not mapped to user space, and whose data can
+    /// be provided again, e.g., a clean page of a file. If the pager returns
+    /// `true`, the pager has dropped the frame and the VMO will decommit the
+    /// page without calling [`Self::decommit_page`]. The pager will be asked
+    /// to provide the frame again when the page is accessed later.
+    ///
+    /// By default, frames are never evicted.
+    fn evict_page(&self, idx: usize) -> bool {
+        false
+    }
 }
@@ -105,7 +105,7 @@ impl<R: TRights> Vmo<TRightSet<R>> {
     /// The method requires the Dup right.
     #[require(R > Dup | Write)]
     pub fn dup_independent(&self) -> Self {
-        Vmo(Arc::new(super::Vmo_::clone(&self.0)), self.1)
+        Vmo(super::Vmo_::clone(&self.0).into_arc(), self.1)
     }
 
     /// Replaces the page at the `page_idx` in the VMO with the input `page`.
@@ -9,7 +9,10 @@ use core::fmt::Debug;
 
 use log::{debug, error};
 
-use super::{device_info::PciDeviceId, PciCommonDevice};
+use super::{
+    device_info::{PciDeviceId, PciDeviceLocation},
+    PciCommonDevice,
+};
 use crate::bus::BusProbeError;
 
 /// PciDevice trait.
@@ -43,6 +46,8 @@ pub struct PciBus {
     common_devices: VecDeque<PciCommonDevice>,
     devices: Vec<Arc<dyn PciDevice>>,
     drivers: Vec<Arc<dyn PciDriver>>,
+    /// The locations and IDs of all the devices found on the bus.
+    device_infos: Vec<(PciDeviceLocation, PciDeviceId)>,
 }
 
 impl PciBus {
@@ -72,9 +77,17 @@ impl PciBus {
         self.drivers.push(driver);
     }
 
+    /// Returns the locations and IDs of all the devices found on the bus, whether or not they
+    /// are claimed by drivers.
+    pub fn device_infos(&self) -> &[(PciDeviceLocation, PciDeviceId)] {
+        &self.device_infos
+    }
+
     pub(super) fn register_common_device(&mut self, mut common_device: PciCommonDevice) {
         debug!("Find pci common devices:{:x?}", common_device);
         let device_id = *common_device.device_id();
+        self.device_infos
+            .push((*common_device.location(), device_id));
         for driver in self.drivers.iter() {
             common_device = match driver.probe(common_device) {
                 Ok(device) => {
@@ -99,6 +112,7 @@ impl PciBus {
             common_devices: VecDeque::new(),
             devices: Vec::new(),
             drivers: Vec::new(),
+            device_infos: Vec::new(),
         }
     }
 }
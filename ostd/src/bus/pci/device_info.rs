@@ -112,6 +112,15 @@ impl PciDeviceLocation {
     }
 }
 
+impl PciDeviceLocation {
+    /// Reads the 32-bit value at `offset` in the configuration space of the device.
+    ///
+    /// The offset must be aligned to 4 bytes.
+    pub fn read_config32(&self, offset: u16) -> u32 {
+        self.read32(offset)
+    }
+}
+
 impl PciDeviceLocation {
     pub(super) const BIT32_ALIGN_MASK: u16 = 0xFFFC;
 
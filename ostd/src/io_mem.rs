@@ -6,8 +6,10 @@ use core::ops::{Deref, Range};
 
 use align_ext::AlignExt;
 use cfg_if::cfg_if;
+use spin::Once;
 
 use crate::{
+    boot::{boot_info, memory_region::MemoryRegionType},
     mm::{
         kspace::kvirt_area::{KVirtArea, Untracked},
         page_prop::{CachePolicy, PageFlags, PageProperty, PrivilegedPageFlags},
@@ -83,6 +85,32 @@ impl IoMem {
         }
     }
 
+    /// Acquires the I/O memory of the framebuffer that is set up by the bootloader.
+    ///
+    /// Returns `None` if the bootloader does not provide a framebuffer.
+    pub fn acquire_framebuffer() -> Option<Self> {
+        static FRAMEBUFFER: Once<Option<IoMem>> = Once::new();
+
+        FRAMEBUFFER
+            .call_once(|| {
+                let framebuffer = boot_info().framebuffer_arg?;
+                let size = boot_info()
+                    .memory_regions
+                    .iter()
+                    .find(|region| region.typ() == MemoryRegionType::Framebuffer)
+                    .map(|region| region.len())
+                    .unwrap_or(
+                        (framebuffer.width * framebuffer.height * framebuffer.bpp).div_ceil(8),
+                    );
+                let range = framebuffer.address..framebuffer.address + size;
+
+                // SAFETY: The range is the framebuffer reported by the bootloader, which is
+                // I/O memory. Writing to it only changes the pixels on the screen.
+                Some(unsafe { IoMem::new(range, PageFlags::RW, CachePolicy::Uncacheable) })
+            })
+            .clone()
+    }
+
     /// Returns the physical address of the I/O memory.
     pub fn paddr(&self) -> Paddr {
         self.pa
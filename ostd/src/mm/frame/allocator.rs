@@ -14,6 +14,7 @@ use crate::{
     mm::{paddr_to_vaddr, Paddr, PAGE_SIZE},
     prelude::*,
     sync::SpinLock,
+    task::atomic_mode::is_atomic_mode,
 };
 
 /// Options for allocating physical memory frames.
@@ -52,12 +53,7 @@ impl FrameAllocOptions {
 
     /// Allocates a single frame with additional metadata.
     pub fn alloc_frame_with<M: AnyFrameMeta>(&self, metadata: M) -> Result<Frame<M>> {
-        let frame = FRAME_ALLOCATOR
-            .get()
-            .unwrap()
-            .disable_irq()
-            .lock()
-            .alloc(1)
+        let frame = alloc_frames(1)
             .map(|idx| {
                 let paddr = idx * PAGE_SIZE;
                 Frame::from_unused(paddr, metadata)
@@ -93,12 +89,7 @@ impl FrameAllocOptions {
         if nframes == 0 {
             return Err(Error::InvalidArgs);
         }
-        let segment = FRAME_ALLOCATOR
-            .get()
-            .unwrap()
-            .disable_irq()
-            .lock()
-            .alloc(nframes)
+        let segment = alloc_frames(nframes)
             .map(|start| {
                 Segment::from_unused(
                     start * PAGE_SIZE..start * PAGE_SIZE + nframes * PAGE_SIZE,
@@ -117,6 +108,42 @@ impl FrameAllocOptions {
     }
 }
 
+static RECLAIM_HANDLER: Once<fn(usize) -> bool> = Once::new();
+
+/// Registers a function to reclaim memory when the frame allocator runs out of memory.
+///
+/// The handler is called with the number of frames that fail to be allocated, and returns
+/// whether some frames have been freed. Then the allocation is retried once. The handler is
+/// only called if the allocation is not in atomic mode, so it may sleep. But it must not wait
+/// for any lock that the allocating task may be holding.
+///
+/// This function can only be registered once. Subsequent calls will do nothing.
+pub fn register_reclaim_handler(func: fn(usize) -> bool) {
+    RECLAIM_HANDLER.call_once(|| func);
+}
+
+/// Allocates `count` contiguous frames, and returns the index of the first frame.
+fn alloc_frames(count: usize) -> Option<usize> {
+    let alloc = || {
+        FRAME_ALLOCATOR
+            .get()
+            .unwrap()
+            .disable_irq()
+            .lock()
+            .alloc(count)
+    };
+
+    if let Some(start) = alloc() {
+        return Some(start);
+    }
+
+    let handler = RECLAIM_HANDLER.get()?;
+    if is_atomic_mode() || !handler(count) {
+        return None;
+    }
+    alloc()
+}
+
 #[cfg(ktest)]
 #[ktest]
 fn test_alloc_dealloc() {
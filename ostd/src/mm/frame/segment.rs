@@ -2,6 +2,7 @@
 
 //! A contiguous range of frames.
 
+use alloc::vec::Vec;
 use core::{mem::ManuallyDrop, ops::Range};
 
 use super::{inc_frame_ref_count, meta::AnyFrameMeta, Frame};
@@ -157,6 +158,68 @@ impl<M: AnyFrameMeta + ?Sized> Segment<M> {
             _marker: core::marker::PhantomData,
         }
     }
+
+    /// Creates a [`Segment`] from the handles to physically contiguous frames.
+    ///
+    /// The frames must be sorted by their physical addresses. If the frames
+    /// are empty or not contiguous, they are returned back as is.
+    ///
+    /// This is useful when the frames are allocated as a contiguous range but
+    /// then managed individually, e.g., by a page cache.
+    pub fn from_frames(frames: Vec<Frame<M>>) -> core::result::Result<Self, Vec<Frame<M>>> {
+        let Some(first) = frames.first() else {
+            return Err(frames);
+        };
+        let start = first.start_paddr();
+        let is_contiguous = frames
+            .iter()
+            .enumerate()
+            .all(|(i, frame)| frame.start_paddr() == start + i * PAGE_SIZE);
+        if !is_contiguous {
+            return Err(frames);
+        }
+
+        let end = start + frames.len() * PAGE_SIZE;
+        for frame in frames {
+            let _ = ManuallyDrop::new(frame);
+        }
+        Ok(Self {
+            range: start..end,
+            _marker: core::marker::PhantomData,
+        })
+    }
+
+    /// Forgets the handle to the frames.
+    ///
+    /// This will result in the frames being leaked without calling the custom
+    /// dropper.
+    ///
+    /// The physical address range of the frames is returned in case the frames
+    /// need to be restored using [`Segment::from_raw`] later. This is useful
+    /// when the page table needs to hold the handle of a huge page.
+    pub(in crate::mm) fn into_raw(self) -> Range<Paddr> {
+        let range = self.range.clone();
+        let _ = ManuallyDrop::new(self);
+        range
+    }
+
+    /// Restores a forgotten [`Segment`] from a physical address range.
+    ///
+    /// # Safety
+    ///
+    /// The caller should only restore a `Segment` that was previously
+    /// forgotten using [`Segment::into_raw`], or a range of frames whose
+    /// handles were all forgotten using [`Frame::into_raw`].
+    ///
+    /// And the restoring operation should only be done once for a forgotten
+    /// `Segment`. Otherwise double-free will happen.
+    pub(in crate::mm) unsafe fn from_raw(range: Range<Paddr>) -> Self {
+        debug_assert!(range.start % PAGE_SIZE == 0 && range.end % PAGE_SIZE == 0);
+        Self {
+            range,
+            _marker: core::marker::PhantomData,
+        }
+    }
 }
 
 impl<M: AnyFrameMeta + ?Sized> From<Frame<M>> for Segment<M> {
@@ -231,6 +294,13 @@ impl<M: AnyUFrameMeta> From<Segment<M>> for USegment {
     }
 }
 
+impl From<USegment> for Segment<dyn AnyFrameMeta> {
+    fn from(seg: USegment) -> Self {
+        // SAFETY: The metadata is coerceable and the struct is transmutable.
+        unsafe { core::mem::transmute(seg) }
+    }
+}
+
 impl TryFrom<Segment<dyn AnyFrameMeta>> for USegment {
     type Error = Segment<dyn AnyFrameMeta>;
 
@@ -346,6 +346,9 @@ impl<M: AllocatorSelector + 'static> Drop for KVirtArea<M> {
                     }
                     _ => panic!("Unexpected `KVirtArea` type"),
                 },
+                PageTableItem::MappedHuge { .. } => {
+                    panic!("Found huge pages mapped into `KVirtArea`");
+                }
                 PageTableItem::NotMapped { .. } => {
                     break;
                 }
@@ -75,6 +75,12 @@ pub(crate) trait PagingConstsTrait: Clone + Debug + Default + Send + Sync + 'sta
 /// The page size
 pub const PAGE_SIZE: usize = page_size::<PagingConsts>(1);
 
+/// The size of the smallest huge page.
+///
+/// Huge pages of this size can be mapped into a [`VmSpace`] with
+/// [`vm_space::CursorMut::map_huge`].
+pub const HUGE_PAGE_SIZE: usize = page_size::<PagingConsts>(2);
+
 /// The page size at a given level.
 pub(crate) const fn page_size<C: PagingConstsTrait>(level: PagingLevel) -> usize {
     C::BASE_PAGE_SIZE << (nr_subpage_per_huge::<C>().ilog2() as usize * (level as usize - 1))
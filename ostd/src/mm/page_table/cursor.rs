@@ -76,7 +76,7 @@ use super::{
 };
 use crate::{
     mm::{
-        frame::{meta::AnyFrameMeta, Frame},
+        frame::{meta::AnyFrameMeta, Frame, Segment},
         kspace::should_map_as_tracked,
         paddr_to_vaddr, Paddr, PageProperty, Vaddr,
     },
@@ -94,6 +94,11 @@ pub enum PageTableItem {
         page: Frame<dyn AnyFrameMeta>,
         prop: PageProperty,
     },
+    MappedHuge {
+        va: Vaddr,
+        frames: Segment<dyn AnyFrameMeta>,
+        prop: PageProperty,
+    },
     #[allow(dead_code)]
     MappedUntracked {
         va: Vaddr,
@@ -236,6 +241,13 @@ where
                 Child::Frame(page, prop) => {
                     return Ok(PageTableItem::Mapped { va, page, prop });
                 }
+                Child::HugeFrames(frames, prop) => {
+                    return Ok(PageTableItem::MappedHuge {
+                        va: va.align_down(page_size::<C>(level)),
+                        frames,
+                        prop,
+                    });
+                }
                 Child::Untracked(pa, plevel, prop) => {
                     debug_assert_eq!(plevel, level);
                     return Ok(PageTableItem::MappedUntracked {
@@ -443,7 +455,7 @@ where
                     let _ = cur_entry.replace(Child::PageTable(pt.clone_raw()));
                     self.0.push_level(pt);
                 }
-                Child::Frame(_, _) => {
+                Child::Frame(_, _) | Child::HugeFrames(_, _) => {
                     panic!("Mapping a smaller page in an already mapped huge page");
                 }
                 Child::Untracked(_, _, _) => {
@@ -464,10 +476,106 @@ where
             Child::PageTable(_) => {
                 todo!("Dropping page table nodes while mapping requires TLB flush")
             }
+            Child::HugeFrames(_, _) => unreachable!("Huge pages are not at the base level"),
             Child::Untracked(_, _, _) => panic!("Mapping a tracked page in an untracked range"),
         }
     }
 
+    /// Maps the range starting from the current address to a huge page made
+    /// of contiguous frames.
+    ///
+    /// The size of the frames must be the page size of a level that supports
+    /// huge pages. Both the current address and the physical address of the
+    /// frames must be aligned to the size.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if
+    ///  - the virtual address range to be mapped is out of the range;
+    ///  - the size or the alignment of the frames is not supported;
+    ///  - the slot to be mapped is not empty, including the case where
+    ///    there is a child page table node in the slot.
+    ///
+    /// # Safety
+    ///
+    /// The caller should ensure that the virtual range being mapped does
+    /// not affect kernel's memory safety.
+    pub unsafe fn map_huge(&mut self, frames: Segment<dyn AnyFrameMeta>, prop: PageProperty) {
+        let size = frames.size();
+        let end = self.0.va + size;
+        assert!(end <= self.0.barrier_va.end);
+        assert!(self.0.va % size == 0 && frames.start_paddr() % size == 0);
+
+        // Go down if not applicable.
+        while self.0.level > C::HIGHEST_TRANSLATION_LEVEL || page_size::<C>(self.0.level) > size {
+            debug_assert!(self.0.should_map_as_tracked());
+            let cur_level = self.0.level;
+            let cur_entry = self.0.cur_entry();
+            match cur_entry.to_owned() {
+                Child::PageTable(pt) => {
+                    self.0.push_level(pt.lock());
+                }
+                Child::None => {
+                    let pt =
+                        PageTableNode::<E, C>::alloc(cur_level - 1, MapTrackingStatus::Tracked);
+                    let _ = cur_entry.replace(Child::PageTable(pt.clone_raw()));
+                    self.0.push_level(pt);
+                }
+                Child::Frame(_, _) | Child::HugeFrames(_, _) => {
+                    panic!("Mapping a smaller page in an already mapped huge page");
+                }
+                Child::Untracked(_, _, _) => {
+                    panic!("Mapping a tracked page in an untracked range");
+                }
+            }
+        }
+        assert!(self.0.level > 1 && page_size::<C>(self.0.level) == size);
+
+        let cur_entry = self.0.cur_entry();
+        assert!(
+            cur_entry.is_none(),
+            "Mapping a huge page in a non-empty slot"
+        );
+
+        // Map the current page.
+        let _ = cur_entry.replace(Child::HugeFrames(frames, prop));
+        self.0.move_forward();
+    }
+
+    /// Splits the huge page containing the current address.
+    ///
+    /// The huge page is split into pages of the next lower level, which are
+    /// mapped by a new child page table node with the same properties. The
+    /// frames are not copied. The cursor is not moved.
+    ///
+    /// Since the mapped physical pages and their properties are not changed,
+    /// no TLB flush is required after the operation.
+    ///
+    /// Returns `true` if a huge page is split, and `false` if the current
+    /// address is not mapped to a huge page.
+    pub fn split_huge(&mut self) -> bool {
+        loop {
+            let cur_entry = self.0.cur_entry();
+            if cur_entry.is_none() {
+                return false;
+            }
+            if cur_entry.is_node() {
+                let Child::PageTable(pt) = cur_entry.to_owned() else {
+                    unreachable!("Already checked");
+                };
+                self.0.push_level(pt.lock());
+                continue;
+            }
+            return match cur_entry.split_if_huge() {
+                Some(split_child) => {
+                    self.0.push_level(split_child);
+                    true
+                }
+                None => false,
+            };
+        }
+    }
+
     /// Maps the range starting from the current address to a physical address range.
     ///
     /// The function will map as more huge pages as possible, and it will split
@@ -526,11 +634,11 @@ where
                         let _ = cur_entry.replace(Child::PageTable(pt.clone_raw()));
                         self.0.push_level(pt);
                     }
-                    Child::Frame(_, _) => {
+                    Child::Frame(_, _) | Child::HugeFrames(_, _) => {
                         panic!("Mapping a smaller page in an already mapped huge page");
                     }
                     Child::Untracked(_, _, _) => {
-                        let split_child = cur_entry.split_if_untracked_huge().unwrap();
+                        let split_child = cur_entry.split_if_huge().unwrap();
                         self.0.push_level(split_child);
                     }
                 }
@@ -574,7 +682,8 @@ where
     /// # Panics
     ///
     /// This function will panic if the end range covers a part of a huge page
-    /// and the next page is that huge page.
+    /// that cannot be split, and the next page is that huge page. Tracked and
+    /// untracked huge pages are split into smaller pages in such cases.
     pub unsafe fn take_next(&mut self, len: usize) -> PageTableItem {
         let start = self.0.va;
         assert!(len % page_size::<C>(1) == 0);
@@ -623,8 +732,8 @@ where
                     Child::Frame(_, _) => {
                         panic!("Removing part of a huge page");
                     }
-                    Child::Untracked(_, _, _) => {
-                        let split_child = cur_entry.split_if_untracked_huge().unwrap();
+                    Child::HugeFrames(_, _) | Child::Untracked(_, _, _) => {
+                        let split_child = cur_entry.split_if_huge().unwrap();
                         self.0.push_level(split_child);
                     }
                 }
@@ -642,6 +751,11 @@ where
                     page,
                     prop,
                 },
+                Child::HugeFrames(frames, prop) => PageTableItem::MappedHuge {
+                    va: cur_va,
+                    frames,
+                    prop,
+                },
                 Child::Untracked(pa, level, prop) => {
                     debug_assert_eq!(level, self.0.level);
                     PageTableItem::MappedUntracked {
@@ -719,10 +833,10 @@ where
             }
 
             // Go down if the page size is too big and we are protecting part
-            // of untracked huge pages.
+            // of huge pages.
             if cur_va % page_size::<C>(cur_level) != 0 || cur_va + page_size::<C>(cur_level) > end {
                 let split_child = cur_entry
-                    .split_if_untracked_huge()
+                    .split_if_huge()
                     .expect("Protecting part of a huge page");
                 self.0.push_level(split_child);
                 continue;
@@ -819,6 +933,24 @@ where
                     debug_assert_eq!(mapped_page_size, page_size::<C>(src.0.level));
                     src.0.move_forward();
                 }
+                Child::HugeFrames(frames, mut prop) => {
+                    // Go down if we are copying part of the huge page.
+                    if src_va % frames.size() != 0 || src_va + frames.size() > src_end {
+                        let split_child = src_entry.split_if_huge().unwrap();
+                        src.0.push_level(split_child);
+                        continue;
+                    }
+
+                    // Do protection.
+                    src_entry.protect(op);
+
+                    // Do copy.
+                    op(&mut prop);
+                    self.jump(src_va).unwrap();
+                    self.map_huge(frames, prop);
+
+                    src.0.move_forward();
+                }
             }
         }
     }
@@ -8,9 +8,9 @@ use super::{MapTrackingStatus, PageTableEntryTrait, RawPageTableNode};
 use crate::{
     arch::mm::{PageTableEntry, PagingConsts},
     mm::{
-        frame::{inc_frame_ref_count, meta::AnyFrameMeta, Frame},
+        frame::{inc_frame_ref_count, meta::AnyFrameMeta, Frame, Segment},
         page_prop::PageProperty,
-        Paddr, PagingConstsTrait, PagingLevel,
+        page_size, Paddr, PagingConstsTrait, PagingLevel,
     },
 };
 
@@ -28,6 +28,11 @@ pub(in crate::mm) enum Child<
 {
     PageTable(RawPageTableNode<E, C>),
     Frame(Frame<dyn AnyFrameMeta>, PageProperty),
+    /// A huge page made of contiguous base frames.
+    ///
+    /// Each base frame is tracked by its own handle, so that splitting the
+    /// huge page is simply handing the frames over to smaller PTEs.
+    HugeFrames(Segment<dyn AnyFrameMeta>, PageProperty),
     /// Pages not tracked by handles.
     Untracked(Paddr, PagingLevel, PageProperty),
     None,
@@ -56,6 +61,11 @@ where
             Child::Frame(p, _) => {
                 node_level == p.level() && is_tracked == MapTrackingStatus::Tracked
             }
+            Child::HugeFrames(frames, _) => {
+                node_level > 1
+                    && frames.size() == page_size::<C>(node_level)
+                    && is_tracked == MapTrackingStatus::Tracked
+            }
             Child::Untracked(_, level, _) => {
                 node_level == *level && is_tracked == MapTrackingStatus::Untracked
             }
@@ -82,6 +92,10 @@ where
                 let level = page.level();
                 E::new_page(page.into_raw(), level, prop)
             }
+            Child::HugeFrames(frames, prop) => {
+                let level = huge_level::<C>(frames.size());
+                E::new_page(frames.into_raw().start, level, prop)
+            }
             Child::Untracked(pa, level, prop) => E::new_page(pa, level, prop),
             Child::None => E::new_absent(),
         }
@@ -117,6 +131,13 @@ where
         }
 
         match is_tracked {
+            MapTrackingStatus::Tracked if level > 1 => {
+                let range = paddr..paddr + page_size::<C>(level);
+                // SAFETY: The physical address range points to valid frames,
+                // all of whose handles are forgotten by the PTE.
+                let frames = unsafe { Segment::<dyn AnyFrameMeta>::from_raw(range) };
+                Child::HugeFrames(frames, pte.prop())
+            }
             MapTrackingStatus::Tracked => {
                 // SAFETY: The physical address points to a valid page.
                 let page = unsafe { Frame::<dyn AnyFrameMeta>::from_raw(paddr) };
@@ -157,6 +178,15 @@ where
         }
 
         match is_tracked {
+            MapTrackingStatus::Tracked if level > 1 => {
+                let range = paddr..paddr + page_size::<C>(level);
+                // SAFETY: The physical address range points to valid frames,
+                // whose references are owned by the PTE. The restored handle
+                // is not dropped but cloned to gain extra references.
+                let frames =
+                    ManuallyDrop::new(unsafe { Segment::<dyn AnyFrameMeta>::from_raw(range) });
+                Child::HugeFrames((*frames).clone(), pte.prop())
+            }
             MapTrackingStatus::Tracked => {
                 // SAFETY: The physical address is valid and the PTE already owns
                 // the reference to the page.
@@ -170,3 +200,13 @@ where
         }
     }
 }
+
+/// Returns the paging level whose page size equals to the given size.
+fn huge_level<C: PagingConstsTrait>(size: usize) -> PagingLevel {
+    let mut level = 2;
+    while page_size::<C>(level) < size {
+        level += 1;
+    }
+    debug_assert_eq!(page_size::<C>(level), size);
+    level
+}
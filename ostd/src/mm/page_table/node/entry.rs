@@ -2,7 +2,7 @@
 
 //! This module provides accessors to the page table entries in a node.
 
-use super::{Child, MapTrackingStatus, PageTableEntryTrait, PageTableNode};
+use super::{Child, PageTableEntryTrait, PageTableNode};
 use crate::mm::{nr_subpage_per_huge, page_prop::PageProperty, page_size, PagingConstsTrait};
 
 /// A view of an entry in a page table node.
@@ -108,33 +108,48 @@ where
         old_child
     }
 
-    /// Splits the entry to smaller pages if it maps to a untracked huge page.
+    /// Splits the entry to smaller pages if it maps to a huge page.
     ///
-    /// If the entry does map to a untracked huge page, it is split into smaller
-    /// pages mapped by a child page table node. The new child page table node
-    /// is returned.
+    /// If the entry does map to a huge page, it is split into smaller pages
+    /// mapped by a child page table node. The new child page table node is
+    /// returned.
     ///
-    /// If the entry does not map to a untracked huge page, the method returns
-    /// `None`.
-    pub(in crate::mm) fn split_if_untracked_huge(self) -> Option<PageTableNode<E, C>> {
+    /// The smaller pages of a tracked huge page take the references of the
+    /// base frames from the huge page, so the frames are not copied.
+    ///
+    /// If the entry does not map to a huge page, the method returns `None`.
+    pub(in crate::mm) fn split_if_huge(self) -> Option<PageTableNode<E, C>> {
         let level = self.node.level();
 
-        if !(self.pte.is_last(level)
-            && level > 1
-            && self.node.is_tracked() == MapTrackingStatus::Untracked)
-        {
+        if !(self.pte.is_present() && self.pte.is_last(level) && level > 1) {
             return None;
         }
 
-        let pa = self.pte.paddr();
-        let prop = self.pte.prop();
-
-        let mut new_page = PageTableNode::<E, C>::alloc(level - 1, MapTrackingStatus::Untracked);
-        for i in 0..nr_subpage_per_huge::<C>() {
-            let small_pa = pa + i * page_size::<C>(level - 1);
-            let _ = new_page
-                .entry(i)
-                .replace(Child::Untracked(small_pa, level - 1, prop));
+        let is_tracked = self.node.is_tracked();
+        let mut new_page = PageTableNode::<E, C>::alloc(level - 1, is_tracked);
+
+        match self.to_owned() {
+            Child::HugeFrames(frames, prop) => {
+                let sub_size = page_size::<C>(level - 1);
+                for i in 0..nr_subpage_per_huge::<C>() {
+                    let mut sub_frames = frames.slice(&(i * sub_size..(i + 1) * sub_size));
+                    let child = if level - 1 == 1 {
+                        Child::Frame(sub_frames.next().unwrap(), prop)
+                    } else {
+                        Child::HugeFrames(sub_frames, prop)
+                    };
+                    let _ = new_page.entry(i).replace(child);
+                }
+            }
+            Child::Untracked(pa, _, prop) => {
+                for i in 0..nr_subpage_per_huge::<C>() {
+                    let small_pa = pa + i * page_size::<C>(level - 1);
+                    let _ = new_page
+                        .entry(i)
+                        .replace(Child::Untracked(small_pa, level - 1, prop));
+                }
+            }
+            Child::PageTable(_) | Child::Frame(_, _) | Child::None => unreachable!(),
         }
 
         let _ = self.replace(Child::PageTable(new_page.clone_raw()));
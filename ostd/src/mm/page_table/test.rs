@@ -7,7 +7,7 @@ use crate::{
     mm::{
         kspace::LINEAR_MAPPING_BASE_VADDR,
         page_prop::{CachePolicy, PageFlags},
-        FrameAllocOptions, MAX_USERSPACE_VADDR,
+        FrameAllocOptions, HUGE_PAGE_SIZE, MAX_USERSPACE_VADDR,
     },
     prelude::*,
 };
@@ -42,6 +42,42 @@ fn test_tracked_map_unmap() {
     assert!(pt.query(from.start + 10).is_none());
 }
 
+#[ktest]
+fn test_tracked_huge_map_split_unmap() {
+    let pt = PageTable::<UserMode>::empty();
+
+    let from = HUGE_PAGE_SIZE..HUGE_PAGE_SIZE * 2;
+    let frames = FrameAllocOptions::new()
+        .alloc_segment(HUGE_PAGE_SIZE / PAGE_SIZE)
+        .unwrap();
+    let start_paddr = frames.start_paddr();
+    assert_eq!(start_paddr % HUGE_PAGE_SIZE, 0);
+    let prop = PageProperty::new(PageFlags::RW, CachePolicy::Writeback);
+    unsafe { pt.cursor_mut(&from).unwrap().map_huge(frames.into(), prop) };
+    assert_eq!(
+        pt.query(from.start + PAGE_SIZE + 10).unwrap().0,
+        start_paddr + PAGE_SIZE + 10
+    );
+    assert!(matches!(
+        pt.cursor(&from).unwrap().query().unwrap(),
+        PageTableItem::MappedHuge { va, .. } if va == from.start
+    ));
+
+    // Unmapping part of the huge page splits it into base pages.
+    let unmap = from.start + PAGE_SIZE..from.start + PAGE_SIZE * 2;
+    assert!(matches!(
+        unsafe { pt.cursor_mut(&unmap).unwrap().take_next(unmap.len()) },
+        PageTableItem::Mapped { .. }
+    ));
+    assert!(pt.query(unmap.start).is_none());
+    assert_eq!(pt.query(from.start).unwrap().0, start_paddr);
+    assert_eq!(pt.query(unmap.end).unwrap().0, start_paddr + PAGE_SIZE * 2);
+    assert!(matches!(
+        pt.cursor(&from).unwrap().query().unwrap(),
+        PageTableItem::Mapped { .. }
+    ));
+}
+
 #[ktest]
 fn test_untracked_map_unmap() {
     let pt = PageTable::<KernelMode>::empty();
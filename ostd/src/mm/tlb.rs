@@ -6,7 +6,7 @@ use alloc::vec::Vec;
 use core::ops::Range;
 
 use super::{
-    frame::{meta::AnyFrameMeta, Frame},
+    frame::{meta::AnyFrameMeta, Frame, Segment},
     Vaddr, PAGE_SIZE,
 };
 use crate::{
@@ -81,6 +81,18 @@ impl<G: PinCurrentCpu> TlbFlusher<G> {
     /// space program can still access the page through the TLB entries. This
     /// method is designed to be used in such cases.
     pub fn issue_tlb_flush_with(&self, op: TlbFlushOp, drop_after_flush: Frame<dyn AnyFrameMeta>) {
+        self.issue_tlb_flush_(op, Some(drop_after_flush.into()));
+    }
+
+    /// Issues a TLB flush request that must happen before dropping the frames.
+    ///
+    /// This is the same as [`Self::issue_tlb_flush_with`], but for multiple
+    /// contiguous frames, e.g., the frames of a removed huge page.
+    pub fn issue_tlb_flush_with_frames(
+        &self,
+        op: TlbFlushOp,
+        drop_after_flush: Segment<dyn AnyFrameMeta>,
+    ) {
         self.issue_tlb_flush_(op, Some(drop_after_flush));
     }
 
@@ -94,7 +106,11 @@ impl<G: PinCurrentCpu> TlbFlusher<G> {
         self.need_self_flush
     }
 
-    fn issue_tlb_flush_(&self, op: TlbFlushOp, drop_after_flush: Option<Frame<dyn AnyFrameMeta>>) {
+    fn issue_tlb_flush_(
+        &self,
+        op: TlbFlushOp,
+        drop_after_flush: Option<Segment<dyn AnyFrameMeta>>,
+    ) {
         let op = op.optimize_for_large_range();
 
         // Fast path for single CPU cases.
@@ -159,7 +175,7 @@ impl TlbFlushOp {
 // Lock ordering: lock FLUSH_OPS before PAGE_KEEPER.
 cpu_local! {
     static FLUSH_OPS: SpinLock<OpsStack, LocalIrqDisabled> = SpinLock::new(OpsStack::new());
-    static PAGE_KEEPER: SpinLock<Vec<Frame<dyn AnyFrameMeta>>, LocalIrqDisabled> = SpinLock::new(Vec::new());
+    static PAGE_KEEPER: SpinLock<Vec<Segment<dyn AnyFrameMeta>>, LocalIrqDisabled> = SpinLock::new(Vec::new());
 }
 
 fn do_remote_flush() {
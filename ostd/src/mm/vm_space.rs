@@ -22,7 +22,7 @@ use crate::{
         kspace::KERNEL_PAGE_TABLE,
         page_table::{self, PageTable, PageTableItem, UserMode},
         tlb::{TlbFlushOp, TlbFlusher, FLUSH_ALL_RANGE_THRESHOLD},
-        PageProperty, UFrame, VmReader, VmWriter, MAX_USERSPACE_VADDR,
+        PageProperty, UFrame, USegment, VmReader, VmWriter, MAX_USERSPACE_VADDR,
     },
     prelude::*,
     sync::{PreemptDisabled, RwLock, RwLockReadGuard},
@@ -335,6 +335,36 @@ impl CursorMut<'_, '_> {
         }
     }
 
+    /// Map a huge page made of contiguous frames into the current slot.
+    ///
+    /// The size of the frames must be [`super::HUGE_PAGE_SIZE`]. Both the
+    /// current virtual address and the physical address of the frames must
+    /// be aligned to the size.
+    ///
+    /// This method will bring the cursor to the next slot after the modification.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the size or the alignment is not satisfied,
+    /// or if the slot is not empty. An empty slot is a slot that [`Self::query`]
+    /// reports as [`VmItem::NotMapped`] with a length not smaller than the huge
+    /// page.
+    pub fn map_huge(&mut self, frames: USegment, prop: PageProperty) {
+        assert_eq!(frames.size(), super::HUGE_PAGE_SIZE);
+        // SAFETY: It is safe to map untyped memory into the userspace.
+        unsafe { self.pt_cursor.map_huge(frames.into(), prop) };
+    }
+
+    /// Split the huge page containing the current slot into base pages.
+    ///
+    /// The frames and the properties of the mapping are kept, so no TLB flush
+    /// is needed. The cursor is not moved.
+    ///
+    /// Returns `true` if a huge page is split.
+    pub fn split_huge(&mut self) -> bool {
+        self.pt_cursor.split_huge()
+    }
+
     /// Clear the mapping starting from the current slot.
     ///
     /// This method will bring the cursor forward by `len` bytes in the virtual
@@ -369,6 +399,15 @@ impl CursorMut<'_, '_> {
                     self.flusher
                         .issue_tlb_flush_with(TlbFlushOp::Address(va), page);
                 }
+                PageTableItem::MappedHuge { va, frames, .. } => {
+                    if !self.flusher.need_remote_flush() && tlb_prefer_flush_all {
+                        drop(frames);
+                        continue;
+                    }
+                    let range = va..va + frames.size();
+                    self.flusher
+                        .issue_tlb_flush_with_frames(TlbFlushOp::Range(range), frames);
+                }
                 PageTableItem::NotMapped { .. } => {
                     break;
                 }
@@ -479,6 +518,15 @@ pub enum VmItem {
         /// The property of the slot.
         prop: PageProperty,
     },
+    /// The current slot is mapped to a huge page.
+    MappedHuge {
+        /// The virtual address of the huge page.
+        va: Vaddr,
+        /// The frames of the huge page.
+        frames: USegment,
+        /// The property of the huge page.
+        prop: PageProperty,
+    },
 }
 
 impl TryFrom<PageTableItem> for VmItem {
@@ -494,6 +542,13 @@ impl TryFrom<PageTableItem> for VmItem {
                     .map_err(|_| "found typed memory mapped into `VmSpace`")?,
                 prop,
             }),
+            PageTableItem::MappedHuge { va, frames, prop } => Ok(VmItem::MappedHuge {
+                va,
+                frames: frames
+                    .try_into()
+                    .map_err(|_| "found typed memory mapped into `VmSpace`")?,
+                prop,
+            }),
             PageTableItem::MappedUntracked { .. } => {
                 Err("found untracked memory mapped into `VmSpace`")
             }
@@ -45,3 +45,8 @@ pub fn might_sleep() {
         );
     }
 }
+
+/// Returns whether the current code is running in atomic mode.
+pub(crate) fn is_atomic_mode() -> bool {
+    super::preempt::cpu_local::get_guard_count() != 0 || !crate::arch::irq::is_local_enabled()
+}
@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <linux/sched.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdint.h>
+#include <sys/epoll.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#ifndef CLONE_PIDFD
+#define CLONE_PIDFD 0x00001000
+#endif
+
+#ifndef PIDFD_NONBLOCK
+#define PIDFD_NONBLOCK O_NONBLOCK
+#endif
+
+static int sys_pidfd_open(pid_t pid, unsigned int flags)
+{
+	return syscall(SYS_pidfd_open, pid, flags);
+}
+
+static int sys_pidfd_send_signal(int pidfd, int sig, siginfo_t *info,
+				 unsigned int flags)
+{
+	return syscall(SYS_pidfd_send_signal, pidfd, sig, info, flags);
+}
+
+static pid_t sys_clone3(struct clone_args *args)
+{
+	return syscall(SYS_clone3, args, sizeof(struct clone_args));
+}
+
+static pid_t fork_paused_child(void)
+{
+	pid_t pid;
+
+	pid = CHECK(fork());
+	if (pid == 0) {
+		pause();
+		_exit(EXIT_FAILURE);
+	}
+
+	return pid;
+}
+
+FN_TEST(invalid_args)
+{
+	TEST_ERRNO(sys_pidfd_open(getpid(), 0xdead0000), EINVAL);
+	TEST_ERRNO(sys_pidfd_open(0, 0), EINVAL);
+	TEST_ERRNO(sys_pidfd_open(0x3fffffff, 0), ESRCH);
+
+	TEST_ERRNO(sys_pidfd_send_signal(STDIN_FILENO, SIGUSR1, NULL, 0),
+		   EBADF);
+}
+END_TEST()
+
+FN_TEST(open_self)
+{
+	int pidfd;
+
+	pidfd = TEST_SUCC(sys_pidfd_open(getpid(), PIDFD_NONBLOCK));
+	TEST_RES(fcntl(pidfd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(fcntl(pidfd, F_GETFL), _ret & O_NONBLOCK);
+
+	TEST_ERRNO(sys_pidfd_send_signal(pidfd, 0, NULL, 0xdead0000), EINVAL);
+	TEST_SUCC(sys_pidfd_send_signal(pidfd, 0, NULL, 0));
+
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
+
+FN_TEST(kill_and_poll)
+{
+	struct pollfd pfd = { .events = POLLIN };
+	int status;
+	pid_t pid;
+
+	pid = TEST_SUCC(fork_paused_child());
+	pfd.fd = TEST_SUCC(sys_pidfd_open(pid, 0));
+
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+
+	TEST_SUCC(sys_pidfd_send_signal(pfd.fd, SIGKILL, NULL, 0));
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1 && pfd.revents == POLLIN);
+
+	// The zombie process can be signaled until it is reaped
+	TEST_SUCC(sys_pidfd_send_signal(pfd.fd, SIGKILL, NULL, 0));
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+	TEST_ERRNO(sys_pidfd_send_signal(pfd.fd, SIGKILL, NULL, 0), ESRCH);
+
+	TEST_SUCC(close(pfd.fd));
+}
+END_TEST()
+
+FN_TEST(epoll)
+{
+	struct epoll_event ev = { .events = EPOLLIN };
+	int pidfd, epfd, status;
+	pid_t pid;
+
+	pid = TEST_SUCC(fork_paused_child());
+	pidfd = TEST_SUCC(sys_pidfd_open(pid, 0));
+
+	epfd = TEST_SUCC(epoll_create1(0));
+	ev.data.fd = pidfd;
+	TEST_SUCC(epoll_ctl(epfd, EPOLL_CTL_ADD, pidfd, &ev));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0), _ret == 0);
+
+	TEST_SUCC(kill(pid, SIGKILL));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 1000),
+		 _ret == 1 && ev.data.fd == pidfd && ev.events == EPOLLIN);
+
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid);
+	TEST_SUCC(close(epfd));
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
+
+FN_TEST(send_siginfo)
+{
+	siginfo_t info = { 0 };
+	int pidfd;
+	pid_t pid;
+
+	pid = TEST_SUCC(fork_paused_child());
+	pidfd = TEST_SUCC(sys_pidfd_open(pid, 0));
+
+	// The signal numbers must match
+	info.si_signo = SIGUSR1;
+	info.si_code = SI_QUEUE;
+	TEST_ERRNO(sys_pidfd_send_signal(pidfd, SIGKILL, &info, 0), EINVAL);
+
+	// Only the kernel can send signals with non-negative codes
+	info.si_signo = SIGKILL;
+	info.si_code = SI_KERNEL;
+	TEST_ERRNO(sys_pidfd_send_signal(pidfd, SIGKILL, &info, 0), EPERM);
+
+	info.si_code = SI_QUEUE;
+	TEST_SUCC(sys_pidfd_send_signal(pidfd, SIGKILL, &info, 0));
+
+	TEST_RES(waitpid(pid, NULL, 0), _ret == pid);
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
+
+FN_TEST(clone_pidfd)
+{
+	struct clone_args args = { 0 };
+	struct pollfd pfd = { .events = POLLIN };
+	int pidfd = -1;
+	pid_t pid;
+
+	args.flags = CLONE_PIDFD;
+	args.pidfd = (uintptr_t)&pidfd;
+	args.exit_signal = SIGCHLD;
+	pid = TEST_SUCC(sys_clone3(&args));
+	if (pid == 0) {
+		pause();
+		_exit(EXIT_FAILURE);
+	}
+
+	TEST_RES(pidfd, _ret >= 0);
+	TEST_RES(fcntl(pidfd, F_GETFD), _ret == FD_CLOEXEC);
+
+	pfd.fd = pidfd;
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+	TEST_SUCC(sys_pidfd_send_signal(pidfd, SIGKILL, NULL, 0));
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1 && pfd.revents == POLLIN);
+
+	TEST_RES(waitpid(pid, NULL, 0), _ret == pid);
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <elf.h>
+#include <fcntl.h>
+#include <pthread.h>
+#include <signal.h>
+#include <sys/mman.h>
+#include <sys/prctl.h>
+#include <sys/procfs.h>
+#include <sys/resource.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#ifndef PAGE_SIZE
+#define PAGE_SIZE 4096
+#endif
+
+#define CORE_PATTERN_PATH "/proc/sys/kernel/core_pattern"
+
+static const char core_pattern[] = "/tmp/core.%p";
+static char old_core_pattern[128];
+
+static volatile long marker = 0x12345678;
+
+static char *dont_dump_region;
+
+FN_SETUP(core_pattern)
+{
+	int fd;
+
+	fd = CHECK(open(CORE_PATTERN_PATH, O_RDWR));
+	CHECK(read(fd, old_core_pattern, sizeof(old_core_pattern) - 1));
+	CHECK(lseek(fd, 0, SEEK_SET));
+	CHECK(write(fd, core_pattern, strlen(core_pattern)));
+	CHECK(close(fd));
+
+	dont_dump_region = mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE,
+				MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	CHECK(dont_dump_region == MAP_FAILED ? -1 : 0);
+	CHECK(madvise(dont_dump_region, PAGE_SIZE, MADV_DONTDUMP));
+}
+END_SETUP()
+
+static pid_t spawn_crashing_child(rlim_t core_limit)
+{
+	struct rlimit rlimit = { .rlim_cur = core_limit,
+				 .rlim_max = RLIM_INFINITY };
+	pid_t pid = CHECK(fork());
+
+	if (pid == 0) {
+		if (setrlimit(RLIMIT_CORE, &rlimit) < 0)
+			_exit(EXIT_FAILURE);
+		marker = 0x87654321;
+		dont_dump_region[0] = 1;
+		abort();
+	}
+
+	return pid;
+}
+
+FN_TEST(dumpable)
+{
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 1);
+	TEST_SUCC(prctl(PR_SET_DUMPABLE, 0));
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 0);
+	TEST_SUCC(prctl(PR_SET_DUMPABLE, 1));
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 1);
+	TEST_ERRNO(prctl(PR_SET_DUMPABLE, 2), EINVAL);
+}
+END_TEST()
+
+FN_TEST(core_limit_zero)
+{
+	pid_t pid = spawn_crashing_child(0);
+	int status;
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGABRT && !WCOREDUMP(status));
+}
+END_TEST()
+
+FN_TEST(core_dump)
+{
+	pid_t pid = spawn_crashing_child(RLIM_INFINITY);
+	char path[64];
+	Elf64_Ehdr ehdr;
+	Elf64_Phdr phdr;
+	long value;
+	int status, fd, i;
+	int found_marker = 0, found_dont_dump = 0;
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGABRT && WCOREDUMP(status));
+
+	snprintf(path, sizeof(path), "/tmp/core.%d", pid);
+	fd = TEST_SUCC(open(path, O_RDONLY));
+
+	TEST_RES(pread(fd, &ehdr, sizeof(ehdr), 0),
+		 _ret == sizeof(ehdr) &&
+			 memcmp(ehdr.e_ident, ELFMAG, SELFMAG) == 0 &&
+			 ehdr.e_type == ET_CORE && ehdr.e_phnum > 1);
+
+	for (i = 0; i < ehdr.e_phnum; i++) {
+		TEST_RES(pread(fd, &phdr, sizeof(phdr),
+			       ehdr.e_phoff + i * sizeof(phdr)),
+			 _ret == sizeof(phdr));
+		if (i == 0) {
+			TEST_RES(phdr.p_type, _ret == PT_NOTE);
+			continue;
+		}
+		if (phdr.p_type != PT_LOAD)
+			continue;
+
+		if (phdr.p_vaddr <= (unsigned long)&marker &&
+		    (unsigned long)&marker < phdr.p_vaddr + phdr.p_memsz) {
+			found_marker = 1;
+			TEST_RES(pread(fd, &value, sizeof(value),
+				       phdr.p_offset +
+					       ((unsigned long)&marker -
+						phdr.p_vaddr)),
+				 _ret == sizeof(value) && value == 0x87654321);
+		}
+		if (phdr.p_vaddr == (unsigned long)dont_dump_region) {
+			found_dont_dump = 1;
+			TEST_RES(phdr.p_filesz, _ret == 0);
+		}
+	}
+	TEST_RES(found_marker && found_dont_dump, _ret);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(path));
+}
+END_TEST()
+
+#define NR_THREADS 3
+
+static volatile long counter = 0;
+
+static void *spin(void *arg)
+{
+	for (;;)
+		counter++;
+	return NULL;
+}
+
+static void *sleep_forever(void *arg)
+{
+	for (;;)
+		pause();
+	return NULL;
+}
+
+static pid_t spawn_crashing_threads(void)
+{
+	pid_t pid = CHECK(fork());
+	pthread_t thread;
+
+	if (pid == 0) {
+		if (pthread_create(&thread, NULL, spin, NULL) != 0 ||
+		    pthread_create(&thread, NULL, sleep_forever, NULL) != 0)
+			_exit(EXIT_FAILURE);
+		while (counter == 0)
+			sched_yield();
+		abort();
+	}
+
+	return pid;
+}
+
+FN_TEST(core_dump_threads)
+{
+	pid_t pid = spawn_crashing_threads();
+	char path[64];
+	char notes[4096];
+	Elf64_Ehdr ehdr;
+	Elf64_Phdr phdr;
+	Elf64_Nhdr *nhdr;
+	struct elf_prstatus *prstatus;
+	size_t offset;
+	int status, fd;
+	int nr_prstatus = 0, first_pid = 0;
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGABRT && WCOREDUMP(status));
+
+	snprintf(path, sizeof(path), "/tmp/core.%d", pid);
+	fd = TEST_SUCC(open(path, O_RDONLY));
+
+	TEST_RES(pread(fd, &ehdr, sizeof(ehdr), 0), _ret == sizeof(ehdr));
+	TEST_RES(pread(fd, &phdr, sizeof(phdr), ehdr.e_phoff),
+		 _ret == sizeof(phdr) && phdr.p_type == PT_NOTE &&
+			 phdr.p_filesz <= sizeof(notes));
+	TEST_RES(pread(fd, notes, phdr.p_filesz, phdr.p_offset),
+		 _ret == phdr.p_filesz);
+
+	for (offset = 0; offset + sizeof(*nhdr) <= phdr.p_filesz;) {
+		nhdr = (Elf64_Nhdr *)(notes + offset);
+		offset += sizeof(*nhdr) + ((nhdr->n_namesz + 3) & ~3);
+		if (nhdr->n_type == NT_PRSTATUS) {
+			prstatus = (struct elf_prstatus *)(notes + offset);
+			if (nr_prstatus == 0)
+				first_pid = prstatus->pr_pid;
+			nr_prstatus++;
+		}
+		offset += (nhdr->n_descsz + 3) & ~3;
+	}
+
+	// The thread that triggers the core dump comes first.
+	TEST_RES(nr_prstatus, _ret == NR_THREADS);
+	TEST_RES(first_pid, _ret == pid);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(path));
+}
+END_TEST()
+
+FN_SETUP(restore_core_pattern)
+{
+	int fd;
+
+	fd = CHECK(open(CORE_PATTERN_PATH, O_WRONLY));
+	CHECK(write(fd, old_core_pattern, strlen(old_core_pattern)));
+	CHECK(close(fd));
+}
+END_SETUP()
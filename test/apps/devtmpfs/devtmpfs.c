@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <string.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/sysmacros.h>
+#include <unistd.h>
+
+#define MOUNT_DIR "/tmp/devtmpfs"
+
+static struct stat st, st2;
+static char buf[4096];
+
+FN_TEST(device_numbers)
+{
+	TEST_RES(stat("/dev/null", &st), S_ISCHR(st.st_mode) &&
+						 major(st.st_rdev) == 1 &&
+						 minor(st.st_rdev) == 3);
+	TEST_RES(stat("/dev/zero", &st), S_ISCHR(st.st_mode) &&
+						 major(st.st_rdev) == 1 &&
+						 minor(st.st_rdev) == 5);
+	TEST_RES(stat("/dev/tty", &st), S_ISCHR(st.st_mode) &&
+						major(st.st_rdev) == 5 &&
+						minor(st.st_rdev) == 0);
+	TEST_RES(stat("/dev/console", &st), S_ISCHR(st.st_mode) &&
+						    major(st.st_rdev) == 5 &&
+						    minor(st.st_rdev) == 1);
+}
+END_TEST()
+
+FN_TEST(proc_devices)
+{
+	int fd;
+
+	memset(buf, 0, sizeof(buf));
+	fd = TEST_SUCC(open("/proc/devices", O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf) - 1), _ret > 0);
+	TEST_SUCC(close(fd));
+
+	TEST_RES(strncmp(buf, "Character devices:\n", 19), _ret == 0);
+	TEST_RES(strstr(buf, "\n  1 mem\n") != NULL, _ret);
+	TEST_RES(strstr(buf, "\nBlock devices:\n") != NULL, _ret);
+}
+END_TEST()
+
+FN_TEST(block_device)
+{
+	int fd;
+
+	TEST_RES(stat("/dev/vext2", &st), S_ISBLK(st.st_mode));
+
+	// The magic number of ext2 is at the offset 56 of the superblock,
+	// which starts at the offset 1024 of the device.
+	fd = TEST_SUCC(open("/dev/vext2", O_RDONLY));
+	TEST_ERRNO(read(fd, buf, 100), EINVAL);
+	TEST_RES(read(fd, buf, 2048),
+		 _ret == 2048 && buf[1024 + 56] == 0x53 &&
+			 (unsigned char)buf[1024 + 57] == 0xEF);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(mknod)
+{
+	TEST_SUCC(mknod("/tmp/devtmpfs_null", S_IFCHR | 0666, makedev(1, 3)));
+	TEST_RES(stat("/tmp/devtmpfs_null", &st),
+		 S_ISCHR(st.st_mode) && st.st_rdev == makedev(1, 3));
+	TEST_ERRNO(mknod("/tmp/devtmpfs_whiteout", S_IFCHR | 0666, 0), EPERM);
+	TEST_SUCC(unlink("/tmp/devtmpfs_null"));
+}
+END_TEST()
+
+FN_TEST(mount)
+{
+	TEST_SUCC(mkdir(MOUNT_DIR, 0755));
+	TEST_SUCC(mount("devtmpfs", MOUNT_DIR, "devtmpfs", 0, NULL));
+
+	// All the devtmpfs mounts show the same instance.
+	TEST_SUCC(stat("/dev/null", &st));
+	TEST_RES(stat(MOUNT_DIR "/null", &st2),
+		 st.st_ino == st2.st_ino && st.st_rdev == st2.st_rdev);
+
+	TEST_SUCC(mknod(MOUNT_DIR "/null2", S_IFCHR | 0666, makedev(1, 3)));
+	TEST_RES(stat("/dev/null2", &st), st.st_rdev == makedev(1, 3));
+	TEST_SUCC(unlink("/dev/null2"));
+
+	TEST_SUCC(umount(MOUNT_DIR));
+	TEST_SUCC(rmdir(MOUNT_DIR));
+}
+END_TEST()
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#define MOUNT_DIR "/tmp/ext4"
+
+#define BLK_SIZE 4096
+
+// Only the even blocks of the file are written, so each of them is mapped by
+// an extent. The extents do not fit in the inode, so the extent tree has leaf
+// nodes.
+#define NR_EXTENTS 1000
+
+// The entries do not fit in a single directory block.
+#define NR_FILES 200
+
+static struct stat st;
+static char buf[BLK_SIZE];
+static char buf2[BLK_SIZE];
+static char path[256];
+
+static void fill_block(int idx)
+{
+	memset(buf, idx % 256, BLK_SIZE);
+}
+
+// Returns the number of the written extents, or -1 on failure.
+static int write_extents(int fd)
+{
+	int i;
+
+	for (i = 0; i < NR_EXTENTS; i++) {
+		fill_block(i);
+		if (pwrite(fd, buf, BLK_SIZE, 2L * i * BLK_SIZE) != BLK_SIZE)
+			return -1;
+	}
+	return i;
+}
+
+// Returns the number of the extents whose blocks and the following holes are
+// read back correctly, or -1 on failure.
+static int check_extents(int fd, int nr_extents)
+{
+	int i;
+
+	for (i = 0; i < nr_extents; i++) {
+		fill_block(i);
+		if (pread(fd, buf2, BLK_SIZE, 2L * i * BLK_SIZE) != BLK_SIZE ||
+		    memcmp(buf, buf2, BLK_SIZE) != 0)
+			return -1;
+
+		// The file ends with an extent.
+		if (i == NR_EXTENTS - 1)
+			continue;
+		memset(buf, 0, BLK_SIZE);
+		if (pread(fd, buf2, BLK_SIZE, (2L * i + 1) * BLK_SIZE) !=
+			    BLK_SIZE ||
+		    memcmp(buf, buf2, BLK_SIZE) != 0)
+			return -1;
+	}
+	return i;
+}
+
+// Returns the number of the created files, or -1 on failure.
+static int create_files(void)
+{
+	int i, fd;
+
+	for (i = 0; i < NR_FILES; i++) {
+		sprintf(path, MOUNT_DIR "/dir/file_with_a_long_name_%d", i);
+		fd = open(path, O_RDWR | O_CREAT, 0644);
+		if (fd < 0)
+			return -1;
+		fill_block(i);
+		if (write(fd, buf, i) != i || close(fd) < 0)
+			return -1;
+	}
+	return i;
+}
+
+// Returns the number of the files that have the right sizes and are unlinked,
+// or -1 on failure.
+static int check_and_unlink_files(void)
+{
+	int i;
+
+	for (i = 0; i < NR_FILES; i++) {
+		sprintf(path, MOUNT_DIR "/dir/file_with_a_long_name_%d", i);
+		if (stat(path, &st) < 0 || st.st_size != i || unlink(path) < 0)
+			return -1;
+	}
+	return i;
+}
+
+FN_TEST(extents)
+{
+	int fd;
+	blkcnt_t blocks;
+
+	TEST_SUCC(mkdir(MOUNT_DIR, 0755));
+	TEST_SUCC(mount("vext4", MOUNT_DIR, "ext4", 0, NULL));
+
+	fd = TEST_SUCC(open(MOUNT_DIR "/sparse", O_RDWR | O_CREAT, 0644));
+	TEST_RES(write_extents(fd), _ret == NR_EXTENTS);
+	TEST_SUCC(fsync(fd));
+	TEST_SUCC(close(fd));
+
+	// The file is read from the device after the remount.
+	TEST_SUCC(umount(MOUNT_DIR));
+	TEST_SUCC(mount("vext4", MOUNT_DIR, "ext4", 0, NULL));
+
+	fd = TEST_SUCC(open(MOUNT_DIR "/sparse", O_RDWR));
+	TEST_RES(fstat(fd, &st),
+		 st.st_size == (2L * NR_EXTENTS - 1) * BLK_SIZE &&
+			 st.st_blocks >= NR_EXTENTS * (BLK_SIZE / 512));
+	blocks = st.st_blocks;
+	TEST_RES(check_extents(fd, NR_EXTENTS), _ret == NR_EXTENTS);
+
+	// Truncating the file frees half of the extents.
+	TEST_SUCC(ftruncate(fd, (long)NR_EXTENTS * BLK_SIZE));
+	TEST_RES(fstat(fd, &st), st.st_size == (long)NR_EXTENTS * BLK_SIZE &&
+					 st.st_blocks < blocks);
+	TEST_RES(check_extents(fd, NR_EXTENTS / 2), _ret == NR_EXTENTS / 2);
+	TEST_RES(pread(fd, buf2, BLK_SIZE, (long)NR_EXTENTS * BLK_SIZE),
+		 _ret == 0);
+	TEST_SUCC(close(fd));
+
+	TEST_SUCC(unlink(MOUNT_DIR "/sparse"));
+	TEST_SUCC(umount(MOUNT_DIR));
+	TEST_SUCC(rmdir(MOUNT_DIR));
+}
+END_TEST()
+
+FN_TEST(directory)
+{
+	TEST_SUCC(mkdir(MOUNT_DIR, 0755));
+	TEST_SUCC(mount("vext4", MOUNT_DIR, "ext4", 0, NULL));
+
+	TEST_SUCC(mkdir(MOUNT_DIR "/dir", 0755));
+	TEST_RES(create_files(), _ret == NR_FILES);
+	TEST_SUCC(mkdir(MOUNT_DIR "/dir/subdir", 0755));
+	TEST_RES(stat(MOUNT_DIR "/dir", &st),
+		 st.st_nlink == 3 && st.st_size > BLK_SIZE);
+	sync();
+
+	// The directory is read from the device after the remount.
+	TEST_SUCC(umount(MOUNT_DIR));
+	TEST_SUCC(mount("vext4", MOUNT_DIR, "ext4", 0, NULL));
+
+	TEST_RES(check_and_unlink_files(), _ret == NR_FILES);
+	TEST_SUCC(rmdir(MOUNT_DIR "/dir/subdir"));
+	TEST_SUCC(rmdir(MOUNT_DIR "/dir"));
+
+	TEST_SUCC(umount(MOUNT_DIR));
+	TEST_SUCC(rmdir(MOUNT_DIR));
+}
+END_TEST()
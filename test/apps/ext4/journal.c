@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <string.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#define JOURNAL_DIR "/tmp/journal"
+#define REPLAY_DIR "/tmp/replay"
+
+// The file is larger than the direct blocks, so it has an indirect block.
+#define FILE_SIZE (64 * 1024)
+
+static struct stat st;
+static char buf[FILE_SIZE];
+static char buf2[FILE_SIZE];
+
+// The journal of the image holds a committed transaction that renames
+// `/replay/old` to `/replay/new`. See `test/Makefile` for how it is created.
+FN_TEST(replay)
+{
+	int fd;
+
+	TEST_SUCC(mkdir(REPLAY_DIR, 0755));
+	TEST_SUCC(mount("vreplay", REPLAY_DIR, "ext4", 0, NULL));
+
+	TEST_RES(stat(REPLAY_DIR "/replay/new", &st),
+		 S_ISREG(st.st_mode) && st.st_nlink == 1);
+	TEST_ERRNO(stat(REPLAY_DIR "/replay/old", &st), ENOENT);
+
+	// The replayed directory can be updated.
+	fd = TEST_SUCC(open(REPLAY_DIR "/replay/file", O_RDWR | O_CREAT, 0644));
+	TEST_RES(write(fd, "replay", 6), _ret == 6);
+	TEST_SUCC(fsync(fd));
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(REPLAY_DIR "/replay/new"));
+
+	TEST_SUCC(umount(REPLAY_DIR));
+	TEST_SUCC(rmdir(REPLAY_DIR));
+}
+END_TEST()
+
+FN_TEST(write_and_sync)
+{
+	int fd, i;
+
+	for (i = 0; i < FILE_SIZE; i++)
+		buf[i] = i % 251;
+
+	TEST_SUCC(mkdir(JOURNAL_DIR, 0755));
+	TEST_SUCC(mount("vjournal", JOURNAL_DIR, "ext4", 0, NULL));
+
+	TEST_SUCC(mkdir(JOURNAL_DIR "/dir", 0755));
+	fd = TEST_SUCC(open(JOURNAL_DIR "/dir/file", O_RDWR | O_CREAT, 0644));
+	TEST_RES(write(fd, buf, FILE_SIZE), _ret == FILE_SIZE);
+	TEST_SUCC(fsync(fd));
+	TEST_SUCC(close(fd));
+
+	fd = TEST_SUCC(open(JOURNAL_DIR "/dir/tmp", O_RDWR | O_CREAT, 0644));
+	TEST_RES(write(fd, buf, FILE_SIZE), _ret == FILE_SIZE);
+	TEST_SUCC(close(fd));
+	TEST_SUCC(rename(JOURNAL_DIR "/dir/file", JOURNAL_DIR "/dir/renamed"));
+	TEST_SUCC(unlink(JOURNAL_DIR "/dir/tmp"));
+	sync();
+
+	// The files are read from the device after the remount.
+	TEST_SUCC(umount(JOURNAL_DIR));
+	TEST_SUCC(mount("vjournal", JOURNAL_DIR, "ext4", 0, NULL));
+
+	TEST_ERRNO(stat(JOURNAL_DIR "/dir/file", &st), ENOENT);
+	TEST_ERRNO(stat(JOURNAL_DIR "/dir/tmp", &st), ENOENT);
+	fd = TEST_SUCC(open(JOURNAL_DIR "/dir/renamed", O_RDONLY));
+	TEST_RES(read(fd, buf2, FILE_SIZE),
+		 _ret == FILE_SIZE && memcmp(buf, buf2, FILE_SIZE) == 0);
+	TEST_SUCC(close(fd));
+
+	TEST_SUCC(unlink(JOURNAL_DIR "/dir/renamed"));
+	TEST_SUCC(rmdir(JOURNAL_DIR "/dir"));
+
+	TEST_SUCC(umount(JOURNAL_DIR));
+	TEST_SUCC(rmdir(JOURNAL_DIR));
+}
+END_TEST()
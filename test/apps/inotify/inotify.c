@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <poll.h>
+#include <stdint.h>
+#include <sys/inotify.h>
+#include <sys/ioctl.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#define DIR_PATH "/tmp/inotify_test"
+#define FILE_PATH DIR_PATH "/file"
+#define FILE2_PATH DIR_PATH "/file2"
+#define SUBDIR_PATH DIR_PATH "/subdir"
+
+#define EVENT_SIZE ((int)sizeof(struct inotify_event))
+
+static int ifd;
+static int dir_wd;
+
+static char buf[4096]
+	__attribute__((aligned(__alignof__(struct inotify_event))));
+static size_t buf_len;
+static size_t buf_pos;
+static uint32_t last_cookie;
+
+static struct inotify_event *next_event(void)
+{
+	struct inotify_event *event;
+	ssize_t len;
+
+	if (buf_pos >= buf_len) {
+		len = read(ifd, buf, sizeof(buf));
+		if (len <= 0)
+			return NULL;
+		buf_len = len;
+		buf_pos = 0;
+	}
+
+	event = (struct inotify_event *)&buf[buf_pos];
+	buf_pos += sizeof(*event) + event->len;
+	last_cookie = event->cookie;
+
+	return event;
+}
+
+static int expect_event(int wd, uint32_t mask, const char *name)
+{
+	struct inotify_event *event;
+
+	event = next_event();
+	if (event == NULL)
+		return -1;
+
+	if (event->wd != wd || event->mask != mask)
+		return -1;
+	if (name == NULL && event->len != 0)
+		return -1;
+	if (name != NULL && strcmp(event->name, name) != 0)
+		return -1;
+
+	return 0;
+}
+
+static int expect_no_event(void)
+{
+	if (buf_pos < buf_len)
+		return -1;
+
+	return read(ifd, buf, sizeof(buf));
+}
+
+FN_SETUP(init)
+{
+	ifd = CHECK(inotify_init1(IN_NONBLOCK | IN_CLOEXEC));
+
+	CHECK(mkdir(DIR_PATH, 0755));
+	dir_wd = CHECK(inotify_add_watch(ifd, DIR_PATH, IN_ALL_EVENTS));
+}
+END_SETUP()
+
+FN_TEST(invalid_args)
+{
+	int fildes[2];
+
+	TEST_ERRNO(inotify_init1(0xdead0000), EINVAL);
+
+	TEST_ERRNO(inotify_add_watch(ifd, DIR_PATH, 0), EINVAL);
+	TEST_ERRNO(inotify_add_watch(ifd, DIR_PATH,
+				     IN_CREATE | IN_MASK_ADD | IN_MASK_CREATE),
+		   EINVAL);
+	TEST_ERRNO(inotify_add_watch(ifd, DIR_PATH, IN_CREATE | IN_MASK_CREATE),
+		   EEXIST);
+	TEST_ERRNO(inotify_add_watch(ifd, DIR_PATH "/none", IN_CREATE), ENOENT);
+	TEST_ERRNO(inotify_add_watch(ifd, "/proc/self/cmdline",
+				     IN_CREATE | IN_ONLYDIR),
+		   ENOTDIR);
+	TEST_ERRNO(inotify_rm_watch(ifd, 12345), EINVAL);
+
+	TEST_SUCC(pipe(fildes));
+	TEST_ERRNO(inotify_add_watch(fildes[0], DIR_PATH, IN_CREATE), EINVAL);
+	TEST_ERRNO(inotify_rm_watch(fildes[0], dir_wd), EINVAL);
+	TEST_SUCC(close(fildes[0]));
+	TEST_SUCC(close(fildes[1]));
+
+	TEST_ERRNO(write(ifd, buf, 1), EINVAL);
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(modify_watch)
+{
+	TEST_RES(inotify_add_watch(ifd, DIR_PATH, IN_CREATE), _ret == dir_wd);
+	TEST_RES(inotify_add_watch(ifd, DIR_PATH, IN_ALL_EVENTS | IN_MASK_ADD),
+		 _ret == dir_wd);
+}
+END_TEST()
+
+FN_TEST(create_write_close)
+{
+	int fd;
+
+	fd = TEST_SUCC(open(FILE_PATH, O_CREAT | O_WRONLY, 0644));
+	TEST_RES(expect_event(dir_wd, IN_CREATE, "file"), _ret == 0);
+	TEST_RES(expect_event(dir_wd, IN_OPEN, "file"), _ret == 0);
+
+	TEST_RES(write(fd, "hello", 5), _ret == 5);
+	TEST_RES(expect_event(dir_wd, IN_MODIFY, "file"), _ret == 0);
+
+	TEST_SUCC(close(fd));
+	TEST_RES(expect_event(dir_wd, IN_CLOSE_WRITE, "file"), _ret == 0);
+
+	fd = TEST_SUCC(open(FILE_PATH, O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf)), _ret == 5);
+	TEST_SUCC(close(fd));
+	TEST_RES(expect_event(dir_wd, IN_OPEN, "file"), _ret == 0);
+	TEST_RES(expect_event(dir_wd, IN_ACCESS, "file"), _ret == 0);
+	TEST_RES(expect_event(dir_wd, IN_CLOSE_NOWRITE, "file"), _ret == 0);
+
+	TEST_SUCC(truncate(FILE_PATH, 1));
+	TEST_RES(expect_event(dir_wd, IN_MODIFY, "file"), _ret == 0);
+
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(attrib)
+{
+	int len;
+
+	TEST_SUCC(chmod(FILE_PATH, 0600));
+
+	// The name is padded with null bytes to the size of the event
+	TEST_RES(ioctl(ifd, FIONREAD, &len), len == 2 * EVENT_SIZE);
+	TEST_RES(expect_event(dir_wd, IN_ATTRIB, "file"), _ret == 0);
+
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(small_buffer)
+{
+	char small_buf[EVENT_SIZE];
+
+	TEST_SUCC(chmod(FILE_PATH, 0644));
+	TEST_ERRNO(read(ifd, small_buf, sizeof(small_buf)), EINVAL);
+	TEST_RES(expect_event(dir_wd, IN_ATTRIB, "file"), _ret == 0);
+
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(rename)
+{
+	uint32_t cookie;
+
+	TEST_SUCC(rename(FILE_PATH, FILE2_PATH));
+	TEST_RES(expect_event(dir_wd, IN_MOVED_FROM, "file"), _ret == 0);
+	cookie = last_cookie;
+	TEST_RES(expect_event(dir_wd, IN_MOVED_TO, "file2"),
+		 _ret == 0 && last_cookie == cookie && cookie != 0);
+
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(unlink)
+{
+	int file_wd;
+
+	file_wd = TEST_SUCC(inotify_add_watch(ifd, FILE2_PATH,
+					      IN_ATTRIB | IN_DELETE_SELF));
+	TEST_RES(file_wd, _ret != dir_wd);
+
+	TEST_SUCC(unlink(FILE2_PATH));
+	TEST_RES(expect_event(file_wd, IN_ATTRIB, NULL), _ret == 0);
+	TEST_RES(expect_event(dir_wd, IN_DELETE, "file2"), _ret == 0);
+	TEST_RES(expect_event(file_wd, IN_DELETE_SELF, NULL), _ret == 0);
+	TEST_RES(expect_event(file_wd, IN_IGNORED, NULL), _ret == 0);
+	TEST_ERRNO(inotify_rm_watch(ifd, file_wd), EINVAL);
+
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(mkdir_rmdir)
+{
+	TEST_SUCC(mkdir(SUBDIR_PATH, 0755));
+	TEST_RES(expect_event(dir_wd, IN_CREATE | IN_ISDIR, "subdir"),
+		 _ret == 0);
+
+	TEST_SUCC(rmdir(SUBDIR_PATH));
+	TEST_RES(expect_event(dir_wd, IN_DELETE | IN_ISDIR, "subdir"),
+		 _ret == 0);
+
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(poll)
+{
+	struct pollfd pfd = { .fd = ifd, .events = POLLIN };
+
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+
+	TEST_SUCC(mkdir(SUBDIR_PATH, 0755));
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLIN);
+	TEST_RES(expect_event(dir_wd, IN_CREATE | IN_ISDIR, "subdir"),
+		 _ret == 0);
+
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(oneshot)
+{
+	int sub_wd;
+
+	sub_wd = TEST_SUCC(
+		inotify_add_watch(ifd, SUBDIR_PATH, IN_CREATE | IN_ONESHOT));
+
+	TEST_SUCC(mkdir(SUBDIR_PATH "/a", 0755));
+	TEST_RES(expect_event(sub_wd, IN_CREATE | IN_ISDIR, "a"), _ret == 0);
+	TEST_RES(expect_event(sub_wd, IN_IGNORED, NULL), _ret == 0);
+
+	TEST_SUCC(rmdir(SUBDIR_PATH "/a"));
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(rm_watch)
+{
+	int sub_wd;
+
+	sub_wd = TEST_SUCC(inotify_add_watch(ifd, SUBDIR_PATH, IN_CREATE));
+
+	TEST_SUCC(inotify_rm_watch(ifd, sub_wd));
+	TEST_RES(expect_event(sub_wd, IN_IGNORED, NULL), _ret == 0);
+	TEST_ERRNO(inotify_rm_watch(ifd, sub_wd), EINVAL);
+
+	TEST_SUCC(mkdir(SUBDIR_PATH "/a", 0755));
+	TEST_SUCC(rmdir(SUBDIR_PATH "/a"));
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(overflow)
+{
+	struct inotify_event *event;
+	int fd, sub_wd, i, num_events;
+
+	TEST_SUCC(rmdir(SUBDIR_PATH));
+	TEST_RES(expect_event(dir_wd, IN_DELETE | IN_ISDIR, "subdir"),
+		 _ret == 0);
+	TEST_SUCC(inotify_rm_watch(ifd, dir_wd));
+	TEST_RES(expect_event(dir_wd, IN_IGNORED, NULL), _ret == 0);
+
+	fd = TEST_SUCC(open(FILE_PATH, O_CREAT | O_RDWR, 0644));
+	sub_wd = TEST_SUCC(
+		inotify_add_watch(ifd, FILE_PATH, IN_ACCESS | IN_MODIFY));
+
+	// Identical consecutive events are merged, so alternate between them
+	for (i = 0; i < 10000; i++) {
+		CHECK_WITH(pwrite(fd, "a", 1, 0), _ret == 1);
+		CHECK_WITH(pread(fd, buf, 1, 0), _ret == 1);
+	}
+
+	num_events = 0;
+	while ((event = next_event()) != NULL) {
+		num_events++;
+		if (event->mask == IN_Q_OVERFLOW)
+			break;
+	}
+	TEST_RES(num_events, _ret == 16384 + 1);
+	TEST_RES(event != NULL ? event->wd : 0, _ret == -1);
+	TEST_ERRNO(expect_no_event(), EAGAIN);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(inotify_rm_watch(ifd, sub_wd));
+	TEST_RES(expect_event(sub_wd, IN_IGNORED, NULL), _ret == 0);
+	TEST_SUCC(unlink(FILE_PATH));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(ifd));
+	CHECK(rmdir(DIR_PATH));
+}
+END_SETUP()
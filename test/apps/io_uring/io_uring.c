@@ -0,0 +1,573 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <linux/io_uring.h>
+#include <poll.h>
+#include <stdatomic.h>
+#include <sys/mman.h>
+#include <sys/syscall.h>
+#include <sys/uio.h>
+#include <time.h>
+#include <unistd.h>
+
+static int io_uring_setup(unsigned int entries, struct io_uring_params *p)
+{
+	return syscall(SYS_io_uring_setup, entries, p);
+}
+
+static int io_uring_enter(int fd, unsigned int to_submit,
+			  unsigned int min_complete, unsigned int flags)
+{
+	return syscall(SYS_io_uring_enter, fd, to_submit, min_complete, flags,
+		       NULL, 0);
+}
+
+static int io_uring_register(int fd, unsigned int opcode, void *arg,
+			     unsigned int nr_args)
+{
+	return syscall(SYS_io_uring_register, fd, opcode, arg, nr_args);
+}
+
+struct ring {
+	int fd;
+	struct io_uring_params params;
+	void *rings;
+	struct io_uring_sqe *sqes;
+	unsigned int *sq_tail;
+	unsigned int *sq_flags;
+	unsigned int *sq_array;
+	unsigned int *cq_head;
+	unsigned int *cq_tail;
+	struct io_uring_cqe *cqes;
+};
+
+static int ring_init(struct ring *ring, unsigned int entries,
+		     unsigned int flags, unsigned int sq_thread_idle)
+{
+	struct io_uring_params *p = &ring->params;
+	size_t rings_size;
+
+	memset(p, 0, sizeof(*p));
+	p->flags = flags;
+	p->sq_thread_idle = sq_thread_idle;
+
+	ring->fd = io_uring_setup(entries, p);
+	if (ring->fd < 0)
+		return -1;
+
+	rings_size = p->sq_off.array + p->sq_entries * sizeof(unsigned int);
+	ring->rings = mmap(NULL, rings_size, PROT_READ | PROT_WRITE,
+			   MAP_SHARED | MAP_POPULATE, ring->fd,
+			   IORING_OFF_SQ_RING);
+	if (ring->rings == MAP_FAILED)
+		return -1;
+
+	ring->sqes = mmap(NULL, p->sq_entries * sizeof(struct io_uring_sqe),
+			  PROT_READ | PROT_WRITE, MAP_SHARED | MAP_POPULATE,
+			  ring->fd, IORING_OFF_SQES);
+	if (ring->sqes == MAP_FAILED)
+		return -1;
+
+	ring->sq_tail = ring->rings + p->sq_off.tail;
+	ring->sq_flags = ring->rings + p->sq_off.flags;
+	ring->sq_array = ring->rings + p->sq_off.array;
+	ring->cq_head = ring->rings + p->cq_off.head;
+	ring->cq_tail = ring->rings + p->cq_off.tail;
+	ring->cqes = ring->rings + p->cq_off.cqes;
+
+	return 0;
+}
+
+static struct io_uring_sqe *ring_get_sqe(struct ring *ring)
+{
+	unsigned int tail = *ring->sq_tail;
+	unsigned int index = tail & (ring->params.sq_entries - 1);
+	struct io_uring_sqe *sqe = &ring->sqes[index];
+
+	memset(sqe, 0, sizeof(*sqe));
+	ring->sq_array[index] = index;
+	atomic_store_explicit((_Atomic unsigned int *)ring->sq_tail, tail + 1,
+			      memory_order_release);
+
+	return sqe;
+}
+
+// Returns the result of the next CQE, or -1 if there are no CQEs.
+static int ring_pop_cqe(struct ring *ring, unsigned long long *user_data)
+{
+	unsigned int head = *ring->cq_head;
+	unsigned int tail = atomic_load_explicit(
+		(_Atomic unsigned int *)ring->cq_tail, memory_order_acquire);
+	struct io_uring_cqe *cqe;
+	int res;
+
+	if (head == tail)
+		return -1;
+
+	cqe = &ring->cqes[head & (ring->params.cq_entries - 1)];
+	*user_data = cqe->user_data;
+	res = cqe->res;
+	atomic_store_explicit((_Atomic unsigned int *)ring->cq_head, head + 1,
+			      memory_order_release);
+
+	return res;
+}
+
+static struct ring ring;
+static int rfd, wfd;
+static unsigned long long user_data;
+
+FN_SETUP(init)
+{
+	int fildes[2];
+
+	CHECK(ring_init(&ring, 8, 0, 0));
+
+	CHECK(pipe(fildes));
+	rfd = fildes[0];
+	wfd = fildes[1];
+}
+END_SETUP()
+
+FN_TEST(setup_invalid)
+{
+	struct io_uring_params p;
+
+	memset(&p, 0, sizeof(p));
+	TEST_ERRNO(io_uring_setup(0, &p), EINVAL);
+	TEST_ERRNO(io_uring_setup(65536, &p), EINVAL);
+
+	p.flags = IORING_SETUP_CQSIZE;
+	p.cq_entries = 2;
+	TEST_ERRNO(io_uring_setup(4, &p), EINVAL);
+
+	memset(&p, 0, sizeof(p));
+	p.flags = IORING_SETUP_SQ_AFF;
+	TEST_ERRNO(io_uring_setup(4, &p), EINVAL);
+	p.flags = IORING_SETUP_SQPOLL | IORING_SETUP_SQ_AFF;
+	p.sq_thread_cpu = 4096;
+	TEST_ERRNO(io_uring_setup(4, &p), EINVAL);
+}
+END_TEST()
+
+FN_TEST(setup_params)
+{
+	TEST_RES(ring.params.sq_entries, _ret == 8);
+	TEST_RES(ring.params.cq_entries, _ret == 16);
+	TEST_RES(ring.params.features & IORING_FEAT_SINGLE_MMAP, _ret != 0);
+	TEST_RES(ring.params.features & IORING_FEAT_NODROP, _ret != 0);
+}
+END_TEST()
+
+FN_TEST(nop)
+{
+	struct io_uring_sqe *sqe;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_NOP;
+	sqe->user_data = 1;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_NOP;
+	sqe->flags = IOSQE_CQE_SKIP_SUCCESS;
+	sqe->user_data = 2;
+
+	TEST_RES(io_uring_enter(ring.fd, 2, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == 0 && user_data == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == -1);
+}
+END_TEST()
+
+FN_TEST(invalid_opcode)
+{
+	struct io_uring_sqe *sqe;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = 0xff;
+	sqe->user_data = 3;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == -EINVAL && user_data == 3);
+}
+END_TEST()
+
+FN_TEST(read_write)
+{
+	struct io_uring_sqe *sqe;
+	char buf[6] = { 0 };
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_WRITE;
+	sqe->fd = wfd;
+	sqe->addr = (unsigned long)"hello";
+	sqe->len = 5;
+	sqe->off = -1;
+	sqe->user_data = 4;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == 5 && user_data == 4);
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_READ;
+	sqe->fd = rfd;
+	sqe->addr = (unsigned long)buf;
+	sqe->len = 5;
+	sqe->off = -1;
+	sqe->user_data = 5;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == 5 && user_data == 5 && strcmp(buf, "hello") == 0);
+}
+END_TEST()
+
+FN_TEST(readv_writev)
+{
+	struct io_uring_sqe *sqe;
+	char buf1[3] = { 0 }, buf2[4] = { 0 };
+	struct iovec wiov[2] = {
+		{ .iov_base = "ab", .iov_len = 2 },
+		{ .iov_base = "cde", .iov_len = 3 },
+	};
+	struct iovec riov[2] = {
+		{ .iov_base = buf1, .iov_len = 2 },
+		{ .iov_base = buf2, .iov_len = 3 },
+	};
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_WRITEV;
+	sqe->fd = wfd;
+	sqe->addr = (unsigned long)wiov;
+	sqe->len = 2;
+	sqe->user_data = 6;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == 5 && user_data == 6);
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_READV;
+	sqe->fd = rfd;
+	sqe->addr = (unsigned long)riov;
+	sqe->len = 2;
+	sqe->user_data = 7;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == 5 && user_data == 7 && strcmp(buf1, "ab") == 0 &&
+			 strcmp(buf2, "cde") == 0);
+}
+END_TEST()
+
+FN_TEST(poll_add)
+{
+	struct io_uring_sqe *sqe;
+	char buf[1];
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_POLL_ADD;
+	sqe->fd = rfd;
+	sqe->poll32_events = POLLIN;
+	sqe->user_data = 8;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 0, 0), _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == -1);
+
+	TEST_RES(write(wfd, "x", 1), _ret == 1);
+	TEST_RES(io_uring_enter(ring.fd, 0, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 0);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == POLLIN && user_data == 8);
+
+	TEST_RES(read(rfd, buf, 1), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(poll_remove)
+{
+	struct io_uring_sqe *sqe;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_POLL_ADD;
+	sqe->fd = rfd;
+	sqe->poll32_events = POLLIN;
+	sqe->user_data = 9;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_POLL_REMOVE;
+	sqe->addr = 9;
+	sqe->user_data = 10;
+
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == -ECANCELED && user_data == 9);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == 0 && user_data == 10);
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_POLL_REMOVE;
+	sqe->addr = 9;
+	sqe->user_data = 11;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == -ENOENT && user_data == 11);
+}
+END_TEST()
+
+FN_TEST(timeout)
+{
+	struct io_uring_sqe *sqe;
+	struct __kernel_timespec ts = { .tv_sec = 0, .tv_nsec = 10000000 };
+	struct __kernel_timespec long_ts = { .tv_sec = 100, .tv_nsec = 0 };
+
+	// The timer expires
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_TIMEOUT;
+	sqe->addr = (unsigned long)&ts;
+	sqe->len = 1;
+	sqe->user_data = 12;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == -ETIME && user_data == 12);
+
+	// Another request completes
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_TIMEOUT;
+	sqe->addr = (unsigned long)&long_ts;
+	sqe->len = 1;
+	sqe->off = 1;
+	sqe->user_data = 13;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_NOP;
+	sqe->user_data = 14;
+
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == 0 && user_data == 14);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == 0 && user_data == 13);
+
+	// The timeout is removed
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_TIMEOUT;
+	sqe->addr = (unsigned long)&long_ts;
+	sqe->len = 1;
+	sqe->user_data = 15;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_TIMEOUT_REMOVE;
+	sqe->addr = 15;
+	sqe->user_data = 16;
+
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == -ECANCELED && user_data == 15);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == 0 && user_data == 16);
+}
+END_TEST()
+
+FN_TEST(link)
+{
+	struct io_uring_sqe *sqe;
+	char buf[4] = { 0 };
+
+	// Successful chain
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_WRITE;
+	sqe->flags = IOSQE_IO_LINK;
+	sqe->fd = wfd;
+	sqe->addr = (unsigned long)"abc";
+	sqe->len = 3;
+	sqe->user_data = 17;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_READ;
+	sqe->fd = rfd;
+	sqe->addr = (unsigned long)buf;
+	sqe->len = 3;
+	sqe->user_data = 18;
+
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret == 3 && user_data == 17);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == 3 && user_data == 18 && strcmp(buf, "abc") == 0);
+
+	// Failed chain
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_READ;
+	sqe->flags = IOSQE_IO_LINK;
+	sqe->fd = wfd;
+	sqe->addr = (unsigned long)buf;
+	sqe->len = 3;
+	sqe->user_data = 19;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_NOP;
+	sqe->user_data = 20;
+
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(ring_pop_cqe(&ring, &user_data), _ret < 0 && user_data == 19);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == -ECANCELED && user_data == 20);
+}
+END_TEST()
+
+FN_TEST(register_files)
+{
+	struct io_uring_sqe *sqe;
+	int fds[2] = { -1, rfd };
+	char buf[2] = { 0 };
+
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_UNREGISTER_FILES, NULL, 0),
+		   ENXIO);
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_FILES, fds, 2));
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_REGISTER_FILES, fds, 2),
+		   EBUSY);
+
+	TEST_RES(write(wfd, "y", 1), _ret == 1);
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_READ;
+	sqe->flags = IOSQE_FIXED_FILE;
+	sqe->fd = 0;
+	sqe->addr = (unsigned long)buf;
+	sqe->len = 1;
+	sqe->user_data = 21;
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_READ;
+	sqe->flags = IOSQE_FIXED_FILE;
+	sqe->fd = 1;
+	sqe->addr = (unsigned long)buf;
+	sqe->len = 1;
+	sqe->user_data = 22;
+
+	// The submission stops at the SQE that fails to be prepared
+	TEST_RES(io_uring_enter(ring.fd, 2, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == -EBADF && user_data == 21);
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == 1 && user_data == 22 && buf[0] == 'y');
+
+	TEST_SUCC(io_uring_register(ring.fd, IORING_UNREGISTER_FILES, NULL, 0));
+}
+END_TEST()
+
+FN_TEST(register_buffers)
+{
+	struct io_uring_sqe *sqe;
+	char buf[4] = { 0 };
+	struct iovec iov = { .iov_base = buf, .iov_len = 2 };
+
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_BUFFERS, &iov, 1));
+
+	sqe = ring_get_sqe(&ring);
+	sqe->opcode = IORING_OP_READ_FIXED;
+	sqe->fd = rfd;
+	sqe->addr = (unsigned long)buf;
+	sqe->len = 4;
+	sqe->buf_index = 0;
+	sqe->user_data = 23;
+
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&ring, &user_data),
+		 _ret == -EFAULT && user_data == 23);
+
+	TEST_SUCC(io_uring_register(ring.fd, IORING_UNREGISTER_BUFFERS, NULL,
+				    0));
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_UNREGISTER_BUFFERS, NULL,
+				     0),
+		   ENXIO);
+}
+END_TEST()
+
+FN_TEST(register_probe)
+{
+	struct {
+		struct io_uring_probe probe;
+		struct io_uring_probe_op ops[256];
+	} probe;
+
+	memset(&probe, 0, sizeof(probe));
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_PROBE, &probe,
+				    256));
+	TEST_RES(probe.probe.ops_len, _ret == probe.probe.last_op + 1);
+	TEST_RES(probe.ops[IORING_OP_READV].flags & IO_URING_OP_SUPPORTED,
+		 _ret != 0);
+	TEST_RES(probe.ops[IORING_OP_POLL_ADD].flags & IO_URING_OP_SUPPORTED,
+		 _ret != 0);
+}
+END_TEST()
+
+FN_TEST(sqpoll)
+{
+	struct ring sq_ring;
+	struct io_uring_sqe *sqe;
+	int i;
+
+	// The SQ polling thread is bound to CPU 0.
+	TEST_SUCC(ring_init(&sq_ring, 4,
+			    IORING_SETUP_SQPOLL | IORING_SETUP_SQ_AFF, 10));
+
+	// The SQ polling thread submits the SQE without `io_uring_enter`
+	sqe = ring_get_sqe(&sq_ring);
+	sqe->opcode = IORING_OP_NOP;
+	sqe->user_data = 24;
+
+	TEST_RES(io_uring_enter(sq_ring.fd, 0, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 0);
+	TEST_RES(ring_pop_cqe(&sq_ring, &user_data),
+		 _ret == 0 && user_data == 24);
+
+	// The SQ polling thread sleeps after being idle
+	for (i = 0; i < 100; ++i) {
+		if (atomic_load((_Atomic unsigned int *)sq_ring.sq_flags) &
+		    IORING_SQ_NEED_WAKEUP)
+			break;
+		usleep(10000);
+	}
+	TEST_RES(*sq_ring.sq_flags & IORING_SQ_NEED_WAKEUP, _ret != 0);
+
+	sqe = ring_get_sqe(&sq_ring);
+	sqe->opcode = IORING_OP_NOP;
+	sqe->user_data = 25;
+
+	TEST_RES(io_uring_enter(sq_ring.fd, 1, 1,
+				IORING_ENTER_SQ_WAKEUP |
+					IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_RES(ring_pop_cqe(&sq_ring, &user_data),
+		 _ret == 0 && user_data == 25);
+
+	TEST_SUCC(close(sq_ring.fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(ring.fd));
+	CHECK(close(rfd));
+	CHECK(close(wfd));
+}
+END_SETUP()
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <poll.h>
+#include <stdint.h>
+#include <sys/epoll.h>
+#include <sys/timerfd.h>
+#include <time.h>
+#include <unistd.h>
+
+#define MSEC_TO_NSEC(ms) ((ms) * 1000 * 1000)
+
+static int tfd;
+
+FN_SETUP(init)
+{
+	tfd = CHECK(timerfd_create(CLOCK_MONOTONIC, TFD_NONBLOCK | TFD_CLOEXEC));
+}
+END_SETUP()
+
+FN_TEST(invalid_args)
+{
+	struct itimerspec its = { 0 };
+	uint32_t small_buf;
+	int fildes[2];
+
+	TEST_ERRNO(timerfd_create(CLOCK_MONOTONIC, 0xdead0000), EINVAL);
+	TEST_ERRNO(timerfd_create(CLOCK_PROCESS_CPUTIME_ID, 0), EINVAL);
+	TEST_ERRNO(timerfd_settime(tfd, 0xdead0000, &its, NULL), EINVAL);
+
+	TEST_SUCC(pipe(fildes));
+	TEST_ERRNO(timerfd_settime(fildes[0], 0, &its, NULL), EINVAL);
+	TEST_ERRNO(timerfd_gettime(fildes[0], &its), EINVAL);
+	TEST_SUCC(close(fildes[0]));
+	TEST_SUCC(close(fildes[1]));
+
+	TEST_ERRNO(read(tfd, &small_buf, sizeof(small_buf)), EINVAL);
+}
+END_TEST()
+
+FN_TEST(disarmed)
+{
+	struct itimerspec its;
+	uint64_t ticks;
+
+	TEST_RES(timerfd_gettime(tfd, &its),
+		 its.it_value.tv_sec == 0 && its.it_value.tv_nsec == 0 &&
+			 its.it_interval.tv_sec == 0 &&
+			 its.it_interval.tv_nsec == 0);
+	TEST_ERRNO(read(tfd, &ticks, sizeof(ticks)), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(oneshot)
+{
+	struct itimerspec its = { .it_value.tv_nsec = MSEC_TO_NSEC(10) };
+	struct pollfd pfd = { .fd = tfd, .events = POLLIN };
+	uint64_t ticks;
+
+	TEST_SUCC(timerfd_settime(tfd, 0, &its, NULL));
+	TEST_RES(timerfd_gettime(tfd, &its),
+		 its.it_value.tv_sec == 0 &&
+			 its.it_value.tv_nsec <= MSEC_TO_NSEC(10) &&
+			 its.it_interval.tv_nsec == 0);
+
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1 && pfd.revents == POLLIN);
+	TEST_RES(read(tfd, &ticks, sizeof(ticks)),
+		 _ret == sizeof(ticks) && ticks == 1);
+	TEST_ERRNO(read(tfd, &ticks, sizeof(ticks)), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(periodic)
+{
+	struct itimerspec its = {
+		.it_value.tv_nsec = MSEC_TO_NSEC(10),
+		.it_interval.tv_nsec = MSEC_TO_NSEC(10),
+	};
+	struct itimerspec old_its;
+	uint64_t ticks;
+
+	TEST_SUCC(timerfd_settime(tfd, 0, &its, NULL));
+	TEST_SUCC(usleep(50 * 1000));
+
+	TEST_RES(read(tfd, &ticks, sizeof(ticks)),
+		 _ret == sizeof(ticks) && ticks >= 2);
+
+	// Disarm the timer
+	memset(&its, 0, sizeof(its));
+	TEST_RES(timerfd_settime(tfd, 0, &its, &old_its),
+		 old_its.it_interval.tv_sec == 0 &&
+			 old_its.it_interval.tv_nsec == MSEC_TO_NSEC(10));
+	TEST_SUCC(usleep(20 * 1000));
+	TEST_ERRNO(read(tfd, &ticks, sizeof(ticks)), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(abstime)
+{
+	struct itimerspec its = { 0 };
+	uint64_t ticks;
+
+	// An absolute time in the past expires immediately
+	its.it_value.tv_nsec = 1;
+	TEST_SUCC(timerfd_settime(tfd, TFD_TIMER_ABSTIME, &its, NULL));
+	TEST_SUCC(usleep(10 * 1000));
+	TEST_RES(read(tfd, &ticks, sizeof(ticks)),
+		 _ret == sizeof(ticks) && ticks == 1);
+}
+END_TEST()
+
+FN_TEST(blocking_read)
+{
+	struct itimerspec its = { .it_value.tv_nsec = MSEC_TO_NSEC(10) };
+	uint64_t ticks;
+	int fd;
+
+	fd = TEST_SUCC(timerfd_create(CLOCK_REALTIME, 0));
+	TEST_SUCC(timerfd_settime(fd, 0, &its, NULL));
+	TEST_RES(read(fd, &ticks, sizeof(ticks)),
+		 _ret == sizeof(ticks) && ticks == 1);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(epoll)
+{
+	struct itimerspec its = { .it_value.tv_nsec = MSEC_TO_NSEC(10) };
+	struct epoll_event ev = { .events = EPOLLIN, .data.fd = tfd };
+	uint64_t ticks;
+	int epfd;
+
+	epfd = TEST_SUCC(epoll_create1(0));
+	TEST_SUCC(epoll_ctl(epfd, EPOLL_CTL_ADD, tfd, &ev));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0), _ret == 0);
+
+	TEST_SUCC(timerfd_settime(tfd, 0, &its, NULL));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 1000),
+		 _ret == 1 && ev.data.fd == tfd && ev.events == EPOLLIN);
+
+	TEST_RES(read(tfd, &ticks, sizeof(ticks)), _ret == sizeof(ticks));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0), _ret == 0);
+
+	TEST_SUCC(close(epfd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(tfd));
+}
+END_SETUP()
@@ -0,0 +1,329 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#ifndef MADV_COLD
+#define MADV_COLD 20
+#endif
+
+#ifndef MADV_PAGEOUT
+#define MADV_PAGEOUT 21
+#endif
+
+#ifndef MADV_POPULATE_READ
+#define MADV_POPULATE_READ 22
+#endif
+
+#ifndef MADV_POPULATE_WRITE
+#define MADV_POPULATE_WRITE 23
+#endif
+
+#ifndef MADV_DONTNEED_LOCKED
+#define MADV_DONTNEED_LOCKED 24
+#endif
+
+static long page_size;
+
+static char *map_anon(size_t len, int prot, int flags)
+{
+	return mmap(NULL, len, prot, flags | MAP_ANONYMOUS, -1, 0);
+}
+
+// Creates a memfd of `len` bytes that is filled with `c`
+static int new_memfd(size_t len, char c)
+{
+	char buf[len];
+	int fd;
+
+	fd = memfd_create("madvise", 0);
+	if (fd < 0)
+		return -1;
+	memset(buf, c, len);
+	if (write(fd, buf, len) != (ssize_t)len) {
+		close(fd);
+		return -1;
+	}
+	return fd;
+}
+
+FN_SETUP(init)
+{
+	page_size = CHECK(sysconf(_SC_PAGESIZE));
+}
+END_SETUP()
+
+FN_TEST(args)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(2 * page_size, PROT_READ | PROT_WRITE,
+			       MAP_PRIVATE));
+	TEST_SUCC(munmap(addr + page_size, page_size));
+
+	TEST_ERRNO(madvise(addr + 1, page_size, MADV_DONTNEED), EINVAL);
+	TEST_ERRNO(madvise(addr, page_size, 99), EINVAL);
+	TEST_SUCC(madvise(addr, 0, MADV_DONTNEED));
+	TEST_SUCC(madvise(addr, 1, MADV_RANDOM));
+
+	TEST_ERRNO(madvise(addr, 2 * page_size, MADV_NORMAL), ENOMEM);
+	TEST_ERRNO(madvise(addr, 2 * page_size, MADV_WILLNEED), ENOMEM);
+	TEST_ERRNO(madvise(addr, 2 * page_size, MADV_DONTNEED), ENOMEM);
+	TEST_ERRNO(madvise(addr, 2 * page_size, MADV_POPULATE_READ), ENOMEM);
+	TEST_ERRNO(madvise(addr + page_size, page_size, MADV_DONTFORK),
+		   ENOMEM);
+
+	TEST_SUCC(munmap(addr, page_size));
+}
+END_TEST()
+
+FN_TEST(dontneed)
+{
+	char *addr;
+	int fd;
+
+	// Private anonymous pages are refaulted as zeros
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(2 * page_size, PROT_READ | PROT_WRITE,
+			       MAP_PRIVATE));
+	memset(addr, 'a', 2 * page_size);
+	TEST_SUCC(madvise(addr, page_size, MADV_DONTNEED));
+	TEST_RES(addr[0] + addr[page_size - 1], _ret == 0);
+	TEST_RES(addr[page_size], _ret == 'a');
+	TEST_SUCC(munmap(addr, 2 * page_size));
+
+	// Shared anonymous pages are kept
+	addr = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_SHARED));
+	addr[0] = 'a';
+	TEST_SUCC(madvise(addr, page_size, MADV_DONTNEED));
+	TEST_RES(addr[0], _ret == 'a');
+	TEST_SUCC(munmap(addr, page_size));
+
+	// Private copies of file pages are refaulted from the file
+	fd = TEST_SUCC(new_memfd(page_size, 'f'));
+	addr = (char *)TEST_SUCC((long)mmap(NULL, page_size,
+					    PROT_READ | PROT_WRITE,
+					    MAP_PRIVATE, fd, 0));
+	addr[0] = 'a';
+	TEST_SUCC(madvise(addr, page_size, MADV_DONTNEED));
+	TEST_RES(addr[0], _ret == 'f');
+	TEST_SUCC(munmap(addr, page_size));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(locked)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_LOCKED));
+	addr[0] = 'a';
+
+	TEST_ERRNO(madvise(addr, page_size, MADV_DONTNEED), EINVAL);
+	TEST_ERRNO(madvise(addr, page_size, MADV_FREE), EINVAL);
+	TEST_ERRNO(madvise(addr, page_size, MADV_REMOVE), EINVAL);
+	TEST_ERRNO(madvise(addr, page_size, MADV_COLD), EINVAL);
+	TEST_ERRNO(madvise(addr, page_size, MADV_PAGEOUT), EINVAL);
+	TEST_RES(addr[0], _ret == 'a');
+
+	TEST_SUCC(madvise(addr, page_size, MADV_DONTNEED_LOCKED));
+	TEST_RES(addr[0], _ret == 0);
+
+	TEST_SUCC(munmap(addr, page_size));
+}
+END_TEST()
+
+FN_TEST(free)
+{
+	char *addr;
+	int fd;
+
+	addr = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	addr[0] = 'a';
+	TEST_SUCC(madvise(addr, page_size, MADV_FREE));
+	// The page may or may not be freed, but it is usable anyway
+	addr[0] = 'b';
+	TEST_RES(addr[0], _ret == 'b');
+	TEST_SUCC(munmap(addr, page_size));
+
+	addr = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_SHARED));
+	TEST_ERRNO(madvise(addr, page_size, MADV_FREE), EINVAL);
+	TEST_SUCC(munmap(addr, page_size));
+
+	fd = TEST_SUCC(new_memfd(page_size, 'f'));
+	addr = (char *)TEST_SUCC((long)mmap(NULL, page_size,
+					    PROT_READ | PROT_WRITE,
+					    MAP_PRIVATE, fd, 0));
+	TEST_ERRNO(madvise(addr, page_size, MADV_FREE), EINVAL);
+	TEST_SUCC(munmap(addr, page_size));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(remove)
+{
+	char *addr;
+	char buf[2];
+	int fd;
+
+	// Shared anonymous pages are removed
+	addr = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_SHARED));
+	addr[0] = 'a';
+	TEST_SUCC(madvise(addr, page_size, MADV_REMOVE));
+	TEST_RES(addr[0], _ret == 0);
+	TEST_SUCC(munmap(addr, page_size));
+
+	// Private anonymous pages cannot be removed
+	addr = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	TEST_ERRNO(madvise(addr, page_size, MADV_REMOVE), EINVAL);
+	TEST_SUCC(munmap(addr, page_size));
+
+	fd = TEST_SUCC(new_memfd(2 * page_size, 'f'));
+
+	// Private file pages cannot be removed
+	addr = (char *)TEST_SUCC((long)mmap(NULL, page_size,
+					    PROT_READ | PROT_WRITE,
+					    MAP_PRIVATE, fd, 0));
+	TEST_ERRNO(madvise(addr, page_size, MADV_REMOVE), EACCES);
+	TEST_SUCC(munmap(addr, page_size));
+
+	// Shared file pages are removed from the file
+	addr = (char *)TEST_SUCC((long)mmap(NULL, 2 * page_size,
+					    PROT_READ | PROT_WRITE, MAP_SHARED,
+					    fd, 0));
+	TEST_SUCC(madvise(addr + page_size, page_size, MADV_REMOVE));
+	TEST_RES(addr[page_size - 1] + addr[page_size],
+		 _ret == 'f' + 0);
+	TEST_RES(pread(fd, buf, 2, page_size - 1),
+		 _ret == 2 && buf[0] == 'f' && buf[1] == 0);
+	TEST_RES(lseek(fd, 0, SEEK_END), _ret == 2 * page_size);
+	TEST_SUCC(munmap(addr, 2 * page_size));
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(fork)
+{
+	char *dont_fork, *wipe_on_fork, *do_fork;
+	int status;
+	pid_t pid;
+
+	dont_fork = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	wipe_on_fork = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	do_fork = (char *)TEST_SUCC((long)map_anon(
+		page_size, PROT_READ | PROT_WRITE, MAP_SHARED));
+	dont_fork[0] = 'a';
+	wipe_on_fork[0] = 'b';
+	do_fork[0] = 'c';
+
+	TEST_SUCC(madvise(dont_fork, page_size, MADV_DONTFORK));
+	TEST_SUCC(madvise(wipe_on_fork, page_size, MADV_WIPEONFORK));
+	TEST_SUCC(madvise(do_fork, page_size, MADV_DONTFORK));
+	TEST_SUCC(madvise(do_fork, page_size, MADV_DOFORK));
+	TEST_ERRNO(madvise(do_fork, page_size, MADV_WIPEONFORK), EINVAL);
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK_WITH(madvise(dont_fork, page_size, MADV_NORMAL),
+			   _ret < 0 && errno == ENOMEM);
+		CHECK_WITH(wipe_on_fork[0], _ret == 0);
+		CHECK_WITH(do_fork[0], _ret == 'c');
+		exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+	TEST_RES(dont_fork[0], _ret == 'a');
+	TEST_RES(wipe_on_fork[0], _ret == 'b');
+
+	// The pages are copied to the child again
+	TEST_SUCC(madvise(wipe_on_fork, page_size, MADV_KEEPONFORK));
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK_WITH(wipe_on_fork[0], _ret == 'b');
+		exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	TEST_SUCC(munmap(dont_fork, page_size));
+	TEST_SUCC(munmap(wipe_on_fork, page_size));
+	TEST_SUCC(munmap(do_fork, page_size));
+}
+END_TEST()
+
+FN_TEST(cold_and_pageout)
+{
+	char *addr;
+	int fd;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(2 * page_size, PROT_READ | PROT_WRITE,
+			       MAP_PRIVATE));
+	memset(addr, 'a', 2 * page_size);
+	TEST_SUCC(madvise(addr, 2 * page_size, MADV_COLD));
+	TEST_SUCC(madvise(addr, 2 * page_size, MADV_PAGEOUT));
+	TEST_RES(addr[0] + addr[2 * page_size - 1], _ret == 'a' + 'a');
+	TEST_SUCC(munmap(addr, 2 * page_size));
+
+	fd = TEST_SUCC(new_memfd(2 * page_size, 'f'));
+	addr = (char *)TEST_SUCC((long)mmap(NULL, 2 * page_size,
+					    PROT_READ | PROT_WRITE, MAP_SHARED,
+					    fd, 0));
+	addr[0] = 'a';
+	TEST_SUCC(madvise(addr, 2 * page_size, MADV_COLD));
+	TEST_SUCC(madvise(addr, 2 * page_size, MADV_PAGEOUT));
+	TEST_RES(addr[0] + addr[2 * page_size - 1], _ret == 'a' + 'f');
+	TEST_SUCC(munmap(addr, 2 * page_size));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(populate)
+{
+	char *addr;
+	int fd;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(2 * page_size, PROT_NONE, MAP_PRIVATE));
+	TEST_ERRNO(madvise(addr, 2 * page_size, MADV_POPULATE_READ), EINVAL);
+
+	TEST_SUCC(mprotect(addr, 2 * page_size, PROT_READ));
+	TEST_SUCC(madvise(addr, 2 * page_size, MADV_POPULATE_READ));
+	TEST_ERRNO(madvise(addr, 2 * page_size, MADV_POPULATE_WRITE),
+		   EINVAL);
+
+	TEST_SUCC(mprotect(addr, 2 * page_size, PROT_READ | PROT_WRITE));
+	TEST_SUCC(madvise(addr, 2 * page_size, MADV_POPULATE_WRITE));
+	TEST_RES(addr[0] + addr[2 * page_size - 1], _ret == 0);
+	TEST_SUCC(munmap(addr, 2 * page_size));
+
+	// Populating the pages beyond the end of the file fails
+	fd = TEST_SUCC(new_memfd(page_size, 'f'));
+	addr = (char *)TEST_SUCC((long)mmap(NULL, 2 * page_size,
+					    PROT_READ | PROT_WRITE, MAP_SHARED,
+					    fd, 0));
+	TEST_ERRNO(madvise(addr, 2 * page_size, MADV_POPULATE_READ), EFAULT);
+	TEST_SUCC(madvise(addr, page_size, MADV_POPULATE_WRITE));
+	TEST_RES(addr[0], _ret == 'f');
+	TEST_SUCC(munmap(addr, 2 * page_size));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
@@ -0,0 +1,300 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/resource.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#ifndef MLOCK_ONFAULT
+#define MLOCK_ONFAULT 0x01
+#endif
+
+#ifndef MCL_ONFAULT
+#define MCL_ONFAULT 4
+#endif
+
+static long page_size;
+
+static char *map_anon(size_t len, int flags)
+{
+	return mmap(NULL, len, PROT_READ | PROT_WRITE,
+		    MAP_PRIVATE | MAP_ANONYMOUS | flags, -1, 0);
+}
+
+// Reads the `Rss` and `Locked` sizes and the `VmFlags` of the mapping that
+// contains `addr` from `/proc/self/smaps`
+static int read_smaps(void *addr, long *rss, long *locked, char *flags,
+		      size_t flags_len)
+{
+	char line[256];
+	int found = 0;
+	unsigned long start, end;
+	FILE *file;
+
+	file = fopen("/proc/self/smaps", "r");
+	if (file == NULL)
+		return -1;
+
+	while (fgets(line, sizeof(line), file) != NULL) {
+		if (sscanf(line, "%lx-%lx ", &start, &end) == 2) {
+			if (found)
+				break;
+			found = start <= (unsigned long)addr &&
+				(unsigned long)addr < end;
+			continue;
+		}
+		if (!found)
+			continue;
+
+		sscanf(line, "Rss: %ld kB", rss);
+		sscanf(line, "Locked: %ld kB", locked);
+		if (strncmp(line, "VmFlags:", 8) == 0) {
+			strncpy(flags, line + 8, flags_len - 1);
+			flags[flags_len - 1] = '\0';
+		}
+	}
+
+	fclose(file);
+	return found ? 0 : -1;
+}
+
+static int is_locked(void *addr)
+{
+	long rss, locked;
+	char flags[128] = "";
+
+	if (read_smaps(addr, &rss, &locked, flags, sizeof(flags)) < 0)
+		return -1;
+	return strstr(flags, " lo ") != NULL;
+}
+
+// Reads the `VmLck` size from `/proc/self/status`
+static long read_vmlck(void)
+{
+	char line[256];
+	long size = -1;
+	FILE *file;
+
+	file = fopen("/proc/self/status", "r");
+	if (file == NULL)
+		return -1;
+
+	while (fgets(line, sizeof(line), file) != NULL)
+		if (sscanf(line, "VmLck: %ld kB", &size) == 1)
+			break;
+
+	fclose(file);
+	return size;
+}
+
+FN_SETUP(init)
+{
+	page_size = CHECK(sysconf(_SC_PAGESIZE));
+}
+END_SETUP()
+
+FN_TEST(args)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC((long)map_anon(2 * page_size, 0));
+	TEST_SUCC(munmap(addr + page_size, page_size));
+
+	TEST_SUCC(mlock(addr, 0));
+	TEST_ERRNO(mlock(addr, 2 * page_size), ENOMEM);
+	TEST_ERRNO(munlock(addr, 2 * page_size), ENOMEM);
+	TEST_ERRNO(mlock2(addr, page_size, ~MLOCK_ONFAULT), EINVAL);
+	TEST_ERRNO(mlockall(0), EINVAL);
+	TEST_ERRNO(mlockall(MCL_ONFAULT), EINVAL);
+	TEST_ERRNO(mlockall(~(MCL_CURRENT | MCL_FUTURE | MCL_ONFAULT)),
+		   EINVAL);
+
+	TEST_SUCC(munlock(addr, page_size));
+	TEST_SUCC(munmap(addr, page_size));
+}
+END_TEST()
+
+FN_TEST(lock_and_unlock)
+{
+	char *addr;
+	long rss, locked, vmlck;
+	char flags[128];
+
+	addr = (char *)TEST_SUCC((long)map_anon(4 * page_size, 0));
+	vmlck = TEST_SUCC(read_vmlck());
+
+	// Lock the middle two pages, which are populated
+	TEST_SUCC(mlock(addr + page_size + 1, page_size));
+	TEST_RES(read_smaps(addr + page_size, &rss, &locked, flags,
+			    sizeof(flags)),
+		 rss == 2 * page_size / 1024 && locked == rss &&
+			 strstr(flags, " lo ") != NULL &&
+			 strstr(flags, " lf ") == NULL);
+	TEST_RES(is_locked(addr), _ret == 0);
+	TEST_RES(is_locked(addr + 3 * page_size), _ret == 0);
+	TEST_RES(read_vmlck(), _ret == vmlck + 2 * page_size / 1024);
+
+	// Locking the pages again does not count them twice
+	TEST_SUCC(mlock(addr + page_size, 2 * page_size));
+	TEST_RES(read_vmlck(), _ret == vmlck + 2 * page_size / 1024);
+
+	TEST_SUCC(munlock(addr, 4 * page_size));
+	TEST_RES(is_locked(addr), _ret == 0);
+	TEST_RES(read_vmlck(), _ret == vmlck);
+
+	TEST_SUCC(munmap(addr, 4 * page_size));
+}
+END_TEST()
+
+FN_TEST(lock_on_fault)
+{
+	char *addr;
+	long rss, locked;
+	char flags[128];
+
+	addr = (char *)TEST_SUCC((long)map_anon(2 * page_size, 0));
+
+	// The pages are not populated until they are accessed
+	TEST_SUCC(mlock2(addr, 2 * page_size, MLOCK_ONFAULT));
+	TEST_RES(read_smaps(addr, &rss, &locked, flags, sizeof(flags)),
+		 rss == 0 && locked == 0 && strstr(flags, " lo ") != NULL &&
+			 strstr(flags, " lf ") != NULL);
+
+	addr[0] = 'a';
+	TEST_RES(read_smaps(addr, &rss, &locked, flags, sizeof(flags)),
+		 rss == page_size / 1024 && locked == rss);
+
+	// Locking the pages without `MLOCK_ONFAULT` populates them
+	TEST_SUCC(mlock(addr, 2 * page_size));
+	TEST_RES(read_smaps(addr, &rss, &locked, flags, sizeof(flags)),
+		 rss == 2 * page_size / 1024 && locked == rss &&
+			 strstr(flags, " lf ") == NULL);
+
+	TEST_SUCC(munmap(addr, 2 * page_size));
+}
+END_TEST()
+
+FN_TEST(map_locked)
+{
+	char *addr;
+	long rss, locked;
+	char flags[128];
+
+	addr = (char *)TEST_SUCC((long)map_anon(2 * page_size, MAP_LOCKED));
+	TEST_RES(read_smaps(addr, &rss, &locked, flags, sizeof(flags)),
+		 rss == 2 * page_size / 1024 && locked == rss &&
+			 strstr(flags, " lo ") != NULL);
+
+	// The pages added to a locked mapping are locked as well
+	addr = (char *)TEST_SUCC((long)mremap(addr, 2 * page_size,
+					      3 * page_size, MREMAP_MAYMOVE));
+	TEST_RES(read_smaps(addr, &rss, &locked, flags, sizeof(flags)),
+		 rss == 3 * page_size / 1024 && locked == rss &&
+			 strstr(flags, " lo ") != NULL);
+
+	TEST_SUCC(munmap(addr, 3 * page_size));
+}
+END_TEST()
+
+FN_TEST(lock_all)
+{
+	char *addr, *addr2;
+
+	addr = (char *)TEST_SUCC((long)map_anon(page_size, 0));
+
+	TEST_SUCC(mlockall(MCL_CURRENT | MCL_FUTURE | MCL_ONFAULT));
+	TEST_RES(is_locked(addr), _ret == 1);
+
+	// New mappings are locked
+	addr2 = (char *)TEST_SUCC((long)map_anon(page_size, 0));
+	TEST_RES(is_locked(addr2), _ret == 1);
+	TEST_SUCC(munmap(addr2, page_size));
+
+	// New mappings are no longer locked without `MCL_FUTURE`
+	TEST_SUCC(mlockall(MCL_CURRENT | MCL_ONFAULT));
+	addr2 = (char *)TEST_SUCC((long)map_anon(page_size, 0));
+	TEST_RES(is_locked(addr2), _ret == 0);
+	TEST_SUCC(munmap(addr2, page_size));
+
+	TEST_SUCC(mlockall(MCL_FUTURE));
+	TEST_SUCC(munlockall());
+	TEST_RES(is_locked(addr), _ret == 0);
+	addr2 = (char *)TEST_SUCC((long)map_anon(page_size, 0));
+	TEST_RES(is_locked(addr2), _ret == 0);
+	TEST_SUCC(munmap(addr2, page_size));
+
+	TEST_SUCC(munmap(addr, page_size));
+}
+END_TEST()
+
+FN_TEST(fork_does_not_inherit_locks)
+{
+	char *addr;
+	int status;
+	pid_t pid;
+
+	addr = (char *)TEST_SUCC((long)map_anon(page_size, MAP_LOCKED));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK_WITH(is_locked(addr), _ret == 0);
+		CHECK_WITH(read_vmlck(), _ret == 0);
+		exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+	TEST_RES(is_locked(addr), _ret == 1);
+
+	TEST_SUCC(munmap(addr, page_size));
+}
+END_TEST()
+
+FN_TEST(rlimit_memlock)
+{
+	char *addr;
+	int status;
+	pid_t pid;
+
+	addr = (char *)TEST_SUCC((long)map_anon(4 * page_size, 0));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		struct rlimit rlimit = { .rlim_cur = 0,
+					 .rlim_max = 2 * page_size };
+
+		// Drop `CAP_IPC_LOCK` so that the limit is enforced
+		CHECK(setrlimit(RLIMIT_MEMLOCK, &rlimit));
+		CHECK(setuid(65534));
+
+		CHECK_WITH(mlock(addr, page_size), _ret < 0 && errno == EPERM);
+		CHECK_WITH(mlockall(MCL_FUTURE), _ret < 0 && errno == EPERM);
+		CHECK_WITH((long)map_anon(page_size, MAP_LOCKED),
+			   _ret == (long)MAP_FAILED && errno == EPERM);
+
+		rlimit.rlim_cur = 2 * page_size;
+		CHECK(setrlimit(RLIMIT_MEMLOCK, &rlimit));
+
+		CHECK(mlock(addr, 2 * page_size));
+		CHECK_WITH(mlock(addr, 3 * page_size),
+			   _ret < 0 && errno == ENOMEM);
+		CHECK_WITH((long)map_anon(page_size, MAP_LOCKED),
+			   _ret == (long)MAP_FAILED && errno == EAGAIN);
+
+		CHECK(munlock(addr, page_size));
+		CHECK(mlock(addr + 2 * page_size, page_size));
+		exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	TEST_SUCC(munmap(addr, 4 * page_size));
+}
+END_TEST()
@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+static long page_size;
+
+static char *map_anon(size_t pages, int prot, int flags)
+{
+	return mmap(NULL, pages * page_size, prot, MAP_ANONYMOUS | flags, -1,
+		    0);
+}
+
+static int is_filled(const char *addr, size_t len, char c)
+{
+	for (size_t i = 0; i < len; i++)
+		if (addr[i] != c)
+			return 0;
+	return 1;
+}
+
+FN_SETUP(init)
+{
+	page_size = CHECK(sysconf(_SC_PAGESIZE));
+}
+END_SETUP()
+
+FN_TEST(invalid_args)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(2, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+
+	TEST_ERRNO((long)mremap(addr, page_size, page_size, 0x80), EINVAL);
+	TEST_ERRNO((long)mremap(addr, page_size, page_size, MREMAP_FIXED,
+				addr + page_size),
+		   EINVAL);
+	TEST_ERRNO((long)mremap(addr, page_size, page_size, MREMAP_DONTUNMAP,
+				NULL),
+		   EINVAL);
+	TEST_ERRNO((long)mremap(addr, page_size, 2 * page_size,
+				MREMAP_MAYMOVE | MREMAP_DONTUNMAP, NULL),
+		   EINVAL);
+	TEST_ERRNO((long)mremap(addr + 1, page_size, page_size, 0), EINVAL);
+	TEST_ERRNO((long)mremap(addr, page_size, 0, 0), EINVAL);
+
+	// The new range cannot overlap the old range
+	TEST_ERRNO((long)mremap(addr, page_size, page_size,
+				MREMAP_MAYMOVE | MREMAP_FIXED,
+				addr + page_size / 2),
+		   EINVAL);
+	TEST_ERRNO((long)mremap(addr, 2 * page_size, page_size,
+				MREMAP_MAYMOVE | MREMAP_FIXED,
+				addr + page_size),
+		   EINVAL);
+
+	TEST_SUCC(munmap(addr, 2 * page_size));
+
+	// The old range must be mapped
+	TEST_ERRNO((long)mremap(addr, page_size, page_size, 0), EFAULT);
+}
+END_TEST()
+
+FN_TEST(shrink)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(4, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	memset(addr, 'a', 4 * page_size);
+
+	TEST_RES((long)mremap(addr, 4 * page_size, 2 * page_size, 0),
+		 _ret == (long)addr && is_filled(addr, 2 * page_size, 'a'));
+	TEST_ERRNO((long)mremap(addr + 2 * page_size, page_size, page_size, 0),
+		   EFAULT);
+
+	TEST_SUCC(munmap(addr, 2 * page_size));
+}
+END_TEST()
+
+FN_TEST(grow_in_place)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(4, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	TEST_SUCC(munmap(addr + 2 * page_size, 2 * page_size));
+	memset(addr, 'a', 2 * page_size);
+
+	TEST_RES((long)mremap(addr, 2 * page_size, 4 * page_size, 0),
+		 _ret == (long)addr && is_filled(addr, 2 * page_size, 'a') &&
+			 is_filled(addr + 2 * page_size, 2 * page_size, 0));
+	memset(addr + 2 * page_size, 'b', 2 * page_size);
+
+	TEST_SUCC(munmap(addr, 4 * page_size));
+}
+END_TEST()
+
+FN_TEST(grow_and_move)
+{
+	char *addr, *new_addr;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(3, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	memset(addr, 'a', 3 * page_size);
+	TEST_SUCC(mprotect(addr + 2 * page_size, page_size, PROT_READ));
+
+	// The next page belongs to another mapping
+	TEST_ERRNO((long)mremap(addr, 2 * page_size, 3 * page_size, 0),
+		   ENOMEM);
+	TEST_ERRNO((long)mremap(addr, page_size, 2 * page_size, 0), ENOMEM);
+
+	new_addr = (char *)TEST_RES(
+		(long)mremap(addr, 2 * page_size, 4 * page_size,
+			     MREMAP_MAYMOVE),
+		_ret != (long)addr);
+	TEST_RES(0, is_filled(new_addr, 2 * page_size, 'a') &&
+			    is_filled(new_addr + 2 * page_size, 2 * page_size,
+				      0));
+	memset(new_addr + 2 * page_size, 'b', 2 * page_size);
+
+	// The old range is unmapped, but the rest of the mapping is kept
+	TEST_ERRNO((long)mremap(addr, page_size, page_size, 0), EFAULT);
+	TEST_RES(0, is_filled(addr + 2 * page_size, page_size, 'a'));
+
+	TEST_SUCC(munmap(addr + 2 * page_size, page_size));
+	TEST_SUCC(munmap(new_addr, 4 * page_size));
+}
+END_TEST()
+
+FN_TEST(move_fixed)
+{
+	char *addr, *new_addr;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(3, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	new_addr = (char *)TEST_SUCC(
+		(long)map_anon(2, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	memset(addr, 'a', 3 * page_size);
+	memset(new_addr, 'b', 2 * page_size);
+
+	// The existing mapping at the new address is replaced, and the pages
+	// that do not fit in the new range are unmapped
+	TEST_RES((long)mremap(addr + page_size, 2 * page_size, page_size,
+			      MREMAP_MAYMOVE | MREMAP_FIXED, new_addr),
+		 _ret == (long)new_addr && is_filled(new_addr, page_size, 'a'));
+	TEST_RES(0, is_filled(new_addr + page_size, page_size, 'b'));
+	TEST_ERRNO((long)mremap(addr + page_size, page_size, page_size, 0),
+		   EFAULT);
+	TEST_ERRNO((long)mremap(addr + 2 * page_size, page_size, page_size,
+				0),
+		   EFAULT);
+	TEST_RES(0, is_filled(addr, page_size, 'a'));
+
+	TEST_SUCC(munmap(addr, page_size));
+	TEST_SUCC(munmap(new_addr, 2 * page_size));
+}
+END_TEST()
+
+FN_TEST(dont_unmap)
+{
+	char *addr, *new_addr;
+
+	addr = (char *)TEST_SUCC(
+		(long)map_anon(2, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	memset(addr, 'a', 2 * page_size);
+
+	new_addr = (char *)TEST_RES(
+		(long)mremap(addr, 2 * page_size, 2 * page_size,
+			     MREMAP_MAYMOVE | MREMAP_DONTUNMAP, NULL),
+		_ret != (long)addr);
+	TEST_RES(0, is_filled(new_addr, 2 * page_size, 'a'));
+
+	// The old range is still mapped, but the pages have been moved away
+	TEST_RES(0, is_filled(addr, 2 * page_size, 0));
+	memset(addr, 'b', 2 * page_size);
+	TEST_RES(0, is_filled(new_addr, 2 * page_size, 'a'));
+
+	TEST_SUCC(munmap(addr, 2 * page_size));
+	TEST_SUCC(munmap(new_addr, 2 * page_size));
+}
+END_TEST()
+
+FN_TEST(move_shared)
+{
+	char *addr, *new_addr;
+	int fd;
+	char buf[8];
+
+	fd = TEST_SUCC(memfd_create("mremap", 0));
+	TEST_SUCC(ftruncate(fd, 2 * page_size));
+	addr = (char *)TEST_SUCC((long)mmap(NULL, 2 * page_size,
+					    PROT_READ | PROT_WRITE, MAP_SHARED,
+					    fd, 0));
+	new_addr = (char *)TEST_SUCC(
+		(long)map_anon(2, PROT_READ | PROT_WRITE, MAP_PRIVATE));
+	memset(addr, 'a', 2 * page_size);
+
+	TEST_RES((long)mremap(addr, 2 * page_size, 2 * page_size,
+			      MREMAP_MAYMOVE | MREMAP_FIXED, new_addr),
+		 _ret == (long)new_addr &&
+			 is_filled(new_addr, 2 * page_size, 'a'));
+
+	// The moved mapping still writes to the file
+	memset(new_addr + page_size, 'b', page_size);
+	TEST_RES(pread(fd, buf, sizeof(buf), page_size),
+		 _ret == sizeof(buf) && is_filled(buf, sizeof(buf), 'b'));
+
+	TEST_SUCC(munmap(new_addr, 2 * page_size));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
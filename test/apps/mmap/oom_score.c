@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define OOM_SCORE "/proc/self/oom_score"
+#define OOM_SCORE_ADJ "/proc/self/oom_score_adj"
+
+// Reads an integer from the file
+static int read_int(const char *path, int *value)
+{
+	char buf[32];
+	int fd;
+	ssize_t len;
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+	len = read(fd, buf, sizeof(buf) - 1);
+	close(fd);
+	if (len < 0)
+		return -1;
+	buf[len] = '\0';
+
+	*value = atoi(buf);
+	return 0;
+}
+
+// Writes a string to the file
+static ssize_t write_str(const char *path, const char *str)
+{
+	int fd;
+	ssize_t len;
+
+	fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+	len = write(fd, str, strlen(str));
+	close(fd);
+	return len;
+}
+
+FN_TEST(oom_score_default)
+{
+	int value;
+
+	TEST_RES(read_int(OOM_SCORE_ADJ, &value), value == 0);
+	TEST_RES(read_int(OOM_SCORE, &value), value >= 0 && value <= 2000);
+}
+END_TEST()
+
+FN_TEST(oom_score_adj_invalid)
+{
+	TEST_ERRNO(write_str(OOM_SCORE_ADJ, "1001"), EINVAL);
+	TEST_ERRNO(write_str(OOM_SCORE_ADJ, "-1001"), EINVAL);
+	TEST_ERRNO(write_str(OOM_SCORE_ADJ, "abc"), EINVAL);
+	TEST_ERRNO(write_str(OOM_SCORE_ADJ, ""), EINVAL);
+}
+END_TEST()
+
+FN_TEST(oom_score_adj)
+{
+	int value;
+
+	TEST_RES(write_str(OOM_SCORE_ADJ, "500\n"), _ret == 4);
+	TEST_RES(read_int(OOM_SCORE_ADJ, &value), value == 500);
+	TEST_RES(read_int(OOM_SCORE, &value), value >= 1500);
+
+	// A process with the minimum adjustment is never killed
+	TEST_RES(write_str(OOM_SCORE_ADJ, "-1000"), _ret == 5);
+	TEST_RES(read_int(OOM_SCORE_ADJ, &value), value == -1000);
+	TEST_RES(read_int(OOM_SCORE, &value), value == 0);
+
+	TEST_RES(write_str(OOM_SCORE_ADJ, "0"), _ret == 1);
+	TEST_RES(read_int(OOM_SCORE_ADJ, &value), value == 0);
+}
+END_TEST()
+
+FN_TEST(oom_score_adj_fork)
+{
+	int value, status;
+	pid_t pid;
+
+	TEST_RES(write_str(OOM_SCORE_ADJ, "300"), _ret == 3);
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// The adjustment is inherited by the child
+		if (read_int(OOM_SCORE_ADJ, &value) < 0 || value != 300)
+			_exit(EXIT_FAILURE);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	TEST_RES(write_str(OOM_SCORE_ADJ, "0"), _ret == 1);
+}
+END_TEST()
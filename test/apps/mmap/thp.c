@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define THP_ENABLED "/sys/kernel/mm/transparent_hugepage/enabled"
+#define HUGE_SIZE (2UL << 20)
+
+static long page_size;
+static char orig_mode[16];
+
+static int is_filled(const char *addr, size_t len, char c)
+{
+	for (size_t i = 0; i < len; i++)
+		if (addr[i] != c)
+			return 0;
+	return 1;
+}
+
+static long read_mode(char *buf, size_t len)
+{
+	int fd;
+	long ret;
+
+	fd = open(THP_ENABLED, O_RDONLY);
+	if (fd < 0)
+		return -1;
+	ret = read(fd, buf, len - 1);
+	close(fd);
+	if (ret >= 0)
+		buf[ret] = '\0';
+	return ret;
+}
+
+static long write_mode(const char *mode)
+{
+	int fd;
+	long ret;
+
+	fd = open(THP_ENABLED, O_WRONLY);
+	if (fd < 0)
+		return -1;
+	ret = write(fd, mode, strlen(mode));
+	close(fd);
+	return ret;
+}
+
+// Maps a huge-page-aligned anonymous region of `len` bytes
+static char *map_huge_aligned(size_t len)
+{
+	char *addr, *aligned;
+
+	addr = mmap(NULL, len + HUGE_SIZE, PROT_READ | PROT_WRITE,
+		    MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (addr == MAP_FAILED)
+		return MAP_FAILED;
+
+	aligned = (char *)(((unsigned long)addr + HUGE_SIZE - 1) &
+			   ~(HUGE_SIZE - 1));
+	if (aligned != addr)
+		munmap(addr, aligned - addr);
+	munmap(aligned + len, addr + HUGE_SIZE - aligned);
+	return aligned;
+}
+
+FN_SETUP(init)
+{
+	char *mode_start, *mode_end;
+	char buf[64];
+
+	page_size = CHECK(sysconf(_SC_PAGESIZE));
+
+	CHECK(read_mode(buf, sizeof(buf)));
+	mode_start = strchr(buf, '[');
+	mode_end = strchr(buf, ']');
+	if (mode_start == NULL || mode_end == NULL ||
+	    mode_end - mode_start > (long)sizeof(orig_mode)) {
+		fprintf(stderr, "fatal error: init: invalid THP mode %s", buf);
+		exit(EXIT_FAILURE);
+	}
+	memcpy(orig_mode, mode_start + 1, mode_end - mode_start - 1);
+}
+END_SETUP()
+
+FN_TEST(sysfs_mode)
+{
+	char buf[64];
+
+	TEST_RES(write_mode("never\n"), _ret == 6);
+	TEST_RES(read_mode(buf, sizeof(buf)),
+		 strstr(buf, "[never]") != NULL && buf[_ret - 1] == '\n');
+
+	TEST_RES(write_mode("madvise"), _ret == 7);
+	TEST_RES(read_mode(buf, sizeof(buf)),
+		 strstr(buf, "[madvise]") != NULL);
+
+	TEST_ERRNO(write_mode("sometimes"), EINVAL);
+	TEST_RES(read_mode(buf, sizeof(buf)),
+		 strstr(buf, "[madvise]") != NULL);
+}
+END_TEST()
+
+FN_TEST(madvise_args)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC((long)map_huge_aligned(HUGE_SIZE));
+
+	TEST_SUCC(madvise(addr, HUGE_SIZE, MADV_HUGEPAGE));
+	TEST_SUCC(madvise(addr, HUGE_SIZE, MADV_NOHUGEPAGE));
+	TEST_SUCC(madvise(addr, HUGE_SIZE, MADV_HUGEPAGE));
+
+	TEST_SUCC(munmap(addr, HUGE_SIZE));
+
+	// The range must be mapped
+	TEST_ERRNO(madvise(addr, HUGE_SIZE, MADV_HUGEPAGE), ENOMEM);
+}
+END_TEST()
+
+FN_TEST(split_huge_page)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC((long)map_huge_aligned(2 * HUGE_SIZE));
+	TEST_SUCC(madvise(addr, 2 * HUGE_SIZE, MADV_HUGEPAGE));
+	memset(addr, 'a', 2 * HUGE_SIZE);
+
+	// Changing the permissions of a part of a huge page keeps the contents
+	TEST_SUCC(mprotect(addr + page_size, page_size, PROT_READ));
+	TEST_RES(0, is_filled(addr, 2 * HUGE_SIZE, 'a'));
+	memset(addr, 'b', page_size);
+	memset(addr + 2 * page_size, 'b', HUGE_SIZE - 2 * page_size);
+	TEST_RES(0, is_filled(addr + page_size, page_size, 'a') &&
+			    is_filled(addr + 2 * page_size,
+				      HUGE_SIZE - 2 * page_size, 'b'));
+
+	// Unmapping a part of a huge page keeps the rest of the huge page
+	TEST_SUCC(munmap(addr + HUGE_SIZE + page_size, page_size));
+	TEST_RES(0, is_filled(addr + HUGE_SIZE, page_size, 'a') &&
+			    is_filled(addr + HUGE_SIZE + 2 * page_size,
+				      HUGE_SIZE - 2 * page_size, 'a'));
+
+	TEST_SUCC(munmap(addr, 2 * HUGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(fork_cow)
+{
+	char *addr;
+	int status;
+	pid_t pid;
+
+	addr = (char *)TEST_SUCC((long)map_huge_aligned(HUGE_SIZE));
+	TEST_SUCC(madvise(addr, HUGE_SIZE, MADV_HUGEPAGE));
+	memset(addr, 'a', HUGE_SIZE);
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		memset(addr + page_size, 'b', page_size);
+		_exit(!(is_filled(addr, page_size, 'a') &&
+			is_filled(addr + page_size, page_size, 'b')));
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	// The writes in the child are not visible to the parent
+	TEST_RES(0, is_filled(addr, HUGE_SIZE, 'a'));
+
+	TEST_SUCC(munmap(addr, HUGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(mremap_huge_page)
+{
+	char *addr, *new_addr;
+
+	addr = (char *)TEST_SUCC((long)map_huge_aligned(HUGE_SIZE));
+	TEST_SUCC(madvise(addr, HUGE_SIZE, MADV_HUGEPAGE));
+	memset(addr, 'a', HUGE_SIZE);
+
+	new_addr = (char *)TEST_RES(
+		(long)mremap(addr, HUGE_SIZE, 2 * HUGE_SIZE, MREMAP_MAYMOVE),
+		_ret != (long)MAP_FAILED);
+	TEST_RES(0, is_filled(new_addr, HUGE_SIZE, 'a') &&
+			    is_filled(new_addr + HUGE_SIZE, HUGE_SIZE, 0));
+
+	TEST_SUCC(munmap(new_addr, 2 * HUGE_SIZE));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(write_mode(orig_mode));
+}
+END_SETUP()
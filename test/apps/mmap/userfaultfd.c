@@ -0,0 +1,361 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <poll.h>
+#include <pthread.h>
+#include <string.h>
+#include <sys/ioctl.h>
+#include <sys/mman.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+#include <linux/userfaultfd.h>
+
+static long page_size;
+static char *src_page;
+
+static int is_filled(const char *addr, size_t len, char c)
+{
+	for (size_t i = 0; i < len; i++)
+		if (addr[i] != c)
+			return 0;
+	return 1;
+}
+
+static int new_uffd(int flags)
+{
+	return syscall(SYS_userfaultfd, flags);
+}
+
+static int api(int uffd, __u64 features)
+{
+	struct uffdio_api api = { .api = UFFD_API, .features = features };
+
+	return ioctl(uffd, UFFDIO_API, &api);
+}
+
+static int do_register(int uffd, void *addr, size_t len, __u64 mode)
+{
+	struct uffdio_register reg = {
+		.range = { .start = (unsigned long)addr, .len = len },
+		.mode = mode,
+	};
+
+	return ioctl(uffd, UFFDIO_REGISTER, &reg);
+}
+
+static int do_unregister(int uffd, void *addr, size_t len)
+{
+	struct uffdio_range range = { .start = (unsigned long)addr,
+				      .len = len };
+
+	return ioctl(uffd, UFFDIO_UNREGISTER, &range);
+}
+
+static int do_copy(int uffd, void *dst, size_t len, __u64 mode)
+{
+	struct uffdio_copy copy = {
+		.dst = (unsigned long)dst,
+		.src = (unsigned long)src_page,
+		.len = len,
+		.mode = mode,
+	};
+
+	return ioctl(uffd, UFFDIO_COPY, &copy);
+}
+
+static int do_writeprotect(int uffd, void *addr, size_t len, __u64 mode)
+{
+	struct uffdio_writeprotect wp = {
+		.range = { .start = (unsigned long)addr, .len = len },
+		.mode = mode,
+	};
+
+	return ioctl(uffd, UFFDIO_WRITEPROTECT, &wp);
+}
+
+static char *map_anon(size_t len)
+{
+	return mmap(NULL, len, PROT_READ | PROT_WRITE,
+		    MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+}
+
+struct fault_handler {
+	int uffd;
+	struct uffd_msg msg;
+	int result;
+};
+
+// Reads one fault and resolves it according to its kind
+static void *handle_fault(void *arg)
+{
+	struct fault_handler *handler = arg;
+	struct uffdio_zeropage zeropage;
+	unsigned long page;
+
+	handler->result = -1;
+	if (read(handler->uffd, &handler->msg, sizeof(handler->msg)) !=
+	    sizeof(handler->msg))
+		return NULL;
+
+	page = handler->msg.arg.pagefault.address & ~(page_size - 1);
+	if (handler->msg.arg.pagefault.flags & UFFD_PAGEFAULT_FLAG_WP) {
+		handler->result = do_writeprotect(handler->uffd, (void *)page,
+						  page_size, 0);
+	} else if (handler->msg.arg.pagefault.flags &
+		   UFFD_PAGEFAULT_FLAG_WRITE) {
+		zeropage.range.start = page;
+		zeropage.range.len = page_size;
+		zeropage.mode = 0;
+		handler->result =
+			ioctl(handler->uffd, UFFDIO_ZEROPAGE, &zeropage);
+	} else {
+		handler->result =
+			do_copy(handler->uffd, (void *)page, page_size, 0);
+	}
+	return NULL;
+}
+
+FN_SETUP(init)
+{
+	page_size = CHECK(sysconf(_SC_PAGESIZE));
+
+	src_page = (char *)CHECK_WITH((long)map_anon(page_size),
+				      _ret != (long)MAP_FAILED);
+	memset(src_page, 'a', page_size);
+}
+END_SETUP()
+
+FN_TEST(api_args)
+{
+	struct uffdio_api uapi;
+	int uffd;
+
+	TEST_ERRNO(new_uffd(~(O_CLOEXEC | O_NONBLOCK)), EINVAL);
+
+	uffd = TEST_SUCC(new_uffd(O_CLOEXEC | O_NONBLOCK));
+
+	// Other ioctls fail before the API is negotiated
+	TEST_ERRNO(do_unregister(uffd, src_page, page_size), EINVAL);
+
+	uapi.api = 0xab;
+	uapi.features = 0;
+	TEST_ERRNO(ioctl(uffd, UFFDIO_API, &uapi), EINVAL);
+	TEST_RES(0, uapi.api == 0 && uapi.features == 0);
+
+	uapi.api = UFFD_API;
+	uapi.features = UFFD_FEATURE_PAGEFAULT_FLAG_WP |
+			UFFD_FEATURE_THREAD_ID;
+	TEST_RES(ioctl(uffd, UFFDIO_API, &uapi),
+		 _ret == 0 &&
+			 (uapi.features & UFFD_FEATURE_PAGEFAULT_FLAG_WP) &&
+			 (uapi.ioctls & (1UL << _UFFDIO_REGISTER)));
+
+	// The API can only be negotiated once
+	TEST_ERRNO(api(uffd, 0), EINVAL);
+
+	TEST_SUCC(close(uffd));
+}
+END_TEST()
+
+FN_TEST(register_args)
+{
+	struct uffdio_register reg;
+	char *addr;
+	int uffd, uffd2;
+
+	uffd = TEST_SUCC(new_uffd(O_NONBLOCK));
+	TEST_SUCC(api(uffd, 0));
+	addr = (char *)TEST_SUCC((long)map_anon(2 * page_size));
+
+	TEST_ERRNO(do_register(uffd, addr + 1, page_size,
+			       UFFDIO_REGISTER_MODE_MISSING),
+		   EINVAL);
+	TEST_ERRNO(do_register(uffd, addr, 0, UFFDIO_REGISTER_MODE_MISSING),
+		   EINVAL);
+	TEST_ERRNO(do_register(uffd, addr, page_size, 0), EINVAL);
+
+	reg.range.start = (unsigned long)addr;
+	reg.range.len = 2 * page_size;
+	reg.mode = UFFDIO_REGISTER_MODE_MISSING;
+	TEST_RES(ioctl(uffd, UFFDIO_REGISTER, &reg),
+		 _ret == 0 && (reg.ioctls & (1UL << _UFFDIO_COPY)) &&
+			 !(reg.ioctls & (1UL << _UFFDIO_WRITEPROTECT)));
+
+	// A range cannot be registered to two userfaultfds
+	uffd2 = TEST_SUCC(new_uffd(O_NONBLOCK));
+	TEST_SUCC(api(uffd2, 0));
+	TEST_ERRNO(do_register(uffd2, addr, page_size,
+			       UFFDIO_REGISTER_MODE_MISSING),
+		   EBUSY);
+
+	// Write protection requires the write-protect mode
+	TEST_ERRNO(do_writeprotect(uffd, addr, page_size,
+				   UFFDIO_WRITEPROTECT_MODE_WP),
+		   ENOENT);
+
+	TEST_SUCC(do_unregister(uffd, addr, 2 * page_size));
+	TEST_ERRNO(do_copy(uffd, addr, page_size, 0), ENOENT);
+
+	// The range must contain a mapping
+	TEST_SUCC(munmap(addr, 2 * page_size));
+	TEST_ERRNO(do_register(uffd2, addr, 2 * page_size,
+			       UFFDIO_REGISTER_MODE_MISSING),
+		   EINVAL);
+
+	TEST_SUCC(close(uffd2));
+	TEST_SUCC(close(uffd));
+}
+END_TEST()
+
+FN_TEST(read_and_poll)
+{
+	struct pollfd pfd = { .events = POLLIN };
+	struct uffd_msg msg;
+	int uffd;
+
+	uffd = TEST_SUCC(new_uffd(O_NONBLOCK));
+	pfd.fd = uffd;
+
+	TEST_ERRNO(read(uffd, &msg, sizeof(msg)), EINVAL);
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLERR);
+
+	TEST_SUCC(api(uffd, 0));
+	TEST_ERRNO(read(uffd, &msg, sizeof(msg) - 1), EINVAL);
+	TEST_ERRNO(read(uffd, &msg, sizeof(msg)), EAGAIN);
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+
+	// Polling a blocking userfaultfd always fails
+	TEST_SUCC(fcntl(uffd, F_SETFL, 0));
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLERR);
+
+	TEST_SUCC(close(uffd));
+}
+END_TEST()
+
+FN_TEST(copy_and_zeropage)
+{
+	struct fault_handler handler;
+	pthread_t thread;
+	char *addr;
+	int uffd;
+
+	uffd = TEST_SUCC(new_uffd(0));
+	TEST_SUCC(api(uffd, UFFD_FEATURE_THREAD_ID));
+	addr = (char *)TEST_SUCC((long)map_anon(3 * page_size));
+	TEST_SUCC(do_register(uffd, addr, 3 * page_size,
+			      UFFDIO_REGISTER_MODE_MISSING));
+
+	// A read fault on a missing page is resolved by copying
+	handler.uffd = uffd;
+	TEST_SUCC(pthread_create(&thread, NULL, handle_fault, &handler));
+	TEST_RES(addr[page_size + 1], _ret == 'a');
+	TEST_SUCC(pthread_join(thread, NULL));
+	TEST_RES(handler.result,
+		 _ret == 0 && handler.msg.event == UFFD_EVENT_PAGEFAULT &&
+			 handler.msg.arg.pagefault.address ==
+				 (unsigned long)addr + page_size &&
+			 handler.msg.arg.pagefault.flags == 0 &&
+			 handler.msg.arg.pagefault.feat.ptid == gettid());
+
+	// A write fault on a missing page is resolved by a zero page
+	TEST_SUCC(pthread_create(&thread, NULL, handle_fault, &handler));
+	addr[2 * page_size] = 'b';
+	TEST_SUCC(pthread_join(thread, NULL));
+	TEST_RES(handler.result,
+		 _ret == 0 && handler.msg.arg.pagefault.flags ==
+				      UFFD_PAGEFAULT_FLAG_WRITE);
+	TEST_RES(0, addr[2 * page_size] == 'b' &&
+			    is_filled(addr + 2 * page_size + 1,
+				      page_size - 1, 0));
+
+	// Existing pages cannot be filled
+	TEST_ERRNO(do_copy(uffd, addr + page_size, page_size, 0), EEXIST);
+
+	// Pages are filled until the first existing page
+	TEST_ERRNO(do_copy(uffd, addr, 2 * page_size, 0), EAGAIN);
+	TEST_RES(0, is_filled(addr, page_size, 'a'));
+
+	TEST_SUCC(close(uffd));
+
+	// Faults are handled by the kernel after the userfaultfd is closed
+	TEST_SUCC(munmap(addr, page_size));
+	TEST_RES(0, is_filled(addr + page_size, page_size, 'a'));
+	TEST_SUCC(munmap(addr + page_size, 2 * page_size));
+}
+END_TEST()
+
+FN_TEST(write_protect)
+{
+	struct fault_handler handler;
+	pthread_t thread;
+	char *addr;
+	int uffd;
+
+	uffd = TEST_SUCC(new_uffd(0));
+	TEST_SUCC(api(uffd, UFFD_FEATURE_PAGEFAULT_FLAG_WP));
+	addr = (char *)TEST_SUCC((long)map_anon(page_size));
+	TEST_SUCC(do_register(uffd, addr, page_size,
+			      UFFDIO_REGISTER_MODE_MISSING |
+				      UFFDIO_REGISTER_MODE_WP));
+	TEST_SUCC(do_copy(uffd, addr, page_size, UFFDIO_COPY_MODE_WP));
+
+	TEST_ERRNO(do_writeprotect(uffd, addr, page_size,
+				   UFFDIO_WRITEPROTECT_MODE_WP |
+					   UFFDIO_WRITEPROTECT_MODE_DONTWAKE),
+		   EINVAL);
+
+	// A write to a write-protected page is resolved by un-protecting it
+	handler.uffd = uffd;
+	TEST_SUCC(pthread_create(&thread, NULL, handle_fault, &handler));
+	TEST_RES(addr[0], _ret == 'a');
+	addr[0] = 'b';
+	TEST_SUCC(pthread_join(thread, NULL));
+	TEST_RES(handler.result,
+		 _ret == 0 && handler.msg.arg.pagefault.flags ==
+				      (UFFD_PAGEFAULT_FLAG_WRITE |
+				       UFFD_PAGEFAULT_FLAG_WP));
+	TEST_RES(0, addr[0] == 'b' && is_filled(addr + 1, page_size - 1, 'a'));
+
+	// The page can be write-protected again
+	TEST_SUCC(do_writeprotect(uffd, addr, page_size,
+				  UFFDIO_WRITEPROTECT_MODE_WP));
+	TEST_SUCC(pthread_create(&thread, NULL, handle_fault, &handler));
+	addr[1] = 'b';
+	TEST_SUCC(pthread_join(thread, NULL));
+	TEST_RES(handler.result, _ret == 0);
+	TEST_RES(0, addr[0] == 'b' && addr[1] == 'b');
+
+	TEST_SUCC(close(uffd));
+	TEST_SUCC(munmap(addr, page_size));
+}
+END_TEST()
+
+FN_TEST(fork)
+{
+	char *addr;
+	int status;
+	pid_t pid;
+	int uffd;
+
+	uffd = TEST_SUCC(new_uffd(0));
+	TEST_SUCC(api(uffd, 0));
+	addr = (char *)TEST_SUCC((long)map_anon(page_size));
+	TEST_SUCC(do_register(uffd, addr, page_size,
+			      UFFDIO_REGISTER_MODE_MISSING));
+
+	// The registration is not inherited by the child
+	pid = TEST_SUCC(fork());
+	if (pid == 0)
+		_exit(addr[0] != 0);
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	TEST_SUCC(close(uffd));
+	TEST_SUCC(munmap(addr, page_size));
+}
+END_TEST()
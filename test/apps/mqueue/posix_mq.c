@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <mqueue.h>
+#include <poll.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <time.h>
+#include <unistd.h>
+
+#define QUEUE_NAME "/test_posix_mq"
+#define MOUNT_DIR "/tmp/test_mqueue"
+#define MAX_MSGS 4
+#define MSG_SIZE 64
+
+static struct mq_attr attr = {
+	.mq_maxmsg = MAX_MSGS,
+	.mq_msgsize = MSG_SIZE,
+};
+
+static mqd_t open_queue(int flags)
+{
+	return mq_open(QUEUE_NAME, O_CREAT | O_EXCL | flags, 0600, &attr);
+}
+
+FN_TEST(invalid_args)
+{
+	struct mq_attr bad_attr = { .mq_maxmsg = 0, .mq_msgsize = MSG_SIZE };
+	mqd_t mqd;
+
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR), ENOENT);
+	TEST_ERRNO(mq_open("/a/b", O_RDWR | O_CREAT, 0600, NULL), EACCES);
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_CREAT, 0600, &bad_attr),
+		   EINVAL);
+
+	mqd = TEST_SUCC(open_queue(O_WRONLY));
+	TEST_RES(fcntl(mqd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_ERRNO(open_queue(O_RDWR), EEXIST);
+
+	TEST_ERRNO(mq_send(mqd, "x", 1, 32768), EINVAL);
+	TEST_ERRNO(mq_send(mqd, "x", MSG_SIZE + 1, 0), EMSGSIZE);
+	TEST_ERRNO(mq_receive(mqd, (char[MSG_SIZE]){}, MSG_SIZE, NULL), EBADF);
+	TEST_ERRNO(mq_send(STDIN_FILENO, "x", 1, 0), EBADF);
+
+	TEST_SUCC(mq_close(mqd));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+	TEST_ERRNO(mq_unlink(QUEUE_NAME), ENOENT);
+}
+END_TEST()
+
+FN_TEST(priority_order)
+{
+	char buf[MSG_SIZE];
+	unsigned int prio;
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(open_queue(O_RDWR));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(mq_send(mqd, "low", 4, 1));
+	TEST_SUCC(mq_send(mqd, "high1", 6, 5));
+	TEST_SUCC(mq_send(mqd, "high2", 6, 5));
+	TEST_SUCC(mq_send(mqd, "mid", 4, 3));
+
+	TEST_ERRNO(mq_receive(mqd, buf, MSG_SIZE - 1, NULL), EMSGSIZE);
+	TEST_RES(mq_receive(mqd, buf, MSG_SIZE, &prio),
+		 _ret == 6 && prio == 5 && strcmp(buf, "high1") == 0);
+	TEST_RES(mq_receive(mqd, buf, MSG_SIZE, &prio),
+		 _ret == 6 && prio == 5 && strcmp(buf, "high2") == 0);
+	TEST_RES(mq_receive(mqd, buf, MSG_SIZE, &prio),
+		 _ret == 4 && prio == 3 && strcmp(buf, "mid") == 0);
+	TEST_RES(mq_receive(mqd, buf, MSG_SIZE, &prio),
+		 _ret == 4 && prio == 1 && strcmp(buf, "low") == 0);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(nonblocking_and_timeout)
+{
+	struct mq_attr new_attr = { .mq_flags = 0 }, old_attr;
+	struct pollfd pfd;
+	struct timespec ts;
+	char buf[MSG_SIZE];
+	mqd_t mqd;
+	int i;
+
+	mqd = TEST_SUCC(open_queue(O_RDWR | O_NONBLOCK));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	pfd.fd = mqd;
+	pfd.events = POLLIN | POLLOUT;
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLOUT);
+	TEST_ERRNO(mq_receive(mqd, buf, MSG_SIZE, NULL), EAGAIN);
+
+	for (i = 0; i < MAX_MSGS; i++)
+		TEST_SUCC(mq_send(mqd, "msg", 4, 0));
+	TEST_ERRNO(mq_send(mqd, "msg", 4, 0), EAGAIN);
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLIN);
+
+	TEST_RES(mq_getattr(mqd, &old_attr),
+		 old_attr.mq_flags == O_NONBLOCK &&
+			 old_attr.mq_maxmsg == MAX_MSGS &&
+			 old_attr.mq_msgsize == MSG_SIZE &&
+			 old_attr.mq_curmsgs == MAX_MSGS);
+	TEST_SUCC(mq_setattr(mqd, &new_attr, NULL));
+	TEST_RES(mq_getattr(mqd, &old_attr), old_attr.mq_flags == 0);
+
+	CHECK(clock_gettime(CLOCK_REALTIME, &ts));
+	ts.tv_nsec += 100 * 1000 * 1000;
+	if (ts.tv_nsec >= 1000 * 1000 * 1000) {
+		ts.tv_sec += 1;
+		ts.tv_nsec -= 1000 * 1000 * 1000;
+	}
+	TEST_ERRNO(mq_timedsend(mqd, "msg", 4, 0, &ts), ETIMEDOUT);
+
+	for (i = 0; i < MAX_MSGS; i++)
+		TEST_SUCC(mq_receive(mqd, buf, MSG_SIZE, NULL));
+	TEST_ERRNO(mq_timedreceive(mqd, buf, MSG_SIZE, NULL, &ts), ETIMEDOUT);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+static volatile int notified_code;
+static volatile int notified_value;
+
+static void notify_handler(int signo, siginfo_t *info, void *context)
+{
+	notified_code = info->si_code;
+	notified_value = info->si_value.sival_int;
+}
+
+FN_TEST(notify)
+{
+	struct sigaction sa = { .sa_sigaction = notify_handler,
+				.sa_flags = SA_SIGINFO };
+	struct sigevent sev = { .sigev_notify = SIGEV_SIGNAL,
+				.sigev_signo = SIGUSR1,
+				.sigev_value.sival_int = 42 };
+	char buf[MSG_SIZE];
+	mqd_t mqd;
+
+	TEST_SUCC(sigaction(SIGUSR1, &sa, NULL));
+
+	mqd = TEST_SUCC(open_queue(O_RDWR));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(mq_notify(mqd, &sev));
+	TEST_ERRNO(mq_notify(mqd, &sev), EBUSY);
+
+	TEST_RES(mq_send(mqd, "msg", 4, 0),
+		 notified_code == SI_MESGQ && notified_value == 42);
+	TEST_SUCC(mq_receive(mqd, buf, MSG_SIZE, NULL));
+
+	// The registration is removed after the notification
+	notified_code = 0;
+	TEST_RES(mq_send(mqd, "msg", 4, 0), notified_code == 0);
+	TEST_SUCC(mq_receive(mqd, buf, MSG_SIZE, NULL));
+
+	TEST_SUCC(mq_notify(mqd, &sev));
+	TEST_SUCC(mq_notify(mqd, NULL));
+	TEST_RES(mq_send(mqd, "msg", 4, 0), notified_code == 0);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mount)
+{
+	char buf[128];
+	mqd_t mqd;
+	int fd;
+
+	mqd = TEST_SUCC(open_queue(O_RDWR));
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+
+	TEST_SUCC(mkdir(MOUNT_DIR, 0755));
+	TEST_SUCC(mount("mqueue", MOUNT_DIR, "mqueue", 0, NULL));
+
+	fd = TEST_SUCC(open(MOUNT_DIR QUEUE_NAME, O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf) - 1),
+		 _ret > 0 && strncmp(buf, "QSIZE:5 ", 8) == 0);
+	TEST_SUCC(close(fd));
+
+	TEST_SUCC(unlink(MOUNT_DIR QUEUE_NAME));
+	TEST_ERRNO(mq_unlink(QUEUE_NAME), ENOENT);
+
+	TEST_SUCC(umount(MOUNT_DIR));
+	TEST_SUCC(rmdir(MOUNT_DIR));
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
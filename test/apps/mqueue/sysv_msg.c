@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/msg.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define QUEUE_KEY 0x4d534731
+
+struct test_msg {
+	long mtype;
+	char mtext[64];
+};
+
+static int send_msg(int msqid, long mtype, const char *text, int flags)
+{
+	struct test_msg msg;
+
+	msg.mtype = mtype;
+	strcpy(msg.mtext, text);
+	return msgsnd(msqid, &msg, strlen(text) + 1, flags);
+}
+
+FN_TEST(invalid_args)
+{
+	struct test_msg msg = { .mtype = 0 };
+	int msqid;
+
+	TEST_ERRNO(msgget(QUEUE_KEY, 0600), ENOENT);
+
+	msqid = TEST_SUCC(msgget(QUEUE_KEY, IPC_CREAT | 0600));
+	TEST_ERRNO(msgget(QUEUE_KEY, IPC_CREAT | IPC_EXCL | 0600), EEXIST);
+	TEST_RES(msgget(QUEUE_KEY, 0600), _ret == msqid);
+
+	TEST_ERRNO(msgsnd(msqid, &msg, 1, 0), EINVAL);
+	TEST_ERRNO(msgsnd(-1, &msg, 1, 0), EINVAL);
+	TEST_ERRNO(msgrcv(msqid, &msg, -1, 0, 0), EINVAL);
+	TEST_ERRNO(msgrcv(msqid, &msg, 1, 0, MSG_COPY), EINVAL);
+	TEST_ERRNO(msgctl(msqid, 0xdead, NULL), EINVAL);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+	TEST_ERRNO(msgctl(msqid, IPC_RMID, NULL), EINVAL);
+	TEST_ERRNO(msgget(QUEUE_KEY, 0600), ENOENT);
+}
+END_TEST()
+
+FN_TEST(select_by_type)
+{
+	struct test_msg msg;
+	int msqid;
+
+	msqid = TEST_SUCC(msgget(IPC_PRIVATE, IPC_CREAT | 0600));
+
+	TEST_SUCC(send_msg(msqid, 3, "three", 0));
+	TEST_SUCC(send_msg(msqid, 1, "one", 0));
+	TEST_SUCC(send_msg(msqid, 2, "two", 0));
+
+	// Receive the message of the type
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 2, IPC_NOWAIT),
+		 _ret == 4 && msg.mtype == 2 && strcmp(msg.mtext, "two") == 0);
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), 2, IPC_NOWAIT),
+		   ENOMSG);
+
+	// Receive the first message whose type is not the type
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 3,
+			IPC_NOWAIT | MSG_EXCEPT),
+		 msg.mtype == 1 && strcmp(msg.mtext, "one") == 0);
+
+	// Copy the message at the index without removing it
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 0,
+			IPC_NOWAIT | MSG_COPY),
+		 msg.mtype == 3 && strcmp(msg.mtext, "three") == 0);
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), 1,
+			  IPC_NOWAIT | MSG_COPY),
+		   ENOMSG);
+
+	// Receive the message of the lowest type not greater than the type
+	TEST_SUCC(send_msg(msqid, 5, "five", 0));
+	TEST_SUCC(send_msg(msqid, 4, "four", 0));
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), -4, IPC_NOWAIT),
+		 msg.mtype == 3);
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), -4, IPC_NOWAIT),
+		 msg.mtype == 4);
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), -4, IPC_NOWAIT),
+		   ENOMSG);
+
+	// Receive the first message
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, IPC_NOWAIT),
+		 msg.mtype == 5 && strcmp(msg.mtext, "five") == 0);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(message_too_long)
+{
+	struct test_msg msg;
+	int msqid;
+
+	msqid = TEST_SUCC(msgget(IPC_PRIVATE, IPC_CREAT | 0600));
+
+	TEST_SUCC(send_msg(msqid, 1, "hello", 0));
+	TEST_ERRNO(msgrcv(msqid, &msg, 3, 0, IPC_NOWAIT), E2BIG);
+	TEST_RES(msgrcv(msqid, &msg, 3, 0, IPC_NOWAIT | MSG_NOERROR),
+		 _ret == 3 && memcmp(msg.mtext, "hel", 3) == 0);
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, IPC_NOWAIT),
+		   ENOMSG);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(stat_and_set)
+{
+	struct msqid_ds ds;
+	int msqid;
+
+	msqid = TEST_SUCC(msgget(IPC_PRIVATE, IPC_CREAT | 0640));
+
+	TEST_SUCC(send_msg(msqid, 1, "abc", 0));
+	TEST_SUCC(send_msg(msqid, 1, "defgh", 0));
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 (ds.msg_perm.mode & 0777) == 0640 && ds.msg_qnum == 2 &&
+			 ds.__msg_cbytes == 10 && ds.msg_lspid == getpid() &&
+			 ds.msg_lrpid == 0 && ds.msg_stime != 0);
+
+	// A full queue rejects the messages
+	ds.msg_qbytes = 12;
+	ds.msg_perm.mode = 0600;
+	TEST_SUCC(msgctl(msqid, IPC_SET, &ds));
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 (ds.msg_perm.mode & 0777) == 0600 && ds.msg_qbytes == 12);
+	TEST_ERRNO(send_msg(msqid, 1, "ijkl", IPC_NOWAIT), EAGAIN);
+	TEST_SUCC(send_msg(msqid, 1, "i", IPC_NOWAIT));
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+	TEST_ERRNO(msgctl(msqid, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(blocking_receive)
+{
+	struct test_msg msg;
+	int msqid, status;
+	pid_t pid;
+
+	msqid = TEST_SUCC(msgget(IPC_PRIVATE, IPC_CREAT | 0600));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		usleep(100 * 1000);
+		send_msg(msqid, 7, "wake up", 0);
+		usleep(100 * 1000);
+		msgctl(msqid, IPC_RMID, NULL);
+		_exit(0);
+	}
+
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 7, 0),
+		 strcmp(msg.mtext, "wake up") == 0);
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), 7, 0), EIDRM);
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
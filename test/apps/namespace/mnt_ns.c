@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <pthread.h>
+#include <sched.h>
+#include <signal.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define SRC_DIR "/tmp/mnt_ns_src"
+#define DST_DIR "/tmp/mnt_ns_dst"
+#define SRC_FILE SRC_DIR "/file"
+#define DST_FILE DST_DIR "/file"
+
+static ino_t mnt_ns_of(const char *pid)
+{
+	char path[64];
+	struct stat stat_buf;
+
+	snprintf(path, sizeof(path), "/proc/%s/ns/mnt", pid);
+	if (stat(path, &stat_buf) < 0)
+		return 0;
+	return stat_buf.st_ino;
+}
+
+static int bind_mount_in_new_ns(void)
+{
+	if (unshare(CLONE_NEWNS) < 0)
+		return -1;
+
+	// On Linux, the mounts may be shared with the parent's mount namespace.
+	// Make them private so that the following mount is not propagated.
+	mount(NULL, "/", NULL, MS_REC | MS_PRIVATE, NULL);
+
+	return mount(SRC_DIR, DST_DIR, NULL, MS_BIND, NULL);
+}
+
+FN_SETUP(dirs)
+{
+	int fd;
+
+	CHECK_WITH(mkdir(SRC_DIR, 0755), _ret >= 0 || errno == EEXIST);
+	CHECK_WITH(mkdir(DST_DIR, 0755), _ret >= 0 || errno == EEXIST);
+	fd = CHECK(open(SRC_FILE, O_WRONLY | O_CREAT, 0644));
+	CHECK(close(fd));
+}
+END_SETUP()
+
+FN_TEST(unshare)
+{
+	ino_t parent_ns = mnt_ns_of("self");
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		if (bind_mount_in_new_ns() < 0)
+			_exit(1);
+		if (mnt_ns_of("self") == parent_ns)
+			_exit(2);
+		if (access(DST_FILE, F_OK) < 0)
+			_exit(3);
+		_exit(0);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+	TEST_RES(mnt_ns_of("self"), _ret == parent_ns);
+	TEST_ERRNO(access(DST_FILE, F_OK), ENOENT);
+}
+END_TEST()
+
+static void *bind_mount_in_thread(void *arg)
+{
+	return (void *)(long)bind_mount_in_new_ns();
+}
+
+static void *unshare_fs_and_files_in_thread(void *arg)
+{
+	int fd = *(int *)arg;
+
+	if (unshare(CLONE_FS | CLONE_FILES) < 0)
+		return (void *)-1L;
+	if (chdir(SRC_DIR) < 0 || close(fd) < 0)
+		return (void *)-1L;
+	return NULL;
+}
+
+FN_TEST(unshare_in_thread)
+{
+	pthread_t thread;
+	void *ret;
+	int fd;
+
+	// The FS information that is shared with the other threads is
+	// unshared implicitly.
+	TEST_RES(pthread_create(&thread, NULL, bind_mount_in_thread, NULL),
+		 _ret == 0);
+	TEST_RES(pthread_join(thread, &ret), _ret == 0 && ret == NULL);
+	TEST_ERRNO(access(DST_FILE, F_OK), ENOENT);
+
+	fd = TEST_SUCC(open(SRC_FILE, O_RDONLY));
+	TEST_RES(pthread_create(&thread, NULL, unshare_fs_and_files_in_thread,
+				&fd),
+		 _ret == 0);
+	TEST_RES(pthread_join(thread, &ret), _ret == 0 && ret == NULL);
+	TEST_ERRNO(access("file", F_OK), ENOENT);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(clone)
+{
+	ino_t parent_ns = mnt_ns_of("self");
+	pid_t pid;
+	int status;
+
+	TEST_ERRNO(syscall(SYS_clone, CLONE_NEWNS | CLONE_FS | SIGCHLD, 0, 0, 0,
+			   0),
+		   EINVAL);
+
+	pid = TEST_SUCC(syscall(SYS_clone, CLONE_NEWNS | SIGCHLD, 0, 0, 0, 0));
+	if (pid == 0) {
+		if (mnt_ns_of("self") == parent_ns)
+			_exit(1);
+		_exit(0);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(setns)
+{
+	int ready_pipe[2], exit_pipe[2];
+	char buf, path[64];
+	pid_t owner, joiner;
+	int status, fd;
+
+	TEST_SUCC(pipe(ready_pipe));
+	TEST_SUCC(pipe(exit_pipe));
+
+	// The owner creates a new mount namespace and keeps it alive.
+	owner = TEST_SUCC(fork());
+	if (owner == 0) {
+		close(ready_pipe[0]);
+		close(exit_pipe[1]);
+		if (bind_mount_in_new_ns() < 0)
+			_exit(1);
+		if (write(ready_pipe[1], "x", 1) != 1)
+			_exit(2);
+		if (read(exit_pipe[0], &buf, 1) < 0)
+			_exit(3);
+		_exit(0);
+	}
+	TEST_SUCC(close(ready_pipe[1]));
+	TEST_SUCC(close(exit_pipe[0]));
+	TEST_RES(read(ready_pipe[0], &buf, 1), _ret == 1);
+
+	snprintf(path, sizeof(path), "/proc/%d/ns/mnt", owner);
+	fd = TEST_SUCC(open(path, O_RDONLY));
+	TEST_ERRNO(setns(fd, CLONE_NEWUTS), EINVAL);
+	TEST_ERRNO(setns(ready_pipe[0], CLONE_NEWNS), EINVAL);
+
+	// The joiner joins the mount namespace of the owner.
+	joiner = TEST_SUCC(fork());
+	if (joiner == 0) {
+		if (setns(fd, CLONE_NEWNS) < 0)
+			_exit(1);
+		if (access(DST_FILE, F_OK) < 0)
+			_exit(2);
+		_exit(0);
+	}
+	TEST_RES(waitpid(joiner, &status, 0),
+		 _ret == joiner && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+	TEST_ERRNO(access(DST_FILE, F_OK), ENOENT);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(close(exit_pipe[1]));
+	TEST_SUCC(close(ready_pipe[0]));
+	TEST_RES(waitpid(owner, &status, 0),
+		 _ret == owner && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+}
+END_TEST()
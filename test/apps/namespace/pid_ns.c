@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static int wait_for_success(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0)
+		return -1;
+	return 0;
+}
+
+static int check_first_process(void)
+{
+	pid_t pid;
+
+	if (getpid() != 1)
+		return 1;
+	// The parent is outside the namespace.
+	if (getppid() != 0)
+		return 2;
+
+	pid = fork();
+	if (pid < 0)
+		return 3;
+	if (pid == 0) {
+		if (getpid() != 2 || getppid() != 1)
+			_exit(1);
+		_exit(0);
+	}
+	if (pid != 2)
+		return 4;
+	if (wait_for_success(pid) < 0)
+		return 5;
+
+	return 0;
+}
+
+FN_TEST(clone)
+{
+	pid_t pid;
+
+	TEST_ERRNO(syscall(SYS_clone,
+			   CLONE_NEWPID | CLONE_THREAD | CLONE_SIGHAND |
+				   CLONE_VM | SIGCHLD,
+			   0, 0, 0, 0),
+		   EINVAL);
+
+	pid = TEST_SUCC(syscall(SYS_clone, CLONE_NEWPID | SIGCHLD, 0, 0, 0, 0));
+	if (pid == 0)
+		_exit(check_first_process());
+
+	TEST_RES(pid, _ret > 1);
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
+
+FN_TEST(unshare)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		pid_t self = getpid();
+		pid_t child;
+
+		if (unshare(CLONE_NEWPID) < 0)
+			_exit(1);
+		// The caller itself does not move to the new namespace.
+		if (getpid() != self)
+			_exit(2);
+
+		child = fork();
+		if (child < 0)
+			_exit(3);
+		if (child == 0) {
+			if (getpid() != 1 || getppid() != 0)
+				_exit(1);
+			_exit(0);
+		}
+		if (wait_for_success(child) < 0)
+			_exit(4);
+
+		// No more processes can be created after the init process
+		// of the namespace exits.
+		if (fork() >= 0 || errno != ENOMEM)
+			_exit(5);
+
+		_exit(0);
+	}
+
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
+
+static volatile sig_atomic_t sender_pid = -1;
+
+static void record_sender(int sig, siginfo_t *info, void *ucontext)
+{
+	sender_pid = info->si_pid;
+}
+
+static int check_signal_sender(int ready_fd)
+{
+	struct sigaction action = { .sa_sigaction = record_sender,
+				    .sa_flags = SA_SIGINFO };
+	sigset_t mask, old_mask;
+
+	sigemptyset(&mask);
+	sigaddset(&mask, SIGUSR1);
+	if (sigaction(SIGUSR1, &action, NULL) < 0 ||
+	    sigprocmask(SIG_BLOCK, &mask, &old_mask) < 0)
+		return 1;
+
+	// The sender in the namespace is seen with its PID in the namespace.
+	if (kill(getpid(), SIGUSR1) < 0)
+		return 2;
+	while (sender_pid < 0)
+		sigsuspend(&old_mask);
+	if (sender_pid != 1)
+		return 3;
+
+	// The sender outside the namespace is seen as PID 0.
+	sender_pid = -1;
+	if (write(ready_fd, "x", 1) != 1)
+		return 4;
+	while (sender_pid < 0)
+		sigsuspend(&old_mask);
+	if (sender_pid != 0)
+		return 5;
+
+	return 0;
+}
+
+FN_TEST(signal_sender)
+{
+	int ready_pipe[2];
+	pid_t pid;
+	char buf;
+
+	TEST_SUCC(pipe(ready_pipe));
+
+	pid = TEST_SUCC(syscall(SYS_clone, CLONE_NEWPID | SIGCHLD, 0, 0, 0, 0));
+	if (pid == 0) {
+		close(ready_pipe[0]);
+		_exit(check_signal_sender(ready_pipe[1]));
+	}
+
+	TEST_SUCC(close(ready_pipe[1]));
+	TEST_RES(read(ready_pipe[0], &buf, 1), _ret == 1);
+	TEST_SUCC(kill(pid, SIGUSR1));
+	TEST_SUCC(wait_for_success(pid));
+	TEST_SUCC(close(ready_pipe[0]));
+}
+END_TEST()
+
+FN_TEST(ns_file)
+{
+	struct stat parent_stat, child_stat;
+	int pipe_fds[2];
+	char path[64];
+	char buf;
+	pid_t pid;
+
+	TEST_SUCC(stat("/proc/self/ns/pid", &parent_stat));
+
+	TEST_SUCC(pipe(pipe_fds));
+	pid = TEST_SUCC(syscall(SYS_clone, CLONE_NEWPID | SIGCHLD, 0, 0, 0, 0));
+	if (pid == 0) {
+		close(pipe_fds[1]);
+		if (read(pipe_fds[0], &buf, 1) < 0)
+			_exit(1);
+		_exit(0);
+	}
+	TEST_SUCC(close(pipe_fds[0]));
+
+	snprintf(path, sizeof(path), "/proc/%d/ns/pid", pid);
+	TEST_SUCC(stat(path, &child_stat));
+	TEST_RES(child_stat.st_ino, _ret != parent_stat.st_ino);
+
+	TEST_SUCC(close(pipe_fds[1]));
+	TEST_SUCC(wait_for_success(pid));
+}
+END_TEST()
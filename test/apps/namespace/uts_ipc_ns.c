@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/sem.h>
+#include <sys/syscall.h>
+#include <sys/utsname.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define HOSTNAME "uts-ns-test"
+#define SEM_KEY 0x5a5a
+
+static int wait_for_success(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0)
+		return -1;
+	return 0;
+}
+
+static int hostname_is(const char *hostname)
+{
+	struct utsname uts;
+
+	if (uname(&uts) < 0)
+		return 0;
+	return strcmp(uts.nodename, hostname) == 0;
+}
+
+static struct utsname parent_uts;
+
+FN_SETUP(uname)
+{
+	CHECK(uname(&parent_uts));
+}
+END_SETUP()
+
+FN_TEST(sethostname)
+{
+	char long_name[65];
+	pid_t pid;
+
+	memset(long_name, 'a', sizeof(long_name));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		if (unshare(CLONE_NEWUTS) < 0)
+			_exit(1);
+		if (sethostname(HOSTNAME, strlen(HOSTNAME)) < 0)
+			_exit(2);
+		if (!hostname_is(HOSTNAME))
+			_exit(3);
+		if (sethostname(long_name, sizeof(long_name)) >= 0 ||
+		    errno != EINVAL)
+			_exit(4);
+		if (setdomainname(HOSTNAME, strlen(HOSTNAME)) < 0)
+			_exit(5);
+		_exit(0);
+	}
+
+	TEST_SUCC(wait_for_success(pid));
+	TEST_RES(hostname_is(parent_uts.nodename), _ret);
+}
+END_TEST()
+
+FN_TEST(setns)
+{
+	int ready_pipe[2], exit_pipe[2];
+	char buf, path[64];
+	pid_t owner, joiner;
+	int fd;
+
+	TEST_SUCC(pipe(ready_pipe));
+	TEST_SUCC(pipe(exit_pipe));
+
+	// The owner creates a new UTS namespace and keeps it alive.
+	owner = TEST_SUCC(fork());
+	if (owner == 0) {
+		close(ready_pipe[0]);
+		close(exit_pipe[1]);
+		if (unshare(CLONE_NEWUTS) < 0)
+			_exit(1);
+		if (sethostname(HOSTNAME, strlen(HOSTNAME)) < 0)
+			_exit(2);
+		if (write(ready_pipe[1], "x", 1) != 1)
+			_exit(3);
+		if (read(exit_pipe[0], &buf, 1) < 0)
+			_exit(4);
+		_exit(0);
+	}
+	TEST_SUCC(close(ready_pipe[1]));
+	TEST_SUCC(close(exit_pipe[0]));
+	TEST_RES(read(ready_pipe[0], &buf, 1), _ret == 1);
+
+	snprintf(path, sizeof(path), "/proc/%d/ns/uts", owner);
+	fd = TEST_SUCC(open(path, O_RDONLY));
+	TEST_ERRNO(setns(fd, CLONE_NEWIPC), EINVAL);
+
+	// The joiner joins the UTS namespace of the owner.
+	joiner = TEST_SUCC(fork());
+	if (joiner == 0) {
+		if (setns(fd, CLONE_NEWUTS) < 0)
+			_exit(1);
+		if (!hostname_is(HOSTNAME))
+			_exit(2);
+		_exit(0);
+	}
+	TEST_SUCC(wait_for_success(joiner));
+	TEST_RES(hostname_is(parent_uts.nodename), _ret);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(close(exit_pipe[1]));
+	TEST_SUCC(close(ready_pipe[0]));
+	TEST_SUCC(wait_for_success(owner));
+}
+END_TEST()
+
+FN_TEST(ipc)
+{
+	int semid;
+	pid_t pid;
+
+	TEST_ERRNO(syscall(SYS_clone, CLONE_NEWIPC | CLONE_SYSVSEM | SIGCHLD,
+			   0, 0, 0, 0),
+		   EINVAL);
+
+	semid = TEST_SUCC(semget(SEM_KEY, 1, IPC_CREAT | IPC_EXCL | 0600));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		int child_semid;
+
+		if (semget(SEM_KEY, 1, 0) != semid)
+			_exit(1);
+		if (unshare(CLONE_NEWIPC) < 0)
+			_exit(2);
+		// The semaphore set is not visible in the new namespace.
+		if (semget(SEM_KEY, 1, 0) >= 0 || errno != ENOENT)
+			_exit(3);
+		child_semid = semget(SEM_KEY, 1, IPC_CREAT | IPC_EXCL | 0600);
+		if (child_semid < 0)
+			_exit(4);
+		if (semctl(child_semid, 0, IPC_RMID) < 0)
+			_exit(5);
+		_exit(0);
+	}
+
+	TEST_SUCC(wait_for_success(pid));
+	TEST_RES(semget(SEM_KEY, 1, 0), _ret == semid);
+	TEST_SUCC(semctl(semid, 0, IPC_RMID));
+}
+END_TEST()
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#include <unistd.h>
+#include <sys/socket.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+
+#include "test.h"
+
+#define TCP_PORT htons(0x1236)
+#define UDP_PORT htons(0x1237)
+#define MAPPED_PORT htons(0x1238)
+
+static struct sockaddr_in6 any_addr;
+static struct sockaddr_in6 lo_addr;
+static struct sockaddr_in6 mapped_addr;
+
+FN_SETUP(general)
+{
+	any_addr.sin6_family = AF_INET6;
+	any_addr.sin6_addr = in6addr_any;
+
+	lo_addr.sin6_family = AF_INET6;
+	lo_addr.sin6_addr = in6addr_loopback;
+
+	mapped_addr.sin6_family = AF_INET6;
+	CHECK(inet_pton(AF_INET6, "::ffff:127.0.0.1", &mapped_addr.sin6_addr));
+}
+END_SETUP()
+
+FN_TEST(getsockname_unbound)
+{
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_STREAM, 0));
+	TEST_RES(getsockname(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == 0 &&
+			 IN6_IS_ADDR_UNSPECIFIED(&saddr.sin6_addr));
+	TEST_SUCC(close(sk));
+
+	addrlen = sizeof(saddr);
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	TEST_RES(getsockname(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == 0 &&
+			 IN6_IS_ADDR_UNSPECIFIED(&saddr.sin6_addr));
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(bind_any)
+{
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_STREAM, 0));
+	any_addr.sin6_port = htons(0x1239);
+	TEST_SUCC(bind(sk, (struct sockaddr *)&any_addr, sizeof(any_addr)));
+	TEST_RES(getsockname(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == htons(0x1239) &&
+			 IN6_IS_ADDR_UNSPECIFIED(&saddr.sin6_addr));
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(bind_bad_family)
+{
+	int sk;
+
+	// An `AF_INET` socket cannot be bound to an IPv6 address.
+	sk = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+	lo_addr.sin6_port = htons(0x123a);
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&lo_addr, sizeof(lo_addr)),
+		   EAFNOSUPPORT);
+	TEST_SUCC(close(sk));
+
+	// The address is too short to be an IPv6 address.
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&lo_addr,
+			sizeof(struct sockaddr_in)),
+		   EINVAL);
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(v6only)
+{
+	int sk, sk4;
+	int val;
+	socklen_t len = sizeof(val);
+
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+
+	TEST_RES(getsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &val, &len),
+		 len == sizeof(val) && val == 0);
+
+	val = 1;
+	TEST_SUCC(setsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &val, sizeof(val)));
+	TEST_RES(getsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &val, &len),
+		 len == sizeof(val) && val == 1);
+
+	// IPv4-mapped addresses are not allowed for IPv6-only sockets.
+	mapped_addr.sin6_port = htons(0x123b);
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&mapped_addr,
+			sizeof(mapped_addr)),
+		   EINVAL);
+
+	lo_addr.sin6_port = htons(0x123b);
+	TEST_SUCC(bind(sk, (struct sockaddr *)&lo_addr, sizeof(lo_addr)));
+
+	// The option cannot be changed after the socket is bound.
+	val = 0;
+	TEST_ERRNO(setsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &val, sizeof(val)),
+		   EINVAL);
+
+	TEST_SUCC(close(sk));
+
+	// The option is not available for `AF_INET` sockets.
+	sk4 = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+	TEST_ERRNO(getsockopt(sk4, IPPROTO_IPV6, IPV6_V6ONLY, &val, &len),
+		   ENOPROTOOPT);
+	TEST_SUCC(close(sk4));
+}
+END_TEST()
+
+FN_TEST(tcp_loopback)
+{
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk_listen, sk_client, sk_accepted;
+	char buf[6];
+
+	sk_listen = TEST_SUCC(socket(AF_INET6, SOCK_STREAM, 0));
+	lo_addr.sin6_port = TCP_PORT;
+	TEST_SUCC(bind(sk_listen, (struct sockaddr *)&lo_addr, sizeof(lo_addr)));
+	TEST_SUCC(listen(sk_listen, 1));
+
+	sk_client = TEST_SUCC(socket(AF_INET6, SOCK_STREAM, 0));
+	TEST_SUCC(
+		connect(sk_client, (struct sockaddr *)&lo_addr, sizeof(lo_addr)));
+
+	sk_accepted = TEST_RES(accept(sk_listen, (struct sockaddr *)&saddr,
+				      &addrlen),
+			       addrlen == sizeof(saddr) &&
+				       saddr.sin6_family == AF_INET6 &&
+				       IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	addrlen = sizeof(saddr);
+	TEST_RES(getpeername(sk_client, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_port == TCP_PORT &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(send(sk_client, "hello", 6, 0), _ret == 6);
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0),
+		 _ret == 6 && strcmp(buf, "hello") == 0);
+
+	TEST_SUCC(close(sk_accepted));
+	TEST_SUCC(close(sk_client));
+	TEST_SUCC(close(sk_listen));
+}
+END_TEST()
+
+FN_TEST(tcp_mapped)
+{
+	struct sockaddr_in6 saddr;
+	struct sockaddr_in addr4;
+	socklen_t addrlen = sizeof(saddr);
+	int sk_listen, sk_client, sk_accepted;
+
+	sk_listen = TEST_SUCC(socket(AF_INET6, SOCK_STREAM, 0));
+	mapped_addr.sin6_port = MAPPED_PORT;
+	TEST_SUCC(bind(sk_listen, (struct sockaddr *)&mapped_addr,
+		       sizeof(mapped_addr)));
+	TEST_SUCC(listen(sk_listen, 1));
+
+	// An `AF_INET` client can connect to the IPv4-mapped listener.
+	sk_client = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	addr4.sin_family = AF_INET;
+	addr4.sin_port = MAPPED_PORT;
+	addr4.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+	TEST_SUCC(connect(sk_client, (struct sockaddr *)&addr4, sizeof(addr4)));
+
+	// The peer address is reported as an IPv4-mapped IPv6 address.
+	sk_accepted = TEST_RES(accept(sk_listen, (struct sockaddr *)&saddr,
+				      &addrlen),
+			       addrlen == sizeof(saddr) &&
+				       saddr.sin6_family == AF_INET6 &&
+				       IN6_IS_ADDR_V4MAPPED(&saddr.sin6_addr));
+
+	addrlen = sizeof(saddr);
+	TEST_RES(getsockname(sk_accepted, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_port == MAPPED_PORT &&
+			 memcmp(&saddr.sin6_addr, &mapped_addr.sin6_addr,
+				sizeof(saddr.sin6_addr)) == 0);
+
+	TEST_SUCC(close(sk_accepted));
+	TEST_SUCC(close(sk_client));
+	TEST_SUCC(close(sk_listen));
+}
+END_TEST()
+
+FN_TEST(udp_loopback)
+{
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk_server, sk_client;
+	char buf[6];
+
+	sk_server = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	lo_addr.sin6_port = UDP_PORT;
+	TEST_SUCC(bind(sk_server, (struct sockaddr *)&lo_addr, sizeof(lo_addr)));
+
+	sk_client = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	TEST_RES(sendto(sk_client, "hello", 6, 0, (struct sockaddr *)&lo_addr,
+			sizeof(lo_addr)),
+		 _ret == 6);
+
+	TEST_RES(recvfrom(sk_server, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&saddr, &addrlen),
+		 _ret == 6 && strcmp(buf, "hello") == 0 &&
+			 addrlen == sizeof(saddr) &&
+			 saddr.sin6_family == AF_INET6 &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_SUCC(close(sk_client));
+	TEST_SUCC(close(sk_server));
+}
+END_TEST()
@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#include <unistd.h>
+#include <sys/socket.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include <net/if.h>
+#include <linux/netlink.h>
+#include <linux/rtnetlink.h>
+
+#include "test.h"
+
+#define BUF_LEN 8192
+
+// The index of the virtio-net iface, which is the first iface.
+#define VIRTIO_IFINDEX 1
+
+static int sk;
+static unsigned int seq;
+static char buf[BUF_LEN];
+
+struct addr_request {
+	struct nlmsghdr hdr;
+	struct ifaddrmsg ifa;
+	struct rtattr rta;
+	struct in_addr addr;
+};
+
+FN_SETUP(general)
+{
+	struct sockaddr_nl addr = { .nl_family = AF_NETLINK };
+
+	sk = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+	CHECK(bind(sk, (struct sockaddr *)&addr, sizeof(addr)));
+}
+END_SETUP()
+
+static int send_dump_request(unsigned short type, unsigned char family)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct rtgenmsg gen;
+	} req = {
+		.hdr = {
+			.nlmsg_len = NLMSG_LENGTH(sizeof(struct rtgenmsg)),
+			.nlmsg_type = type,
+			.nlmsg_flags = NLM_F_REQUEST | NLM_F_DUMP,
+			.nlmsg_seq = ++seq,
+		},
+		.gen = { .rtgen_family = family },
+	};
+
+	return send(sk, &req, req.hdr.nlmsg_len, 0);
+}
+
+// Receives all segments of a dump. Returns the number of segments before `NLMSG_DONE`, or -1 if
+// any segment is unexpected. `check` is called on each segment and returns whether it matches.
+static int recv_dump(int (*check)(struct nlmsghdr *), int *matched)
+{
+	struct nlmsghdr *hdr;
+	int len, count = 0;
+
+	*matched = 0;
+
+	for (;;) {
+		len = recv(sk, buf, sizeof(buf), 0);
+		if (len < 0)
+			return -1;
+
+		for (hdr = (struct nlmsghdr *)buf; NLMSG_OK(hdr, len);
+		     hdr = NLMSG_NEXT(hdr, len)) {
+			if (hdr->nlmsg_seq != seq)
+				return -1;
+			if (hdr->nlmsg_type == NLMSG_DONE)
+				return count;
+			if (hdr->nlmsg_type == NLMSG_ERROR ||
+			    !(hdr->nlmsg_flags & NLM_F_MULTI))
+				return -1;
+
+			++count;
+			if (check(hdr))
+				*matched = 1;
+		}
+	}
+}
+
+// Receives an acknowledgment and returns the error code in it.
+static int recv_ack(void)
+{
+	struct nlmsghdr *hdr = (struct nlmsghdr *)buf;
+	struct nlmsgerr *err = NLMSG_DATA(hdr);
+	int len;
+
+	len = recv(sk, buf, sizeof(buf), 0);
+	if (len < 0)
+		return 1;
+	if (!NLMSG_OK(hdr, len) || hdr->nlmsg_type != NLMSG_ERROR ||
+	    hdr->nlmsg_seq != seq)
+		return 1;
+
+	return err->error;
+}
+
+FN_TEST(getsockname)
+{
+	struct sockaddr_nl addr;
+	socklen_t addrlen = sizeof(addr);
+
+	TEST_RES(getsockname(sk, (struct sockaddr *)&addr, &addrlen),
+		 addrlen == sizeof(addr) && addr.nl_family == AF_NETLINK &&
+			 addr.nl_pid != 0 && addr.nl_groups == 0);
+}
+END_TEST()
+
+static int is_lo_link(struct nlmsghdr *hdr)
+{
+	struct ifinfomsg *ifi = NLMSG_DATA(hdr);
+	struct rtattr *rta = IFLA_RTA(ifi);
+	int len = IFLA_PAYLOAD(hdr);
+
+	if (hdr->nlmsg_type != RTM_NEWLINK)
+		return 0;
+
+	for (; RTA_OK(rta, len); rta = RTA_NEXT(rta, len))
+		if (rta->rta_type == IFLA_IFNAME &&
+		    strcmp(RTA_DATA(rta), "lo") == 0)
+			return (ifi->ifi_flags & IFF_LOOPBACK) != 0;
+
+	return 0;
+}
+
+FN_TEST(dump_links)
+{
+	int matched;
+
+	TEST_SUCC(send_dump_request(RTM_GETLINK, AF_PACKET));
+	TEST_RES(recv_dump(is_lo_link, &matched), _ret >= 2 && matched);
+}
+END_TEST()
+
+static int is_lo_addr(struct nlmsghdr *hdr)
+{
+	struct ifaddrmsg *ifa = NLMSG_DATA(hdr);
+	struct rtattr *rta = IFA_RTA(ifa);
+	int len = IFA_PAYLOAD(hdr);
+
+	if (hdr->nlmsg_type != RTM_NEWADDR || ifa->ifa_family != AF_INET)
+		return 0;
+
+	for (; RTA_OK(rta, len); rta = RTA_NEXT(rta, len))
+		if (rta->rta_type == IFA_ADDRESS &&
+		    *(in_addr_t *)RTA_DATA(rta) == htonl(INADDR_LOOPBACK))
+			return ifa->ifa_prefixlen == 8;
+
+	return 0;
+}
+
+FN_TEST(dump_addrs)
+{
+	int matched;
+
+	TEST_SUCC(send_dump_request(RTM_GETADDR, AF_INET));
+	TEST_RES(recv_dump(is_lo_addr, &matched), _ret >= 2 && matched);
+}
+END_TEST()
+
+static int is_route(struct nlmsghdr *hdr)
+{
+	struct rtmsg *rtm = NLMSG_DATA(hdr);
+
+	return hdr->nlmsg_type == RTM_NEWROUTE && rtm->rtm_family == AF_INET &&
+	       rtm->rtm_table == RT_TABLE_MAIN;
+}
+
+FN_TEST(dump_routes)
+{
+	int matched;
+
+	TEST_SUCC(send_dump_request(RTM_GETROUTE, AF_INET));
+	TEST_RES(recv_dump(is_route, &matched), _ret >= 1 && matched);
+}
+END_TEST()
+
+static int send_addr_request(unsigned short type, unsigned short flags,
+			     const char *addr)
+{
+	struct addr_request req = {
+		.hdr = {
+			.nlmsg_len = sizeof(struct addr_request),
+			.nlmsg_type = type,
+			.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK | flags,
+			.nlmsg_seq = ++seq,
+		},
+		.ifa = {
+			.ifa_family = AF_INET,
+			.ifa_prefixlen = 24,
+			.ifa_index = VIRTIO_IFINDEX,
+		},
+		.rta = {
+			.rta_len = RTA_LENGTH(sizeof(struct in_addr)),
+			.rta_type = IFA_LOCAL,
+		},
+	};
+
+	if (inet_pton(AF_INET, addr, &req.addr) != 1)
+		return -1;
+
+	return send(sk, &req, sizeof(req), 0);
+}
+
+FN_TEST(new_and_del_addr)
+{
+	TEST_SUCC(send_addr_request(RTM_NEWADDR, NLM_F_CREATE | NLM_F_EXCL,
+				    "10.0.2.100"));
+	TEST_RES(recv_ack(), _ret == 0);
+
+	TEST_SUCC(send_addr_request(RTM_NEWADDR, NLM_F_CREATE | NLM_F_EXCL,
+				    "10.0.2.100"));
+	TEST_RES(recv_ack(), _ret == -EEXIST);
+
+	TEST_SUCC(send_addr_request(RTM_DELADDR, 0, "10.0.2.100"));
+	TEST_RES(recv_ack(), _ret == 0);
+
+	TEST_SUCC(send_addr_request(RTM_DELADDR, 0, "10.0.2.100"));
+	TEST_RES(recv_ack(), _ret == -EADDRNOTAVAIL);
+}
+END_TEST()
+
+FN_TEST(unknown_type)
+{
+	struct nlmsghdr hdr = {
+		.nlmsg_len = NLMSG_LENGTH(0),
+		.nlmsg_type = RTM_MAX + 1,
+		.nlmsg_flags = NLM_F_REQUEST,
+		.nlmsg_seq = ++seq,
+	};
+
+	TEST_RES(send(sk, &hdr, hdr.nlmsg_len, 0), _ret == hdr.nlmsg_len);
+	TEST_RES(recv_ack(), _ret == -EOPNOTSUPP);
+}
+END_TEST()
+
+FN_TEST(send_to_user)
+{
+	struct sockaddr_nl addr = { .nl_family = AF_NETLINK, .nl_pid = 1234 };
+	struct nlmsghdr hdr = {
+		.nlmsg_len = NLMSG_LENGTH(0),
+		.nlmsg_type = NLMSG_NOOP,
+		.nlmsg_flags = NLM_F_REQUEST,
+	};
+
+	// No socket in user space is bound to the port.
+	TEST_ERRNO(sendto(sk, &hdr, hdr.nlmsg_len, 0, (struct sockaddr *)&addr,
+			  sizeof(addr)),
+		   ECONNREFUSED);
+
+	addr.nl_pid = 0;
+	TEST_SUCC(connect(sk, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_RES(send(sk, &hdr, hdr.nlmsg_len, 0), _ret == hdr.nlmsg_len);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk));
+}
+END_SETUP()
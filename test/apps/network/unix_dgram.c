@@ -0,0 +1,363 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <sys/socket.h>
+#include <sys/un.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <stddef.h>
+
+#include "test.h"
+
+#define PATH_OFFSET offsetof(struct sockaddr_un, sun_path)
+
+#define SERVER_PATH "/tmp/unix_dgram_server"
+#define ABSTRACT_NAME "\0unix_dgram_abstract"
+#define CONNECTED_NAME "\0unix_dgram_connected"
+
+static struct sockaddr_un path_addr = {
+	.sun_family = AF_UNIX,
+	.sun_path = SERVER_PATH,
+};
+static socklen_t path_addrlen = PATH_OFFSET + sizeof(SERVER_PATH);
+
+static struct sockaddr_un abstract_addr = {
+	.sun_family = AF_UNIX,
+	.sun_path = ABSTRACT_NAME,
+};
+static socklen_t abstract_addrlen = PATH_OFFSET + sizeof(ABSTRACT_NAME) - 1;
+
+static struct sockaddr_un connected_addr = {
+	.sun_family = AF_UNIX,
+	.sun_path = CONNECTED_NAME,
+};
+static socklen_t connected_addrlen = PATH_OFFSET + sizeof(CONNECTED_NAME) - 1;
+
+static int sk_path;
+static int sk_abstract;
+static int sk_connected;
+static int sk_unbound;
+static int sk_pair[2];
+
+FN_SETUP(general)
+{
+	sk_path = CHECK(socket(AF_UNIX, SOCK_DGRAM, 0));
+	CHECK(bind(sk_path, (struct sockaddr *)&path_addr, path_addrlen));
+
+	sk_abstract = CHECK(socket(AF_UNIX, SOCK_DGRAM, 0));
+	CHECK(bind(sk_abstract, (struct sockaddr *)&abstract_addr,
+		   abstract_addrlen));
+
+	sk_connected = CHECK(socket(AF_UNIX, SOCK_DGRAM, 0));
+	CHECK(bind(sk_connected, (struct sockaddr *)&connected_addr,
+		   connected_addrlen));
+	CHECK(connect(sk_connected, (struct sockaddr *)&path_addr,
+		      path_addrlen));
+
+	sk_unbound = CHECK(socket(AF_UNIX, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+
+	CHECK(socketpair(AF_UNIX, SOCK_DGRAM, 0, sk_pair));
+}
+END_SETUP()
+
+FN_TEST(send_to_path)
+{
+	char buf[16];
+	struct sockaddr_un addr;
+	socklen_t addrlen = sizeof(addr);
+
+	TEST_RES(sendto(sk_abstract, "hello", 5, 0,
+			(struct sockaddr *)&path_addr, path_addrlen),
+		 _ret == 5);
+
+	TEST_RES(recvfrom(sk_path, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&addr, &addrlen),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0 &&
+			 addrlen == abstract_addrlen &&
+			 memcmp(addr.sun_path, ABSTRACT_NAME,
+				sizeof(ABSTRACT_NAME) - 1) == 0);
+}
+END_TEST()
+
+FN_TEST(send_to_abstract)
+{
+	char buf[16];
+	struct sockaddr_un addr;
+	socklen_t addrlen = sizeof(addr);
+
+	TEST_RES(sendto(sk_unbound, "world", 5, 0,
+			(struct sockaddr *)&abstract_addr, abstract_addrlen),
+		 _ret == 5);
+
+	// The sender is unnamed.
+	TEST_RES(recvfrom(sk_abstract, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&addr, &addrlen),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0 &&
+			 addrlen == sizeof(sa_family_t));
+}
+END_TEST()
+
+FN_TEST(send_connected)
+{
+	char buf[16];
+	struct sockaddr_un addr;
+	socklen_t addrlen = sizeof(addr);
+
+	TEST_RES(send(sk_connected, "abc", 3, 0), _ret == 3);
+	TEST_RES(recvfrom(sk_path, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&addr, &addrlen),
+		 _ret == 3 && memcmp(buf, "abc", 3) == 0 &&
+			 addrlen == connected_addrlen);
+
+	TEST_RES(getpeername(sk_connected, (struct sockaddr *)&addr, &addrlen),
+		 addrlen == path_addrlen &&
+			 strcmp(addr.sun_path, SERVER_PATH) == 0);
+
+	// Only the connected peer can send datagrams to a connected socket.
+	TEST_ERRNO(sendto(sk_unbound, "x", 1, 0,
+			  (struct sockaddr *)&connected_addr,
+			  connected_addrlen),
+		   EPERM);
+}
+END_TEST()
+
+FN_TEST(message_boundaries)
+{
+	char buf[16];
+
+	TEST_RES(sendto(sk_unbound, "abc", 3, 0, (struct sockaddr *)&path_addr,
+			path_addrlen),
+		 _ret == 3);
+	TEST_RES(sendto(sk_unbound, "defgh", 5, 0,
+			(struct sockaddr *)&path_addr, path_addrlen),
+		 _ret == 5);
+
+	// Truncated datagrams are discarded.
+	TEST_RES(recv(sk_path, buf, 2, 0),
+		 _ret == 2 && memcmp(buf, "ab", 2) == 0);
+	TEST_RES(recv(sk_path, buf, 2, MSG_PEEK | MSG_TRUNC), _ret == 5);
+	TEST_RES(recv(sk_path, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "defgh", 5) == 0);
+
+	TEST_ERRNO(recv(sk_path, buf, sizeof(buf), MSG_DONTWAIT), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(send_errors)
+{
+	struct sockaddr_un addr = {
+		.sun_family = AF_UNIX,
+		.sun_path = "\0unix_dgram_nonexistent",
+	};
+
+	TEST_ERRNO(send(sk_unbound, "x", 1, 0), ENOTCONN);
+	TEST_ERRNO(sendto(sk_unbound, "x", 1, 0, (struct sockaddr *)&addr,
+			  PATH_OFFSET + sizeof("\0unix_dgram_nonexistent") - 1),
+		   ECONNREFUSED);
+}
+END_TEST()
+
+FN_TEST(socketpair_dgram)
+{
+	char buf[16];
+	struct sockaddr_un addr;
+	socklen_t addrlen = sizeof(addr);
+
+	TEST_RES(write(sk_pair[0], "ping", 4), _ret == 4);
+	TEST_RES(write(sk_pair[0], "pong", 4), _ret == 4);
+	TEST_RES(read(sk_pair[1], buf, sizeof(buf)),
+		 _ret == 4 && memcmp(buf, "ping", 4) == 0);
+	TEST_RES(read(sk_pair[1], buf, sizeof(buf)),
+		 _ret == 4 && memcmp(buf, "pong", 4) == 0);
+
+	TEST_RES(getpeername(sk_pair[0], (struct sockaddr *)&addr, &addrlen),
+		 addrlen == sizeof(sa_family_t));
+}
+END_TEST()
+
+static int send_fd(int sk, int fd)
+{
+	char cbuf[CMSG_SPACE(sizeof(int))] = { 0 };
+	struct iovec iov = { .iov_base = "F", .iov_len = 1 };
+	struct msghdr msg = {
+		.msg_iov = &iov,
+		.msg_iovlen = 1,
+		.msg_control = cbuf,
+		.msg_controllen = sizeof(cbuf),
+	};
+	struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg);
+
+	cmsg->cmsg_level = SOL_SOCKET;
+	cmsg->cmsg_type = SCM_RIGHTS;
+	cmsg->cmsg_len = CMSG_LEN(sizeof(int));
+	memcpy(CMSG_DATA(cmsg), &fd, sizeof(int));
+
+	return sendmsg(sk, &msg, 0);
+}
+
+FN_TEST(scm_rights)
+{
+	int pipe_fds[2];
+	int received_fd;
+	char buf[16];
+	char cbuf[CMSG_SPACE(sizeof(int))];
+	struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+	struct msghdr msg = {
+		.msg_iov = &iov,
+		.msg_iovlen = 1,
+		.msg_control = cbuf,
+		.msg_controllen = sizeof(cbuf),
+	};
+	struct cmsghdr *cmsg;
+
+	TEST_SUCC(pipe(pipe_fds));
+	TEST_RES(send_fd(sk_pair[0], pipe_fds[0]), _ret == 1);
+	TEST_SUCC(close(pipe_fds[0]));
+
+	TEST_RES(recvmsg(sk_pair[1], &msg, MSG_CMSG_CLOEXEC),
+		 _ret == 1 && buf[0] == 'F' && !(msg.msg_flags & MSG_CTRUNC));
+
+	cmsg = CMSG_FIRSTHDR(&msg);
+	TEST_RES(0, cmsg != NULL && cmsg->cmsg_level == SOL_SOCKET &&
+			    cmsg->cmsg_type == SCM_RIGHTS &&
+			    cmsg->cmsg_len == CMSG_LEN(sizeof(int)));
+	memcpy(&received_fd, CMSG_DATA(cmsg), sizeof(int));
+
+	TEST_RES(fcntl(received_fd, F_GETFD), _ret == FD_CLOEXEC);
+
+	// The received file descriptor refers to the read end of the pipe.
+	TEST_RES(write(pipe_fds[1], "pipe", 4), _ret == 4);
+	TEST_RES(read(received_fd, buf, sizeof(buf)),
+		 _ret == 4 && memcmp(buf, "pipe", 4) == 0);
+
+	TEST_SUCC(close(received_fd));
+	TEST_SUCC(close(pipe_fds[1]));
+}
+END_TEST()
+
+FN_TEST(scm_rights_truncated)
+{
+	char buf[16];
+	char cbuf[sizeof(struct cmsghdr)];
+	struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+	struct msghdr msg = {
+		.msg_iov = &iov,
+		.msg_iovlen = 1,
+		.msg_control = cbuf,
+		.msg_controllen = sizeof(cbuf),
+	};
+
+	TEST_RES(send_fd(sk_pair[0], STDIN_FILENO), _ret == 1);
+
+	// The control buffer is too small to hold any file descriptors.
+	TEST_RES(recvmsg(sk_pair[1], &msg, 0),
+		 _ret == 1 && (msg.msg_flags & MSG_CTRUNC) &&
+			 CMSG_FIRSTHDR(&msg) == NULL);
+}
+END_TEST()
+
+FN_TEST(scm_credentials)
+{
+	int one = 1, zero = 0;
+	char buf[16];
+	char cbuf[CMSG_SPACE(sizeof(struct ucred))];
+	struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+	struct msghdr msg = {
+		.msg_iov = &iov,
+		.msg_iovlen = 1,
+		.msg_control = cbuf,
+		.msg_controllen = sizeof(cbuf),
+	};
+	struct cmsghdr *cmsg;
+	struct ucred cred;
+
+	TEST_SUCC(setsockopt(sk_pair[1], SOL_SOCKET, SO_PASSCRED, &one,
+			     sizeof(one)));
+
+	TEST_RES(send(sk_pair[0], "cred", 4, 0), _ret == 4);
+	TEST_RES(recvmsg(sk_pair[1], &msg, 0),
+		 _ret == 4 && msg.msg_flags == 0);
+
+	cmsg = CMSG_FIRSTHDR(&msg);
+	TEST_RES(0, cmsg != NULL && cmsg->cmsg_level == SOL_SOCKET &&
+			    cmsg->cmsg_type == SCM_CREDENTIALS &&
+			    cmsg->cmsg_len == CMSG_LEN(sizeof(struct ucred)));
+	memcpy(&cred, CMSG_DATA(cmsg), sizeof(cred));
+	TEST_RES(0, cred.pid == getpid() && cred.uid == getuid() &&
+			    cred.gid == getgid());
+
+	TEST_SUCC(setsockopt(sk_pair[1], SOL_SOCKET, SO_PASSCRED, &zero,
+			     sizeof(zero)));
+}
+END_TEST()
+
+FN_TEST(peer_cred)
+{
+	int sks[2];
+	struct ucred cred;
+	socklen_t credlen = sizeof(cred);
+
+	TEST_SUCC(socketpair(AF_UNIX, SOCK_STREAM, 0, sks));
+
+	TEST_RES(getsockopt(sks[0], SOL_SOCKET, SO_PEERCRED, &cred, &credlen),
+		 credlen == sizeof(cred) && cred.pid == getpid() &&
+			 cred.uid == geteuid() && cred.gid == getegid());
+
+	TEST_SUCC(close(sks[0]));
+	TEST_SUCC(close(sks[1]));
+}
+END_TEST()
+
+FN_TEST(scm_rights_stream)
+{
+	int sks[2];
+	char buf[16];
+	char cbuf[CMSG_SPACE(sizeof(int))];
+	struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+	struct msghdr msg = {
+		.msg_iov = &iov,
+		.msg_iovlen = 1,
+		.msg_control = cbuf,
+		.msg_controllen = sizeof(cbuf),
+	};
+	struct cmsghdr *cmsg;
+	int received_fd;
+
+	TEST_SUCC(socketpair(AF_UNIX, SOCK_STREAM, 0, sks));
+
+	TEST_RES(write(sks[0], "ab", 2), _ret == 2);
+	TEST_RES(send_fd(sks[0], STDOUT_FILENO), _ret == 1);
+	TEST_RES(write(sks[0], "cd", 2), _ret == 2);
+
+	// The read stops after the bytes that the file descriptor is attached to.
+	TEST_RES(recvmsg(sks[1], &msg, 0),
+		 _ret == 3 && memcmp(buf, "abF", 3) == 0);
+
+	cmsg = CMSG_FIRSTHDR(&msg);
+	TEST_RES(0, cmsg != NULL && cmsg->cmsg_type == SCM_RIGHTS);
+	memcpy(&received_fd, CMSG_DATA(cmsg), sizeof(int));
+	TEST_SUCC(close(received_fd));
+
+	msg.msg_controllen = sizeof(cbuf);
+	TEST_RES(recvmsg(sks[1], &msg, 0),
+		 _ret == 2 && memcmp(buf, "cd", 2) == 0 &&
+			 msg.msg_controllen == 0);
+
+	TEST_SUCC(close(sks[0]));
+	TEST_SUCC(close(sks[1]));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_path));
+	CHECK(close(sk_abstract));
+	CHECK(close(sk_connected));
+	CHECK(close(sk_unbound));
+	CHECK(close(sk_pair[0]));
+	CHECK(close(sk_pair[1]));
+
+	CHECK(unlink(SERVER_PATH));
+}
+END_SETUP()
@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <signal.h>
+#include <stddef.h>
+#include <sys/ptrace.h>
+#include <sys/syscall.h>
+#include <sys/user.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static volatile long shared_word = 0x1234;
+
+static pid_t spawn_tracee(void)
+{
+	pid_t pid = CHECK(fork());
+
+	if (pid == 0) {
+		CHECK(ptrace(PTRACE_TRACEME, 0, NULL, NULL));
+		CHECK(raise(SIGSTOP));
+		syscall(SYS_getppid);
+		_exit(shared_word == 0x5678 ? 0 : 1);
+	}
+
+	return pid;
+}
+
+FN_TEST(signal_delivery_stop)
+{
+	pid_t pid = spawn_tracee();
+	int status;
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	TEST_RES(ptrace(PTRACE_PEEKDATA, pid, &shared_word, NULL),
+		 _ret == 0x1234);
+	TEST_SUCC(ptrace(PTRACE_POKEDATA, pid, &shared_word, (void *)0x5678));
+
+	// The `SIGSTOP` is suppressed, so the tracee can continue to run.
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(syscall_stop)
+{
+	pid_t pid = spawn_tracee();
+	struct user_regs_struct regs;
+	int status;
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status));
+	TEST_SUCC(ptrace(PTRACE_SETOPTIONS, pid, NULL,
+			 (void *)PTRACE_O_TRACESYSGOOD));
+	TEST_SUCC(ptrace(PTRACE_POKEDATA, pid, &shared_word, (void *)0x5678));
+
+	// Skip the remaining syscalls in `raise` until `getppid` is entered.
+	do {
+		TEST_SUCC(ptrace(PTRACE_SYSCALL, pid, NULL, NULL));
+		TEST_RES(waitpid(pid, &status, 0),
+			 _ret == pid && WIFSTOPPED(status) &&
+				 WSTOPSIG(status) == (SIGTRAP | 0x80));
+		TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &regs));
+	} while (regs.orig_rax != SYS_getppid);
+	TEST_RES(regs.rax, _ret == -ENOSYS);
+
+	// The syscall exit.
+	TEST_SUCC(ptrace(PTRACE_SYSCALL, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == (SIGTRAP | 0x80));
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &regs));
+	TEST_RES(regs.rax, _ret == getpid());
+
+	TEST_SUCC(ptrace(PTRACE_DETACH, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(invalid_regs)
+{
+	pid_t pid = spawn_tracee();
+	struct user_regs_struct regs;
+	struct user_regs_struct bad_regs;
+	int status;
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status));
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &regs));
+
+	bad_regs = regs;
+	bad_regs.fs_base = 0x8000000000000000UL;
+	TEST_ERRNO(ptrace(PTRACE_SETREGS, pid, NULL, &bad_regs), EIO);
+	TEST_ERRNO(ptrace(PTRACE_POKEUSER, pid,
+			  offsetof(struct user_regs_struct, fs_base),
+			  (void *)0x8000000000000000UL),
+		   EIO);
+
+	// The registers are unchanged if any of them is invalid.
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &bad_regs));
+	TEST_RES(bad_regs.fs_base, _ret == regs.fs_base);
+	TEST_SUCC(ptrace(PTRACE_SETREGS, pid, NULL, &regs));
+
+	TEST_SUCC(ptrace(PTRACE_KILL, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+}
+END_TEST()
+
+static void exit_with_value(int sig, siginfo_t *info, void *ucontext)
+{
+	_exit(info->si_code == SI_QUEUE ? info->si_value.sival_int : 1);
+}
+
+FN_TEST(inject_signal)
+{
+	struct sigaction action = { .sa_sigaction = exit_with_value,
+				    .sa_flags = SA_SIGINFO };
+	siginfo_t info;
+	pid_t pid;
+	int status;
+
+	pid = CHECK(fork());
+	if (pid == 0) {
+		CHECK(sigaction(SIGUSR1, &action, NULL));
+		CHECK(ptrace(PTRACE_TRACEME, 0, NULL, NULL));
+		CHECK(raise(SIGSTOP));
+		_exit(1);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	// The signal number must not be truncated to a valid one.
+	TEST_ERRNO(ptrace(PTRACE_CONT, pid, NULL, (void *)(256 + SIGUSR1)),
+		   EIO);
+
+	// The signal information set by the tracer is delivered.
+	TEST_SUCC(ptrace(PTRACE_GETSIGINFO, pid, NULL, &info));
+	info.si_signo = SIGUSR1;
+	info.si_code = SI_QUEUE;
+	info.si_value.sival_int = 42;
+	TEST_SUCC(ptrace(PTRACE_SETSIGINFO, pid, NULL, &info));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, (void *)SIGUSR1));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 42);
+}
+END_TEST()
+
+FN_TEST(interrupt_and_listen)
+{
+	pid_t pid;
+	int fds[2];
+	int status;
+	char byte;
+
+	TEST_SUCC(pipe(fds));
+
+	pid = CHECK(fork());
+	if (pid == 0) {
+		CHECK(close(fds[1]));
+		// The read is interrupted by `PTRACE_INTERRUPT` and restarted.
+		CHECK_WITH(read(fds[0], &byte, 1), _ret == 1);
+		CHECK(raise(SIGSTOP));
+		_exit(byte == 'x' ? 4 : 1);
+	}
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(ptrace(PTRACE_SEIZE, pid, NULL, NULL));
+
+	TEST_SUCC(ptrace(PTRACE_INTERRUPT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 status >> 8 == (SIGTRAP | PTRACE_EVENT_STOP << 8));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(write(fds[1], "x", 1), _ret == 1);
+
+	// The signal-delivery-stop, and then the group-stop.
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) && status >> 8 == SIGSTOP);
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, (void *)SIGSTOP));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 status >> 8 == (SIGSTOP | PTRACE_EVENT_STOP << 8));
+
+	// The listening tracee cannot be inspected until it stops again.
+	TEST_SUCC(ptrace(PTRACE_LISTEN, pid, NULL, NULL));
+	TEST_ERRNO(ptrace(PTRACE_CONT, pid, NULL, NULL), ESRCH);
+	TEST_SUCC(ptrace(PTRACE_INTERRUPT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 status >> 8 == (SIGSTOP | PTRACE_EVENT_STOP << 8));
+
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 4);
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(tracee_exit)
+{
+	pid_t tracee, tracer;
+	int fds[2];
+	int status;
+	char byte;
+
+	TEST_SUCC(pipe(fds));
+
+	tracee = CHECK(fork());
+	if (tracee == 0) {
+		CHECK(close(fds[1]));
+		CHECK(read(fds[0], &byte, 1));
+		_exit(3);
+	}
+
+	// The tracer is a sibling of the tracee, so it is not the parent.
+	tracer = CHECK(fork());
+	if (tracer == 0) {
+		CHECK(ptrace(PTRACE_SEIZE, tracee, NULL, NULL));
+		CHECK(write(fds[1], "", 1));
+		CHECK_WITH(waitpid(tracee, &status, 0),
+			   _ret == tracee && WIFEXITED(status) &&
+				   WEXITSTATUS(status) == 3);
+		_exit(0);
+	}
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+
+	TEST_RES(waitpid(tracer, &status, 0),
+		 _ret == tracer && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+	TEST_RES(waitpid(tracee, &status, 0),
+		 _ret == tracee && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 3);
+}
+END_TEST()
+
+FN_TEST(attach_errors)
+{
+	TEST_ERRNO(ptrace(PTRACE_CONT, getppid(), NULL, NULL), ESRCH);
+	TEST_ERRNO(ptrace(PTRACE_ATTACH, getpid(), NULL, NULL), EPERM);
+
+	// Only the seized tracees can be interrupted.
+	pid_t pid = spawn_tracee();
+	int status;
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status));
+	TEST_ERRNO(ptrace(PTRACE_INTERRUPT, pid, NULL, NULL), EIO);
+	TEST_ERRNO(ptrace(PTRACE_LISTEN, pid, NULL, NULL), EIO);
+	TEST_SUCC(ptrace(PTRACE_KILL, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+}
+END_TEST()
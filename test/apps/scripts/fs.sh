@@ -65,3 +65,10 @@ pipe/pipe_err
 pipe/short_rw
 epoll/epoll_err
 epoll/poll_err
+inotify/inotify
+io_uring/io_uring
+xattr/xattr
+ext4/journal
+ext4/extent
+sysfs/sysfs
+devtmpfs/devtmpfs
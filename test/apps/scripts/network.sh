@@ -32,6 +32,9 @@ sleep 0.2
 ./tcp_err
 ./tcp_poll
 ./udp_err
+./ipv6
+./netlink_route
 ./unix_err
+./unix_dgram
 
 echo "All network test passed"
@@ -13,6 +13,8 @@ tests="
 clone3/clone_exit_signal
 clone3/clone_no_exit_signal
 clone3/clone_process
+clone3/pidfd
+coredump/coredump
 cpu_affinity/cpu_affinity
 execve/execve
 exit/exit_code
@@ -25,14 +27,31 @@ hello_pie/hello
 hello_world/hello_world
 itimer/setitimer
 itimer/timer_create
+itimer/timerfd
+mmap/madvise
+mmap/mlock
 mmap/mmap_and_fork
 mmap/mmap_shared_filebacked
 mmap/mmap_readahead
+mmap/mremap
+mmap/oom_score
+mmap/thp
+mmap/userfaultfd
+mqueue/posix_mq
+mqueue/sysv_msg
+namespace/mnt_ns
+namespace/pid_ns
+namespace/uts_ipc_ns
 pthread/pthread_test
+ptrace/ptrace
 pty/open_pty
+shm/memfd
 shm/posix_shm
+shm/sysv_shm
 signal_c/parent_death_signal
 signal_c/signal_test
+signal_c/signalfd
+swap/swapon
 "
 
 for testcase in ${tests}
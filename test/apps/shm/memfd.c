@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+static long page_size;
+
+FN_SETUP(init)
+{
+	page_size = CHECK(sysconf(_SC_PAGESIZE));
+}
+END_SETUP()
+
+FN_TEST(create)
+{
+	char path[64], link[128];
+	struct stat st;
+	int fd;
+
+	TEST_ERRNO(memfd_create("test", 0x100), EINVAL);
+
+	fd = TEST_SUCC(memfd_create("test", MFD_CLOEXEC));
+	TEST_RES(fcntl(fd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(fcntl(fd, F_GETFL), (_ret & O_ACCMODE) == O_RDWR);
+	TEST_RES(fstat(fd, &st),
+		 S_ISREG(st.st_mode) && st.st_size == 0 && st.st_nlink == 0);
+
+	snprintf(path, sizeof(path), "/proc/self/fd/%d", fd);
+	TEST_RES(readlink(path, link, sizeof(link) - 1),
+		 _ret == strlen("/memfd:test (deleted)") &&
+			 memcmp(link, "/memfd:test (deleted)", _ret) == 0);
+
+	TEST_RES(write(fd, "hello", 5), _ret == 5);
+	TEST_RES(pread(fd, link, sizeof(link), 0),
+		 _ret == 5 && memcmp(link, "hello", 5) == 0);
+	TEST_SUCC(ftruncate(fd, page_size));
+	TEST_RES(fstat(fd, &st), st.st_size == page_size);
+
+	// The seals cannot be added without `MFD_ALLOW_SEALING`
+	TEST_RES(fcntl(fd, F_GET_SEALS), _ret == F_SEAL_SEAL);
+	TEST_ERRNO(fcntl(fd, F_ADD_SEALS, F_SEAL_WRITE), EPERM);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(not_sealable)
+{
+	int fds[2];
+
+	TEST_SUCC(pipe(fds));
+	TEST_ERRNO(fcntl(fds[0], F_GET_SEALS), EINVAL);
+	TEST_ERRNO(fcntl(fds[1], F_ADD_SEALS, F_SEAL_SEAL), EINVAL);
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(seal_resize)
+{
+	int fd;
+
+	fd = TEST_SUCC(memfd_create("test", MFD_ALLOW_SEALING));
+	TEST_RES(fcntl(fd, F_GET_SEALS), _ret == 0);
+	TEST_ERRNO(fcntl(fd, F_ADD_SEALS, 0x8000), EINVAL);
+
+	TEST_SUCC(ftruncate(fd, page_size));
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_SHRINK));
+	TEST_ERRNO(ftruncate(fd, page_size / 2), EPERM);
+	TEST_SUCC(ftruncate(fd, page_size * 2));
+
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_GROW));
+	TEST_ERRNO(ftruncate(fd, page_size * 3), EPERM);
+	TEST_ERRNO(pwrite(fd, "x", 1, page_size * 2), EPERM);
+	TEST_RES(pwrite(fd, "x", 1, page_size * 2 - 1), _ret == 1);
+	TEST_RES(fcntl(fd, F_GET_SEALS),
+		 _ret == (F_SEAL_SHRINK | F_SEAL_GROW));
+
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_SEAL));
+	TEST_ERRNO(fcntl(fd, F_ADD_SEALS, F_SEAL_WRITE), EPERM);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(seal_write)
+{
+	char *addr;
+	int fd;
+
+	fd = TEST_SUCC(memfd_create("test", MFD_ALLOW_SEALING));
+	TEST_SUCC(ftruncate(fd, page_size));
+
+	// A shared writable mapping prevents `F_SEAL_WRITE`
+	addr = (char *)TEST_SUCC((long)mmap(NULL, page_size, PROT_READ,
+					    MAP_SHARED, fd, 0));
+	TEST_ERRNO(fcntl(fd, F_ADD_SEALS, F_SEAL_WRITE), EBUSY);
+	TEST_SUCC(munmap(addr, page_size));
+
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_WRITE));
+	TEST_ERRNO(write(fd, "x", 1), EPERM);
+	TEST_ERRNO((long)mmap(NULL, page_size, PROT_READ | PROT_WRITE,
+			      MAP_SHARED, fd, 0),
+		   EPERM);
+
+	// Read-only shared mappings are allowed, but cannot become writable
+	addr = (char *)TEST_SUCC((long)mmap(NULL, page_size, PROT_READ,
+					    MAP_SHARED, fd, 0));
+	TEST_ERRNO(mprotect(addr, page_size, PROT_READ | PROT_WRITE), EACCES);
+	TEST_SUCC(munmap(addr, page_size));
+
+	// Private mappings are not affected
+	addr = (char *)TEST_SUCC((long)mmap(NULL, page_size,
+					    PROT_READ | PROT_WRITE, MAP_PRIVATE,
+					    fd, 0));
+	addr[0] = 'x';
+	TEST_SUCC(munmap(addr, page_size));
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(seal_future_write)
+{
+	char *addr;
+	int fd;
+
+	fd = TEST_SUCC(memfd_create("test", MFD_ALLOW_SEALING));
+	TEST_SUCC(ftruncate(fd, page_size));
+
+	addr = (char *)TEST_SUCC((long)mmap(NULL, page_size,
+					    PROT_READ | PROT_WRITE, MAP_SHARED,
+					    fd, 0));
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_FUTURE_WRITE));
+
+	// The existing mapping can still write to the file
+	addr[0] = 'x';
+	TEST_RES(pread(fd, &addr[1], 1, 0), _ret == 1 && addr[1] == 'x');
+
+	TEST_ERRNO(write(fd, "x", 1), EPERM);
+	TEST_ERRNO((long)mmap(NULL, page_size, PROT_READ | PROT_WRITE,
+			      MAP_SHARED, fd, 0),
+		   EPERM);
+
+	TEST_SUCC(munmap(addr, page_size));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <stdio.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/mman.h>
+#include <sys/shm.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define SEG_SIZE 8192
+#define SEG_KEY 0x5a5a1234
+
+static long page_size;
+
+FN_SETUP(init)
+{
+	page_size = CHECK(sysconf(_SC_PAGESIZE));
+}
+END_SETUP()
+
+FN_TEST(invalid_args)
+{
+	int shmid;
+
+	TEST_ERRNO(shmget(IPC_PRIVATE, 0, IPC_CREAT | 0600), EINVAL);
+	TEST_ERRNO(shmget(SEG_KEY, SEG_SIZE, 0600), ENOENT);
+
+	shmid = TEST_SUCC(shmget(SEG_KEY, SEG_SIZE, IPC_CREAT | 0600));
+	TEST_ERRNO(shmget(SEG_KEY, SEG_SIZE, IPC_CREAT | IPC_EXCL | 0600),
+		   EEXIST);
+	TEST_ERRNO(shmget(SEG_KEY, SEG_SIZE * 2, 0600), EINVAL);
+	TEST_RES(shmget(SEG_KEY, SEG_SIZE / 2, 0600), _ret == shmid);
+
+	TEST_ERRNO((long)shmat(shmid, (void *)1, 0), EINVAL);
+	TEST_ERRNO((long)shmat(shmid, NULL, SHM_REMAP), EINVAL);
+	TEST_ERRNO((long)shmat(-1, NULL, 0), EINVAL);
+	TEST_ERRNO(shmdt((void *)1), EINVAL);
+	TEST_ERRNO(shmdt((void *)page_size), EINVAL);
+	TEST_ERRNO(shmctl(shmid, 0xdead, NULL), EINVAL);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+	TEST_ERRNO(shmctl(shmid, IPC_RMID, NULL), EINVAL);
+	TEST_ERRNO(shmget(SEG_KEY, SEG_SIZE, 0600), ENOENT);
+}
+END_TEST()
+
+FN_TEST(attach_and_share)
+{
+	struct shmid_ds ds;
+	char *addr1, *addr2;
+	int shmid;
+
+	shmid = TEST_SUCC(shmget(IPC_PRIVATE, SEG_SIZE, IPC_CREAT | 0600));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_segsz == SEG_SIZE && ds.shm_nattch == 0 &&
+			 ds.shm_cpid == getpid() &&
+			 (ds.shm_perm.mode & 0777) == 0600 &&
+			 ds.shm_perm.uid == getuid());
+
+	addr1 = (char *)TEST_SUCC((long)shmat(shmid, NULL, 0));
+	addr2 = (char *)TEST_SUCC((long)shmat(shmid, NULL, SHM_RDONLY));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_nattch == 2 && ds.shm_lpid == getpid() &&
+			 ds.shm_atime != 0);
+
+	// The pages of the segment are initially zeroed and shared
+	TEST_RES(addr2[SEG_SIZE - 1], _ret == 0);
+	strcpy(addr1, "hello");
+	TEST_RES(strcmp(addr2, "hello"), _ret == 0);
+
+	TEST_SUCC(shmdt(addr2));
+	TEST_ERRNO(shmdt(addr2), EINVAL);
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds), ds.shm_nattch == 1);
+
+	TEST_SUCC(shmdt(addr1));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_nattch == 0 && ds.shm_dtime != 0);
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(attach_at_address)
+{
+	char *addr, *base;
+	int shmid;
+
+	shmid = TEST_SUCC(shmget(IPC_PRIVATE, SEG_SIZE, IPC_CREAT | 0600));
+
+	base = (char *)TEST_SUCC((long)mmap(NULL, SEG_SIZE * 2,
+					    PROT_READ | PROT_WRITE,
+					    MAP_PRIVATE | MAP_ANONYMOUS, -1, 0));
+
+	// The region is occupied
+	TEST_ERRNO((long)shmat(shmid, base, 0), EINVAL);
+
+	// The region is taken over
+	addr = (char *)TEST_RES(
+		(long)shmat(shmid, base + 1, SHM_RND | SHM_REMAP),
+		_ret == (long)base);
+	addr[0] = 'a';
+	TEST_SUCC(shmdt(addr));
+
+	// The rest of the region is still mapped
+	base[SEG_SIZE] = 'b';
+	TEST_SUCC(munmap(base, SEG_SIZE * 2));
+
+	addr = (char *)TEST_RES((long)shmat(shmid, base, 0),
+				_ret == (long)base);
+	TEST_RES(addr[0], _ret == 'a');
+	TEST_SUCC(shmdt(addr));
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(deferred_destruction)
+{
+	struct shmid_ds ds;
+	char *addr;
+	int shmid, shmid2;
+
+	shmid = TEST_SUCC(shmget(SEG_KEY, SEG_SIZE, IPC_CREAT | 0600));
+	addr = (char *)TEST_SUCC((long)shmat(shmid, NULL, 0));
+
+	// The segment is still usable until the last detach
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_perm.__key == IPC_PRIVATE &&
+			 (ds.shm_perm.mode & SHM_DEST) && ds.shm_nattch == 1);
+	addr[0] = 'x';
+
+	// The key can be reused
+	shmid2 = TEST_RES(shmget(SEG_KEY, SEG_SIZE, IPC_CREAT | 0600),
+			  _ret != shmid);
+	TEST_SUCC(shmctl(shmid2, IPC_RMID, NULL));
+
+	TEST_SUCC(shmdt(addr));
+	TEST_ERRNO(shmctl(shmid, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(fork)
+{
+	struct shmid_ds ds;
+	char *addr;
+	int shmid;
+	pid_t pid;
+
+	shmid = TEST_SUCC(shmget(IPC_PRIVATE, SEG_SIZE, IPC_CREAT | 0600));
+	addr = (char *)TEST_SUCC((long)shmat(shmid, NULL, 0));
+	addr[0] = 0;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		addr[0] = 'c';
+		_exit(EXIT_SUCCESS);
+	}
+
+	// The attachment of the child is released after the child is reaped
+	TEST_RES(waitpid(pid, NULL, 0), _ret == pid);
+	TEST_RES(addr[0], _ret == 'c');
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds), ds.shm_nattch == 1);
+
+	TEST_SUCC(shmdt(addr));
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(ipc_set)
+{
+	struct shmid_ds ds;
+	int shmid;
+
+	shmid = TEST_SUCC(shmget(IPC_PRIVATE, SEG_SIZE, IPC_CREAT | 0600));
+
+	TEST_SUCC(shmctl(shmid, IPC_STAT, &ds));
+	ds.shm_perm.mode = 0444;
+	TEST_SUCC(shmctl(shmid, IPC_SET, &ds));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 (ds.shm_perm.mode & 0777) == 0444);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(permission)
+{
+	int shmid;
+	pid_t pid;
+	int status;
+
+	shmid = TEST_SUCC(shmget(SEG_KEY, SEG_SIZE, IPC_CREAT | 0400));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(setuid(65534));
+		CHECK_WITH((long)shmat(shmid, NULL, SHM_RDONLY),
+			   _ret == -1 && errno == EACCES);
+		CHECK_WITH(shmget(SEG_KEY, SEG_SIZE, 0400),
+			   _ret < 0 && errno == EACCES);
+		CHECK_WITH(shmctl(shmid, IPC_RMID, NULL),
+			   _ret < 0 && errno == EPERM);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(proc_sysvipc)
+{
+	char line[256];
+	FILE *file;
+	int shmid, key, id, found = 0;
+
+	shmid = TEST_SUCC(shmget(SEG_KEY, SEG_SIZE, IPC_CREAT | 0640));
+
+	file = (FILE *)TEST_SUCC((long)fopen("/proc/sysvipc/shm", "r"));
+	TEST_RES(fgets(line, sizeof(line), file) != NULL,
+		 _ret && strstr(line, "shmid") != NULL);
+	while (fgets(line, sizeof(line), file) != NULL) {
+		if (sscanf(line, "%d %d", &key, &id) == 2 && id == shmid)
+			found = key == SEG_KEY;
+	}
+	TEST_RES(found, _ret);
+	TEST_SUCC(fclose(file));
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <poll.h>
+#include <signal.h>
+#include <sys/epoll.h>
+#include <sys/signalfd.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+
+static int sfd;
+static sigset_t mask;
+
+FN_SETUP(init)
+{
+	sigemptyset(&mask);
+	sigaddset(&mask, SIGUSR1);
+	CHECK(sigprocmask(SIG_BLOCK, &mask, NULL));
+
+	sfd = CHECK(signalfd(-1, &mask, SFD_NONBLOCK | SFD_CLOEXEC));
+}
+END_SETUP()
+
+FN_TEST(invalid_args)
+{
+	int fildes[2];
+	char buf[sizeof(struct signalfd_siginfo) - 1];
+
+	TEST_ERRNO(signalfd(-1, &mask, 0xdead0000), EINVAL);
+	TEST_ERRNO(syscall(SYS_signalfd4, -1, &mask, 4, 0), EINVAL);
+	TEST_ERRNO(signalfd(-2, &mask, 0), EBADF);
+
+	TEST_SUCC(pipe(fildes));
+	TEST_ERRNO(signalfd(fildes[0], &mask, 0), EINVAL);
+	TEST_SUCC(close(fildes[0]));
+	TEST_SUCC(close(fildes[1]));
+
+	TEST_ERRNO(read(sfd, buf, sizeof(buf)), EINVAL);
+	TEST_ERRNO(write(sfd, buf, sizeof(buf)), EINVAL);
+}
+END_TEST()
+
+FN_TEST(read_signal)
+{
+	struct signalfd_siginfo info;
+
+	TEST_ERRNO(read(sfd, &info, sizeof(info)), EAGAIN);
+
+	TEST_SUCC(kill(getpid(), SIGUSR1));
+	TEST_RES(read(sfd, &info, sizeof(info)),
+		 _ret == sizeof(info) && info.ssi_signo == SIGUSR1 &&
+			 info.ssi_code == SI_USER && info.ssi_pid == getpid() &&
+			 info.ssi_uid == getuid());
+
+	TEST_ERRNO(read(sfd, &info, sizeof(info)), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(unmasked_signal)
+{
+	struct signalfd_siginfo info;
+	sigset_t pending, usr2_mask;
+
+	sigemptyset(&usr2_mask);
+	sigaddset(&usr2_mask, SIGUSR2);
+	TEST_SUCC(sigprocmask(SIG_BLOCK, &usr2_mask, NULL));
+
+	// Signals that are not in the mask of the file are left pending
+	TEST_SUCC(kill(getpid(), SIGUSR2));
+	TEST_ERRNO(read(sfd, &info, sizeof(info)), EAGAIN);
+	TEST_RES(sigpending(&pending), sigismember(&pending, SIGUSR2));
+
+	TEST_RES(signalfd(sfd, &usr2_mask, 0), _ret == sfd);
+	TEST_RES(read(sfd, &info, sizeof(info)),
+		 _ret == sizeof(info) && info.ssi_signo == SIGUSR2);
+	TEST_RES(sigpending(&pending), !sigismember(&pending, SIGUSR2));
+
+	TEST_RES(signalfd(sfd, &mask, 0), _ret == sfd);
+	TEST_SUCC(sigprocmask(SIG_UNBLOCK, &usr2_mask, NULL));
+}
+END_TEST()
+
+FN_TEST(poll)
+{
+	struct pollfd pfd = { .fd = sfd, .events = POLLIN };
+	struct signalfd_siginfo info;
+
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+
+	TEST_SUCC(kill(getpid(), SIGUSR1));
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLIN);
+
+	TEST_RES(read(sfd, &info, sizeof(info)), _ret == sizeof(info));
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(epoll)
+{
+	struct epoll_event ev = { .events = EPOLLIN, .data.fd = sfd };
+	struct signalfd_siginfo info;
+	int epfd;
+
+	epfd = TEST_SUCC(epoll_create1(0));
+	TEST_SUCC(epoll_ctl(epfd, EPOLL_CTL_ADD, sfd, &ev));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0), _ret == 0);
+
+	TEST_SUCC(kill(getpid(), SIGUSR1));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0),
+		 _ret == 1 && ev.data.fd == sfd && ev.events == EPOLLIN);
+
+	TEST_RES(read(sfd, &info, sizeof(info)), _ret == sizeof(info));
+	TEST_RES(epoll_wait(epfd, &ev, 1, 0), _ret == 0);
+
+	TEST_SUCC(close(epfd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sfd));
+}
+END_SETUP()
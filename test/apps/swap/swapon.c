@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/swap.h>
+#include <sys/sysinfo.h>
+#include <unistd.h>
+
+#define NONEXISTENT_DEVICE "/nonexistent_swap_device"
+// The virtio disk formatted by `mkswap`
+#define SWAP_DEVICE "/dev/vswap"
+
+#define PAGE_SIZE 4096
+#define NR_PAGES 1024
+// The memory allocated beyond the free memory to test swapping under pressure
+#define EXTRA_SIZE (64UL << 20)
+
+// Reads the beginning of a file into `buf` as a string
+static long read_file(const char *path, char *buf, size_t len)
+{
+	int fd;
+	long ret;
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+	ret = read(fd, buf, len - 1);
+	close(fd);
+	if (ret >= 0)
+		buf[ret] = '\0';
+	return ret;
+}
+
+// Fills each page with a byte derived from its index
+static void fill_pages(char *buf, size_t nr_pages)
+{
+	for (size_t i = 0; i < nr_pages; ++i)
+		memset(buf + i * PAGE_SIZE, (char)(i * 7 + 1), PAGE_SIZE);
+}
+
+// Returns the number of pages whose contents are not the ones filled
+static size_t count_bad_pages(const char *buf, size_t nr_pages)
+{
+	size_t nr_bad_pages = 0;
+
+	for (size_t i = 0; i < nr_pages; ++i) {
+		for (size_t j = 0; j < PAGE_SIZE; ++j) {
+			if (buf[i * PAGE_SIZE + j] != (char)(i * 7 + 1)) {
+				++nr_bad_pages;
+				break;
+			}
+		}
+	}
+
+	return nr_bad_pages;
+}
+
+static unsigned long used_swap(void)
+{
+	struct sysinfo info;
+
+	if (sysinfo(&info) < 0)
+		return -1;
+	return (info.totalswap - info.freeswap) * info.mem_unit;
+}
+
+FN_TEST(swapon_invalid)
+{
+	TEST_ERRNO(swapon(NONEXISTENT_DEVICE, 0), ENOENT);
+	TEST_ERRNO(swapon(NONEXISTENT_DEVICE, SWAP_FLAG_PREFER | 10), ENOENT);
+
+	// Flags other than the priority and the discard flags are invalid
+	TEST_ERRNO(swapon(NONEXISTENT_DEVICE, 0x100000), EINVAL);
+	TEST_ERRNO(swapon(NONEXISTENT_DEVICE, -1), EINVAL);
+}
+END_TEST()
+
+FN_TEST(swapoff_invalid)
+{
+	TEST_ERRNO(swapoff(NONEXISTENT_DEVICE), ENOENT);
+	TEST_ERRNO(swapoff(""), ENOENT);
+}
+END_TEST()
+
+FN_TEST(swap_stats)
+{
+	struct sysinfo info;
+	char buf[512];
+
+	TEST_RES(sysinfo(&info), info.freeswap <= info.totalswap);
+
+	TEST_RES(read_file("/proc/swaps", buf, sizeof(buf)),
+		 strncmp(buf, "Filename", strlen("Filename")) == 0);
+	TEST_RES(read_file("/proc/meminfo", buf, sizeof(buf)),
+		 strstr(buf, "SwapTotal:") != NULL &&
+			 strstr(buf, "SwapFree:") != NULL);
+}
+END_TEST()
+
+FN_TEST(swap_out_and_in)
+{
+	char *buf;
+
+	TEST_SUCC(swapon(SWAP_DEVICE, 0));
+	TEST_ERRNO(swapon(SWAP_DEVICE, 0), EBUSY);
+
+	buf = (char *)TEST_SUCC((long)mmap(NULL, NR_PAGES * PAGE_SIZE,
+					   PROT_READ | PROT_WRITE,
+					   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0));
+	fill_pages(buf, NR_PAGES);
+
+	// The pages are swapped out, and swapped in on page faults.
+	TEST_SUCC(madvise(buf, NR_PAGES * PAGE_SIZE, MADV_PAGEOUT));
+	TEST_RES(used_swap(), _ret >= NR_PAGES * PAGE_SIZE);
+	TEST_RES(count_bad_pages(buf, NR_PAGES), _ret == 0);
+	TEST_RES(used_swap(), _ret == 0);
+
+	// The pages are read back when the swap device is disabled.
+	TEST_SUCC(madvise(buf, NR_PAGES * PAGE_SIZE, MADV_PAGEOUT));
+	TEST_RES(used_swap(), _ret >= NR_PAGES * PAGE_SIZE);
+	TEST_SUCC(swapoff(SWAP_DEVICE));
+	TEST_RES(used_swap(), _ret == 0);
+	TEST_RES(count_bad_pages(buf, NR_PAGES), _ret == 0);
+
+	TEST_SUCC(munmap(buf, NR_PAGES * PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(swap_under_pressure)
+{
+	struct sysinfo info;
+	size_t size, nr_pages;
+	char *buf;
+
+	// Allocate more memory than the free memory, so some pages must be
+	// swapped out. This only works if the swap device is large enough,
+	// e.g., when the VM is booted with `MEM=256M`.
+	TEST_SUCC(sysinfo(&info));
+	size = info.freeram * info.mem_unit + EXTRA_SIZE;
+	nr_pages = size / PAGE_SIZE;
+
+	TEST_SUCC(swapon(SWAP_DEVICE, 0));
+	TEST_SUCC(sysinfo(&info));
+	if (size + EXTRA_SIZE > info.freeswap * info.mem_unit) {
+		fprintf(stderr,
+			"%s: skipped since the swap space is too small\n",
+			__func__);
+	} else {
+		buf = (char *)TEST_SUCC((long)mmap(NULL, size,
+						   PROT_READ | PROT_WRITE,
+						   MAP_PRIVATE | MAP_ANONYMOUS,
+						   -1, 0));
+		fill_pages(buf, nr_pages);
+		TEST_RES(used_swap(), _ret > 0);
+		TEST_RES(count_bad_pages(buf, nr_pages), _ret == 0);
+
+		TEST_SUCC(munmap(buf, size));
+		TEST_RES(used_swap(), _ret == 0);
+	}
+	TEST_SUCC(swapoff(SWAP_DEVICE));
+}
+END_TEST()
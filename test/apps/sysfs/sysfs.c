@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <linux/netlink.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#define CPU_DIR "/sys/devices/system/cpu"
+#define CPU0_DIR CPU_DIR "/cpu0"
+#define CPU0_DEVPATH "/devices/system/cpu/cpu0"
+
+static char buf[4096];
+
+static ssize_t read_file(const char *path)
+{
+	int fd;
+	ssize_t len;
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	len = read(fd, buf, sizeof(buf) - 1);
+	close(fd);
+	if (len >= 0)
+		buf[len] = '\0';
+
+	return len;
+}
+
+static ssize_t write_file(const char *path, const char *content)
+{
+	int fd;
+	ssize_t len;
+
+	fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	len = write(fd, content, strlen(content));
+	close(fd);
+
+	return len;
+}
+
+FN_TEST(cpu)
+{
+	TEST_RES(read_file(CPU_DIR "/online"), buf[0] == '0');
+	TEST_RES(read_file(CPU_DIR "/possible"), buf[0] == '0');
+	TEST_RES(read_file(CPU0_DIR "/online"), strcmp(buf, "1\n") == 0);
+	TEST_RES(read_file(CPU0_DIR "/topology/core_id"),
+		 strcmp(buf, "0\n") == 0);
+
+	TEST_RES(readlink(CPU0_DIR "/subsystem", buf, sizeof(buf) - 1),
+		 _ret == strlen("../../../../bus/cpu") &&
+			 memcmp(buf, "../../../../bus/cpu", _ret) == 0);
+	TEST_RES(readlink("/sys/bus/cpu/devices/cpu0", buf, sizeof(buf) - 1),
+		 _ret == strlen("../../../devices/system/cpu/cpu0") &&
+			 memcmp(buf, "../../../devices/system/cpu/cpu0",
+				_ret) == 0);
+
+	TEST_ERRNO(read_file(CPU_DIR "/cpu_none/online"), ENOENT);
+}
+END_TEST()
+
+FN_TEST(uevent_attr)
+{
+	TEST_RES(read_file(CPU0_DIR "/uevent"), _ret == 0);
+
+	TEST_ERRNO(write_file(CPU0_DIR "/uevent", "none"), EINVAL);
+	TEST_ERRNO(write_file(CPU0_DIR "/online", "0"), EACCES);
+}
+END_TEST()
+
+FN_TEST(uevent_socket)
+{
+	int sk;
+	struct sockaddr_nl addr = {
+		.nl_family = AF_NETLINK,
+		.nl_groups = 1,
+	};
+	const char *header = "change@" CPU0_DEVPATH;
+	const char *env;
+
+	sk = TEST_SUCC(socket(AF_NETLINK, SOCK_DGRAM | SOCK_NONBLOCK,
+			      NETLINK_KOBJECT_UEVENT));
+	TEST_SUCC(bind(sk, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_ERRNO(recv(sk, buf, sizeof(buf), 0), EAGAIN);
+
+	TEST_RES(write_file(CPU0_DIR "/uevent", "change"),
+		 _ret == strlen("change"));
+
+	TEST_RES(recv(sk, buf, sizeof(buf), 0),
+		 _ret > strlen(header) + 1 && strcmp(buf, header) == 0);
+	env = buf + strlen(header) + 1;
+	TEST_RES(strcmp(env, "ACTION=change"), _ret == 0);
+	env += strlen(env) + 1;
+	TEST_RES(strcmp(env, "DEVPATH=" CPU0_DEVPATH), _ret == 0);
+	env += strlen(env) + 1;
+	TEST_RES(strcmp(env, "SUBSYSTEM=cpu"), _ret == 0);
+
+	TEST_ERRNO(recv(sk, buf, sizeof(buf), 0), EAGAIN);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <sys/xattr.h>
+#include <unistd.h>
+
+#define FILE_PATH "/tmp/xattr_test"
+#define LINK_PATH "/tmp/xattr_test_link"
+
+static int fd;
+static char buf[256];
+
+FN_SETUP(create)
+{
+	fd = CHECK(open(FILE_PATH, O_CREAT | O_RDWR, 0644));
+	CHECK(symlink(FILE_PATH, LINK_PATH));
+}
+END_SETUP()
+
+FN_TEST(set_and_get)
+{
+	TEST_SUCC(setxattr(FILE_PATH, "user.a", "hello", 5, 0));
+
+	TEST_RES(getxattr(FILE_PATH, "user.a", NULL, 0), _ret == 5);
+	TEST_RES(getxattr(FILE_PATH, "user.a", buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(fgetxattr(fd, "user.a", buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_ERRNO(getxattr(FILE_PATH, "user.a", buf, 2), ERANGE);
+	TEST_ERRNO(getxattr(FILE_PATH, "user.none", buf, sizeof(buf)),
+		   ENODATA);
+
+	TEST_SUCC(fsetxattr(fd, "user.empty", "", 0, 0));
+	TEST_RES(getxattr(FILE_PATH, "user.empty", buf, sizeof(buf)),
+		 _ret == 0);
+}
+END_TEST()
+
+FN_TEST(flags)
+{
+	TEST_ERRNO(setxattr(FILE_PATH, "user.a", "x", 1, XATTR_CREATE),
+		   EEXIST);
+	TEST_ERRNO(setxattr(FILE_PATH, "user.b", "x", 1, XATTR_REPLACE),
+		   ENODATA);
+	TEST_ERRNO(setxattr(FILE_PATH, "user.a", "x", 1,
+			    XATTR_CREATE | XATTR_REPLACE),
+		   EINVAL);
+
+	TEST_SUCC(setxattr(FILE_PATH, "user.a", "world!", 6, XATTR_REPLACE));
+	TEST_RES(getxattr(FILE_PATH, "user.a", buf, sizeof(buf)),
+		 _ret == 6 && memcmp(buf, "world!", 6) == 0);
+}
+END_TEST()
+
+FN_TEST(invalid_names)
+{
+	TEST_ERRNO(setxattr(FILE_PATH, "unknown.a", "x", 1, 0), EOPNOTSUPP);
+	TEST_ERRNO(setxattr(FILE_PATH, "user.", "x", 1, 0), EINVAL);
+	TEST_ERRNO(setxattr(FILE_PATH, "system.unknown", "x", 1, 0),
+		   EOPNOTSUPP);
+}
+END_TEST()
+
+FN_TEST(symlink)
+{
+	TEST_RES(getxattr(LINK_PATH, "user.a", buf, sizeof(buf)), _ret == 6);
+	// User xattrs are not allowed on symlinks
+	TEST_ERRNO(lsetxattr(LINK_PATH, "user.a", "x", 1, 0), EPERM);
+	TEST_ERRNO(lgetxattr(LINK_PATH, "user.a", buf, sizeof(buf)), ENODATA);
+}
+END_TEST()
+
+FN_TEST(list_and_remove)
+{
+	const char expected[] = "user.a\0user.empty";
+
+	TEST_RES(listxattr(FILE_PATH, NULL, 0), _ret == sizeof(expected));
+	TEST_RES(flistxattr(fd, buf, sizeof(buf)),
+		 _ret == sizeof(expected) &&
+			 memcmp(buf, expected, sizeof(expected)) == 0);
+	TEST_ERRNO(listxattr(FILE_PATH, buf, 4), ERANGE);
+
+	TEST_SUCC(removexattr(FILE_PATH, "user.a"));
+	TEST_ERRNO(removexattr(FILE_PATH, "user.a"), ENODATA);
+	TEST_SUCC(fremovexattr(fd, "user.empty"));
+	TEST_RES(listxattr(FILE_PATH, buf, sizeof(buf)), _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fd));
+	CHECK(unlink(LINK_PATH));
+	CHECK(unlink(FILE_PATH));
+}
+END_SETUP()